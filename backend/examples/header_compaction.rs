@@ -0,0 +1,83 @@
+// Heap held by the parsed headers of a buffer of packets, as JSON trees and
+// compacted. Run with `cargo run --release --example header_compaction`.
+//
+// The counting allocator would slow down and skew every other target, so the
+// measurement lives in its own binary, which compiles the application's
+// modules in as `main.rs` does.
+
+#![allow(dead_code, unused, clippy::all)]
+
+#[path = "../src/api/mod.rs"]
+mod api;
+#[path = "../src/capture/mod.rs"]
+mod capture;
+#[path = "../src/models/mod.rs"]
+mod models;
+#[path = "../src/utils/mod.rs"]
+mod utils;
+
+use pcap::Linktype;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::capture::parser::PacketParser;
+use crate::capture::self_test::{arp_frame, ipv4, rtp_packet, tcp, tftp_read_request, udp, IP_TCP, IP_UDP};
+
+/// Packets parsed into the measured buffer
+const PACKETS: usize = 100_000;
+
+/// Counts the bytes of live allocations
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+    
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let parser = PacketParser::new();
+    let frames = [
+        ipv4(IP_TCP, &tcp(40000, 5001, &[0x55; 1000])),
+        ipv4(IP_UDP, &udp(40000, 40002, &rtp_packet(1, 160))),
+        ipv4(IP_UDP, &udp(40000, 69, &tftp_read_request())),
+        arp_frame(),
+    ];
+    let base = ALLOCATED.load(Ordering::Relaxed);
+    let mut packets: Vec<_> = (0..PACKETS)
+        .map(|i| parser.parse_frame(frames[i % frames.len()].clone(), "bench", Linktype::ETHERNET).unwrap())
+        .collect();
+    let parsed = ALLOCATED.load(Ordering::Relaxed) - base;
+    
+    packets.iter_mut().for_each(|packet| { packet.compact(); });
+    let compacted = ALLOCATED.load(Ordering::Relaxed) - base;
+    let text: usize = packets.iter().filter_map(|packet| packet.compact_headers.as_ref()).map(|json| json.len()).sum();
+    
+    println!(
+        "{} packets: {:.0} bytes each parsed, {:.0} compacted ({:.0}% less); headers {:.0} bytes as JSON values, {:.0} as text",
+        PACKETS,
+        parsed as f64 / PACKETS as f64,
+        compacted as f64 / PACKETS as f64,
+        (parsed - compacted) as f64 * 100.0 / parsed as f64,
+        (parsed - compacted + text) as f64 / PACKETS as f64,
+        text as f64 / PACKETS as f64
+    );
+    assert!(compacted < parsed);
+}
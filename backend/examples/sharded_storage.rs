@@ -0,0 +1,77 @@
+// Insert throughput of the packet store with one shard and with 16, under 8
+// writers while a reader keeps scanning it. Run with
+// `cargo run --release --example sharded_storage`.
+//
+// Like the header compaction benchmark, it compiles the application's modules
+// in as `main.rs` does, which has no library target to link against.
+
+#![allow(dead_code, unused, clippy::all)]
+
+#[path = "../src/api/mod.rs"]
+mod api;
+#[path = "../src/capture/mod.rs"]
+mod capture;
+#[path = "../src/models/mod.rs"]
+mod models;
+#[path = "../src/utils/mod.rs"]
+mod utils;
+
+use pcap::Linktype;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::capture::packet_store::PacketStore;
+use crate::capture::parser::PacketParser;
+use crate::capture::self_test::{ipv4, udp, IP_UDP};
+
+const WRITERS: u64 = 8;
+const PER_WRITER: u64 = 50_000;
+const CAPACITY: usize = 200_000;
+
+fn main() {
+    let template = PacketParser::new()
+        .parse_frame(ipv4(IP_UDP, &udp(40000, 5000, b"hello")), "bench", Linktype::ETHERNET)
+        .unwrap();
+    for shards in [1, 16] {
+        let store = Arc::new(PacketStore::new(shards));
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (store, done) = (store.clone(), done.clone());
+            std::thread::spawn(move || {
+                let mut scans = 0;
+                while !done.load(Ordering::Relaxed) {
+                    std::hint::black_box(store.iter().filter(|p| p.id % 7 == 0).count());
+                    scans += 1;
+                }
+                scans
+            })
+        };
+        
+        let started = Instant::now();
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|writer| {
+                let (store, template) = (store.clone(), template.clone());
+                std::thread::spawn(move || {
+                    for i in 0..PER_WRITER {
+                        let id = i * WRITERS + writer;
+                        store.insert_bounded(id, template.clone(), CAPACITY);
+                    }
+                })
+            })
+            .collect();
+        writers.into_iter().for_each(|writer| writer.join().unwrap());
+        let elapsed = started.elapsed();
+        done.store(true, Ordering::Relaxed);
+        
+        println!(
+            "{:>2} shard(s): {:.0} inserts/s with {} writers, {} reader scans, {} stored",
+            shards,
+            (WRITERS * PER_WRITER) as f64 / elapsed.as_secs_f64(),
+            WRITERS,
+            reader.join().unwrap(),
+            store.len()
+        );
+        assert!(store.len() <= CAPACITY);
+    }
+}
@@ -0,0 +1,137 @@
+// Cross-origin requests (CORS) for browser UIs served from another origin
+//
+// A browser only lets a page call the API from a different origin when the
+// responses name that origin in `Access-Control-Allow-Origin`, and asks first
+// with an OPTIONS preflight for anything but simple requests (a JSON POST,
+// a PUT or DELETE). Requests from the origins given with `--cors-origin` get
+// those headers, with credentials allowed, and their preflights are answered
+// here. `*` lets any other origin call too, but answers with a literal `*`
+// and no credentials, so no site can read the API with a user's cookies or
+// auth. Without any configured origin, or for other origins, requests pass
+// through untouched and carry no CORS headers, so browsers keep enforcing
+// the same-origin policy.
+//
+// Browsers apply no CORS to WebSockets, but do send the page's `Origin` with
+// the upgrade; once origins are configured, `/api/ws` refuses pages from
+// origins the policy does not allow.
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderMap, HeaderValue};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+
+/// Methods the API's routes use
+const ALLOWED_METHODS: &str = "GET, POST, PUT, DELETE, OPTIONS";
+
+/// Request headers allowed when a preflight does not ask for specific ones
+const ALLOWED_HEADERS: &str = "Content-Type, Authorization";
+
+/// Response headers of the export endpoints that scripts may read
+const EXPOSED_HEADERS: &str = "Content-Disposition, X-Missing-Packet-Ids";
+
+/// How long browsers may cache a preflight answer, in seconds
+const PREFLIGHT_MAX_AGE: &str = "3600";
+
+/// Origins allowed to call the API from a browser
+#[derive(Debug, Clone, Default)]
+pub struct CorsPolicy {
+    /// Exact origins (`https://ui.example.com`), or `*` for any
+    origins: Vec<String>,
+}
+
+impl CorsPolicy {
+    pub fn new(origins: Vec<String>) -> Self {
+        Self {
+            origins: origins
+                .into_iter()
+                .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect(),
+        }
+    }
+    
+    /// Whether a request's `Origin` may call the API
+    pub fn allows(&self, origin: &str) -> bool {
+        self.listed(origin) || self.any_origin()
+    }
+    
+    /// Whether a WebSocket upgrade is accepted: from anywhere without
+    /// configured origins, else from clients sending no `Origin` (not a
+    /// browser), the API's own origin (named by `host`) and allowed origins
+    pub fn allows_websocket(&self, origin: Option<&str>, host: Option<&str>) -> bool {
+        let Some(origin) = origin else { return true };
+        let same_origin = origin
+            .split_once("://")
+            .zip(host)
+            .is_some_and(|((_, authority), host)| authority.eq_ignore_ascii_case(host));
+        self.origins.is_empty() || same_origin || self.allows(origin)
+    }
+    
+    fn listed(&self, origin: &str) -> bool {
+        self.origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin))
+    }
+    
+    fn any_origin(&self) -> bool {
+        self.origins.iter().any(|allowed| allowed == "*")
+    }
+    
+    /// The `Access-Control-Allow-Origin` value for an `Origin`: the origin
+    /// itself when listed, `*` when only allowed as any origin
+    fn allowed_origin(&self, origin: &str) -> Option<HeaderValue> {
+        if self.listed(origin) {
+            HeaderValue::from_str(origin).ok()
+        } else {
+            self.any_origin().then(|| HeaderValue::from_static("*"))
+        }
+    }
+}
+
+/// Middleware answering preflights and adding CORS headers for allowed origins
+pub async fn handle(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let origin = req
+        .headers()
+        .get(header::ORIGIN)
+        .and_then(|origin| origin.to_str().ok())
+        .and_then(|origin| req.app_data::<web::Data<CorsPolicy>>()?.allowed_origin(origin));
+    
+    let Some(origin) = origin else {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    };
+    
+    // A preflight is answered here; the routes have no OPTIONS handlers
+    if req.method() == Method::OPTIONS && req.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD) {
+        let allowed_headers = req
+            .headers()
+            .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+            .cloned()
+            .unwrap_or(HeaderValue::from_static(ALLOWED_HEADERS));
+        
+        let mut response = HttpResponse::NoContent().finish();
+        let headers = response.headers_mut();
+        allow_origin(headers, origin);
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, HeaderValue::from_static(ALLOWED_METHODS));
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allowed_headers);
+        headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static(PREFLIGHT_MAX_AGE));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    
+    let mut response = next.call(req).await?;
+    let headers = response.headers_mut();
+    allow_origin(headers, origin);
+    headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, HeaderValue::from_static(EXPOSED_HEADERS));
+    Ok(response.map_into_left_body())
+}
+
+/// Name the origin, allowing credentials unless it is `*` (any origin)
+fn allow_origin(headers: &mut HeaderMap, origin: HeaderValue) {
+    if origin != "*" {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+    }
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.append(header::VARY, HeaderValue::from_static("Origin"));
+}
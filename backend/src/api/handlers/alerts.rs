@@ -0,0 +1,56 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use std::time::Duration;
+
+use crate::capture::alerts::MAX_ALERTS;
+use crate::capture::manager::CaptureManager;
+use crate::models::alert::Alert;
+
+/// Query parameters for listing alerts
+#[derive(Deserialize)]
+pub struct AlertsQuery {
+    /// Maximum number of most recent alerts to return
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+fn default_limit() -> usize { MAX_ALERTS }
+
+/// Response for listing alerts
+#[derive(Serialize)]
+struct AlertsResponse {
+    alerts: Vec<Alert>,
+    total: usize,
+}
+
+/// Get recent security alerts
+pub async fn get_alerts(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<AlertsQuery>,
+) -> impl Responder {
+    // Create a future for alert retrieval
+    let alerts_future = async {
+        let capture_manager = capture_manager.read().await;
+        
+        AlertsResponse {
+            alerts: capture_manager.get_alerts(query.limit),
+            total: capture_manager.get_alert_count(),
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), alerts_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving alerts"
+            }))
+        }
+    }
+}
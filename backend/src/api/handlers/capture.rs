@@ -1,13 +1,20 @@
 use actix_web::{web, HttpResponse, Responder};
 use log::{info, error, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use futures::future::FutureExt;
 
-use crate::capture::manager::CaptureManager;
+use crate::capture::bpf;
+use crate::capture::file::{FileSource, ReadFiles};
+use crate::capture::manager::{CaptureManager, FilterChange};
+use crate::capture::trigger::{self, TriggerSettings};
 use crate::models::config::AppConfig;
+use crate::models::filter::CompiledFilter;
 use crate::models::interface::InterfaceInfo;
+use crate::models::stats::{ByteBudget, CaptureDescription, RateTrend};
+use crate::utils::json_guard::JsonLimits;
 
 /// Request for starting capture
 #[derive(Deserialize)]
@@ -18,8 +25,45 @@ pub struct StartCaptureRequest {
     /// Promiscuous mode
     pub promiscuous: Option<bool>,
     
+    /// Capture a bond or bridge on each of its members
+    pub members: Option<bool>,
+    
+    /// Open every member first and start them together
+    pub synchronized_start: Option<bool>,
+    
     /// Filter expression
     pub filter: Option<String>,
+    
+    /// ID of a saved filter whose BPF expression to use; `filter` takes precedence
+    pub filter_id: Option<String>,
+    
+    /// Free-text description of what is being captured and why
+    pub description: Option<String>,
+    
+    /// Key/value tags for the capture session
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Request for stopping capture; without an interface every capture stops
+#[derive(Deserialize)]
+pub struct StopCaptureRequest {
+    /// Interface whose capture to stop, leaving the others running
+    pub interface: Option<String>,
+}
+
+/// Request for editing the capture description; absent fields are kept
+#[derive(Deserialize)]
+pub struct UpdateDescriptionRequest {
+    pub description: Option<String>,
+    pub tags: Option<BTreeMap<String, String>>,
+}
+
+/// Request for changing the capture filter
+#[derive(Deserialize)]
+pub struct SetFilterRequest {
+    /// BPF expression to apply
+    pub filter: String,
 }
 
 /// Request for updating capture settings
@@ -36,6 +80,54 @@ pub struct UpdateSettingsRequest {
     
     /// Buffer size
     pub buffer_size: Option<usize>,
+    
+    /// Number of packets to discard at capture start
+    pub warmup_packets: Option<usize>,
+    
+    /// Warm-up time window in milliseconds
+    pub warmup_ms: Option<u64>,
+    
+    /// Delay before capture starts reading packets, in milliseconds
+    pub start_delay_ms: Option<u64>,
+    
+    /// Seconds without packets after which the capture stops (0 disables)
+    pub stop_after_idle_secs: Option<u64>,
+    
+    /// Packets processed per conversation before the rest are dropped (0 disables)
+    pub flow_head_packets: Option<usize>,
+    
+    /// Frames shorter than this are dropped before decoding (0 disables)
+    pub min_size: Option<usize>,
+    
+    /// Frames longer than this are dropped before decoding (0 disables)
+    pub max_size: Option<usize>,
+    
+    /// Leave RustShark's own API traffic out of the next capture
+    pub exclude_api_traffic: Option<bool>,
+}
+
+/// Request for loading capture files
+#[derive(Deserialize)]
+pub struct LoadFilesRequest {
+    /// Files to load, each with an optional source label
+    pub files: Vec<FileSource>,
+    
+    /// Add to the packets already stored instead of replacing them
+    #[serde(default)]
+    pub append: bool,
+    
+    /// Store at most this many packets (0 for no cap; defaults to `--read-max-packets`)
+    pub max_packets: Option<usize>,
+}
+
+/// Request for adding or removing a decode-as override
+#[derive(Deserialize)]
+pub struct DecodeAsRequest {
+    /// TCP/UDP port the override applies to (source or destination)
+    pub port: u16,
+    
+    /// Protocol to decode the port's traffic as; null or empty removes the override
+    pub protocol: Option<String>,
 }
 
 /// Response for listing interfaces
@@ -52,7 +144,23 @@ struct InterfacesResponse {
 #[derive(Serialize)]
 struct CaptureStatusResponse {
     is_running: bool,
+    session_id: Option<String>,
+    
+    /// Interfaces (or remote sources) still being captured
+    interfaces: Vec<String>,
     stats: Option<serde_json::Value>,
+    
+    /// Byte budget and its use, with --max-total-bytes
+    budget: Option<ByteBudget>,
+    
+    /// Seconds after which any capture is stopped, with --max-capture-seconds
+    max_capture_seconds: Option<u64>,
+    
+    /// Promiscuous mode the device actually entered (null when unverified)
+    actual_promiscuous: Option<bool>,
+    
+    /// Packets and bytes per second over the last minute
+    rates: RateTrend,
 }
 
 /// Response for capture diagnostic information
@@ -60,34 +168,35 @@ struct CaptureStatusResponse {
 struct CaptureDiagnosticResponse {
     is_running: bool,
     packet_count: usize,
+    description: CaptureDescription,
     stats: serde_json::Value,
     interfaces: Vec<String>,
     detailed_interfaces: Vec<InterfaceInfo>,
     selected_interface: Option<String>,
     promiscuous_mode: bool,
+    
+    /// Promiscuous mode the device actually entered (null when unverified)
+    actual_promiscuous: Option<bool>,
     filter: Option<String>,
+    
+    /// The filter captures are opened with, including the API traffic exclusion
+    capture_filter: Option<String>,
+    json_limits: JsonLimits,
 }
 
 /// List available network interfaces
 pub async fn list_interfaces(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
 ) -> impl Responder {
-    // Use a non-blocking async approach
-    let interfaces_future = async {
-        let capture_manager = capture_manager.read().await;
-        
-        // Basic interface listing
-        let interfaces = capture_manager.list_interfaces();
-        
-        // Detailed interface info
-        let detailed_interfaces = capture_manager.get_interface_info();
-        
-        (interfaces, detailed_interfaces)
+    // Only the lock is awaited here; the lister bounds the enumeration itself
+    let lister_future = async {
+        capture_manager.read().await.interface_lister()
     };
     
-    // Add a timeout to prevent blocking for too long
-    match tokio::time::timeout(std::time::Duration::from_secs(5), interfaces_future).await {
-        Ok((interfaces, detailed_interfaces)) => {
+    match tokio::time::timeout(std::time::Duration::from_secs(5), lister_future).await {
+        Ok(lister) => {
+            let detailed_interfaces = lister.list().await;
+            let interfaces = detailed_interfaces.iter().map(|info| info.device_name.clone()).collect();
             HttpResponse::Ok().json(InterfacesResponse { 
                 interfaces,
                 detailed_interfaces
@@ -108,12 +217,23 @@ pub async fn start_capture(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
     request: Option<web::Json<StartCaptureRequest>>,
 ) -> impl Responder {
+    // Resolve a saved filter up front so a bad reference fails before anything changes
+    let saved_filter = match request.as_ref().filter(|req| req.filter.is_none()).and_then(|req| req.filter_id.as_deref()) {
+        Some(filter_id) => match resolve_saved_filter(&capture_manager, filter_id).await {
+            Ok(expression) => Some(expression),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+    
     // Create a future to handle the start capture operation
     let start_future = async {
         let mut capture_manager = capture_manager.write().await;
         
         // Apply request parameters if provided
+        let mut description = CaptureDescription::default();
         if let Some(req) = request {
+            let req = req.into_inner();
             if let Some(interface) = &req.interface {
                 capture_manager.set_interface(interface.clone());
             }
@@ -122,17 +242,30 @@ pub async fn start_capture(
                 capture_manager.set_promiscuous(promiscuous);
             }
             
-            if let Some(filter) = &req.filter {
-                capture_manager.set_filter(filter.clone());
+            if let Some(members) = req.members {
+                capture_manager.set_capture_members(members);
+            }
+            
+            if let Some(synchronized) = req.synchronized_start {
+                capture_manager.set_synchronized_start(synchronized);
+            }
+            
+            if let Some(filter) = req.filter.clone().or(saved_filter) {
+                capture_manager.set_filter(filter);
             }
+            
+            description = CaptureDescription { description: req.description, tags: req.tags };
         }
-
+        
         info!("Starting capture with interface: {:?}, promiscuous: {:?}, filter: {:?}",
               capture_manager.get_selected_interface(),
               capture_manager.is_promiscuous(),
               capture_manager.get_filter());
         
-        capture_manager.start_capture().await
+        // The description belongs to the new session, so only replace it once that started
+        capture_manager.start_capture().await?;
+        capture_manager.set_description(description);
+        Ok::<(), anyhow::Error>(())
     };
     
     // Execute with timeout to prevent hanging the server
@@ -166,25 +299,282 @@ pub async fn start_capture(
     }
 }
 
+/// Look up the BPF expression of a saved filter
+async fn resolve_saved_filter(
+    capture_manager: &RwLock<CaptureManager>,
+    filter_id: &str,
+) -> Result<String, HttpResponse> {
+    let filter = capture_manager.read().await.get_saved_filter(filter_id);
+    match filter {
+        None => Err(HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Filter {} not found", filter_id)
+        }))),
+        Some(filter) => match filter.bpf_expression.filter(|e| !e.trim().is_empty()) {
+            Some(expression) => Ok(expression),
+            None => Err(HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Filter {} ({}) has no BPF expression", filter_id, filter.name)
+            }))),
+        },
+    }
+}
+
+/// Query parameters for paging through the recovered packet index
+#[derive(Deserialize)]
+pub struct RecoveredIndexQuery {
+    #[serde(default)]
+    offset: usize,
+    
+    #[serde(default = "default_index_limit")]
+    limit: usize,
+}
+
+fn default_index_limit() -> usize { 100 }
+
+/// Get the packet summaries recovered at startup from the index file of a crashed run
+pub async fn get_recovered_index(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<RecoveredIndexQuery>,
+) -> impl Responder {
+    let index_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_recovered_index().map(|recovered| serde_json::json!({
+            "session_id": recovered.session_id,
+            "total": recovered.entries.len(),
+            "offset": query.offset,
+            "limit": query.limit,
+            "packets": recovered.entries.iter().skip(query.offset).take(query.limit).collect::<Vec<_>>(),
+        }))
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(3), index_future).await {
+        Ok(Some(response)) => HttpResponse::Ok().json(response),
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": "No packet index was recovered (see --index-file)"
+            }))
+        },
+        Err(_) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while reading the recovered packet index"
+            }))
+        }
+    }
+}
+
+/// Edit the description and tags of the current capture (also while it runs)
+pub async fn update_capture_description(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<UpdateDescriptionRequest>,
+) -> impl Responder {
+    let request = request.into_inner();
+    
+    let update_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        let mut description = capture_manager.get_description();
+        if let Some(text) = request.description {
+            description.description = Some(text).filter(|t| !t.is_empty());
+        }
+        if let Some(tags) = request.tags {
+            description.tags = tags;
+        }
+        capture_manager.set_description(description.clone());
+        description
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), update_future).await {
+        Ok(description) => {
+            info!("Capture description updated");
+            HttpResponse::Ok().json(description)
+        },
+        Err(_) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while updating the capture description"
+            }))
+        }
+    }
+}
+
+/// Change the capture filter, without restarting the capture where possible
+pub async fn set_capture_filter(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<SetFilterRequest>,
+) -> impl Responder {
+    let filter = request.into_inner().filter;
+    if filter.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "filter must not be empty"
+        }));
+    }
+    
+    let apply_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        capture_manager.apply_filter(filter.clone()).await
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(15), apply_future).await {
+        Ok(Ok(change)) => {
+            info!("Capture filter set to '{}' ({})", filter, change.as_str());
+            let message = match change {
+                FilterChange::Live => "Filter applied to the running capture",
+                FilterChange::Restart => "Filter could not be changed live; the capture was restarted and its buffer cleared",
+                FilterChange::Stored => "No capture is running; the filter applies to the next one",
+            };
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "filter": filter,
+                "applied": change.as_str(),
+                "message": message
+            }))
+        },
+        Ok(Err(e)) => {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string()
+            }))
+        },
+        Err(_) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while changing the capture filter"
+            }))
+        }
+    }
+}
+
+/// Query parameters for compiling a filter
+#[derive(Deserialize)]
+pub struct CompiledFilterQuery {
+    /// Expression to compile instead of the capture's filter
+    filter: Option<String>,
+    
+    /// Link-layer type by libpcap name (e.g. "EN10MB", "IEEE802_11_RADIO"); Ethernet by default
+    link_type: Option<String>,
+    
+    #[serde(default = "default_optimize")]
+    optimize: bool,
+}
+
+fn default_optimize() -> bool { true }
+
+/// Get a filter compiled to BPF instructions, as `tcpdump -d` shows them
+pub async fn get_compiled_filter(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<CompiledFilterQuery>,
+) -> impl Responder {
+    let query = query.into_inner();
+    let filter = match query.filter.filter(|filter| !filter.trim().is_empty()) {
+        Some(filter) => Some(filter),
+        None => match tokio::time::timeout(std::time::Duration::from_secs(2), capture_manager.read()).await {
+            Ok(capture_manager) => capture_manager.get_filter(),
+            Err(_) => return HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while reading the capture filter"
+            })),
+        },
+    };
+    let Some(filter) = filter else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "No capture filter is set; pass one with ?filter="
+        }));
+    };
+    
+    let link_type = match query.link_type.as_deref() {
+        Some(name) => match pcap::Linktype::from_name(name) {
+            Ok(link_type) => link_type,
+            Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Unknown link type '{}'", name)
+            })),
+        },
+        None => pcap::Linktype::ETHERNET,
+    };
+    
+    match bpf::compile(&filter, link_type, query.optimize) {
+        Ok(instructions) => HttpResponse::Ok().json(CompiledFilter {
+            link_type: link_type.get_name().unwrap_or_else(|_| link_type.0.to_string()),
+            filter,
+            optimized: query.optimize,
+            instructions,
+        }),
+        Err(e) => HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": e.to_string()
+        })),
+    }
+}
+
+/// Zero the capture statistics without dropping the stored packets
+pub async fn reset_capture_stats(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let reset_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        capture_manager.reset_stats().await
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), reset_future).await {
+        Ok(stats) => {
+            info!("Capture statistics reset");
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "message": "Statistics reset; stored packets were kept",
+                "stats": stats
+            }))
+        },
+        Err(_) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while resetting the capture statistics"
+            }))
+        }
+    }
+}
+
 /// Stop packet capture
 pub async fn stop_capture(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: Option<web::Json<StopCaptureRequest>>,
 ) -> impl Responder {
+    let interface = request.and_then(|request| request.into_inner().interface);
+    
     // Create a future to handle the stop capture operation
     let stop_future = async {
         let mut capture_manager = capture_manager.write().await;
-        capture_manager.stop_capture().await
+        let result = match &interface {
+            Some(interface) if !capture_manager.active_interfaces().contains(interface) => {
+                return Err(HttpResponse::NotFound().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("No capture is running on {}", interface)
+                })));
+            },
+            Some(interface) => capture_manager.stop_interface(interface).await,
+            None => capture_manager.stop_capture().await,
+        };
+        Ok((result, capture_manager.active_interfaces()))
     };
     
     // Execute with timeout to prevent hanging the server
     match tokio::time::timeout(std::time::Duration::from_secs(10), stop_future).await {
-        Ok(result) => {
+        Ok(Err(response)) => response,
+        Ok(Ok((result, remaining))) => {
             match result {
                 Ok(_) => {
-                    info!("Capture stopped successfully");
+                    let message = match &interface {
+                        Some(interface) => format!("Capture on {} stopped successfully", interface),
+                        None => "Capture stopped successfully".to_string(),
+                    };
+                    info!("{}", message);
                     HttpResponse::Ok().json(serde_json::json!({
                         "status": "success",
-                        "message": "Capture stopped successfully"
+                        "message": message,
+                        "interfaces": remaining
                     }))
                 },
                 Err(e) => {
@@ -207,6 +597,61 @@ pub async fn stop_capture(
     }
 }
 
+/// Load packets from one or more capture files in `--load-dir`, merged into one timeline
+pub async fn load_files(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<LoadFilesRequest>,
+) -> impl Responder {
+    let LoadFilesRequest { files, append, max_packets } = request.into_inner();
+    let sources: Vec<String> = files.iter().map(FileSource::label).collect();
+    
+    let Some(load_dir) = capture_manager.read().await.load_dir() else {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "message": "Loading capture files is disabled; start the server with --load-dir"
+        }));
+    };
+    
+    // The files are read before the manager is locked, so the API keeps answering meanwhile
+    let load_future = async {
+        let read = tokio::task::spawn_blocking(move || {
+            let files = files.iter().map(|file| file.confined_to(&load_dir)).collect::<anyhow::Result<_>>()?;
+            ReadFiles::read(files)
+        })
+        .await??;
+        capture_manager.write().await.load_files(read, append, max_packets)
+    };
+    
+    // Execute with timeout to prevent hanging the server
+    match tokio::time::timeout(std::time::Duration::from_secs(30), load_future).await {
+        Ok(Ok(summary)) => {
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "message": format!("Loaded {} of {} packets ({} retained)", summary.loaded, summary.read, summary.retained),
+                "read": summary.read,
+                "loaded": summary.loaded,
+                "retained": summary.retained,
+                "errors": summary.errors,
+                "sources": sources,
+            }))
+        },
+        Ok(Err(e)) => {
+            error!("Failed to load capture files: {}", e);
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to load capture files: {}", e)
+            }))
+        },
+        Err(_) => {
+            error!("Timeout while loading capture files");
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while loading capture files - operation took too long"
+            }))
+        }
+    }
+}
+
 /// Get capture status
 pub async fn get_capture_status(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
@@ -225,7 +670,13 @@ pub async fn get_capture_status(
         
         CaptureStatusResponse {
             is_running,
+            session_id: capture_manager.get_session_id(),
+            interfaces: capture_manager.active_interfaces(),
             stats,
+            budget: capture_manager.get_byte_budget(),
+            max_capture_seconds: capture_manager.get_max_capture_seconds(),
+            actual_promiscuous: capture_manager.get_actual_promiscuous(),
+            rates: capture_manager.get_rate_trend(),
         }
     };
     
@@ -244,6 +695,42 @@ pub async fn get_capture_status(
     }
 }
 
+/// Estimate the memory used by the packet buffer
+pub async fn get_capture_memory(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let memory_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.estimate_memory()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), memory_future).await {
+        Ok(estimate) => HttpResponse::Ok().json(estimate),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while estimating capture memory"
+        })),
+    }
+}
+
+/// Summarize the capture: counts, duration, average rates, protocols and file hashes
+pub async fn get_capture_info(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let info_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_capture_info()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), info_future).await {
+        Ok(info) => HttpResponse::Ok().json(info),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while summarizing capture"
+        })),
+    }
+}
+
 /// Get capture diagnostic information
 pub async fn get_capture_diagnostic(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
@@ -258,39 +745,33 @@ pub async fn get_capture_diagnostic(
         let packet_count = capture_manager.get_packet_count();
         let stats = serde_json::to_value(&capture_manager.get_stats()).unwrap_or_default();
         
-        // Start a separate task for potentially slow interface operations
-        let interfaces_future = async {
-            let interfaces = capture_manager.list_interfaces();
-            let detailed_interfaces = capture_manager.get_interface_info();
-            (interfaces, detailed_interfaces)
-        };
-        
-        // Use a short timeout for interface operations
-        let (interfaces, detailed_interfaces) = match tokio::time::timeout(
-            std::time::Duration::from_secs(2), 
-            interfaces_future
-        ).await {
-            Ok(result) => result,
-            Err(_) => {
-                // On timeout, use empty values
-                warn!("Timeout while getting interface information");
-                (Vec::new(), Vec::new())
-            }
-        };
-        
         let selected_interface = capture_manager.get_selected_interface();
         let promiscuous_mode = capture_manager.is_promiscuous();
+        let actual_promiscuous = capture_manager.get_actual_promiscuous();
         let filter = capture_manager.get_filter();
+        let capture_filter = capture_manager.capture_filter();
+        let json_limits = capture_manager.get_json_limits();
+        let description = capture_manager.get_description();
+        let lister = capture_manager.interface_lister();
+        drop(capture_manager);
+        
+        // Enumerated off the async threads, falling back to the last known list
+        let detailed_interfaces = lister.list().await;
+        let interfaces = detailed_interfaces.iter().map(|info| info.device_name.clone()).collect();
         
         CaptureDiagnosticResponse {
             is_running,
             packet_count,
+            description,
             stats,
             interfaces,
             detailed_interfaces,
             selected_interface,
             promiscuous_mode,
+            actual_promiscuous,
             filter,
+            capture_filter,
+            json_limits,
         }
     };
     
@@ -312,6 +793,209 @@ pub async fn get_capture_diagnostic(
     }
 }
 
+/// Get the current decode-as overrides
+pub async fn get_decode_as(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let decode_as_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_decode_as()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(2), decode_as_future).await {
+        Ok(decode_as) => HttpResponse::Ok().json(serde_json::json!({
+            "decode_as": decode_as,
+        })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while retrieving decode-as overrides"
+        })),
+    }
+}
+
+/// Get the session's event log: capture warnings and errors, and link changes of the capture interfaces
+pub async fn get_capture_events(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let events_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_events()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(2), events_future).await {
+        Ok(events) => HttpResponse::Ok().json(serde_json::json!({
+            "total": events.len(),
+            "events": events,
+        })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while retrieving capture events"
+        })),
+    }
+}
+
+/// Add, replace or remove a decode-as override (applies to the running capture too)
+pub async fn set_decode_as(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<DecodeAsRequest>,
+) -> impl Responder {
+    let protocol = request.protocol
+        .as_deref()
+        .map(|p| p.trim().to_ascii_uppercase())
+        .filter(|p| !p.is_empty());
+    
+    let decode_as_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        capture_manager.set_decode_as(request.port, protocol.clone());
+        capture_manager.get_decode_as()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), decode_as_future).await {
+        Ok(decode_as) => {
+            match &protocol {
+                Some(protocol) => info!("Decoding port {} as {}", request.port, protocol),
+                None => info!("Removed decode-as override for port {}", request.port),
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "decode_as": decode_as,
+            }))
+        },
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while updating decode-as overrides"
+        })),
+    }
+}
+
+/// Arm a threshold trigger that starts a capture when traffic crosses it
+pub async fn arm_capture(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<TriggerSettings>,
+) -> impl Responder {
+    let manager = capture_manager.get_ref().clone();
+    let mut settings = request.into_inner();
+    
+    let arm_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        if settings.interface.is_none() {
+            settings.interface = capture_manager.get_selected_interface();
+        }
+        
+        // Replacing an armed trigger disarms the old one
+        let armed = trigger::arm(manager, settings)?;
+        let status = armed.status();
+        capture_manager.set_trigger(Some(armed));
+        anyhow::Ok(status)
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), arm_future).await {
+        Ok(Ok(status)) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "success",
+            "trigger": status,
+        })),
+        Ok(Err(e)) => {
+            warn!("Failed to arm capture: {}", e);
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Failed to arm capture: {}", e)
+            }))
+        },
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while arming capture"
+        })),
+    }
+}
+
+/// Get the status of the armed trigger
+pub async fn get_trigger(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let trigger_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_trigger_status()
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(2), trigger_future).await {
+        Ok(status) => HttpResponse::Ok().json(serde_json::json!({
+            "trigger": status,
+        })),
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while retrieving trigger status"
+        })),
+    }
+}
+
+/// Disarm the trigger (a capture it already started keeps running)
+pub async fn disarm_capture(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let disarm_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        capture_manager.set_trigger(None);
+    };
+    
+    match tokio::time::timeout(std::time::Duration::from_secs(5), disarm_future).await {
+        Ok(()) => {
+            info!("Capture trigger disarmed");
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "message": "Capture trigger disarmed"
+            }))
+        },
+        Err(_) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": "Timeout while disarming capture"
+        })),
+    }
+}
+
+/// Request for injecting synthetic frames into an "inject" capture session
+#[cfg(feature = "test-inject")]
+#[derive(Deserialize)]
+pub struct InjectRequest {
+    /// Hex-encoded Ethernet frames, processed in order
+    frames: Vec<String>,
+}
+
+/// Feed frames through the packet processor as if they had been captured
+#[cfg(feature = "test-inject")]
+pub async fn inject_packets(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<InjectRequest>,
+) -> impl Responder {
+    let mut frames = Vec::with_capacity(request.frames.len());
+    for (index, frame) in request.frames.iter().enumerate() {
+        match crate::capture::snapshot::decode_hex(frame) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Frame {}: {}", index, e)
+                }));
+            },
+        }
+    }
+    
+    let capture_manager = capture_manager.read().await;
+    let count = frames.len();
+    for frame in frames {
+        if let Err(e) = capture_manager.inject_packet(frame, chrono::Utc::now()) {
+            return HttpResponse::Conflict().json(serde_json::json!({
+                "status": "error",
+                "message": e.to_string()
+            }));
+        }
+    }
+    
+    HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "injected": count
+    }))
+}
+
 /// Update capture settings
 pub async fn update_capture_settings(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
@@ -319,6 +1003,17 @@ pub async fn update_capture_settings(
 ) -> impl Responder {
     let mut capture_manager = capture_manager.write().await;
     
+    // Check the size range against the current bounds before changing anything
+    let (current_min, current_max) = capture_manager.get_size_limits();
+    let min_size = request.min_size.unwrap_or(current_min);
+    let max_size = request.max_size.unwrap_or(current_max);
+    if max_size > 0 && min_size > max_size {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": format!("min_size ({}) must not exceed max_size ({})", min_size, max_size)
+        }));
+    }
+    
     // Update selected interface
     if let Some(interface) = &request.interface {
         info!("Setting interface to {}", interface);
@@ -337,12 +1032,51 @@ pub async fn update_capture_settings(
         capture_manager.set_filter(filter.clone());
     }
     
+    // Update self-traffic exclusion, applied with the filter when the next capture starts
+    if let Some(exclude_api_traffic) = request.exclude_api_traffic {
+        info!("{} API traffic in captures", if exclude_api_traffic { "Excluding" } else { "Including" });
+        capture_manager.set_exclude_api_traffic(exclude_api_traffic);
+    }
+    
     // Update buffer size (if available in CaptureManager)
     if let Some(buffer_size) = request.buffer_size {
         info!("Setting buffer size to {}", buffer_size);
         capture_manager.set_buffer_size(buffer_size);
     }
     
+    // Update warm-up discard limits
+    if request.warmup_packets.is_some() || request.warmup_ms.is_some() {
+        let (current_packets, current_ms) = capture_manager.get_warmup();
+        let packets = request.warmup_packets.unwrap_or(current_packets);
+        let ms = request.warmup_ms.unwrap_or(current_ms);
+        info!("Setting warm-up to {} packets / {}ms", packets, ms);
+        capture_manager.set_warmup(packets, ms);
+    }
+    
+    // Update start delay
+    if let Some(start_delay_ms) = request.start_delay_ms {
+        info!("Setting start delay to {}ms", start_delay_ms);
+        capture_manager.set_start_delay(start_delay_ms);
+    }
+    
+    // Update idle timeout
+    if let Some(stop_after_idle_secs) = request.stop_after_idle_secs {
+        info!("Setting idle timeout to {}s", stop_after_idle_secs);
+        capture_manager.set_stop_after_idle(stop_after_idle_secs);
+    }
+    
+    // Update flow-head mode
+    if let Some(flow_head_packets) = request.flow_head_packets {
+        info!("Setting flow head to {} packets", flow_head_packets);
+        capture_manager.set_flow_head(flow_head_packets);
+    }
+    
+    // Update size filtering, effective immediately
+    if request.min_size.is_some() || request.max_size.is_some() {
+        info!("Setting packet size range to {}..{} bytes", min_size, max_size);
+        capture_manager.set_size_limits(min_size, max_size);
+    }
+    
     HttpResponse::Ok().json(serde_json::json!({
         "status": "success",
         "message": "Settings updated successfully"
@@ -1,12 +1,14 @@
 use actix_web::{web, HttpResponse, Responder};
-use log::{info, error};
-use serde::{Deserialize, Serialize};
+use log::info;
+use serde::Deserialize;
+use std::net::IpAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::capture::color_rules::ColorRules;
 use crate::capture::manager::CaptureManager;
-use crate::models::filter::Filter;
+use crate::models::filter::{ColorRule, Filter};
 
 /// Create filter request
 #[derive(Deserialize)]
@@ -39,15 +41,60 @@ pub struct UpdateFilterRequest {
     active: Option<bool>,
 }
 
+/// Parse an optional IP address field, naming the field in the error
+fn parse_ip(field: &str, value: Option<&str>) -> Result<Option<IpAddr>, HttpResponse> {
+    match value {
+        None => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid {}: {}", field, value)
+            }))
+        }),
+    }
+}
+
+/// Response for an unknown filter ID
+fn filter_not_found(filter_id: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "status": "error",
+        "message": format!("Filter {} not found", filter_id)
+    }))
+}
+
 /// Create a new filter
 pub async fn create_filter(
-    _capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
-    _req: web::Json<CreateFilterRequest>,
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    req: web::Json<CreateFilterRequest>,
 ) -> impl Responder {
-    // In a real implementation, we would create a filter here
-    // For now, we just return a mock response
+    let req = req.into_inner();
+    let source_ip = match parse_ip("source_ip", req.source_ip.as_deref()) {
+        Ok(ip) => ip,
+        Err(response) => return response,
+    };
+    let destination_ip = match parse_ip("destination_ip", req.destination_ip.as_deref()) {
+        Ok(ip) => ip,
+        Err(response) => return response,
+    };
+    
+    let filter = Filter {
+        id: Uuid::new_v4().to_string(),
+        name: req.name,
+        bpf_expression: req.bpf_expression,
+        protocol: req.protocol,
+        source_ip,
+        destination_ip,
+        source_port: req.source_port,
+        destination_port: req.destination_port,
+        min_size: req.min_size,
+        max_size: req.max_size,
+        custom_expression: req.custom_expression,
+        active: true,
+    };
+    let filter_id = filter.id.clone();
     
-    let filter_id = Uuid::new_v4().to_string();
+    capture_manager.write().await.save_filter(filter);
+    info!("Created filter {}", filter_id);
     
     HttpResponse::Created().json(serde_json::json!({
         "status": "success",
@@ -58,55 +105,71 @@ pub async fn create_filter(
 
 /// List available filters
 pub async fn list_filters(
-    _capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
 ) -> impl Responder {
-    // In a real implementation, we would list filters here
-    // For now, we just return a mock response
-    
-    let filters = vec![
-        Filter {
-            id: "1".to_string(),
-            name: "HTTP Traffic".to_string(),
-            bpf_expression: Some("tcp port 80".to_string()),
-            protocol: Some("TCP".to_string()),
-            source_ip: None,
-            destination_ip: None,
-            source_port: None,
-            destination_port: Some(80),
-            min_size: None,
-            max_size: None,
-            custom_expression: None,
-            active: true,
-        },
-        Filter {
-            id: "2".to_string(),
-            name: "HTTPS Traffic".to_string(),
-            bpf_expression: Some("tcp port 443".to_string()),
-            protocol: Some("TCP".to_string()),
-            source_ip: None,
-            destination_ip: None,
-            source_port: None,
-            destination_port: Some(443),
-            min_size: None,
-            max_size: None,
-            custom_expression: None,
-            active: true,
-        },
-    ];
+    let filters = capture_manager.read().await.list_filters();
     
     HttpResponse::Ok().json(filters)
 }
 
-/// Update a filter
+/// Update a filter; absent fields keep their value
 pub async fn update_filter(
-    _capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
-    _path: web::Path<String>,
-    _req: web::Json<UpdateFilterRequest>,
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<String>,
+    req: web::Json<UpdateFilterRequest>,
 ) -> impl Responder {
-    // In a real implementation, we would update a filter here
-    // For now, we just return a mock response
+    let filter_id = path.into_inner();
+    let req = req.into_inner();
+    let source_ip = match parse_ip("source_ip", req.source_ip.as_deref()) {
+        Ok(ip) => ip,
+        Err(response) => return response,
+    };
+    let destination_ip = match parse_ip("destination_ip", req.destination_ip.as_deref()) {
+        Ok(ip) => ip,
+        Err(response) => return response,
+    };
+    
+    let mut capture_manager = capture_manager.write().await;
+    let Some(mut filter) = capture_manager.get_saved_filter(&filter_id) else {
+        return filter_not_found(&filter_id);
+    };
+    
+    if let Some(name) = req.name {
+        filter.name = name;
+    }
+    if req.bpf_expression.is_some() {
+        filter.bpf_expression = req.bpf_expression;
+    }
+    if req.protocol.is_some() {
+        filter.protocol = req.protocol;
+    }
+    if source_ip.is_some() {
+        filter.source_ip = source_ip;
+    }
+    if destination_ip.is_some() {
+        filter.destination_ip = destination_ip;
+    }
+    if req.source_port.is_some() {
+        filter.source_port = req.source_port;
+    }
+    if req.destination_port.is_some() {
+        filter.destination_port = req.destination_port;
+    }
+    if req.min_size.is_some() {
+        filter.min_size = req.min_size;
+    }
+    if req.max_size.is_some() {
+        filter.max_size = req.max_size;
+    }
+    if req.custom_expression.is_some() {
+        filter.custom_expression = req.custom_expression;
+    }
+    if let Some(active) = req.active {
+        filter.active = active;
+    }
     
-    let filter_id = _path.into_inner();
+    capture_manager.save_filter(filter);
+    info!("Updated filter {}", filter_id);
     
     HttpResponse::Ok().json(serde_json::json!({
         "status": "success",
@@ -116,16 +179,55 @@ pub async fn update_filter(
 
 /// Delete a filter
 pub async fn delete_filter(
-    _capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
-    _path: web::Path<String>,
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<String>,
 ) -> impl Responder {
-    // In a real implementation, we would delete a filter here
-    // For now, we just return a mock response
+    let filter_id = path.into_inner();
     
-    let filter_id = _path.into_inner();
+    if capture_manager.write().await.delete_saved_filter(&filter_id).is_none() {
+        return filter_not_found(&filter_id);
+    }
+    info!("Deleted filter {}", filter_id);
     
     HttpResponse::Ok().json(serde_json::json!({
         "status": "success",
         "message": format!("Filter {} deleted successfully", filter_id)
     }))
-} 
\ No newline at end of file
+}
+
+/// List the packet coloring rules, in the order they are tried
+pub async fn get_color_rules(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let color_rules = capture_manager.read().await.color_rules();
+    
+    HttpResponse::Ok().json(color_rules.list())
+}
+
+/// Replace the packet coloring rules; a packet takes the color of the first rule it matches
+pub async fn set_color_rules(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    req: web::Json<Vec<ColorRule>>,
+) -> impl Responder {
+    let compiled = match ColorRules::compile(req.into_inner()) {
+        Ok(compiled) => compiled,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid color rules: {}", e)
+            }));
+        },
+    };
+    let count = compiled.len();
+    
+    let color_rules = capture_manager.read().await.color_rules();
+    if let Err(e) = color_rules.replace(compiled) {
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Failed to save color rules: {}", e)
+        }));
+    }
+    info!("Set {} color rules", count);
+    
+    HttpResponse::Ok().json(color_rules.list())
+}
@@ -0,0 +1,82 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Lock-free state backing the health probes
+pub struct HealthState {
+    /// Time the process started serving
+    started_at: Instant,
+    
+    /// Capture running flag shared with the capture manager
+    capture_running: Arc<AtomicBool>,
+    
+    /// Whether the capture subsystem finished initializing
+    ready: AtomicBool,
+    
+    /// Why the snapshot or packet index of the previous run was not restored
+    restore_errors: Vec<String>,
+}
+
+impl HealthState {
+    /// Create health state around the capture manager's running flag
+    pub fn new(capture_running: Arc<AtomicBool>) -> Self {
+        Self {
+            started_at: Instant::now(),
+            capture_running,
+            ready: AtomicBool::new(false),
+            restore_errors: Vec::new(),
+        }
+    }
+    
+    /// Report the startup restores that failed in the health check
+    pub fn with_restore_errors(mut self, errors: Vec<String>) -> Self {
+        self.restore_errors = errors;
+        self
+    }
+    
+    /// Mark the capture subsystem as initialized
+    pub fn set_ready(&self, ready: bool) {
+        self.ready.store(ready, Ordering::SeqCst);
+    }
+}
+
+/// Response for health probes
+#[derive(Serialize)]
+struct HealthResponse<'a> {
+    status: &'static str,
+    uptime_secs: u64,
+    capture_running: bool,
+    
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    restore_errors: &'a [String],
+}
+
+fn health_response<'a>(state: &'a HealthState, status: &'static str) -> HealthResponse<'a> {
+    HealthResponse {
+        status,
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        capture_running: state.capture_running.load(Ordering::Relaxed),
+        restore_errors: &state.restore_errors,
+    }
+}
+
+/// Combined health check
+pub async fn health(state: web::Data<HealthState>) -> impl Responder {
+    HttpResponse::Ok().json(health_response(&state, "ok"))
+}
+
+/// Liveness probe - the process is up and serving requests
+pub async fn health_live(state: web::Data<HealthState>) -> impl Responder {
+    HttpResponse::Ok().json(health_response(&state, "alive"))
+}
+
+/// Readiness probe - the capture subsystem is initialized
+pub async fn health_ready(state: web::Data<HealthState>) -> impl Responder {
+    if state.ready.load(Ordering::SeqCst) {
+        HttpResponse::Ok().json(health_response(&state, "ready"))
+    } else {
+        HttpResponse::ServiceUnavailable().json(health_response(&state, "not_ready"))
+    }
+}
@@ -0,0 +1,71 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::{info, LevelFilter};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::logging::{self, LOG_LEVELS};
+
+/// Active logging settings
+#[derive(Serialize)]
+struct LogLevelResponse {
+    /// Most verbose level logged ("trace" to "error", or "off")
+    level: String,
+    
+    /// Whether the console shows every message logged, not only warnings, errors and start/stop events
+    verbose: bool,
+    
+    /// Accepted level names
+    levels: &'static [&'static str],
+}
+
+impl LogLevelResponse {
+    fn current() -> Self {
+        Self {
+            level: level_name(logging::current_log_level()),
+            verbose: logging::is_verbose_mode(),
+            levels: LOG_LEVELS,
+        }
+    }
+}
+
+/// Request body for changing the logging settings; omitted fields are left as they are
+#[derive(Deserialize)]
+pub struct LogLevelRequest {
+    level: Option<String>,
+    verbose: Option<bool>,
+}
+
+fn level_name(level: LevelFilter) -> String {
+    level.to_string().to_lowercase()
+}
+
+/// Get the active log level and console verbosity
+pub async fn get_log_level() -> impl Responder {
+    HttpResponse::Ok().json(LogLevelResponse::current())
+}
+
+/// Change the log level and console verbosity without restarting
+pub async fn set_log_level(request: web::Json<LogLevelRequest>) -> impl Responder {
+    let level = match request.level.as_deref() {
+        Some(name) => match logging::parse_log_level(name) {
+            Some(level) => Some(level),
+            None => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Unknown log level '{}' (expected one of: {})", name, LOG_LEVELS.join(", "))
+                }));
+            },
+        },
+        None => None,
+    };
+    
+    if let Some(level) = level {
+        logging::set_log_level(level);
+        info!("Log level set to {} via the API", level_name(level));
+    }
+    if let Some(verbose) = request.verbose {
+        logging::store_verbose_mode(verbose);
+        info!("Verbose logging {} via the API", if verbose { "enabled" } else { "disabled" });
+    }
+    
+    HttpResponse::Ok().json(LogLevelResponse::current())
+}
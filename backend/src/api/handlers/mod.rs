@@ -1,3 +1,9 @@
 pub mod capture;
 pub mod packets;
-pub mod filters; 
\ No newline at end of file
+pub mod filters;
+pub mod alerts;
+pub mod stats;
+pub mod streams;
+pub mod protocols;
+pub mod health;
+pub mod logging; 
\ No newline at end of file
@@ -1,12 +1,17 @@
 use actix_web::{web, HttpResponse, Responder};
-use log::{info, error};
+use log::{info, error, warn};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::time::Duration;
 
-use crate::capture::manager::CaptureManager;
-use crate::models::packet::PacketSummary;
+use crate::capture::display_filter::DisplayFilter;
+use crate::capture::export::{self, ExportFormat, SectionInfo};
+use crate::capture::host_info;
+use crate::capture::manager::{CaptureManager, PacketCriteria};
+use crate::models::packet::{AddressScope, Packet, PacketDirection, PacketSummary, TcpAnalysis};
+use crate::models::stats::GroupBy;
+use crate::utils::hexdump::{self, HexDumpOptions};
 
 /// Query parameters for listing packets
 #[derive(Deserialize)]
@@ -18,6 +23,25 @@ pub struct PacketsQuery {
     /// Limit for pagination
     #[serde(default = "default_limit")]
     limit: usize,
+    
+    /// Comma-separated list of summary fields to return (all fields when absent)
+    fields: Option<String>,
+    
+    /// Only return packets loaded from the capture file with this source label
+    source: Option<String>,
+    
+    /// Only return packets of this address scope (unicast, multicast, broadcast, link_local)
+    scope: Option<String>,
+    
+    /// Only return packets in this direction relative to the capture host (in, out, local, transit)
+    direction: Option<String>,
+    
+    /// Only return TCP segments with this analysis flag (retransmission, keep_alive, window_update)
+    tcp_analysis: Option<String>,
+    
+    /// Leave out packets marked as duplicates
+    #[serde(default)]
+    exclude_duplicates: bool,
 }
 
 fn default_offset() -> usize { 0 }
@@ -47,13 +71,87 @@ pub struct FilterQuery {
     limit: usize,
 }
 
+/// Query parameters for searching decoded fields
+#[derive(Deserialize)]
+pub struct SearchFieldQuery {
+    /// Value to look for in the packets' headers and metadata
+    q: String,
+    
+    /// Offset for pagination
+    #[serde(default = "default_offset")]
+    offset: usize,
+    
+    /// Limit for pagination
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+/// Query parameters for deleting stored packets
+#[derive(Deserialize)]
+pub struct DeletePacketsQuery {
+    /// Display filter selecting the packets to delete
+    display_filter: Option<String>,
+    
+    /// Required to delete every packet, when no filter is given
+    #[serde(default)]
+    confirm: bool,
+}
+
 /// Response for listing packets
 #[derive(Serialize)]
-struct PacketsResponse {
-    packets: Vec<PacketSummary>,
+struct PacketsResponse<T = PacketSummary> {
+    packets: Vec<T>,
     total: usize,
     offset: usize,
     limit: usize,
+    
+    /// Whether packets remain past this page
+    has_more: bool,
+    
+    /// Offset of the next page (null on the last page)
+    next_offset: Option<usize>,
+}
+
+impl<T> PacketsResponse<T> {
+    /// Build a page of a list of `total` packets, with its navigation fields
+    fn page(packets: Vec<T>, total: usize, offset: usize, limit: usize) -> Self {
+        let end = offset.saturating_add(limit);
+        let has_more = limit > 0 && end < total;
+        
+        Self {
+            packets,
+            total,
+            offset,
+            limit,
+            has_more,
+            next_offset: has_more.then_some(end),
+        }
+    }
+    
+    /// Convert the packets of the page, keeping its navigation fields
+    fn map<U>(self, f: impl FnMut(&T) -> U) -> PacketsResponse<U> {
+        PacketsResponse {
+            packets: self.packets.iter().map(f).collect(),
+            total: self.total,
+            offset: self.offset,
+            limit: self.limit,
+            has_more: self.has_more,
+            next_offset: self.next_offset,
+        }
+    }
+}
+
+/// Response for a single packet, with its capture-relative times
+#[derive(Serialize)]
+struct PacketDetail {
+    #[serde(flatten)]
+    packet: Packet,
+    
+    /// Seconds since the capture session started
+    relative_time: Option<f64>,
+    
+    /// Seconds since the previous packet (by ID)
+    delta_time: Option<f64>,
 }
 
 /// Get list of packets
@@ -61,12 +159,58 @@ pub async fn get_packets(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
     query: web::Query<PacketsQuery>,
 ) -> impl Responder {
+    // Validate the requested field projection before touching the store
+    let fields = match parse_fields(query.fields.as_deref()) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    let scope = match query.scope.as_deref().map(str::parse::<AddressScope>).transpose() {
+        Ok(scope) => scope,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": message
+            }));
+        }
+    };
+    let direction = match query.direction.as_deref().map(str::parse::<PacketDirection>).transpose() {
+        Ok(direction) => direction,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": message
+            }));
+        }
+    };
+    let analysis = match query.tcp_analysis.as_deref().map(str::parse::<TcpAnalysis>).transpose() {
+        Ok(analysis) => analysis,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": message
+            }));
+        }
+    };
+    
     // Create a future for packet retrieval
     let packets_future = async {
         let capture_manager = capture_manager.read().await;
         
-        let packets = capture_manager.get_packets(query.offset, query.limit);
-        let total_count = capture_manager.get_packet_count();
+        let criteria = PacketCriteria {
+            source: query.source.as_deref(),
+            scope,
+            direction,
+            analysis,
+            exclude_duplicates: query.exclude_duplicates,
+        };
+        let (packets, total_count) = if criteria.is_empty() {
+            (
+                capture_manager.get_packets(query.offset, query.limit),
+                capture_manager.get_packet_count(),
+            )
+        } else {
+            capture_manager.get_packets_matching(&criteria, query.offset, query.limit)
+        };
         
         // Log information about packet retrieval
         info!("Retrieved {} packets (offset: {}, limit: {}, total: {})",
@@ -77,18 +221,14 @@ pub async fn get_packets(
                  if capture_manager.get_status() { "running" } else { "stopped" });
         }
         
-        PacketsResponse {
-            packets,
-            total: total_count,
-            offset: query.offset,
-            limit: query.limit,
-        }
+        PacketsResponse::page(packets, total_count, query.offset, query.limit)
     };
     
     // Execute with timeout to prevent hanging
     match tokio::time::timeout(Duration::from_secs(3), packets_future).await {
-        Ok(response) => {
-            HttpResponse::Ok().json(response)
+        Ok(response) => match fields {
+            Some(fields) => HttpResponse::Ok().json(response.map(|p| p.project(&fields))),
+            None => HttpResponse::Ok().json(response),
         },
         Err(_) => {
             // Timeout occurred
@@ -100,6 +240,36 @@ pub async fn get_packets(
     }
 }
 
+/// Parse a `fields` query value into a list of known summary field names
+fn parse_fields(fields: Option<&str>) -> Result<Option<Vec<String>>, HttpResponse> {
+    let fields = match fields {
+        Some(fields) => fields,
+        None => return Ok(None),
+    };
+    
+    let requested: Vec<String> = fields
+        .split(',')
+        .map(|f| f.trim())
+        .filter(|f| !f.is_empty())
+        .map(|f| f.to_string())
+        .collect();
+    
+    let unknown: Vec<&String> = requested
+        .iter()
+        .filter(|f| !PacketSummary::FIELDS.contains(&f.as_str()))
+        .collect();
+    
+    if !unknown.is_empty() {
+        return Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Unknown field(s): {}", unknown.iter().map(|f| f.as_str()).collect::<Vec<_>>().join(", ")),
+            "available_fields": PacketSummary::FIELDS,
+        })));
+    }
+    
+    Ok(Some(requested))
+}
+
 /// Get a specific packet by ID
 pub async fn get_packet(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
@@ -110,14 +280,31 @@ pub async fn get_packet(
     // Create a future for packet retrieval
     let packet_future = async {
         let capture_manager = capture_manager.read().await;
-        capture_manager.get_packet(id)
+        if let Some(json) = capture_manager.cached_packet_detail(id) {
+            return Some(json);
+        }
+        
+        let cache = capture_manager.detail_cache();
+        let generation = cache.generation();
+        let limits = capture_manager.get_json_limits();
+        
+        // Guard clients against pathologically deep or large decoded headers
+        let mut packet = capture_manager.get_packet(id)?;
+        if limits.apply(&mut packet.headers) {
+            warn!("Truncated headers of packet {} to depth {} / {} bytes", id, limits.max_depth, limits.max_bytes);
+        }
+        let (relative_time, delta_time) = capture_manager.packet_times(&packet);
+        
+        let json: Arc<str> = serde_json::to_string(&PacketDetail { packet, relative_time, delta_time }).ok()?.into();
+        cache.insert(id, json.clone(), generation);
+        Some(json)
     };
     
     // Execute with timeout to prevent hanging
     match tokio::time::timeout(Duration::from_secs(2), packet_future).await {
         Ok(maybe_packet) => {
             match maybe_packet {
-                Some(packet) => HttpResponse::Ok().json(packet),
+                Some(json) => HttpResponse::Ok().content_type("application/json").body(json.to_string()),
                 None => {
                     HttpResponse::NotFound().json(serde_json::json!({
                         "status": "error",
@@ -136,14 +323,187 @@ pub async fn get_packet(
     }
 }
 
+/// Query parameters for a packet's hex dump
+#[derive(Deserialize)]
+pub struct HexDumpQuery {
+    /// Bytes per line: 8, 16 (default) or 32
+    width: Option<usize>,
+    
+    /// Whether to include the ASCII gutter (default true)
+    ascii: Option<bool>,
+}
+
+/// Get a packet's bytes as a plain-text hex dump
+pub async fn get_packet_hexdump(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<u64>,
+    query: web::Query<HexDumpQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    
+    let mut options = HexDumpOptions::default();
+    if let Some(width) = query.width {
+        if !hexdump::WIDTHS.contains(&width) {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Unsupported width {}, expected 8, 16 or 32", width)
+            }));
+        }
+        options.width = width;
+    }
+    options.ascii = query.ascii.unwrap_or(options.ascii);
+    
+    let dump_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_packet(id).map(|packet| hexdump::format(&packet.raw_data, options))
+    };
+    
+    match tokio::time::timeout(Duration::from_secs(2), dump_future).await {
+        Ok(Some(dump)) => HttpResponse::Ok().content_type("text/plain; charset=utf-8").body(dump),
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Packet with ID {} not found", id)
+            }))
+        },
+        Err(_) => {
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Timeout while retrieving packet with ID {}", id)
+            }))
+        }
+    }
+}
+
+/// Request body for exporting selected packets
+#[derive(Deserialize)]
+pub struct ExportRequest {
+    /// IDs of the packets to export
+    ids: Vec<u64>,
+    
+    /// Output format (pcap when absent)
+    #[serde(default)]
+    format: ExportFormat,
+    
+    /// Cut pcap/pcapng packets after their transport header, keeping the original length
+    #[serde(default)]
+    strip_payload: bool,
+}
+
+/// Export exactly the selected packets as a pcap, pcapng, CSV or JSON lines file.
+///
+/// IDs that are no longer stored are skipped and listed in the
+/// `X-Missing-Packet-Ids` response header.
+pub async fn export_packets(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    request: web::Json<ExportRequest>,
+) -> impl Responder {
+    let request = request.into_inner();
+    if request.ids.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "No packet IDs given"
+        }));
+    }
+    
+    let export_future = async {
+        let capture_manager = capture_manager.read().await;
+        let (packets, missing) = capture_manager.get_packets_by_ids(&request.ids);
+        let body = export_body(&capture_manager, &packets, request.format, request.strip_payload);
+        (body, packets.len(), missing)
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(10), export_future).await {
+        Ok((_, 0, _)) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": "None of the requested packets are stored"
+            }))
+        },
+        Ok((body, exported, missing)) => {
+            info!("Exported {} packets as {}", exported, request.format.extension());
+            
+            let mut response = HttpResponse::Ok();
+            response
+                .content_type(request.format.content_type())
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"rustshark-export.{}\"", request.format.extension()),
+                ));
+            
+            if !missing.is_empty() {
+                let missing: Vec<String> = missing.iter().map(|id| id.to_string()).collect();
+                warn!("Export skipped {} packets that are not stored: {}", missing.len(), missing.join(","));
+                response.insert_header(("X-Missing-Packet-Ids", missing.join(",")));
+            }
+            
+            response.body(body)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while exporting packets"
+            }))
+        }
+    }
+}
+
+/// Write packets in an export format; pcapng carries the capture description,
+/// the capturing host and the interface
+pub fn export_body(capture_manager: &CaptureManager, packets: &[Packet], format: ExportFormat, strip_payload: bool) -> Vec<u8> {
+    match format {
+        ExportFormat::Pcap => export::write_pcap(packets, strip_payload),
+        ExportFormat::Pcapng => {
+            let description = capture_manager.get_description();
+            let comment = (!description.is_empty()).then(|| description.to_comment());
+            let host = host_info::get();
+            let show_host = !capture_manager.hides_host_info();
+            let interface = capture_manager.capture_interface();
+            let section = SectionInfo {
+                comment: comment.as_deref(),
+                hardware: host.hardware.as_deref().filter(|_| show_host),
+                os: host.os.as_deref().filter(|_| show_host),
+                application: Some(&host.application),
+                interface_name: interface.as_ref().map(|(name, _)| name.as_str()),
+                interface_description: interface.as_ref().and_then(|(_, alias)| alias.as_deref()),
+            };
+            export::write_pcapng(packets, &section, strip_payload)
+        },
+        ExportFormat::Jsonl => export::write_jsonl(packets),
+        ExportFormat::Csv => {
+            let summaries: Vec<PacketSummary> = packets.iter().map(|packet| {
+                let mut summary = CaptureManager::summarize(packet);
+                (summary.relative_time, summary.delta_time) = capture_manager.packet_times(packet);
+                summary
+            }).collect();
+            export::write_csv(&summaries)
+        },
+    }
+}
+
+/// Query parameters for packet statistics
+#[derive(Deserialize)]
+pub struct PacketStatsQuery {
+    /// Key sources and destinations by resolved hostname (`host`) or IP (default)
+    #[serde(default)]
+    group_by: GroupBy,
+}
+
 /// Get packet statistics
 pub async fn get_packet_stats(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<PacketStatsQuery>,
 ) -> impl Responder {
     // Create a future for stats retrieval
     let stats_future = async {
         let capture_manager = capture_manager.read().await;
-        capture_manager.get_stats()
+        let stats = capture_manager.get_stats();
+        match query.group_by {
+            GroupBy::Ip => stats,
+            GroupBy::Host => stats.grouped_by_host(),
+        }
     };
     
     // Execute with timeout to prevent hanging
@@ -161,6 +521,87 @@ pub async fn get_packet_stats(
     }
 }
 
+/// Find packets with any decoded header or metadata field matching a value
+pub async fn search_fields(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<SearchFieldQuery>,
+) -> impl Responder {
+    let term = query.q.trim();
+    if term.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "Search term 'q' must not be empty"
+        }));
+    }
+    
+    let search_future = async {
+        let capture_manager = capture_manager.read().await;
+        let (packets, total) = capture_manager.search_fields(term, query.offset, query.limit);
+        PacketsResponse::page(packets, total, query.offset, query.limit)
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(3), search_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while searching packet fields"
+            }))
+        }
+    }
+}
+
+/// Delete the stored packets matching a display filter, taking them out of the stats
+pub async fn delete_packets(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<DeletePacketsQuery>,
+) -> impl Responder {
+    // An empty filter would match everything, so that needs saying explicitly
+    let expression = query.display_filter.as_deref().map(str::trim).filter(|filter| !filter.is_empty());
+    let filter = match expression.map(DisplayFilter::parse).transpose() {
+        Ok(None) if !query.confirm => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": "Deleting every packet needs confirm=true; pass a display_filter to delete only matching packets"
+            }));
+        },
+        Ok(filter) => filter,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Invalid display filter: {}", e)
+            }));
+        }
+    };
+    
+    let delete_future = async {
+        let mut capture_manager = capture_manager.write().await;
+        let removed = capture_manager.delete_packets(filter.as_ref()).await;
+        (removed, capture_manager.get_packet_count())
+    };
+    
+    match tokio::time::timeout(Duration::from_secs(10), delete_future).await {
+        Ok((removed, remaining)) => {
+            info!("Deleted {} packets matching '{}', {} remain", removed, expression.unwrap_or(""), remaining);
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "removed": removed,
+                "remaining": remaining
+            }))
+        },
+        Err(_) => {
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while deleting packets"
+            }))
+        }
+    }
+}
+
 /// Filter packets
 pub async fn filter_packets(
     capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
@@ -174,16 +615,10 @@ pub async fn filter_packets(
         // For now, we just return all packets from the specified range
         let packets = capture_manager.get_packets(query.offset, query.limit);
         
-        // In a real implementation, we would get the actual total count
-        // For now, we'll just return the number of packets we're sending
-        let total = packets.len();
+        // The pages cover every stored packet, so that is the total
+        let total = capture_manager.get_packet_count();
         
-        PacketsResponse {
-            packets,
-            total,
-            offset: query.offset,
-            limit: query.limit,
-        }
+        PacketsResponse::page(packets, total, query.offset, query.limit)
     };
     
     // Execute with timeout to prevent hanging
@@ -0,0 +1,64 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use std::time::Duration;
+
+use crate::capture::manager::CaptureManager;
+use crate::capture::protocols::registry::{ProtocolInfo, PROTOCOLS};
+
+/// A decodable protocol and whether its decoder is in use
+#[derive(Serialize)]
+struct ProtocolEntry {
+    #[serde(flatten)]
+    info: ProtocolInfo,
+    
+    /// Whether it can be turned off with --disable-protocols
+    optional: bool,
+    
+    /// False when turned off with --disable-protocols
+    enabled: bool,
+}
+
+/// Response for listing protocols
+#[derive(Serialize)]
+struct ProtocolsResponse {
+    protocols: Vec<ProtocolEntry>,
+    total: usize,
+}
+
+/// List the protocols the parser decodes, with the header fields each populates
+pub async fn get_protocols(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let protocols_future = async {
+        let capture_manager = capture_manager.read().await;
+        let protocols: Vec<ProtocolEntry> = PROTOCOLS
+            .iter()
+            .map(|info| ProtocolEntry {
+                info: *info,
+                optional: info.optional(),
+                enabled: !info.optional() || capture_manager.decoder_enabled(info.name),
+            })
+            .collect();
+        
+        ProtocolsResponse {
+            total: protocols.len(),
+            protocols,
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), protocols_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while listing protocols"
+            }))
+        }
+    }
+}
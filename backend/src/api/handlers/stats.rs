@@ -0,0 +1,428 @@
+use actix_web::{web, HttpResponse, Responder};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use std::time::Duration;
+
+use crate::api::handlers::packets::export_body;
+use crate::capture::conversations;
+use crate::capture::export::{self, ExportFormat};
+use crate::capture::manager::CaptureManager;
+use crate::models::conversation::{Conversation, ConversationSort};
+use crate::models::latency::LatencyProtocol;
+use crate::models::neighbor::Neighbor;
+use crate::models::rtp::RtpStream;
+use crate::models::stats::GroupBy;
+use crate::models::wifi::AccessPoint;
+
+/// Response for listing discovered neighbors
+#[derive(Serialize)]
+struct NeighborsResponse {
+    neighbors: Vec<Neighbor>,
+    total: usize,
+}
+
+/// Get the network devices discovered from LLDP and CDP announcements
+pub async fn get_neighbors(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let neighbors_future = async {
+        let capture_manager = capture_manager.read().await;
+        let neighbors = capture_manager.get_neighbors();
+        
+        NeighborsResponse {
+            total: neighbors.len(),
+            neighbors,
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), neighbors_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving neighbors"
+            }))
+        }
+    }
+}
+
+/// Response for listing Wi-Fi access points
+#[derive(Serialize)]
+struct AccessPointsResponse {
+    access_points: Vec<AccessPoint>,
+    total: usize,
+}
+
+/// Get the Wi-Fi access points seen in beacons and probe responses
+pub async fn get_wifi(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let wifi_future = async {
+        let capture_manager = capture_manager.read().await;
+        let access_points = capture_manager.get_access_points();
+        
+        AccessPointsResponse {
+            total: access_points.len(),
+            access_points,
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), wifi_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving access points"
+            }))
+        }
+    }
+}
+
+/// Get oversized packet counts and the path MTU hint for each destination
+pub async fn get_mtu(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let mtu_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_mtu_report()
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), mtu_future).await {
+        Ok(report) => {
+            HttpResponse::Ok().json(report)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving MTU statistics"
+            }))
+        }
+    }
+}
+
+/// Query parameters for response time statistics
+#[derive(Deserialize)]
+pub struct LatencyQuery {
+    /// Protocol to report on: dns or http
+    protocol: Option<String>,
+}
+
+/// Get min/avg/max/p95 response times of DNS or HTTP, with unanswered queries counted as timeouts
+pub async fn get_latency(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<LatencyQuery>,
+) -> impl Responder {
+    let protocol = match query.protocol.as_deref().unwrap_or("dns").parse::<LatencyProtocol>() {
+        Ok(protocol) => protocol,
+        Err(message) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "message": message
+            }));
+        }
+    };
+    
+    let latency_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_latency_report(protocol)
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), latency_future).await {
+        Ok(report) => {
+            HttpResponse::Ok().json(report)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving latency statistics"
+            }))
+        }
+    }
+}
+
+/// Response for listing RTP streams
+#[derive(Serialize)]
+struct RtpStreamsResponse {
+    streams: Vec<RtpStream>,
+    total: usize,
+}
+
+/// Get the RTP streams with their codec, packet loss and jitter
+pub async fn get_rtp(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+) -> impl Responder {
+    let rtp_future = async {
+        let capture_manager = capture_manager.read().await;
+        let streams = capture_manager.get_rtp_streams();
+        
+        RtpStreamsResponse {
+            total: streams.len(),
+            streams,
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), rtp_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving RTP streams"
+            }))
+        }
+    }
+}
+
+/// Query parameters for the protocol distribution chart
+#[derive(Deserialize)]
+pub struct ProtocolChartQuery {
+    /// Number of protocols shown as their own slice
+    #[serde(default = "default_chart_top")]
+    top: usize,
+    
+    /// Protocols below this share (in percent) go into "Other" as well
+    #[serde(default)]
+    min_percent: f64,
+}
+
+fn default_chart_top() -> usize {
+    8
+}
+
+/// Get the protocol distribution sorted, with percentages and an "Other" slice
+pub async fn get_protocol_chart(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<ProtocolChartQuery>,
+) -> impl Responder {
+    if query.top == 0 {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "top must be at least 1"
+        }));
+    }
+    
+    let chart_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager.get_stats().protocol_chart(query.top, query.min_percent)
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), chart_future).await {
+        Ok(chart) => {
+            HttpResponse::Ok().json(chart)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while building the protocol chart"
+            }))
+        }
+    }
+}
+
+/// Query parameters for listing conversations
+#[derive(Deserialize)]
+pub struct ConversationsQuery {
+    /// Only conversations that carried this transport or application protocol
+    protocol: Option<String>,
+    
+    /// Maximum number of conversations returned
+    #[serde(default = "default_conversations_limit")]
+    limit: usize,
+    
+    /// Include the recent bytes per second of the busiest conversations
+    #[serde(default)]
+    sparkline: bool,
+    
+    /// Combine the conversations between the same resolved hosts (`host`)
+    #[serde(default)]
+    group_by: GroupBy,
+}
+
+fn default_conversations_limit() -> usize {
+    100
+}
+
+/// Response for listing conversations
+#[derive(Serialize)]
+struct ConversationsResponse {
+    conversations: Vec<Conversation>,
+    total: usize,
+}
+
+/// Get the conversations with their application protocols, most bytes first
+pub async fn get_conversations(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<ConversationsQuery>,
+) -> impl Responder {
+    let conversations_future = async {
+        let capture_manager = capture_manager.read().await;
+        let mut conversations = capture_manager.get_conversations(query.protocol.as_deref(), query.sparkline);
+        if query.group_by == GroupBy::Host {
+            conversations = conversations::group_by_host(conversations, &capture_manager.get_stats().hostnames);
+        }
+        let total = conversations.len();
+        conversations.truncate(query.limit);
+        
+        ConversationsResponse {
+            conversations,
+            total,
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(2), conversations_future).await {
+        Ok(response) => {
+            HttpResponse::Ok().json(response)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while retrieving conversations"
+            }))
+        }
+    }
+}
+
+/// Query parameters for exporting the conversation table
+#[derive(Deserialize)]
+pub struct ConversationsExportQuery {
+    /// Output format: csv (the default) or jsonl
+    format: Option<ExportFormat>,
+    
+    /// Only conversations that carried this transport or application protocol
+    protocol: Option<String>,
+    
+    /// Combine the conversations between the same resolved hosts (`host`)
+    #[serde(default)]
+    group_by: GroupBy,
+    
+    /// Row order (most bytes first by default)
+    #[serde(default)]
+    sort: ConversationSort,
+}
+
+/// Export the conversation table for spreadsheets, one row per conversation
+pub async fn export_conversations(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    query: web::Query<ConversationsExportQuery>,
+) -> impl Responder {
+    let format = query.format.unwrap_or(ExportFormat::Csv);
+    if !matches!(format, ExportFormat::Csv | ExportFormat::Jsonl) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": "Conversations are exported as csv or jsonl"
+        }));
+    }
+    
+    let export_future = async {
+        let capture_manager = capture_manager.read().await;
+        let mut conversations = capture_manager.get_conversations(query.protocol.as_deref(), false);
+        if query.group_by == GroupBy::Host {
+            conversations = conversations::group_by_host(conversations, &capture_manager.get_stats().hostnames);
+        }
+        drop(capture_manager);
+        
+        query.sort.sort(&mut conversations);
+        let body = match format {
+            ExportFormat::Jsonl => export::write_jsonl(&conversations),
+            _ => export::write_conversations_csv(&conversations),
+        };
+        (body, conversations.len())
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(10), export_future).await {
+        Ok((body, exported)) => {
+            info!("Exported {} conversations as {}", exported, format.extension());
+            HttpResponse::Ok()
+                .content_type(format.content_type())
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"rustshark-conversations.{}\"", format.extension()),
+                ))
+                .body(body)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while exporting conversations"
+            }))
+        }
+    }
+}
+
+/// Query parameters for exporting a conversation
+#[derive(Deserialize)]
+pub struct ConversationExportQuery {
+    /// Output format (pcap when absent)
+    #[serde(default)]
+    format: ExportFormat,
+    
+    /// Cut pcap/pcapng packets after their transport header, keeping the original length
+    #[serde(default)]
+    strip_payload: bool,
+}
+
+/// Export the stored packets of one conversation (both directions), by its ID
+pub async fn export_conversation(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<String>,
+    query: web::Query<ConversationExportQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    
+    let export_future = async {
+        let capture_manager = capture_manager.read().await;
+        capture_manager
+            .get_conversation_packets(&id)
+            .map(|packets| (export_body(&capture_manager, &packets, query.format, query.strip_payload), packets.len()))
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(10), export_future).await {
+        Ok(None) => {
+            HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "message": format!("Conversation {} not found", id)
+            }))
+        },
+        Ok(Some((body, exported))) => {
+            info!("Exported {} packets of conversation {} as {}", exported, id, query.format.extension());
+            HttpResponse::Ok()
+                .content_type(query.format.content_type())
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"rustshark-conversation-{}.{}\"", id, query.format.extension()),
+                ))
+                .body(body)
+        },
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while exporting conversation"
+            }))
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use std::time::Duration;
+
+use crate::capture::carving::{self, CarvedStream};
+use crate::capture::manager::CaptureManager;
+
+/// Query parameters for stream timing
+#[derive(Deserialize)]
+pub struct TimingQuery {
+    /// Maximum number of inter-packet gaps listed (statistics cover all of them)
+    #[serde(default = "default_gaps_limit")]
+    limit: usize,
+}
+
+fn default_gaps_limit() -> usize {
+    1000
+}
+
+/// Get inter-packet gaps, their mean/stddev and a jitter estimate for the
+/// stream (flow direction) of the given packet
+pub async fn get_stream_timing(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<u64>,
+    query: web::Query<TimingQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    
+    let timing_future = async {
+        let capture_manager = capture_manager.read().await;
+        match capture_manager.get_stream_timing(id, query.limit) {
+            Some(timing) => HttpResponse::Ok().json(timing),
+            None if capture_manager.get_packet(id).is_none() => {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Packet with ID {} not found", id)
+                }))
+            },
+            None => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Packet {} has no IP addresses to identify a stream by", id)
+                }))
+            },
+        }
+    };
+    
+    // Execute with timeout to prevent hanging
+    match tokio::time::timeout(Duration::from_secs(3), timing_future).await {
+        Ok(response) => response,
+        Err(_) => {
+            // Timeout occurred
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while measuring stream timing"
+            }))
+        }
+    }
+}
+
+/// Query parameters for bytes in flight
+#[derive(Deserialize)]
+pub struct InflightQuery {
+    /// Maximum number of samples listed (statistics cover all of them)
+    #[serde(default = "default_samples_limit")]
+    limit: usize,
+}
+
+fn default_samples_limit() -> usize {
+    1000
+}
+
+/// Get the bytes in flight over time from the sender of the given packet,
+/// over its TCP connection
+pub async fn get_stream_inflight(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<u64>,
+    query: web::Query<InflightQuery>,
+) -> impl Responder {
+    let id = path.into_inner();
+    
+    let inflight_future = async {
+        let capture_manager = capture_manager.read().await;
+        match capture_manager.get_stream_inflight(id, query.limit) {
+            Some(inflight) => HttpResponse::Ok().json(inflight),
+            None if capture_manager.get_packet(id).is_none() => {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Packet with ID {} not found", id)
+                }))
+            },
+            None => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Packet {} is not part of a TCP stream", id)
+                }))
+            },
+        }
+    };
+    
+    match tokio::time::timeout(Duration::from_secs(3), inflight_future).await {
+        Ok(response) => response,
+        Err(_) => {
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while estimating bytes in flight"
+            }))
+        }
+    }
+}
+
+/// List the files transferred over the TCP connection of the given packet,
+/// carved out of its reassembled stream
+pub async fn get_stream_objects(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<u64>,
+) -> impl Responder {
+    let id = path.into_inner();
+    
+    let objects_future = async {
+        match carve_stream(&capture_manager, id).await {
+            Ok(carved) => HttpResponse::Ok().json(carved.objects),
+            Err(response) => response,
+        }
+    };
+    
+    match tokio::time::timeout(Duration::from_secs(10), objects_future).await {
+        Ok(response) => response,
+        Err(_) => {
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while carving objects"
+            }))
+        }
+    }
+}
+
+/// Download one file carved out of the TCP connection of the given packet,
+/// by its index in the listing
+pub async fn get_stream_object(
+    capture_manager: web::Data<Arc<RwLock<CaptureManager>>>,
+    path: web::Path<(u64, usize)>,
+) -> impl Responder {
+    let (id, index) = path.into_inner();
+    
+    let object_future = async {
+        let carved = match carve_stream(&capture_manager, id).await {
+            Ok(carved) => carved,
+            Err(response) => return response,
+        };
+        // Only the object asked for has its content coding undone
+        match web::block(move || carved.into_object(index)).await {
+            Ok(Some((object, data))) => {
+                HttpResponse::Ok()
+                    .content_type(object.content_type.as_deref().unwrap_or("application/octet-stream"))
+                    .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", object.filename)))
+                    .body(data)
+            },
+            Ok(None) => {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("No object {} was carved from the stream of packet {}", index, id)
+                }))
+            },
+            Err(e) => carving_failed(e),
+        }
+    };
+    
+    match tokio::time::timeout(Duration::from_secs(10), object_future).await {
+        Ok(response) => response,
+        Err(_) => {
+            HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "status": "error",
+                "message": "Timeout while carving objects"
+            }))
+        }
+    }
+}
+
+/// Carve the objects out of the TCP connection of a packet on the blocking
+/// pool, from a copy of its segments taken under a brief read lock
+async fn carve_stream(capture_manager: &Arc<RwLock<CaptureManager>>, id: u64) -> Result<CarvedStream, HttpResponse> {
+    let pieces = capture_manager.read().await.get_stream_pieces(id);
+    let Some((packet, pieces)) = pieces else {
+        return Err(HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Packet with ID {} not found", id)
+        })));
+    };
+    
+    match web::block(move || carving::carve(&packet, pieces)).await {
+        Ok(Some(carved)) => Ok(carved),
+        Ok(None) => Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "message": format!("Packet {} is not part of a TCP stream", id)
+        }))),
+        Err(e) => Err(carving_failed(e)),
+    }
+}
+
+fn carving_failed(e: impl std::fmt::Display) -> HttpResponse {
+    HttpResponse::InternalServerError().json(serde_json::json!({
+        "status": "error",
+        "message": format!("Carving objects failed: {}", e)
+    }))
+}
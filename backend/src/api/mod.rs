@@ -1,3 +1,6 @@
 pub mod routes;
 pub mod handlers;
-pub mod websocket; 
\ No newline at end of file
+pub mod cors;
+pub mod openapi;
+pub mod websocket;
+pub mod ws_deflate;
\ No newline at end of file
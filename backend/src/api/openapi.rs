@@ -0,0 +1,542 @@
+// OpenAPI 3.0 description of the API
+//
+// The spec served at /api/openapi.json is built from the same route table the
+// index lists, so a route added there is described too. Query parameters,
+// request bodies and the shared schemas are read off the structs the handlers
+// deserialize: their `Deserialize` impls are run against a probe that answers
+// every value it is asked for with the JSON schema of that value instead of
+// data. Field names (after renames), types, enum variants and which fields may
+// be left out thus come from the same derive the handlers use. Nested structs
+// become references to component schemas, which must be listed here. Response
+// bodies are built from `Serialize`-only structs and are named by hand.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+use crate::capture::file::FileSource;
+use crate::models::filter::ColorRule;
+use crate::models::interface::InterfaceInfo;
+use crate::models::packet::PacketSummary;
+use crate::models::stats::{CaptureStats, ScopeCounts};
+
+/// Produces the schema of one type
+pub type SchemaFn = fn() -> Value;
+
+/// Component schemas, by the struct names the probe refers to them with
+const COMPONENTS: &[(&str, SchemaFn)] = &[
+    ("PacketSummary", schema::<PacketSummary>),
+    ("CaptureStats", schema::<CaptureStats>),
+    ("ScopeCounts", schema::<ScopeCounts>),
+    ("InterfaceInfo", schema::<InterfaceInfo>),
+    ("FileSource", schema::<FileSource>),
+    ("ColorRule", schema::<ColorRule>),
+];
+
+/// What the successful JSON response of a route carries
+#[derive(Debug, Clone, Copy)]
+pub enum Returns {
+    /// A component schema
+    Schema(&'static str),
+    
+    /// An object listing a component schema in the given property
+    ListIn(&'static str, &'static str),
+}
+
+/// A route, as listed by the index and described by the spec
+pub struct Endpoint {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub description: &'static str,
+    
+    /// Type of the `{...}` path parameters (strings when unset)
+    path_param: Option<SchemaFn>,
+    query: Option<SchemaFn>,
+    body: Option<SchemaFn>,
+    returns: Option<Returns>,
+}
+
+impl Endpoint {
+    pub const fn new(method: &'static str, path: &'static str, description: &'static str) -> Self {
+        Self {
+            method,
+            path,
+            description,
+            path_param: None,
+            query: None,
+            body: None,
+            returns: None,
+        }
+    }
+    
+    pub const fn path_param(mut self, schema: SchemaFn) -> Self {
+        self.path_param = Some(schema);
+        self
+    }
+    
+    pub const fn query(mut self, schema: SchemaFn) -> Self {
+        self.query = Some(schema);
+        self
+    }
+    
+    pub const fn body(mut self, schema: SchemaFn) -> Self {
+        self.body = Some(schema);
+        self
+    }
+    
+    pub const fn returns(mut self, returns: Returns) -> Self {
+        self.returns = Some(returns);
+        self
+    }
+    
+    /// The operation object of this route
+    fn operation(&self) -> Value {
+        let mut parameters = Vec::new();
+        for segment in self.path.split('/') {
+            if let Some(name) = segment.strip_prefix('{').and_then(|segment| segment.strip_suffix('}')) {
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": self.path_param.map_or_else(|| json!({ "type": "string" }), |schema| schema()),
+                }));
+            }
+        }
+        if let Some(query) = self.query.map(|schema| schema()) {
+            let required = query["required"].as_array().cloned().unwrap_or_default();
+            if let Some(properties) = query["properties"].as_object() {
+                for (name, schema) in properties {
+                    parameters.push(json!({
+                        "name": name,
+                        "in": "query",
+                        "required": required.contains(&json!(name)),
+                        "schema": schema,
+                    }));
+                }
+            }
+        }
+        
+        let response = match self.returns {
+            Some(Returns::Schema(name)) => reference(name),
+            Some(Returns::ListIn(property, name)) => json!({
+                "type": "object",
+                "properties": { property: { "type": "array", "items": reference(name) } },
+            }),
+            None => json!({}),
+        };
+        
+        let mut operation = json!({
+            "operationId": self.operation_id(),
+            "summary": self.description,
+            "responses": {
+                "200": {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": response } },
+                },
+            },
+        });
+        if !parameters.is_empty() {
+            operation["parameters"] = json!(parameters);
+        }
+        if let Some(body) = self.body {
+            operation["requestBody"] = json!({
+                "content": { "application/json": { "schema": body() } },
+            });
+        }
+        operation
+    }
+    
+    /// `get_api_packets_id_hexdump` for `GET /api/packets/{id}/hexdump`
+    fn operation_id(&self) -> String {
+        let mut id = self.method.to_ascii_lowercase();
+        for word in self.path.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+            id.push('_');
+            id.push_str(word);
+        }
+        id
+    }
+}
+
+/// The OpenAPI document describing the given routes
+pub fn spec(endpoints: &[Endpoint]) -> Value {
+    let mut paths = Map::new();
+    for endpoint in endpoints {
+        let item = paths.entry(endpoint.path).or_insert_with(|| json!({}));
+        item[endpoint.method.to_ascii_lowercase()] = endpoint.operation();
+    }
+    
+    let schemas: Map<String, Value> = COMPONENTS
+        .iter()
+        .map(|(name, schema)| (name.to_string(), schema()))
+        .collect();
+    
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "RustShark API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "A Wireshark-like network packet analyzer with REST API",
+        },
+        "paths": paths,
+        "components": { "schemas": schemas },
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// The JSON schema of a deserializable type: an object schema for a struct
+pub fn schema<T: DeserializeOwned>() -> Value {
+    let fields = match T::deserialize(Probe::Fields) {
+        Err(Probed::Fields(fields)) => fields,
+        Err(Probed::Shape(shape)) => return shape,
+        _ => return json!({}),
+    };
+    
+    let mut properties = Map::new();
+    for &field in fields {
+        let shape = match T::deserialize(Probe::Field(field)) {
+            Err(Probed::Shape(shape)) => shape,
+            _ => json!({}),
+        };
+        properties.insert(field.to_string(), shape);
+    }
+    
+    // Fields the derive reports missing, each filled in to find the next one
+    let mut required = Vec::new();
+    while let Err(Probed::Missing(field)) = T::deserialize(Probe::Filled(&required)) {
+        if required.contains(&field) {
+            break;
+        }
+        required.push(field);
+    }
+    
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+/// How a probe ends a deserialization: with what it found out
+#[derive(Debug)]
+enum Probed {
+    /// The fields of the struct being deserialized
+    Fields(&'static [&'static str]),
+    
+    /// The schema of the value asked for
+    Shape(Value),
+    
+    /// A field that cannot be left out
+    Missing(&'static str),
+    
+    Other(String),
+}
+
+impl fmt::Display for Probed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Probed::Fields(fields) => write!(f, "fields {:?}", fields),
+            Probed::Shape(shape) => write!(f, "shape {}", shape),
+            Probed::Missing(field) => write!(f, "missing field `{}`", field),
+            Probed::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Probed {}
+
+impl de::Error for Probed {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Probed::Other(msg.to_string())
+    }
+    
+    fn missing_field(field: &'static str) -> Self {
+        Probed::Missing(field)
+    }
+}
+
+/// Replace the schema a nested deserialization found with one built around it
+fn wrap<T>(result: Result<T, Probed>, outer: impl FnOnce(Value) -> Value) -> Result<T, Probed> {
+    match result {
+        Err(Probed::Shape(shape)) => Err(Probed::Shape(outer(shape))),
+        Err(e) => Err(e),
+        Ok(_) => Err(Probed::Shape(outer(json!({})))),
+    }
+}
+
+/// A deserializer answering with schemas rather than values
+#[derive(Clone, Copy)]
+enum Probe<'a> {
+    /// Any value: its schema, a struct as a reference to its component
+    Value,
+    
+    /// The outermost struct: its field names
+    Fields,
+    
+    /// The outermost struct: the schema of one field
+    Field(&'static str),
+    
+    /// The outermost struct with only these fields given, as plain values
+    Filled(&'a [&'static str]),
+}
+
+macro_rules! shapes {
+    ($($method:ident => $shape:tt),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Probed> {
+                Err(Probed::Shape(json!($shape)))
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Probe<'_> {
+    type Error = Probed;
+    
+    shapes! {
+        deserialize_any => {},
+        deserialize_bool => { "type": "boolean" },
+        deserialize_i8 => { "type": "integer" },
+        deserialize_i16 => { "type": "integer" },
+        deserialize_i32 => { "type": "integer" },
+        deserialize_i64 => { "type": "integer" },
+        deserialize_u8 => { "type": "integer", "minimum": 0 },
+        deserialize_u16 => { "type": "integer", "minimum": 0 },
+        deserialize_u32 => { "type": "integer", "minimum": 0 },
+        deserialize_u64 => { "type": "integer", "minimum": 0 },
+        deserialize_f32 => { "type": "number" },
+        deserialize_f64 => { "type": "number" },
+        deserialize_char => { "type": "string" },
+        deserialize_str => { "type": "string" },
+        deserialize_string => { "type": "string" },
+        deserialize_bytes => { "type": "string" },
+        deserialize_byte_buf => { "type": "string" },
+        deserialize_unit => {},
+        deserialize_identifier => { "type": "string" },
+        deserialize_ignored_any => {},
+    }
+    
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probed> {
+        wrap(visitor.visit_some(Probe::Value), |shape| {
+            if shape.get("$ref").is_some() {
+                json!({ "allOf": [shape], "nullable": true })
+            } else {
+                let mut shape = shape;
+                shape["nullable"] = json!(true);
+                shape
+            }
+        })
+    }
+    
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Probed> {
+        self.deserialize_unit(visitor)
+    }
+    
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Probed> {
+        visitor.visit_newtype_struct(Probe::Value)
+    }
+    
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probed> {
+        wrap(visitor.visit_seq(ProbeEntries::Element), |items| json!({ "type": "array", "items": items }))
+    }
+    
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Probed> {
+        self.deserialize_seq(visitor)
+    }
+    
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Probed> {
+        self.deserialize_seq(visitor)
+    }
+    
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probed> {
+        wrap(visitor.visit_map(ProbeEntries::Entry { key_given: false }), |values| {
+            json!({ "type": "object", "additionalProperties": values })
+        })
+    }
+    
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Probed> {
+        match self {
+            Probe::Value => Err(Probed::Shape(reference(name))),
+            Probe::Fields => Err(Probed::Fields(fields)),
+            Probe::Field(field) => visitor.visit_map(ProbeEntries::Field { name: Some(field) }),
+            Probe::Filled(given) => visitor.visit_map(Filled { fields: given.iter() }),
+        }
+    }
+    
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        Err(Probed::Shape(json!({ "type": "string", "enum": variants })))
+    }
+}
+
+/// The one element, entry or struct field a probe looks at
+enum ProbeEntries {
+    Element,
+    Entry { key_given: bool },
+    Field { name: Option<&'static str> },
+}
+
+impl<'de> SeqAccess<'de> for ProbeEntries {
+    type Error = Probed;
+    
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Probed> {
+        seed.deserialize(Probe::Value).map(Some)
+    }
+}
+
+impl<'de> MapAccess<'de> for ProbeEntries {
+    type Error = Probed;
+    
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Probed> {
+        match self {
+            ProbeEntries::Entry { key_given } if !*key_given => {
+                *key_given = true;
+                seed.deserialize(Plain).map(Some)
+            },
+            ProbeEntries::Field { name } => match name.take() {
+                Some(name) => seed.deserialize(name.into_deserializer()).map(Some),
+                None => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+    
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Probed> {
+        seed.deserialize(Probe::Value)
+    }
+}
+
+/// Struct fields given as plain values
+struct Filled<'a> {
+    fields: std::slice::Iter<'a, &'static str>,
+}
+
+impl<'de> MapAccess<'de> for Filled<'_> {
+    type Error = Probed;
+    
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Probed> {
+        match self.fields.next() {
+            Some(&field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+    
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Probed> {
+        seed.deserialize(Plain)
+    }
+}
+
+/// An empty sequence or map
+struct Empty;
+
+impl<'de> SeqAccess<'de> for Empty {
+    type Error = Probed;
+    
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, _seed: T) -> Result<Option<T::Value>, Probed> {
+        Ok(None)
+    }
+}
+
+impl<'de> MapAccess<'de> for Empty {
+    type Error = Probed;
+    
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, _seed: K) -> Result<Option<K::Value>, Probed> {
+        Ok(None)
+    }
+    
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, _seed: V) -> Result<V::Value, Probed> {
+        Err(Probed::Other("no value in an empty map".to_string()))
+    }
+}
+
+macro_rules! plain_values {
+    ($($method:ident => $visit:ident($($value:expr)?)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probed> {
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+/// A string any string field accepts, timestamps included
+const PLAIN_STRING: &str = "1970-01-01T00:00:00Z";
+
+/// A deserializer producing a plain value of whatever type is asked for:
+/// zero, false, `PLAIN_STRING`, an empty collection or the first enum variant
+struct Plain;
+
+impl<'de> Deserializer<'de> for Plain {
+    type Error = Probed;
+    
+    plain_values! {
+        deserialize_any => visit_unit(),
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_u64(0),
+        deserialize_i16 => visit_u64(0),
+        deserialize_i32 => visit_u64(0),
+        deserialize_i64 => visit_u64(0),
+        deserialize_u8 => visit_u64(0),
+        deserialize_u16 => visit_u64(0),
+        deserialize_u32 => visit_u64(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_f32 => visit_f64(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char(' '),
+        deserialize_str => visit_str(PLAIN_STRING),
+        deserialize_string => visit_str(PLAIN_STRING),
+        deserialize_bytes => visit_bytes(&[]),
+        deserialize_byte_buf => visit_bytes(&[]),
+        deserialize_option => visit_none(),
+        deserialize_unit => visit_unit(),
+        deserialize_seq => visit_seq(Empty),
+        deserialize_map => visit_map(Empty),
+        deserialize_identifier => visit_str(PLAIN_STRING),
+        deserialize_ignored_any => visit_unit(),
+    }
+    
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Probed> {
+        visitor.visit_unit()
+    }
+    
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Probed> {
+        visitor.visit_newtype_struct(self)
+    }
+    
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Probed> {
+        visitor.visit_seq(Empty)
+    }
+    
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Probed> {
+        visitor.visit_seq(Empty)
+    }
+    
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Probed> {
+        visitor.visit_map(Empty)
+    }
+    
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Probed> {
+        visitor.visit_enum(variants.first().copied().unwrap_or_default().into_deserializer())
+    }
+}
@@ -1,4 +1,5 @@
 use actix_web::{web, Scope, HttpResponse, Responder};
+use actix_web::middleware::from_fn;
 use serde_json::json;
 use crate::api::handlers::{
     capture::{
@@ -7,86 +8,219 @@ use crate::api::handlers::{
         stop_capture,
         get_capture_status,
         get_capture_diagnostic,
+        get_capture_memory,
+        get_capture_info,
+        get_recovered_index,
+        get_capture_events,
         update_capture_settings,
+        update_capture_description,
+        set_capture_filter,
+        get_compiled_filter,
+        reset_capture_stats,
+        load_files,
+        get_decode_as,
+        set_decode_as,
+        arm_capture,
+        get_trigger,
+        disarm_capture,
     },
     packets::{
         get_packets,
+        delete_packets,
         get_packet_stats,
         get_packet,
+        get_packet_hexdump,
+        export_packets,
+        search_fields,
     },
+    filters::{
+        create_filter,
+        list_filters,
+        update_filter,
+        delete_filter,
+        get_color_rules,
+        set_color_rules,
+    },
+    alerts::get_alerts,
+    protocols::get_protocols,
+    streams::{get_stream_timing, get_stream_inflight, get_stream_objects, get_stream_object},
+    stats::{get_neighbors, get_wifi, get_mtu, get_latency, get_rtp, get_protocol_chart, get_conversations, export_conversations, export_conversation},
+    health::{health, health_live, health_ready},
+    logging::{get_log_level, set_log_level},
+};
+use crate::api::handlers::{
+    alerts::AlertsQuery,
+    capture::{
+        StartCaptureRequest,
+        StopCaptureRequest,
+        RecoveredIndexQuery,
+        UpdateSettingsRequest,
+        UpdateDescriptionRequest,
+        SetFilterRequest,
+        CompiledFilterQuery,
+        LoadFilesRequest,
+        DecodeAsRequest,
+    },
+    packets::{PacketsQuery, DeletePacketsQuery, HexDumpQuery, ExportRequest, SearchFieldQuery, PacketStatsQuery},
+    filters::{CreateFilterRequest, UpdateFilterRequest},
+    stats::{LatencyQuery, ProtocolChartQuery, ConversationsQuery, ConversationsExportQuery, ConversationExportQuery},
+    streams::{TimingQuery, InflightQuery},
+    logging::LogLevelRequest,
 };
+use crate::api::cors;
+use crate::api::openapi::{self, schema, Endpoint, Returns};
 use crate::api::websocket::ws_index;
+use crate::capture::trigger::TriggerSettings;
+use crate::models::filter::ColorRule;
+
+/// Every route, for the index and the OpenAPI spec
+pub const ENDPOINTS: &[Endpoint] = &[
+    Endpoint::new("GET", "/health", "Health check with uptime and capture state"),
+    Endpoint::new("GET", "/health/live", "Liveness probe"),
+    Endpoint::new("GET", "/health/ready", "Readiness probe (capture subsystem initialized)"),
+    Endpoint::new("GET", "/api/interfaces", "List available network interfaces")
+        .returns(Returns::ListIn("detailed_interfaces", "InterfaceInfo")),
+    Endpoint::new("POST", "/api/capture/start", "Start packet capture")
+        .body(schema::<StartCaptureRequest>),
+    Endpoint::new("POST", "/api/capture/stop", "Stop packet capture (optionally {\"interface\": ...} to stop just that one)")
+        .body(schema::<StopCaptureRequest>),
+    Endpoint::new("GET", "/api/capture/status", "Get status of the capture, with the per-second rates of the last minute"),
+    Endpoint::new("GET", "/api/capture/diagnostic", "Get diagnostic info about the capture"),
+    Endpoint::new("GET", "/api/capture/memory", "Estimate the memory used by the packet buffer"),
+    Endpoint::new("GET", "/api/capture/info", "Summarize the capture like capinfos (counts, duration, rates, protocols, file hashes)"),
+    Endpoint::new("GET", "/api/capture/index", "Get the packet summaries recovered from the index file of a crashed run")
+        .query(schema::<RecoveredIndexQuery>),
+    Endpoint::new("GET", "/api/capture/events", "Get the session's event log: capture warnings and errors, and the capture interfaces' link going down or up"),
+    Endpoint::new("POST", "/api/capture/settings", "Update capture settings")
+        .body(schema::<UpdateSettingsRequest>),
+    Endpoint::new("PUT", "/api/capture/description", "Edit the description and tags of the capture")
+        .body(schema::<UpdateDescriptionRequest>),
+    Endpoint::new("POST", "/api/capture/stats/reset", "Zero the statistics, keeping the captured packets"),
+    Endpoint::new("POST", "/api/capture/filter", "Change the capture filter, live when the capture handle allows it")
+        .body(schema::<SetFilterRequest>),
+    Endpoint::new("GET", "/api/capture/filter/compiled", "Get the capture filter (or ?filter=) compiled to BPF instructions, like tcpdump -d")
+        .query(schema::<CompiledFilterQuery>),
+    Endpoint::new("POST", "/api/capture/load", "Load and merge packets from capture files, tagged by source")
+        .body(schema::<LoadFilesRequest>),
+    Endpoint::new("GET", "/api/capture/decode-as", "Get the port to protocol decode-as overrides"),
+    Endpoint::new("POST", "/api/capture/decode-as", "Force a port to be decoded as a protocol (or remove the override)")
+        .body(schema::<DecodeAsRequest>),
+    Endpoint::new("GET", "/api/capture/arm", "Get the status of the armed capture trigger"),
+    Endpoint::new("POST", "/api/capture/arm", "Start a capture automatically when interface traffic exceeds a pps/bps threshold")
+        .body(schema::<TriggerSettings>),
+    Endpoint::new("POST", "/api/capture/disarm", "Disarm the capture trigger"),
+    Endpoint::new("GET", "/api/packets", "Get list of captured packets (optionally ?source= for loaded files, ?scope= for an address scope)")
+        .query(schema::<PacketsQuery>)
+        .returns(Returns::ListIn("packets", "PacketSummary")),
+    Endpoint::new("DELETE", "/api/packets", "Delete the stored packets matching ?display_filter= (all of them only with ?confirm=true)")
+        .query(schema::<DeletePacketsQuery>),
+    Endpoint::new("GET", "/api/packets/{id}", "Get details of a specific packet")
+        .path_param(schema::<u64>),
+    Endpoint::new("GET", "/api/packets/{id}/hexdump", "Get a packet's bytes as a plain-text hex dump")
+        .path_param(schema::<u64>)
+        .query(schema::<HexDumpQuery>),
+    Endpoint::new("POST", "/api/packets/export", "Export selected packets as pcap, CSV or JSON lines")
+        .body(schema::<ExportRequest>),
+    Endpoint::new("GET", "/api/packets/search-field", "Find packets with any decoded header or metadata field matching ?q=")
+        .query(schema::<SearchFieldQuery>)
+        .returns(Returns::ListIn("packets", "PacketSummary")),
+    Endpoint::new("GET", "/api/packets/stats", "Get packet statistics (?group_by=host to key top talkers by resolved hostname)")
+        .query(schema::<PacketStatsQuery>)
+        .returns(Returns::Schema("CaptureStats")),
+    Endpoint::new("GET", "/api/filters", "List saved filters"),
+    Endpoint::new("POST", "/api/filters", "Save a filter")
+        .body(schema::<CreateFilterRequest>),
+    Endpoint::new("PUT", "/api/filters/{id}", "Update a saved filter")
+        .body(schema::<UpdateFilterRequest>),
+    Endpoint::new("DELETE", "/api/filters/{id}", "Delete a saved filter"),
+    Endpoint::new("GET", "/api/color-rules", "List the packet coloring rules"),
+    Endpoint::new("POST", "/api/color-rules", "Replace the packet coloring rules (display filter and color label, first match wins)")
+        .body(schema::<Vec<ColorRule>>),
+    Endpoint::new("GET", "/api/alerts", "Get recent SYN flood and port scan alerts")
+        .query(schema::<AlertsQuery>),
+    Endpoint::new("GET", "/api/protocols", "List decodable protocols with the header fields each populates"),
+    Endpoint::new("GET", "/api/log-level", "Get the active log level and console verbosity"),
+    Endpoint::new("PUT", "/api/log-level", "Change the log level and console verbosity at runtime")
+        .body(schema::<LogLevelRequest>),
+    Endpoint::new("GET", "/api/stats/neighbors", "Get network devices discovered from LLDP/CDP announcements"),
+    Endpoint::new("GET", "/api/stats/wifi", "Get Wi-Fi access points seen in beacons and probe responses"),
+    Endpoint::new("GET", "/api/stats/mtu", "Get oversized packets and path MTU hints per destination"),
+    Endpoint::new("GET", "/api/stats/latency", "Get DNS or HTTP response times (?protocol=dns|http)")
+        .query(schema::<LatencyQuery>),
+    Endpoint::new("GET", "/api/stats/rtp", "Get RTP streams by SSRC with their codec, packet loss (sequence gaps) and jitter"),
+    Endpoint::new("GET", "/api/stats/protocols/chart", "Get the protocol distribution as sorted chart slices with an Other bucket")
+        .query(schema::<ProtocolChartQuery>),
+    Endpoint::new("GET", "/api/stats/conversations", "Get traffic per 5-tuple with its application protocols (?protocol= to select, ?sparkline=true for recent throughput, ?group_by=host to combine by resolved hostname)")
+        .query(schema::<ConversationsQuery>),
+    Endpoint::new("GET", "/api/stats/conversations/export", "Export the conversation table as CSV (default) or JSON lines, most bytes first (?sort=packets|duration|first_seen|last_seen, ?protocol=, ?group_by=host)")
+        .query(schema::<ConversationsExportQuery>),
+    Endpoint::new("GET", "/api/stats/conversations/{id}/export", "Export the packets of one conversation (both directions) as pcap, pcapng, CSV or JSON lines")
+        .query(schema::<ConversationExportQuery>),
+    Endpoint::new("GET", "/api/streams/{id}/timing", "Get inter-packet gaps, mean/stddev and jitter of the stream (flow direction) of a packet")
+        .path_param(schema::<u64>)
+        .query(schema::<TimingQuery>),
+    Endpoint::new("GET", "/api/streams/{id}/inflight", "Get the bytes in flight (sent, unacknowledged) over time from the sender of a TCP packet, unknown where a direction is missing")
+        .path_param(schema::<u64>)
+        .query(schema::<InflightQuery>),
+    Endpoint::new("GET", "/api/streams/{id}/objects", "List the files transferred over the TCP connection of a packet (HTTP bodies, dechunked and decompressed), like Export Objects")
+        .path_param(schema::<u64>),
+    Endpoint::new("GET", "/api/streams/{id}/objects/{n}", "Download a file carved from the TCP connection of a packet, by its index in the listing")
+        .path_param(schema::<u64>),
+    Endpoint::new("GET", "/api/openapi.json", "OpenAPI 3.0 description of these endpoints, their parameters and schemas"),
+    Endpoint::new("GET", "/api/ws", "WebSocket endpoint for real-time updates"),
+];
 
 /// Root endpoint to provide information about the API
 async fn index() -> impl Responder {
+    let endpoints: Vec<_> = ENDPOINTS
+        .iter()
+        .map(|endpoint| json!({
+            "path": endpoint.path,
+            "method": endpoint.method,
+            "description": endpoint.description,
+        }))
+        .collect();
+    
     HttpResponse::Ok().json(json!({
         "name": "RustShark API",
         "version": env!("CARGO_PKG_VERSION"),
         "description": "A Wireshark-like network packet analyzer with REST API",
-        "endpoints": [
-            {
-                "path": "/api/interfaces",
-                "method": "GET",
-                "description": "List available network interfaces"
-            },
-            {
-                "path": "/api/capture/start",
-                "method": "POST",
-                "description": "Start packet capture"
-            },
-            {
-                "path": "/api/capture/stop",
-                "method": "POST",
-                "description": "Stop packet capture"
-            },
-            {
-                "path": "/api/capture/status",
-                "method": "GET",
-                "description": "Get status of the capture"
-            },
-            {
-                "path": "/api/capture/diagnostic",
-                "method": "GET",
-                "description": "Get diagnostic info about the capture"
-            },
-            {
-                "path": "/api/capture/settings",
-                "method": "POST",
-                "description": "Update capture settings"
-            },
-            {
-                "path": "/api/packets",
-                "method": "GET",
-                "description": "Get list of captured packets"
-            },
-            {
-                "path": "/api/packets/{id}",
-                "method": "GET",
-                "description": "Get details of a specific packet"
-            },
-            {
-                "path": "/api/packets/stats",
-                "method": "GET",
-                "description": "Get packet statistics"
-            },
-            {
-                "path": "/api/ws",
-                "method": "GET",
-                "description": "WebSocket endpoint for real-time updates"
-            }
-        ]
+        "endpoints": endpoints
     }))
 }
 
+/// OpenAPI description of the API, for generating clients or a Swagger UI
+async fn openapi_spec() -> impl Responder {
+    HttpResponse::Ok().json(openapi::spec(ENDPOINTS))
+}
+
+/// Frame injection, only served by builds with the `test-inject` feature
+#[cfg_attr(not(feature = "test-inject"), allow(unused_variables))]
+fn injection_routes(cfg: &mut web::ServiceConfig) {
+    #[cfg(feature = "test-inject")]
+    cfg.route("/inject", web::post().to(crate::api::handlers::capture::inject_packets));
+}
+
 /// Configure API routes
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg
         // Root endpoint
         .route("/", web::get().to(index))
+        // Health probes (outside /api so they stay cheap and unauthenticated)
+        .service(
+            web::scope("/health")
+                .route("", web::get().to(health))
+                .route("/live", web::get().to(health_live))
+                .route("/ready", web::get().to(health_ready))
+        )
         .service(
             web::scope("/api")
+                // CORS headers for the origins given with --cors-origin
+                .wrap(from_fn(cors::handle))
                 // WebSocket route for real-time updates
                 .route("/ws", web::get().to(ws_index))
+                // Machine-readable description of the routes
+                .route("/openapi.json", web::get().to(openapi_spec))
                 
                 // Capture management
                 .service(
@@ -99,14 +233,72 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                         .route("/stop", web::post().to(stop_capture))
                         .route("/status", web::get().to(get_capture_status))
                         .route("/diagnostic", web::get().to(get_capture_diagnostic))
+                        .route("/memory", web::get().to(get_capture_memory))
+                        .route("/info", web::get().to(get_capture_info))
+                        .route("/index", web::get().to(get_recovered_index))
+                        .route("/events", web::get().to(get_capture_events))
                         .route("/settings", web::post().to(update_capture_settings))
+                        .route("/description", web::put().to(update_capture_description))
+                        .route("/filter", web::post().to(set_capture_filter))
+                        .route("/filter/compiled", web::get().to(get_compiled_filter))
+                        .route("/stats/reset", web::post().to(reset_capture_stats))
+                        .route("/load", web::post().to(load_files))
+                        .route("/decode-as", web::get().to(get_decode_as))
+                        .route("/decode-as", web::post().to(set_decode_as))
+                        .route("/arm", web::get().to(get_trigger))
+                        .route("/arm", web::post().to(arm_capture))
+                        .route("/disarm", web::post().to(disarm_capture))
+                        .configure(injection_routes)
                 )
                 // Packet data
                 .service(
                     web::scope("/packets")
                         .route("", web::get().to(get_packets))
+                        .route("", web::delete().to(delete_packets))
                         .route("/stats", web::get().to(get_packet_stats))
+                        .route("/export", web::post().to(export_packets))
+                        .route("/search-field", web::get().to(search_fields))
                         .route("/{id}", web::get().to(get_packet))
+                        .route("/{id}/hexdump", web::get().to(get_packet_hexdump))
+                )
+                // Saved filters
+                .service(
+                    web::scope("/filters")
+                        .route("", web::get().to(list_filters))
+                        .route("", web::post().to(create_filter))
+                        .route("/{id}", web::put().to(update_filter))
+                        .route("/{id}", web::delete().to(delete_filter))
+                )
+                // Packet coloring rules
+                .route("/color-rules", web::get().to(get_color_rules))
+                .route("/color-rules", web::post().to(set_color_rules))
+                // Security alerts
+                .route("/alerts", web::get().to(get_alerts))
+                // Decodable protocols and their header fields
+                .route("/protocols", web::get().to(get_protocols))
+                // Logging, adjustable without a restart
+                .route("/log-level", web::get().to(get_log_level))
+                .route("/log-level", web::put().to(set_log_level))
+                // Derived statistics
+                .service(
+                    web::scope("/stats")
+                        .route("/neighbors", web::get().to(get_neighbors))
+                        .route("/wifi", web::get().to(get_wifi))
+                        .route("/mtu", web::get().to(get_mtu))
+                        .route("/latency", web::get().to(get_latency))
+                        .route("/rtp", web::get().to(get_rtp))
+                        .route("/protocols/chart", web::get().to(get_protocol_chart))
+                        .route("/conversations", web::get().to(get_conversations))
+                        .route("/conversations/export", web::get().to(export_conversations))
+                        .route("/conversations/{id}/export", web::get().to(export_conversation))
+                )
+                // Per-stream analysis, streams being selected by one of their packets
+                .service(
+                    web::scope("/streams")
+                        .route("/{id}/timing", web::get().to(get_stream_timing))
+                        .route("/{id}/inflight", web::get().to(get_stream_inflight))
+                        .route("/{id}/objects", web::get().to(get_stream_objects))
+                        .route("/{id}/objects/{n}", web::get().to(get_stream_object))
                 )
         );
 } 
\ No newline at end of file
@@ -1,15 +1,22 @@
-use actix_web::{web, Error, HttpRequest, Responder};
+use actix_web::http::header;
+use actix_web::{web, Error, FromRequest, HttpRequest, Responder};
 use actix_ws::{self, Message};
+use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
 use log::{debug, info, warn};
 use serde::Serialize;
+use serde_json::{json, Value};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tokio::time::interval;
 
+use crate::api::cors::CorsPolicy;
+use crate::api::ws_deflate;
 use crate::capture::manager::CaptureManager;
-use crate::models::stats::CaptureStats;
+use crate::models::alert::Alert;
+use crate::models::event::EventLevel;
+use crate::models::stats::{CaptureStats, StatsBucket};
 
 // How often heartbeat pings are sent
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
@@ -19,13 +26,33 @@ const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 #[serde(tag = "type")]
 enum WsOutMessage {
     #[serde(rename = "stats")]
-    Stats { stats: CaptureStats },
+    Stats { stats: Box<CaptureStats> },
+    
+    #[serde(rename = "history")]
+    History { buckets: Vec<StatsBucket> },
     
     #[serde(rename = "status")]
     Status { running: bool, packet_count: usize },
     
     #[serde(rename = "ping")]
     Ping { timestamp: u64 },
+    
+    #[serde(rename = "alert")]
+    Alert { alert: Alert },
+    
+    #[serde(rename = "event")]
+    Event { level: EventLevel, message: String, timestamp: DateTime<Utc> },
+}
+
+impl WsOutMessage {
+    /// Serialize the message, tagged with the capture session it belongs to
+    fn encode(&self, session_id: Option<String>) -> Option<String> {
+        let mut value = serde_json::to_value(self).ok()?;
+        if let Value::Object(ref mut obj) = value {
+            obj.insert("session_id".to_string(), json!(session_id));
+        }
+        serde_json::to_string(&value).ok()
+    }
 }
 
 /// Handle WebSocket connections
@@ -42,8 +69,25 @@ pub async fn ws_index(
     };
     info!("WebSocket connection from: {}", addr);
     
-    // Setup WebSocket connection
+    // Pages from origins the CORS policy does not allow may not read the live feed
+    let origin = req.headers().get(header::ORIGIN).and_then(|origin| origin.to_str().ok());
+    let host = req.headers().get(header::HOST).and_then(|host| host.to_str().ok());
+    if let Some(policy) = req.app_data::<web::Data<CorsPolicy>>() {
+        if !policy.allows_websocket(origin, host) {
+            warn!("Refusing WebSocket connection from {} with origin {}", addr, origin.unwrap_or_default());
+            return Err(actix_web::error::ErrorForbidden("Origin not allowed"));
+        }
+    }
+    
+    // Setup WebSocket connection, compressing frames if the client offers permessage-deflate
+    let compress = ws_deflate::offered(req.headers());
+    let body = if compress {
+        web::Payload::from_request(&req, &mut ws_deflate::inflate_payload(body.into_inner())).await?
+    } else {
+        body
+    };
     let (response, session, mut msg_stream) = actix_ws::handle(&req, body)?;
+    let response = if compress { ws_deflate::compress_response(response) } else { response };
     
     // Access capture manager for WebSocket task
     let cm = capture_manager.into_inner();
@@ -52,6 +96,8 @@ pub async fn ws_index(
     let session_for_handler = session.clone();
     let session_for_updates = session.clone();
     let session_for_heartbeat = session.clone();
+    let session_for_alerts = session.clone();
+    let session_for_events = session.clone();
     
     // Spawn task to handle the WebSocket connection
     actix_web::rt::spawn(async move {
@@ -66,6 +112,10 @@ pub async fn ws_index(
         // Subscribe to stats updates
         let manager = cm.read().await;
         let mut stats_rx = manager.subscribe_to_stats();
+        let mut alerts_rx = manager.subscribe_to_alerts();
+        let mut events_rx = manager.subscribe_to_events();
+        let initial_stats = manager.get_stats();
+        let capture_session = manager.session_handle();
         drop(manager); // Release read lock
         
         // Send initial status and stats
@@ -80,6 +130,12 @@ pub async fn ws_index(
             return;
         }
         
+        // Backfill the charts of late-joining clients
+        if let Err(e) = send_history(&mut session_clone, &cm).await {
+            warn!("Failed to send stats history: {}", e);
+            return;
+        }
+        
         // Create a future that completes when the client sends a close message or disconnects
         let ws_msg_task = {
             let mut session = session_for_handler;
@@ -153,31 +209,26 @@ pub async fn ws_index(
                 let mut buffer_stats = None;
                 let mut buffer_count = 0;
                 
-                // Keep track of the current capture session by its start time
-                let mut current_session_start: Option<String> = None;
+                // Keep track of the current capture session
+                let mut current_session = initial_stats.session_id.clone();
+                
+                // Totals as of the last stats message, for the per-message deltas
+                let mut last_sent_totals = (initial_stats.total_packets, initial_stats.total_bytes);
                 
                 while let Ok(stats) = stats_rx.recv().await {
                     updates_received += 1;
                     
-                    // Check if this is a new capture session by comparing start times
-                    if let Some(start_time) = &stats.start_time {
-                        let start_str = start_time.to_string();
+                    // Check if this is a new capture session
+                    if stats.session_id != current_session {
+                        // This is a new capture session, reset our state
+                        info!("Detected new capture session {:?}, resetting WebSocket state", stats.session_id);
+                        buffer_stats = None;
+                        buffer_count = 0;
+                        current_session = stats.session_id.clone();
+                        last_sent_totals = (0, 0);
                         
-                        if let Some(current_start) = &current_session_start {
-                            if &start_str != current_start {
-                                // This is a new capture session, reset our state
-                                info!("Detected new capture session, resetting WebSocket state");
-                                buffer_stats = None;
-                                buffer_count = 0;
-                                current_session_start = Some(start_str);
-                                
-                                // Force an immediate update
-                                last_stats_update = Instant::now() - Duration::from_secs(2);
-                            }
-                        } else {
-                            // First capture session we've seen
-                            current_session_start = Some(start_str);
-                        }
+                        // Force an immediate update
+                        last_stats_update = Instant::now() - Duration::from_secs(2);
                     }
                     
                     // Update the buffer with new values
@@ -191,6 +242,12 @@ pub async fn ws_index(
                             buffered.total_packets = stats.total_packets;
                             buffered.total_bytes = stats.total_bytes;
                             buffered.errors = stats.errors;
+                            buffered.warmup_discarded = stats.warmup_discarded;
+                            buffered.size_filtered = stats.size_filtered;
+                            buffered.duplicates = stats.duplicates;
+                            buffered.backpressure_dropped = stats.backpressure_dropped;
+                            buffered.untracked_sources = stats.untracked_sources;
+                            buffered.untracked_destinations = stats.untracked_destinations;
                             
                             // Average the rates
                             buffered.packet_rate = (buffered.packet_rate * (buffer_count as f64 - 1.0) + stats.packet_rate) / buffer_count as f64;
@@ -200,8 +257,13 @@ pub async fn ws_index(
                             buffered.protocols = stats.protocols;
                             buffered.sources = stats.sources;
                             buffered.destinations = stats.destinations;
+                            buffered.hostnames = stats.hostnames;
+                            buffered.description = stats.description;
+                            buffered.tags = stats.tags;
+                            buffered.scopes = stats.scopes;
                             
-                            // Keep latest timestamps
+                            // Keep latest session and timestamps
+                            buffered.session_id = stats.session_id;
                             buffered.start_time = stats.start_time;
                             buffered.end_time = stats.end_time;
                             
@@ -212,10 +274,16 @@ pub async fn ws_index(
                     // Check if enough time has passed since the last update
                     let now = Instant::now();
                     if now.duration_since(last_stats_update).as_millis() >= STATS_THROTTLE_MS {
-                        if let Some(buffered_stats) = buffer_stats.take() {
+                        if let Some(mut buffered_stats) = buffer_stats.take() {
+                            // Deltas since the previous message
+                            buffered_stats.packets_delta = buffered_stats.total_packets.saturating_sub(last_sent_totals.0);
+                            buffered_stats.bytes_delta = buffered_stats.total_bytes.saturating_sub(last_sent_totals.1);
+                            last_sent_totals = (buffered_stats.total_packets, buffered_stats.total_bytes);
+                            
                             // Send the update with averaged values
-                            let msg = WsOutMessage::Stats { stats: buffered_stats };
-                            if let Ok(json) = serde_json::to_string(&msg) {
+                            let session_id = buffered_stats.session_id.clone();
+                            let msg = WsOutMessage::Stats { stats: Box::new(buffered_stats) };
+                            if let Some(json) = msg.encode(session_id) {
                                 if session.text(json).await.is_err() {
                                     break;
                                 }
@@ -244,9 +312,66 @@ pub async fn ws_index(
             }
         };
         
+        // Create a future that forwards security alerts as they are raised
+        let alerts_task = {
+            let mut session = session_for_alerts;
+            let alerts_session = capture_session.clone();
+            
+            async move {
+                loop {
+                    match alerts_rx.recv().await {
+                        Ok(alert) => {
+                            let msg = WsOutMessage::Alert { alert };
+                            let session_id = alerts_session.read().clone();
+                            if let Some(json) = msg.encode(session_id) {
+                                if session.text(json).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("WebSocket client lagged, skipped {} alerts", skipped);
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+        };
+        
+        // Create a future that forwards capture warnings and errors
+        let events_task = {
+            let mut session = session_for_events;
+            let events_session = capture_session.clone();
+            
+            async move {
+                loop {
+                    match events_rx.recv().await {
+                        Ok(event) => {
+                            let msg = WsOutMessage::Event {
+                                level: event.level,
+                                message: event.message,
+                                timestamp: event.timestamp,
+                            };
+                            let session_id = events_session.read().clone();
+                            if let Some(json) = msg.encode(session_id) {
+                                if session.text(json).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("WebSocket client lagged, skipped {} events", skipped);
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+        };
+        
         // Create a future for heartbeats
         let heartbeat_task = {
             let mut session = session_for_heartbeat;
+            let heartbeat_session = capture_session;
             
             async move {
                 loop {
@@ -266,7 +391,8 @@ pub async fn ws_index(
                         timestamp: chrono::Utc::now().timestamp() as u64 
                     };
                     
-                    if let Ok(json) = serde_json::to_string(&ping_msg) {
+                    let session_id = heartbeat_session.read().clone();
+                    if let Some(json) = ping_msg.encode(session_id) {
                         if session.text(json).await.is_err() {
                             break;
                         }
@@ -280,6 +406,8 @@ pub async fn ws_index(
             _ = ws_msg_task => {},
             _ = stats_updates_task => {},
             _ = heartbeat_task => {},
+            _ = alerts_task => {},
+            _ = events_task => {},
         }
         
         // Cleanup: we don't need to close the session here as it's done in the tasks if needed
@@ -298,12 +426,14 @@ async fn send_status(
     let is_running = manager.get_status();
     let packet_count = manager.get_packet_count();
     
+    let session_id = manager.get_session_id();
+    
     let msg = WsOutMessage::Status {
         running: is_running,
         packet_count,
     };
     
-    if let Ok(json) = serde_json::to_string(&msg) {
+    if let Some(json) = msg.encode(session_id) {
         session.text(json).await?;
     }
     
@@ -317,12 +447,32 @@ async fn send_stats(
 ) -> Result<(), actix_ws::Closed> {
     let manager = cm.read().await;
     let stats = manager.get_stats();
+    let session_id = stats.session_id.clone();
     
-    let msg = WsOutMessage::Stats { stats };
+    let msg = WsOutMessage::Stats { stats: Box::new(stats) };
     
-    if let Ok(json) = serde_json::to_string(&msg) {
+    if let Some(json) = msg.encode(session_id) {
         session.text(json).await?;
     }
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Send the retained per-second stats history to WebSocket client
+async fn send_history(
+    session: &mut actix_ws::Session,
+    cm: &Arc<RwLock<CaptureManager>>,
+) -> Result<(), actix_ws::Closed> {
+    let manager = cm.read().await;
+    let buckets = manager.get_stats_history();
+    let session_id = manager.get_session_id();
+    drop(manager);
+    
+    let msg = WsOutMessage::History { buckets };
+    
+    if let Some(json) = msg.encode(session_id) {
+        session.text(json).await?;
+    }
+    
+    Ok(())
+}
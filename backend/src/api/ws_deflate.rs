@@ -0,0 +1,372 @@
+// permessage-deflate (RFC 7692) for the WebSocket endpoint
+//
+// actix-ws does its own framing and knows nothing about extensions, so
+// compression wraps it on both sides. Outgoing data frames are re-framed with
+// a deflated payload and RSV1 set. Incoming compressed frames are inflated
+// back into plain frames before actix-ws parses them. Both ends reset their
+// compression context after every message (no context takeover), so each
+// message is self-contained and a connection holds no deflate state between
+// messages. Clients that don't offer the extension get plain frames as before.
+
+use actix_web::body::{BodyStream, MessageBody};
+use actix_web::dev::Payload;
+use actix_web::error::PayloadError;
+use actix_web::http::header::{HeaderMap, HeaderValue, SEC_WEBSOCKET_EXTENSIONS};
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::HttpResponse;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Decompress, FlushDecompress, Status};
+use futures_util::{stream, StreamExt};
+use std::io::{self, Write};
+
+/// Extension parameters the server answers an accepted offer with
+const EXTENSION_RESPONSE: &str = "permessage-deflate; server_no_context_takeover; client_no_context_takeover";
+
+/// The empty stored block a sync flush ends with, left off the wire (RFC 7692 7.2.1)
+const DEFLATE_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Payloads shorter than this are sent as they are; deflate can't win much on them
+const MIN_COMPRESS_LEN: usize = 64;
+
+/// Largest incoming message inflated, matching actix-ws's default frame limit
+const MAX_INFLATED_LEN: usize = 64 * 1024;
+
+/// First-byte flags and opcodes
+const FIN: u8 = 0x80;
+const RSV1: u8 = 0x40;
+const OPCODE_MASK: u8 = 0x0f;
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CONTROL: u8 = 0x8;
+
+/// Whether the client offered permessage-deflate with parameters the server can honor.
+///
+/// Offers asking for a smaller server window than the deflate default, or with
+/// unknown parameters, are declined.
+pub fn offered(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(SEC_WEBSOCKET_EXTENSIONS)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(acceptable_offer)
+}
+
+fn acceptable_offer(offer: &str) -> bool {
+    let mut parts = offer.split(';').map(str::trim);
+    if parts.next() != Some("permessage-deflate") {
+        return false;
+    }
+    
+    parts.all(|param| {
+        let (name, value) = param.split_once('=').map_or((param, None), |(n, v)| (n.trim(), Some(v.trim().trim_matches('"'))));
+        match name {
+            "server_no_context_takeover" | "client_no_context_takeover" | "client_max_window_bits" => true,
+            "server_max_window_bits" => value == Some("15"),
+            _ => false,
+        }
+    })
+}
+
+/// Accept the extension on a handshake response and compress its outgoing frames
+pub fn compress_response(mut response: HttpResponse) -> HttpResponse {
+    response.headers_mut().insert(SEC_WEBSOCKET_EXTENSIONS, HeaderValue::from_static(EXTENSION_RESPONSE));
+    
+    response.map_body(|_, mut body| {
+        let mut deflater = Deflater::default();
+        let frames = stream::poll_fn(move |cx| std::pin::Pin::new(&mut body).poll_next(cx))
+            .map(move |chunk| chunk.map(|bytes| deflater.rewrite(&bytes)));
+        BodyStream::new(frames).boxed()
+    })
+}
+
+/// Inflate compressed client frames before actix-ws parses them
+pub fn inflate_payload(payload: Payload) -> Payload {
+    let mut inflater = Inflater::default();
+    let frames = payload.map(move |chunk| {
+        chunk.and_then(|bytes| inflater.rewrite(&bytes).map_err(PayloadError::Io))
+    });
+    Payload::from(Box::pin(frames) as std::pin::Pin<Box<dyn futures_util::Stream<Item = _>>>)
+}
+
+/// A parsed frame: its first header byte (FIN, RSV and opcode) and unmasked payload
+struct Frame {
+    head: u8,
+    payload: Vec<u8>,
+}
+
+impl Frame {
+    fn opcode(&self) -> u8 {
+        self.head & OPCODE_MASK
+    }
+    
+    fn is_final(&self) -> bool {
+        self.head & FIN != 0
+    }
+}
+
+/// Compresses the data frames of the server's outgoing byte stream
+#[derive(Default)]
+struct Deflater {
+    buffer: BytesMut,
+}
+
+impl Deflater {
+    /// Feed encoded frames, returning them with complete data messages compressed.
+    /// Fragmented messages and control frames pass through unchanged.
+    fn rewrite(&mut self, chunk: &[u8]) -> Bytes {
+        self.buffer.extend_from_slice(chunk);
+        
+        let mut out = BytesMut::new();
+        while let Some(frame) = take_frame(&mut self.buffer) {
+            let data = matches!(frame.opcode(), OPCODE_TEXT | OPCODE_BINARY);
+            let compressed = (data && frame.is_final() && frame.payload.len() >= MIN_COMPRESS_LEN)
+                .then(|| deflate(&frame.payload))
+                .flatten()
+                .filter(|compressed| compressed.len() < frame.payload.len());
+            
+            match compressed {
+                Some(compressed) => encode_frame(frame.head | RSV1, &compressed, false, &mut out),
+                None => encode_frame(frame.head, &frame.payload, false, &mut out),
+            }
+        }
+        out.freeze()
+    }
+}
+
+/// Inflates compressed messages in the client's incoming byte stream
+#[derive(Default)]
+struct Inflater {
+    buffer: BytesMut,
+    
+    /// Opcode and payload so far of a fragmented compressed message
+    fragmented: Option<(u8, Vec<u8>)>,
+}
+
+impl Inflater {
+    /// Feed client frames, returning them with compressed messages inflated into
+    /// single plain frames (masked with a zero key, as clients' frames must be)
+    fn rewrite(&mut self, chunk: &[u8]) -> io::Result<Bytes> {
+        self.buffer.extend_from_slice(chunk);
+        
+        let mut out = BytesMut::new();
+        loop {
+            // Refuse oversized frames before buffering them
+            let declared = frame_header(&self.buffer).map_or(0, |(len, _, _)| len);
+            let fragmented = self.fragmented.as_ref().map_or(0, |(_, payload)| payload.len());
+            if declared.saturating_add(fragmented) > MAX_INFLATED_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "WebSocket message too large"));
+            }
+            let Some(frame) = take_frame(&mut self.buffer) else { break };
+            
+            if frame.opcode() & OPCODE_CONTROL != 0 {
+                encode_frame(frame.head, &frame.payload, true, &mut out);
+            } else if let Some((opcode, mut payload)) = self.fragmented.take() {
+                if frame.opcode() != OPCODE_CONTINUATION {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Expected a continuation frame"));
+                }
+                payload.extend_from_slice(&frame.payload);
+                if frame.is_final() {
+                    encode_frame(FIN | opcode, &inflate(&payload)?, true, &mut out);
+                } else {
+                    self.fragmented = Some((opcode, payload));
+                }
+            } else if frame.head & RSV1 != 0 {
+                if frame.is_final() {
+                    encode_frame(frame.head & !RSV1, &inflate(&frame.payload)?, true, &mut out);
+                } else {
+                    self.fragmented = Some((frame.opcode(), frame.payload));
+                }
+            } else {
+                encode_frame(frame.head, &frame.payload, true, &mut out);
+            }
+        }
+        Ok(out.freeze())
+    }
+}
+
+/// Payload length, header length and masking key of the frame at the start of the buffer
+fn frame_header(buffer: &[u8]) -> Option<(usize, usize, Option<[u8; 4]>)> {
+    if buffer.len() < 2 {
+        return None;
+    }
+    
+    let (len, offset) = match buffer[1] & 0x7f {
+        126 => (u16::from_be_bytes(buffer.get(2..4)?.try_into().ok()?) as usize, 4),
+        127 => (u64::from_be_bytes(buffer.get(2..10)?.try_into().ok()?) as usize, 10),
+        len => (len as usize, 2),
+    };
+    if buffer[1] & 0x80 == 0 {
+        return Some((len, offset, None));
+    }
+    let key: [u8; 4] = buffer.get(offset..offset + 4)?.try_into().ok()?;
+    Some((len, offset + 4, Some(key)))
+}
+
+/// Remove the next complete frame from the buffer, unmasking its payload
+fn take_frame(buffer: &mut BytesMut) -> Option<Frame> {
+    let (len, offset, mask) = frame_header(buffer)?;
+    if buffer.len() < offset.saturating_add(len) {
+        return None;
+    }
+    
+    let head = buffer[0];
+    let raw = buffer.split_to(offset + len);
+    let mut payload = raw[offset..].to_vec();
+    if let Some(key) = mask {
+        payload.iter_mut().enumerate().for_each(|(i, byte)| *byte ^= key[i % 4]);
+    }
+    Some(Frame { head, payload })
+}
+
+/// Append a frame; `masked` frames get a zero key, which leaves the payload as is
+fn encode_frame(head: u8, payload: &[u8], masked: bool, out: &mut BytesMut) {
+    let mask_bit = if masked { 0x80 } else { 0 };
+    out.extend_from_slice(&[head]);
+    match payload.len() {
+        len if len < 126 => out.extend_from_slice(&[mask_bit | len as u8]),
+        len if len <= u16::MAX as usize => {
+            out.extend_from_slice(&[mask_bit | 126]);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        },
+        len => {
+            out.extend_from_slice(&[mask_bit | 127]);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        },
+    }
+    if masked {
+        out.extend_from_slice(&[0; 4]);
+    }
+    out.extend_from_slice(payload);
+}
+
+/// Compress a message payload with a fresh context, without the sync flush tail
+fn deflate(payload: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(payload.len() / 2), Compression::default());
+    encoder.write_all(payload).ok()?;
+    encoder.flush().ok()?;
+    
+    let mut compressed = std::mem::take(encoder.get_mut());
+    if compressed.ends_with(&DEFLATE_TAIL) {
+        compressed.truncate(compressed.len() - DEFLATE_TAIL.len());
+    }
+    Some(compressed)
+}
+
+/// Decompress a message payload with a fresh context, up to `MAX_INFLATED_LEN`
+fn inflate(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    
+    let mut input = Vec::with_capacity(payload.len() + DEFLATE_TAIL.len());
+    input.extend_from_slice(payload);
+    input.extend_from_slice(&DEFLATE_TAIL);
+    
+    let mut decompress = Decompress::new(false);
+    let mut out = Vec::with_capacity((payload.len() * 4).clamp(256, MAX_INFLATED_LEN));
+    loop {
+        if out.len() >= MAX_INFLATED_LEN {
+            return Err(invalid("Inflated WebSocket message too large"));
+        }
+        if out.len() == out.capacity() {
+            // Grow by doubling, but never past the limit
+            out.reserve_exact(out.len().min(MAX_INFLATED_LEN - out.len()));
+        }
+        
+        let (read, written) = (decompress.total_in(), decompress.total_out());
+        let status = decompress
+            .decompress_vec(&input[read as usize..], &mut out, FlushDecompress::Sync)
+            .map_err(|_| invalid("Corrupt deflate data in WebSocket message"))?;
+        
+        let consumed = decompress.total_in() as usize == input.len();
+        let stalled = decompress.total_in() == read && decompress.total_out() == written;
+        if status == Status::StreamEnd || (consumed && out.len() < out.capacity()) {
+            break;
+        }
+        if stalled && out.len() < out.capacity() {
+            return Err(invalid("Truncated deflate data in WebSocket message"));
+        }
+    }
+    
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::stats::CaptureStats;
+    
+    /// A stats message of a busy capture, with a few hundred talkers
+    fn large_stats_message() -> String {
+        let mut stats = CaptureStats::default();
+        for i in 0..400 {
+            let address = format!("10.0.{}.{}", i / 250, i % 250 + 1);
+            stats.sources.insert(address.clone(), i * 3);
+            stats.destinations.insert(address, i * 5);
+        }
+        for protocol in ["TCP", "UDP", "DNS", "HTTP", "TLS", "ICMP", "ARP"] {
+            stats.protocols.insert(protocol.to_string(), 1000);
+        }
+        serde_json::json!({ "type": "stats", "stats": stats }).to_string()
+    }
+    
+    #[test]
+    fn large_stats_frames_shrink() {
+        let message = large_stats_message();
+        let mut plain = BytesMut::new();
+        encode_frame(FIN | OPCODE_TEXT, message.as_bytes(), false, &mut plain);
+        
+        let mut compressed = BytesMut::from(&Deflater::default().rewrite(&plain)[..]);
+        assert!(compressed.len() * 3 < plain.len(), "{} of {} bytes", compressed.len(), plain.len());
+        
+        let frame = take_frame(&mut compressed).expect("a complete frame");
+        assert_eq!(frame.head, FIN | RSV1 | OPCODE_TEXT);
+        assert_eq!(inflate(&frame.payload).unwrap(), message.as_bytes());
+    }
+    
+    #[test]
+    fn compressed_client_frames_are_inflated() {
+        let message = large_stats_message();
+        let compressed = deflate(&message.as_bytes()[..8000]).unwrap();
+        
+        // Split across two fragments and delivered in uneven chunks
+        let (first, second) = compressed.split_at(compressed.len() / 2);
+        let mut wire = BytesMut::new();
+        encode_frame(RSV1 | OPCODE_TEXT, first, true, &mut wire);
+        encode_frame(FIN | OPCODE_CONTINUATION, second, true, &mut wire);
+        encode_frame(FIN | 0x9, b"ping", true, &mut wire);
+        
+        let mut inflater = Inflater::default();
+        let mut out = BytesMut::new();
+        for chunk in wire.chunks(700) {
+            out.extend_from_slice(&inflater.rewrite(chunk).unwrap());
+        }
+        
+        let text = take_frame(&mut out).unwrap();
+        assert_eq!(text.head, FIN | OPCODE_TEXT);
+        assert_eq!(text.payload, &message.as_bytes()[..8000]);
+        assert_eq!(take_frame(&mut out).unwrap().payload, b"ping");
+    }
+    
+    #[test]
+    fn inflating_stops_at_the_limit() {
+        let bomb = deflate(&vec![b'a'; MAX_INFLATED_LEN * 8]).unwrap();
+        assert_eq!(inflate(&bomb).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        
+        let largest = vec![b'a'; MAX_INFLATED_LEN - 1];
+        let inflated = inflate(&deflate(&largest).unwrap()).unwrap();
+        assert_eq!(inflated, largest);
+        assert!(inflated.capacity() <= MAX_INFLATED_LEN);
+    }
+    
+    #[test]
+    fn small_frames_and_declined_offers() {
+        let mut plain = BytesMut::new();
+        encode_frame(FIN | OPCODE_TEXT, b"{\"type\":\"ping\"}", false, &mut plain);
+        assert_eq!(Deflater::default().rewrite(&plain), plain.freeze());
+        
+        assert!(acceptable_offer("permessage-deflate; client_max_window_bits"));
+        assert!(acceptable_offer("permessage-deflate; server_max_window_bits=15"));
+        assert!(!acceptable_offer("permessage-deflate; server_max_window_bits=10"));
+        assert!(!acceptable_offer("x-webkit-deflate-frame"));
+    }
+}
@@ -0,0 +1,86 @@
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+use crate::models::packet::Packet;
+use crate::models::wifi::AccessPoint;
+
+/// Maximum number of access points kept; new BSSIDs are ignored beyond this
+const MAX_ACCESS_POINTS: usize = 4096;
+
+/// Table of Wi-Fi access points announced by beacons and probe responses, keyed by BSSID
+#[derive(Clone, Default)]
+pub struct AccessPointTable {
+    access_points: Arc<DashMap<String, AccessPoint>>,
+    
+    /// Serializes inserts so the size cap holds
+    insert_lock: Arc<Mutex<()>>,
+}
+
+impl AccessPointTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add or refresh the access point announced by a beacon or probe response
+    pub fn record(&self, packet: &Packet) {
+        let Some(announced) = Self::from_packet(packet) else { return };
+        
+        if let Some(mut existing) = self.access_points.get_mut(&announced.bssid) {
+            existing.ssid = announced.ssid.or(existing.ssid.take());
+            existing.channel = announced.channel.or(existing.channel);
+            existing.signal_dbm = announced.signal_dbm.or(existing.signal_dbm);
+            existing.privacy = announced.privacy;
+            existing.beacons += announced.beacons;
+            existing.probe_responses += announced.probe_responses;
+            existing.last_seen = existing.last_seen.max(announced.last_seen);
+            return;
+        }
+        
+        let _guard = self.insert_lock.lock();
+        if self.access_points.len() < MAX_ACCESS_POINTS {
+            self.access_points.entry(announced.bssid.clone()).or_insert(announced);
+        }
+    }
+    
+    /// All known access points, strongest signal first
+    pub fn list(&self) -> Vec<AccessPoint> {
+        let mut access_points: Vec<AccessPoint> = self.access_points.iter().map(|ap| ap.value().clone()).collect();
+        access_points.sort_by_key(|ap| std::cmp::Reverse(ap.signal_dbm));
+        access_points
+    }
+    
+    /// Forget all access points
+    pub fn clear(&self) {
+        self.access_points.clear();
+    }
+    
+    /// Build an access point entry from a decoded beacon or probe response
+    fn from_packet(packet: &Packet) -> Option<AccessPoint> {
+        let wifi = packet.headers.get("wifi")?;
+        let subtype = wifi["subtype_name"].as_str()?;
+        if subtype != "Beacon" && subtype != "Probe Response" {
+            return None;
+        }
+        let radiotap = &packet.headers["radiotap"];
+        
+        // A hidden network announces an empty or zeroed SSID
+        let ssid = wifi["ssid"]
+            .as_str()
+            .filter(|_| wifi["hidden_ssid"].as_bool() != Some(true))
+            .map(str::to_string);
+        
+        Some(AccessPoint {
+            bssid: wifi["bssid"].as_str()?.to_string(),
+            ssid,
+            channel: wifi["channel"].as_u64().or_else(|| radiotap["channel"].as_u64()),
+            signal_dbm: radiotap["signal_dbm"].as_i64(),
+            privacy: wifi["privacy"].as_bool().unwrap_or(false),
+            beacons: (subtype == "Beacon") as u64,
+            probe_responses: (subtype == "Probe Response") as u64,
+            first_seen: packet.timestamp,
+            last_seen: packet.timestamp,
+        })
+    }
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::models::alert::{Alert, AlertKind};
+use crate::models::config::AppConfig;
+use crate::models::packet::Packet;
+
+/// Maximum number of alerts retained in the alert log
+pub const MAX_ALERTS: usize = 1000;
+
+/// Thresholds used by the detector
+#[derive(Debug, Clone)]
+pub struct DetectionSettings {
+    /// Length of the sliding window in seconds
+    pub window_secs: u64,
+    
+    /// SYN-without-ACK packets per source within the window that trigger an alert (0 disables)
+    pub syn_flood_threshold: usize,
+    
+    /// Distinct destination ports per source within the window that trigger an alert (0 disables)
+    pub port_scan_threshold: usize,
+    
+    /// Sources tracked at once; SYNs of further sources are not inspected (0 for no cap)
+    pub max_sources: usize,
+}
+
+impl DetectionSettings {
+    /// Build detection settings from the application configuration
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            window_secs: config.detection_window_secs.max(1),
+            syn_flood_threshold: config.syn_flood_threshold,
+            port_scan_threshold: config.port_scan_threshold,
+            max_sources: config.max_tracked_addresses,
+        }
+    }
+}
+
+/// A threshold crossing found by the detector
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub kind: AlertKind,
+    pub source_ip: IpAddr,
+    pub count: usize,
+    pub threshold: usize,
+}
+
+/// Per-source activity within the sliding window
+#[derive(Default)]
+struct SourceActivity {
+    /// Timestamps and destination ports of SYN-without-ACK packets, recorded
+    /// only until they are enough to cross the thresholds
+    syns: VecDeque<(DateTime<Utc>, u16)>,
+    
+    /// Recorded SYNs per destination port
+    ports: HashMap<u16, usize>,
+    
+    /// Last time each kind of alert was raised for this source
+    last_syn_alert: Option<DateTime<Utc>>,
+    last_scan_alert: Option<DateTime<Utc>>,
+}
+
+/// Detects SYN floods and port scans from per-source sliding windows.
+///
+/// Only TCP segments with SYN set and ACK clear are considered, since those
+/// are connection attempts. A source raises at most one alert of each kind
+/// per window so a sustained flood doesn't fill the alert log.
+pub struct ThreatDetector {
+    settings: DetectionSettings,
+    sources: HashMap<IpAddr, SourceActivity>,
+    last_prune: Option<DateTime<Utc>>,
+}
+
+impl ThreatDetector {
+    /// Create a detector with the given thresholds
+    pub fn new(settings: DetectionSettings) -> Self {
+        Self {
+            settings,
+            sources: HashMap::new(),
+            last_prune: None,
+        }
+    }
+    
+    /// Inspect a parsed packet and return any alerts it triggers
+    pub fn inspect(&mut self, packet: &Packet) -> Vec<Detection> {
+        let mut triggered = Vec::new();
+        
+        if self.settings.syn_flood_threshold == 0 && self.settings.port_scan_threshold == 0 {
+            return triggered;
+        }
+        
+        let flags = &packet.headers["tcp"]["flags"];
+        let is_syn = flags["syn"].as_bool().unwrap_or(false) && !flags["ack"].as_bool().unwrap_or(false);
+        
+        let (source, dest_port) = match (packet.source_ip, packet.destination_port) {
+            (Some(source), Some(port)) if is_syn => (source, port),
+            _ => return triggered,
+        };
+        
+        let now = packet.timestamp;
+        let window = Duration::seconds(self.settings.window_secs as i64);
+        let cutoff = now - window;
+        
+        self.prune(now, cutoff);
+        
+        // Past the cap, new sources are only taken once inactive ones are dropped
+        let max_sources = self.settings.max_sources;
+        if max_sources > 0 && self.sources.len() >= max_sources && !self.sources.contains_key(&source) {
+            self.last_prune = None;
+            self.prune(now, cutoff);
+            if self.sources.len() >= max_sources {
+                return triggered;
+            }
+        }
+        
+        let activity = self.sources.entry(source).or_default();
+        
+        // Slide the window forward
+        while let Some(&(t, port)) = activity.syns.front() {
+            if t >= cutoff {
+                break;
+            }
+            activity.syns.pop_front();
+            if let Some(count) = activity.ports.get_mut(&port) {
+                *count -= 1;
+                if *count == 0 {
+                    activity.ports.remove(&port);
+                }
+            }
+        }
+        
+        // Record the SYN while it can still make a difference: until the SYN
+        // count passes its threshold, and past that only for ports new to the
+        // window until the port count passes its own
+        let syn_threshold = self.settings.syn_flood_threshold;
+        let scan_threshold = self.settings.port_scan_threshold;
+        let new_port = !activity.ports.contains_key(&dest_port);
+        if activity.syns.len() <= syn_threshold || (new_port && activity.ports.len() <= scan_threshold) {
+            activity.syns.push_back((now, dest_port));
+            *activity.ports.entry(dest_port).or_default() += 1;
+        }
+        
+        // SYN flood: too many connection attempts from one source
+        if syn_threshold > 0 && activity.syns.len() > syn_threshold
+            && activity.last_syn_alert.is_none_or(|t| t < cutoff)
+        {
+            activity.last_syn_alert = Some(now);
+            triggered.push(Detection {
+                kind: AlertKind::SynFlood,
+                source_ip: source,
+                count: activity.syns.len(),
+                threshold: syn_threshold,
+            });
+        }
+        
+        // Port scan: too many distinct destination ports from one source
+        if scan_threshold > 0 {
+            let distinct_ports = activity.ports.len();
+            if distinct_ports > scan_threshold && activity.last_scan_alert.is_none_or(|t| t < cutoff) {
+                activity.last_scan_alert = Some(now);
+                triggered.push(Detection {
+                    kind: AlertKind::PortScan,
+                    source_ip: source,
+                    count: distinct_ports,
+                    threshold: scan_threshold,
+                });
+            }
+        }
+        
+        triggered
+    }
+    
+    /// Drop sources with no activity inside the window, at most once per window
+    fn prune(&mut self, now: DateTime<Utc>, cutoff: DateTime<Utc>) {
+        if self.last_prune.is_some_and(|t| t >= cutoff) {
+            return;
+        }
+        
+        self.sources.retain(|_, activity| activity.syns.back().is_some_and(|(t, _)| *t >= cutoff));
+        self.last_prune = Some(now);
+    }
+    
+    /// Window length used by this detector
+    pub fn window_secs(&self) -> u64 {
+        self.settings.window_secs
+    }
+}
+
+/// Bounded, shareable log of raised alerts with a broadcast feed
+#[derive(Clone)]
+pub struct AlertLog {
+    alerts: Arc<Mutex<VecDeque<Alert>>>,
+    next_id: Arc<Mutex<u64>>,
+    tx: broadcast::Sender<Alert>,
+}
+
+impl Default for AlertLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlertLog {
+    /// Create an empty alert log
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(100);
+        
+        Self {
+            alerts: Arc::new(Mutex::new(VecDeque::new())),
+            next_id: Arc::new(Mutex::new(1)),
+            tx,
+        }
+    }
+    
+    /// Record an alert, evicting the oldest one when the log is full
+    pub fn record(&self, detection: Detection, window_secs: u64, timestamp: DateTime<Utc>) {
+        let Detection { kind, source_ip, count, threshold } = detection;
+        
+        let message = match kind {
+            AlertKind::SynFlood => format!(
+                "Possible SYN flood from {}: {} SYNs without ACK in {}s (threshold {})",
+                source_ip, count, window_secs, threshold
+            ),
+            AlertKind::PortScan => format!(
+                "Possible port scan from {}: {} distinct destination ports in {}s (threshold {})",
+                source_ip, count, window_secs, threshold
+            ),
+        };
+        warn!("{}", message);
+        
+        let id = {
+            let mut next_id = self.next_id.lock();
+            let id = *next_id;
+            *next_id += 1;
+            id
+        };
+        
+        let alert = Alert {
+            id,
+            timestamp,
+            kind,
+            source_ip,
+            count,
+            threshold,
+            window_secs,
+            message,
+        };
+        
+        {
+            let mut alerts = self.alerts.lock();
+            alerts.push_back(alert.clone());
+            while alerts.len() > MAX_ALERTS {
+                alerts.pop_front();
+            }
+        }
+        
+        let _ = self.tx.send(alert);
+    }
+    
+    /// Get the most recent alerts, oldest first
+    pub fn recent(&self, limit: usize) -> Vec<Alert> {
+        let alerts = self.alerts.lock();
+        let skip = alerts.len().saturating_sub(limit);
+        alerts.iter().skip(skip).cloned().collect()
+    }
+    
+    /// Number of alerts currently retained
+    pub fn count(&self) -> usize {
+        self.alerts.lock().len()
+    }
+    
+    /// Remove all alerts
+    pub fn clear(&self) {
+        self.alerts.lock().clear();
+    }
+    
+    /// Subscribe to newly raised alerts
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.tx.subscribe()
+    }
+}
@@ -0,0 +1,137 @@
+// BPF filter expressions read from files
+//
+// A filter file holds one expression spread over as many lines as is
+// readable: `#` starts a comment that runs to the end of the line, and a
+// trailing backslash (optional, as lines are joined anyway) marks a
+// continuation. The pieces are joined with single spaces.
+//
+// Compiled programs are disassembled the way libpcap's bpf_image() (and so
+// `tcpdump -d`) prints them.
+
+use anyhow::{anyhow, Context, Result};
+use pcap::{Capture, Linktype};
+use std::path::Path;
+
+use crate::models::filter::BpfInstruction;
+
+/// Read a filter file and assemble its expression
+pub fn read_filter_file(path: &Path) -> Result<String> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read filter file {}", path.display()))?;
+    
+    let expression = assemble(&text);
+    if expression.is_empty() {
+        return Err(anyhow!("Filter file {} contains no expression", path.display()));
+    }
+    Ok(expression)
+}
+
+/// Join the lines of a filter file, dropping comments and continuation backslashes
+fn assemble(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_once('#').map_or(line, |(code, _)| code))
+        .map(|line| line.trim().trim_end_matches('\\').trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Check that an expression compiles, without opening a capture device
+pub fn validate(expression: &str) -> Result<()> {
+    let capture = Capture::dead(Linktype::ETHERNET)?;
+    capture
+        .compile(expression, true)
+        .map(|_| ())
+        .map_err(|e| anyhow!("Invalid BPF filter '{}': {}", expression, e))
+}
+
+/// Compile an expression for a link type and disassemble the resulting program
+pub fn compile(expression: &str, link_type: Linktype, optimize: bool) -> Result<Vec<BpfInstruction>> {
+    let capture = Capture::dead(link_type)?;
+    let program = capture
+        .compile(expression, optimize)
+        .map_err(|e| anyhow!("Invalid BPF filter '{}': {}", expression, e))?;
+    
+    program.get_instructions().iter().enumerate().map(|(index, instruction)| {
+        // The instruction's fields are private; its Display is "code jt jf k"
+        let raw = instruction.to_string();
+        let fields: Vec<u32> = raw.split(' ').filter_map(|field| field.parse().ok()).collect();
+        match fields[..] {
+            [code, jt, jf, k] => {
+                let (code, jt, jf) = (code as u16, jt as u8, jf as u8);
+                Ok(BpfInstruction { code, jt, jf, k, text: disassemble(index, code, jt, jf, k) })
+            },
+            _ => Err(anyhow!("Unexpected BPF instruction '{}'", raw)),
+        }
+    }).collect()
+}
+
+/// Render instruction `index` like libpcap's bpf_image()
+fn disassemble(index: usize, code: u16, jt: u8, jf: u8, k: u32) -> String {
+    let signed = k as i32;
+    let (op, operand) = match code {
+        0x06 => ("ret", format!("#{}", signed)),
+        0x16 => ("ret", String::new()),
+        
+        0x20 => ("ld", format!("[{}]", signed)),
+        0x28 => ("ldh", format!("[{}]", signed)),
+        0x30 => ("ldb", format!("[{}]", signed)),
+        0x80 => ("ld", "#pktlen".to_string()),
+        0x40 => ("ld", format!("[x + {}]", signed)),
+        0x48 => ("ldh", format!("[x + {}]", signed)),
+        0x50 => ("ldb", format!("[x + {}]", signed)),
+        0x00 => ("ld", format!("#0x{:x}", k)),
+        0x01 => ("ldx", format!("#0x{:x}", k)),
+        0x81 => ("ldx", "#pktlen".to_string()),
+        0xb1 => ("ldxb", format!("4*([{}]&0xf)", signed)),
+        0x60 => ("ld", format!("M[{}]", signed)),
+        0x61 => ("ldx", format!("M[{}]", signed)),
+        0x02 => ("st", format!("M[{}]", signed)),
+        0x03 => ("stx", format!("M[{}]", signed)),
+        
+        0x05 => ("ja", (index as i64 + 1 + k as i64).to_string()),
+        0x15 => ("jeq", format!("#0x{:x}", k)),
+        0x25 => ("jgt", format!("#0x{:x}", k)),
+        0x35 => ("jge", format!("#0x{:x}", k)),
+        0x45 => ("jset", format!("#0x{:x}", k)),
+        0x1d => ("jeq", "x".to_string()),
+        0x2d => ("jgt", "x".to_string()),
+        0x3d => ("jge", "x".to_string()),
+        0x4d => ("jset", "x".to_string()),
+        
+        0x04 => ("add", format!("#{}", signed)),
+        0x14 => ("sub", format!("#{}", signed)),
+        0x24 => ("mul", format!("#{}", signed)),
+        0x34 => ("div", format!("#{}", signed)),
+        0x94 => ("mod", format!("#{}", signed)),
+        0x44 => ("or", format!("#0x{:x}", k)),
+        0x54 => ("and", format!("#0x{:x}", k)),
+        0xa4 => ("xor", format!("#0x{:x}", k)),
+        0x64 => ("lsh", format!("#{}", signed)),
+        0x74 => ("rsh", format!("#{}", signed)),
+        0x0c => ("add", "x".to_string()),
+        0x1c => ("sub", "x".to_string()),
+        0x2c => ("mul", "x".to_string()),
+        0x3c => ("div", "x".to_string()),
+        0x9c => ("mod", "x".to_string()),
+        0x4c => ("or", "x".to_string()),
+        0x5c => ("and", "x".to_string()),
+        0xac => ("xor", "x".to_string()),
+        0x6c => ("lsh", "x".to_string()),
+        0x7c => ("rsh", "x".to_string()),
+        0x84 => ("neg", String::new()),
+        
+        0x07 => ("tax", String::new()),
+        0x87 => ("txa", String::new()),
+        
+        _ => ("unimp", format!("0x{:x}", code)),
+    };
+    
+    // Conditional jumps show both targets
+    if code & 0x07 == 0x05 && code != 0x05 {
+        format!("({:03}) {:<8} {:<16} jt {}\tjf {}",
+            index, op, operand, index + 1 + jt as usize, index + 1 + jf as usize)
+    } else {
+        format!("({:03}) {:<8} {}", index, op, operand)
+    }
+}
@@ -0,0 +1,278 @@
+// HTTP/1.x objects: the bodies of responses, and of requests uploading one
+//
+// Requests are read off the client's data and responses off the server's,
+// the Nth final response (1xx ones aside) answering the Nth request, which
+// names the host and the file. A body is framed by its chunked
+// transfer-coding, else its Content-Length, else (responses only) by the
+// server closing the connection; HEAD responses and 1xx, 204 and 304 ones
+// have none. gzip and deflate content-codings are decoded when an object is
+// downloaded, so it is the file itself. Reading a side stops at the first
+// thing that is not an HTTP message, as the framing of what follows is lost.
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use std::io::Read;
+
+use crate::capture::carving::{CarvedObject, Direction, ObjectCarver, Reassembled};
+use crate::models::object::StreamObject;
+
+/// Longest message head (start line and header fields) looked for
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+/// Largest decoded body kept; bigger ones are left encoded
+const MAX_DECODED_LEN: u64 = 64 * 1024 * 1024;
+
+/// Carves the bodies of HTTP/1.x messages
+pub struct HttpCarver;
+
+impl ObjectCarver for HttpCarver {
+    fn carve(&self, stream: &Reassembled) -> Vec<CarvedObject> {
+        let requests = messages(&stream.client, false, |_| false);
+        let mut answered = requests.iter();
+        let mut request_for_response = Vec::new();
+        let responses = messages(&stream.server, true, |status| {
+            // Interim responses precede the final one to the same request
+            let request = if (100..200).contains(&status) { None } else { answered.next() };
+            let no_body = matches!(status, 100..=199 | 204 | 304) || request.is_some_and(|request| request.method() == Some("HEAD"));
+            request_for_response.push(request);
+            no_body
+        });
+        
+        let mut objects: Vec<CarvedObject> = requests
+            .iter()
+            .filter(|request| !request.body.is_empty())
+            .map(|request| request.object(Some(request), &stream.client))
+            .chain(
+                responses
+                    .iter()
+                    .zip(request_for_response)
+                    .filter(|(response, _)| !response.body.is_empty())
+                    .map(|(response, request)| response.object(request, &stream.server)),
+            )
+            .collect();
+        objects.sort_by_key(|carved| carved.object.packet_id);
+        objects
+    }
+    
+    fn decode(&self, object: &StreamObject, data: Vec<u8>) -> Vec<u8> {
+        match object.content_encoding.as_deref().and_then(|encoding| decode(encoding, &data)) {
+            Some(decoded) => decoded,
+            None => data,
+        }
+    }
+}
+
+/// A request or response with its (transfer-decoded) body
+struct Message {
+    /// Offset of the start line in its side's data
+    offset: usize,
+    start_line: String,
+    fields: Vec<(String, String)>,
+    body: Vec<u8>,
+    complete: bool,
+}
+
+impl Message {
+    /// Value of a header field, by case-insensitive name
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+    
+    fn status_code(&self) -> Option<u16> {
+        let status = self.start_line.strip_prefix("HTTP/")?.split(' ').nth(1)?;
+        status.parse().ok().filter(|status| (100..600).contains(status))
+    }
+    
+    fn method(&self) -> Option<&str> {
+        self.status_code().is_none().then(|| self.start_line.split(' ').next()).flatten()
+    }
+    
+    fn target(&self) -> Option<&str> {
+        self.method().and(self.start_line.split(' ').nth(1))
+    }
+    
+    /// The object this message's body is, transferred in answer to `request`
+    /// (or by it, for an upload)
+    fn object(&self, request: Option<&Message>, side: &Direction) -> CarvedObject {
+        let content_encoding = self
+            .field("Content-Encoding")
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .filter(|encoding| encoding != "identity");
+        
+        let status_code = self.status_code();
+        CarvedObject {
+            object: StreamObject {
+                index: 0,
+                protocol: "HTTP".to_string(),
+                packet_id: side.packet_at(self.offset),
+                hostname: request.and_then(|request| request.field("Host")).map(str::to_string),
+                request: request.and_then(|request| Some(format!("{} {}", request.method()?, request.target()?))),
+                status_code,
+                upload: status_code.is_none(),
+                filename: filename(request.and_then(Message::target).unwrap_or_default()),
+                content_type: self.field("Content-Type").map(str::to_string),
+                content_encoding,
+                size: self.body.len(),
+                complete: self.complete,
+            },
+            data: self.body.clone(),
+        }
+    }
+}
+
+/// How the end of a body is found
+enum Framing {
+    Empty,
+    Length(usize),
+    Chunked,
+    UntilClose,
+}
+
+/// The requests (or `responses`) at the start of one side's data. `no_body`
+/// tells from a response's status code (called in order) whether it has no body.
+fn messages(side: &Direction, responses: bool, mut no_body: impl FnMut(u16) -> bool) -> Vec<Message> {
+    let data = &side.data;
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let Some((head_len, mut message)) = head(&data[offset..], offset) else { break };
+        if message.status_code().is_some() != responses {
+            break;
+        }
+        
+        let framing = match message.status_code() {
+            Some(status) if no_body(status) => Framing::Empty,
+            _ if message.field("Transfer-Encoding").is_some_and(|coding| coding.to_ascii_lowercase().contains("chunked")) => Framing::Chunked,
+            _ => match message.field("Content-Length").map(|length| length.trim().parse::<usize>()) {
+                Some(Ok(length)) => Framing::Length(length),
+                Some(Err(_)) => break,
+                None if message.status_code().is_some() => Framing::UntilClose,
+                None => Framing::Empty,
+            },
+        };
+        
+        let body = &data[offset + head_len..];
+        let consumed = match framing {
+            Framing::Empty => 0,
+            Framing::Length(length) => {
+                let available = length.min(body.len());
+                message.body = body[..available].to_vec();
+                message.complete = available == length;
+                available
+            },
+            Framing::Chunked => {
+                let (chunks, consumed, complete) = dechunk(body);
+                message.body = chunks;
+                message.complete = complete;
+                consumed
+            },
+            Framing::UntilClose => {
+                message.body = body.to_vec();
+                message.complete = side.closed;
+                body.len()
+            },
+        };
+        offset += head_len + consumed;
+        messages.push(message);
+    }
+    messages
+}
+
+/// The length of a message head and the message without its body, if the
+/// data (at `offset` of its side) starts with a whole HTTP/1.x head
+fn head(data: &[u8], offset: usize) -> Option<(usize, Message)> {
+    let window = &data[..data.len().min(MAX_HEAD_LEN)];
+    let end = window.windows(4).position(|bytes| bytes == b"\r\n\r\n")?;
+    let text = std::str::from_utf8(&data[..end]).ok()?;
+    let mut lines = text.split("\r\n");
+    let start_line = lines.next()?;
+    
+    let mut parts = start_line.split(' ');
+    let is_response = start_line.starts_with("HTTP/1.");
+    let is_request = matches!(
+        (parts.next(), parts.next(), parts.next(), parts.next()),
+        (Some(method), Some(target), Some(version), None)
+            if !method.is_empty() && method.bytes().all(|b| b.is_ascii_uppercase()) && !target.is_empty() && version.starts_with("HTTP/1.")
+    );
+    if !is_response && !is_request {
+        return None;
+    }
+    
+    let fields = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    Some((end + 4, Message { offset, start_line: start_line.to_string(), fields, body: Vec::new(), complete: true }))
+}
+
+/// The body of a chunked message, the bytes it took and whether its last
+/// chunk (and trailer) was seen
+fn dechunk(data: &[u8]) -> (Vec<u8>, usize, bool) {
+    let line_end = |from: usize| data[from..].windows(2).position(|bytes| bytes == b"\r\n").map(|end| from + end);
+    let mut body = Vec::new();
+    let mut offset = 0;
+    loop {
+        // Chunk size in hex, possibly followed by extensions
+        let Some(end) = line_end(offset) else { return (body, data.len(), false) };
+        let size = std::str::from_utf8(&data[offset..end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next().unwrap_or_default().trim(), 16).ok());
+        let Some(size) = size else { return (body, data.len(), false) };
+        offset = end + 2;
+        
+        if size == 0 {
+            // Trailer fields, up to an empty line
+            while let Some(end) = line_end(offset) {
+                let empty = end == offset;
+                offset = end + 2;
+                if empty {
+                    return (body, offset, true);
+                }
+            }
+            return (body, data.len(), false);
+        }
+        
+        let available = size.min(data.len() - offset);
+        body.extend_from_slice(&data[offset..offset + available]);
+        if available < size || !data[offset + size..].starts_with(b"\r\n") {
+            return (body, data.len(), false);
+        }
+        offset += size + 2;
+    }
+}
+
+/// A body with its content-coding undone, None for codings not decoded or data that does not decode
+fn decode(encoding: &str, body: &[u8]) -> Option<Vec<u8>> {
+    let read = |reader: &mut dyn Read| {
+        let mut decoded = Vec::new();
+        reader.take(MAX_DECODED_LEN + 1).read_to_end(&mut decoded).ok()?;
+        (decoded.len() as u64 <= MAX_DECODED_LEN).then_some(decoded)
+    };
+    match encoding {
+        "gzip" | "x-gzip" => read(&mut GzDecoder::new(body)),
+        // Servers send zlib-wrapped data as RFC 9110 says, or raw deflate as some always did
+        "deflate" => read(&mut ZlibDecoder::new(body)).or_else(|| read(&mut DeflateDecoder::new(body))),
+        _ => None,
+    }
+}
+
+/// The last segment of a request target's path, safe as a download name
+fn filename(target: &str) -> String {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    // An absolute-form target (through a proxy) starts with the scheme and authority
+    let path = match path.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => path,
+    };
+    let name: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_control() || matches!(c, '"' | '\\') { '_' } else { c })
+        .collect();
+    if name.is_empty() || name == "." || name == ".." {
+        "object".to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,218 @@
+// Files transferred over a TCP connection, carved out for export
+//
+// Like Wireshark's "Export Objects": both directions of the connection a
+// packet belongs to are reassembled from the stored segments in sequence
+// order, retransmitted bytes taken once. A gap (a segment the capture missed
+// or no longer stores) ends a direction, as nothing after it can be placed.
+// The client is the side that sent the SYN, or else the one on the higher
+// port. Each carver in `CARVERS` then looks for the transfers of its protocol
+// in the two byte streams, the first to find any winning. HTTP is carved so
+// far; FTP data connections or SMB reads plug in as further carvers.
+//
+// Carving runs on a copy of the connection's segments, away from the capture
+// manager, and leaves content codings (gzip bodies, say) to the download of
+// the one object asked for, so a listing never decompresses anything.
+
+pub mod http;
+
+use chrono::{DateTime, Utc};
+
+use crate::capture::inflight;
+use crate::capture::tcp_analysis::{seq_before, Segment};
+use crate::models::object::{StreamObject, StreamObjects};
+use crate::models::packet::Packet;
+
+/// Finds the transfers of one protocol in a reassembled connection
+pub trait ObjectCarver: Sync {
+    /// The objects transferred, in the order they were sent; none when the
+    /// stream does not carry the protocol
+    fn carve(&self, stream: &Reassembled) -> Vec<CarvedObject>;
+    
+    /// The file a carved object's data is, undoing the content coding it was
+    /// sent with; only called for an object being downloaded
+    fn decode(&self, _object: &StreamObject, data: Vec<u8>) -> Vec<u8> {
+        data
+    }
+}
+
+/// Carvers tried on a stream, in order
+static CARVERS: &[&dyn ObjectCarver] = &[&http::HttpCarver];
+
+/// An object and its data as transferred; the carver leaves `index` to the listing
+pub struct CarvedObject {
+    pub object: StreamObject,
+    pub data: Vec<u8>,
+}
+
+/// The objects of a connection with their data, in listing order
+pub struct CarvedStream {
+    pub objects: StreamObjects,
+    data: Vec<Vec<u8>>,
+    
+    /// Carver that found the objects, which decodes their data
+    carver: Option<&'static dyn ObjectCarver>,
+}
+
+impl CarvedStream {
+    /// Object `index` with its data decoded into the file
+    pub fn into_object(mut self, index: usize) -> Option<(StreamObject, Vec<u8>)> {
+        let object = self.objects.objects.get(index)?.clone();
+        let data = self.data.swap_remove(index);
+        let data = match self.carver {
+            Some(carver) => carver.decode(&object, data),
+            None => data,
+        };
+        Some((object, data))
+    }
+}
+
+/// A segment of the connection with the data it carries
+pub struct Piece {
+    timestamp: DateTime<Utc>,
+    packet_id: u64,
+    from_sender: bool,
+    segment: Segment,
+    payload: Vec<u8>,
+}
+
+/// The piece of `other` if it is a segment of the TCP connection of `packet`
+pub fn piece(packet: &Packet, other: &Packet) -> Option<Piece> {
+    Some(Piece {
+        timestamp: other.timestamp,
+        packet_id: other.id,
+        from_sender: inflight::side(packet, other)?,
+        segment: Segment::of(other)?,
+        payload: other.payload.clone().unwrap_or_default(),
+    })
+}
+
+/// The bytes one side of a connection sent, in sequence order
+#[derive(Debug, Default)]
+pub struct Direction {
+    pub data: Vec<u8>,
+    
+    /// Offset in `data` where the bytes each packet contributed start
+    starts: Vec<(usize, u64)>,
+    
+    /// Whether a missing segment cut the data short
+    pub gap: bool,
+    
+    /// Whether the side closed the connection (a FIN) right after the data
+    pub closed: bool,
+}
+
+impl Direction {
+    /// Packet that carried the byte at `offset`
+    pub fn packet_at(&self, offset: usize) -> Option<u64> {
+        let after = self.starts.partition_point(|&(start, _)| start <= offset);
+        after.checked_sub(1).map(|index| self.starts[index].1)
+    }
+}
+
+/// Both sides of a connection
+#[derive(Debug, Default)]
+pub struct Reassembled {
+    pub client: Direction,
+    pub server: Direction,
+}
+
+/// Carve the objects out of the TCP connection of `packet`, given its pieces.
+/// None when the packet is not TCP over IP.
+pub fn carve(packet: &Packet, mut pieces: Vec<Piece>) -> Option<CarvedStream> {
+    let (source_ip, destination_ip) = (packet.source_ip?, packet.destination_ip?);
+    let (source_port, destination_port) = (packet.source_port?, packet.destination_port?);
+    Segment::of(packet)?;
+    pieces.sort_unstable_by_key(|piece| (piece.timestamp, piece.packet_id));
+    
+    let opened_by = |from_sender: bool| {
+        pieces.iter().any(|piece| piece.from_sender == from_sender && piece.segment.syn && !piece.segment.has_ack)
+    };
+    let sender_is_client = if opened_by(true) || opened_by(false) {
+        opened_by(true)
+    } else {
+        source_port > destination_port
+    };
+    let (client_pieces, server_pieces): (Vec<&Piece>, Vec<&Piece>) =
+        pieces.iter().partition(|piece| piece.from_sender == sender_is_client);
+    let stream = Reassembled {
+        client: reassemble(&client_pieces),
+        server: reassemble(&server_pieces),
+    };
+    
+    let (carver, carved) = CARVERS
+        .iter()
+        .find_map(|&carver| {
+            let carved = carver.carve(&stream);
+            (!carved.is_empty()).then_some((carver, carved))
+        })
+        .unzip();
+    let (mut objects, data): (Vec<StreamObject>, Vec<Vec<u8>>) =
+        carved.unwrap_or_default().into_iter().map(|carved| (carved.object, carved.data)).unzip();
+    for (index, object) in objects.iter_mut().enumerate() {
+        object.index = index;
+    }
+    
+    let sender = format!("{}:{}", source_ip, source_port);
+    let receiver = format!("{}:{}", destination_ip, destination_port);
+    let (client, server) = if sender_is_client { (sender, receiver) } else { (receiver, sender) };
+    Some(CarvedStream {
+        objects: StreamObjects {
+            packet_id: packet.id,
+            client,
+            server,
+            client_bytes: stream.client.data.len(),
+            server_bytes: stream.server.data.len(),
+            gaps: stream.client.gap || stream.server.gap,
+            objects,
+        },
+        data,
+        carver,
+    })
+}
+
+/// The data of one side's segments (in capture order) put in sequence order
+fn reassemble(pieces: &[&Piece]) -> Direction {
+    // Data starts after the SYN, or else at the lowest sequence number seen
+    let base = match pieces.iter().find(|piece| piece.segment.syn) {
+        Some(syn) => syn.segment.seq.wrapping_add(1),
+        None => pieces
+            .iter()
+            .filter(|piece| !piece.payload.is_empty())
+            .map(|piece| piece.segment.seq)
+            .reduce(|lowest, seq| if seq_before(seq, lowest) { seq } else { lowest })
+            .unwrap_or_default(),
+    };
+    
+    // Offsets past half the sequence space lie before the base: data of an earlier connection
+    let mut segments: Vec<(usize, &Piece)> = pieces
+        .iter()
+        .filter(|piece| !piece.payload.is_empty())
+        .map(|piece| {
+            let start = piece.segment.seq.wrapping_add(piece.segment.syn as u32);
+            (start.wrapping_sub(base) as usize, *piece)
+        })
+        .filter(|&(offset, _)| offset < 1 << 31)
+        .collect();
+    segments.sort_by_key(|&(offset, _)| offset);
+    
+    let mut direction = Direction::default();
+    for (offset, piece) in segments {
+        let end = direction.data.len();
+        if offset > end {
+            direction.gap = true;
+            break;
+        }
+        // Retransmitted bytes are taken once, from the first copy
+        if let Some(new) = piece.payload.get(end - offset..).filter(|new| !new.is_empty()) {
+            direction.starts.push((end, piece.packet_id));
+            direction.data.extend_from_slice(new);
+        }
+    }
+    
+    let data_end = base.wrapping_add(direction.data.len() as u32);
+    direction.closed = !direction.gap
+        && pieces.iter().any(|piece| {
+            piece.segment.fin && piece.segment.seq.wrapping_add(piece.payload.len() as u32) == data_end
+        });
+    direction
+}
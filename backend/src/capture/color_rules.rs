@@ -0,0 +1,112 @@
+// Packet coloring rules
+//
+// Like Wireshark's coloring rules, each rule pairs a display filter with a
+// color label, and a packet takes the label of the first rule it matches.
+// Only the label is served; what it looks like is up to the client. The rules
+// are saved to a JSON file whenever they are replaced, and read back at start.
+
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use parking_lot::RwLock;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::capture::display_filter::DisplayFilter;
+use crate::models::filter::ColorRule;
+use crate::models::packet::{Packet, PacketSummary};
+use crate::utils::atomic_file;
+
+/// Rules with their parsed filters
+type CompiledRules = Vec<(ColorRule, DisplayFilter)>;
+
+/// The active coloring rules, in evaluation order
+#[derive(Clone, Default)]
+pub struct ColorRules {
+    rules: Arc<RwLock<CompiledRules>>,
+    
+    /// Where the rules are saved (not saved when None)
+    file: Option<PathBuf>,
+}
+
+impl ColorRules {
+    /// Load the rules saved in `file`, if it exists; rules that no longer parse are dropped
+    pub fn load(file: Option<PathBuf>) -> Self {
+        let mut compiled = Vec::new();
+        if let Some(path) = file.as_deref().filter(|path| path.exists()) {
+            match read(path) {
+                Ok(rules) => {
+                    for rule in rules {
+                        match DisplayFilter::parse(&rule.filter) {
+                            Ok(filter) => compiled.push((rule, filter)),
+                            Err(e) => warn!("Dropping color rule '{}' from {}: {}", rule.filter, path.display(), e),
+                        }
+                    }
+                    info!("Loaded {} color rules from {}", compiled.len(), path.display());
+                },
+                Err(e) => warn!("Failed to read color rules from {}: {}", path.display(), e),
+            }
+        }
+        
+        Self {
+            rules: Arc::new(RwLock::new(compiled)),
+            file,
+        }
+    }
+    
+    /// The rules, in evaluation order
+    pub fn list(&self) -> Vec<ColorRule> {
+        self.rules.read().iter().map(|(rule, _)| rule.clone()).collect()
+    }
+    
+    /// Parse every rule's filter, naming the first rule that does not parse
+    pub fn compile(rules: Vec<ColorRule>) -> Result<CompiledRules, String> {
+        rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                if rule.color.trim().is_empty() {
+                    return Err(format!("rule {}: color must not be empty", index));
+                }
+                let filter = DisplayFilter::parse(&rule.filter).map_err(|e| format!("rule {} ('{}'): {}", index, rule.filter, e))?;
+                Ok((rule, filter))
+            })
+            .collect()
+    }
+    
+    /// Save compiled rules and make them the active set
+    pub fn replace(&self, compiled: CompiledRules) -> Result<()> {
+        if let Some(path) = &self.file {
+            let rules: Vec<&ColorRule> = compiled.iter().map(|(rule, _)| rule).collect();
+            save(path, &rules)?;
+        }
+        *self.rules.write() = compiled;
+        Ok(())
+    }
+    
+    /// Color label of the first rule the packet matches
+    pub fn color_for(&self, packet: &Packet, summary: &PacketSummary) -> Option<String> {
+        let rules = self.rules.read();
+        if rules.is_empty() {
+            return None;
+        }
+        
+        // Compacted headers are expanded once for all the rules
+        let headers = packet.expanded_headers();
+        let summary = serde_json::to_value(summary).ok()?;
+        rules
+            .iter()
+            .find(|(_, filter)| filter.matches(packet, &headers, &summary))
+            .map(|(rule, _)| rule.color.clone())
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<ColorRule>> {
+    let data = fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| anyhow!("invalid color rules file: {}", e))
+}
+
+/// Write the rules atomically, replacing the old ones
+fn save(path: &Path, rules: &[&ColorRule]) -> Result<()> {
+    atomic_file::write(path, |file| Ok(serde_json::to_writer_pretty(file, rules)?))
+}
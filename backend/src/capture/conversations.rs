@@ -0,0 +1,429 @@
+// Conversations: traffic per 5-tuple, in both directions
+//
+// Packets are grouped by transport and their two endpoints, so both directions
+// of a connection land in the same conversation. Each keeps the application
+// protocols its packets were classified as: a TCP connection's handshake is
+// plain "TCP" while its data may be "HTTP", and a port can carry different
+// protocols over a long capture, so the set is kept alongside the latest one.
+//
+// In flow-head mode only the first packets of each conversation are kept; the
+// table decides, as it already counts them, and tallies what it turned away.
+//
+// A conversation's ID is derived from its key alone, so the same 5-tuple has
+// the same ID in every capture and the ID in a bug report stays meaningful.
+//
+// Grouped by host, conversations between the same two resolved hostnames over
+// the same transport are combined, so a dual-stack host's IPv4 and IPv6 flows
+// count as one; addresses without a name stay as they are.
+//
+// The busiest conversations also keep their bytes per second over the last
+// minute for sparklines. Rings are handed to the top flows by bytes once per
+// capture second, so their memory stays bounded however many flows there are.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use ring::digest;
+use std::collections::BTreeSet;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use crate::models::conversation::Conversation;
+use crate::models::packet::Packet;
+
+/// Maximum number of conversations tracked; new conversations are ignored beyond this
+const MAX_CONVERSATIONS: usize = 16_384;
+
+/// Length of a sparkline, in one-second buckets
+const SPARKLINE_SECONDS: usize = 60;
+
+/// Number of conversations (the most bytes first) that keep a sparkline
+const SPARKLINE_FLOWS: usize = 256;
+
+/// Transports whose header (when decoded) identifies the conversation's ports
+const PORT_TRANSPORTS: &[(&str, &str)] = &[("tcp", "TCP"), ("udp", "UDP"), ("sctp", "SCTP")];
+
+/// Transport and endpoints of a conversation, the lower endpoint first so
+/// both directions map to the same key
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ConversationKey {
+    transport: String,
+    low: (IpAddr, Option<u16>),
+    high: (IpAddr, Option<u16>),
+}
+
+/// A conversation and its protocol set while it is being tracked
+struct ConversationState {
+    conversation: Conversation,
+    protocols: BTreeSet<String>,
+    
+    /// Endpoint A, which packets of the forward direction come from
+    from: (IpAddr, Option<u16>),
+    
+    /// When endpoint A sent its SYN, until the SYN/ACK gives the handshake RTT
+    syn_seen: Option<DateTime<Utc>>,
+    
+    /// Recent bytes per second, while among the top `SPARKLINE_FLOWS`
+    sparkline: Option<Box<Sparkline>>,
+}
+
+/// Ring of per-second byte counts, indexed by capture second modulo its length
+struct Sparkline {
+    /// Capture second (Unix time) of the newest bucket
+    newest: i64,
+    buckets: [usize; SPARKLINE_SECONDS],
+}
+
+/// Tracks packet and byte counts and application protocols per conversation
+#[derive(Clone, Default)]
+pub struct ConversationTable {
+    conversations: Arc<DashMap<ConversationKey, ConversationState>>,
+    
+    /// Serializes inserts so the size cap holds
+    insert_lock: Arc<Mutex<()>>,
+    
+    /// Latest capture second seen; sparklines end here and are reassigned when it advances
+    latest_second: Arc<AtomicI64>,
+}
+
+impl ConversationTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Account for a packet in its conversation, if it has IP addresses.
+    ///
+    /// With a `head_packets` limit (0 for none), a packet past the first
+    /// `head_packets` of its conversation is only counted as skipped, and
+    /// false is returned so the caller drops it.
+    pub fn record(&self, packet: &Packet, data_len: usize, head_packets: usize) -> bool {
+        let Some((key, from)) = ConversationKey::of(packet) else { return true };
+        let transport = key.transport.clone();
+        let (source, source_port) = from;
+        let (destination, destination_port) = if key.low == from { key.high } else { key.low };
+        
+        let second = packet.timestamp.timestamp();
+        if self.latest_second.fetch_max(second, Ordering::Relaxed) < second {
+            self.assign_sparklines();
+        }
+        
+        if let Some(mut state) = self.conversations.get_mut(&key) {
+            return state.add(packet, from, &transport, data_len, head_packets);
+        }
+        
+        let _guard = self.insert_lock.lock();
+        if self.conversations.len() >= MAX_CONVERSATIONS && !self.conversations.contains_key(&key) {
+            return true;
+        }
+        
+        let id = key.id();
+        let mut state = self.conversations.entry(key).or_insert_with(|| ConversationState {
+            conversation: Conversation {
+                id,
+                transport: transport.clone(),
+                protocol: transport.clone(),
+                protocols: Vec::new(),
+                address_a: source.to_string(),
+                port_a: source_port,
+                address_b: destination.to_string(),
+                port_b: destination_port,
+                packets_a_to_b: 0,
+                bytes_a_to_b: 0,
+                packets_b_to_a: 0,
+                bytes_b_to_a: 0,
+                skipped_packets: 0,
+                skipped_bytes: 0,
+                first_seen: packet.timestamp,
+                last_seen: packet.timestamp,
+                handshake_rtt_ms: None,
+                sparkline: None,
+                merged_ids: Vec::new(),
+            },
+            protocols: BTreeSet::new(),
+            from,
+            syn_seen: None,
+            sparkline: None,
+        });
+        state.add(packet, from, &transport, data_len, head_packets)
+    }
+    
+    /// All conversations, or those that carried `protocol` (by transport or
+    /// application protocol, case-insensitively), most bytes first. With
+    /// `sparkline`, the busiest ones come with their recent bytes per second.
+    pub fn list(&self, protocol: Option<&str>, sparkline: bool) -> Vec<Conversation> {
+        let now = self.latest_second.load(Ordering::Relaxed);
+        let mut conversations: Vec<Conversation> = self.conversations
+            .iter()
+            .filter(|state| protocol.is_none_or(|protocol| state.matches(protocol)))
+            .map(|state| {
+                let mut conversation = state.conversation();
+                if sparkline {
+                    conversation.sparkline = state.sparkline.as_ref().map(|ring| ring.series(now));
+                }
+                conversation
+            })
+            .collect();
+        conversations.sort_by_key(|c| std::cmp::Reverse(c.bytes()));
+        conversations
+    }
+    
+    /// Whether a packet belongs to the conversation with the given ID (either
+    /// direction); None when no such conversation is tracked
+    pub fn member_filter(&self, id: &str) -> Option<impl Fn(&Packet) -> bool> {
+        let key = self.conversations
+            .iter()
+            .find(|state| state.conversation.id == id)
+            .map(|state| state.key().clone())?;
+        Some(move |packet: &Packet| ConversationKey::of(packet).is_some_and(|(other, _)| other == key))
+    }
+    
+    /// Forget all conversations
+    pub fn clear(&self) {
+        self.conversations.clear();
+        self.latest_second.store(0, Ordering::Relaxed);
+    }
+    
+    /// Give sparkline rings to the `SPARKLINE_FLOWS` conversations with the
+    /// most bytes and take them from the rest
+    fn assign_sparklines(&self) {
+        let _guard = self.insert_lock.lock();
+        let mut by_bytes: Vec<(usize, ConversationKey)> = self.conversations
+            .iter()
+            .map(|state| (state.conversation.bytes(), state.key().clone()))
+            .collect();
+        if by_bytes.len() > SPARKLINE_FLOWS {
+            by_bytes.select_nth_unstable_by_key(SPARKLINE_FLOWS, |&(bytes, _)| std::cmp::Reverse(bytes));
+            by_bytes.truncate(SPARKLINE_FLOWS);
+        }
+        let top: HashSet<ConversationKey> = by_bytes.into_iter().map(|(_, key)| key).collect();
+        
+        for mut state in self.conversations.iter_mut() {
+            if !top.contains(state.key()) {
+                state.sparkline = None;
+            } else if state.sparkline.is_none() {
+                state.sparkline = Some(Box::new(Sparkline::new()));
+            }
+        }
+    }
+    
+    /// The transport of a packet: TCP, UDP or SCTP when one was decoded, else
+    /// the packet's own protocol (ICMP, OSPF, ...)
+    pub fn transport(packet: &Packet) -> String {
+        let headers = packet.expanded_headers();
+        PORT_TRANSPORTS
+            .iter()
+            .find(|&&(header, _)| headers.get(header).is_some())
+            .map_or_else(|| packet.protocol.clone(), |&(_, name)| name.to_string())
+    }
+}
+
+impl ConversationKey {
+    /// Key of a packet's conversation and the packet's sending endpoint, if it
+    /// has IP addresses
+    fn of(packet: &Packet) -> Option<(Self, (IpAddr, Option<u16>))> {
+        let (source, destination) = (packet.source_ip?, packet.destination_ip?);
+        let transport = ConversationTable::transport(packet);
+        let (source_port, destination_port) = if PORT_TRANSPORTS.iter().any(|&(_, name)| name == transport) {
+            (packet.source_port, packet.destination_port)
+        } else {
+            (None, None)
+        };
+        
+        let from = (source, source_port);
+        let to = (destination, destination_port);
+        let key = Self {
+            transport,
+            low: from.min(to),
+            high: from.max(to),
+        };
+        Some((key, from))
+    }
+    
+    /// Stable ID: the first 8 bytes of a SHA-256 over the normalized tuple, in hex
+    fn id(&self) -> String {
+        let endpoint = |(ip, port): (IpAddr, Option<u16>)| format!("{}/{}", ip, port.map_or(String::new(), |p| p.to_string()));
+        short_digest(&format!("{}|{}|{}", self.transport, endpoint(self.low), endpoint(self.high)))
+    }
+}
+
+/// ID of the conversation a packet belongs to, if it has IP addresses
+pub fn conversation_id(packet: &Packet) -> Option<String> {
+    ConversationKey::of(packet).map(|(key, _)| key.id())
+}
+
+/// First 8 bytes of a SHA-256 of `text`, in hex
+fn short_digest(text: &str) -> String {
+    digest::digest(&digest::SHA256, text.as_bytes()).as_ref()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Combine conversations between the same hosts over the same transport,
+/// naming endpoints by `hostnames` (address to resolved name) where known.
+///
+/// Ports are kept when all the combined conversations agree on them. The
+/// result is sorted most bytes first, like `ConversationTable::list`.
+pub fn group_by_host(conversations: Vec<Conversation>, hostnames: &HashMap<String, String>) -> Vec<Conversation> {
+    let host = |address: &String| hostnames.get(address).unwrap_or(address).clone();
+    let mut groups: HashMap<(String, String, String), Conversation> = HashMap::new();
+    
+    for mut conversation in conversations {
+        conversation.address_a = host(&conversation.address_a);
+        conversation.address_b = host(&conversation.address_b);
+        let key = (
+            conversation.transport.clone(),
+            conversation.address_a.clone().min(conversation.address_b.clone()),
+            conversation.address_a.clone().max(conversation.address_b.clone()),
+        );
+        
+        match groups.get_mut(&key) {
+            Some(group) => merge(group, conversation),
+            None => {
+                conversation.merged_ids = vec![conversation.id.clone()];
+                conversation.id = short_digest(&format!("host|{}|{}|{}", key.0, key.1, key.2));
+                groups.insert(key, conversation);
+            },
+        }
+    }
+    
+    let mut grouped: Vec<Conversation> = groups.into_values().collect();
+    for group in &mut grouped {
+        group.merged_ids.sort();
+    }
+    grouped.sort_by_key(|c| std::cmp::Reverse(c.bytes()));
+    grouped
+}
+
+/// Add a conversation's counts to a group, lining up its directions with the group's
+fn merge(group: &mut Conversation, mut other: Conversation) {
+    if other.address_a != group.address_a {
+        std::mem::swap(&mut other.address_a, &mut other.address_b);
+        std::mem::swap(&mut other.port_a, &mut other.port_b);
+        std::mem::swap(&mut other.packets_a_to_b, &mut other.packets_b_to_a);
+        std::mem::swap(&mut other.bytes_a_to_b, &mut other.bytes_b_to_a);
+    }
+    
+    if group.port_a != other.port_a {
+        group.port_a = None;
+    }
+    if group.port_b != other.port_b {
+        group.port_b = None;
+    }
+    group.packets_a_to_b += other.packets_a_to_b;
+    group.bytes_a_to_b += other.bytes_a_to_b;
+    group.packets_b_to_a += other.packets_b_to_a;
+    group.bytes_b_to_a += other.bytes_b_to_a;
+    group.skipped_packets += other.skipped_packets;
+    group.skipped_bytes += other.skipped_bytes;
+    
+    if other.last_seen > group.last_seen {
+        group.protocol = other.protocol.clone();
+    }
+    group.handshake_rtt_ms = group.handshake_rtt_ms.or(other.handshake_rtt_ms);
+    group.first_seen = group.first_seen.min(other.first_seen);
+    group.last_seen = group.last_seen.max(other.last_seen);
+    
+    let protocols: BTreeSet<String> = group.protocols.drain(..).chain(other.protocols).collect();
+    group.protocols = protocols.into_iter().collect();
+    
+    // Sparklines all end at the latest capture second, so they add up bucket by bucket
+    group.sparkline = match (group.sparkline.take(), other.sparkline) {
+        (Some(mut series), Some(more)) => {
+            series.iter_mut().zip(more).for_each(|(bytes, more)| *bytes += more);
+            Some(series)
+        },
+        (series, more) => series.or(more),
+    };
+    group.merged_ids.push(other.id);
+}
+
+impl ConversationState {
+    /// Count a packet in one direction and note its application protocol, or
+    /// count it as skipped past the flow head; returns whether it was kept
+    fn add(&mut self, packet: &Packet, from: (IpAddr, Option<u16>), transport: &str, data_len: usize, head_packets: usize) -> bool {
+        let conversation = &mut self.conversation;
+        if head_packets > 0 && conversation.packets_a_to_b + conversation.packets_b_to_a >= head_packets {
+            conversation.skipped_packets += 1;
+            conversation.skipped_bytes += data_len;
+            conversation.last_seen = conversation.last_seen.max(packet.timestamp);
+            return false;
+        }
+        
+        if from == self.from {
+            conversation.packets_a_to_b += 1;
+            conversation.bytes_a_to_b += data_len;
+        } else {
+            conversation.packets_b_to_a += 1;
+            conversation.bytes_b_to_a += data_len;
+        }
+        conversation.first_seen = conversation.first_seen.min(packet.timestamp);
+        conversation.last_seen = conversation.last_seen.max(packet.timestamp);
+        if let Some(sparkline) = &mut self.sparkline {
+            sparkline.add(packet.timestamp.timestamp(), data_len);
+        }
+        if transport == "TCP" && conversation.handshake_rtt_ms.is_none() {
+            let flags = &packet.headers["tcp"]["flags"];
+            match (flags["syn"].as_bool(), flags["ack"].as_bool(), from == self.from) {
+                (Some(true), Some(false), true) => self.syn_seen = Some(packet.timestamp),
+                (Some(true), Some(true), false) => {
+                    conversation.handshake_rtt_ms = self.syn_seen.and_then(|syn| (packet.timestamp - syn).num_microseconds()).map(|us| us as f64 / 1000.0);
+                },
+                _ => {},
+            }
+        }
+        
+        // Packets left at the transport (handshakes, bare ACKs) don't change the classification
+        if packet.protocol != transport {
+            conversation.protocol = packet.protocol.clone();
+            self.protocols.insert(packet.protocol.clone());
+        }
+        true
+    }
+    
+    fn matches(&self, protocol: &str) -> bool {
+        self.conversation.transport.eq_ignore_ascii_case(protocol)
+            || self.protocols.iter().any(|p| p.eq_ignore_ascii_case(protocol))
+    }
+    
+    fn conversation(&self) -> Conversation {
+        Conversation {
+            protocols: self.protocols.iter().cloned().collect(),
+            ..self.conversation.clone()
+        }
+    }
+}
+
+impl Sparkline {
+    fn new() -> Self {
+        Self {
+            newest: 0,
+            buckets: [0; SPARKLINE_SECONDS],
+        }
+    }
+    
+    /// Add bytes to the bucket of `second`, clearing the buckets it moves past
+    fn add(&mut self, second: i64, bytes: usize) {
+        let len = SPARKLINE_SECONDS as i64;
+        if second > self.newest {
+            for s in (self.newest + 1).max(second - len + 1)..=second {
+                self.buckets[s.rem_euclid(len) as usize] = 0;
+            }
+            self.newest = second;
+        } else if second <= self.newest - len {
+            return;
+        }
+        self.buckets[second.rem_euclid(len) as usize] += bytes;
+    }
+    
+    /// Bytes per second of the `SPARKLINE_SECONDS` seconds ending at `now`, oldest first
+    fn series(&self, now: i64) -> Vec<usize> {
+        let len = SPARKLINE_SECONDS as i64;
+        (now - len + 1..=now)
+            .map(|s| if s > self.newest || s <= self.newest - len { 0 } else { self.buckets[s.rem_euclid(len) as usize] })
+            .collect()
+    }
+}
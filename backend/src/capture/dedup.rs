@@ -0,0 +1,158 @@
+// Duplicate frame detection
+//
+// Taps and SPAN ports often deliver the same packet twice. Like `editcap -D`,
+// each frame is compared with the last few frames: it is hashed over its IP
+// packet with the fields a router rewrites (IPv4 TTL and header checksum, IPv6
+// hop limit) zeroed, so a copy mirrored before and after a hop still matches.
+// VLAN tags are skipped, so a copy mirrored on a trunk matches the untagged
+// one. Frames without a located IP header are hashed whole. Duplicates are kept but
+// marked with `metadata["duplicate"] = true`.
+
+use parking_lot::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use crate::models::packet::Packet;
+
+/// Ethernet header, before any network-layer header
+const ETHERNET_HEADER_LEN: usize = 14;
+
+/// PPPoE session header and the PPP protocol field
+const PPPOE_SESSION_HEADER_LEN: usize = 8;
+
+/// 802.1Q tag, after which the EtherType repeats
+const VLAN_TAG_LEN: usize = 4;
+
+/// EtherTypes of VLAN tags: 802.1Q, and the 802.1ad and pre-standard QinQ outer tags
+const VLAN_ETHERTYPES: [u16; 3] = [0x8100, 0x88a8, 0x9100];
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_PPPOE_SESSION: u16 = 0x8864;
+
+/// PPP protocol numbers of IPv4 and IPv6
+const PPP_IPV4: u16 = 0x0021;
+const PPP_IPV6: u16 = 0x0057;
+
+/// Hashes of the most recent frames, with a count per hash for quick lookup
+#[derive(Default)]
+struct RecentFrames {
+    order: VecDeque<u64>,
+    counts: HashMap<u64, usize>,
+}
+
+impl RecentFrames {
+    /// Remember a hash, forgetting the oldest beyond `window`
+    fn push(&mut self, hash: u64, window: usize) {
+        self.order.push_back(hash);
+        *self.counts.entry(hash).or_default() += 1;
+        
+        while self.order.len() > window {
+            let Some(oldest) = self.order.pop_front() else { break };
+            if let Some(count) = self.counts.get_mut(&oldest) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+/// Marks frames identical to one of the previous `window` frames
+#[derive(Clone)]
+pub struct DuplicateDetector {
+    /// Frames compared against (0 disables detection)
+    window: usize,
+    
+    recent: Arc<Mutex<RecentFrames>>,
+}
+
+impl DuplicateDetector {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            recent: Arc::new(Mutex::new(RecentFrames::default())),
+        }
+    }
+    
+    /// Check a packet against the window, marking it when it is a duplicate
+    pub fn inspect(&self, packet: &mut Packet) -> bool {
+        if self.window == 0 {
+            return false;
+        }
+        
+        let hash = fingerprint(packet);
+        let duplicate = {
+            let mut recent = self.recent.lock();
+            let seen = recent.counts.contains_key(&hash);
+            recent.push(hash, self.window);
+            seen
+        };
+        
+        if duplicate {
+            if let Some(metadata) = packet.metadata.as_object_mut() {
+                metadata.insert("duplicate".to_string(), serde_json::Value::Bool(true));
+            }
+        }
+        duplicate
+    }
+    
+    /// Forget the recent frames, as when a new capture starts
+    pub fn clear(&self) {
+        let mut recent = self.recent.lock();
+        recent.order.clear();
+        recent.counts.clear();
+    }
+}
+
+/// Hash of the frame's IP packet, ignoring the hop-by-hop fields, or of the whole frame
+fn fingerprint(packet: &Packet) -> u64 {
+    let data = &packet.raw_data;
+    let mut hasher = DefaultHasher::new();
+    
+    let Some(offset) = ip_offset(packet).filter(|&offset| offset < data.len()) else {
+        hasher.write(data);
+        return hasher.finish();
+    };
+    
+    let mut ip = data[offset..].to_vec();
+    match ip[0] >> 4 {
+        4 if ip.len() >= 12 => {
+            ip[8] = 0;
+            ip[10] = 0;
+            ip[11] = 0;
+        },
+        6 if ip.len() >= 8 => ip[7] = 0,
+        _ => {}
+    }
+    hasher.write(&ip);
+    hasher.finish()
+}
+
+/// Where the IP header starts in the frame, for Ethernet (and PPPoE) framing,
+/// past any VLAN tags (one for 802.1Q, two or more for QinQ)
+fn ip_offset(packet: &Packet) -> Option<usize> {
+    packet.headers.get("ethernet")?;
+    
+    let data = &packet.raw_data;
+    let ethertype_at = |offset: usize| data.get(offset..offset + 2).map(|field| u16::from_be_bytes([field[0], field[1]]));
+    
+    let mut offset = ETHERNET_HEADER_LEN;
+    let mut ethertype = ethertype_at(offset - 2)?;
+    while VLAN_ETHERTYPES.contains(&ethertype) {
+        offset += VLAN_TAG_LEN;
+        ethertype = ethertype_at(offset - 2)?;
+    }
+    
+    match ethertype {
+        ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => Some(offset),
+        ETHERTYPE_PPPOE_SESSION => match ethertype_at(offset + PPPOE_SESSION_HEADER_LEN - 2)? {
+            PPP_IPV4 | PPP_IPV6 => Some(offset + PPPOE_SESSION_HEADER_LEN),
+            _ => None,
+        },
+        _ => None,
+    }
+}
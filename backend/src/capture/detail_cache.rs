@@ -0,0 +1,86 @@
+// Cache of serialized packet details
+//
+// A stored packet only changes when the resolver backfills its hostnames, so
+// the JSON served by `GET /api/packets/{id}` can be reused until then, until
+// the packet is evicted, or until the capture is reset. UIs re-fetch the
+// selected packet often; the least recently used entries are dropped first.
+//
+// Every invalidation bumps a generation number. A detail is only cached if no
+// invalidation happened while it was being serialized, so a backfill racing
+// with a fetch cannot leave a stale entry behind.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Packet details kept
+const DETAIL_CACHE_ENTRIES: usize = 256;
+
+#[derive(Default)]
+struct Entries {
+    /// Serialized detail and the tick it was last used at, by packet ID
+    details: HashMap<u64, (Arc<str>, u64)>,
+    tick: u64,
+    generation: u64,
+}
+
+/// LRU cache of packet detail JSON by packet ID
+#[derive(Clone, Default)]
+pub struct DetailCache {
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl DetailCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Get the cached detail of a packet, marking it as recently used
+    pub fn get(&self, id: u64) -> Option<Arc<str>> {
+        let mut entries = self.entries.lock();
+        entries.tick += 1;
+        let tick = entries.tick;
+        entries.details.get_mut(&id).map(|(json, used)| {
+            *used = tick;
+            json.clone()
+        })
+    }
+    
+    /// Current generation, to pass to `insert` once the detail is serialized
+    pub fn generation(&self) -> u64 {
+        self.entries.lock().generation
+    }
+    
+    /// Cache a packet's detail, unless something was invalidated since `generation`
+    pub fn insert(&self, id: u64, json: Arc<str>, generation: u64) {
+        let mut entries = self.entries.lock();
+        if entries.generation != generation {
+            return;
+        }
+        
+        entries.tick += 1;
+        let tick = entries.tick;
+        entries.details.insert(id, (json, tick));
+        
+        if entries.details.len() > DETAIL_CACHE_ENTRIES {
+            let oldest = entries.details.iter().min_by_key(|(_, (_, used))| *used).map(|(&id, _)| id);
+            if let Some(oldest) = oldest {
+                entries.details.remove(&oldest);
+            }
+        }
+    }
+    
+    /// Drop a packet's detail after it changed or was removed
+    pub fn invalidate(&self, id: u64) {
+        let mut entries = self.entries.lock();
+        entries.generation += 1;
+        entries.details.remove(&id);
+    }
+    
+    /// Drop all details, as when the packets or the capture start time change
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock();
+        entries.generation += 1;
+        entries.details.clear();
+    }
+}
@@ -0,0 +1,85 @@
+// Packet direction relative to the capture host
+//
+// A packet from one of the capture interface's own IP addresses was sent by
+// this host and one to them was received; with both ends local it never left
+// the host. Frames without IP addresses (ARP, LLDP) are judged by the
+// interface MAC instead. Anything else was seen but not addressed to or from
+// this host, which happens with promiscuous capture or a mirror port.
+
+use std::net::IpAddr;
+
+use crate::models::interface::InterfaceInfo;
+use crate::models::packet::{Packet, PacketDirection};
+
+/// The addresses that identify the capture host on one interface
+#[derive(Debug, Clone, Default)]
+pub struct LocalAddresses {
+    ips: Vec<IpAddr>,
+    mac: Option<String>,
+}
+
+impl LocalAddresses {
+    /// The addresses reported for an interface
+    pub fn of_interface(info: &InterfaceInfo) -> Self {
+        let ips = info.ip_addresses.iter()
+            .chain(info.ipv4_address.iter())
+            .filter_map(|ip| ip.parse().ok())
+            .collect();
+        
+        LocalAddresses {
+            ips,
+            mac: info.mac_address.as_ref().map(|mac| mac.to_ascii_lowercase()),
+        }
+    }
+    
+    /// Whether nothing is known about the interface, so no direction can be inferred
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.mac.is_none()
+    }
+    
+    /// Infer a packet's direction, or None when the packet carries no addresses
+    pub fn direction(&self, packet: &Packet) -> Option<PacketDirection> {
+        if self.is_empty() {
+            return None;
+        }
+        
+        let (source, destination) = match (packet.source_ip, packet.destination_ip) {
+            (Some(source), Some(destination)) => (self.is_local_ip(source), self.is_local_ip(destination)),
+            _ => (
+                self.is_local_mac(packet.source_mac.as_deref()?),
+                self.is_local_mac(packet.destination_mac.as_deref()?),
+            ),
+        };
+        
+        Some(match (source, destination) {
+            (true, true) => PacketDirection::Local,
+            (true, false) => PacketDirection::Out,
+            (false, true) => PacketDirection::In,
+            (false, false) => PacketDirection::Transit,
+        })
+    }
+    
+    /// One of the interface's addresses, or loopback, which is always this host
+    fn is_local_ip(&self, ip: IpAddr) -> bool {
+        ip.is_loopback() || self.ips.contains(&ip)
+    }
+    
+    fn is_local_mac(&self, mac: &str) -> bool {
+        self.mac.as_deref().is_some_and(|local| local.eq_ignore_ascii_case(mac))
+    }
+}
+
+/// Read the MAC address of a local interface
+#[cfg(target_os = "linux")]
+pub fn interface_mac(interface: &str) -> Option<String> {
+    std::fs::read_to_string(format!("/sys/class/net/{}/address", interface))
+        .ok()
+        .map(|mac| mac.trim().to_string())
+        .filter(|mac| !mac.is_empty())
+}
+
+/// Read the MAC address of a local interface
+#[cfg(not(target_os = "linux"))]
+pub fn interface_mac(_interface: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,302 @@
+// Display filters: Wireshark-style expressions evaluated against stored packets
+//
+// A subset of Wireshark's syntax: `tcp`, `dns && length > 100`,
+// `source contains "192.0.2." || !(protocol == ARP)`, `tcp.flags.syn == true`.
+// A name is looked up as a packet summary field first (protocol, source,
+// info, ...), then as a dotted path into the decoded headers (`ipv4.ttl`) or,
+// prefixed with `metadata.`, into the metadata. Alone, a name tests that the
+// field is present and not false, zero or empty; a name that is no field
+// matches packets labeled with that protocol. Strings compare
+// case-insensitively, numbers numerically, and an array matches when any of
+// its elements does.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::models::packet::Packet;
+
+/// Comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// A bare name: the field is present and truthy, or the packet is of that protocol
+    Test(String),
+    Compare(String, Op, String),
+}
+
+/// A parsed display filter
+#[derive(Debug, Clone)]
+pub struct DisplayFilter {
+    expr: Expr,
+}
+
+impl DisplayFilter {
+    /// Parse a filter expression, describing the first problem found
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Err("empty filter".to_string());
+        }
+        
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(format!("unexpected {}", describe(token)));
+        }
+        Ok(Self { expr })
+    }
+    
+    /// Whether a packet matches, given its headers (expanded, see
+    /// `Packet::expanded_headers`) and its summary serialized as a JSON object
+    pub fn matches(&self, packet: &Packet, headers: &Value, summary: &Value) -> bool {
+        eval(&self.expr, packet, headers, summary)
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            },
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            },
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            },
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => value.extend(chars.next()),
+                        Some(c) => value.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            },
+            '&' | '|' | '=' | '!' | '<' | '>' => {
+                chars.next();
+                let next = chars.peek().copied();
+                let (token, pair) = match (c, next) {
+                    ('&', Some('&')) => (Token::And, true),
+                    ('|', Some('|')) => (Token::Or, true),
+                    ('=', Some('=')) => (Token::Op(Op::Eq), true),
+                    ('!', Some('=')) => (Token::Op(Op::Ne), true),
+                    ('>', Some('=')) => (Token::Op(Op::Ge), true),
+                    ('<', Some('=')) => (Token::Op(Op::Le), true),
+                    ('!', _) => (Token::Not, false),
+                    ('>', _) => (Token::Op(Op::Gt), false),
+                    ('<', _) => (Token::Op(Op::Lt), false),
+                    _ => return Err(format!("unexpected '{}'", c)),
+                };
+                if pair {
+                    chars.next();
+                }
+                tokens.push(token);
+            },
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()\"&|=!<>".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "eq" => Token::Op(Op::Eq),
+                    "ne" => Token::Op(Op::Ne),
+                    "gt" => Token::Op(Op::Gt),
+                    "ge" => Token::Op(Op::Ge),
+                    "lt" => Token::Op(Op::Lt),
+                    "le" => Token::Op(Op::Le),
+                    "contains" => Token::Op(Op::Contains),
+                    _ => Token::Word(word),
+                });
+            },
+        }
+    }
+    
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+        Token::And => "'&&'".to_string(),
+        Token::Or => "'||'".to_string(),
+        Token::Not => "'!'".to_string(),
+        Token::Op(op) => format!("operator {:?}", op),
+        Token::Word(word) => format!("'{}'", word),
+        Token::Quoted(value) => format!("\"{}\"", value),
+    }
+}
+
+/// Recursive descent over: or := and (|| and)*, and := not (&& not)*,
+/// not := ! not | primary, primary := ( or ) | name [op value]
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+    
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+    
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+    
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+    
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    Some(token) => Err(format!("expected ')', found {}", describe(&token))),
+                    None => Err("missing ')'".to_string()),
+                }
+            },
+            Some(Token::Word(name)) => {
+                let Some(&Token::Op(op)) = self.peek() else { return Ok(Expr::Test(name)) };
+                self.pos += 1;
+                match self.next() {
+                    Some(Token::Word(value) | Token::Quoted(value)) => Ok(Expr::Compare(name, op, value)),
+                    Some(token) => Err(format!("expected a value after {}, found {}", name, describe(&token))),
+                    None => Err(format!("expected a value after {}", name)),
+                }
+            },
+            Some(token) => Err(format!("unexpected {}", describe(&token))),
+            None => Err("unexpected end of filter".to_string()),
+        }
+    }
+}
+
+fn eval(expr: &Expr, packet: &Packet, headers: &Value, summary: &Value) -> bool {
+    match expr {
+        Expr::Or(a, b) => eval(a, packet, headers, summary) || eval(b, packet, headers, summary),
+        Expr::And(a, b) => eval(a, packet, headers, summary) && eval(b, packet, headers, summary),
+        Expr::Not(a) => !eval(a, packet, headers, summary),
+        Expr::Test(name) => match lookup(name, packet, headers, summary) {
+            Some(value) => truthy(value),
+            None => packet.protocol.eq_ignore_ascii_case(name),
+        },
+        Expr::Compare(name, op, expected) => match lookup(name, packet, headers, summary) {
+            Some(Value::Array(values)) => values.iter().any(|value| compare(value, *op, expected)),
+            Some(value) => compare(value, *op, expected),
+            None => false,
+        },
+    }
+}
+
+/// A summary field, or a dotted path into the headers or (with `metadata.`) the metadata
+fn lookup<'a>(name: &str, packet: &'a Packet, headers: &'a Value, summary: &'a Value) -> Option<&'a Value> {
+    if let Some(value) = summary.get(name).filter(|value| !value.is_null()) {
+        return Some(value);
+    }
+    let (root, path) = match name.strip_prefix("metadata.") {
+        Some(path) => (&packet.metadata, path),
+        None => (headers, name),
+    };
+    root.pointer(&format!("/{}", path.replace('.', "/"))).filter(|value| !value.is_null())
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn compare(value: &Value, op: Op, expected: &str) -> bool {
+    let actual = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    
+    let ordering = || match (actual.parse::<f64>(), expected.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b),
+        _ => Some(actual.to_lowercase().cmp(&expected.to_lowercase())),
+    };
+    
+    match op {
+        Op::Contains => actual.to_lowercase().contains(&expected.to_lowercase()),
+        Op::Eq => ordering() == Some(Ordering::Equal),
+        Op::Ne => ordering() != Some(Ordering::Equal),
+        Op::Gt => ordering() == Some(Ordering::Greater),
+        Op::Ge => matches!(ordering(), Some(Ordering::Greater | Ordering::Equal)),
+        Op::Lt => ordering() == Some(Ordering::Less),
+        Op::Le => matches!(ordering(), Some(Ordering::Less | Ordering::Equal)),
+    }
+}
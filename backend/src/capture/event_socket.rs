@@ -0,0 +1,105 @@
+// Live packet feed on a Unix domain socket (or a Windows named pipe)
+//
+// Local tools connect to the `--event-socket` path and read one JSON packet
+// summary per line, without going through HTTP. Every consumer gets its own
+// subscription to the packet broadcast, so a slow or vanished consumer only
+// loses its own lines (it is told how many) and never holds up the capture.
+
+use anyhow::Result;
+use log::{info, warn};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::broadcast;
+
+use crate::capture::manager::CaptureManager;
+use crate::models::packet::Packet;
+
+/// Accept consumers on the socket at `path` and stream packet summaries to each.
+///
+/// The socket is bound before this returns, so a bad path fails at startup;
+/// consumers are then served in the background.
+#[cfg(unix)]
+pub fn serve(path: &str, feed: broadcast::Sender<Packet>) -> Result<()> {
+    use anyhow::anyhow;
+    use std::os::unix::fs::FileTypeExt;
+    use tokio::net::UnixListener;
+    
+    // A socket left behind by an earlier run would make the bind fail
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(anyhow!("{} exists and is not a socket", path));
+        }
+        std::fs::remove_file(path)?;
+    }
+    
+    let listener = UnixListener::bind(path)?;
+    info!("Streaming packet summaries to consumers of {}", path);
+    
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(stream_packets(stream, feed.subscribe()));
+                },
+                Err(e) => warn!("Failed to accept event socket consumer: {}", e),
+            }
+        }
+    });
+    
+    Ok(())
+}
+
+/// Accept consumers on the named pipe at `path` (e.g. `\\.\pipe\rustshark`) and
+/// stream packet summaries to each
+#[cfg(windows)]
+pub fn serve(path: &str, feed: broadcast::Sender<Packet>) -> Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+    
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(path)?;
+    info!("Streaming packet summaries to consumers of {}", path);
+    
+    let path = path.to_string();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                warn!("Failed to accept event pipe consumer: {}", e);
+                continue;
+            }
+            
+            // Open the next instance before serving this one, so consumers can keep connecting
+            let connected = match ServerOptions::new().create(&path) {
+                Ok(next) => std::mem::replace(&mut server, next),
+                Err(e) => {
+                    warn!("Failed to create event pipe instance: {}", e);
+                    return;
+                }
+            };
+            tokio::spawn(stream_packets(connected, feed.subscribe()));
+        }
+    });
+    
+    Ok(())
+}
+
+/// Write every packet of the feed to one consumer until it disconnects
+async fn stream_packets<W: AsyncWrite + Unpin>(mut consumer: W, mut rx: broadcast::Receiver<Packet>) {
+    info!("Event socket consumer connected");
+    
+    loop {
+        let line = match rx.recv().await {
+            Ok(packet) => serde_json::to_string(&CaptureManager::summarize(&packet)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Event socket consumer fell behind, skipped {} packets", skipped);
+                serde_json::to_string(&serde_json::json!({ "type": "lagged", "skipped": skipped }))
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let Ok(mut line) = line else { continue };
+        line.push('\n');
+        
+        if consumer.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    
+    info!("Event socket consumer disconnected");
+}
@@ -0,0 +1,189 @@
+use log::{error, info, warn};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::models::event::{CaptureEvent, EventLevel};
+
+/// Maximum number of events published per rate-limit window
+const MAX_EVENTS_PER_WINDOW: usize = 10;
+
+/// Length of the rate-limit window
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Events kept for the session log; the oldest are dropped first
+const MAX_HISTORY: usize = 500;
+
+/// Link changes kept for the session log, apart from the other events so a
+/// flood of those never drops them
+const MAX_LINK_HISTORY: usize = 100;
+
+/// Rate limiter state: events in the current window, how many were dropped,
+/// and whether a report of those is scheduled for the window's end
+struct RateState {
+    window_start: Instant,
+    published: usize,
+    suppressed: usize,
+    flush_scheduled: bool,
+}
+
+/// Shareable, rate-limited feed of capture warnings and errors.
+///
+/// Every event is logged; at most `MAX_EVENTS_PER_WINDOW` per second are
+/// published to subscribers, and the number dropped is reported once the
+/// window ends so a broken link can't flood remote clients. Published
+/// events are also kept as the session's event log, which link changes
+/// always reach: they are rare, and what explains a gap in a capture, so
+/// they are kept in a list of their own that other events cannot evict.
+#[derive(Clone)]
+pub struct EventLog {
+    state: Arc<Mutex<RateState>>,
+    tx: broadcast::Sender<CaptureEvent>,
+    history: Arc<Mutex<VecDeque<CaptureEvent>>>,
+    links: Arc<Mutex<VecDeque<CaptureEvent>>>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    /// Create an event log with no subscribers
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(100);
+        
+        Self {
+            state: Arc::new(Mutex::new(RateState {
+                window_start: Instant::now(),
+                published: 0,
+                suppressed: 0,
+                flush_scheduled: false,
+            })),
+            tx,
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            links: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+    
+    /// Log and publish a warning
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        warn!("{}", message);
+        self.publish(EventLevel::Warning, message);
+    }
+    
+    /// Log and publish an error
+    pub fn error(&self, message: impl Into<String>) {
+        let message = message.into();
+        error!("{}", message);
+        self.publish(EventLevel::Error, message);
+    }
+    
+    /// Log and record a capture interface's link going down or coming back up
+    pub fn link_change(&self, interface: &str, up: bool) {
+        let mut event = if up {
+            let message = format!("Link on {} is up", interface);
+            info!("{}", message);
+            CaptureEvent::new(EventLevel::Info, message)
+        } else {
+            let message = format!("Link on {} went down", interface);
+            warn!("{}", message);
+            CaptureEvent::new(EventLevel::Warning, message)
+        };
+        event.interface = Some(interface.to_string());
+        event.link_up = Some(up);
+        
+        {
+            let mut links = self.links.lock();
+            if links.len() == MAX_LINK_HISTORY {
+                links.pop_front();
+            }
+            links.push_back(event.clone());
+        }
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(event);
+        }
+    }
+    
+    /// Get a receiver for published events
+    pub fn subscribe(&self) -> broadcast::Receiver<CaptureEvent> {
+        self.tx.subscribe()
+    }
+    
+    /// Events of the session so far, link changes among them, oldest first
+    pub fn history(&self) -> Vec<CaptureEvent> {
+        let mut events: Vec<CaptureEvent> = self.history.lock().iter().cloned().collect();
+        events.extend(self.links.lock().iter().cloned());
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
+    
+    /// Forget the events of the previous session
+    pub fn clear_history(&self) {
+        self.history.lock().clear();
+        self.links.lock().clear();
+    }
+    
+    /// Publish an event to subscribers, subject to the rate limit
+    fn publish(&self, level: EventLevel, message: String) {
+        let mut state = self.state.lock();
+        self.roll_window(&mut state);
+        
+        if state.published >= MAX_EVENTS_PER_WINDOW {
+            state.suppressed += 1;
+            
+            // Report the drops when the window ends, even if nothing follows the burst
+            if !state.flush_scheduled {
+                state.flush_scheduled = true;
+                let events = self.clone();
+                let window_end = state.window_start + RATE_WINDOW;
+                std::thread::spawn(move || {
+                    std::thread::sleep(window_end.saturating_duration_since(Instant::now()));
+                    let mut state = events.state.lock();
+                    events.roll_window(&mut state);
+                });
+            }
+            return;
+        }
+        
+        state.published += 1;
+        self.send(CaptureEvent::new(level, message));
+    }
+    
+    /// Open a new window once the current one is over, reporting how many
+    /// events it suppressed
+    fn roll_window(&self, state: &mut RateState) {
+        if state.window_start.elapsed() < RATE_WINDOW {
+            return;
+        }
+        
+        let suppressed = state.suppressed;
+        state.window_start = Instant::now();
+        state.published = 0;
+        state.suppressed = 0;
+        state.flush_scheduled = false;
+        
+        if suppressed > 0 {
+            state.published += 1;
+            self.send(CaptureEvent::new(EventLevel::Warning, format!("{} capture events suppressed by rate limit", suppressed)));
+        }
+    }
+    
+    /// Keep an event in the session log, and send it if anyone is listening
+    fn send(&self, event: CaptureEvent) {
+        {
+            let mut history = self.history.lock();
+            if history.len() == MAX_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(event);
+        }
+    }
+}
@@ -0,0 +1,373 @@
+// Export a selection of stored packets as pcap, pcapng, CSV or JSON lines,
+// and the conversation table as CSV or JSON lines
+
+use pcap::Linktype;
+use serde::{Deserialize, Serialize};
+
+use crate::models::conversation::Conversation;
+use crate::models::packet::{Packet, PacketSummary};
+
+/// Classic pcap magic number (microsecond timestamps), version 2.4
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_VERSION: (u16, u16) = (2, 4);
+
+/// Snapshot length written to the file header
+const PCAP_SNAPLEN: u32 = 65535;
+
+/// Ethernet header, before any network-layer header
+const ETHERNET_HEADER_LEN: usize = 14;
+
+/// PPPoE session header and the PPP protocol field
+const PPPOE_SESSION_HEADER_LEN: usize = 8;
+
+/// Fixed transport header sizes (the TCP header length is taken from its data offset)
+const IPV6_HEADER_LEN: usize = 40;
+const UDP_HEADER_LEN: usize = 8;
+const SCTP_HEADER_LEN: usize = 12;
+const ICMP_HEADER_LEN: usize = 8;
+
+/// pcapng block types
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+/// pcapng byte-order magic and section version 1.0
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+/// pcapng option codes
+const OPT_ENDOFOPT: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const SHB_HARDWARE: u16 = 2;
+const SHB_OS: u16 = 3;
+const SHB_USERAPPL: u16 = 4;
+const IF_NAME: u16 = 2;
+const IF_DESCRIPTION: u16 = 3;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Classic libpcap file that Wireshark and tcpdump open directly
+    #[default]
+    Pcap,
+    
+    /// pcapng file carrying the capture description as its section comment
+    Pcapng,
+    
+    /// One row of summary columns per packet
+    Csv,
+    
+    /// One JSON object per packet and line
+    Jsonl,
+}
+
+impl ExportFormat {
+    /// MIME type of the exported file
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Pcap => "application/vnd.tcpdump.pcap",
+            ExportFormat::Pcapng => "application/x-pcapng",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+    
+    /// File extension of the exported file
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Pcap => "pcap",
+            ExportFormat::Pcapng => "pcapng",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Write packets as a classic pcap file, cut after the transport header with `strip_payload`.
+///
+/// A pcap file has a single link type, that of the first packet; packets of
+/// other link types are left out (pcapng keeps them).
+pub fn write_pcap(packets: &[Packet], strip_payload: bool) -> Vec<u8> {
+    let link_type = packets.first().map_or(Linktype::ETHERNET, |p| p.link_type);
+    let size = 24 + packets.iter().map(|p| 16 + p.raw_data.len()).sum::<usize>();
+    let mut out = Vec::with_capacity(size);
+    
+    push_pcap_header(&mut out, link_type);
+    for packet in packets.iter().filter(|p| p.link_type == link_type) {
+        push_pcap_record(&mut out, packet, strip_payload);
+    }
+    
+    out
+}
+
+/// Append the classic pcap global header, written once at the start of a file or stream
+pub fn push_pcap_header(out: &mut Vec<u8>, link_type: Linktype) {
+    out.extend_from_slice(&PCAP_MAGIC.to_le_bytes());
+    out.extend_from_slice(&PCAP_VERSION.0.to_le_bytes());
+    out.extend_from_slice(&PCAP_VERSION.1.to_le_bytes());
+    out.extend_from_slice(&0i32.to_le_bytes()); // thiszone
+    out.extend_from_slice(&0u32.to_le_bytes()); // sigfigs
+    out.extend_from_slice(&PCAP_SNAPLEN.to_le_bytes());
+    out.extend_from_slice(&(link_type.0 as u32).to_le_bytes());
+}
+
+/// Append one packet as a pcap record
+pub fn push_pcap_record(out: &mut Vec<u8>, packet: &Packet, strip_payload: bool) {
+    let data = frame_data(packet, strip_payload);
+    let original_len = packet.length.max(packet.raw_data.len());
+    
+    out.extend_from_slice(&(packet.timestamp.timestamp() as u32).to_le_bytes());
+    out.extend_from_slice(&packet.timestamp.timestamp_subsec_micros().to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(original_len as u32).to_le_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Options of a pcapng section header and its interface, each left out when absent
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SectionInfo<'a> {
+    pub comment: Option<&'a str>,
+    pub hardware: Option<&'a str>,
+    pub os: Option<&'a str>,
+    pub application: Option<&'a str>,
+    pub interface_name: Option<&'a str>,
+    pub interface_description: Option<&'a str>,
+}
+
+/// Write packets as a pcapng file, putting `section` into the section header;
+/// with `strip_payload`, each packet is cut after its transport header. The
+/// interface is described once per link type among the packets, in order of
+/// appearance.
+pub fn write_pcapng(packets: &[Packet], section: &SectionInfo, strip_payload: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    
+    // Section header: byte-order magic, version 1.0, unknown section length
+    let mut body = Vec::new();
+    body.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes());
+    body.extend_from_slice(&0u16.to_le_bytes());
+    body.extend_from_slice(&(-1i64).to_le_bytes());
+    let options = [
+        (OPT_COMMENT, section.comment),
+        (SHB_HARDWARE, section.hardware),
+        (SHB_OS, section.os),
+        (SHB_USERAPPL, section.application),
+    ];
+    let mut any_option = false;
+    for (code, value) in options {
+        if let Some(value) = value {
+            push_option(&mut body, code, value);
+            any_option = true;
+        }
+    }
+    if any_option {
+        push_option(&mut body, OPT_ENDOFOPT, "");
+    }
+    push_block(&mut out, PCAPNG_SECTION_HEADER, &body);
+    
+    let mut link_types = Vec::new();
+    for packet in packets {
+        if !link_types.contains(&packet.link_type) {
+            link_types.push(packet.link_type);
+        }
+    }
+    if link_types.is_empty() {
+        link_types.push(Linktype::ETHERNET);
+    }
+    
+    // Interface descriptions: link type, reserved, snapshot length
+    for link_type in &link_types {
+        let mut body = Vec::new();
+        body.extend_from_slice(&(link_type.0 as u16).to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&PCAP_SNAPLEN.to_le_bytes());
+        let options = [(IF_NAME, section.interface_name), (IF_DESCRIPTION, section.interface_description)];
+        if options.iter().any(|(_, value)| value.is_some()) {
+            for (code, value) in options {
+                if let Some(value) = value {
+                    push_option(&mut body, code, value);
+                }
+            }
+            push_option(&mut body, OPT_ENDOFOPT, "");
+        }
+        push_block(&mut out, PCAPNG_INTERFACE_DESCRIPTION, &body);
+    }
+    
+    for packet in packets {
+        let data = frame_data(packet, strip_payload);
+        let original_len = packet.length.max(packet.raw_data.len());
+        let micros = packet.timestamp.timestamp_micros() as u64;
+        let interface = link_types.iter().position(|&link_type| link_type == packet.link_type).unwrap_or(0);
+        
+        // Enhanced packet: interface of its link type, microsecond timestamp split high/low
+        let mut body = Vec::with_capacity(20 + data.len() + 3);
+        body.extend_from_slice(&(interface as u32).to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(original_len as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        pad_to_32_bits(&mut body);
+        push_block(&mut out, PCAPNG_ENHANCED_PACKET, &body);
+    }
+    
+    out
+}
+
+/// The bytes of a packet to write: at most the snapshot length, and with
+/// `strip_payload` only up to the end of its transport header
+fn frame_data(packet: &Packet, strip_payload: bool) -> &[u8] {
+    let mut len = packet.raw_data.len().min(PCAP_SNAPLEN as usize);
+    if strip_payload {
+        len = len.min(header_length(packet));
+    }
+    &packet.raw_data[..len]
+}
+
+/// Length of a packet's headers, from the layers the parser decoded.
+///
+/// Decoding stops at the deepest header known: the transport header for TCP,
+/// UDP, SCTP and ICMP, the IP header for other IP protocols, and the link
+/// header otherwise. ARP and PPPoE discovery frames are all header and kept
+/// whole, as are 802.11 frames, whose framing is not measured.
+fn header_length(packet: &Packet) -> usize {
+    let headers = packet.expanded_headers();
+    let headers = headers.as_ref();
+    if headers.get("arp").is_some() || headers.get("ethernet").is_none() {
+        return packet.raw_data.len();
+    }
+    
+    let mut len = ETHERNET_HEADER_LEN;
+    if let Some(pppoe) = headers.get("pppoe") {
+        if pppoe.get("ppp_protocol").is_none() {
+            return packet.raw_data.len();
+        }
+        len += PPPOE_SESSION_HEADER_LEN;
+    }
+    
+    if let Some(ipv4) = headers.get("ipv4") {
+        len += ipv4["header_length"].as_u64().unwrap_or(5) as usize * 4;
+    } else if headers.get("ipv6").is_some() {
+        len += IPV6_HEADER_LEN;
+    } else {
+        return len;
+    }
+    
+    if let Some(tcp) = headers.get("tcp") {
+        len += tcp["data_offset"].as_u64().unwrap_or(5) as usize * 4;
+    } else if headers.get("udp").is_some() {
+        len += UDP_HEADER_LEN;
+    } else if headers.get("sctp").is_some() {
+        len += SCTP_HEADER_LEN;
+    } else if headers.get("icmp").is_some() {
+        len += ICMP_HEADER_LEN;
+    }
+    len
+}
+
+/// Append a pcapng block: type, total length, body, total length again
+fn push_block(out: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+    let total_len = (12 + body.len()) as u32;
+    out.extend_from_slice(&block_type.to_le_bytes());
+    out.extend_from_slice(&total_len.to_le_bytes());
+    out.extend_from_slice(body);
+    out.extend_from_slice(&total_len.to_le_bytes());
+}
+
+/// Append a pcapng option, padding its value to 32 bits. A value longer than
+/// the 16-bit option length allows is truncated at a character boundary.
+fn push_option(out: &mut Vec<u8>, code: u16, value: &str) {
+    let mut end = value.len().min(u16::MAX as usize);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    let value = &value.as_bytes()[..end];
+    
+    out.extend_from_slice(&code.to_le_bytes());
+    out.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    out.extend_from_slice(value);
+    pad_to_32_bits(out);
+}
+
+/// Zero-pad a block body to a multiple of four bytes
+fn pad_to_32_bits(out: &mut Vec<u8>) {
+    out.resize(out.len().next_multiple_of(4), 0);
+}
+
+/// Write summaries as CSV with a header row of `PacketSummary::FIELDS`
+pub fn write_csv(summaries: &[PacketSummary]) -> Vec<u8> {
+    let mut out = String::new();
+    out.push_str(&PacketSummary::FIELDS.join(","));
+    out.push('\n');
+    
+    for summary in summaries {
+        let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(summary) else { continue };
+        let row: Vec<String> = PacketSummary::FIELDS
+            .iter()
+            .map(|field| match fields.get(*field) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(s)) => csv_escape(s),
+                Some(other) => other.to_string(),
+            })
+            .collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    
+    out.into_bytes()
+}
+
+/// Write conversations as CSV with a header row of `Conversation::CSV_FIELDS`
+pub fn write_conversations_csv(conversations: &[Conversation]) -> Vec<u8> {
+    let mut out = String::new();
+    out.push_str(&Conversation::CSV_FIELDS.join(","));
+    out.push('\n');
+    
+    let port = |port: Option<u16>| port.map(|p| p.to_string()).unwrap_or_default();
+    for c in conversations {
+        let row = [
+            csv_escape(&c.id),
+            csv_escape(&c.transport),
+            csv_escape(&c.protocol),
+            csv_escape(&c.protocols.join(";")),
+            csv_escape(&c.address_a),
+            port(c.port_a),
+            csv_escape(&c.address_b),
+            port(c.port_b),
+            c.packets().to_string(),
+            c.bytes().to_string(),
+            c.packets_a_to_b.to_string(),
+            c.bytes_a_to_b.to_string(),
+            c.packets_b_to_a.to_string(),
+            c.bytes_b_to_a.to_string(),
+            c.first_seen.to_rfc3339(),
+            c.last_seen.to_rfc3339(),
+            format!("{:.6}", c.duration()),
+            c.handshake_rtt_ms.map(|ms| format!("{:.3}", ms)).unwrap_or_default(),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    
+    out.into_bytes()
+}
+
+/// Write packets (or conversations) as JSON lines, serialized as by their API endpoints
+pub fn write_jsonl<T: Serialize>(items: &[T]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for item in items {
+        if serde_json::to_writer(&mut out, item).is_ok() {
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// Quote a CSV value if it contains a separator, quote or line break
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
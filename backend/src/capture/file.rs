@@ -0,0 +1,251 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use pcap::{Capture, Linktype, Offline};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A capture file to load, with the label its packets are tagged with
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileSource {
+    /// Path to the pcap/pcapng file
+    pub path: PathBuf,
+    
+    /// Label stored in `metadata["source"]` (defaults to the file stem)
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+impl FileSource {
+    /// Parse a CLI value of the form `label=path` or just `path`
+    pub fn parse(value: &str) -> Self {
+        match value.split_once('=') {
+            Some((label, path)) if !label.is_empty() && !path.is_empty() => Self {
+                path: PathBuf::from(path),
+                source: Some(label.to_string()),
+            },
+            _ => Self {
+                path: PathBuf::from(value),
+                source: None,
+            },
+        }
+    }
+    
+    /// The label for this file's packets
+    pub fn label(&self) -> String {
+        self.source.clone().unwrap_or_else(|| {
+            self.path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.path.display().to_string())
+        })
+    }
+    
+    /// This file resolved inside `dir` (a relative path is taken from it),
+    /// refusing a path that leads out of it through `..` or a symlink
+    pub fn confined_to(&self, dir: &Path) -> Result<Self> {
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("Capture directory {} is not accessible", dir.display()))?;
+        let path = dir
+            .join(&self.path)
+            .canonicalize()
+            .with_context(|| format!("Failed to open capture file {}", self.path.display()))?;
+        if !path.starts_with(&dir) {
+            return Err(anyhow!("Capture file {} is outside {}", self.path.display(), dir.display()));
+        }
+        
+        Ok(Self { path, source: Some(self.label()) })
+    }
+}
+
+/// A raw frame read from a capture file
+pub struct FileFrame {
+    pub data: Vec<u8>,
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+    
+    /// Path of the file, standing in for the interface the frame was captured on
+    pub interface: String,
+    
+    /// Link-layer type of the file the frame came from
+    pub link_type: Linktype,
+}
+
+/// An open capture file and its next unread frame
+struct OpenFile {
+    path: PathBuf,
+    label: String,
+    interface: String,
+    capture: Capture<Offline>,
+    next: Option<FileFrame>,
+}
+
+impl OpenFile {
+    /// Read the next frame into `self.next` (None at end of file)
+    fn advance(&mut self) -> Result<()> {
+        self.next = match self.capture.next_packet() {
+            Ok(packet) => {
+                let ts = packet.header.ts;
+                // time_t/suseconds_t are narrower than 64 bits on some platforms
+                #[allow(clippy::unnecessary_cast)]
+                let timestamp = Utc
+                    .timestamp_opt(ts.tv_sec as i64, (ts.tv_usec as u32).saturating_mul(1000))
+                    .single()
+                    .ok_or_else(|| anyhow!("Invalid timestamp in {}", self.path.display()))?;
+                
+                Some(FileFrame {
+                    data: packet.data.to_vec(),
+                    timestamp,
+                    source: self.label.clone(),
+                    interface: self.interface.clone(),
+                    link_type: self.capture.get_datalink(),
+                })
+            },
+            Err(pcap::Error::NoMorePackets) => None,
+            Err(e) => return Err(anyhow!("Failed to read {}: {}", self.path.display(), e)),
+        };
+        Ok(())
+    }
+}
+
+/// Streams the frames of several capture files as a single timeline.
+///
+/// Only one frame per file is held in memory at a time. Frames are ordered
+/// by timestamp; frames with equal timestamps keep the order of the files
+/// they came from.
+pub struct MergedReader {
+    files: Vec<OpenFile>,
+}
+
+impl MergedReader {
+    /// Open every file and read its first frame
+    pub fn open(sources: &[FileSource]) -> Result<Self> {
+        let mut files = Vec::with_capacity(sources.len());
+        
+        for source in sources {
+            let capture = Capture::from_file(&source.path)
+                .with_context(|| format!("Failed to open capture file {}", source.path.display()))?;
+            
+            let mut file = OpenFile {
+                path: source.path.clone(),
+                label: source.label(),
+                interface: source.path.display().to_string(),
+                capture,
+                next: None,
+            };
+            file.advance()?;
+            files.push(file);
+        }
+        
+        Ok(Self { files })
+    }
+}
+
+impl Iterator for MergedReader {
+    type Item = Result<FileFrame>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        // Pick the file whose pending frame is the oldest
+        let index = self.files
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| f.next.as_ref().map(|frame| (i, frame.timestamp)))
+            .min_by_key(|&(i, timestamp)| (timestamp, i))
+            .map(|(i, _)| i)?;
+        
+        let file = &mut self.files[index];
+        let frame = file.next.take()?;
+        
+        match file.advance() {
+            Ok(()) => Some(Ok(frame)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Capture files read ahead of loading, so that no file is touched under the manager lock
+pub struct ReadFiles {
+    /// The files read, with their hashes
+    pub files: Vec<LoadedFile>,
+    
+    /// Their frames, merged into one timeline
+    pub frames: Vec<FileFrame>,
+}
+
+impl ReadFiles {
+    /// Hash the files and read every frame of them; this blocks on the disk
+    pub fn read(sources: Vec<FileSource>) -> Result<Self> {
+        if sources.is_empty() {
+            return Err(anyhow!("No capture files given"));
+        }
+        
+        let files = sources.iter().map(LoadedFile::hash).collect::<Result<_>>()?;
+        let frames = MergedReader::open(&sources)?.collect::<Result<_>>()?;
+        Ok(Self { files, frames })
+    }
+}
+
+/// A loaded capture file as identified for chain-of-custody records
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedFile {
+    /// Label its packets were tagged with
+    pub source: String,
+    
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    
+    /// Hex SHA-256 of the file contents
+    pub sha256: String,
+}
+
+impl LoadedFile {
+    /// Hash a capture file's contents
+    pub fn hash(source: &FileSource) -> Result<Self> {
+        let (sha256, size_bytes) = sha256_file(&source.path)
+            .with_context(|| format!("Failed to hash capture file {}", source.path.display()))?;
+        
+        Ok(Self {
+            source: source.label(),
+            path: source.path.clone(),
+            size_bytes,
+            sha256,
+        })
+    }
+}
+
+/// Hex SHA-256 and size of a file, read in chunks
+fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut size = 0u64;
+    
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+        size += read as u64;
+    }
+    
+    let digest = context.finish();
+    Ok((digest.as_ref().iter().map(|b| format!("{:02x}", b)).collect(), size))
+}
+
+/// Outcome of loading capture files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoadSummary {
+    /// Frames read from the files
+    pub read: usize,
+    
+    /// Frames parsed and stored (at most `--read-max-packets`)
+    pub loaded: usize,
+    
+    /// Loaded packets still in the buffer after `buffer_size` eviction
+    pub retained: usize,
+    
+    /// Frames that could not be parsed
+    pub errors: usize,
+}
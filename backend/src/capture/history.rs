@@ -0,0 +1,73 @@
+// Per-second traffic history of the current capture
+//
+// The stats broadcast only carries the current totals, so a dashboard that
+// connects mid-capture would start its chart empty. The last few minutes are
+// kept here in one-second buckets and sent to it on connect.
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::models::stats::StatsBucket;
+
+/// Number of one-second buckets retained (five minutes)
+pub const HISTORY_BUCKETS: usize = 300;
+
+/// Rolling window of per-second packet and byte counts, keyed by packet timestamp
+#[derive(Clone, Default)]
+pub struct StatsHistory {
+    buckets: Arc<Mutex<VecDeque<StatsBucket>>>,
+}
+
+impl StatsHistory {
+    /// Create an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Count a packet in the bucket of its second
+    pub fn record(&self, timestamp: DateTime<Utc>, bytes: usize) {
+        let Ok(start) = timestamp.duration_trunc(TimeDelta::seconds(1)) else { return };
+        let mut buckets = self.buckets.lock();
+        
+        let gap = match buckets.back() {
+            Some(last) => (start - last.start).num_seconds(),
+            None => i64::MAX,
+        };
+        
+        if gap <= 0 {
+            // Buckets are contiguous, so an earlier second is found by its offset
+            let index = buckets.len() as i64 - 1 + gap;
+            if let Some(bucket) = usize::try_from(index).ok().and_then(|i| buckets.get_mut(i)) {
+                bucket.packets += 1;
+                bucket.bytes += bytes;
+            }
+            return;
+        }
+        
+        if gap > HISTORY_BUCKETS as i64 {
+            buckets.clear();
+        } else if let Some(last) = buckets.back().map(|last| last.start) {
+            // Seconds without traffic get empty buckets so the series stays continuous
+            for offset in 1..gap {
+                buckets.push_back(StatsBucket { start: last + TimeDelta::seconds(offset), packets: 0, bytes: 0 });
+            }
+        }
+        buckets.push_back(StatsBucket { start, packets: 1, bytes });
+        
+        while buckets.len() > HISTORY_BUCKETS {
+            buckets.pop_front();
+        }
+    }
+    
+    /// The retained buckets, oldest first
+    pub fn buckets(&self) -> Vec<StatsBucket> {
+        self.buckets.lock().iter().cloned().collect()
+    }
+    
+    /// Forget the history
+    pub fn clear(&self) {
+        self.buckets.lock().clear();
+    }
+}
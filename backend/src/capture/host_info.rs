@@ -0,0 +1,87 @@
+// Description of the capturing host for exported captures
+//
+// Like dumpcap, pcapng exports name the operating system, hardware and
+// application in their section header, where Wireshark shows them under
+// Capture File Properties. They are gathered once, on first use: from uname
+// and the CPU model on Unix, and through PowerShell (as the Windows
+// diagnostics are) on Windows.
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// What a pcapng section header says about where the capture was taken
+#[derive(Debug, Clone)]
+pub struct HostInfo {
+    /// Operating system and version, e.g. "Linux 6.8.0-45-generic"
+    pub os: Option<String>,
+    
+    /// CPU model and architecture
+    pub hardware: Option<String>,
+    
+    /// This application and its version
+    pub application: String,
+}
+
+static HOST_INFO: OnceLock<HostInfo> = OnceLock::new();
+
+/// The host description, gathered on the first call
+pub fn get() -> &'static HostInfo {
+    HOST_INFO.get_or_init(|| HostInfo {
+        os: os(),
+        hardware: hardware(),
+        application: format!("RustShark v{}", env!("CARGO_PKG_VERSION")),
+    })
+}
+
+/// Run a PowerShell command and return its standard output
+#[cfg(target_os = "windows")]
+pub fn powershell(command: &str) -> std::io::Result<String> {
+    let output = Command::new("powershell").args(["-Command", command]).output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Trimmed standard output of a command that succeeded, if it printed anything
+#[cfg(not(target_os = "windows"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !text.is_empty()).then_some(text)
+}
+
+#[cfg(target_os = "windows")]
+fn os() -> Option<String> {
+    let os = powershell("$os = Get-CimInstance Win32_OperatingSystem; \"$($os.Caption) $($os.Version)\"").ok()?;
+    let os = os.trim();
+    (!os.is_empty()).then(|| os.to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn os() -> Option<String> {
+    command_output("uname", &["-sr"])
+}
+
+#[cfg(target_os = "windows")]
+fn hardware() -> Option<String> {
+    let cpu = powershell("(Get-CimInstance Win32_Processor | Select-Object -First 1).Name").ok()?;
+    let cpu = cpu.trim();
+    (!cpu.is_empty()).then(|| cpu.to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn hardware() -> Option<String> {
+    let arch = command_output("uname", &["-m"]);
+    let cpu = std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .or_else(|| command_output("sysctl", &["-n", "machdep.cpu.brand_string"]));
+    
+    match (cpu, arch) {
+        (Some(cpu), Some(arch)) => Some(format!("{} ({})", cpu, arch)),
+        (cpu, arch) => cpu.or(arch),
+    }
+}
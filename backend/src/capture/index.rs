@@ -0,0 +1,266 @@
+// Crash-recoverable packet index in a memory-mapped file
+//
+// Every stored packet's summary is written into a fixed-size slot of a ring
+// that lives in a shared memory mapping, so the kernel keeps the contents in
+// the file even if the process dies without flushing anything. Only the
+// summary is kept, not the frame, which keeps a slot small enough to index a
+// long forensic capture. A slot's ID is written last and cleared first, so a
+// torn write leaves an empty slot rather than a mixed-up one.
+
+use anyhow::{Result, anyhow};
+use chrono::DateTime;
+use std::fs::{self, OpenOptions};
+use std::path::Path;
+
+use crate::models::packet::PacketSummary;
+
+/// File magic, bumped with the layout
+const INDEX_MAGIC: &[u8; 8] = b"RSINDEX1";
+
+/// Header: magic, slot size, slot count, slots written, session ID
+const HEADER_LEN: usize = 64;
+const SESSION_OFFSET: usize = 24;
+const SESSION_LEN: usize = 36;
+
+/// Slot layout: ID, timestamp (Unix microseconds), length, then fixed-width strings
+const RECORD_LEN: usize = 256;
+const PROTOCOL_FIELD: (usize, usize) = (24, 24);
+const SOURCE_FIELD: (usize, usize) = (48, 56);
+const DESTINATION_FIELD: (usize, usize) = (104, 56);
+const INFO_FIELD: (usize, usize) = (160, 96);
+
+/// Summaries read back from an index file left by an earlier run
+#[derive(Debug, Clone)]
+pub struct RecoveredIndex {
+    /// Session the indexed packets belong to
+    pub session_id: Option<String>,
+    
+    /// Indexed packets in ID order
+    pub entries: Vec<PacketSummary>,
+}
+
+/// Read the index file left by an earlier run, if there is a usable one
+pub fn recover(path: &Path) -> Result<Option<RecoveredIndex>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let data = fs::read(path)?;
+    if data.len() < HEADER_LEN || &data[..8] != INDEX_MAGIC {
+        return Err(anyhow!("{} is not a packet index file", path.display()));
+    }
+    if read_u32(&data, 8) as usize != RECORD_LEN {
+        return Err(anyhow!("{} has an unsupported slot size", path.display()));
+    }
+    
+    let capacity = read_u32(&data, 12) as usize;
+    let slots = data[HEADER_LEN..].chunks_exact(RECORD_LEN).take(capacity);
+    let mut entries: Vec<PacketSummary> = slots.filter_map(decode_record).collect();
+    entries.sort_by_key(|entry| entry.id);
+    
+    Ok(Some(RecoveredIndex {
+        session_id: Some(read_str(&data[SESSION_OFFSET..SESSION_OFFSET + SESSION_LEN])).filter(|s| !s.is_empty()),
+        entries,
+    }))
+}
+
+/// Decode one slot, skipping empty ones
+fn decode_record(record: &[u8]) -> Option<PacketSummary> {
+    let id = read_u64(record, 0);
+    if id == 0 {
+        return None;
+    }
+    
+    let field = |(offset, len): (usize, usize)| read_str(&record[offset..offset + len]);
+    Some(PacketSummary {
+        id,
+        timestamp: DateTime::from_timestamp_micros(read_u64(record, 8) as i64)?,
+        protocol: field(PROTOCOL_FIELD),
+        source: field(SOURCE_FIELD),
+        destination: field(DESTINATION_FIELD),
+        length: read_u32(record, 16) as usize,
+        info: field(INFO_FIELD),
+        source_host: None,
+        destination_host: None,
+        relative_time: None,
+        delta_time: None,
+        color: None,
+    })
+}
+
+/// Ring of packet summaries in a shared memory mapping
+pub struct PacketIndex {
+    map: mapping::Mapping,
+    capacity: usize,
+}
+
+impl PacketIndex {
+    /// Open (or create) the index file with room for `capacity` packets.
+    ///
+    /// An existing file of the same size is mapped as is, so its contents can
+    /// still be recovered until the next capture resets it.
+    pub fn open(path: &Path, capacity: usize) -> Result<Self> {
+        let capacity = capacity.max(1);
+        let len = HEADER_LEN + capacity * RECORD_LEN;
+        
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let fresh = file.metadata()?.len() != len as u64;
+        if fresh {
+            file.set_len(0)?;
+            file.set_len(len as u64)?;
+        }
+        
+        let mut index = PacketIndex { map: mapping::Mapping::new(&file, len)?, capacity };
+        if fresh {
+            index.reset(None);
+        }
+        Ok(index)
+    }
+    
+    /// Empty the ring and tag it with a new session
+    pub fn reset(&mut self, session_id: Option<&str>) {
+        let bytes = self.map.bytes_mut();
+        bytes.fill(0);
+        bytes[..8].copy_from_slice(INDEX_MAGIC);
+        bytes[8..12].copy_from_slice(&(RECORD_LEN as u32).to_le_bytes());
+        bytes[12..16].copy_from_slice(&(self.capacity as u32).to_le_bytes());
+        write_str(&mut bytes[SESSION_OFFSET..SESSION_OFFSET + SESSION_LEN], session_id.unwrap_or(""));
+    }
+    
+    /// Write a packet's summary into the next slot, overwriting the oldest when full
+    pub fn append(&mut self, summary: &PacketSummary) {
+        let capacity = self.capacity;
+        let bytes = self.map.bytes_mut();
+        
+        let written = read_u64(bytes, 16);
+        let offset = HEADER_LEN + (written as usize % capacity) * RECORD_LEN;
+        let record = &mut bytes[offset..offset + RECORD_LEN];
+        
+        // Invalidate the slot while it is being rewritten
+        record[..8].copy_from_slice(&0u64.to_le_bytes());
+        record[8..16].copy_from_slice(&summary.timestamp.timestamp_micros().to_le_bytes());
+        record[16..20].copy_from_slice(&(summary.length as u32).to_le_bytes());
+        record[20..24].fill(0);
+        for ((field_offset, len), value) in [
+            (PROTOCOL_FIELD, &summary.protocol),
+            (SOURCE_FIELD, &summary.source),
+            (DESTINATION_FIELD, &summary.destination),
+            (INFO_FIELD, &summary.info),
+        ] {
+            write_str(&mut record[field_offset..field_offset + len], value);
+        }
+        record[..8].copy_from_slice(&summary.id.to_le_bytes());
+        
+        bytes[16..24].copy_from_slice(&(written + 1).to_le_bytes());
+    }
+    
+    /// Ask the kernel to write the mapping back to disk
+    pub fn flush(&self) {
+        self.map.flush();
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap_or_default())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap_or_default())
+}
+
+/// Read a NUL-padded string field
+fn read_str(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Write a string into a NUL-padded field, truncating at a character boundary
+fn write_str(field: &mut [u8], value: &str) {
+    let mut end = value.len().min(field.len());
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    field.fill(0);
+    field[..end].copy_from_slice(&value.as_bytes()[..end]);
+}
+
+#[cfg(unix)]
+mod mapping {
+    use anyhow::{Result, anyhow};
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+    
+    /// A shared, writable mapping of a whole file
+    pub struct Mapping {
+        ptr: *mut u8,
+        len: usize,
+    }
+    
+    // The mapping is owned exclusively and only reached through `&mut self`
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+    
+    impl Mapping {
+        pub fn new(file: &File, len: usize) -> Result<Self> {
+            // SAFETY: a fresh shared mapping of `len` bytes of a file at least that long
+            let ptr = unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_SHARED,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(anyhow!("Failed to map the index file: {}", std::io::Error::last_os_error()));
+            }
+            Ok(Mapping { ptr: ptr.cast(), len })
+        }
+        
+        pub fn bytes_mut(&mut self) -> &mut [u8] {
+            // SAFETY: the mapping is `len` bytes long and lives as long as `self`
+            unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+        }
+        
+        pub fn flush(&self) {
+            // SAFETY: flushes the range mapped in `new`
+            unsafe {
+                libc::msync(self.ptr.cast(), self.len, libc::MS_ASYNC);
+            }
+        }
+    }
+    
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            // SAFETY: unmaps the range mapped in `new`, which is not used afterwards
+            unsafe {
+                libc::munmap(self.ptr.cast(), self.len);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod mapping {
+    use anyhow::{Result, anyhow};
+    use std::fs::File;
+    
+    /// Memory-mapped indexes are only implemented for Unix
+    pub struct Mapping {
+        bytes: Vec<u8>,
+    }
+    
+    impl Mapping {
+        pub fn new(_file: &File, _len: usize) -> Result<Self> {
+            Err(anyhow!("The packet index file is only supported on Unix"))
+        }
+        
+        pub fn bytes_mut(&mut self) -> &mut [u8] {
+            &mut self.bytes
+        }
+        
+        pub fn flush(&self) {}
+    }
+}
@@ -0,0 +1,112 @@
+// Bytes in flight of a TCP stream: data sent but not yet acknowledged
+//
+// The stream is one direction of a connection, the data its sender has sent
+// and the other end acknowledges. After each segment of either direction the
+// estimate is the end of the highest sequence number sent less the highest
+// acknowledgement received. While the sender is limited by its congestion
+// window this follows the window: a plateau under steady demand shows a
+// window limit, a long gap between samples a stall. It is as reliable as the
+// capture is complete, so it is unknown (null) until both directions have
+// been seen, and while the receiver acknowledges data the capture never saw
+// being sent (a capture started mid-stream, or seeing one direction only).
+
+use chrono::{DateTime, Utc};
+
+use crate::capture::conversations::ConversationTable;
+use crate::capture::tcp_analysis::{seq_before, Segment};
+use crate::capture::timing;
+use crate::models::packet::Packet;
+use crate::models::timing::{InflightSample, StreamInflight};
+
+/// A segment of the connection, in either direction
+pub struct Observation {
+    timestamp: DateTime<Utc>,
+    packet_id: u64,
+    from_sender: bool,
+    segment: Segment,
+}
+
+/// The observation of `other` if it is a segment of the TCP connection of `packet`
+pub fn observe(packet: &Packet, other: &Packet) -> Option<Observation> {
+    Some(Observation {
+        timestamp: other.timestamp,
+        packet_id: other.id,
+        from_sender: side(packet, other)?,
+        segment: Segment::of(other)?,
+    })
+}
+
+/// Whether `other`, a segment of the TCP connection of `packet`, comes from
+/// the sender of `packet` (true) or from its receiver (false); None for
+/// packets of other connections
+pub fn side(packet: &Packet, other: &Packet) -> Option<bool> {
+    if ConversationTable::transport(other) != "TCP" {
+        return None;
+    }
+    let from_sender = timing::same_stream(packet, "TCP", other);
+    let from_receiver = other.source_ip == packet.destination_ip
+        && other.destination_ip == packet.source_ip
+        && other.source_port == packet.destination_port
+        && other.destination_port == packet.source_port;
+    (from_sender || from_receiver).then_some(from_sender)
+}
+
+/// Bytes in flight from the sender of `packet` after each segment of its
+/// connection, listing at most `limit` samples. None when the packet is not
+/// TCP over IP.
+pub fn stream_inflight(packet: &Packet, mut observations: Vec<Observation>, limit: usize) -> Option<StreamInflight> {
+    let (source_ip, destination_ip) = (packet.source_ip?, packet.destination_ip?);
+    let (source_port, destination_port) = (packet.source_port?, packet.destination_port?);
+    if ConversationTable::transport(packet) != "TCP" {
+        return None;
+    }
+    observations.sort_unstable_by_key(|observation| (observation.timestamp, observation.packet_id));
+    
+    // Sequence number following the highest data sent, and the highest acknowledgement
+    let mut sent: Option<u32> = None;
+    let mut acked: Option<u32> = None;
+    let samples: Vec<InflightSample> = observations
+        .iter()
+        .map(|observation| {
+            let segment = &observation.segment;
+            if observation.from_sender {
+                // A SYN starts the sequence space afresh, possibly for a new connection on the same ports
+                if segment.syn {
+                    acked = None;
+                }
+                if segment.syn || sent.is_none_or(|sent| seq_before(sent, segment.end())) {
+                    sent = Some(segment.end());
+                }
+            } else if segment.has_ack && (segment.syn || acked.is_none_or(|acked| seq_before(acked, segment.ack))) {
+                acked = Some(segment.ack);
+            }
+            
+            let bytes_in_flight = match (sent, acked) {
+                (Some(sent), Some(acked)) if !seq_before(sent, acked) => Some(sent.wrapping_sub(acked) as u64),
+                _ => None,
+            };
+            InflightSample {
+                timestamp: observation.timestamp,
+                packet_id: observation.packet_id,
+                bytes_in_flight,
+            }
+        })
+        .collect();
+    
+    let known: Vec<u64> = samples.iter().filter_map(|sample| sample.bytes_in_flight).collect();
+    let mean = (!known.is_empty()).then(|| known.iter().sum::<u64>() as f64 / known.len() as f64);
+    
+    Some(StreamInflight {
+        packet_id: packet.id,
+        source: format!("{}:{}", source_ip, source_port),
+        destination: format!("{}:{}", destination_ip, destination_port),
+        packets: samples.len(),
+        first_seen: samples.first().map_or(packet.timestamp, |sample| sample.timestamp),
+        last_seen: samples.last().map_or(packet.timestamp, |sample| sample.timestamp),
+        samples_truncated: samples.len() > limit,
+        unknown_samples: samples.len() - known.len(),
+        max_bytes_in_flight: known.iter().copied().max(),
+        mean_bytes_in_flight: mean,
+        samples: samples.into_iter().take(limit).collect(),
+    })
+}
@@ -0,0 +1,229 @@
+// Interface enumeration for the API
+//
+// Listing devices goes through pcap (Npcap on Windows), which can block for
+// seconds. Enumerations run on the blocking thread pool, one at a time: requests
+// arriving while one runs wait for it and take its cached result instead of
+// starting their own, so a burst of /api/interfaces calls costs one enumeration.
+//
+// Devices can be given labels of the team's choosing with `--interface-aliases`,
+// a JSON object of device names to labels (on Windows the names are GUIDs). A
+// label becomes the interface's friendly name wherever the list is shown; the
+// description pcap reported is kept, and devices without a label are untouched.
+
+use log::{info, warn};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::models::interface::InterfaceInfo;
+
+/// Enumerations allowed to run at once
+const MAX_CONCURRENT_ENUMERATIONS: usize = 1;
+
+/// Last enumerated list and when it was taken
+type CachedList = Option<(Vec<InterfaceInfo>, Instant)>;
+
+/// Cached, serialized access to the interface list
+#[derive(Clone)]
+pub struct InterfaceLister {
+    fetch: fn() -> Vec<InterfaceInfo>,
+    aliases: Arc<HashMap<String, String>>,
+    cache: Arc<RwLock<CachedList>>,
+    running: Arc<Semaphore>,
+    cache_duration: Duration,
+    timeout: Duration,
+}
+
+impl InterfaceLister {
+    pub fn new(fetch: fn() -> Vec<InterfaceInfo>, aliases: HashMap<String, String>, cache_duration: Duration, timeout: Duration) -> Self {
+        Self {
+            fetch,
+            aliases: Arc::new(aliases),
+            cache: Arc::new(RwLock::new(None)),
+            running: Arc::new(Semaphore::new(MAX_CONCURRENT_ENUMERATIONS)),
+            cache_duration,
+            timeout,
+        }
+    }
+    
+    /// The cached list, if it is recent enough to use
+    pub fn cached(&self) -> Option<Vec<InterfaceInfo>> {
+        let cached = self.cache.read();
+        let (interfaces, timestamp) = cached.as_ref()?;
+        let elapsed = timestamp.elapsed();
+        (elapsed < self.cache_duration).then(|| {
+            info!("Using cached interface list ({} interfaces, {}s old)",
+                 interfaces.len(), elapsed.as_secs());
+            interfaces.clone()
+        })
+    }
+    
+    /// Replace the cached list
+    pub fn store(&self, interfaces: Vec<InterfaceInfo>) {
+        *self.cache.write() = Some((interfaces, Instant::now()));
+    }
+    
+    /// Enumerate on the calling thread, caching the result
+    pub fn list_blocking(&self) -> Vec<InterfaceInfo> {
+        if let Some(interfaces) = self.cached() {
+            return interfaces;
+        }
+        let mut interfaces = (self.fetch)();
+        apply_aliases(&mut interfaces, &self.aliases);
+        self.store(interfaces.clone());
+        interfaces
+    }
+    
+    /// Get the interface list without blocking the async runtime.
+    ///
+    /// Waits at most the configured timeout, for a running enumeration or a new
+    /// one; past it the last known list (possibly stale or empty) is returned and
+    /// the enumeration completes in the background, still caching its result.
+    pub async fn list(&self) -> Vec<InterfaceInfo> {
+        if let Some(interfaces) = self.cached() {
+            return interfaces;
+        }
+        
+        let enumerate = async {
+            let permit = self.running.clone().acquire_owned().await.ok()?;
+            // Whoever held the permit may just have filled the cache
+            if let Some(interfaces) = self.cached() {
+                return Some(interfaces);
+            }
+            let lister = self.clone();
+            tokio::task::spawn_blocking(move || {
+                // Held until pcap returns, even when the caller has given up
+                let _permit = permit;
+                lister.list_blocking()
+            }).await.ok()
+        };
+        
+        match tokio::time::timeout(self.timeout, enumerate).await {
+            Ok(Some(interfaces)) => interfaces,
+            Ok(None) => self.last_known(),
+            Err(_) => {
+                warn!("Interface enumeration did not finish within {:?}, using the last known list", self.timeout);
+                self.last_known()
+            }
+        }
+    }
+    
+    fn last_known(&self) -> Vec<InterfaceInfo> {
+        self.cache.read().as_ref().map(|(interfaces, _)| interfaces.clone()).unwrap_or_default()
+    }
+}
+
+/// Read the `--interface-aliases` file: a JSON object of device names to labels
+pub fn read_aliases(path: &str) -> Result<HashMap<String, String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+    serde_json::from_str(&text).map_err(|e| format!("'{}' is not a JSON object of device names to labels: {}", path, e))
+}
+
+/// Make each alias the friendly name of its device, leaving the description as it is
+pub fn apply_aliases(interfaces: &mut [InterfaceInfo], aliases: &HashMap<String, String>) {
+    for interface in interfaces {
+        if let Some(alias) = aliases.get(&interface.device_name).filter(|alias| !alias.is_empty()) {
+            interface.friendly_name = Some(alias.clone());
+        }
+    }
+}
+
+/// Print the interface list for `--list-interfaces`: a table like `tcpdump -D`, or JSON.
+/// Output stops quietly when stdout is closed, as when piped into `head`.
+pub fn print_list(interfaces: &[InterfaceInfo], json: bool) {
+    let mut out = std::io::stdout().lock();
+    if json {
+        let _ = writeln!(out, "{}", serde_json::to_string_pretty(interfaces).unwrap_or_default());
+        return;
+    }
+    
+    let rows: Vec<[String; 5]> = interfaces
+        .iter()
+        .enumerate()
+        .map(|(i, interface)| {
+            let mut state = if interface.is_up { "up" } else { "down" }.to_string();
+            if interface.is_loopback {
+                state.push_str(", loopback");
+            }
+            [
+                (i + 1).to_string(),
+                interface.device_name.clone(),
+                interface.friendly_name.clone().unwrap_or_else(|| "-".to_string()),
+                state,
+                if interface.ip_addresses.is_empty() {
+                    "-".to_string()
+                } else {
+                    interface.ip_addresses.join(", ")
+                },
+            ]
+        })
+        .collect();
+    
+    let header = ["#", "NAME", "FRIENDLY NAME", "STATE", "ADDRESSES"].map(String::from);
+    let mut widths = header.clone().map(|title| title.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row.iter().zip(widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        if writeln!(out, "{}", line.join("  ").trim_end()).is_err() {
+            return;
+        }
+    }
+}
+
+/// `IFF_PROMISC` in the interface flags
+#[cfg(target_os = "linux")]
+const IFF_PROMISC: u32 = 0x100;
+
+/// Whether a local interface is in promiscuous mode, as the kernel reports it.
+/// The sysfs flags include the promiscuity libpcap requests through its packet
+/// socket, which `SIOCGIFFLAGS` leaves out.
+#[cfg(target_os = "linux")]
+pub fn interface_promiscuous(interface: &str) -> Option<bool> {
+    let flags = std::fs::read_to_string(format!("/sys/class/net/{}/flags", interface)).ok()?;
+    let flags = u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok()?;
+    Some(flags & IFF_PROMISC != 0)
+}
+
+/// Whether a local interface is in promiscuous mode; not known on this platform
+#[cfg(not(target_os = "linux"))]
+pub fn interface_promiscuous(_interface: &str) -> Option<bool> {
+    None
+}
+
+/// The members of a bond (its slaves) or bridge (its ports), which see the
+/// frames the master shows without saying which link each crossed. None when
+/// the interface is neither.
+#[cfg(target_os = "linux")]
+pub fn member_interfaces(master: &str) -> Option<Vec<String>> {
+    members_in(std::path::Path::new("/sys/class/net"), master)
+}
+
+/// The members of a bond or bridge; not known on this platform
+#[cfg(not(target_os = "linux"))]
+pub fn member_interfaces(_master: &str) -> Option<Vec<String>> {
+    None
+}
+
+/// Members of a bond or bridge as listed in a sysfs net class directory
+#[cfg(any(target_os = "linux", test))]
+pub fn members_in(net_class: &std::path::Path, master: &str) -> Option<Vec<String>> {
+    let dir = net_class.join(master);
+    if let Ok(slaves) = std::fs::read_to_string(dir.join("bonding").join("slaves")) {
+        return Some(slaves.split_whitespace().map(String::from).collect());
+    }
+    
+    let mut ports: Vec<String> = std::fs::read_dir(dir.join("brif"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    ports.sort();
+    Some(ports)
+}
@@ -0,0 +1,269 @@
+// Response times of request/response protocols
+//
+// DNS queries are matched to responses by client address, port, server and
+// transaction ID. HTTP has no such ID, so requests are queued per TCP stream
+// and each response answers the oldest outstanding request, which is how
+// HTTP/1.x pipelining orders them. Queries left unanswered past a timeout
+// (measured in capture time, so loaded files work the same) count as timeouts.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::models::latency::{LatencyProtocol, LatencyReport};
+use crate::models::packet::Packet;
+
+/// Response times kept for the percentile; min, avg and max cover all matches
+const MAX_SAMPLES: usize = 10_000;
+
+/// Outstanding queries tracked per protocol; further queries are ignored
+const MAX_PENDING: usize = 4096;
+
+/// How long a query may wait for its response before it counts as a timeout
+const DNS_TIMEOUT_SECS: i64 = 5;
+const HTTP_TIMEOUT_SECS: i64 = 30;
+
+/// DNS header: ID, flags and the four section counts
+const DNS_HEADER_LEN: usize = 12;
+
+/// Request line prefixes recognized as the start of an HTTP request
+const HTTP_METHODS: &[&[u8]] = &[
+    b"GET ", b"POST ", b"PUT ", b"DELETE ", b"HEAD ", b"OPTIONS ", b"PATCH ", b"CONNECT ", b"TRACE ",
+];
+
+/// A client/server conversation, oriented from the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Flow {
+    client: IpAddr,
+    client_port: u16,
+    server: IpAddr,
+    server_port: u16,
+}
+
+impl Flow {
+    /// The flow of a packet sent by the client
+    fn from_request(packet: &Packet) -> Option<Self> {
+        Some(Flow {
+            client: packet.source_ip?,
+            client_port: packet.source_port?,
+            server: packet.destination_ip?,
+            server_port: packet.destination_port?,
+        })
+    }
+    
+    /// The flow of a packet sent by the server
+    fn from_response(packet: &Packet) -> Option<Self> {
+        Some(Flow {
+            client: packet.destination_ip?,
+            client_port: packet.destination_port?,
+            server: packet.source_ip?,
+            server_port: packet.source_port?,
+        })
+    }
+}
+
+/// Outstanding queries and measured response times of one protocol
+struct Measurements<K> {
+    timeout: TimeDelta,
+    pending: HashMap<K, VecDeque<DateTime<Utc>>>,
+    pending_count: usize,
+    
+    /// Latest packet time seen, the clock timeouts are measured against
+    latest: Option<DateTime<Utc>>,
+    last_sweep: Option<DateTime<Utc>>,
+    
+    responses: usize,
+    timeouts: usize,
+    sum_ms: f64,
+    min_ms: Option<f64>,
+    max_ms: Option<f64>,
+    samples: VecDeque<f64>,
+}
+
+impl<K: Hash + Eq> Measurements<K> {
+    fn new(timeout_secs: i64) -> Self {
+        Measurements {
+            timeout: TimeDelta::seconds(timeout_secs),
+            pending: HashMap::new(),
+            pending_count: 0,
+            latest: None,
+            last_sweep: None,
+            responses: 0,
+            timeouts: 0,
+            sum_ms: 0.0,
+            min_ms: None,
+            max_ms: None,
+            samples: VecDeque::new(),
+        }
+    }
+    
+    /// Record a query; with `queued`, several may wait on the same key
+    /// (pipelined requests), otherwise a repeat is a retransmission
+    fn query(&mut self, key: K, timestamp: DateTime<Utc>, queued: bool) {
+        self.advance(timestamp);
+        if self.pending_count >= MAX_PENDING {
+            return;
+        }
+        
+        let waiting = self.pending.entry(key).or_default();
+        if queued || waiting.is_empty() {
+            waiting.push_back(timestamp);
+            self.pending_count += 1;
+        }
+    }
+    
+    /// Match a response to the oldest query waiting on its key
+    fn response(&mut self, key: &K, timestamp: DateTime<Utc>) {
+        self.advance(timestamp);
+        let Some(waiting) = self.pending.get_mut(key) else { return };
+        let Some(sent) = waiting.pop_front() else { return };
+        if waiting.is_empty() {
+            self.pending.remove(key);
+        }
+        self.pending_count -= 1;
+        
+        // Reordered timestamps would give a negative time; count them as zero
+        let ms = (timestamp - sent).num_microseconds().unwrap_or(0).max(0) as f64 / 1000.0;
+        self.responses += 1;
+        self.sum_ms += ms;
+        self.min_ms = Some(self.min_ms.map_or(ms, |min| min.min(ms)));
+        self.max_ms = Some(self.max_ms.map_or(ms, |max| max.max(ms)));
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ms);
+    }
+    
+    /// Move the clock forward, expiring overdue queries about once a second
+    fn advance(&mut self, timestamp: DateTime<Utc>) {
+        self.latest = Some(self.latest.map_or(timestamp, |latest| latest.max(timestamp)));
+        let due = self.last_sweep.is_none_or(|swept| timestamp - swept >= TimeDelta::seconds(1));
+        if due {
+            self.last_sweep = Some(timestamp);
+            self.expire();
+        }
+    }
+    
+    /// Count queries older than the timeout as timeouts and forget them
+    fn expire(&mut self) {
+        let Some(latest) = self.latest else { return };
+        let deadline = latest - self.timeout;
+        
+        let mut expired = 0;
+        self.pending.retain(|_, waiting| {
+            while waiting.front().is_some_and(|&sent| sent < deadline) {
+                waiting.pop_front();
+                expired += 1;
+            }
+            !waiting.is_empty()
+        });
+        self.pending_count -= expired;
+        self.timeouts += expired;
+    }
+    
+    fn report(&mut self, protocol: LatencyProtocol) -> LatencyReport {
+        self.expire();
+        
+        LatencyReport {
+            protocol,
+            responses: self.responses,
+            timeouts: self.timeouts,
+            pending: self.pending_count,
+            min_ms: self.min_ms,
+            avg_ms: (self.responses > 0).then(|| self.sum_ms / self.responses as f64),
+            max_ms: self.max_ms,
+            p95_ms: percentile(&self.samples, 0.95),
+        }
+    }
+}
+
+/// Nearest-rank percentile of the samples
+fn percentile(samples: &VecDeque<f64>, quantile: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Matches DNS and HTTP requests with their responses
+#[derive(Clone)]
+pub struct LatencyTracker {
+    dns: Arc<Mutex<Measurements<(Flow, u16)>>>,
+    http: Arc<Mutex<Measurements<Flow>>>,
+}
+
+impl LatencyTracker {
+    /// Create a tracker with no measurements
+    pub fn new() -> Self {
+        LatencyTracker {
+            dns: Arc::new(Mutex::new(Measurements::new(DNS_TIMEOUT_SECS))),
+            http: Arc::new(Mutex::new(Measurements::new(HTTP_TIMEOUT_SECS))),
+        }
+    }
+    
+    /// Forget all queries and measurements
+    pub fn clear(&self) {
+        *self.dns.lock() = Measurements::new(DNS_TIMEOUT_SECS);
+        *self.http.lock() = Measurements::new(HTTP_TIMEOUT_SECS);
+    }
+    
+    /// Account for a packet if it is a DNS or HTTP query or response
+    pub fn record(&self, packet: &Packet) {
+        let Some(payload) = packet.payload.as_deref() else { return };
+        
+        if packet.protocol == "DNS" && packet.headers.get("udp").is_some() {
+            if payload.len() < DNS_HEADER_LEN {
+                return;
+            }
+            let id = u16::from_be_bytes([payload[0], payload[1]]);
+            let is_response = payload[2] & 0x80 != 0;
+            self.record_dns(packet, id, is_response);
+        } else if packet.headers.get("tcp").is_some() {
+            if HTTP_METHODS.iter().any(|method| payload.starts_with(method)) {
+                if let Some(flow) = Flow::from_request(packet) {
+                    self.http.lock().query(flow, packet.timestamp, true);
+                }
+            } else if Self::is_final_http_response(payload) {
+                if let Some(flow) = Flow::from_response(packet) {
+                    self.http.lock().response(&flow, packet.timestamp);
+                }
+            }
+        }
+    }
+    
+    /// Response times of one protocol
+    pub fn report(&self, protocol: LatencyProtocol) -> LatencyReport {
+        match protocol {
+            LatencyProtocol::Dns => self.dns.lock().report(protocol),
+            LatencyProtocol::Http => self.http.lock().report(protocol),
+        }
+    }
+    
+    fn record_dns(&self, packet: &Packet, id: u16, is_response: bool) {
+        let mut dns = self.dns.lock();
+        if is_response {
+            if let Some(flow) = Flow::from_response(packet) {
+                dns.response(&(flow, id), packet.timestamp);
+            }
+        } else if let Some(flow) = Flow::from_request(packet) {
+            dns.query((flow, id), packet.timestamp, false);
+        }
+    }
+    
+    /// A status line other than an informational (1xx) one, which precedes the real response
+    fn is_final_http_response(payload: &[u8]) -> bool {
+        payload.starts_with(b"HTTP/1.") && payload.len() > 9 && payload[9] != b'1'
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
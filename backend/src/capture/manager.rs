@@ -1,62 +1,385 @@
 use anyhow::{Result, anyhow};
-use dashmap::DashMap;
 use log::{info, warn, error, debug, trace};
-use pcap::{Device, Capture, Active, DeviceFlags, Address};
+use pcap::{Device, Capture, Active, DeviceFlags, Address, Linktype};
 // use pnet_datalink::interfaces;  // Uncomment if needed and available
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use chrono::{DateTime, Utc};
-use std::process::Command;
 use std::net::IpAddr;
+use std::path::PathBuf;
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use parking_lot::RwLock;
+use uuid::Uuid;
 use tokio::sync::broadcast;
 
-use crate::models::config::AppConfig;
-use crate::models::packet::{Packet, PacketSummary};
-use crate::models::stats::CaptureStats;
+use crate::models::config::{AppConfig, BudgetAction, LinkTypeOverride, API_HOST};
+use crate::models::packet::{AddressScope, FieldMatch, Packet, PacketDirection, PacketSummary, TcpAnalysis};
+use crate::models::stats::{ByteBudget, CaptureDescription, CaptureInfo, CaptureStats, MemoryEstimate, RateTrend, StatsBucket};
 use crate::models::interface::InterfaceInfo;
-use crate::capture::parser::PacketParser;
+use crate::capture::parser::{DecodeAsMap, PacketParser};
+use crate::capture::queue::{self, FrameReceiver, FrameSender};
+use crate::capture::packet_store::PacketStore;
+use crate::capture::file::{LoadSummary, LoadedFile, ReadFiles};
+use crate::capture::direction::{self, LocalAddresses};
+use crate::capture::color_rules::ColorRules;
+use crate::capture::display_filter::DisplayFilter;
+use crate::capture::conversations::ConversationTable;
+use crate::capture::dedup::DuplicateDetector;
+use crate::capture::detail_cache::DetailCache;
+use crate::capture::history::StatsHistory;
+use crate::capture::rate_history::RateHistory;
+use crate::capture::interfaces::{self, InterfaceLister};
+use crate::capture::index::{PacketIndex, RecoveredIndex};
+use crate::capture::latency::LatencyTracker;
+use crate::capture::rtp::RtpStreams;
+use crate::capture::mtu::{self, MtuTracker};
+use crate::capture::neighbors::NeighborTable;
+use crate::capture::access_points::AccessPointTable;
+use crate::capture::events::EventLog;
+use crate::capture::alerts::{AlertLog, DetectionSettings, ThreatDetector};
+use crate::capture::remote::{self, RemoteAuth, RemoteCapture, RemoteFrame};
+use crate::capture::resolver::{NameResolver, DST_HOST_KEY, SRC_HOST_KEY};
+use crate::capture::tcp_analysis::TcpAnalyzer;
+use crate::capture::timing;
+use crate::capture::inflight;
+use crate::capture::carving::{self, Piece};
+use crate::capture::snapshot::{self, Snapshot, SnapshotPacket, SNAPSHOT_VERSION};
+use crate::capture::start_barrier::StartBarrier;
+use crate::capture::trigger::{ArmedTrigger, TriggerStatus};
+use crate::capture::tun::{self, TunCapture};
+use crate::models::alert::Alert;
+use crate::models::conversation::Conversation;
+use crate::models::event::CaptureEvent;
+use crate::models::filter::Filter;
+use crate::models::neighbor::Neighbor;
+use crate::models::wifi::AccessPoint;
+use crate::models::latency::{LatencyProtocol, LatencyReport};
+use crate::models::rtp::RtpStream;
+use crate::models::mtu::MtuReport;
+use crate::models::timing::{StreamInflight, StreamTiming};
+use crate::utils::json_guard::JsonLimits;
 
 #[cfg(target_os = "windows")]
 use crate::capture::windows_helper::WindowsCaptureHelper;
 
-// Static variables for signaling and control
-lazy_static::lazy_static! {
-    static ref STOP_SIGNAL: Mutex<Option<mpsc::Sender<()>>> = Mutex::new(None);
+/// Frames the capture thread may queue ahead of the packet processor
+const PACKET_QUEUE_CAPACITY: usize = 100;
+
+/// Pause after a failed read, doubled with each further failure in a row up
+/// to the maximum, so a source that fails at once (a NIC gone down) is not
+/// read in a tight loop
+const FAILED_READ_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_FAILED_READ_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Interface of capture sessions fed by `inject_packet` instead of a device
+#[cfg(any(test, feature = "test-inject"))]
+pub const INJECTED_INTERFACE: &str = "inject";
+
+/// Per-packet bytes counted by the memory estimate beyond the raw data,
+/// payload and headers: the struct itself, its map entry, and an allowance
+/// for the metadata JSON
+const PACKET_OVERHEAD_BYTES: usize = std::mem::size_of::<Packet>() + 16 + 128;
+
+/// Allowance for the parsed headers of a packet whose headers are not
+/// compacted, as measured by the header compaction benchmark
+const HEADERS_ALLOWANCE_BYTES: usize = 3072;
+
+/// Shared, updatable ID of the current capture session
+pub type SessionId = Arc<RwLock<Option<String>>>;
+
+/// How long to wait for the capture thread to apply a filter change; it
+/// checks for requests between reads, which time out after a second
+const FILTER_APPLY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A filter change for the running capture thread, answered once applied
+struct FilterRequest {
+    filter: String,
+    reply: tokio::sync::oneshot::Sender<Result<(), String>>,
+}
+
+/// How a capture filter change took effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterChange {
+    /// Installed on the running capture handle
+    Live,
+    
+    /// The capture was restarted with the new filter (its buffer was cleared)
+    Restart,
+    
+    /// No capture is running; the filter applies to the next one
+    Stored,
+}
+
+impl FilterChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterChange::Live => "live",
+            FilterChange::Restart => "restart",
+            FilterChange::Stored => "stored",
+        }
+    }
+}
+
+/// A frame read from a capture source
+struct Frame<'a> {
+    data: &'a [u8],
+    
+    /// When the source captured it, for sources that record it
+    captured_at: Option<DateTime<Utc>>,
+}
+
+/// Why a capture source returned no frame
+enum FrameError {
+    /// The read timeout expired (expected on quiet links)
+    Timeout,
+    
+    /// The source has no more frames
+    End,
+    
+    /// A read failed but the source can keep capturing
+    Failed(String),
+    
+    /// The source is unusable (e.g. a lost remote connection)
+    Fatal(String),
+}
+
+/// A blocking source of raw frames for the capture loop
+trait FrameSource: Send + 'static {
+    /// Wait for the next frame
+    fn next_frame(&mut self) -> Result<Frame<'_>, FrameError>;
+    
+    /// Replace the BPF filter without reopening the source
+    fn set_filter(&mut self, filter: &str) -> Result<(), String>;
+}
+
+impl FrameSource for Capture<Active> {
+    fn next_frame(&mut self) -> Result<Frame<'_>, FrameError> {
+        match self.next_packet() {
+            Ok(packet) => {
+                let ts = packet.header.ts;
+                // time_t/suseconds_t are narrower than 64 bits on some platforms
+                #[allow(clippy::unnecessary_cast)]
+                let captured_at = DateTime::from_timestamp(ts.tv_sec as i64, (ts.tv_usec as u32).saturating_mul(1000));
+                Ok(Frame { data: packet.data, captured_at })
+            },
+            Err(pcap::Error::TimeoutExpired) => Err(FrameError::Timeout),
+            Err(pcap::Error::NoMorePackets) => Err(FrameError::End),
+            Err(e) => Err(FrameError::Failed(format!("{:?}", e))),
+        }
+    }
+    
+    fn set_filter(&mut self, filter: &str) -> Result<(), String> {
+        self.filter(filter, true).map_err(|e| e.to_string())
+    }
+}
+
+impl FrameSource for TunCapture {
+    fn next_frame(&mut self) -> Result<Frame<'_>, FrameError> {
+        match TunCapture::next_frame(self) {
+            Ok(Some(data)) => Ok(Frame { data, captured_at: None }),
+            Ok(None) => Err(FrameError::Timeout),
+            Err(e) => Err(FrameError::Fatal(e.to_string())),
+        }
+    }
+    
+    fn set_filter(&mut self, filter: &str) -> Result<(), String> {
+        TunCapture::set_filter(self, filter).map_err(|e| e.to_string())
+    }
+}
+
+impl FrameSource for RemoteCapture {
+    fn next_frame(&mut self) -> Result<Frame<'_>, FrameError> {
+        match RemoteCapture::next_frame(self) {
+            Ok(RemoteFrame::Data(data)) => Ok(Frame { data, captured_at: None }),
+            Ok(RemoteFrame::Timeout) => Err(FrameError::Timeout),
+            Err(e) => Err(FrameError::Fatal(e)),
+        }
+    }
+    
+    fn set_filter(&mut self, filter: &str) -> Result<(), String> {
+        RemoteCapture::set_filter(self, filter).map_err(|e| e.to_string())
+    }
+}
+
+/// Stop request of one capture task: a flag its blocking read loop polls, and
+/// a channel that wakes the task itself. Filter changes for the task arrive
+/// alongside, applied by the read loop between reads.
+struct StopSignal {
+    requested: Arc<AtomicBool>,
+    rx: mpsc::Receiver<()>,
+    filters: std::sync::mpsc::Receiver<FilterRequest>,
+}
+
+/// When a capture task starts reading: after its start delay and, for a
+/// synchronized start, once every capture of the session is ready
+struct CaptureStart {
+    delay_ms: u64,
+    barrier: Option<Arc<StartBarrier>>,
+}
+
+impl CaptureStart {
+    fn delayed(delay_ms: u64) -> Self {
+        Self { delay_ms, barrier: None }
+    }
+    
+    /// Wait for the start, returning false when stopped first
+    fn wait(&self, stop_requested: &AtomicBool) -> bool {
+        // Honor the configured start delay, still reacting to stop requests
+        if self.delay_ms > 0 {
+            info!("Delaying capture start by {}ms", self.delay_ms);
+            let delay_end = Instant::now() + Duration::from_millis(self.delay_ms);
+            while Instant::now() < delay_end {
+                if stop_requested.load(Ordering::Relaxed) {
+                    info!("Capture task stop requested during start delay");
+                    if let Some(barrier) = &self.barrier {
+                        barrier.leave();
+                    }
+                    return false;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+        
+        match &self.barrier {
+            Some(barrier) => {
+                let released = barrier.wait(stop_requested).is_some();
+                if !released {
+                    info!("Capture task stop requested before the synchronized start");
+                }
+                released
+            },
+            None => true,
+        }
+    }
+}
+
+/// A running capture source, stopped independently of any others
+struct CaptureHandle {
+    /// Interface (or remote source) being captured
+    interface: String,
+    
+    requested: Arc<AtomicBool>,
+    stop_tx: mpsc::Sender<()>,
+    
+    /// Filter changes for this capture's thread, which owns the source
+    filter_tx: std::sync::mpsc::Sender<FilterRequest>,
+    
+    /// Background task reading the source
+    task: JoinHandle<()>,
+}
+
+impl CaptureHandle {
+    /// Start the capture task of `interface` with `spawn`, handing it its stop signal
+    fn spawn(interface: String, spawn: impl FnOnce(StopSignal) -> JoinHandle<()>) -> Self {
+        let (stop_tx, rx) = mpsc::channel(1);
+        let (filter_tx, filters) = std::sync::mpsc::channel();
+        let requested = Arc::new(AtomicBool::new(false));
+        let task = spawn(StopSignal { requested: requested.clone(), rx, filters });
+        
+        Self {
+            interface,
+            requested,
+            stop_tx,
+            filter_tx,
+            task,
+        }
+    }
+    
+    /// Ask the capture to stop and wait (a bounded time) for its task to finish
+    async fn stop(self) {
+        self.requested.store(true, Ordering::SeqCst);
+        let _ = self.stop_tx.try_send(());
+        
+        match tokio::time::timeout(Duration::from_secs(5), self.task).await {
+            Ok(_) => info!("Capture task for {} completed gracefully", self.interface),
+            Err(_) => warn!("Timeout waiting for capture task for {} to complete, proceeding anyway", self.interface),
+        }
+    }
+}
+
+/// Frame length bounds, shared with the packet processor so they apply
+/// immediately when changed (0 leaves that side open)
+#[derive(Default)]
+struct SizeLimits {
+    min: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl SizeLimits {
+    fn set(&self, min: usize, max: usize) {
+        self.min.store(min, Ordering::Relaxed);
+        self.max.store(max, Ordering::Relaxed);
+    }
+    
+    /// Whether a frame of `len` captured bytes is within the bounds
+    fn admits(&self, len: usize) -> bool {
+        let min = self.min.load(Ordering::Relaxed);
+        let max = self.max.load(Ordering::Relaxed);
+        len >= min && (max == 0 || len <= max)
+    }
+}
+
+/// What `get_packets_matching` selects on; unset criteria match every packet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PacketCriteria<'a> {
+    /// Label of the capture file the packet was loaded from
+    pub source: Option<&'a str>,
+    
+    pub scope: Option<AddressScope>,
+    pub direction: Option<PacketDirection>,
+    pub analysis: Option<TcpAnalysis>,
+    
+    /// Leave out packets marked as duplicates
+    pub exclude_duplicates: bool,
+}
+
+impl PacketCriteria<'_> {
+    /// Whether no criterion is set
+    pub fn is_empty(&self) -> bool {
+        self.source.is_none() && self.scope.is_none() && self.direction.is_none()
+            && self.analysis.is_none() && !self.exclude_duplicates
+    }
+    
+    fn matches(&self, p: &Packet) -> bool {
+        self.source.is_none_or(|source| p.metadata.get("source").and_then(|v| v.as_str()) == Some(source))
+            && self.scope.is_none_or(|scope| AddressScope::of(p) == scope)
+            && self.direction.is_none_or(|direction| PacketDirection::of(p) == Some(direction))
+            && self.analysis.is_none_or(|analysis| analysis.is_set(p))
+            && !(self.exclude_duplicates && p.metadata.get("duplicate").and_then(|v| v.as_bool()) == Some(true))
+    }
 }
-static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
 
 /// Manages packet capture operations
 pub struct CaptureManager {
     /// Application configuration
     config: AppConfig,
     
-    /// Packet storage - using a thread-safe concurrent hashmap
-    packets: Arc<DashMap<u64, Packet>>,
+    /// Packet storage - concurrent maps, sharded with --storage-shards
+    packets: Arc<PacketStore>,
     
     /// Capture statistics
     stats: CaptureStats,
     
     /// Flag indicating if capture is running
-    is_running: AtomicBool,
+    is_running: Arc<AtomicBool>,
+    
+    /// Next packet ID of the session, shared with the processor; restarts at 1 with each session
+    next_id: Arc<AtomicU64>,
     
-    /// Next packet ID
-    next_id: AtomicU64,
+    /// Capture sources currently running, each with its own stop signal
+    captures: Vec<CaptureHandle>,
     
-    /// Handle to background capture task
-    capture_task: Option<JoinHandle<()>>,
+    /// Frames outside these sizes are dropped before decoding
+    size_limits: Arc<SizeLimits>,
     
     /// Shared statistics
     shared_stats: Option<Arc<tokio::sync::Mutex<CaptureStats>>>,
     
     /// Cached interface info - to avoid repeated expensive calls
-    cached_interfaces: RwLock<Option<(Vec<InterfaceInfo>, Instant)>>,
-    
-    /// Cache duration for interfaces (in seconds)
-    interface_cache_duration: u64,
+    interfaces: InterfaceLister,
     
     /// Broadcast channel for statistics updates
     stats_tx: broadcast::Sender<CaptureStats>,
@@ -66,6 +389,112 @@ pub struct CaptureManager {
     
     /// Minimum interval between stats broadcasts (milliseconds)
     stats_broadcast_interval_ms: u64,
+    
+    /// Security alerts raised by the traffic detector
+    alerts: AlertLog,
+    
+    /// Broadcast channel carrying every stored packet (for live consumers like tail mode)
+    packet_tx: broadcast::Sender<Packet>,
+    
+    /// Reverse-DNS resolver (only when name resolution is enabled)
+    resolver: Option<NameResolver>,
+    
+    /// Rate-limited feed of capture warnings and errors
+    events: EventLog,
+    
+    /// Port to protocol overrides consulted by the parser
+    decode_as: DecodeAsMap,
+    
+    /// ID of the current capture session (shared with WebSocket connections)
+    session_id: SessionId,
+    
+    /// Threshold trigger waiting to start a capture (armed mode)
+    trigger: Option<ArmedTrigger>,
+    
+    /// Devices discovered from LLDP and CDP announcements
+    neighbors: NeighborTable,
+    
+    /// Wi-Fi access points discovered from beacons and probe responses
+    access_points: AccessPointTable,
+    
+    /// Oversized packets and reported next-hop MTUs per destination
+    mtu: MtuTracker,
+    
+    /// DNS and HTTP response times
+    latency: LatencyTracker,
+    
+    /// Loss and jitter per RTP stream
+    rtp: RtpStreams,
+    
+    /// Traffic and application protocols per 5-tuple
+    conversations: ConversationTable,
+    
+    /// Sequence state for retransmission, keep-alive and window update flags
+    tcp_analysis: TcpAnalyzer,
+    
+    /// Recent frame hashes for marking duplicates
+    duplicates: DuplicateDetector,
+    
+    /// Serialized details of recently fetched packets
+    details: DetailCache,
+    
+    /// Addresses of the capture interface, which packet directions are judged against
+    local_addresses: LocalAddresses,
+    
+    /// Per-second packet and byte counts of the recent past
+    history: StatsHistory,
+    
+    /// Packets and bytes per second over the last minute, sampled by a tick task
+    rates: RateHistory,
+    
+    /// Display filters that give listed packets a color label
+    color_rules: ColorRules,
+    
+    /// When the running capture was started, for the `max_capture_seconds` cap
+    capture_started: Option<Instant>,
+    
+    /// Promiscuous mode the capture device actually entered, None when unverified
+    actual_promiscuous: Option<bool>,
+    
+    /// Barrier releasing the captures of a synchronized start, kept for its release time
+    start_barrier: Option<Arc<StartBarrier>>,
+    
+    /// Link type the running (or last) capture's frames are decoded as
+    link_type: Linktype,
+    
+    /// Interface the running (or last) capture reads, which a trigger may
+    /// have started on another than the selected one
+    captured_interface: Option<String>,
+    
+    /// When the capture last received a frame (Unix milliseconds), for the idle timeout
+    last_packet_ms: Arc<AtomicI64>,
+    
+    /// Bytes the live capture has taken in against `max_total_bytes`; with the
+    /// evict action, the bytes of its frames still stored
+    budget_used: Arc<AtomicU64>,
+    
+    /// Set when a capture with the stop action reaches its byte budget
+    budget_exhausted: Arc<AtomicBool>,
+    
+    /// Description and tags documenting the current capture, shared with the
+    /// packet processor for its stats broadcasts
+    description: Arc<RwLock<CaptureDescription>>,
+    
+    /// Saved filters by ID
+    filters: HashMap<String, Filter>,
+    
+    /// Memory-mapped index of stored packets, when `--index-file` is set
+    index: Option<Arc<parking_lot::Mutex<PacketIndex>>>,
+    
+    /// Packet summaries recovered from the index of an earlier run
+    recovered_index: Option<RecoveredIndex>,
+    
+    /// Capture files the stored packets were loaded from, with their hashes
+    loaded_files: Vec<LoadedFile>,
+    
+    /// Queue of a running injected capture session
+    #[cfg(any(test, feature = "test-inject"))]
+    injector: Option<FrameSender>,
 }
 
 impl CaptureManager {
@@ -73,57 +502,106 @@ impl CaptureManager {
     pub fn new(config: AppConfig) -> Self {
         // Create a broadcast channel with capacity for 100 messages
         let (stats_tx, _) = broadcast::channel(100);
+        let (packet_tx, _) = broadcast::channel(1024);
+        
+        let packets = Arc::new(PacketStore::new(config.storage_shards));
+        let details = DetailCache::new();
+        let resolver = config.resolve_names.then(|| NameResolver::new(packets.clone(), details.clone(), config.max_tracked_addresses));
+        let decode_as = Arc::new(RwLock::new(config.decode_as.clone()));
+        let duplicates = DuplicateDetector::new(config.dedup_window);
+        let color_rules = ColorRules::load(config.color_rules_file.as_ref().map(PathBuf::from));
+        let size_limits = Arc::new(SizeLimits::default());
+        size_limits.set(config.min_packet_size, config.max_packet_size);
+        // Cache interface results for 60 seconds
+        let interfaces = InterfaceLister::new(
+            Self::get_pnet_interfaces,
+            config.interface_aliases.clone(),
+            Duration::from_secs(60),
+            Duration::from_secs(config.interface_timeout_secs),
+        );
         
         Self {
             config,
-            packets: Arc::new(DashMap::new()),
+            packets,
             stats: CaptureStats::default(),
-            is_running: AtomicBool::new(false),
-            next_id: AtomicU64::new(1),
-            capture_task: None,
+            is_running: Arc::new(AtomicBool::new(false)),
+            next_id: Arc::new(AtomicU64::new(1)),
+            captures: Vec::new(),
+            size_limits,
             shared_stats: None,
-            cached_interfaces: RwLock::new(None),
-            interface_cache_duration: 60, // Cache interface results for 60 seconds
+            interfaces,
             stats_tx,
             last_stats_broadcast: RwLock::new(Instant::now()),
             stats_broadcast_interval_ms: 1000, // Default interval is 1 second
+            alerts: AlertLog::new(),
+            packet_tx,
+            resolver,
+            events: EventLog::new(),
+            decode_as,
+            session_id: SessionId::default(),
+            trigger: None,
+            neighbors: NeighborTable::new(),
+            access_points: AccessPointTable::new(),
+            mtu: MtuTracker::new(),
+            latency: LatencyTracker::new(),
+            rtp: RtpStreams::new(),
+            conversations: ConversationTable::new(),
+            tcp_analysis: TcpAnalyzer::new(),
+            duplicates,
+            details,
+            local_addresses: LocalAddresses::default(),
+            history: StatsHistory::new(),
+            rates: RateHistory::new(),
+            color_rules,
+            capture_started: None,
+            actual_promiscuous: None,
+            start_barrier: None,
+            link_type: Linktype::ETHERNET,
+            captured_interface: None,
+            last_packet_ms: Arc::new(AtomicI64::new(0)),
+            budget_used: Arc::new(AtomicU64::new(0)),
+            budget_exhausted: Arc::new(AtomicBool::new(false)),
+            description: Arc::default(),
+            filters: HashMap::new(),
+            index: None,
+            recovered_index: None,
+            loaded_files: Vec::new(),
+            #[cfg(any(test, feature = "test-inject"))]
+            injector: None,
         }
     }
     
-    /// List available network interfaces - bypassing problematic pnet_datalink on Windows
-    pub fn list_interfaces(&self) -> Vec<String> {
-            // On non-Windows platforms, use the original code
-            self.get_interface_info()
-            .into_iter()
-                .map(|info| info.device_name)
-            .collect()
+    /// Start a new capture session and return its generated ID
+    fn new_session(&mut self) -> String {
+        let id = Uuid::new_v4().to_string();
+        *self.session_id.write() = Some(id.clone());
+        info!("Capture session {}", id);
+        id
+    }
+    
+    /// Get the ID of the current capture session
+    pub fn get_session_id(&self) -> Option<String> {
+        self.session_id.read().clone()
+    }
+    
+    /// Get a shared handle to the current session ID that follows session changes
+    pub fn session_handle(&self) -> SessionId {
+        self.session_id.clone()
     }
     
     /// Get detailed information about available network interfaces
     pub fn get_interface_info(&self) -> Vec<InterfaceInfo> {
-        // Check if we have a valid cached result
-        {
-            let cached = self.cached_interfaces.read();
-            if let Some((interfaces, timestamp)) = &*cached {
-                let elapsed = timestamp.elapsed();
-                if elapsed < Duration::from_secs(self.interface_cache_duration) {
-                    info!("Using cached interface list ({} interfaces, {}s old)", 
-                         interfaces.len(), elapsed.as_secs());
-                    return interfaces.clone();
-                }
-            }
-        }
-        
-        // If we don't have cached data or it's expired, fetch new data
-        let interfaces = self.fetch_interface_info();
-        
-        // Cache the result
-        {
-            let mut cache = self.cached_interfaces.write();
-            *cache = Some((interfaces.clone(), Instant::now()));
-        }
-        
-        interfaces
+        self.interfaces.list_blocking()
+    }
+    
+    /// Get a handle for listing interfaces off the async threads, sharing this manager's cache
+    pub fn interface_lister(&self) -> InterfaceLister {
+        self.interfaces.clone()
+    }
+    
+    /// Get a handle on the packet buffer, for compacting it off the async threads
+    pub fn packet_store(&self) -> Arc<PacketStore> {
+        self.packets.clone()
     }
     
     
@@ -141,19 +619,45 @@ impl CaptureManager {
         };
         
         info!("Starting packet capture on interface: {}", interface);
+        self.captured_interface = Some(interface.clone());
         
         // Reset any previous state
         self.packets.clear();
+        self.next_id.store(1, Ordering::SeqCst);
+        self.events.clear_history();
+        self.alerts.clear();
+        self.neighbors.clear();
+        self.access_points.clear();
+        self.mtu.reset(self.config.mtu.or_else(|| mtu::interface_mtu(&interface)));
+        self.latency.clear();
+        self.rtp.clear();
+        self.conversations.clear();
+        self.tcp_analysis.clear();
+        self.duplicates.clear();
+        self.details.clear();
+        self.history.clear();
+        self.rates.clear();
+        self.loaded_files.clear();
         self.stats = CaptureStats::default();
         self.stats.start_time = Some(Utc::now());
         self.stats.end_time = None;
         
-        // Reset the broadcaster to ensure we start with a clean state
-        let (new_tx, _) = broadcast::channel(100);
-        self.stats_tx = new_tx;
+        // The idle timeout counts from the start until the first packet, and
+        // the length cap from the start regardless of stats resets
+        self.capture_started = Some(Instant::now());
+        self.actual_promiscuous = None;
+        self.start_barrier = None;
+        self.last_packet_ms.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+        self.budget_used.store(0, Ordering::Relaxed);
+        self.budget_exhausted.store(false, Ordering::Relaxed);
         
-        // Reset the stop flag
-        STOP_REQUESTED.store(false, Ordering::SeqCst);
+        // Tag everything from this run with a fresh session ID. The stats
+        // broadcaster is kept across sessions so subscribers stay connected;
+        // they tell sessions apart by this ID.
+        let session_id = self.new_session();
+        if let Some(index) = &self.index {
+            index.lock().reset(Some(&session_id));
+        }
         
         // Get interface from config or find default
         let interface_name = match &self.config.interface {
@@ -172,6 +676,51 @@ impl CaptureManager {
         // Reset logging counters when starting a new capture
         crate::utils::logging::reset_counters();
         
+        // Remote sources bypass the local device setup entirely; their
+        // addresses are not known, so packets get no direction
+        if remote::is_remote_source(&interface_name) {
+            self.local_addresses = LocalAddresses::default();
+            return self.start_remote_capture(interface_name);
+        }
+        
+        // Injected frames skip the device but are processed like captured ones
+        #[cfg(any(test, feature = "test-inject"))]
+        if interface_name == INJECTED_INTERFACE {
+            self.local_addresses = LocalAddresses::default();
+            return self.start_injected_capture();
+        }
+        
+        // Tun devices carry raw IP and are not listed with the capture
+        // interfaces; the device's own addresses give packet directions
+        if tun::is_tun_source(&interface_name) {
+            let device = interface_name.trim_start_matches(tun::TUN_SCHEME);
+            self.local_addresses = self.get_interface_info()
+                .iter()
+                .find(|info| info.device_name == device)
+                .map(LocalAddresses::of_interface)
+                .unwrap_or_default();
+            return self.start_tun_capture(interface_name);
+        }
+        
+        self.local_addresses = self.get_interface_info()
+            .iter()
+            .find(|info| info.device_name == interface_name)
+            .map(LocalAddresses::of_interface)
+            .unwrap_or_default();
+        if self.local_addresses.is_empty() {
+            warn!("No addresses known for {}, packet directions will not be recorded", interface_name);
+        }
+        
+        // Frames on a bond or bridge do not say which link they crossed; its
+        // members' do, so they are captured instead when asked to
+        if self.config.capture_members {
+            match interfaces::member_interfaces(&interface_name) {
+                Some(members) if !members.is_empty() => return self.start_member_captures(&interface_name, members),
+                Some(_) => return Err(anyhow!("{} has no member interfaces to capture", interface_name)),
+                None => self.events.warn(format!("{} is not a bond or bridge, capturing it directly", interface_name)),
+            }
+        }
+        
         // On Windows, use a very simple device creation approach
         // that is known to have fewer compatibility issues
         #[cfg(target_os = "windows")]
@@ -190,133 +739,64 @@ impl CaptureManager {
             // Try to create capture in one step
             let capture_result = Capture::from_device(device)
                 .map(|c| c.promisc(self.config.promiscuous)
-            .snaplen(65535)
-                     .timeout(1000))
+                    .snaplen(65535)
+                    .timeout(1000))
                 .and_then(|c| c.open());
             
             // Check if the standard pcap approach worked
             match capture_result {
                 Ok(mut active_capture) => {
                     info!("Successfully opened capture using standard pcap");
-        
-        // Apply filter if specified
-        if let Some(filter) = &self.config.filter {
+                    self.verify_promiscuous(&interface_name);
+                    
+                    // Apply filter if specified
+                    if let Some(filter) = &self.capture_filter() {
                         match active_capture.filter(filter.as_str(), true) {
                             Ok(_) => info!("Applied filter: {}", filter),
-                            Err(e) => warn!("Failed to apply filter: {}", e)
+                            Err(e) => self.events.warn(format!("Failed to apply filter: {}", e))
                         }
-        }
-        
-        // Reset statistics
-        self.stats = CaptureStats::default();
-        self.stats.start_time = Some(Utc::now());
-        
-        // Create channel for packet processing
-        let (tx, mut rx) = mpsc::channel(100);
-        
-        // Clone data for the capture task
-        let packets = self.packets.clone();
-        let config = self.config.clone();
-        
-        // Create shared stats using Arc and Mutex for thread-safety
-        let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
-        let stats_clone = stats.clone();
-        
-        // Set running flag
-        self.is_running.store(true, Ordering::SeqCst);
-        
-        // Launch background task for capture
-                    let capture_task = tokio::spawn(Self::run_capture(
+                    }
+                    
+                    // Reset statistics
+                    self.stats = CaptureStats::default();
+                    self.stats.start_time = Some(Utc::now());
+                    
+                    // Create the queue for packet processing
+                    let (tx, rx) = queue::channel(PACKET_QUEUE_CAPACITY, self.config.overflow_policy);
+                    
+                    // Create shared stats using Arc and Mutex for thread-safety
+                    let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
+                    
+                    // Set running flag
+                    self.is_running.store(true, Ordering::SeqCst);
+                    
+                    // Launch background task for capture
+                    let link_type = active_capture.get_datalink();
+                    let start = CaptureStart::delayed(self.config.start_delay_ms);
+                    let events = self.events.clone();
+                    let capture = CaptureHandle::spawn(interface_name.clone(), |stop| tokio::spawn(Self::run_capture(
                         active_capture,
                         tx,
-                        interface_name
-                    ));
+                        interface_name,
+                        None,
+                        start,
+                        events,
+                        stop
+                    )));
                     
                     // Launch background task for processing
-        let stats_tx_clone = self.stats_tx.clone();
-        let process_task = tokio::spawn(async move {
-            let parser = PacketParser::new();
-            
-                        while let Some((data, timestamp)) = rx.recv().await {
-                            // Store the length before we move data
-                            let data_len = data.len();
-                
-                            match parser.parse_packet(data, &config.interface.clone().unwrap_or_default()) {
-                    Ok(mut packet) => {
-                                    // Update timestamp
-                                    packet.timestamp = timestamp;
-                                    
-                                    // Generate ID and store packet
-                        let id = Self::generate_id(&packets);
-                        packet.id = id;
-                        
-                                    // Insert packet into storage
-                        packets.insert(id, packet.clone());
-                        
-                                    // Update stats
-                                    if let Ok(mut stats) = stats.try_lock() {
-                        stats.total_packets += 1;
-                                        stats.total_bytes += data_len; // Use stored length
-                        
-                                        // Update protocol stats
-                        let protocol = packet.protocol.clone();
-                                        let protocol_count = stats.protocols.entry(protocol).or_insert(0);
-                                        *protocol_count += 1;
-                                        
-                                        // Update source stats
-                                        if let Some(source) = packet.source_ip.as_ref().map(|ip| ip.to_string()) {
-                                            let source_count = stats.sources.entry(source).or_insert(0);
-                                            *source_count += 1;
-                                        }
-                                        
-                                        // Update destination stats
-                                        if let Some(dest) = packet.destination_ip.as_ref().map(|ip| ip.to_string()) {
-                                            let dest_count = stats.destinations.entry(dest).or_insert(0);
-                                            *dest_count += 1;
-                                        }
-                                        
-                                        // Calculate packet rate
-                                        if let Some(start_time) = stats.start_time {
-                                            let elapsed = Utc::now().signed_duration_since(start_time);
-                                            let elapsed_secs = elapsed.num_milliseconds() as f64 / 1000.0;
-                                            if elapsed_secs > 0.0 {
-                                                stats.packet_rate = stats.total_packets as f64 / elapsed_secs;
-                                                stats.data_rate = stats.total_bytes as f64 / elapsed_secs;
-                                            }
-                                        }
-                                        
-                                        // Update the packet count in the logger
-                                        crate::utils::logging::update_packet_count(stats.total_packets);
-                                        
-                                        // Broadcast the updated stats (using cloned stats_tx)
-                                        let _ = stats_tx_clone.send(stats.clone());
-                                    }
-                                    
-                                    // Enforce buffer size limit
-                                    Self::enforce_buffer_limit(&packets, config.buffer_size);
-                                },
-                                Err(e) => {
-                                    error!("Failed to parse packet: {}", e);
-                                    if let Ok(mut stats) = stats.try_lock() {
-                                        stats.errors += 1;
-                                    }
-                                }
-                            }
-                        }
-                        
-                        info!("Packet processor task stopped");
-                    });
+                    self.spawn_packet_processor(rx, stats.clone(), link_type);
                     
                     // Save shared stats
-                    self.shared_stats = Some(stats_clone);
+                    self.shared_stats = Some(stats);
                     
                     // Save capture task handle
-                    self.capture_task = Some(capture_task);
+                    self.captures.push(capture);
                     
                     Ok(())
                 },
                 Err(e) => {
-                    warn!("Standard pcap approach failed: {}", e);
+                    self.events.warn(format!("Standard pcap approach failed: {}", e));
                     warn!("Trying fallback Windows capture method");
                     
                     // Try the Windows helper fallback method
@@ -324,121 +804,56 @@ impl CaptureManager {
                     self.stats = CaptureStats::default();
                     self.stats.start_time = Some(Utc::now());
                     
-                    // Create channel for packet processing
-                    let (tx, mut rx) = mpsc::channel(100);
-                    
-                    // Clone data for the capture task
-                    let packets = self.packets.clone();
-                    let config = self.config.clone();
+                    // Create the queue for packet processing
+                    let (tx, rx) = queue::channel(PACKET_QUEUE_CAPACITY, self.config.overflow_policy);
                     
                     // Create shared stats using Arc and Mutex for thread-safety
                     let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
-                    let stats_clone = stats.clone();
                     
                     // Set running flag
                     self.is_running.store(true, Ordering::SeqCst);
                     
+                    // The helper feeds a channel; relay it into the queue
+                    let (helper_tx, mut helper_rx) = mpsc::channel(100);
+                    tokio::task::spawn_blocking(move || {
+                        while let Some((data, timestamp)) = helper_rx.blocking_recv() {
+                            if tx.send((data, timestamp, None)).is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    
                     // Try to start capture using the Windows helper
                     match WindowsCaptureHelper::start_capture(
                         &interface_name, 
-                        self.config.filter.as_deref(),
-                        tx
+                        self.capture_filter().as_deref(),
+                        helper_tx
                     ) {
                         Ok(handle) => {
                             info!("Successfully started capture using Windows helper");
                             
                             // Convert std::thread::JoinHandle to tokio::task::JoinHandle
-                            let capture_task = tokio::task::spawn_blocking(move || {
+                            // (the helper thread takes no stop signal)
+                            let events = self.events.clone();
+                            let capture = CaptureHandle::spawn(interface_name.clone(), |_stop| tokio::task::spawn_blocking(move || {
                                 if let Err(e) = handle.join() {
-                                    error!("Windows capture helper thread panicked: {:?}", e);
+                                    events.error(format!("Windows capture helper thread panicked: {:?}", e));
                                 }
-                            });
+                            }));
                             
                             // Launch background task for processing
-                            let stats_tx_clone = self.stats_tx.clone();
-                            let process_task = tokio::spawn(async move {
-                                let parser = PacketParser::new();
-                                
-                                while let Some((data, timestamp)) = rx.recv().await {
-                                    // Store the length before we move data
-                                    let data_len = data.len();
-                                    
-                                    match parser.parse_packet(data, &config.interface.clone().unwrap_or_default()) {
-                                        Ok(mut packet) => {
-                                            // Update timestamp
-                                            packet.timestamp = timestamp;
-                                            
-                                            // Generate ID and store packet
-                                            let id = Self::generate_id(&packets);
-                                            packet.id = id;
-                                            
-                                            // Insert packet into storage
-                                            packets.insert(id, packet.clone());
-                                            
-                                            // Update stats
-                                            if let Ok(mut stats) = stats.try_lock() {
-                                                stats.total_packets += 1;
-                                                stats.total_bytes += data_len; // Use stored length
-                                                
-                                                // Update protocol stats
-                                                let protocol = packet.protocol.clone();
-                                                let protocol_count = stats.protocols.entry(protocol).or_insert(0);
-                                                *protocol_count += 1;
-                                                
-                                                // Update source stats
-                                                if let Some(source) = packet.source_ip.as_ref().map(|ip| ip.to_string()) {
-                                                    let source_count = stats.sources.entry(source).or_insert(0);
-                                                    *source_count += 1;
-                                                }
-                                                
-                                                // Update destination stats
-                                                if let Some(dest) = packet.destination_ip.as_ref().map(|ip| ip.to_string()) {
-                                                    let dest_count = stats.destinations.entry(dest).or_insert(0);
-                                                    *dest_count += 1;
-                                                }
-                                                
-                                                // Calculate packet rate
-                                                if let Some(start_time) = stats.start_time {
-                                                    let elapsed = Utc::now().signed_duration_since(start_time);
-                                                    let elapsed_secs = elapsed.num_milliseconds() as f64 / 1000.0;
-                                                    if elapsed_secs > 0.0 {
-                                                        stats.packet_rate = stats.total_packets as f64 / elapsed_secs;
-                                                        stats.data_rate = stats.total_bytes as f64 / elapsed_secs;
-                                                    }
-                                                }
-                                                
-                                                // Update the packet count in the logger
-                                                crate::utils::logging::update_packet_count(stats.total_packets);
-                                                
-                                                // Broadcast the updated stats (using cloned stats_tx)
-                                                let _ = stats_tx_clone.send(stats.clone());
-                                            }
-                                            
-                                            // Enforce buffer size limit
-                                            Self::enforce_buffer_limit(&packets, config.buffer_size);
-                    },
-                    Err(e) => {
-                        error!("Failed to parse packet: {}", e);
-                                            if let Ok(mut stats) = stats.try_lock() {
-                        stats.errors += 1;
-                                            }
-                                        }
-                                    }
-                                }
-                                
-                                info!("Packet processor task stopped");
-                            });
+                            self.spawn_packet_processor(rx, stats.clone(), Linktype::ETHERNET);
                             
                             // Save shared stats
-                            self.shared_stats = Some(stats_clone);
+                            self.shared_stats = Some(stats);
                             
                             // Save capture task handle
-                            self.capture_task = Some(capture_task);
+                            self.captures.push(capture);
                             
                             Ok(())
                         },
                         Err(e) => {
-                            error!("Failed to use Windows helper: {}", e);
+                            self.events.error(format!("Failed to use Windows helper: {}", e));
                             Err(anyhow!("Both standard and fallback capture methods failed. Please check your Npcap installation and run as administrator."))
                         }
                     }
@@ -483,12 +898,13 @@ impl CaptureManager {
                     match capture.open() {
                         Ok(mut active_capture) => {
                             info!("Successfully opened capture");
+                            self.verify_promiscuous(&interface_name);
                             
                             // Apply filter if specified
-                            if let Some(filter) = &self.config.filter {
+                            if let Some(filter) = &self.capture_filter() {
                                 match active_capture.filter(filter.as_str(), true) {
                                     Ok(_) => info!("Applied filter: {}", filter),
-                                    Err(e) => warn!("Failed to apply filter: {}", e)
+                                    Err(e) => self.events.warn(format!("Failed to apply filter: {}", e))
                                 }
                             }
                             
@@ -496,187 +912,824 @@ impl CaptureManager {
                             self.stats = CaptureStats::default();
                             self.stats.start_time = Some(Utc::now());
                             
-                            // Create channel for packet processing
-                            let (tx, mut rx) = mpsc::channel(100);
-                            
-                            // Clone data for the capture task
-                            let packets = self.packets.clone();
-                            let config = self.config.clone();
+                            // Create the queue for packet processing
+                            let (tx, rx) = queue::channel(PACKET_QUEUE_CAPACITY, self.config.overflow_policy);
                             
                             // Create shared stats using Arc and Mutex for thread-safety
                             let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
-                            let stats_clone = stats.clone();
                             
                             // Set running flag
                             self.is_running.store(true, Ordering::SeqCst);
                             
                             // Launch background task for capture
-                            let capture_task = tokio::spawn(Self::run_capture(
+                            let link_type = active_capture.get_datalink();
+                            let start = CaptureStart::delayed(self.config.start_delay_ms);
+                            let events = self.events.clone();
+                            let capture = CaptureHandle::spawn(interface_name.clone(), |stop| tokio::spawn(Self::run_capture(
                                 active_capture,
                                 tx,
-                                interface_name
-                            ));
+                                interface_name,
+                                None,
+                                start,
+                                events,
+                                stop
+                            )));
                             
                             // Launch background task for processing
-                            let stats_tx_clone = self.stats_tx.clone();
-                            let process_task = tokio::spawn(async move {
-                                let parser = PacketParser::new();
-                                
-                                while let Some((data, timestamp)) = rx.recv().await {
-                                    // Store the length before we move data
-                                    let data_len = data.len();
-                                    
-                                    match parser.parse_packet(data, &config.interface.clone().unwrap_or_default()) {
-                                        Ok(mut packet) => {
-                                            // Update timestamp
-                                            packet.timestamp = timestamp;
-                                            
-                                            // Generate ID and store packet
-                                            let id = Self::generate_id(&packets);
-                                            packet.id = id;
-                                            
-                                            // Insert packet into storage
-                                            packets.insert(id, packet.clone());
-                                            
-                                            // Update stats
-                                            if let Ok(mut stats) = stats.try_lock() {
-                                                stats.total_packets += 1;
-                                                stats.total_bytes += data_len; // Use stored length
-                                                
-                                                // Update protocol stats
-                                                let protocol = packet.protocol.clone();
-                                                let protocol_count = stats.protocols.entry(protocol).or_insert(0);
-                                                *protocol_count += 1;
-                                                
-                                                // Update source stats
-                                                if let Some(source) = packet.source_ip.as_ref().map(|ip| ip.to_string()) {
-                                                    let source_count = stats.sources.entry(source).or_insert(0);
-                                                    *source_count += 1;
-                                                }
-                                                
-                                                // Update destination stats
-                                                if let Some(dest) = packet.destination_ip.as_ref().map(|ip| ip.to_string()) {
-                                                    let dest_count = stats.destinations.entry(dest).or_insert(0);
-                                                    *dest_count += 1;
-                                                }
-                                                
-                                                // Calculate packet rate
-                                                if let Some(start_time) = stats.start_time {
-                                                    let elapsed = Utc::now().signed_duration_since(start_time);
-                                                    let elapsed_secs = elapsed.num_milliseconds() as f64 / 1000.0;
-                                                    if elapsed_secs > 0.0 {
-                                                        stats.packet_rate = stats.total_packets as f64 / elapsed_secs;
-                                                        stats.data_rate = stats.total_bytes as f64 / elapsed_secs;
-                                                    }
-                                                }
-                                                
-                                                // Update the packet count in the logger
-                                                crate::utils::logging::update_packet_count(stats.total_packets);
-                                                
-                                                // Broadcast the updated stats (using cloned stats_tx)
-                                                let _ = stats_tx_clone.send(stats.clone());
-                                            }
-                                            
-                                            // Enforce buffer size limit
-                                            Self::enforce_buffer_limit(&packets, config.buffer_size);
-                                        },
-                                        Err(e) => {
-                                            error!("Failed to parse packet: {}", e);
-                                            if let Ok(mut stats) = stats.try_lock() {
-                                                stats.errors += 1;
-                                            }
-                                        }
-                                    }
-                                }
-                                
-                                info!("Packet processor task stopped");
-                            });
+                            self.spawn_packet_processor(rx, stats.clone(), link_type);
                             
                             // Save shared stats
-                            self.shared_stats = Some(stats_clone);
+                            self.shared_stats = Some(stats);
                             
                             // Save capture task handle
-        self.capture_task = Some(capture_task);
-        
-        Ok(())
+                            self.captures.push(capture);
+                            
+                            Ok(())
                         },
                         Err(e) => {
-                            error!("Failed to open capture: {}", e);
+                            self.events.error(format!("Failed to open capture: {}", e));
                             Err(anyhow!("Failed to open capture: {}. Please run as administrator and ensure Npcap is properly installed.", e))
                         }
                     }
                 },
                 Err(e) => {
-                    error!("Failed to create capture from device: {}", e);
+                    self.events.error(format!("Failed to create capture from device: {}", e));
                     Err(anyhow!("Failed to create capture from device: {}. Please run as administrator and ensure Npcap is properly installed.", e))
                 }
             }
         }
     }
     
-    /// Run packet capture in a background task
-    async fn run_capture(
-        mut capture: Capture<Active>, 
-        tx: mpsc::Sender<(Vec<u8>, chrono::DateTime<Utc>)>,
-        interface_name: String
-    ) {
-        // Create a channel with capacity for faster signaling
-        let (stop_tx, mut stop_rx) = mpsc::channel::<()>(1);
+    /// Start capturing from an `rpcap://` remote source
+    fn start_remote_capture(&mut self, source: String) -> Result<()> {
+        let auth = self.config.rpcap_username.as_ref().map(|username| RemoteAuth {
+            username: username.clone(),
+            password: self.config.rpcap_password.clone().unwrap_or_default(),
+        });
         
-        // Store the stop signal sender somewhere it can be accessed by stop_capture
-        // This is a global static for simplicity - in production code, consider a more elegant approach
-        {
-            if let Ok(mut guard) = crate::capture::manager::STOP_SIGNAL.lock() {
-                *guard = Some(stop_tx);
+        info!("Opening remote capture source: {}", source);
+        let remote_capture = match RemoteCapture::open(
+            &source,
+            self.config.promiscuous,
+            1000,
+            auth.as_ref(),
+            self.capture_filter().as_deref(),
+        ) {
+            Ok(capture) => capture,
+            Err(e) => {
+                self.events.error(format!("Failed to open remote capture: {}", e));
+                return Err(e);
             }
+        };
+        
+        if self.config.promiscuous {
+            warn!("Promiscuous mode of remote source {} is unverified: rpcap does not report it", source);
         }
         
-        // Create a task for packet capturing
-        let packet_capture_task = tokio::task::spawn_blocking(move || -> Result<(), String> {
-            // Use an internal buffer for better performance
-            let mut packet_buffer = Vec::with_capacity(2048);
+        self.launch_capture(source, remote_capture, Linktype::ETHERNET);
+        Ok(())
+    }
+    
+    /// Capture each member of a bond or bridge, tagging its frames with the
+    /// member's name. Every member must open, all with the same link type.
+    fn start_member_captures(&mut self, master: &str, members: Vec<String>) -> Result<()> {
+        info!("Capturing {} on its members: {}", master, members.join(", "));
+        let filter = self.capture_filter();
+        let mut sources = Vec::with_capacity(members.len());
+        let mut link_type = None;
+        let mut promiscuous = Vec::with_capacity(members.len());
+        
+        for member in members {
+            let mut capture = match Capture::from_device(member.as_str())
+                .and_then(|capture| capture.promisc(self.config.promiscuous).snaplen(65535).timeout(1000).open())
+            {
+                Ok(capture) => capture,
+                Err(e) => {
+                    self.events.error(format!("Failed to open capture on {} (member of {}): {}", member, master, e));
+                    return Err(anyhow!("Failed to open capture on {} (member of {}): {}", member, master, e));
+                }
+            };
+            self.verify_promiscuous(&member);
+            promiscuous.push(self.actual_promiscuous);
             
-            loop {
-                // Check if we've been asked to stop
-                if crate::capture::manager::STOP_REQUESTED.load(Ordering::Relaxed) {
-                    info!("Capture task stop requested");
-                    return Ok(());
+            if let Some(filter) = &filter {
+                match capture.filter(filter, true) {
+                    Ok(_) => info!("Applied filter on {}: {}", member, filter),
+                    Err(e) => self.events.warn(format!("Failed to apply filter on {}: {}", member, e)),
                 }
-                
-                // Try to get the next packet
-                match capture.next_packet() {
-                    Ok(packet) => {
-                        // Get timestamp
-                        let timestamp = Utc::now();
-                        
+            }
+            
+            let member_link_type = capture.get_datalink();
+            if link_type.is_some_and(|link_type| link_type != member_link_type) {
+                return Err(anyhow!("Members of {} differ in link type ({:?} on {})", master, member_link_type, member));
+            }
+            link_type = Some(member_link_type);
+            
+            let phys_iface = Some(Arc::from(member.as_str()));
+            sources.push((member, capture, phys_iface));
+        }
+        
+        // Promiscuous only when every member is
+        self.actual_promiscuous = if promiscuous.contains(&None) {
+            None
+        } else {
+            Some(promiscuous.iter().all(|actual| *actual == Some(true)))
+        };
+        
+        self.launch_captures(sources, link_type.unwrap_or(Linktype::ETHERNET));
+        Ok(())
+    }
+    
+    /// Record the promiscuous mode an opened device is actually in, warning
+    /// when it differs from the requested one. Some virtual NICs accept the
+    /// request but never enter the mode, leaving only this host's traffic.
+    fn verify_promiscuous(&mut self, interface: &str) {
+        let requested = self.config.promiscuous;
+        self.actual_promiscuous = interfaces::interface_promiscuous(interface);
+        match self.actual_promiscuous {
+            Some(actual) if actual == requested => info!("Promiscuous mode on {}: {}", interface, actual),
+            Some(false) => self.events.warn(format!(
+                "Promiscuous mode was requested but {} did not enter it; only traffic to and from this host will be captured",
+                interface
+            )),
+            Some(true) => self.events.warn(format!(
+                "{} is in promiscuous mode though it was not requested (another program enabled it); traffic between other hosts will be captured",
+                interface
+            )),
+            None => warn!("Promiscuous mode of {} is unverified: the platform does not report it", interface),
+        }
+    }
+    
+    /// Start capturing from a `tun:` device, whose packets are raw IP
+    fn start_tun_capture(&mut self, source: String) -> Result<()> {
+        info!("Opening tun device: {}", source);
+        let forward = self.config.tun_forward.clone();
+        let tun_capture = match TunCapture::open(&source, forward.as_deref(), self.capture_filter().as_deref()) {
+            Ok(capture) => capture,
+            Err(e) => {
+                self.events.error(format!("Failed to open tun device: {}", e));
+                return Err(e);
+            }
+        };
+        if let Some(forward) = tun_capture.forward_name() {
+            info!("Forwarding packets of tun device {} to {}", tun_capture.name(), forward);
+        }
+        
+        self.launch_capture(source, tun_capture, Linktype::RAW);
+        Ok(())
+    }
+    
+    /// Start a session whose Ethernet frames are queued by `inject_packet`
+    #[cfg(any(test, feature = "test-inject"))]
+    fn start_injected_capture(&mut self) -> Result<()> {
+        let (tx, rx) = queue::channel(PACKET_QUEUE_CAPACITY, self.config.overflow_policy);
+        let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
+        self.is_running.store(true, Ordering::SeqCst);
+        
+        // There is no device to read; the task only waits to be stopped
+        let capture = CaptureHandle::spawn(INJECTED_INTERFACE.to_string(), |stop| tokio::spawn(async move {
+            let StopSignal { mut rx, .. } = stop;
+            rx.recv().await;
+        }));
+        self.spawn_packet_processor(rx, stats.clone(), Linktype::ETHERNET);
+        
+        self.shared_stats = Some(stats);
+        self.captures.push(capture);
+        self.injector = Some(tx);
+        Ok(())
+    }
+    
+    /// Queue a raw Ethernet frame, captured at `timestamp`, for the parsing,
+    /// storage, stats and broadcasts of a running injected session
+    #[cfg(any(test, feature = "test-inject"))]
+    pub fn inject_packet(&self, raw: Vec<u8>, timestamp: DateTime<Utc>) -> Result<()> {
+        let injector = self.injector
+            .as_ref()
+            .ok_or_else(|| anyhow!("No capture is running on the '{}' interface", INJECTED_INTERFACE))?;
+        injector.send((raw, timestamp, None)).map_err(|e| anyhow!("{}", e))
+    }
+    
+    /// Launch the capture and processing tasks of an opened source whose
+    /// frames are of the given link type
+    fn launch_capture<S: FrameSource>(&mut self, source: String, frame_source: S, link_type: Linktype) {
+        self.launch_captures(vec![(source, frame_source, None)], link_type);
+    }
+    
+    /// Launch a capture task for each opened source, feeding one processing
+    /// task; frames are tagged with the physical interface given for their source
+    fn launch_captures<S: FrameSource>(&mut self, sources: Vec<(String, S, Option<Arc<str>>)>, link_type: Linktype) {
+        // Reset statistics
+        self.stats = CaptureStats::default();
+        self.stats.start_time = Some(Utc::now());
+        
+        // Create the queue for packet processing
+        let (tx, rx) = queue::channel(PACKET_QUEUE_CAPACITY, self.config.overflow_policy);
+        
+        // Create shared stats using Arc and Mutex for thread-safety
+        let stats = Arc::new(tokio::sync::Mutex::new(self.stats.clone()));
+        
+        // Set running flag
+        self.is_running.store(true, Ordering::SeqCst);
+        
+        // Several sources are armed and released together when asked to
+        self.start_barrier = (self.config.synchronized_start && sources.len() > 1)
+            .then(|| Arc::new(StartBarrier::new(sources.len())));
+        
+        // Launch background tasks for capture and processing
+        for (source, frame_source, phys_iface) in sources {
+            let tx = tx.clone();
+            let events = self.events.clone();
+            let start = CaptureStart { delay_ms: self.config.start_delay_ms, barrier: self.start_barrier.clone() };
+            let capture = CaptureHandle::spawn(source.clone(), |stop| tokio::spawn(Self::run_capture(
+                frame_source,
+                tx,
+                source,
+                phys_iface,
+                start,
+                events,
+                stop
+            )));
+            self.captures.push(capture);
+        }
+        self.spawn_packet_processor(rx, stats.clone(), link_type);
+        
+        self.shared_stats = Some(stats);
+    }
+    
+    /// The link type frames reported as `reported` are decoded as: the one
+    /// forced with --link-type, if any
+    fn decoded_link_type(&self, reported: Linktype) -> Linktype {
+        self.config.link_type.map_or(reported, LinkTypeOverride::linktype)
+    }
+    
+    /// Launch the background task that parses, stores and accounts for captured
+    /// packets, whose frames are of the given link type
+    fn spawn_packet_processor(
+        &mut self,
+        mut rx: FrameReceiver,
+        stats: Arc<tokio::sync::Mutex<CaptureStats>>,
+        link_type: Linktype,
+    ) -> JoinHandle<()> {
+        if let Some(forced) = self.config.link_type {
+            info!("Decoding frames as {:?} (--link-type) instead of {:?}", forced, link_type);
+        }
+        let link_type = self.decoded_link_type(link_type);
+        self.link_type = link_type;
+        
+        // Clone data for the processing task
+        let packets = self.packets.clone();
+        let config = self.config.clone();
+        let stats_tx = self.stats_tx.clone();
+        let alerts = self.alerts.clone();
+        let neighbors = self.neighbors.clone();
+        let access_points = self.access_points.clone();
+        let mtu = self.mtu.clone();
+        let latency = self.latency.clone();
+        let rtp = self.rtp.clone();
+        let conversations = self.conversations.clone();
+        let tcp_analysis = self.tcp_analysis.clone();
+        let duplicates = self.duplicates.clone();
+        let local_addresses = self.local_addresses.clone();
+        let index = self.index.clone();
+        let history = self.history.clone();
+        let last_packet_ms = self.last_packet_ms.clone();
+        let budget_used = self.budget_used.clone();
+        let next_id = self.next_id.clone();
+        let budget_exhausted = self.budget_exhausted.clone();
+        let events = self.events.clone();
+        let packet_tx = self.packet_tx.clone();
+        let resolver = self.resolver.clone();
+        let decode_as = self.decode_as.clone();
+        let size_limits = self.size_limits.clone();
+        let description = self.description.clone();
+        let start_barrier = self.start_barrier.clone();
+        let session_id = self.get_session_id();
+        
+        // Stats were just created for this run, so the lock is free
+        if let Ok(mut stats) = stats.try_lock() {
+            stats.session_id = session_id.clone();
+        }
+        
+        tokio::spawn(async move {
+            let parser = PacketParser::new()
+                .with_disabled_protocols(&config.disabled_protocols)
+                .with_decode_as(decode_as);
+            let mut detector = ThreatDetector::new(DetectionSettings::from_config(&config));
+            let interface = config.interface.clone().unwrap_or_default();
+            
+            // Warm-up state: packets are discarded until both the packet count
+            // and the time window since the first frame have been exceeded. The
+            // window starts there, not at the session start, so a start delay
+            // or synchronized start does not use it up.
+            let warmup_window = chrono::Duration::milliseconds(config.warmup_ms as i64);
+            let mut warming_up = config.warmup_packets > 0 || config.warmup_ms > 0;
+            let mut warmup_start: Option<DateTime<Utc>> = None;
+            let mut warmup_discarded = 0;
+            
+            // Rates are measured from the end of the warm-up, not the session start
+            let mut rate_start: Option<DateTime<Utc>> = None;
+            let mut ids_exhausted = false;
+            
+            while let Some((data, timestamp, phys_iface)) = rx.recv().await {
+                let backpressure_dropped = rx.dropped();
+                last_packet_ms.store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+                
+                if warming_up {
+                    let elapsed = timestamp.signed_duration_since(*warmup_start.get_or_insert(timestamp));
+                    if warmup_discarded < config.warmup_packets || elapsed < warmup_window {
+                        warmup_discarded += 1;
+                        if let Ok(mut stats) = stats.try_lock() {
+                            stats.warmup_discarded = warmup_discarded;
+                            stats.backpressure_dropped = backpressure_dropped;
+                        }
+                        continue;
+                    }
+                    
+                    warming_up = false;
+                    rate_start = Some(timestamp);
+                    info!("Capture warm-up complete, discarded {} packets", warmup_discarded);
+                }
+                
+                // Store the length before we move data
+                let data_len = data.len();
+                
+                // Drop frames outside the configured size range before decoding them
+                if !size_limits.admits(data_len) {
+                    if let Ok(mut stats) = stats.try_lock() {
+                        stats.size_filtered += 1;
+                    }
+                    continue;
+                }
+                
+                // Charge the frame to the byte budget; with the stop action,
+                // frames past it are dropped until the watchdog stops the capture.
+                // With the evict action it counts the stored frames instead.
+                let evict_budget = config.max_total_bytes > 0 && config.budget_action == BudgetAction::Evict;
+                if config.max_total_bytes > 0 && !evict_budget {
+                    let used = budget_used.fetch_add(data_len as u64, Ordering::Relaxed) + data_len as u64;
+                    if used > config.max_total_bytes {
+                        budget_used.fetch_sub(data_len as u64, Ordering::Relaxed);
+                        budget_exhausted.store(true, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+                
+                match parser.parse_frame(data, &interface, link_type) {
+                    Ok(mut packet) => {
+                        // Update timestamp
+                        packet.timestamp = timestamp;
+                        
+                        // In flow-head mode, drop packets past the head of their conversation
+                        if !conversations.record(&packet, data_len, config.flow_head_packets) {
+                            if let Ok(mut stats) = stats.try_lock() {
+                                stats.flow_head_skipped += 1;
+                            }
+                            continue;
+                        }
+                        
+                        // Mark copies of a recent frame, as taps and SPAN ports deliver them
+                        duplicates.inspect(&mut packet);
+                        
+                        // Generate ID and store packet
+                        let Some(id) = Self::generate_id(&next_id) else {
+                            if !ids_exhausted {
+                                error!("Packet IDs exhausted, no further packets are stored this session");
+                                ids_exhausted = true;
+                            }
+                            continue;
+                        };
+                        packet.id = id;
+                        
+                        // Tie the packet to this capture session
+                        if let (Some(session_id), Some(metadata)) = (&session_id, packet.metadata.as_object_mut()) {
+                            metadata.insert("session_id".to_string(), serde_json::Value::String(session_id.clone()));
+                        }
+                        
+                        // Record the member of a bond or bridge it crossed
+                        if let (Some(phys_iface), Some(metadata)) = (&phys_iface, packet.metadata.as_object_mut()) {
+                            metadata.insert("phys_iface".to_string(), serde_json::Value::String(phys_iface.to_string()));
+                        }
+                        
+                        // Record whether the capture host sent or received it
+                        if let (Some(direction), Some(metadata)) = (local_addresses.direction(&packet), packet.metadata.as_object_mut()) {
+                            metadata.insert("direction".to_string(), serde_json::Value::String(direction.as_str().to_string()));
+                        }
+                        
+                        // Attach cached hostnames, queueing lookups for unknown addresses
+                        if let Some(resolver) = &resolver {
+                            resolver.annotate(&mut packet);
+                        }
+                        
+                        // Check for scan and flood activity
+                        for detection in detector.inspect(&packet) {
+                            alerts.record(detection, detector.window_secs(), timestamp);
+                        }
+                        
+                        // Keep the tables of LLDP/CDP neighbors and Wi-Fi access points current
+                        neighbors.record(&packet);
+                        access_points.record(&packet);
+                        
+                        // Flag packets at or above the MTU and track path MTU hints
+                        mtu.inspect(&mut packet);
+                        tcp_analysis.inspect(&mut packet);
+                        latency.record(&packet);
+                        rtp.record(&packet);
+                        history.record(timestamp, data_len);
+                        
+                        // Insert packet into storage, evicting the oldest beyond the buffer size
+                        if evict_budget {
+                            budget_used.fetch_add(data_len as u64, Ordering::Relaxed);
+                        }
+                        for evicted in packets.insert_bounded(id, packet.clone(), config.buffer_size) {
+                            debug!("Removed oldest packet ID {} to maintain buffer size", evicted.id);
+                            if evict_budget {
+                                budget_used.fetch_sub(evicted.raw_data.len() as u64, Ordering::Relaxed);
+                            }
+                        }
+                        if let Some(index) = &index {
+                            index.lock().append(&Self::summarize(&packet));
+                        }
+                        
+                        // Feed live consumers, if any are listening
+                        if packet_tx.receiver_count() > 0 {
+                            let _ = packet_tx.send(packet.clone());
+                        }
+                        
+                        // Update stats
+                        if let Ok(mut stats) = stats.try_lock() {
+                            Self::account_packet(&mut stats, &packet, data_len, config.max_tracked_addresses);
+                            stats.warmup_discarded = warmup_discarded;
+                            stats.backpressure_dropped = backpressure_dropped;
+                            
+                            // Record resolved names for the top talkers, as many as addresses are tracked
+                            if let Some(resolver) = &resolver {
+                                for ip in [packet.source_ip, packet.destination_ip].into_iter().flatten() {
+                                    let max_hostnames = config.max_tracked_addresses;
+                                    if max_hostnames > 0 && stats.hostnames.len() >= max_hostnames {
+                                        break;
+                                    }
+                                    if let std::collections::hash_map::Entry::Vacant(entry) = stats.hostnames.entry(ip.to_string()) {
+                                        if let Some(name) = resolver.cached(&ip) {
+                                            entry.insert(name);
+                                        }
+                                    }
+                                }
+                            }
+                            
+                            // Calculate packet rate, from the later of the warm-up end and a stats reset
+                            if let Some(start_time) = rate_start.max(stats.start_time) {
+                                let elapsed = Utc::now().signed_duration_since(start_time);
+                                let elapsed_secs = elapsed.num_milliseconds() as f64 / 1000.0;
+                                if elapsed_secs > 0.0 {
+                                    stats.packet_rate = stats.total_packets as f64 / elapsed_secs;
+                                    stats.data_rate = stats.total_bytes as f64 / elapsed_secs;
+                                }
+                            }
+                            
+                            // Update the packet count in the logger
+                            crate::utils::logging::update_packet_count(stats.total_packets);
+                            
+                            // Broadcast the updated stats (using cloned stats_tx)
+                            let mut update = stats.clone();
+                            Self::annotate_stats(&mut update, &description, start_barrier.as_deref());
+                            let _ = stats_tx.send(update);
+                        }
+                        
+                        // Enforce the byte budget
+                        if evict_budget {
+                            Self::enforce_byte_budget(&packets, &budget_used, config.max_total_bytes);
+                        }
+                    },
+                    Err(e) => {
+                        events.error(format!("Failed to parse packet: {}", e));
+                        if let Ok(mut stats) = stats.try_lock() {
+                            stats.errors += 1;
+                        }
+                    }
+                }
+            }
+            
+            info!("Packet processor task stopped");
+        })
+    }
+    
+    /// Add a stored packet to the protocol, source and destination counters
+    fn account_packet(stats: &mut CaptureStats, packet: &Packet, data_len: usize, max_addresses: usize) {
+        stats.total_packets += 1;
+        stats.total_bytes += data_len;
+        
+        // Update protocol stats
+        *stats.protocols.entry(packet.protocol.clone()).or_insert(0) += 1;
+        
+        if packet.metadata.get("duplicate").and_then(|v| v.as_bool()) == Some(true) {
+            stats.duplicates += 1;
+        }
+        
+        // Update address scope stats
+        let scope = stats.scopes.entry(AddressScope::of(packet).to_string()).or_default();
+        scope.packets += 1;
+        scope.bytes += data_len;
+        
+        // Update source stats
+        if let Some(source) = packet.source_ip.as_ref().map(|ip| ip.to_string()) {
+            Self::count_address(&mut stats.sources, &mut stats.untracked_sources, source, max_addresses);
+        }
+        
+        // Update destination stats
+        if let Some(dest) = packet.destination_ip.as_ref().map(|ip| ip.to_string()) {
+            Self::count_address(&mut stats.destinations, &mut stats.untracked_destinations, dest, max_addresses);
+        }
+    }
+    
+    /// Take a removed packet back out of the counts `account_packet` added it to
+    fn unaccount_packet(stats: &mut CaptureStats, packet: &Packet) {
+        stats.total_packets = stats.total_packets.saturating_sub(1);
+        stats.total_bytes = stats.total_bytes.saturating_sub(packet.length);
+        
+        Self::uncount(&mut stats.protocols, &packet.protocol);
+        
+        if packet.metadata.get("duplicate").and_then(|v| v.as_bool()) == Some(true) {
+            stats.duplicates = stats.duplicates.saturating_sub(1);
+        }
+        
+        let scope_key = AddressScope::of(packet).to_string();
+        if let Some(scope) = stats.scopes.get_mut(&scope_key) {
+            scope.packets = scope.packets.saturating_sub(1);
+            scope.bytes = scope.bytes.saturating_sub(packet.length);
+            if scope.packets == 0 {
+                stats.scopes.remove(&scope_key);
+            }
+        }
+        
+        // Packets not in the address maps were counted as untracked
+        if let Some(source) = packet.source_ip.as_ref().map(|ip| ip.to_string()) {
+            if !Self::uncount(&mut stats.sources, &source) {
+                stats.untracked_sources = stats.untracked_sources.saturating_sub(1);
+            }
+        }
+        if let Some(dest) = packet.destination_ip.as_ref().map(|ip| ip.to_string()) {
+            if !Self::uncount(&mut stats.destinations, &dest) {
+                stats.untracked_destinations = stats.untracked_destinations.saturating_sub(1);
+            }
+        }
+    }
+    
+    /// Decrement a count, dropping it at zero; false if there was none
+    fn uncount(counts: &mut HashMap<String, usize>, key: &str) -> bool {
+        let Some(count) = counts.get_mut(key) else { return false };
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(key);
+        }
+        true
+    }
+    
+    /// Count a packet for an address, or as untracked once the map holds
+    /// `max_addresses` other addresses (so a scan cannot grow it without bound)
+    fn count_address(counts: &mut HashMap<String, usize>, untracked: &mut usize, address: String, max_addresses: usize) {
+        if let Some(count) = counts.get_mut(&address) {
+            *count += 1;
+        } else if max_addresses == 0 || counts.len() < max_addresses {
+            counts.insert(address, 1);
+        } else {
+            *untracked += 1;
+        }
+    }
+    
+    /// Load packets from capture files, read beforehand with `ReadFiles::read`,
+    /// into the buffer.
+    ///
+    /// Packets from all files form one timeline (IDs follow the timestamps)
+    /// and are tagged with their file's label in `metadata["source"]`. With
+    /// `append`, they are added to the packets already stored. Only the first
+    /// `max_packets` frames are stored (0 for no cap; `None` uses the
+    /// configured `--read-max-packets`), and `buffer_size` eviction runs while
+    /// loading.
+    pub fn load_files(&mut self, read: ReadFiles, append: bool, max_packets: Option<usize>) -> Result<LoadSummary> {
+        if self.is_running.load(Ordering::SeqCst) {
+            return Err(anyhow!("Cannot load files while a capture is running"));
+        }
+        
+        let ReadFiles { files, frames } = read;
+        let file_count = files.len();
+        let parser = PacketParser::new()
+            .with_disabled_protocols(&self.config.disabled_protocols)
+            .with_decode_as(self.decode_as.clone());
+        let max_packets = max_packets.unwrap_or(self.config.read_max_packets);
+        let buffer_size = self.config.buffer_size;
+        if let Some(forced) = self.config.link_type {
+            info!("Decoding the files' frames as {:?} (--link-type)", forced);
+        }
+        
+        if !append {
+            self.packets.clear();
+            self.next_id.store(1, Ordering::SeqCst);
+            self.events.clear_history();
+            self.alerts.clear();
+            self.neighbors.clear();
+            self.access_points.clear();
+            self.mtu.reset(self.config.mtu);
+            self.latency.clear();
+            self.rtp.clear();
+            self.conversations.clear();
+            self.tcp_analysis.clear();
+            self.duplicates.clear();
+            self.history.clear();
+            self.rates.clear();
+            self.loaded_files.clear();
+            self.stats = CaptureStats::default();
+            self.stats.session_id = Some(self.new_session());
+            self.start_barrier = None;
+        }
+        self.loaded_files.extend(files);
+        self.shared_stats = None;
+        let session_id = self.get_session_id();
+        
+        let mut summary = LoadSummary::default();
+        let mut first_loaded_id = None;
+        let mut evicted_loaded = 0;
+        
+        for frame in frames {
+            summary.read += 1;
+            
+            // Past the cap, keep reading only to report the file's size
+            if max_packets > 0 && summary.loaded >= max_packets {
+                continue;
+            }
+            
+            let data_len = frame.data.len();
+            if !self.size_limits.admits(data_len) {
+                self.stats.size_filtered += 1;
+                continue;
+            }
+            match parser.parse_frame(frame.data, &frame.interface, self.decoded_link_type(frame.link_type)) {
+                Ok(mut packet) => {
+                    packet.timestamp = frame.timestamp;
+                    if !self.conversations.record(&packet, data_len, self.config.flow_head_packets) {
+                        self.stats.flow_head_skipped += 1;
+                        continue;
+                    }
+                    
+                    packet.id = Self::generate_id(&self.next_id).ok_or_else(|| anyhow!("Packet IDs exhausted"))?;
+                    if let Some(metadata) = packet.metadata.as_object_mut() {
+                        metadata.insert("source".to_string(), serde_json::Value::String(frame.source));
+                        if let Some(session_id) = &session_id {
+                            metadata.insert("session_id".to_string(), serde_json::Value::String(session_id.clone()));
+                        }
+                    }
+                    if let Some(resolver) = &self.resolver {
+                        resolver.annotate(&mut packet);
+                    }
+                    self.neighbors.record(&packet);
+                    self.access_points.record(&packet);
+                    self.mtu.inspect(&mut packet);
+                    self.duplicates.inspect(&mut packet);
+                    self.tcp_analysis.inspect(&mut packet);
+                    self.latency.record(&packet);
+                    self.rtp.record(&packet);
+                    self.history.record(frame.timestamp, data_len);
+                    
+                    Self::account_packet(&mut self.stats, &packet, data_len, self.config.max_tracked_addresses);
+                    self.stats.start_time = Some(self.stats.start_time.map_or(frame.timestamp, |t| t.min(frame.timestamp)));
+                    self.stats.end_time = Some(self.stats.end_time.map_or(frame.timestamp, |t| t.max(frame.timestamp)));
+                    
+                    first_loaded_id.get_or_insert(packet.id);
+                    summary.loaded += 1;
+                    
+                    // Evict the oldest packets, as live capture does
+                    for evicted in self.packets.insert_bounded(packet.id, packet, buffer_size) {
+                        if first_loaded_id.is_some_and(|first| evicted.id >= first) {
+                            evicted_loaded += 1;
+                        }
+                    }
+                },
+                Err(e) => {
+                    self.events.warn(format!("Skipping unparseable packet from {}: {}", frame.source, e));
+                    self.stats.errors += 1;
+                    summary.errors += 1;
+                }
+            }
+        }
+        
+        // Rates over the span of the loaded captures
+        if let (Some(start), Some(end)) = (self.stats.start_time, self.stats.end_time) {
+            let elapsed_secs = end.signed_duration_since(start).num_milliseconds() as f64 / 1000.0;
+            if elapsed_secs > 0.0 {
+                self.stats.packet_rate = self.stats.total_packets as f64 / elapsed_secs;
+                self.stats.data_rate = self.stats.total_bytes as f64 / elapsed_secs;
+            }
+        }
+        
+        // Appended files can move the start time that relative times are based on
+        self.details.clear();
+        
+        summary.retained = summary.loaded - evicted_loaded;
+        info!("Read {} packets from {} file(s): {} loaded, {} retained",
+              summary.read, file_count, summary.loaded, summary.retained);
+        Ok(summary)
+    }
+    
+    /// Run packet capture in a background task
+    async fn run_capture<S: FrameSource>(
+        mut capture: S, 
+        tx: FrameSender,
+        interface_name: String,
+        phys_iface: Option<Arc<str>>,
+        start: CaptureStart,
+        events: EventLog,
+        stop: StopSignal
+    ) {
+        let StopSignal { requested: stop_requested, rx: mut stop_rx, filters: filter_rx } = stop;
+        
+        // Create a task for packet capturing
+        let capture_events = events.clone();
+        let capture_interface = interface_name.clone();
+        let capture_stop_requested = stop_requested.clone();
+        let packet_capture_task = tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let events = capture_events;
+            let stop_requested = capture_stop_requested;
+            // Use an internal buffer for better performance
+            let mut packet_buffer = Vec::with_capacity(2048);
+            
+            if !start.wait(&stop_requested) {
+                return Ok(());
+            }
+            // The device was open while waiting for the others, so frames it
+            // already holds predate the synchronized start
+            let released_at = start.barrier.as_ref().and_then(|barrier| barrier.released_at());
+            let mut failed_reads: u32 = 0;
+            
+            loop {
+                // Check if we've been asked to stop
+                if stop_requested.load(Ordering::Relaxed) {
+                    info!("Capture task stop requested");
+                    return Ok(());
+                }
+                
+                // Apply filter changes requested since the last read
+                while let Ok(request) = filter_rx.try_recv() {
+                    let result = capture.set_filter(&request.filter);
+                    match &result {
+                        Ok(()) => info!("Capture filter on {} changed to '{}'", capture_interface, request.filter),
+                        Err(e) => warn!("Rejected capture filter '{}': {}", request.filter, e),
+                    }
+                    let _ = request.reply.send(result);
+                }
+                
+                // Try to get the next packet
+                match capture.next_frame() {
+                    Ok(frame) if released_at.zip(frame.captured_at).is_some_and(|(released, captured)| captured < released) => {}
+                    Ok(frame) => {
+                        if failed_reads > 0 {
+                            info!("Capture on {} recovered after {} failed reads", capture_interface, failed_reads);
+                            failed_reads = 0;
+                        }
+                        
+                        // Get timestamp
+                        let timestamp = Utc::now();
+                        
                         // Copy packet data to our buffer
                         packet_buffer.clear();
-                        packet_buffer.extend_from_slice(&packet.data);
+                        packet_buffer.extend_from_slice(frame.data);
                         
-                        // Send packet data and timestamp through mpsc channel
-                        if let Err(e) = tx.blocking_send((packet_buffer.clone(), timestamp)) {
-                            error!("Failed to send packet: {}", e);
+                        // Queue packet data and timestamp (blocking or dropping per the overflow policy)
+                        if let Err(e) = tx.send((packet_buffer.clone(), timestamp, phys_iface.clone())) {
+                            events.error(format!("Failed to send packet: {}", e));
                             // Check if the receiver has been dropped
                             return Err(format!("Packet channel closed: {}", e));
                         }
                     }
-                    Err(e) => {
-                        // Check if it's a timeout (which is expected)
-                        if e.to_string().contains("timed out") {
-                            // This is expected, just continue
-                        } else if e.to_string().contains("no more packets") {
-                            info!("No more packets to capture");
+                    Err(FrameError::Timeout) => {
+                        // This is expected, just continue
+                    }
+                    Err(FrameError::End) => {
+                        info!("No more packets to capture");
+                        return Ok(());
+                    }
+                    Err(FrameError::Fatal(e)) => {
+                        return Err(format!("Capture source on {} failed: {}", capture_interface, e));
+                    }
+                    Err(FrameError::Failed(e)) => {
+                        // Handle other errors gracefully
+                        if stop_requested.load(Ordering::Relaxed) {
+                            // If stop was requested, this is expected
+                            info!("Capture stopped while waiting for packets");
                             return Ok(());
-                        } else {
-                            // Handle other errors gracefully
-                            if crate::capture::manager::STOP_REQUESTED.load(Ordering::Relaxed) {
-                                // If stop was requested, this is expected
-                                info!("Capture stopped while waiting for packets");
-                                return Ok(());
-                            } else {
-                                error!("Error capturing packets: {:?}", e);
-                                // Continue capturing despite the error
-                            }
+                        }
+                        
+                        // Report the first failure of a run, then each time the run doubles
+                        failed_reads = failed_reads.saturating_add(1);
+                        if failed_reads.is_power_of_two() {
+                            events.error(format!("Error capturing packets on {} ({} failed reads in a row): {}", capture_interface, failed_reads, e));
+                        }
+                        
+                        // Continue capturing despite the error, after a pause
+                        let backoff = FAILED_READ_BACKOFF
+                            .saturating_mul(1u32 << (failed_reads - 1).min(16))
+                            .min(MAX_FAILED_READ_BACKOFF);
+                        let resume = Instant::now() + backoff;
+                        while Instant::now() < resume && !stop_requested.load(Ordering::Relaxed) {
+                            std::thread::sleep(Duration::from_millis(10).min(backoff));
                         }
                     }
                 }
@@ -689,7 +1742,7 @@ impl CaptureManager {
             if let Some(_) = stop_rx.recv().await {
                 info!("Stop signal received by capture task");
                 // Set the stop flag to notify the blocking task
-                crate::capture::manager::STOP_REQUESTED.store(true, Ordering::Relaxed);
+                stop_requested.store(true, Ordering::Relaxed);
             }
         };
         
@@ -698,8 +1751,8 @@ impl CaptureManager {
             result = packet_capture_task => {
                 match result {
                     Ok(Ok(())) => info!("Packet capture task completed successfully"),
-                    Ok(Err(e)) => error!("Packet capture task failed: {}", e),
-                    Err(e) => error!("Packet capture task panicked: {}", e),
+                    Ok(Err(e)) => events.error(format!("Packet capture task failed: {}", e)),
+                    Err(e) => events.error(format!("Packet capture task panicked: {}", e)),
                 }
             }
             _ = stop_monitor_task => {
@@ -707,20 +1760,10 @@ impl CaptureManager {
             }
         }
         
-        // Clear the stop signal
-        {
-            if let Ok(mut guard) = crate::capture::manager::STOP_SIGNAL.lock() {
-                *guard = None;
-            }
-        }
-        
-        // Reset the stop flag
-        crate::capture::manager::STOP_REQUESTED.store(false, Ordering::Relaxed);
-        
         info!("Capture task terminated for interface: {}", interface_name);
     }
     
-    /// Stop an active capture
+    /// Stop all active captures
     pub async fn stop_capture(&mut self) -> Result<()> {
         info!("Stopping packet capture");
         
@@ -731,25 +1774,53 @@ impl CaptureManager {
         // Set the flag to false first
         self.is_running.store(false, Ordering::SeqCst);
         
-        // Send stop signal if available
-        if let Some(sender) = STOP_SIGNAL.lock().unwrap().take() {
-            let _ = sender.send(()).await;
+        for capture in std::mem::take(&mut self.captures) {
+            capture.stop().await;
         }
         
-        // Set atomic flag for old capture method
-        STOP_REQUESTED.store(true, Ordering::SeqCst);
+        self.finish_capture();
+        Ok(())
+    }
+    
+    /// Stop the capture on one interface, leaving any others running. The
+    /// session ends (as with `stop_capture`) when it was the last one.
+    pub async fn stop_interface(&mut self, interface: &str) -> Result<()> {
+        let position = self.captures
+            .iter()
+            .position(|capture| capture.interface == interface)
+            .ok_or_else(|| anyhow!("No capture is running on {}", interface))?;
         
-        // Wait for the task to complete
-        if let Some(task) = self.capture_task.take() {
-            // Set timeout for joining the capture task
-            match tokio::time::timeout(Duration::from_secs(5), task).await {
-                Ok(_) => {
-                    info!("Capture task completed gracefully");
-                }
-                Err(_) => {
-                    warn!("Timeout waiting for capture task to complete, proceeding anyway");
-                }
-            }
+        info!("Stopping packet capture on {}", interface);
+        if self.captures.len() == 1 {
+            self.is_running.store(false, Ordering::SeqCst);
+        }
+        self.captures.remove(position).stop().await;
+        
+        if self.captures.is_empty() {
+            self.finish_capture();
+        }
+        Ok(())
+    }
+    
+    /// Interfaces (or remote sources) currently being captured
+    pub fn active_interfaces(&self) -> Vec<String> {
+        if !self.is_running.load(Ordering::SeqCst) {
+            return Vec::new();
+        }
+        self.captures.iter().map(|capture| capture.interface.clone()).collect()
+    }
+    
+    /// Close the session once its last capture has stopped: flush the index,
+    /// finalize the rates and publish the final stats
+    fn finish_capture(&mut self) {
+        // Closing the queue ends the processor of an injected session
+        #[cfg(any(test, feature = "test-inject"))]
+        {
+            self.injector = None;
+        }
+        
+        if let Some(index) = &self.index {
+            index.lock().flush();
         }
         
         // Update end time in stats
@@ -768,16 +1839,11 @@ impl CaptureManager {
         }
         
         // Send a final stats update with the capture stopped flag
-        let final_stats = self.stats.clone();
+        let mut final_stats = self.stats.clone();
+        Self::annotate_stats(&mut final_stats, &self.description, self.start_barrier.as_deref());
         let _ = self.stats_tx.send(final_stats);
         
-        // Reset the broadcaster to clean up any lingering broadcast tasks
-        // This ensures old capture data won't continue to be sent
-        let (new_tx, _) = broadcast::channel(100);
-        self.stats_tx = new_tx;
-        
         info!("Capture stopped successfully");
-        Ok(())
     }
     
     /// Get capture status
@@ -785,10 +1851,46 @@ impl CaptureManager {
         self.is_running.load(Ordering::SeqCst)
     }
     
+    /// Get a shared handle to the running flag, for lock-free status checks
+    pub fn running_flag(&self) -> Arc<AtomicBool> {
+        self.is_running.clone()
+    }
+    
+    /// Zero the counters, maps and rates, keeping the stored packets.
+    ///
+    /// The session and its description carry over; the rates and the per-second
+    /// history restart from now. The reset stats are broadcast so charts start over.
+    pub async fn reset_stats(&mut self) -> CaptureStats {
+        let now = Utc::now();
+        let running = self.is_running.load(Ordering::SeqCst);
+        let fresh = |previous: &CaptureStats| CaptureStats {
+            session_id: previous.session_id.clone(),
+            description: previous.description.clone(),
+            tags: previous.tags.clone(),
+            start_time: Some(now),
+            end_time: if running { None } else { Some(now) },
+            ..CaptureStats::default()
+        };
+        
+        if let Some(shared_stats) = &self.shared_stats {
+            let mut stats = shared_stats.lock().await;
+            *stats = fresh(&stats);
+        }
+        self.stats = fresh(&self.stats);
+        self.history.clear();
+        self.rates.clear();
+        // Cached details carry relative times from the old start
+        self.details.clear();
+        
+        let stats = self.get_stats();
+        let _ = self.stats_tx.send(stats.clone());
+        stats
+    }
+    
     /// Get capture statistics
     pub fn get_stats(&self) -> CaptureStats {
         // If we have shared stats (during active capture), use those
-        if let Some(shared_stats) = &self.shared_stats {
+        let mut stats = if let Some(shared_stats) = &self.shared_stats {
             // Try to acquire the lock. If it fails, fall back to the last stored stats
             match shared_stats.try_lock() {
                 Ok(stats) => stats.clone(),
@@ -797,33 +1899,235 @@ impl CaptureManager {
         } else {
             // Otherwise, return the stored stats
             self.stats.clone()
-        }
+        };
+        
+        Self::annotate_stats(&mut stats, &self.description, self.start_barrier.as_deref());
+        stats
     }
     
-    /// Get packet by ID
+    /// Fill in what the counters leave out: the description, the tags and
+    /// when a synchronized start released the captures
+    fn annotate_stats(stats: &mut CaptureStats, description: &RwLock<CaptureDescription>, barrier: Option<&StartBarrier>) {
+        let description = description.read();
+        stats.description = description.description.clone();
+        stats.tags = description.tags.clone();
+        stats.synchronized_start = barrier.and_then(StartBarrier::released_at);
+    }
+    
+    /// Get the description and tags of the current capture
+    pub fn get_description(&self) -> CaptureDescription {
+        self.description.read().clone()
+    }
+    
+    /// Replace the description and tags of the current capture
+    pub fn set_description(&mut self, description: CaptureDescription) {
+        *self.description.write() = description;
+    }
+    
+    /// Get packet by ID, with its headers expanded if they were compacted
     pub fn get_packet(&self, id: u64) -> Option<Packet> {
-        self.packets.get(&id).map(|p| p.clone())
+        self.packets.get(&id).map(|p| p.clone().expanded())
+    }
+    
+    /// Get the packets with the given IDs, in the order given and without duplicates.
+    ///
+    /// Returns the packets found and the IDs that are not (or no longer) stored.
+    pub fn get_packets_by_ids(&self, ids: &[u64]) -> (Vec<Packet>, Vec<u64>) {
+        let mut seen = std::collections::HashSet::new();
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        
+        for &id in ids {
+            if !seen.insert(id) {
+                continue;
+            }
+            match self.packets.get(&id) {
+                Some(packet) => found.push(packet.clone().expanded()),
+                None => missing.push(id),
+            }
+        }
+        
+        (found, missing)
     }
     
     /// Get all packets
     pub fn get_packets(&self, offset: usize, limit: usize) -> Vec<PacketSummary> {
-        self.packets
+        let ordered = self.packets.iter().map(|p| (p.id, p.timestamp)).collect();
+        self.summarize_page(ordered, offset, limit)
+    }
+    
+    /// Get packets loaded from the capture file with the given source label, of the
+    /// given address scope, in the given direction and/or carrying a TCP analysis
+    /// flag, in timeline order.
+    ///
+    /// Returns the requested page and the total number of matching packets.
+    pub fn get_packets_matching(&self, criteria: &PacketCriteria, offset: usize, limit: usize) -> (Vec<PacketSummary>, usize) {
+        let matching: Vec<(u64, DateTime<Utc>)> = self.packets
+            .iter()
+            .filter(|p| criteria.matches(p))
+            .map(|p| (p.id, p.timestamp))
+            .collect();
+        
+        let total = matching.len();
+        (self.summarize_page(matching, offset, limit), total)
+    }
+    
+    /// Get packets with a decoded header or metadata value matching `term`
+    /// (see `Packet::matching_fields`), in timeline order.
+    ///
+    /// Returns the requested page and the total number of matching packets.
+    pub fn search_fields(&self, term: &str, offset: usize, limit: usize) -> (Vec<FieldMatch>, usize) {
+        let mut fields: HashMap<u64, Vec<String>> = HashMap::new();
+        let matching: Vec<(u64, DateTime<Utc>)> = self.packets
             .iter()
-            .skip(offset)
-            .take(limit)
-            .map(|p| {
-                let packet = p.value();
-                PacketSummary {
-                    id: packet.id,
-                    timestamp: packet.timestamp,
-                    protocol: packet.protocol.clone(),
-                    source: Self::format_address(packet),
-                    destination: Self::format_destination(packet),
-                    length: packet.length,
-                    info: Self::generate_info(packet),
+            .filter_map(|p| {
+                let matches = p.matching_fields(term);
+                if matches.is_empty() {
+                    return None;
                 }
+                fields.insert(p.id, matches);
+                Some((p.id, p.timestamp))
             })
-            .collect()
+            .collect();
+        
+        let total = matching.len();
+        let page = self.summarize_page(matching, offset, limit)
+            .into_iter()
+            .map(|summary| FieldMatch {
+                fields: fields.remove(&summary.id).unwrap_or_default(),
+                summary,
+            })
+            .collect();
+        (page, total)
+    }
+    
+    /// Summarize one page of packets in ID order, with their relative and delta times.
+    ///
+    /// The delta of a page's first packet is measured from the packet before it in
+    /// the full ordering, so it doesn't depend on where the page boundary falls.
+    fn summarize_page(&self, mut ordered: Vec<(u64, DateTime<Utc>)>, offset: usize, limit: usize) -> Vec<PacketSummary> {
+        ordered.sort_unstable_by_key(|&(id, _)| id);
+        let session_start = self.stats.start_time;
+        
+        let mut previous = offset.checked_sub(1).and_then(|i| ordered.get(i)).map(|&(_, timestamp)| timestamp);
+        let mut page = Vec::with_capacity(limit.min(ordered.len()));
+        
+        for &(id, timestamp) in ordered.iter().skip(offset).take(limit) {
+            let Some(packet) = self.packets.get(&id) else { continue };
+            let mut summary = Self::summarize(&packet);
+            summary.relative_time = session_start.map(|start| seconds_between(start, timestamp));
+            summary.delta_time = Some(previous.map_or(0.0, |prev| seconds_between(prev, timestamp)));
+            summary.color = self.color_rules.color_for(&packet, &summary);
+            previous = Some(timestamp);
+            page.push(summary);
+        }
+        
+        page
+    }
+    
+    /// Seconds since the session start and since the previous packet (by ID) for one packet
+    pub fn packet_times(&self, packet: &Packet) -> (Option<f64>, Option<f64>) {
+        let relative = self.stats.start_time.map(|start| seconds_between(start, packet.timestamp));
+        
+        // IDs are sequential within a capture, so the previous packet is usually id - 1
+        let previous = match packet.id.checked_sub(1).and_then(|id| self.packets.get(&id)) {
+            Some(prev) => Some(prev.timestamp),
+            None => self.packets
+                .iter()
+                .filter(|p| p.id < packet.id)
+                .max_by_key(|p| p.id)
+                .map(|p| p.timestamp),
+        };
+        let delta = previous.map_or(0.0, |prev| seconds_between(prev, packet.timestamp));
+        
+        (relative, Some(delta))
+    }
+    
+    /// Get a packet's cached detail JSON, if it is still stored
+    pub fn cached_packet_detail(&self, id: u64) -> Option<Arc<str>> {
+        if !self.packets.contains_key(&id) {
+            self.details.invalidate(id);
+            return None;
+        }
+        self.details.get(id)
+    }
+    
+    /// Get the cache of packet details, to store newly serialized ones
+    pub fn detail_cache(&self) -> DetailCache {
+        self.details.clone()
+    }
+    
+    /// Get the packet coloring rules
+    pub fn color_rules(&self) -> ColorRules {
+        self.color_rules.clone()
+    }
+    
+    /// Build the list-view summary for a packet
+    pub fn summarize(packet: &Packet) -> PacketSummary {
+        PacketSummary {
+            id: packet.id,
+            timestamp: packet.timestamp,
+            protocol: packet.protocol.clone(),
+            source: Self::format_address(packet),
+            destination: Self::format_destination(packet),
+            length: packet.length,
+            info: Self::generate_info(packet),
+            source_host: Self::metadata_str(packet, SRC_HOST_KEY),
+            destination_host: Self::metadata_str(packet, DST_HOST_KEY),
+            relative_time: None,
+            delta_time: None,
+            color: None,
+        }
+    }
+    
+    /// Remove the stored packets a display filter matches (every packet
+    /// without one), taking them out of the stats as well. Returns how many
+    /// were removed.
+    pub async fn delete_packets(&mut self, filter: Option<&DisplayFilter>) -> usize {
+        let ids: Vec<u64> = self.packets
+            .iter()
+            .filter(|packet| match filter {
+                Some(filter) => {
+                    let summary = serde_json::to_value(Self::summarize(packet)).unwrap_or_default();
+                    filter.matches(packet, &packet.expanded_headers(), &summary)
+                },
+                None => true,
+            })
+            .map(|packet| packet.id)
+            .collect();
+        
+        let mut removed = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some((_, packet)) = self.packets.remove(&id) {
+                self.details.invalidate(id);
+                removed.push(packet);
+            }
+        }
+        if removed.is_empty() {
+            return 0;
+        }
+        
+        let unaccount = |stats: &mut CaptureStats| {
+            for packet in &removed {
+                Self::unaccount_packet(stats, packet);
+            }
+        };
+        if let Some(shared_stats) = &self.shared_stats {
+            unaccount(&mut *shared_stats.lock().await);
+        }
+        unaccount(&mut self.stats);
+        
+        // Deleted frames no longer count against the byte budget
+        let freed: u64 = removed.iter().map(|packet| packet.raw_data.len() as u64).sum();
+        let _ = self.budget_used.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| Some(used.saturating_sub(freed)));
+        
+        let _ = self.stats_tx.send(self.get_stats());
+        removed.len()
+    }
+    
+    /// Read a string value from a packet's metadata
+    fn metadata_str(packet: &Packet, key: &str) -> Option<String> {
+        packet.metadata.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
     }
     
     /// Get the total number of packets
@@ -831,26 +2135,183 @@ impl CaptureManager {
         self.packets.len()
     }
     
+    /// Summarize the capture (or loaded files) in the style of `capinfos`
+    pub fn get_capture_info(&self) -> CaptureInfo {
+        let stats = self.get_stats();
+        let is_running = self.get_status();
+        // The session may have started before its oldest stored packet, or have evicted some
+        let first_packet_time = self.packets.iter().map(|packet| packet.timestamp).min();
+        let last_packet_time = if is_running { Some(Utc::now()) } else { stats.end_time };
+        let duration_secs = match (first_packet_time, last_packet_time) {
+            (Some(start), Some(end)) => (end.signed_duration_since(start).num_milliseconds() as f64 / 1000.0).max(0.0),
+            _ => 0.0,
+        };
+        let rate = |count: f64| if duration_secs > 0.0 { count / duration_secs } else { 0.0 };
+        
+        let mut protocols: Vec<String> = stats.protocols.keys().cloned().collect();
+        protocols.sort();
+        
+        CaptureInfo {
+            session_id: stats.session_id,
+            is_running,
+            packets: stats.total_packets,
+            bytes: stats.total_bytes,
+            first_packet_time,
+            last_packet_time,
+            duration_secs,
+            packets_per_sec: rate(stats.total_packets as f64),
+            bits_per_sec: rate(stats.total_bytes as f64 * 8.0),
+            bytes_per_sec: rate(stats.total_bytes as f64),
+            average_packet_size: if stats.total_packets > 0 {
+                stats.total_bytes as f64 / stats.total_packets as f64
+            } else {
+                0.0
+            },
+            protocols,
+            files: self.loaded_files.clone(),
+        }
+    }
+    
+    /// Estimate the memory held by the packet buffer.
+    ///
+    /// Parsed headers and metadata are only counted through a fixed per-packet
+    /// allowance, as measuring their JSON trees would cost more than the walk
+    /// itself; compacted headers are counted by the length of their text.
+    pub fn estimate_memory(&self) -> MemoryEstimate {
+        let mut raw_bytes = 0;
+        let mut payload_bytes = 0;
+        let mut header_bytes = 0;
+        let mut compacted_packets = 0;
+        let mut overhead_bytes = 0;
+        
+        for packet in self.packets.iter() {
+            raw_bytes += packet.raw_data.capacity();
+            payload_bytes += packet.payload.as_ref().map_or(0, |p| p.capacity());
+            header_bytes += match &packet.compact_headers {
+                Some(json) => {
+                    compacted_packets += 1;
+                    json.len()
+                },
+                None => HEADERS_ALLOWANCE_BYTES,
+            };
+            overhead_bytes += PACKET_OVERHEAD_BYTES
+                + packet.interface.capacity()
+                + packet.protocol.capacity()
+                + packet.source_mac.as_ref().map_or(0, |m| m.capacity())
+                + packet.destination_mac.as_ref().map_or(0, |m| m.capacity());
+        }
+        
+        let packet_count = self.packets.len();
+        let estimated_bytes = raw_bytes + payload_bytes + header_bytes + overhead_bytes;
+        let buffer_size = self.config.buffer_size;
+        let estimated_bytes_at_limit = estimated_bytes
+            .checked_div(packet_count)
+            .map_or(0, |average| average * buffer_size);
+        
+        MemoryEstimate {
+            computed_at: Utc::now(),
+            packet_count,
+            buffer_size,
+            raw_bytes,
+            payload_bytes,
+            header_bytes,
+            compacted_packets,
+            overhead_bytes,
+            estimated_bytes,
+            estimated_bytes_at_limit,
+        }
+    }
+    
+    /// Directory the API may load capture files from, None when it may load none
+    pub fn load_dir(&self) -> Option<PathBuf> {
+        self.config.load_dir.as_ref().map(PathBuf::from)
+    }
+    
     /// Get the currently selected interface
     pub fn get_selected_interface(&self) -> Option<String> {
         self.config.interface.clone()
     }
     
+    /// Link type the running (or last) capture's frames are decoded as
+    pub fn link_type(&self) -> Linktype {
+        self.link_type
+    }
+    
+    /// Device name and alias of the interface the stored packets were
+    /// captured on; None when they were loaded from files
+    pub fn capture_interface(&self) -> Option<(String, Option<String>)> {
+        if !self.loaded_files.is_empty() {
+            return None;
+        }
+        let interface = self.captured_interface.clone().or_else(|| self.config.interface.clone())?;
+        let alias = self.config.interface_aliases.get(&interface).cloned();
+        Some((interface, alias))
+    }
+    
     /// Check if promiscuous mode is enabled
     pub fn is_promiscuous(&self) -> bool {
         self.config.promiscuous
     }
     
+    /// Promiscuous mode the capture device actually entered, None when no device
+    /// was opened or the mode could not be verified
+    pub fn get_actual_promiscuous(&self) -> Option<bool> {
+        self.actual_promiscuous
+    }
+    
     /// Get the current filter
     pub fn get_filter(&self) -> Option<String> {
         self.config.filter.clone()
     }
     
-    /// Generate a packet ID
-    fn generate_id(packets: &DashMap<u64, Packet>) -> u64 {
-        // Use an atomic counter for sequential IDs
-        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
-        NEXT_ID.fetch_add(1, Ordering::SeqCst)
+    /// The filter captures are opened with: the configured one, without the
+    /// API server's own traffic unless that is included
+    pub fn capture_filter(&self) -> Option<String> {
+        self.with_api_exclusion(self.config.filter.as_deref())
+    }
+    
+    /// Add the exclusion of the API server's traffic to a filter, when enabled
+    fn with_api_exclusion(&self, filter: Option<&str>) -> Option<String> {
+        let filter = filter.map(str::trim).filter(|filter| !filter.is_empty());
+        if !self.config.exclude_api_traffic {
+            return filter.map(str::to_string);
+        }
+        
+        let exclusion = format!("not (host {} and tcp port {})", API_HOST, self.config.port);
+        Some(match filter {
+            Some(filter) => format!("({}) and {}", filter, exclusion),
+            None => exclusion,
+        })
+    }
+    
+    /// Leave the API server's traffic out of captures started from now on
+    pub fn set_exclude_api_traffic(&mut self, exclude: bool) {
+        self.config.exclude_api_traffic = exclude;
+    }
+    
+    /// Whether pcapng exports leave out the OS and hardware of this host
+    pub fn hides_host_info(&self) -> bool {
+        self.config.hide_host_info
+    }
+    
+    /// Get the limits applied to header JSON served by the API
+    pub fn get_json_limits(&self) -> JsonLimits {
+        JsonLimits {
+            max_depth: self.config.max_headers_depth,
+            max_bytes: self.config.max_headers_bytes,
+        }
+    }
+    
+    /// Get the warm-up discard limits (packet count and time window in milliseconds)
+    pub fn get_warmup(&self) -> (usize, u64) {
+        (self.config.warmup_packets, self.config.warmup_ms)
+    }
+    
+    /// Take the next packet ID of the session. IDs never wrap around, as
+    /// pagination and everything referring to packets by ID rely on them
+    /// increasing: once the last one is taken, None is returned instead.
+    fn generate_id(next_id: &AtomicU64) -> Option<u64> {
+        next_id.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |id| id.checked_add(1)).ok()
     }
     
     /// Format source address
@@ -881,6 +2342,7 @@ impl CaptureManager {
     
     /// Generate info string for packet summary
     fn generate_info(packet: &Packet) -> String {
+        let headers = packet.expanded_headers();
         match packet.protocol.as_str() {
             "TCP" => {
                 if let (Some(sport), Some(dport)) = (packet.source_port, packet.destination_port) {
@@ -897,38 +2359,229 @@ impl CaptureManager {
             },
             "UDP" => "UDP Datagram".to_string(),
             "ICMP" => "ICMP Message".to_string(),
-            "DNS" => "DNS Query/Response".to_string(),
+            "GTP-U" => {
+                let gtp = &headers["gtp"];
+                format!(
+                    "GTP-U {} (TEID 0x{:08x})",
+                    gtp["message_type_name"].as_str().unwrap_or("Message"),
+                    gtp["teid"].as_u64().unwrap_or(0)
+                )
+            },
+            "DNS" => {
+                let dns = &headers["dns"];
+                let kind = if dns["is_response"].as_bool() == Some(true) { "response" } else { "query" };
+                let mut info = match (dns["id"].as_u64(), dns["query_name"].as_str()) {
+                    (Some(id), Some(name)) => format!(
+                        "DNS {} 0x{:04x} {} {}",
+                        kind,
+                        id,
+                        dns["query_type_name"].as_str().unwrap_or("?"),
+                        name
+                    ),
+                    (Some(id), None) => format!("DNS {} 0x{:04x}", kind, id),
+                    _ => "DNS Query/Response".to_string(),
+                };
+                if dns["zone_transfer"].as_bool() == Some(true) {
+                    info.push_str(" (zone transfer)");
+                }
+                if dns["continuation"].as_bool() == Some(true) {
+                    info.push_str(" (continuation)");
+                } else if dns["transport"] == "tcp" {
+                    info.push_str(" over TCP");
+                }
+                info
+            },
+            "NTP" => {
+                let ntp = &headers["ntp"];
+                match (ntp["mode"].as_u64(), ntp["stratum"].as_u64()) {
+                    (Some(mode), Some(stratum)) => format!(
+                        "NTP {} (version {}, stratum {})",
+                        crate::capture::protocols::ntp::mode_name(mode as u8),
+                        ntp["version"].as_u64().unwrap_or(0),
+                        stratum
+                    ),
+                    _ => "NTP Message".to_string(),
+                }
+            },
+            "RADIUS" => {
+                let radius = &headers["radius"];
+                let mut info = format!(
+                    "RADIUS {} (id {})",
+                    radius["code_name"].as_str().unwrap_or("Message"),
+                    radius["identifier"].as_u64().unwrap_or(0)
+                );
+                if let Some(user) = radius["user_name"].as_str() {
+                    info.push_str(&format!(" user={}", user));
+                }
+                info
+            },
+            "RTP" => {
+                let rtp = &headers["rtp"];
+                let mut info = format!(
+                    "PT={}, SSRC=0x{:08X}, Seq={}, Time={}",
+                    match rtp["codec"].as_str() {
+                        Some("dynamic") | None => format!("DynamicRTP-Type-{}", rtp["payload_type"]),
+                        Some(codec) => codec.to_string(),
+                    },
+                    rtp["ssrc"].as_u64().unwrap_or(0),
+                    rtp["sequence"],
+                    rtp["timestamp"]
+                );
+                if rtp["marker"].as_bool() == Some(true) {
+                    info.push_str(", Mark");
+                }
+                info
+            },
+            "SIP" => {
+                let sip = &headers["sip"];
+                let mut info = match (sip["method"].as_str(), sip["status_code"].as_u64()) {
+                    (Some(method), _) => format!("SIP Request: {} {}", method, sip["request_uri"].as_str().unwrap_or("")),
+                    (None, Some(code)) => format!("SIP Status: {} {}", code, sip["reason"].as_str().unwrap_or("")),
+                    (None, None) => "SIP".to_string(),
+                };
+                if let Some(call_id) = sip["call_id"].as_str() {
+                    info.push_str(&format!(", Call-ID: {}", call_id));
+                }
+                if sip.get("sdp").is_some() {
+                    info.push_str(" (SDP)");
+                }
+                info
+            },
+            "SNMP" => {
+                let snmp = &headers["snmp"];
+                let version = snmp["version_name"].as_str().unwrap_or("?");
+                let pdu = snmp["pdu_type_name"].as_str()
+                    .unwrap_or(if snmp["encrypted"].as_bool() == Some(true) { "encrypted PDU" } else { "Message" });
+                let mut info = format!("SNMP{} {}", version, pdu);
+                if let Some(community) = snmp["community"].as_str() {
+                    info.push_str(&format!(" community={}", community));
+                } else if let Some(user) = snmp["user_name"].as_str() {
+                    info.push_str(&format!(" user={}", user));
+                }
+                if let Some(request_id) = snmp["request_id"].as_i64() {
+                    info.push_str(&format!(" (id {})", request_id));
+                }
+                info
+            },
+            "Diameter" => {
+                let diameter = &headers["diameter"];
+                format!(
+                    "Diameter {} (app {}, hbh {})",
+                    diameter["command_name"].as_str().unwrap_or("Message"),
+                    diameter["application_id"].as_u64().unwrap_or(0),
+                    diameter["hop_by_hop_id"].as_u64().unwrap_or(0)
+                )
+            },
+            "SSH" => {
+                let ssh = &headers["ssh"];
+                let side = match ssh["role"].as_str() {
+                    Some("client") => "SSH client",
+                    Some("server") => "SSH server",
+                    _ => "SSH",
+                };
+                match (ssh["software"].as_str(), ssh["message_type_name"].as_str()) {
+                    (Some(software), _) => match ssh["comments"].as_str() {
+                        Some(comments) => format!("{}: {} {} (protocol {})", side, software, comments, ssh["protocol_version"].as_str().unwrap_or("?")),
+                        None => format!("{}: {} (protocol {})", side, software, ssh["protocol_version"].as_str().unwrap_or("?")),
+                    },
+                    (None, Some(message)) => format!("{} {}", side, message),
+                    (None, None) => side.to_string(),
+                }
+            },
+            "FTP" => {
+                let ftp = &headers["ftp"];
+                match (ftp["command"].as_str(), ftp["reply_code"].as_u64()) {
+                    (Some(command), _) => match ftp["argument"].as_str() {
+                        Some(argument) => format!("FTP Request: {} {}", command, argument),
+                        None => format!("FTP Request: {}", command),
+                    },
+                    (None, Some(code)) => format!("FTP Response: {} {}", code, ftp["reply_text"].as_str().unwrap_or("")),
+                    (None, None) => "FTP".to_string(),
+                }
+            },
+            "FTP-DATA" => format!("FTP Data: {} bytes", headers["ftp-data"]["data_length"]),
+            "TFTP" => {
+                let tftp = &headers["tftp"];
+                let opcode = tftp["opcode_name"].as_str().unwrap_or("Message");
+                match tftp["opcode"].as_u64() {
+                    Some(1) | Some(2) => format!(
+                        "TFTP {}, File: {}, Transfer type: {}",
+                        opcode,
+                        tftp["filename"].as_str().unwrap_or("?"),
+                        tftp["mode"].as_str().unwrap_or("?")
+                    ),
+                    Some(3) if tftp["last_block"].as_bool() == Some(true) => format!("TFTP {}, Block: {} (last)", opcode, tftp["block"]),
+                    Some(3) | Some(4) => format!("TFTP {}, Block: {}", opcode, tftp["block"]),
+                    Some(5) => format!(
+                        "TFTP {}, Code: {}, Message: {}",
+                        opcode,
+                        tftp["error_name"].as_str().unwrap_or("?"),
+                        tftp["error_message"].as_str().unwrap_or("")
+                    ),
+                    _ => format!("TFTP {}", opcode),
+                }
+            },
+            "SCTP" => "SCTP Packet".to_string(),
+            "OSPF" => {
+                let ospf = &headers["ospf"];
+                let mut info = format!(
+                    "OSPFv{} {} router {} area {}",
+                    ospf["version"].as_u64().unwrap_or(0),
+                    ospf["type_name"].as_str().unwrap_or("Packet"),
+                    ospf["router_id"].as_str().unwrap_or("?"),
+                    ospf["area_id"].as_str().unwrap_or("?")
+                );
+                if let Some(neighbors) = ospf["neighbors"].as_array() {
+                    info.push_str(&format!(" ({} neighbors)", neighbors.len()));
+                } else if let Some(lsas) = ospf["lsas"].as_array() {
+                    info.push_str(&format!(" ({} LSAs)", lsas.len()));
+                }
+                info
+            },
+            "LLDP" | "CDP" => {
+                let key = packet.protocol.to_ascii_lowercase();
+                let discovery = &headers[key.as_str()];
+                let device = discovery["system_name"].as_str()
+                    .or(discovery["device_id"].as_str())
+                    .or(discovery["chassis_id"].as_str())
+                    .unwrap_or("?");
+                format!("{} {} port {}", packet.protocol, device, discovery["port_id"].as_str().unwrap_or("?"))
+            },
+            "WOL" => format!("Wake-on-LAN for {}", headers["wol"]["target_mac"].as_str().unwrap_or("?")),
+            "802.11" => {
+                let wifi = &headers["wifi"];
+                let mut info = wifi["subtype_name"].as_str().unwrap_or("802.11 Frame").to_string();
+                if let Some(ssid) = wifi["ssid"].as_str().filter(|_| wifi["hidden_ssid"].as_bool() != Some(true)) {
+                    info.push_str(&format!(" SSID={}", ssid));
+                }
+                if let Some(channel) = wifi["channel"].as_u64().or_else(|| headers["radiotap"]["channel"].as_u64()) {
+                    info.push_str(&format!(" ch {}", channel));
+                }
+                if let Some(signal) = headers["radiotap"]["signal_dbm"].as_i64() {
+                    info.push_str(&format!(" {} dBm", signal));
+                }
+                info
+            },
             "ARP" => "ARP Request/Reply".to_string(),
+            "PPPoE" => {
+                let pppoe = &headers["pppoe"];
+                let session_id = pppoe["session_id"].as_u64().unwrap_or(0);
+                match pppoe["ppp_protocol_name"].as_str() {
+                    Some(protocol) => format!("PPPoE session 0x{:04x} {}", session_id, protocol),
+                    None => format!("PPPoE {} session 0x{:04x}", pppoe["code_name"].as_str().unwrap_or("Discovery"), session_id),
+                }
+            },
             _ => format!("{} Packet", packet.protocol),
         }
     }
     
-    /// Enforce the buffer size limit by removing oldest packets if needed
-    fn enforce_buffer_limit(packets: &DashMap<u64, Packet>, buffer_size: usize) {
-        // If we're within the limit, do nothing
-        if packets.len() <= buffer_size {
-            return;
-        }
-        
-        // Get all packet IDs sorted by timestamp (oldest first)
-        let mut packet_ids: Vec<(u64, DateTime<Utc>)> = packets
-            .iter()
-            .map(|p| (p.id, p.timestamp))
-            .collect();
-        
-        // Sort by timestamp (oldest first)
-        packet_ids.sort_by(|a, b| a.1.cmp(&b.1));
-        
-        // Calculate how many to remove
-        let to_remove = packets.len().saturating_sub(buffer_size);
-        
-        // Remove oldest packets
-        for i in 0..to_remove {
-            if i < packet_ids.len() {
-                let id = packet_ids[i].0;
-                packets.remove(&id);
-                debug!("Removed oldest packet ID {} to maintain buffer size", id);
-            }
+    /// Evict the oldest packets until the stored frames, counted by `stored`,
+    /// fit in `max_bytes`
+    fn enforce_byte_budget(packets: &PacketStore, stored: &AtomicU64, max_bytes: u64) {
+        while stored.load(Ordering::Relaxed) > max_bytes {
+            let Some(packet) = packets.pop_oldest() else { break };
+            stored.fetch_sub(packet.raw_data.len() as u64, Ordering::Relaxed);
+            debug!("Removed oldest packet ID {} to stay within the byte budget", packet.id);
         }
     }
     
@@ -937,44 +2590,221 @@ impl CaptureManager {
         self.config.interface = Some(interface);
     }
     
+    /// Start a capture on `interface`, leaving the selected interface as it is
+    pub async fn start_capture_on(&mut self, interface: String) -> Result<()> {
+        let selected = self.config.interface.replace(interface);
+        let result = self.start_capture().await;
+        self.config.interface = selected;
+        result
+    }
+    
     /// Set promiscuous mode
     pub fn set_promiscuous(&mut self, promiscuous: bool) {
         self.config.promiscuous = promiscuous;
     }
     
+    /// Set whether a bond or bridge is captured on its members
+    pub fn set_capture_members(&mut self, members: bool) {
+        self.config.capture_members = members;
+    }
+    
+    /// Set whether the captures of a session wait for each other and start together
+    pub fn set_synchronized_start(&mut self, synchronized: bool) {
+        self.config.synchronized_start = synchronized;
+    }
+    
     /// Set filter for capture
     pub fn set_filter(&mut self, filter: String) {
         self.config.filter = Some(filter);
     }
     
+    /// Change the capture filter, on the running capture handle when possible.
+    ///
+    /// An invalid filter is rejected without touching the capture. Captures
+    /// without a live handle (the Windows helper) are restarted instead.
+    pub async fn apply_filter(&mut self, filter: String) -> Result<FilterChange> {
+        if !self.is_running.load(Ordering::SeqCst) {
+            self.config.filter = Some(filter);
+            return Ok(FilterChange::Stored);
+        }
+        
+        // Only one capture thread takes live changes; several are restarted
+        let sender = match self.captures.as_slice() {
+            [capture] => Some(capture.filter_tx.clone()),
+            _ => None,
+        };
+        if let Some(sender) = sender {
+            let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+            let effective = self.with_api_exclusion(Some(&filter)).unwrap_or_default();
+            if sender.send(FilterRequest { filter: effective, reply: reply_tx }).is_ok() {
+                match tokio::time::timeout(FILTER_APPLY_TIMEOUT, reply_rx).await {
+                    Ok(Ok(Ok(()))) => {
+                        self.config.filter = Some(filter);
+                        return Ok(FilterChange::Live);
+                    },
+                    Ok(Ok(Err(e))) => return Err(anyhow!("Invalid capture filter '{}': {}", filter, e)),
+                    Ok(Err(_)) | Err(_) => warn!("Capture thread did not apply the filter, restarting the capture"),
+                }
+            }
+        }
+        
+        self.stop_capture().await?;
+        self.config.filter = Some(filter);
+        self.start_capture().await?;
+        Ok(FilterChange::Restart)
+    }
+    
     /// Set buffer size for packet capture
     pub fn set_buffer_size(&mut self, buffer_size: usize) {
         // Ensure a reasonable minimum
         self.config.buffer_size = buffer_size.max(100);
     }
     
-    /// Fetch interface information with pnet_datalink completely disabled on Windows
-    fn fetch_interface_info(&self) -> Vec<InterfaceInfo> {
-        info!("Fetching network interface information");
+    /// Set the warm-up discard limits (packet count and time window in milliseconds)
+    pub fn set_warmup(&mut self, packets: usize, ms: u64) {
+        self.config.warmup_packets = packets;
+        self.config.warmup_ms = ms;
+    }
+    
+    /// Set how long a capture may go without packets before it is stopped (0 disables)
+    pub fn set_stop_after_idle(&mut self, secs: u64) {
+        self.config.stop_after_idle_secs = secs;
+    }
+    
+    /// Set how many packets of each conversation are processed (0 for all), from the next capture
+    pub fn set_flow_head(&mut self, packets: usize) {
+        self.config.flow_head_packets = packets;
+    }
+    
+    /// Set the frame sizes kept (0 leaves a side open), applied to a running capture at once
+    pub fn set_size_limits(&mut self, min: usize, max: usize) {
+        self.config.min_packet_size = min;
+        self.config.max_packet_size = max;
+        self.size_limits.set(min, max);
+    }
+    
+    /// Get the frame sizes kept as (min, max)
+    pub fn get_size_limits(&self) -> (usize, usize) {
+        (self.config.min_packet_size, self.config.max_packet_size)
+    }
+    
+    /// Whether a running capture has gone without packets for longer than the idle timeout
+    pub fn idle_timeout_expired(&self) -> bool {
+        let timeout_secs = self.config.stop_after_idle_secs;
+        if timeout_secs == 0 || !self.get_status() {
+            return false;
+        }
+        
+        let idle_ms = Utc::now().timestamp_millis() - self.last_packet_ms.load(Ordering::Relaxed);
+        idle_ms >= (timeout_secs as i64).saturating_mul(1000)
+    }
+    
+    /// Whether a running capture has run for `max_capture_seconds`
+    pub fn capture_cap_reached(&self) -> bool {
+        match (self.get_max_capture_seconds(), self.capture_started) {
+            (Some(cap), Some(started)) if self.get_status() => started.elapsed() >= Duration::from_secs(cap),
+            _ => false,
+        }
+    }
+    
+    /// Get the capture length cap in seconds, if one is configured
+    pub fn get_max_capture_seconds(&self) -> Option<u64> {
+        Some(self.config.max_capture_seconds).filter(|&cap| cap > 0)
+    }
+    
+    /// Whether a running capture with the stop action has used up its byte budget
+    pub fn byte_budget_exhausted(&self) -> bool {
+        self.get_status() && self.budget_exhausted.load(Ordering::Relaxed)
+    }
+    
+    /// Get the byte budget and its use, if one is configured
+    pub fn get_byte_budget(&self) -> Option<ByteBudget> {
+        let max_total_bytes = self.config.max_total_bytes;
+        if max_total_bytes == 0 {
+            return None;
+        }
         
-        // For non-Windows systems, use the pnet_datalink interfaces
-        Self::get_pnet_interfaces()
+        let used_bytes = self.budget_used.load(Ordering::Relaxed);
+        Some(ByteBudget {
+            max_total_bytes,
+            used_bytes,
+            action: self.config.budget_action,
+            exhausted: self.budget_exhausted.load(Ordering::Relaxed) || used_bytes >= max_total_bytes,
+        })
+    }
+    
+    /// Set the delay before capture starts reading packets
+    pub fn set_start_delay(&mut self, delay_ms: u64) {
+        self.config.start_delay_ms = delay_ms;
+    }
+    
+    /// Get the saved filters, ordered by name
+    pub fn list_filters(&self) -> Vec<Filter> {
+        let mut filters: Vec<Filter> = self.filters.values().cloned().collect();
+        filters.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        filters
+    }
+    
+    /// Get a saved filter by ID
+    pub fn get_saved_filter(&self, id: &str) -> Option<Filter> {
+        self.filters.get(id).cloned()
+    }
+    
+    /// Save a filter, replacing any filter with the same ID
+    pub fn save_filter(&mut self, filter: Filter) {
+        self.filters.insert(filter.id.clone(), filter);
+    }
+    
+    /// Delete a saved filter, returning it if it existed
+    pub fn delete_saved_filter(&mut self, id: &str) -> Option<Filter> {
+        self.filters.remove(id)
+    }
+    
+    /// Force traffic on a port to be decoded as a protocol, or remove the override with `None`.
+    ///
+    /// Takes effect immediately, including for a running capture.
+    pub fn set_decode_as(&mut self, port: u16, protocol: Option<String>) {
+        let mut decode_as = self.decode_as.write();
+        match protocol {
+            Some(protocol) => {
+                decode_as.insert(port, protocol.clone());
+                self.config.decode_as.insert(port, protocol);
+            },
+            None => {
+                decode_as.remove(&port);
+                self.config.decode_as.remove(&port);
+            }
+        }
+    }
+    
+    /// Get the current port to protocol overrides
+    pub fn get_decode_as(&self) -> HashMap<u16, String> {
+        self.decode_as.read().clone()
     }
     
-    /// Helper method to get interfaces from pnet_datalink
+    /// Fetch interface information with pnet_datalink completely disabled on Windows
     fn get_pnet_interfaces() -> Vec<InterfaceInfo> {
+        info!("Fetching network interface information");
+        
         let pcap_interfaces = match pcap::Device::list() {
             Ok(interfaces) => interfaces,
             Err(e) => {
                 error!("Failed to get interfaces from pcap: {}", e);
-                return Vec::new();
+                Vec::new()
             }
         };
         
         // Convert pcap interfaces to our InterfaceInfo format
-        pcap_interfaces.into_iter().map(|iface| {
+        let interfaces: Vec<InterfaceInfo> = pcap_interfaces.into_iter().map(|iface| {
             // Create interface info with name and description
             let mut info = InterfaceInfo::new(iface.name.clone());
+            info.mtu = mtu::interface_mtu(&iface.name);
+            info.mac_address = direction::interface_mac(&iface.name);
+            info.ip_addresses = iface.addresses.iter().map(|address| address.addr.to_string()).collect();
+            info.ipv4_address = iface.addresses.iter()
+                .find(|address| address.addr.is_ipv4())
+                .map(|address| address.addr.to_string());
+            info.is_loopback = iface.flags.is_loopback();
             
             // Set the description field from the pcap device description
             if let Some(desc) = iface.desc {
@@ -984,9 +2814,354 @@ impl CaptureManager {
             }
             
             info
+        }).collect();
+        
+        if !interfaces.is_empty() {
+            return interfaces;
+        }
+        
+        // Some setups (notably Npcap on Windows) list no devices even though
+        // adapters exist; ask the platform so an interface can still be selected
+        warn!("pcap listed no interfaces, falling back to the platform's adapter list");
+        Self::get_platform_interfaces()
+    }
+    
+    /// Whether the links of interfaces are up, read the way the interface
+    /// list reads `is_up` (listing the interfaces once, which runs PowerShell
+    /// on Windows); interfaces the platform does not list are left out
+    pub fn link_states(interfaces: &[String]) -> Vec<(String, bool)> {
+        Self::get_platform_interfaces()
+            .into_iter()
+            .filter(|info| interfaces.contains(&info.device_name))
+            .map(|info| (info.device_name, info.is_up))
+            .collect()
+    }
+    
+    /// List adapters with PowerShell, named the way Npcap names its devices
+    #[cfg(target_os = "windows")]
+    fn get_platform_interfaces() -> Vec<InterfaceInfo> {
+        let output = match crate::capture::host_info::powershell(
+            "Get-NetAdapter | Select-Object -Property Name, InterfaceDescription, InterfaceGuid, Status, MtuSize | ConvertTo-Json",
+        ) {
+            Ok(output) => output,
+            Err(e) => {
+                error!("Failed to list network adapters: {}", e);
+                return Vec::new();
+            }
+        };
+        
+        // A single adapter is serialized as an object rather than an array
+        let adapters = match serde_json::from_str::<serde_json::Value>(&output) {
+            Ok(serde_json::Value::Array(adapters)) => adapters,
+            Ok(adapter @ serde_json::Value::Object(_)) => vec![adapter],
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                error!("Failed to parse the network adapter list: {}", e);
+                return Vec::new();
+            }
+        };
+        
+        adapters.iter().filter_map(|adapter| {
+            let guid = adapter["InterfaceGuid"].as_str()?;
+            let mut info = InterfaceInfo::new(format!("\\Device\\NPF_{}", guid))
+                .with_friendly_name(adapter["Name"].as_str().map(str::to_string))
+                .with_description(adapter["InterfaceDescription"].as_str().map(str::to_string));
+            info.is_up = adapter["Status"].as_str() == Some("Up");
+            info.mtu = adapter["MtuSize"].as_u64().map(|mtu| mtu as u32);
+            Some(info)
         }).collect()
     }
     
+    /// List interfaces from /sys/class/net
+    #[cfg(target_os = "linux")]
+    fn get_platform_interfaces() -> Vec<InterfaceInfo> {
+        let entries = match std::fs::read_dir("/sys/class/net") {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("Failed to read /sys/class/net: {}", e);
+                return Vec::new();
+            }
+        };
+        
+        let mut interfaces: Vec<InterfaceInfo> = entries.filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            let path = std::path::Path::new("/sys/class/net").join(&name);
+            let mut info = InterfaceInfo::new(name.clone());
+            info.mtu = mtu::interface_mtu(&name);
+            info.mac_address = std::fs::read_to_string(path.join("address")).ok().map(|mac| mac.trim().to_string());
+            info.is_up = std::fs::read_to_string(path.join("operstate")).is_ok_and(|state| state.trim() != "down");
+            info.is_loopback = name == "lo";
+            Some(info)
+        }).collect();
+        
+        interfaces.sort_by(|a, b| a.device_name.cmp(&b.device_name));
+        interfaces
+    }
+    
+    /// No platform fallback elsewhere
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    fn get_platform_interfaces() -> Vec<InterfaceInfo> {
+        Vec::new()
+    }
+    
+    /// Replace the armed trigger (None disarms it)
+    pub fn set_trigger(&mut self, trigger: Option<ArmedTrigger>) {
+        self.trigger = trigger;
+    }
+    
+    /// Get the status of the armed trigger, if any
+    pub fn get_trigger_status(&self) -> Option<TriggerStatus> {
+        self.trigger.as_ref().map(|t| t.status())
+    }
+    
+    /// Get the most recent security alerts, oldest first
+    pub fn get_alerts(&self, limit: usize) -> Vec<Alert> {
+        self.alerts.recent(limit)
+    }
+    
+    /// Get the devices discovered from LLDP and CDP, most recently seen first
+    pub fn get_neighbors(&self) -> Vec<Neighbor> {
+        self.neighbors.list()
+    }
+    
+    /// Whether a decoder is enabled, i.e. not turned off with `--disable-protocols`
+    pub fn decoder_enabled(&self, protocol: &str) -> bool {
+        !self.config.disabled_protocols.iter().any(|p| p.trim().eq_ignore_ascii_case(protocol))
+    }
+    
+    /// Get the Wi-Fi access points seen in beacons and probe responses, strongest first
+    pub fn get_access_points(&self) -> Vec<AccessPoint> {
+        self.access_points.list()
+    }
+    
+    /// Get the MTU statistics and per-destination path MTU hints
+    pub fn get_mtu_report(&self) -> MtuReport {
+        self.mtu.report()
+    }
+    
+    /// Get the measured response times of a request/response protocol
+    pub fn get_latency_report(&self, protocol: LatencyProtocol) -> LatencyReport {
+        self.latency.report(protocol)
+    }
+    
+    /// Get the RTP streams with their loss and jitter
+    pub fn get_rtp_streams(&self) -> Vec<RtpStream> {
+        self.rtp.list()
+    }
+    
+    /// Get the conversations, optionally only those that carried a protocol, most bytes first
+    pub fn get_conversations(&self, protocol: Option<&str>, sparkline: bool) -> Vec<Conversation> {
+        self.conversations.list(protocol, sparkline)
+    }
+    
+    /// The stored packets of the conversation with the given ID, in capture
+    /// order; None when no such conversation is tracked
+    pub fn get_conversation_packets(&self, id: &str) -> Option<Vec<Packet>> {
+        let belongs = self.conversations.member_filter(id)?;
+        let mut packets: Vec<Packet> = self.packets
+            .iter()
+            .filter(|p| belongs(p))
+            .map(|p| p.clone().expanded())
+            .collect();
+        packets.sort_unstable_by_key(|p| p.id);
+        Some(packets)
+    }
+    
+    /// Inter-packet timing of the stream (flow direction) the given packet
+    /// belongs to, listing at most `limit` gaps. None when the packet is not
+    /// stored or has no IP addresses.
+    pub fn get_stream_timing(&self, id: u64, limit: usize) -> Option<StreamTiming> {
+        let packet = self.get_packet(id)?;
+        packet.source_ip?;
+        
+        let transport = ConversationTable::transport(&packet);
+        let arrivals = self.packets
+            .iter()
+            .filter(|p| timing::same_stream(&packet, &transport, p))
+            .map(|p| p.timestamp)
+            .collect();
+        timing::stream_timing(&packet, arrivals, limit)
+    }
+    
+    /// Bytes in flight over time from the sender of the given packet, sampled
+    /// after each segment of its TCP connection, listing at most `limit`
+    /// samples. None when the packet is not stored or is not TCP over IP.
+    pub fn get_stream_inflight(&self, id: u64, limit: usize) -> Option<StreamInflight> {
+        let packet = self.get_packet(id)?;
+        let observations = self.packets
+            .iter()
+            .filter_map(|p| inflight::observe(&packet, &p))
+            .collect();
+        inflight::stream_inflight(&packet, observations, limit)
+    }
+    
+    /// The given packet and the segments of its TCP connection, copied out
+    /// for `carving::carve` to run on without the manager. None when the
+    /// packet is not stored.
+    pub fn get_stream_pieces(&self, id: u64) -> Option<(Packet, Vec<Piece>)> {
+        let packet = self.get_packet(id)?;
+        let pieces = self.packets
+            .iter()
+            .filter_map(|p| carving::piece(&packet, &p))
+            .collect();
+        Some((packet, pieces))
+    }
+    
+    /// Get the per-second traffic history of the retained window, oldest first
+    pub fn get_stats_history(&self) -> Vec<StatsBucket> {
+        self.history.buckets()
+    }
+    
+    /// Sample the packet and byte totals into the rate history; called once a second
+    pub fn tick_rates(&self) {
+        let stats = self.get_stats();
+        self.rates.tick(stats.total_packets, stats.total_bytes);
+    }
+    
+    /// Get the packets and bytes per second over the last minute, oldest first
+    pub fn get_rate_trend(&self) -> RateTrend {
+        self.rates.trend()
+    }
+    
+    /// Get the number of retained security alerts
+    pub fn get_alert_count(&self) -> usize {
+        self.alerts.count()
+    }
+    
+    /// Get a receiver for capture warnings and errors
+    pub fn subscribe_to_events(&self) -> broadcast::Receiver<CaptureEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Get the warnings, errors and link changes of the current session, oldest first
+    pub fn get_events(&self) -> Vec<CaptureEvent> {
+        self.events.history()
+    }
+    
+    /// Get a handle to the event log, for recording events from outside the capture path
+    pub fn event_log(&self) -> EventLog {
+        self.events.clone()
+    }
+    
+    /// Get a receiver for newly raised security alerts
+    pub fn subscribe_to_alerts(&self) -> broadcast::Receiver<Alert> {
+        self.alerts.subscribe()
+    }
+    
+    /// Build a snapshot of the current packet buffer and statistics
+    pub fn create_snapshot(&self) -> Snapshot {
+        let mut packets: Vec<SnapshotPacket> = self.packets
+            .iter()
+            .map(|p| SnapshotPacket {
+                id: p.id,
+                timestamp: p.timestamp,
+                interface: p.interface.clone(),
+                data: snapshot::encode_hex(&p.raw_data),
+                link_type: Some(p.link_type.0),
+            })
+            .collect();
+        packets.sort_by_key(|p| p.id);
+        
+        Snapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            stats: self.get_stats(),
+            packets,
+        }
+    }
+    
+    /// Cheap change indicator for the snapshot writer (packet count and newest ID)
+    pub fn snapshot_fingerprint(&self) -> (usize, u64) {
+        let newest = self.packets.iter().map(|p| p.id).max().unwrap_or(0);
+        (self.packets.len(), newest)
+    }
+    
+    /// Keep an index of stored packets in a memory-mapped file for crash recovery
+    pub fn enable_index(&mut self, path: &std::path::Path) -> Result<()> {
+        let index = PacketIndex::open(path, self.config.buffer_size)?;
+        self.index = Some(Arc::new(parking_lot::Mutex::new(index)));
+        info!("Indexing packets into {}", path.display());
+        Ok(())
+    }
+    
+    /// Keep the packet summaries recovered from an earlier run's index.
+    ///
+    /// An index from a session other than the one restored from the snapshot is
+    /// stale (the snapshot is newer) and is ignored.
+    pub fn restore_index(&mut self, recovered: RecoveredIndex) {
+        let current = self.get_session_id();
+        if current.is_some() && current != recovered.session_id {
+            info!("Ignoring packet index of session {:?}, the restored session is {:?}", recovered.session_id, current);
+            return;
+        }
+        
+        info!("Recovered {} packet summaries from the index of session {:?}", recovered.entries.len(), recovered.session_id);
+        if current.is_none() {
+            *self.session_id.write() = recovered.session_id.clone();
+        }
+        self.recovered_index = Some(recovered);
+    }
+    
+    /// Get the packet summaries recovered from an earlier run's index
+    pub fn get_recovered_index(&self) -> Option<&RecoveredIndex> {
+        self.recovered_index.as_ref()
+    }
+    
+    /// Repopulate the packet buffer and statistics from a snapshot
+    pub fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        let parser = PacketParser::new()
+            .with_disabled_protocols(&self.config.disabled_protocols)
+            .with_decode_as(self.decode_as.clone());
+        let keep_from = snapshot.packets.len().saturating_sub(self.config.buffer_size);
+        let mut restored = 0;
+        
+        self.packets.clear();
+        self.next_id.store(1, Ordering::SeqCst);
+        
+        for stored in snapshot.packets.into_iter().skip(keep_from) {
+            let data = match snapshot::decode_hex(&stored.data) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Skipping snapshot packet {}: {}", stored.id, e);
+                    continue;
+                }
+            };
+            
+            let link_type = stored.link_type.map_or_else(|| self.decoded_link_type(Linktype::ETHERNET), Linktype);
+            match parser.parse_frame(data, &stored.interface, link_type) {
+                Ok(mut packet) => {
+                    packet.id = stored.id;
+                    packet.timestamp = stored.timestamp;
+                    self.next_id.fetch_max(stored.id.saturating_add(1), Ordering::SeqCst);
+                    self.packets.insert(packet.id, packet);
+                    restored += 1;
+                },
+                Err(e) => warn!("Skipping snapshot packet {}: {}", stored.id, e),
+            }
+        }
+        
+        // Restored stats describe a finished session
+        self.stats = snapshot.stats;
+        *self.session_id.write() = self.stats.session_id.clone();
+        if self.stats.end_time.is_none() {
+            self.stats.end_time = Some(snapshot.created_at);
+        }
+        self.shared_stats = None;
+        self.details.clear();
+        
+        info!("Restored {} packets from snapshot", restored);
+    }
+    
+    /// Get a receiver for every packet stored by the processor
+    pub fn subscribe_to_packets(&self) -> broadcast::Receiver<Packet> {
+        self.packet_tx.subscribe()
+    }
+    
+    /// Get the sender of the packet feed, for subscribing consumers as they come and go
+    pub fn packet_feed(&self) -> broadcast::Sender<Packet> {
+        self.packet_tx.clone()
+    }
+    
     /// Get a receiver for stats updates
     pub fn subscribe_to_stats(&self) -> broadcast::Receiver<CaptureStats> {
         self.stats_tx.subscribe()
@@ -1013,4 +3188,10 @@ impl CaptureManager {
             trace!("Skipping stats broadcast due to throttling");
         }
     }
-} 
\ No newline at end of file
+} 
+
+/// Signed seconds from one timestamp to another, with microsecond precision
+pub(crate) fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    let elapsed = to.signed_duration_since(from);
+    elapsed.num_microseconds().map_or(elapsed.num_milliseconds() as f64 / 1e3, |us| us as f64 / 1e6)
+}
@@ -1,5 +1,44 @@
+pub mod access_points;
+pub mod alerts;
+pub mod bpf;
+pub mod carving;
+pub mod color_rules;
+pub mod conversations;
+pub mod dedup;
+pub mod detail_cache;
+pub mod direction;
+pub mod display_filter;
+pub mod event_socket;
+pub mod events;
+pub mod export;
+pub mod file;
+pub mod history;
+pub mod host_info;
+pub mod index;
+pub mod inflight;
+pub mod interfaces;
+pub mod latency;
 pub mod manager;
+pub mod mtu;
+pub mod neighbors;
+pub mod otlp;
+pub mod packet_store;
 pub mod parser;
+pub mod protocols;
+pub mod queue;
+pub mod rate_history;
+pub mod remote;
+pub mod resolver;
+pub mod rtp;
+pub mod self_test;
+pub mod snapshot;
+pub mod start_barrier;
+pub mod stats_report;
+pub mod tail;
+pub mod tcp_analysis;
+pub mod timing;
+pub mod trigger;
+pub mod tun;
 
 #[cfg(target_os = "windows")]
 pub mod windows_helper; 
\ No newline at end of file
@@ -0,0 +1,174 @@
+// MTU awareness: oversized packets and "fragmentation needed" messages per destination
+//
+// A path MTU black hole shows up as large packets towards a destination that
+// are never answered, often alongside ICMP "fragmentation needed" messages
+// that a firewall somewhere fails to pass on. Keeping both per destination
+// makes the mismatch visible without reading through the packet list.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::models::mtu::{MtuReport, PathMtuHint};
+use crate::models::packet::Packet;
+
+/// Maximum number of destinations tracked; new destinations are ignored beyond this
+const MAX_PATHS: usize = 4096;
+
+/// IPv6 fixed header, not included in the payload length
+const IPV6_HEADER_LEN: usize = 40;
+
+/// Per destination state
+#[derive(Debug, Clone)]
+struct PathState {
+    largest_packet: usize,
+    oversized_packets: usize,
+    frag_needed: usize,
+    reported_mtu: Option<(u16, String)>,
+    last_seen: DateTime<Utc>,
+}
+
+/// Tracks packet sizes against the interface MTU and reported next-hop MTUs
+#[derive(Clone, Default)]
+pub struct MtuTracker {
+    /// MTU packets are compared against; None when unknown
+    mtu: Arc<RwLock<Option<u32>>>,
+    
+    paths: Arc<DashMap<IpAddr, PathState>>,
+    oversized_packets: Arc<AtomicUsize>,
+    frag_needed_messages: Arc<AtomicUsize>,
+    
+    /// Serializes inserts so the size cap holds
+    insert_lock: Arc<Mutex<()>>,
+}
+
+impl MtuTracker {
+    /// Create an empty tracker with no known MTU
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Forget everything and compare future packets against `mtu`
+    pub fn reset(&self, mtu: Option<u32>) {
+        *self.mtu.write() = mtu;
+        self.paths.clear();
+        self.oversized_packets.store(0, Ordering::Relaxed);
+        self.frag_needed_messages.store(0, Ordering::Relaxed);
+    }
+    
+    /// Account for a packet, flagging it in its metadata when it is at or above the MTU
+    pub fn inspect(&self, packet: &mut Packet) {
+        // A "fragmentation needed" message is about the destination of the quoted datagram
+        let icmp = &packet.headers["icmp"];
+        if let Some(next_hop_mtu) = icmp["next_hop_mtu"].as_u64() {
+            self.frag_needed_messages.fetch_add(1, Ordering::Relaxed);
+            let original = icmp["original_destination"].as_str().and_then(|d| d.parse::<IpAddr>().ok());
+            let reporter = packet.source_ip.map(|ip| ip.to_string()).unwrap_or_default();
+            if let Some(destination) = original {
+                let next_hop_mtu = next_hop_mtu as u16;
+                self.update(destination, packet.timestamp, |path| {
+                    path.frag_needed += 1;
+                    if path.reported_mtu.as_ref().is_none_or(|(mtu, _)| next_hop_mtu < *mtu) {
+                        path.reported_mtu = Some((next_hop_mtu, reporter));
+                    }
+                });
+            }
+            return;
+        }
+        
+        let (Some(destination), Some(size)) = (packet.destination_ip, Self::ip_length(packet)) else { return };
+        let oversized = self.mtu.read().is_some_and(|mtu| size >= mtu as usize);
+        if oversized {
+            self.oversized_packets.fetch_add(1, Ordering::Relaxed);
+            if let Some(metadata) = packet.metadata.as_object_mut() {
+                metadata.insert("exceeds_mtu".to_string(), serde_json::Value::Bool(true));
+            }
+        }
+        
+        self.update(destination, packet.timestamp, |path| {
+            path.largest_packet = path.largest_packet.max(size);
+            if oversized {
+                path.oversized_packets += 1;
+            }
+        });
+    }
+    
+    /// Current statistics and per-destination hints
+    pub fn report(&self) -> MtuReport {
+        let interface_mtu = *self.mtu.read();
+        
+        let mut paths: Vec<PathMtuHint> = self.paths.iter().map(|entry| {
+            let path = entry.value();
+            let (reported_mtu, reported_by) = match &path.reported_mtu {
+                Some((mtu, by)) => (Some(*mtu), Some(by.clone())),
+                None => (None, None),
+            };
+            PathMtuHint {
+                destination: entry.key().to_string(),
+                largest_packet: path.largest_packet,
+                oversized_packets: path.oversized_packets,
+                frag_needed: path.frag_needed,
+                reported_mtu,
+                reported_by,
+                path_mtu_hint: reported_mtu.map(u32::from).or(interface_mtu),
+                last_seen: path.last_seen,
+            }
+        }).collect();
+        
+        paths.sort_by_key(|p| (p.reported_mtu.is_none(), std::cmp::Reverse(p.oversized_packets), std::cmp::Reverse(p.largest_packet)));
+        
+        MtuReport {
+            interface_mtu,
+            oversized_packets: self.oversized_packets.load(Ordering::Relaxed),
+            frag_needed_messages: self.frag_needed_messages.load(Ordering::Relaxed),
+            paths,
+        }
+    }
+    
+    /// Apply `f` to the state of a destination, creating it if there is room
+    fn update(&self, destination: IpAddr, timestamp: DateTime<Utc>, f: impl FnOnce(&mut PathState)) {
+        if let Some(mut path) = self.paths.get_mut(&destination) {
+            path.last_seen = path.last_seen.max(timestamp);
+            f(&mut path);
+            return;
+        }
+        
+        let _guard = self.insert_lock.lock();
+        if self.paths.len() >= MAX_PATHS {
+            return;
+        }
+        let mut path = self.paths.entry(destination).or_insert(PathState {
+            largest_packet: 0,
+            oversized_packets: 0,
+            frag_needed: 0,
+            reported_mtu: None,
+            last_seen: timestamp,
+        });
+        f(&mut path);
+    }
+    
+    /// Size of the packet's IP datagram, the unit the MTU limits
+    fn ip_length(packet: &Packet) -> Option<usize> {
+        if let Some(length) = packet.headers["ipv4"]["total_length"].as_u64() {
+            return Some(length as usize);
+        }
+        packet.headers["ipv6"]["payload_length"].as_u64().map(|length| length as usize + IPV6_HEADER_LEN)
+    }
+}
+
+/// Read the MTU of a local interface
+#[cfg(target_os = "linux")]
+pub fn interface_mtu(interface: &str) -> Option<u32> {
+    std::fs::read_to_string(format!("/sys/class/net/{}/mtu", interface))
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Read the MTU of a local interface
+#[cfg(not(target_os = "linux"))]
+pub fn interface_mtu(_interface: &str) -> Option<u32> {
+    None
+}
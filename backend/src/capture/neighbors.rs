@@ -0,0 +1,93 @@
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::models::neighbor::Neighbor;
+use crate::models::packet::Packet;
+
+/// Maximum number of neighbors kept; announcements from new devices are ignored beyond this
+const MAX_NEIGHBORS: usize = 1024;
+
+/// Table of devices discovered from LLDP and CDP announcements, keyed by chassis and port
+#[derive(Clone, Default)]
+pub struct NeighborTable {
+    neighbors: Arc<DashMap<(String, String), Neighbor>>,
+    
+    /// Serializes inserts so the size cap holds
+    insert_lock: Arc<Mutex<()>>,
+}
+
+impl NeighborTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add or refresh the neighbor announced by an LLDP or CDP packet
+    pub fn record(&self, packet: &Packet) {
+        let Some(neighbor) = Self::from_packet(packet) else { return };
+        let key = (neighbor.chassis_id.clone(), neighbor.port_id.clone());
+        
+        if let Some(mut existing) = self.neighbors.get_mut(&key) {
+            let first_seen = existing.first_seen;
+            let announcements = existing.announcements + 1;
+            *existing = Neighbor { first_seen, announcements, ..neighbor };
+            return;
+        }
+        
+        let _guard = self.insert_lock.lock();
+        if self.neighbors.len() < MAX_NEIGHBORS {
+            self.neighbors.entry(key).or_insert(neighbor);
+        }
+    }
+    
+    /// All known neighbors, most recently seen first
+    pub fn list(&self) -> Vec<Neighbor> {
+        let mut neighbors: Vec<Neighbor> = self.neighbors.iter().map(|n| n.value().clone()).collect();
+        neighbors.sort_by_key(|n| std::cmp::Reverse(n.last_seen));
+        neighbors
+    }
+    
+    /// Forget all neighbors
+    pub fn clear(&self) {
+        self.neighbors.clear();
+    }
+    
+    /// Build a neighbor entry from a decoded discovery packet
+    fn from_packet(packet: &Packet) -> Option<Neighbor> {
+        let (headers, chassis_key, description_key) = match packet.protocol.as_str() {
+            "LLDP" => (&packet.headers["lldp"], "chassis_id", "system_description"),
+            "CDP" => (&packet.headers["cdp"], "device_id", "software_version"),
+            _ => return None,
+        };
+        
+        let string = |key: &str| headers[key].as_str().map(str::to_string);
+        let strings = |key: &str| -> Vec<String> {
+            headers[key]
+                .as_array()
+                .map(|values| values.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+        
+        let addresses = if packet.protocol == "LLDP" { strings("management_addresses") } else { strings("addresses") };
+        
+        Some(Neighbor {
+            protocol: packet.protocol.clone(),
+            chassis_id: string(chassis_key)?,
+            port_id: string("port_id")?,
+            port_description: string("port_description"),
+            system_name: string("system_name"),
+            system_description: string(description_key),
+            platform: string("platform"),
+            addresses,
+            capabilities: strings("capabilities"),
+            source_mac: packet.source_mac.clone(),
+            interface: packet.interface.clone(),
+            ttl: headers["ttl"].as_u64().unwrap_or(0),
+            first_seen: packet.timestamp,
+            last_seen: packet.timestamp,
+            announcements: 1,
+        })
+    }
+}
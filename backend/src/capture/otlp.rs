@@ -0,0 +1,162 @@
+// Push capture metrics to an OpenTelemetry collector over OTLP/HTTP
+//
+// The exporter follows the same stats broadcast the WebSocket clients get and,
+// once per second (the WebSocket stats throttle), posts the latest counters to
+// the collector's `/v1/metrics` in the OTLP JSON encoding. Counters are sent as
+// cumulative sums starting at the capture start, rates as gauges. A collector
+// that is down or rejects the data only costs a logged warning.
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use crate::models::stats::CaptureStats;
+
+/// How often the latest stats are exported, matching the WebSocket stats throttle
+const EXPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the collector before giving up on one export
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// OTLP aggregation temporality of counters that start at the capture start
+const CUMULATIVE: u8 = 2;
+
+/// Export the stats received on `stats_rx` to the OTLP/HTTP collector at `endpoint`
+/// (e.g. `http://localhost:4318`) in the background
+pub fn spawn(endpoint: &str, mut stats_rx: broadcast::Receiver<CaptureStats>) {
+    let url = format!("{}/v1/metrics", endpoint.trim_end_matches('/'));
+    info!("Exporting capture metrics to {}", url);
+    
+    tokio::spawn(async move {
+        let client = match reqwest::Client::builder().timeout(EXPORT_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Failed to create OTLP client, metrics will not be exported: {}", e);
+                return;
+            }
+        };
+        let mut ticker = tokio::time::interval(EXPORT_INTERVAL);
+        let mut reachable = true;
+        
+        loop {
+            ticker.tick().await;
+            
+            // Only the newest stats of the interval matter
+            let mut latest = None;
+            loop {
+                match stats_rx.try_recv() {
+                    Ok(stats) => latest = Some(stats),
+                    Err(TryRecvError::Lagged(_)) => continue,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Closed) => return,
+                }
+            }
+            let Some(stats) = latest else { continue };
+            
+            let result = client.post(&url).json(&metrics_request(&stats, Utc::now())).send().await;
+            match result.and_then(|response| response.error_for_status()) {
+                Ok(_) if !reachable => {
+                    info!("OTLP collector at {} is reachable again", url);
+                    reachable = true;
+                },
+                Ok(_) => {},
+                // Warn once per outage rather than every second
+                Err(e) if reachable => {
+                    warn!("Failed to export metrics to {}: {}", url, e);
+                    reachable = false;
+                },
+                Err(_) => {},
+            }
+        }
+    });
+}
+
+/// Build an OTLP `ExportMetricsServiceRequest` (JSON encoding) from capture stats
+fn metrics_request(stats: &CaptureStats, now: DateTime<Utc>) -> Value {
+    let start = stats.start_time.unwrap_or(now);
+    let sum = |name: &str, unit: &str, description: &str, points: Vec<Value>| json!({
+        "name": name,
+        "unit": unit,
+        "description": description,
+        "sum": {
+            "aggregationTemporality": CUMULATIVE,
+            "isMonotonic": true,
+            "dataPoints": points,
+        },
+    });
+    let gauge = |name: &str, unit: &str, description: &str, value: f64| json!({
+        "name": name,
+        "unit": unit,
+        "description": description,
+        "gauge": { "dataPoints": [double_point(value, now)] },
+    });
+    
+    let protocol_points = stats.protocols
+        .iter()
+        .map(|(protocol, &count)| int_point(count, start, now, &[("protocol", protocol)]))
+        .collect();
+    let drop_points = [
+        ("backpressure", stats.backpressure_dropped),
+        ("warmup", stats.warmup_discarded),
+        ("flow_head", stats.flow_head_skipped),
+        ("size", stats.size_filtered),
+    ]
+    .iter()
+    .map(|&(reason, count)| int_point(count, start, now, &[("reason", reason)]))
+    .collect();
+    
+    let mut resource = vec![attribute("service.name", "rustshark")];
+    if let Some(session_id) = &stats.session_id {
+        resource.push(attribute("rustshark.session_id", session_id));
+    }
+    
+    json!({
+        "resourceMetrics": [{
+            "resource": { "attributes": resource },
+            "scopeMetrics": [{
+                "scope": { "name": "rustshark", "version": env!("CARGO_PKG_VERSION") },
+                "metrics": [
+                    sum("rustshark.capture.packets", "{packet}", "Packets captured",
+                        vec![int_point(stats.total_packets, start, now, &[])]),
+                    sum("rustshark.capture.bytes", "By", "Bytes captured",
+                        vec![int_point(stats.total_bytes, start, now, &[])]),
+                    sum("rustshark.capture.protocol.packets", "{packet}", "Packets captured per protocol",
+                        protocol_points),
+                    sum("rustshark.capture.dropped", "{packet}", "Packets dropped before processing, by reason",
+                        drop_points),
+                    sum("rustshark.capture.errors", "{error}", "Errors during capture",
+                        vec![int_point(stats.errors, start, now, &[])]),
+                    gauge("rustshark.capture.packet_rate", "{packet}/s", "Packets per second", stats.packet_rate),
+                    gauge("rustshark.capture.data_rate", "By/s", "Bytes per second", stats.data_rate),
+                ],
+            }],
+        }],
+    })
+}
+
+/// A cumulative integer data point (OTLP JSON carries 64-bit integers as strings)
+fn int_point(value: usize, start: DateTime<Utc>, now: DateTime<Utc>, attributes: &[(&str, &str)]) -> Value {
+    json!({
+        "asInt": value.to_string(),
+        "startTimeUnixNano": unix_nanos(start),
+        "timeUnixNano": unix_nanos(now),
+        "attributes": attributes.iter().map(|&(key, value)| attribute(key, value)).collect::<Vec<_>>(),
+    })
+}
+
+fn double_point(value: f64, now: DateTime<Utc>) -> Value {
+    json!({
+        "asDouble": value,
+        "timeUnixNano": unix_nanos(now),
+    })
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn unix_nanos(time: DateTime<Utc>) -> String {
+    time.timestamp_nanos_opt().unwrap_or_default().to_string()
+}
@@ -0,0 +1,162 @@
+// Stored packets, optionally sharded for very large buffers
+//
+// Packets are kept in N shards keyed by `id % N`, each a map with a queue of
+// its IDs in insertion order. Evicting pops the oldest IDs of the shard a new
+// packet went to, instead of sorting the whole buffer by timestamp on every
+// insert, so eviction stays cheap however large the buffer and inserts into
+// different shards neither wait for each other nor for a reader scanning
+// another shard. Each shard holds its share of the buffer size; IDs are
+// sequential, so the shares fill evenly and the oldest packets go first.
+//
+// With one shard (the default) this is a single map and queue. Readers see
+// the shards chained together and order packets by timestamp themselves, as
+// the listing and pagination always have.
+//
+// A packet's parsed headers are a tree of JSON values, dozens of small
+// allocations that outweigh the frame itself. With `--compact-headers-secs`
+// the stored packets' headers are periodically turned into JSON text
+// (`Packet::compact`), one allocation, and decoded again whenever they are
+// read: readers of stored packets go through `Packet::expanded_headers`, and
+// copies taken out of the store are expanded.
+
+use dashmap::mapref::multiple::RefMulti;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+use crate::models::packet::Packet;
+
+/// Queued IDs beyond twice the stored packets (plus this slack) are swept of removed ones
+const ORDER_SLACK: usize = 1024;
+
+/// One shard: its packets and their IDs, oldest first. IDs of packets removed
+/// other than by eviction stay queued until popped or swept.
+#[derive(Default)]
+struct Shard {
+    packets: DashMap<u64, Packet>,
+    order: Mutex<VecDeque<u64>>,
+}
+
+/// Packet storage keyed by packet ID
+pub struct PacketStore {
+    shards: Box<[Shard]>,
+}
+
+impl PacketStore {
+    /// Create an empty store with the given number of shards (at least one)
+    pub fn new(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| Shard::default()).collect(),
+        }
+    }
+    
+    fn shard(&self, id: u64) -> &Shard {
+        &self.shards[(id % self.shards.len() as u64) as usize]
+    }
+    
+    /// Store a packet, returning the one it replaced
+    pub fn insert(&self, id: u64, packet: Packet) -> Option<Packet> {
+        let shard = self.shard(id);
+        let mut order = shard.order.lock();
+        let previous = shard.packets.insert(id, packet);
+        if previous.is_none() {
+            order.push_back(id);
+            if order.len() > 2 * shard.packets.len() + ORDER_SLACK {
+                order.retain(|id| shard.packets.contains_key(id));
+            }
+        }
+        previous
+    }
+    
+    /// Store a packet and evict the oldest ones of its shard beyond the
+    /// shard's share of `capacity`. Returns the packets evicted.
+    pub fn insert_bounded(&self, id: u64, packet: Packet, capacity: usize) -> Vec<Packet> {
+        self.insert(id, packet);
+        
+        let index = (id % self.shards.len() as u64) as usize;
+        let share = capacity / self.shards.len() + usize::from(index < capacity % self.shards.len());
+        let shard = &self.shards[index];
+        let mut evicted = Vec::new();
+        let mut order = shard.order.lock();
+        while shard.packets.len() > share {
+            let Some(oldest) = order.pop_front() else { break };
+            if let Some((_, packet)) = shard.packets.remove(&oldest) {
+                evicted.push(packet);
+            }
+        }
+        evicted
+    }
+    
+    /// Remove the oldest stored packet: the lowest ID at the front of a shard's queue
+    pub fn pop_oldest(&self) -> Option<Packet> {
+        let oldest = self.shards
+            .iter()
+            .filter_map(|shard| {
+                let mut order = shard.order.lock();
+                while let Some(&id) = order.front() {
+                    if shard.packets.contains_key(&id) {
+                        return Some(id);
+                    }
+                    order.pop_front();
+                }
+                None
+            })
+            .min()?;
+        self.remove(&oldest).map(|(_, packet)| packet)
+    }
+    
+    pub fn get(&self, id: &u64) -> Option<Ref<'_, u64, Packet>> {
+        self.shard(*id).packets.get(id)
+    }
+    
+    pub fn get_mut(&self, id: &u64) -> Option<RefMut<'_, u64, Packet>> {
+        self.shard(*id).packets.get_mut(id)
+    }
+    
+    pub fn contains_key(&self, id: &u64) -> bool {
+        self.shard(*id).packets.contains_key(id)
+    }
+    
+    pub fn remove(&self, id: &u64) -> Option<(u64, Packet)> {
+        self.shard(*id).packets.remove(id)
+    }
+    
+    /// Packets stored in all shards
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.packets.len()).sum()
+    }
+    
+    /// Every stored packet, shard by shard in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = RefMulti<'_, u64, Packet>> {
+        self.shards.iter().flat_map(|shard| shard.packets.iter())
+    }
+    
+    /// Compact the headers of every packet not compacted yet (see
+    /// `Packet::compact`), returning how many were
+    pub fn compact_headers(&self) -> usize {
+        let mut compacted = 0;
+        for shard in self.shards.iter() {
+            // Collect first so inserts wait for one packet at a time, not a whole scan
+            let ids: Vec<u64> = shard.packets
+                .iter()
+                .filter(|packet| packet.compact_headers.is_none())
+                .map(|packet| packet.id)
+                .collect();
+            for id in ids {
+                if shard.packets.get_mut(&id).is_some_and(|mut packet| packet.compact()) {
+                    compacted += 1;
+                }
+            }
+        }
+        compacted
+    }
+    
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            let mut order = shard.order.lock();
+            shard.packets.clear();
+            order.clear();
+        }
+    }
+}
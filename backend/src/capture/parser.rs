@@ -1,7 +1,7 @@
-use anyhow::{Result, anyhow};
+use anyhow::{anyhow, Result};
 use log::{debug, error, trace, log_enabled, Level};
 use pnet::packet::{
-    ethernet::{EthernetPacket, EtherTypes},
+    ethernet::{EtherType, EthernetPacket, EtherTypes},
     ip::{IpNextHeaderProtocol, IpNextHeaderProtocols},
     ipv4::Ipv4Packet,
     ipv6::Ipv6Packet,
@@ -12,36 +12,177 @@ use pnet::packet::{
     Packet as PnetPacket,
 };
 use pnet::util::MacAddr;
+use pcap::Linktype;
+use parking_lot::RwLock;
 use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
 use std::net::IpAddr;
+use std::sync::Arc;
 
+use crate::capture::protocols::{cdp, diameter, dns, ftp, gtp, lldp, ntp, ospf, radius, rtp, sip, snmp, ssh, tftp, wifi, wol};
+use crate::capture::protocols::expected::ExpectedConversations;
 use crate::models::packet::Packet;
 
+/// DLT_RAW, which libpcap reports for LINKTYPE_RAW captures on most platforms
+const DLT_RAW: Linktype = Linktype(12);
+
+/// Packet type, ARPHRD type, address length, address (8 bytes) and protocol
+const SLL_HEADER_LEN: usize = 16;
+
+/// The address family a BSD loopback header holds
+const LOOPBACK_HEADER_LEN: usize = 4;
+
+/// Ports, verification tag and checksum
+const SCTP_HEADER_LEN: usize = 12;
+
+/// Version/type, code, session ID and payload length
+const PPPOE_HEADER_LEN: usize = 6;
+
+/// ICMP destination unreachable type and its "fragmentation needed" code
+const ICMP_DEST_UNREACHABLE: u8 = 3;
+const ICMP_FRAG_NEEDED: u8 = 4;
+
+/// A header cut short by the snapshot length or the end of the frame
+#[derive(Debug)]
+struct Truncated(&'static str);
+
+impl std::fmt::Display for Truncated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} header truncated", self.0)
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+/// Port to protocol overrides, shared so changes apply to a running capture
+pub type DecodeAsMap = Arc<RwLock<HashMap<u16, String>>>;
+
 /// Parses raw packet data into structured packet objects
-pub struct PacketParser {}
+pub struct PacketParser {
+    /// Application-layer decoders to skip (upper-case protocol labels)
+    disabled: HashSet<String>,
+    
+    /// Ports whose traffic is forced to a given protocol ("Decode As")
+    decode_as: DecodeAsMap,
+    
+    /// TCP conversations announced on control connections (FTP data connections)
+    expected_tcp: ExpectedConversations,
+    
+    /// UDP conversations announced by earlier datagrams (TFTP transfers)
+    expected_udp: ExpectedConversations,
+}
 
 impl PacketParser {
     /// Create a new packet parser
     pub fn new() -> Self {
-        Self {}
+        Self {
+            disabled: HashSet::new(),
+            decode_as: DecodeAsMap::default(),
+            expected_tcp: ExpectedConversations::default(),
+            expected_udp: ExpectedConversations::default(),
+        }
     }
     
-    /// Parse raw packet data into a Packet object
-    pub fn parse_packet(&self, data: Vec<u8>, interface: &str) -> Result<Packet> {
-        // Only log in verbose mode
-        if log_enabled!(Level::Debug) {
-            debug!("Parsing packet from interface '{}', size: {} bytes", interface, data.len());
+    /// Consult the given port to protocol overrides before the default port heuristics
+    pub fn with_decode_as(mut self, decode_as: DecodeAsMap) -> Self {
+        self.decode_as = decode_as;
+        self
+    }
+    
+    /// Protocol forced for a port pair, preferring the destination port
+    fn decode_as_for(&self, source_port: u16, destination_port: u16) -> Option<String> {
+        let decode_as = self.decode_as.read();
+        if decode_as.is_empty() {
+            return None;
+        }
+        decode_as
+            .get(&destination_port)
+            .or_else(|| decode_as.get(&source_port))
+            .cloned()
+    }
+    
+    /// Protocol expected for a packet's conversation, learned from an earlier control message
+    fn expected_protocol(expected: &ExpectedConversations, packet: &Packet) -> Option<&'static str> {
+        match (packet.source_ip, packet.source_port, packet.destination_ip, packet.destination_port) {
+            (Some(source_ip), Some(source_port), Some(destination_ip), Some(destination_port)) => {
+                expected.protocol_for((source_ip, source_port), (destination_ip, destination_port))
+            },
+            _ => None,
+        }
+    }
+    
+    /// Run the decoder for a forced protocol, or just relabel the packet when there is none
+    fn decode_forced(&self, protocol: &str, payload: &[u8], packet: &mut Packet) {
+        let protocol = protocol.to_ascii_uppercase();
+        if !self.decodes(&protocol) {
+            return;
         }
         
-        // Parse Ethernet frame
-        let eth_packet = match EthernetPacket::new(&data) {
-            Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse Ethernet packet")),
+        let result = match protocol.as_str() {
+            "DIAMETER" => diameter::parse(payload, packet),
+            "DNS" if packet.headers.get("tcp").is_some() => dns::parse_tcp(payload, packet),
+            "DNS" => dns::parse(payload, packet),
+            "FTP" => self.parse_ftp(payload, packet),
+            "FTP-DATA" => ftp::parse_data(payload, packet),
+            "GTP-U" => self.parse_gtp(payload, packet),
+            "NTP" => ntp::parse(payload, packet),
+            "RADIUS" => radius::parse(payload, packet),
+            "RTP" => rtp::parse(payload, packet),
+            "SIP" => self.parse_sip(payload, packet),
+            "SNMP" => snmp::parse(payload, packet),
+            "SSH" => {
+                // The forced port is the server's, preferring the destination as the lookup does
+                let destination_port = packet.destination_port.unwrap_or_default();
+                let server_port = if self.decode_as.read().contains_key(&destination_port) {
+                    destination_port
+                } else {
+                    packet.source_port.unwrap_or_default()
+                };
+                ssh::parse(payload, packet, server_port)
+            },
+            "TFTP" => self.parse_tftp(payload, packet),
+            "WOL" => wol::parse(payload, packet),
+            _ => {
+                packet.protocol = protocol.clone();
+                Ok(())
+            }
         };
         
-        // Get MAC addresses
-        let src_mac = self.format_mac(eth_packet.get_source());
-        let dst_mac = self.format_mac(eth_packet.get_destination());
+        if let Err(e) = result {
+            trace!("Not decoding as {} (decode-as): {}", protocol, e);
+        }
+    }
+    
+    /// Skip the given application-layer decoders, leaving such packets at the transport layer
+    pub fn with_disabled_protocols<I, S>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.disabled = protocols
+            .into_iter()
+            .map(|p| p.as_ref().trim().to_ascii_uppercase())
+            .collect();
+        self
+    }
+    
+    /// Whether the decoder for a protocol label is enabled
+    fn decodes(&self, protocol: &str) -> bool {
+        !self.disabled.contains(protocol)
+    }
+    
+    /// Parse a raw frame of the given link type into a Packet object.
+    ///
+    /// Ethernet, radiotap and bare 802.11 frames, raw IP, Linux cooked
+    /// captures and BSD loopback are understood; anything else is parsed as
+    /// Ethernet. A frame whose headers end early (a short
+    /// snaplen) keeps the layers decoded before the cut, is labeled with the
+    /// deepest of them and gets `metadata["parse_truncated"] = true`.
+    pub fn parse_frame(&self, data: Vec<u8>, interface: &str, link_type: Linktype) -> Result<Packet> {
+        // Only log in verbose mode
+        if log_enabled!(Level::Debug) {
+            debug!("Parsing packet from interface '{}', size: {} bytes", interface, data.len());
+        }
         
         // Initialize packet with defaults
         let mut packet = Packet {
@@ -54,13 +195,56 @@ impl PacketParser {
             destination_ip: None,
             source_port: None,
             destination_port: None,
-            source_mac: Some(src_mac),
-            destination_mac: Some(dst_mac),
+            source_mac: None,
+            destination_mac: None,
             raw_data: data.clone(),
+            link_type: if link_type == DLT_RAW { Linktype::RAW } else { link_type },
             headers: json!({}),
             payload: None,
             metadata: json!({}),
+            compact_headers: None,
+        };
+        
+        let result = match link_type {
+            Linktype::IEEE802_11_RADIOTAP => {
+                wifi::parse_radiotap(&data, &mut packet).and_then(|frame| self.parse_wifi(frame, &mut packet))
+            },
+            Linktype::IEEE802_11 => self.parse_wifi(&data, &mut packet),
+            Linktype::RAW | Linktype::IPV4 | Linktype::IPV6 | DLT_RAW => self.parse_raw_ip(&data, &mut packet),
+            Linktype::LINUX_SLL => self.parse_sll(&data, &mut packet),
+            Linktype::NULL => self.parse_loopback(&data, &mut packet, false),
+            Linktype::LOOP => self.parse_loopback(&data, &mut packet, true),
+            _ => self.parse_ethernet(&data, &mut packet),
+        };
+        
+        // Each layer labels the packet as it is decoded, so a truncated one
+        // leaves the label of the layer before it
+        if let Err(e) = result {
+            let decoded_any = packet.headers.as_object().is_some_and(|headers| !headers.is_empty());
+            if e.downcast_ref::<Truncated>().is_none() || !decoded_any {
+                return Err(e);
+            }
+            trace!("Keeping the outer layers of a truncated frame: {}", e);
+            if let Some(metadata) = packet.metadata.as_object_mut() {
+                metadata.insert("parse_truncated".to_string(), Value::Bool(true));
+            }
+        }
+        
+        Ok(packet)
+    }
+    
+    /// Parse an Ethernet frame
+    fn parse_ethernet(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        // Parse Ethernet frame
+        let eth_packet = match EthernetPacket::new(data) {
+            Some(packet) => packet,
+            None => return Err(Truncated("Ethernet").into()),
         };
+        packet.protocol = "Ethernet".to_string();
+        
+        // Get MAC addresses
+        packet.source_mac = Some(self.format_mac(eth_packet.get_source()));
+        packet.destination_mac = Some(self.format_mac(eth_packet.get_destination()));
         
         // Add ethernet header to JSON
         let ethertype = eth_packet.get_ethertype();
@@ -79,33 +263,143 @@ impl PacketParser {
                    packet.destination_mac.as_ref().unwrap_or(&"?".to_string()));
         }
         
+        self.parse_ethertype(ethertype, eth_packet.payload(), packet)
+    }
+    
+    /// Parse a Linux cooked capture (SLL) frame, whose header replaces the
+    /// link layer's on the `any` device
+    fn parse_sll(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        if data.len() < SLL_HEADER_LEN {
+            return Err(Truncated("SLL").into());
+        }
+        packet.protocol = "SLL".to_string();
+        
+        let packet_type = u16::from_be_bytes([data[0], data[1]]);
+        let address_len = (u16::from_be_bytes([data[4], data[5]]) as usize).min(8);
+        let address = data[6..6 + address_len].iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+        let protocol = u16::from_be_bytes([data[14], data[15]]);
+        if address_len == 6 {
+            packet.source_mac = Some(address.clone());
+        }
+        packet.headers = json!({
+            "sll": {
+                "packet_type": packet_type,
+                "packet_type_name": match packet_type {
+                    0 => "to us",
+                    1 => "broadcast",
+                    2 => "multicast",
+                    3 => "to another host",
+                    4 => "sent by us",
+                    _ => "unknown",
+                },
+                "link_type": u16::from_be_bytes([data[2], data[3]]),
+                "source_address": address,
+                "protocol": format!("{:?}", EtherType(protocol)),
+            }
+        });
+        
+        self.parse_ethertype(EtherType(protocol), &data[SLL_HEADER_LEN..], packet)
+    }
+    
+    /// Parse a BSD loopback frame: the address family in 4 bytes, in network
+    /// byte order for LOOP and in the capturing host's order for NULL, then an
+    /// IPv4 or IPv6 packet
+    fn parse_loopback(&self, data: &[u8], packet: &mut Packet, network_order: bool) -> Result<()> {
+        let Some(header) = data.first_chunk::<LOOPBACK_HEADER_LEN>() else {
+            return Err(Truncated("Loopback").into());
+        };
+        packet.protocol = "Loopback".to_string();
+        
+        // Families are small numbers, so the order that gives one is the host's
+        let family = match (network_order, u32::from_le_bytes(*header)) {
+            (false, family) if family <= 0xffff => family,
+            _ => u32::from_be_bytes(*header),
+        };
+        packet.headers = json!({ "loopback": { "family": family } });
+        
+        let ip = &data[LOOPBACK_HEADER_LEN..];
+        match family {
+            2 => self.parse_ipv4(ip, packet),
+            // AF_INET6 differs between Linux, the BSDs and macOS
+            10 | 24 | 28 | 30 => self.parse_ipv6(ip, packet),
+            _ => Err(anyhow!("Unsupported loopback address family {}", family)),
+        }
+    }
+    
+    /// Parse an 802.11 frame, and the network layer of data frames that carry one
+    fn parse_wifi(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        if let Some((ethertype, payload)) = wifi::parse(data, packet)? {
+            self.parse_ethertype(EtherType(ethertype), payload, packet)?;
+        }
+        Ok(())
+    }
+    
+    /// Parse an IP packet without a link-layer header, as tun devices deliver them
+    fn parse_raw_ip(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        match data.first().map(|byte| byte >> 4) {
+            Some(4) => self.parse_ipv4(data, packet),
+            Some(6) => self.parse_ipv6(data, packet),
+            Some(version) => Err(anyhow!("Not an IP packet (version {})", version)),
+            None => Err(Truncated("IP").into()),
+        }
+    }
+    
+    /// Parse the payload of a link-layer frame based on its EtherType
+    fn parse_ethertype(&self, ethertype: EtherType, payload: &[u8], packet: &mut Packet) -> Result<()> {
         // Process based on EtherType
         match ethertype {
             EtherTypes::Ipv4 => {
-                self.parse_ipv4(eth_packet.payload(), &mut packet)?;
+                self.parse_ipv4(payload, packet)?;
             },
             EtherTypes::Ipv6 => {
-                self.parse_ipv6(eth_packet.payload(), &mut packet)?;
+                self.parse_ipv6(payload, packet)?;
             },
             EtherTypes::Arp => {
-                self.parse_arp(eth_packet.payload(), &mut packet)?;
+                self.parse_arp(payload, packet)?;
+            },
+            EtherTypes::PppoeDiscovery | EtherTypes::PppoeSession => {
+                self.parse_pppoe(ethertype == EtherTypes::PppoeSession, payload, packet)?;
+            },
+            EtherTypes::Lldp if self.decodes("LLDP") => {
+                packet.payload = Some(payload.to_vec());
+                if let Err(e) = lldp::parse(payload, packet) {
+                    trace!("Not decoding as LLDP: {}", e);
+                    packet.protocol = format!("Other ({:?})", ethertype);
+                }
+            },
+            _ if ethertype.0 == wol::WOL_ETHERTYPE && self.decodes("WOL") => {
+                packet.payload = Some(payload.to_vec());
+                if let Err(e) = wol::parse(payload, packet) {
+                    trace!("Not decoding as WOL: {}", e);
+                    packet.protocol = format!("Other ({:?})", ethertype);
+                }
+            },
+            // An 802.3 length field; CDP is carried in LLC/SNAP
+            _ if ethertype.0 <= 1500 && self.decodes("CDP") && payload.starts_with(&cdp::CDP_SNAP_HEADER) => {
+                let payload = &payload[cdp::CDP_SNAP_HEADER.len()..];
+                packet.payload = Some(payload.to_vec());
+                if let Err(e) = cdp::parse(payload, packet) {
+                    trace!("Not decoding as CDP: {}", e);
+                    packet.protocol = format!("Other ({:?})", ethertype);
+                }
             },
             _ => {
                 // Other protocols can be added here
                 packet.protocol = format!("Other ({:?})", ethertype);
-                packet.payload = Some(eth_packet.payload().to_vec());
+                packet.payload = Some(payload.to_vec());
             }
         }
         
-        Ok(packet)
+        Ok(())
     }
     
     /// Parse IPv4 packet
     fn parse_ipv4(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let ipv4_packet = match Ipv4Packet::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse IPv4 packet")),
+            None => return Err(Truncated("IPv4").into()),
         };
+        packet.protocol = "IPv4".to_string();
         
         // Set IP addresses
         packet.source_ip = Some(IpAddr::V4(ipv4_packet.get_source()));
@@ -118,6 +412,14 @@ impl PacketParser {
                    ipv4_packet.get_next_level_protocol());
         }
         
+        // Options sit between the fixed 20-byte header and the declared header length
+        let header_len = (ipv4_packet.get_header_length() as usize * 4).min(data.len());
+        let options = if header_len > 20 {
+            self.parse_ipv4_options(&data[20..header_len])
+        } else {
+            Vec::new()
+        };
+        
         // Update headers in JSON
         let mut headers = serde_json::from_value(packet.headers.clone()).unwrap_or_else(|_| serde_json::Map::new());
         headers.insert("ipv4".to_string(), json!({
@@ -129,6 +431,7 @@ impl PacketParser {
             "checksum": ipv4_packet.get_checksum(),
             "source_ip": packet.source_ip,
             "destination_ip": packet.destination_ip,
+            "options": options,
         }));
         packet.headers = serde_json::Value::Object(headers);
         
@@ -138,12 +441,81 @@ impl PacketParser {
         Ok(())
     }
     
+    /// Parse the IPv4 options area into a list of option objects
+    fn parse_ipv4_options(&self, data: &[u8]) -> Vec<Value> {
+        let mut options = Vec::new();
+        let mut offset = 0;
+        
+        while offset < data.len() {
+            let option_type = data[offset];
+            let mut option = json!({
+                "type": option_type,
+                "name": Self::ipv4_option_name(option_type),
+                "copied": option_type & 0x80 != 0,
+                "class": (option_type >> 5) & 0x03,
+                "number": option_type & 0x1f,
+            });
+            
+            // End of Option List and No-Operation are single bytes
+            if option_type == 0 || option_type == 1 {
+                options.push(option);
+                if option_type == 0 {
+                    break;
+                }
+                offset += 1;
+                continue;
+            }
+            
+            // Every other option carries a length covering the type and length bytes
+            let length = data.get(offset + 1).copied().unwrap_or(0) as usize;
+            if length < 2 || offset + length > data.len() {
+                option["malformed"] = json!(true);
+                option["length"] = json!(length);
+                options.push(option);
+                break;
+            }
+            
+            let value = &data[offset + 2..offset + length];
+            option["length"] = json!(length);
+            option["data"] = json!(value.iter().map(|b| format!("{:02x}", b)).collect::<String>());
+            
+            // Router Alert (RFC 2113) carries a 16-bit value; 0 means "examine packet"
+            if option_type == 148 && value.len() == 2 {
+                option["router_alert"] = json!(u16::from_be_bytes([value[0], value[1]]));
+            }
+            
+            options.push(option);
+            offset += length;
+        }
+        
+        options
+    }
+    
+    /// Name of a well-known IPv4 option type
+    fn ipv4_option_name(option_type: u8) -> &'static str {
+        match option_type {
+            0 => "End of Option List",
+            1 => "No-Operation",
+            7 => "Record Route",
+            68 => "Timestamp",
+            82 => "Traceroute",
+            130 => "Security",
+            131 => "Loose Source Route",
+            133 => "Extended Security",
+            136 => "Stream ID",
+            137 => "Strict Source Route",
+            148 => "Router Alert",
+            _ => "Unknown",
+        }
+    }
+    
     /// Parse IPv6 packet
     fn parse_ipv6(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let ipv6_packet = match Ipv6Packet::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse IPv6 packet")),
+            None => return Err(Truncated("IPv6").into()),
         };
+        packet.protocol = "IPv6".to_string();
         
         // Set IP addresses
         packet.source_ip = Some(IpAddr::V6(ipv6_packet.get_source()));
@@ -177,7 +549,7 @@ impl PacketParser {
     fn parse_arp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let arp_packet = match ArpPacket::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse ARP packet")),
+            None => return Err(Truncated("ARP").into()),
         };
         
         packet.protocol = "ARP".to_string();
@@ -208,6 +580,98 @@ impl PacketParser {
         Ok(())
     }
     
+    /// Parse a PPPoE discovery or session frame, handing IP payloads to the IP parsers
+    fn parse_pppoe(&self, session: bool, data: &[u8], packet: &mut Packet) -> Result<()> {
+        if data.len() < PPPOE_HEADER_LEN {
+            return Err(Truncated("PPPoE").into());
+        }
+        
+        let code = data[1];
+        let session_id = u16::from_be_bytes([data[2], data[3]]);
+        let length = u16::from_be_bytes([data[4], data[5]]) as usize;
+        let payload = &data[PPPOE_HEADER_LEN..(PPPOE_HEADER_LEN + length).min(data.len())];
+        
+        let mut pppoe = json!({
+            "version": data[0] >> 4,
+            "type": data[0] & 0x0f,
+            "code": code,
+            "code_name": Self::pppoe_code_name(code),
+            "session_id": session_id,
+            "length": length,
+        });
+        
+        packet.protocol = "PPPoE".to_string();
+        
+        if !session {
+            // Discovery: tag type, tag length, value
+            let mut offset = 0;
+            while offset + 4 <= payload.len() {
+                let tag = u16::from_be_bytes([payload[offset], payload[offset + 1]]);
+                let tag_len = u16::from_be_bytes([payload[offset + 2], payload[offset + 3]]) as usize;
+                let Some(value) = payload.get(offset + 4..offset + 4 + tag_len) else { break };
+                match tag {
+                    0x0101 => pppoe["service_name"] = json!(String::from_utf8_lossy(value)),
+                    0x0102 => pppoe["ac_name"] = json!(String::from_utf8_lossy(value)),
+                    _ => {},
+                }
+                offset += 4 + tag_len;
+            }
+            
+            packet.payload = Some(payload.to_vec());
+            if let Value::Object(ref mut obj) = packet.headers {
+                obj.insert("pppoe".to_string(), pppoe);
+            }
+            return Ok(());
+        }
+        
+        // Session: a PPP protocol field, then the PPP payload
+        if payload.len() < 2 {
+            return Err(Truncated("PPP").into());
+        }
+        let ppp_protocol = u16::from_be_bytes([payload[0], payload[1]]);
+        pppoe["ppp_protocol"] = json!(ppp_protocol);
+        pppoe["ppp_protocol_name"] = json!(Self::ppp_protocol_name(ppp_protocol));
+        if let Value::Object(ref mut obj) = packet.headers {
+            obj.insert("pppoe".to_string(), pppoe);
+        }
+        
+        match ppp_protocol {
+            0x0021 => self.parse_ipv4(&payload[2..], packet),
+            0x0057 => self.parse_ipv6(&payload[2..], packet),
+            _ => {
+                packet.payload = Some(payload[2..].to_vec());
+                Ok(())
+            }
+        }
+    }
+    
+    /// Name of a PPPoE discovery code
+    fn pppoe_code_name(code: u8) -> &'static str {
+        match code {
+            0x00 => "Session Data",
+            0x09 => "PADI",
+            0x07 => "PADO",
+            0x19 => "PADR",
+            0x65 => "PADS",
+            0xa7 => "PADT",
+            _ => "Unknown",
+        }
+    }
+    
+    /// Name of a PPP protocol number
+    fn ppp_protocol_name(protocol: u16) -> &'static str {
+        match protocol {
+            0x0021 => "IPv4",
+            0x0057 => "IPv6",
+            0x8021 => "IPCP",
+            0x8057 => "IPV6CP",
+            0xc021 => "LCP",
+            0xc023 => "PAP",
+            0xc223 => "CHAP",
+            _ => "Unknown",
+        }
+    }
+    
     /// Parse transport layer protocols
     fn parse_transport_protocol(&self, proto: IpNextHeaderProtocol, data: &[u8], packet: &mut Packet) -> Result<()> {
         if log_enabled!(Level::Trace) {
@@ -224,6 +688,16 @@ impl PacketParser {
             IpNextHeaderProtocols::Icmp => {
                 self.parse_icmp(data, packet)?;
             },
+            IpNextHeaderProtocols::Sctp => {
+                self.parse_sctp(data, packet)?;
+            },
+            IpNextHeaderProtocols::OspfigP if self.decodes("OSPF") => {
+                packet.payload = Some(data.to_vec());
+                if let Err(e) = ospf::parse(data, packet) {
+                    trace!("Not decoding as OSPF: {}", e);
+                    packet.protocol = format!("IP({:?})", proto);
+                }
+            },
             _ => {
                 packet.protocol = format!("IP({:?})", proto);
                 packet.payload = Some(data.to_vec());
@@ -237,7 +711,7 @@ impl PacketParser {
     fn parse_tcp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let tcp_packet = match TcpPacket::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse TCP packet")),
+            None => return Err(Truncated("TCP").into()),
         };
         
         // Set TCP specific info
@@ -287,6 +761,53 @@ impl PacketParser {
             packet.payload = Some(tcp_packet.payload().to_vec());
         }
         
+        // A decode-as override replaces the port heuristics below
+        if let Some(protocol) = self.decode_as_for(tcp_packet.get_source(), tcp_packet.get_destination()) {
+            self.decode_forced(&protocol, tcp_packet.payload(), packet);
+            return Ok(());
+        }
+        
+        // So does a connection announced on a control connection, such as FTP data
+        if let Some(protocol) = Self::expected_protocol(&self.expected_tcp, packet) {
+            self.decode_forced(protocol, tcp_packet.payload(), packet);
+            return Ok(());
+        }
+        
+        // Detect the FTP control connection (port 21), learning the data connections it announces
+        if self.decodes("FTP") && (tcp_packet.get_source() == ftp::FTP_PORT || tcp_packet.get_destination() == ftp::FTP_PORT) {
+            if let Err(e) = self.parse_ftp(tcp_packet.payload(), packet) {
+                trace!("Not decoding as FTP: {}", e);
+            }
+        }
+        
+        // Detect DNS over TCP (port 53): zone transfers and responses too large for UDP
+        if self.decodes("DNS") && (tcp_packet.get_source() == dns::DNS_PORT || tcp_packet.get_destination() == dns::DNS_PORT) {
+            if let Err(e) = dns::parse_tcp(tcp_packet.payload(), packet) {
+                trace!("Not decoding as DNS: {}", e);
+            }
+        }
+        
+        // Detect Diameter (port 3868)
+        if self.decodes("DIAMETER") && (tcp_packet.get_source() == diameter::DIAMETER_PORT || tcp_packet.get_destination() == diameter::DIAMETER_PORT) {
+            if let Err(e) = diameter::parse(tcp_packet.payload(), packet) {
+                trace!("Not decoding as Diameter: {}", e);
+            }
+        }
+        
+        // Detect the SSH identification exchange and key exchange (port 22)
+        if self.decodes("SSH") && (tcp_packet.get_source() == ssh::SSH_PORT || tcp_packet.get_destination() == ssh::SSH_PORT) {
+            if let Err(e) = ssh::parse(tcp_packet.payload(), packet, ssh::SSH_PORT) {
+                trace!("Not decoding as SSH: {}", e);
+            }
+        }
+        
+        // Detect SIP (port 5060), expecting the RTP its SDP bodies announce
+        if self.decodes("SIP") && (tcp_packet.get_source() == sip::SIP_PORT || tcp_packet.get_destination() == sip::SIP_PORT) {
+            if let Err(e) = self.parse_sip(tcp_packet.payload(), packet) {
+                trace!("Not decoding as SIP: {}", e);
+            }
+        }
+        
         Ok(())
     }
     
@@ -294,7 +815,7 @@ impl PacketParser {
     fn parse_udp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let udp_packet = match UdpPacket::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse UDP packet")),
+            None => return Err(Truncated("UDP").into()),
         };
         
         // Set UDP specific fields
@@ -321,19 +842,240 @@ impl PacketParser {
             packet.payload = Some(udp_packet.payload().to_vec());
         }
         
-        // Detect DNS (ports 53)
-        if udp_packet.get_source() == 53 || udp_packet.get_destination() == 53 {
-            packet.protocol = "DNS".to_string();
+        // A decode-as override replaces the port heuristics below
+        if let Some(protocol) = self.decode_as_for(udp_packet.get_source(), udp_packet.get_destination()) {
+            self.decode_forced(&protocol, udp_packet.payload(), packet);
+            return Ok(());
+        }
+        
+        // So does a conversation an earlier datagram announced, such as a TFTP transfer
+        if let Some(protocol) = Self::expected_protocol(&self.expected_udp, packet) {
+            self.decode_forced(protocol, udp_packet.payload(), packet);
+            return Ok(());
         }
         
+        // Detect DNS (port 53)
+        if self.decodes("DNS") && (udp_packet.get_source() == dns::DNS_PORT || udp_packet.get_destination() == dns::DNS_PORT) {
+            if let Err(e) = dns::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as DNS: {}", e);
+            }
+        }
+        
+        // Detect Wake-on-LAN magic packets (ports 7 and 9)
+        if self.decodes("WOL") && (wol::WOL_PORTS.contains(&udp_packet.get_source()) || wol::WOL_PORTS.contains(&udp_packet.get_destination())) {
+            if let Err(e) = wol::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as WOL: {}", e);
+            }
+        }
+        
+        // Detect NTP (port 123)
+        if self.decodes("NTP") && (udp_packet.get_source() == ntp::NTP_PORT || udp_packet.get_destination() == ntp::NTP_PORT) {
+            if let Err(e) = ntp::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as NTP: {}", e);
+            }
+        }
+        
+        // Detect RADIUS (ports 1812/1813)
+        if self.decodes("RADIUS") && (radius::is_radius_port(udp_packet.get_source()) || radius::is_radius_port(udp_packet.get_destination())) {
+            if let Err(e) = radius::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as RADIUS: {}", e);
+            }
+        }
+        
+        // Detect SNMP (ports 161/162)
+        if self.decodes("SNMP") && (snmp::is_snmp_port(udp_packet.get_source()) || snmp::is_snmp_port(udp_packet.get_destination())) {
+            if let Err(e) = snmp::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as SNMP: {}", e);
+            }
+        }
+        
+        // Detect GTP-U (port 2152)
+        if self.decodes("GTP-U") && (udp_packet.get_source() == gtp::GTPU_PORT || udp_packet.get_destination() == gtp::GTPU_PORT) {
+            if let Err(e) = self.parse_gtp(udp_packet.payload(), packet) {
+                trace!("Not decoding as GTP-U: {}", e);
+            }
+        }
+        
+        // Detect SIP (port 5060), expecting the RTP its SDP bodies announce
+        if self.decodes("SIP") && (udp_packet.get_source() == sip::SIP_PORT || udp_packet.get_destination() == sip::SIP_PORT) {
+            if let Err(e) = self.parse_sip(udp_packet.payload(), packet) {
+                trace!("Not decoding as SIP: {}", e);
+            }
+        }
+        
+        // Detect TFTP requests (port 69); the transfers they start on other ports are expected above
+        if self.decodes("TFTP") && (udp_packet.get_source() == tftp::TFTP_PORT || udp_packet.get_destination() == tftp::TFTP_PORT) {
+            if let Err(e) = self.parse_tftp(udp_packet.payload(), packet) {
+                trace!("Not decoding as TFTP: {}", e);
+            }
+        }
+        
+        // Try RTP between two even ports, last as it has no port of its own
+        if self.decodes("RTP") && packet.protocol == "UDP" && rtp::is_heuristic_port_pair(udp_packet.get_source(), udp_packet.get_destination()) {
+            if let Err(e) = rtp::parse(udp_packet.payload(), packet) {
+                trace!("Not decoding as RTP: {}", e);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Parse an FTP control message, expecting the data connection it announces
+    /// on the sender's host from the receiver's
+    fn parse_ftp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        if let Some(data_port) = ftp::parse(data, packet)? {
+            if let (Some(announcer_ip), Some(peer_ip)) = (packet.source_ip, packet.destination_ip) {
+                self.expected_tcp.expect((announcer_ip, data_port), peer_ip, "FTP-DATA");
+            }
+        }
+        Ok(())
+    }
+    
+    /// Parse a SIP message, expecting RTP from anywhere on the media endpoints
+    /// its SDP body announces
+    fn parse_sip(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        for (address, port) in sip::parse(data, packet)? {
+            match address.parse() {
+                Ok(address) => self.expected_udp.expect_from_any((address, port), "RTP"),
+                Err(_) => trace!("Not expecting RTP on unresolved SDP address {}", address),
+            }
+        }
         Ok(())
     }
     
+    /// Parse a TFTP message, expecting the transfer a request starts and
+    /// recording the block size an OACK agrees on for it
+    fn parse_tftp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        let (Some(source_ip), Some(source_port), Some(destination_ip), Some(destination_port)) =
+            (packet.source_ip, packet.source_port, packet.destination_ip, packet.destination_port)
+        else {
+            return tftp::parse(data, packet, tftp::DEFAULT_BLOCK_SIZE).map(|_| ());
+        };
+        let (source, destination) = ((source_ip, source_port), (destination_ip, destination_port));
+        
+        let block_size = self.expected_udp.block_size(source, destination).unwrap_or(tftp::DEFAULT_BLOCK_SIZE);
+        let (opcode, agreed_block_size) = tftp::parse(data, packet, block_size)?;
+        if tftp::is_request(opcode) {
+            self.expected_udp.expect(source, destination_ip, "TFTP");
+        } else if let Some(agreed_block_size) = agreed_block_size {
+            self.expected_udp.set_block_size(source, destination, agreed_block_size);
+        }
+        Ok(())
+    }
+    
+    /// Parse a GTP-U message and the packet a G-PDU tunnels, whose layers
+    /// replace the outer ones (the tunnel endpoints are kept under `gtp`)
+    fn parse_gtp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        let Some(inner) = gtp::parse(data, packet)? else { return Ok(()) };
+        
+        packet.payload = Some(inner.to_vec());
+        if let Err(e) = self.parse_raw_ip(inner, packet) {
+            trace!("Not decoding the packet tunneled in GTP-U: {}", e);
+        }
+        Ok(())
+    }
+    
+    /// Parse SCTP packet: the common header and the chunk headers
+    fn parse_sctp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
+        if data.len() < SCTP_HEADER_LEN {
+            return Err(Truncated("SCTP").into());
+        }
+        
+        let source_port = u16::from_be_bytes([data[0], data[1]]);
+        let destination_port = u16::from_be_bytes([data[2], data[3]]);
+        
+        packet.protocol = "SCTP".to_string();
+        packet.source_port = Some(source_port);
+        packet.destination_port = Some(destination_port);
+        
+        // Walk the chunks, remembering the first DATA chunk's user data
+        let mut chunks = Vec::new();
+        let mut user_data: Option<(u32, &[u8])> = None;
+        let mut offset = SCTP_HEADER_LEN;
+        
+        while offset + 4 <= data.len() {
+            let chunk_type = data[offset];
+            let chunk_len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+            if chunk_len < 4 || offset + chunk_len > data.len() {
+                break;
+            }
+            
+            // DATA: TSN, stream id, stream sequence and payload protocol id before the user data
+            if chunk_type == 0 && chunk_len > 16 && user_data.is_none() {
+                let ppid = u32::from_be_bytes([data[offset + 12], data[offset + 13], data[offset + 14], data[offset + 15]]);
+                user_data = Some((ppid, &data[offset + 16..offset + chunk_len]));
+            }
+            
+            chunks.push(json!({
+                "type": chunk_type,
+                "type_name": Self::sctp_chunk_name(chunk_type),
+                "flags": data[offset + 1],
+                "length": chunk_len,
+            }));
+            
+            // Chunks are padded to a multiple of four bytes
+            offset += (chunk_len + 3) & !3;
+        }
+        
+        let mut headers = packet.headers.clone();
+        let sctp_json = json!({
+            "source_port": source_port,
+            "destination_port": destination_port,
+            "verification_tag": u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+            "checksum": u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+            "chunks": chunks,
+        });
+        
+        if let Value::Object(ref mut obj) = headers {
+            obj.insert("sctp".to_string(), sctp_json);
+            packet.headers = Value::Object(obj.clone());
+        }
+        
+        let Some((ppid, payload)) = user_data else { return Ok(()) };
+        packet.payload = Some(payload.to_vec());
+        
+        // A decode-as override replaces the port heuristics below
+        if let Some(protocol) = self.decode_as_for(source_port, destination_port) {
+            self.decode_forced(&protocol, payload, packet);
+            return Ok(());
+        }
+        
+        // Detect Diameter (port 3868 or its payload protocol id)
+        let diameter_port = source_port == diameter::DIAMETER_PORT || destination_port == diameter::DIAMETER_PORT;
+        if self.decodes("DIAMETER") && (diameter_port || ppid == diameter::DIAMETER_PPID) {
+            if let Err(e) = diameter::parse(payload, packet) {
+                trace!("Not decoding as Diameter: {}", e);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Name of an SCTP chunk type
+    fn sctp_chunk_name(chunk_type: u8) -> &'static str {
+        match chunk_type {
+            0 => "DATA",
+            1 => "INIT",
+            2 => "INIT_ACK",
+            3 => "SACK",
+            4 => "HEARTBEAT",
+            5 => "HEARTBEAT_ACK",
+            6 => "ABORT",
+            7 => "SHUTDOWN",
+            8 => "SHUTDOWN_ACK",
+            9 => "ERROR",
+            10 => "COOKIE_ECHO",
+            11 => "COOKIE_ACK",
+            14 => "SHUTDOWN_COMPLETE",
+            _ => "Unknown",
+        }
+    }
+    
     /// Parse ICMP packet
     fn parse_icmp(&self, data: &[u8], packet: &mut Packet) -> Result<()> {
         let icmp_packet = match IcmpPacket::new(data) {
             Some(packet) => packet,
-            None => return Err(anyhow!("Failed to parse ICMP packet")),
+            None => return Err(Truncated("ICMP").into()),
         };
         
         // Set ICMP specific fields
@@ -341,12 +1083,22 @@ impl PacketParser {
         
         // Add ICMP header to JSON
         let mut headers = packet.headers.clone();
-        let icmp_json = json!({
+        let mut icmp_json = json!({
             "icmp_type": icmp_packet.get_icmp_type().0,
             "icmp_code": icmp_packet.get_icmp_code().0,
             "checksum": icmp_packet.get_checksum(),
         });
         
+        // Fragmentation needed: the next-hop MTU follows two unused bytes, then
+        // the start of the datagram that was too big
+        let payload = icmp_packet.payload();
+        if icmp_packet.get_icmp_type().0 == ICMP_DEST_UNREACHABLE && icmp_packet.get_icmp_code().0 == ICMP_FRAG_NEEDED && payload.len() >= 4 {
+            icmp_json["next_hop_mtu"] = json!(u16::from_be_bytes([payload[2], payload[3]]));
+            if let Some(original) = Ipv4Packet::new(&payload[4..]) {
+                icmp_json["original_destination"] = json!(original.get_destination().to_string());
+            }
+        }
+        
         if let Value::Object(ref mut obj) = headers {
             obj.insert("icmp".to_string(), icmp_json);
             packet.headers = Value::Object(obj.clone());
@@ -0,0 +1,123 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+use std::net::Ipv4Addr;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// LLC/SNAP header of CDP frames: DSAP, SSAP, control, Cisco OUI and protocol ID 0x2000
+pub const CDP_SNAP_HEADER: [u8; 8] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x0c, 0x20, 0x00];
+
+/// Version, TTL and checksum
+const CDP_HEADER_LEN: usize = 4;
+
+/// NLPID protocol type and value identifying an IPv4 address
+const PROTOCOL_TYPE_NLPID: u8 = 1;
+const NLPID_IPV4: u8 = 0xcc;
+
+/// Decode a CDP message (after the SNAP header) into `packet.headers["cdp"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < CDP_HEADER_LEN {
+        return Err(anyhow!("CDP message too short: {} bytes", data.len()));
+    }
+    
+    let version = data[0];
+    let ttl = data[1];
+    
+    let mut fields = Map::new();
+    fields.insert("version".to_string(), json!(version));
+    fields.insert("ttl".to_string(), json!(ttl));
+    
+    // Walk the TLVs: 16-bit type, 16-bit length including the header
+    let mut offset = CDP_HEADER_LEN;
+    while offset + 4 <= data.len() {
+        let tlv_type = u16::from_be_bytes([data[offset], data[offset + 1]]);
+        let tlv_len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if tlv_len < 4 || offset + tlv_len > data.len() {
+            break;
+        }
+        let value = &data[offset + 4..offset + tlv_len];
+        offset += tlv_len;
+        
+        match tlv_type {
+            1 => { fields.insert("device_id".to_string(), json!(text(value))); },
+            2 => { fields.insert("addresses".to_string(), json!(addresses(value))); },
+            3 => { fields.insert("port_id".to_string(), json!(text(value))); },
+            4 if value.len() >= 4 => {
+                let bits = u32::from_be_bytes([value[0], value[1], value[2], value[3]]);
+                fields.insert("capabilities".to_string(), json!(capability_names(bits)));
+            },
+            5 => { fields.insert("software_version".to_string(), json!(text(value))); },
+            6 => { fields.insert("platform".to_string(), json!(text(value))); },
+            10 if value.len() >= 2 => {
+                fields.insert("native_vlan".to_string(), json!(u16::from_be_bytes([value[0], value[1]])));
+            },
+            _ => {},
+        }
+    }
+    
+    if !fields.contains_key("device_id") {
+        return Err(anyhow!("CDP message without a device ID"));
+    }
+    
+    packet.protocol = "CDP".to_string();
+    insert_header(packet, "cdp", Value::Object(fields));
+    
+    Ok(())
+}
+
+/// Decode the IPv4 entries of an Addresses TLV
+fn addresses(value: &[u8]) -> Vec<String> {
+    let mut result = Vec::new();
+    if value.len() < 4 {
+        return result;
+    }
+    
+    let count = u32::from_be_bytes([value[0], value[1], value[2], value[3]]);
+    let mut offset = 4;
+    
+    for _ in 0..count {
+        // Protocol type, protocol length, protocol, address length, address
+        if offset + 2 > value.len() {
+            break;
+        }
+        let protocol_type = value[offset];
+        let protocol_len = value[offset + 1] as usize;
+        let protocol_end = offset + 2 + protocol_len;
+        if protocol_end + 2 > value.len() {
+            break;
+        }
+        let address_len = u16::from_be_bytes([value[protocol_end], value[protocol_end + 1]]) as usize;
+        let address_start = protocol_end + 2;
+        if address_start + address_len > value.len() {
+            break;
+        }
+        
+        let protocol = &value[offset + 2..protocol_end];
+        let address = &value[address_start..address_start + address_len];
+        if protocol_type == PROTOCOL_TYPE_NLPID && protocol == [NLPID_IPV4] && address.len() == 4 {
+            result.push(Ipv4Addr::new(address[0], address[1], address[2], address[3]).to_string());
+        }
+        
+        offset = address_start + address_len;
+    }
+    
+    result
+}
+
+/// Render a TLV string, replacing invalid UTF-8
+fn text(value: &[u8]) -> String {
+    String::from_utf8_lossy(value).trim_end_matches('\0').to_string()
+}
+
+/// Names of the set bits of a CDP capabilities field
+fn capability_names(bits: u32) -> Vec<&'static str> {
+    const NAMES: [&str; 7] = ["router", "transparent_bridge", "source_route_bridge", "switch", "host", "igmp", "repeater"];
+    
+    NAMES
+        .iter()
+        .enumerate()
+        .filter(|&(bit, _)| bits & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
@@ -0,0 +1,170 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Diameter runs on TCP and SCTP port 3868
+pub const DIAMETER_PORT: u16 = 3868;
+
+/// SCTP payload protocol identifier for Diameter
+pub const DIAMETER_PPID: u32 = 46;
+
+/// Version, length, flags, command code, application id and the two ids
+const DIAMETER_HEADER_LEN: usize = 20;
+
+/// AVP code, flags and length (without the optional vendor id)
+const AVP_HEADER_LEN: usize = 8;
+
+/// AVP flag marking a vendor-specific AVP (adds a vendor id to the header)
+const AVP_FLAG_VENDOR: u8 = 0x80;
+
+/// Decode a Diameter message into `packet.headers["diameter"]`.
+///
+/// Only the AVP headers are decoded. A message split across segments is
+/// decoded as far as it was captured and marked as truncated.
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < DIAMETER_HEADER_LEN {
+        return Err(anyhow!("Diameter message too short: {} bytes", data.len()));
+    }
+    
+    let version = data[0];
+    if version != 1 {
+        return Err(anyhow!("Unsupported Diameter version {}", version));
+    }
+    
+    let length = read_u24(data, 1) as usize;
+    if length < DIAMETER_HEADER_LEN || !length.is_multiple_of(4) {
+        return Err(anyhow!("Invalid Diameter length {}", length));
+    }
+    
+    let flags = data[4];
+    let command_code = read_u24(data, 5);
+    let application_id = read_u32(data, 8);
+    let hop_by_hop_id = read_u32(data, 12);
+    let end_to_end_id = read_u32(data, 16);
+    let request = flags & 0x80 != 0;
+    
+    // Walk the AVPs, bounded by the declared length and the captured bytes
+    let end = length.min(data.len());
+    let mut avps = Vec::new();
+    let mut offset = DIAMETER_HEADER_LEN;
+    
+    while offset + AVP_HEADER_LEN <= end {
+        let code = read_u32(data, offset);
+        let avp_flags = data[offset + 4];
+        let avp_len = read_u24(data, offset + 5) as usize;
+        let vendor = avp_flags & AVP_FLAG_VENDOR != 0;
+        let header_len = if vendor { AVP_HEADER_LEN + 4 } else { AVP_HEADER_LEN };
+        
+        if avp_len < header_len || offset + header_len > end {
+            break;
+        }
+        
+        avps.push(json!({
+            "code": code,
+            "name": avp_name(code),
+            "flags": {
+                "vendor": vendor,
+                "mandatory": avp_flags & 0x40 != 0,
+                "protected": avp_flags & 0x20 != 0,
+            },
+            "length": avp_len,
+            "vendor_id": if vendor { json!(read_u32(data, offset + 8)) } else { Value::Null },
+        }));
+        
+        // AVPs are padded to a multiple of four bytes
+        offset += (avp_len + 3) & !3;
+    }
+    
+    packet.protocol = "Diameter".to_string();
+    
+    insert_header(packet, "diameter", json!({
+        "version": version,
+        "length": length,
+        "flags": {
+            "request": request,
+            "proxiable": flags & 0x40 != 0,
+            "error": flags & 0x20 != 0,
+            "retransmitted": flags & 0x10 != 0,
+        },
+        "command_code": command_code,
+        "command_name": command_name(command_code, request),
+        "application_id": application_id,
+        "hop_by_hop_id": hop_by_hop_id,
+        "end_to_end_id": end_to_end_id,
+        "avps": avps,
+        "truncated": data.len() < length,
+    }));
+    
+    Ok(())
+}
+
+/// Human-readable name for a base protocol command code
+pub fn command_name(code: u32, request: bool) -> &'static str {
+    match (code, request) {
+        (257, true) => "Capabilities-Exchange-Request",
+        (257, false) => "Capabilities-Exchange-Answer",
+        (258, true) => "Re-Auth-Request",
+        (258, false) => "Re-Auth-Answer",
+        (271, true) => "Accounting-Request",
+        (271, false) => "Accounting-Answer",
+        (272, true) => "Credit-Control-Request",
+        (272, false) => "Credit-Control-Answer",
+        (274, true) => "Abort-Session-Request",
+        (274, false) => "Abort-Session-Answer",
+        (275, true) => "Session-Termination-Request",
+        (275, false) => "Session-Termination-Answer",
+        (280, true) => "Device-Watchdog-Request",
+        (280, false) => "Device-Watchdog-Answer",
+        (282, true) => "Disconnect-Peer-Request",
+        (282, false) => "Disconnect-Peer-Answer",
+        (316, true) => "Update-Location-Request",
+        (316, false) => "Update-Location-Answer",
+        (318, true) => "Authentication-Information-Request",
+        (318, false) => "Authentication-Information-Answer",
+        _ => "Unknown",
+    }
+}
+
+/// Name of a base protocol AVP code
+fn avp_name(code: u32) -> Option<&'static str> {
+    let name = match code {
+        1 => "User-Name",
+        25 => "Class",
+        27 => "Session-Timeout",
+        257 => "Host-IP-Address",
+        258 => "Auth-Application-Id",
+        259 => "Acct-Application-Id",
+        260 => "Vendor-Specific-Application-Id",
+        263 => "Session-Id",
+        264 => "Origin-Host",
+        265 => "Supported-Vendor-Id",
+        266 => "Vendor-Id",
+        267 => "Firmware-Revision",
+        268 => "Result-Code",
+        269 => "Product-Name",
+        278 => "Origin-State-Id",
+        279 => "Failed-AVP",
+        281 => "Error-Message",
+        283 => "Destination-Realm",
+        293 => "Destination-Host",
+        296 => "Origin-Realm",
+        297 => "Experimental-Result",
+        298 => "Experimental-Result-Code",
+        415 => "CC-Request-Number",
+        416 => "CC-Request-Type",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Read a big-endian 24-bit value
+fn read_u24(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([0, data[offset], data[offset + 1], data[offset + 2]])
+}
+
+/// Read a big-endian 32-bit value
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
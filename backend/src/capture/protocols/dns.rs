@@ -0,0 +1,198 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Well-known DNS port, over UDP and TCP
+pub const DNS_PORT: u16 = 53;
+
+/// Header: ID, flags and the four section counts
+const DNS_HEADER_LEN: usize = 12;
+
+/// Zone transfer query types, only ever sent over TCP
+const QTYPE_IXFR: u16 = 251;
+const QTYPE_AXFR: u16 = 252;
+
+/// Compression pointers followed before a name is considered malformed
+const MAX_POINTERS: usize = 16;
+
+/// Decode a DNS message carried in a UDP datagram into `packet.headers["dns"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    let fields = message(data, "udp")?;
+    
+    packet.protocol = "DNS".to_string();
+    insert_header(packet, "dns", Value::Object(fields));
+    
+    Ok(())
+}
+
+/// Decode DNS over TCP, where each message is preceded by a 2-byte length.
+///
+/// A segment that does not start a message (the rest of a large response or
+/// zone transfer) is still labelled DNS, with only the transport recorded.
+pub fn parse_tcp(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.is_empty() {
+        return Err(anyhow!("Empty TCP segment"));
+    }
+    
+    let fields = tcp_message(data).unwrap_or_else(continuation);
+    
+    packet.protocol = "DNS".to_string();
+    insert_header(packet, "dns", Value::Object(fields));
+    
+    Ok(())
+}
+
+/// Decode the length-prefixed message a TCP segment starts with
+fn tcp_message(data: &[u8]) -> Option<Map<String, Value>> {
+    if data.len() < 2 {
+        return None;
+    }
+    let declared = u16::from_be_bytes([data[0], data[1]]) as usize;
+    if declared < DNS_HEADER_LEN {
+        return None;
+    }
+    
+    let end = (2 + declared).min(data.len());
+    let mut fields = message(&data[2..end], "tcp").ok()?;
+    
+    // A continuation segment can pass for a header; real messages carry at most one question
+    if fields["questions"].as_u64().is_none_or(|count| count > 1) {
+        return None;
+    }
+    fields.insert("message_length".to_string(), json!(declared));
+    fields.insert("segmented".to_string(), json!(2 + declared > data.len()));
+    Some(fields)
+}
+
+/// Fields of a TCP segment continuing an earlier message
+fn continuation() -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("transport".to_string(), json!("tcp"));
+    fields.insert("continuation".to_string(), json!(true));
+    fields
+}
+
+/// Decode the header and the first question of a DNS message
+fn message(data: &[u8], transport: &str) -> Result<Map<String, Value>> {
+    if data.len() < DNS_HEADER_LEN {
+        return Err(anyhow!("DNS message too short: {} bytes", data.len()));
+    }
+    
+    let flags = u16::from_be_bytes([data[2], data[3]]);
+    let opcode = ((flags >> 11) & 0x0f) as u8;
+    let rcode = (flags & 0x0f) as u8;
+    if opcode > 6 {
+        return Err(anyhow!("Invalid DNS opcode {}", opcode));
+    }
+    let question_count = u16::from_be_bytes([data[4], data[5]]);
+    
+    let mut fields = Map::new();
+    fields.insert("transport".to_string(), json!(transport));
+    fields.insert("id".to_string(), json!(u16::from_be_bytes([data[0], data[1]])));
+    fields.insert("is_response".to_string(), json!(flags & 0x8000 != 0));
+    fields.insert("opcode".to_string(), json!(opcode));
+    fields.insert("rcode".to_string(), json!(rcode));
+    fields.insert("rcode_name".to_string(), json!(rcode_name(rcode)));
+    fields.insert("flags".to_string(), json!({
+        "authoritative": flags & 0x0400 != 0,
+        "truncated": flags & 0x0200 != 0,
+        "recursion_desired": flags & 0x0100 != 0,
+        "recursion_available": flags & 0x0080 != 0,
+    }));
+    fields.insert("questions".to_string(), json!(question_count));
+    fields.insert("answers".to_string(), json!(u16::from_be_bytes([data[6], data[7]])));
+    fields.insert("authorities".to_string(), json!(u16::from_be_bytes([data[8], data[9]])));
+    fields.insert("additionals".to_string(), json!(u16::from_be_bytes([data[10], data[11]])));
+    
+    if question_count > 0 {
+        if let Some((name, offset)) = read_name(data, DNS_HEADER_LEN) {
+            if offset + 4 <= data.len() {
+                let query_type = u16::from_be_bytes([data[offset], data[offset + 1]]);
+                fields.insert("query_name".to_string(), json!(name));
+                fields.insert("query_type".to_string(), json!(query_type));
+                fields.insert("query_type_name".to_string(), json!(type_name(query_type)));
+                if query_type == QTYPE_AXFR || query_type == QTYPE_IXFR {
+                    fields.insert("zone_transfer".to_string(), json!(true));
+                }
+            }
+        }
+    }
+    
+    Ok(fields)
+}
+
+/// Read a possibly compressed domain name, returning it and the offset after it
+fn read_name(data: &[u8], start: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut offset = start;
+    let mut end = None;
+    let mut pointers = 0;
+    
+    loop {
+        let len = *data.get(offset)? as usize;
+        match len {
+            0 => {
+                offset += 1;
+                break;
+            },
+            // Compression pointer: continue at the 14-bit offset
+            l if l & 0xc0 == 0xc0 => {
+                let target = ((l & 0x3f) << 8) | *data.get(offset + 1)? as usize;
+                end.get_or_insert(offset + 2);
+                pointers += 1;
+                if pointers > MAX_POINTERS {
+                    return None;
+                }
+                offset = target;
+            },
+            l if l & 0xc0 != 0 => return None,
+            l => {
+                let label = data.get(offset + 1..offset + 1 + l)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                offset += 1 + l;
+            },
+        }
+    }
+    
+    let name = if labels.is_empty() { ".".to_string() } else { labels.join(".") };
+    Some((name, end.unwrap_or(offset)))
+}
+
+/// Name of a DNS response code
+fn rcode_name(rcode: u8) -> &'static str {
+    match rcode {
+        0 => "NOERROR",
+        1 => "FORMERR",
+        2 => "SERVFAIL",
+        3 => "NXDOMAIN",
+        4 => "NOTIMP",
+        5 => "REFUSED",
+        _ => "Unknown",
+    }
+}
+
+/// Name of a DNS query type
+pub fn type_name(query_type: u16) -> &'static str {
+    match query_type {
+        1 => "A",
+        2 => "NS",
+        5 => "CNAME",
+        6 => "SOA",
+        12 => "PTR",
+        15 => "MX",
+        16 => "TXT",
+        28 => "AAAA",
+        33 => "SRV",
+        41 => "OPT",
+        43 => "DS",
+        46 => "RRSIG",
+        48 => "DNSKEY",
+        65 => "HTTPS",
+        QTYPE_IXFR => "IXFR",
+        QTYPE_AXFR => "AXFR",
+        255 => "ANY",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,103 @@
+// Conversations expected on ports negotiated by another protocol
+//
+// Some protocols agree on an endpoint over a control exchange and then carry
+// their data to it: FTP announces the port of each data connection with
+// PORT/PASV, and a TFTP server answers a request from a fresh port. The
+// decoder of the control exchange registers the endpoint and the address of
+// the peer expected to use it (or any peer, for the RTP a SIP call's SDP
+// announces, as the signalling often passes through proxies), and packets
+// between the two are then decoded as the protocol it names, like a temporary
+// decode-as for that conversation alone (Wireshark's "conversation
+// dissectors"). An expectation also carries what the conversation agrees on
+// that its decoder needs, such as a TFTP transfer's block size. An expectation lapses when unused for `EXPECTATION_TTL`,
+// every packet it matches renewing it, and only the most recent
+// `MAX_EXPECTATIONS` are kept.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// How long an expectation lasts without a packet matching it
+pub const EXPECTATION_TTL: Duration = Duration::from_secs(60);
+
+/// Expectations remembered at once; the oldest is forgotten first
+const MAX_EXPECTATIONS: usize = 256;
+
+/// A conversation expected between an endpoint and a peer address
+#[derive(Debug, Clone, Copy)]
+struct Expectation {
+    endpoint: (IpAddr, u16),
+    peer: Option<IpAddr>,
+    protocol: &'static str,
+    block_size: Option<usize>,
+    expires: Instant,
+}
+
+impl Expectation {
+    fn matches(&self, a: (IpAddr, u16), b: (IpAddr, u16)) -> bool {
+        let from = |peer: IpAddr| self.peer.is_none_or(|expected| expected == peer);
+        (self.endpoint == a && from(b.0)) || (self.endpoint == b && from(a.0))
+    }
+}
+
+/// Conversations learned from control exchanges, with the protocol to decode them as
+#[derive(Debug, Default)]
+pub struct ExpectedConversations {
+    expectations: Mutex<VecDeque<Expectation>>,
+}
+
+impl ExpectedConversations {
+    /// Expect packets of `protocol` between `endpoint` and any port of `peer`
+    pub fn expect(&self, endpoint: (IpAddr, u16), peer: IpAddr, protocol: &'static str) {
+        self.add(endpoint, Some(peer), protocol);
+    }
+    
+    /// Expect packets of `protocol` between `endpoint` and any other
+    pub fn expect_from_any(&self, endpoint: (IpAddr, u16), protocol: &'static str) {
+        self.add(endpoint, None, protocol);
+    }
+    
+    fn add(&self, endpoint: (IpAddr, u16), peer: Option<IpAddr>, protocol: &'static str) {
+        let now = Instant::now();
+        let mut expectations = self.expectations.lock();
+        expectations.retain(|known| known.expires > now && !(known.endpoint == endpoint && known.peer == peer));
+        if expectations.len() == MAX_EXPECTATIONS {
+            expectations.pop_front();
+        }
+        expectations.push_back(Expectation { endpoint, peer, protocol, block_size: None, expires: now + EXPECTATION_TTL });
+    }
+    
+    /// Live expectation matching a packet between two endpoints, the most recent first
+    fn find(expectations: &mut VecDeque<Expectation>, a: (IpAddr, u16), b: (IpAddr, u16), now: Instant) -> Option<&mut Expectation> {
+        expectations
+            .iter_mut()
+            .rev()
+            .find(|expectation| expectation.expires > now && expectation.matches(a, b))
+    }
+    
+    /// Protocol expected for a packet between two endpoints, renewing the expectation it matches
+    pub fn protocol_for(&self, a: (IpAddr, u16), b: (IpAddr, u16)) -> Option<&'static str> {
+        let mut expectations = self.expectations.lock();
+        if expectations.is_empty() {
+            return None;
+        }
+        
+        let now = Instant::now();
+        let expectation = Self::find(&mut expectations, a, b, now)?;
+        expectation.expires = now + EXPECTATION_TTL;
+        Some(expectation.protocol)
+    }
+    
+    /// Block size agreed for the expected conversation between two endpoints, if one was recorded
+    pub fn block_size(&self, a: (IpAddr, u16), b: (IpAddr, u16)) -> Option<usize> {
+        Self::find(&mut self.expectations.lock(), a, b, Instant::now())?.block_size
+    }
+    
+    /// Record the block size the expected conversation between two endpoints agreed on
+    pub fn set_block_size(&self, a: (IpAddr, u16), b: (IpAddr, u16), block_size: usize) {
+        if let Some(expectation) = Self::find(&mut self.expectations.lock(), a, b, Instant::now()) {
+            expectation.block_size = Some(block_size);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// FTP control connection (RFC 959, with the extensions of RFC 2428)
+//
+// Commands and replies are text lines on TCP port 21. Each file transfer or
+// listing runs over a separate data connection to a port announced on the
+// control connection: by the client with PORT or EPRT (active mode), or by
+// the server in its reply to PASV or EPSV (passive mode). The announced port
+// is returned so the parser can expect the data connection and label it
+// FTP-DATA; it is taken to be on the host that announced it, as the address
+// written in the message is often a private one rewritten by NAT.
+
+use anyhow::{Result, anyhow};
+use serde_json::json;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// The control connection runs on TCP port 21
+pub const FTP_PORT: u16 = 21;
+
+/// Lines longer than this are not taken for FTP
+const MAX_LINE_LEN: usize = 512;
+
+/// Decode the first command or reply line of a segment into
+/// `packet.headers["ftp"]`, returning the data port it announces
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<Option<u16>> {
+    let line = first_line(data)?;
+    let mut ftp = json!({});
+    
+    let data_endpoint = if let Some(code) = reply_code(line) {
+        let text = line[3..].strip_prefix(['-', ' ']).unwrap_or_default();
+        ftp["reply_code"] = json!(code);
+        ftp["reply_text"] = json!(text);
+        if line.as_bytes().get(3) == Some(&b'-') {
+            ftp["continues"] = json!(true);
+        }
+        match code {
+            227 => passive_endpoint(text),
+            229 => extended_passive_port(text).map(|port| (None, port)),
+            _ => None,
+        }
+    } else {
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        if !(3..=4).contains(&command.len()) || !command.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(anyhow!("Not an FTP command or reply: '{}'", line));
+        }
+        let command = command.to_ascii_uppercase();
+        let argument = argument.trim();
+        ftp["command"] = json!(command);
+        if !argument.is_empty() {
+            ftp["argument"] = json!(argument);
+        }
+        match command.as_str() {
+            "PORT" => address_and_port(argument),
+            "EPRT" => extended_address_and_port(argument),
+            _ => None,
+        }
+    };
+    
+    if let Some((address, port)) = &data_endpoint {
+        if let Some(address) = address {
+            ftp["data_address"] = json!(address);
+        }
+        ftp["data_port"] = json!(port);
+    }
+    
+    packet.protocol = "FTP".to_string();
+    insert_header(packet, "ftp", ftp);
+    Ok(data_endpoint.map(|(_, port)| port))
+}
+
+/// Label a segment of a data connection, recording how much it carries
+pub fn parse_data(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.is_empty() {
+        return Err(anyhow!("No FTP data in segment"));
+    }
+    
+    packet.protocol = "FTP-DATA".to_string();
+    insert_header(packet, "ftp-data", json!({ "data_length": data.len() }));
+    Ok(())
+}
+
+/// The first line of a segment, which must be printable text
+fn first_line(data: &[u8]) -> Result<&str> {
+    let end = data.iter().position(|&b| b == b'\n').unwrap_or(data.len());
+    let line = std::str::from_utf8(&data[..end])
+        .map_err(|_| anyhow!("FTP line is not text"))?
+        .trim_end_matches('\r');
+    if line.is_empty() || line.len() > MAX_LINE_LEN || line.chars().any(|c| c.is_control()) {
+        return Err(anyhow!("Not an FTP line ({} bytes)", line.len()));
+    }
+    Ok(line)
+}
+
+/// The three-digit code a reply line starts with
+fn reply_code(line: &str) -> Option<u16> {
+    let digits = line.get(..3).filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))?;
+    if !matches!(line.as_bytes().get(3), None | Some(b' ') | Some(b'-')) {
+        return None;
+    }
+    digits.parse().ok().filter(|code| (100..600).contains(code))
+}
+
+/// `h1,h2,h3,h4,p1,p2`, the argument of PORT
+fn address_and_port(argument: &str) -> Option<(Option<String>, u16)> {
+    let numbers: Vec<u8> = argument.split(',').map(|n| n.trim().parse().ok()).collect::<Option<_>>()?;
+    let [h1, h2, h3, h4, p1, p2] = numbers[..] else { return None };
+    Some((Some(format!("{}.{}.{}.{}", h1, h2, h3, h4)), u16::from_be_bytes([p1, p2])))
+}
+
+/// The `h1,h2,h3,h4,p1,p2` somewhere in a 227 reply, usually in parentheses
+fn passive_endpoint(text: &str) -> Option<(Option<String>, u16)> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let numbers = &text[start..];
+    let end = numbers.find(|c: char| !c.is_ascii_digit() && c != ',').unwrap_or(numbers.len());
+    address_and_port(&numbers[..end])
+}
+
+/// `|protocol|address|port|` with any delimiter, the argument of EPRT
+fn extended_address_and_port(argument: &str) -> Option<(Option<String>, u16)> {
+    let delimiter = argument.chars().next()?;
+    let fields: Vec<&str> = argument.split(delimiter).collect();
+    let ["", "1" | "2", address, port, ""] = fields[..] else { return None };
+    Some((Some(address.to_string()), port.parse().ok()?))
+}
+
+/// The port of `(|||port|)` in a 229 reply
+fn extended_passive_port(text: &str) -> Option<u16> {
+    let inner = text.split_once('(')?.1.split_once(')')?.0;
+    let delimiter = inner.chars().next()?;
+    let fields: Vec<&str> = inner.split(delimiter).collect();
+    let ["", "", "", port, ""] = fields[..] else { return None };
+    port.parse().ok()
+}
@@ -0,0 +1,119 @@
+// GTP-U, the user plane of the GPRS Tunnelling Protocol (3GPP TS 29.281)
+//
+// LTE and 5G core networks carry subscriber traffic between base stations and
+// gateways in GTP-U on UDP port 2152, one tunnel per bearer, identified by its
+// TEID. The header is decoded here; the parser then decodes the IP packet a
+// G-PDU carries, whose layers describe the packet from there on while the
+// tunnel endpoints stay in `headers["gtp"]`.
+
+use anyhow::{Result, anyhow};
+use serde_json::json;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// GTP-U runs on UDP port 2152
+pub const GTPU_PORT: u16 = 2152;
+
+/// Metadata key holding the tunnel endpoint identifier, for grouping flows by tunnel
+pub const TEID_KEY: &str = "gtp_teid";
+
+/// Flags, message type, length and TEID
+const GTPU_HEADER_LEN: usize = 8;
+
+/// Sequence number, N-PDU number and next extension header type, present when E, S or PN is set
+const GTPU_OPTIONAL_LEN: usize = 4;
+
+/// Message type of a tunneled user packet
+const G_PDU: u8 = 255;
+
+/// Extension header carrying the 5G QoS flow of the packet
+const PDU_SESSION_CONTAINER: u8 = 0x85;
+
+/// Decode a GTP-U message into `packet.headers["gtp"]`, returning the packet a G-PDU tunnels
+pub fn parse<'a>(data: &'a [u8], packet: &mut Packet) -> Result<Option<&'a [u8]>> {
+    if data.len() < GTPU_HEADER_LEN {
+        return Err(anyhow!("GTP-U message too short: {} bytes", data.len()));
+    }
+    
+    // GTP' (charging) shares the version but clears the protocol type bit
+    let flags = data[0];
+    let version = flags >> 5;
+    if version != 1 || flags & 0x10 == 0 {
+        return Err(anyhow!("Not GTP-U (version {}, protocol type {})", version, (flags >> 4) & 1));
+    }
+    
+    let message_type = data[1];
+    let length = u16::from_be_bytes([data[2], data[3]]) as usize;
+    let teid = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+    let end = (GTPU_HEADER_LEN + length).min(data.len());
+    
+    let mut gtp = json!({
+        "version": version,
+        "message_type": message_type,
+        "message_type_name": message_type_name(message_type),
+        "length": length,
+        "teid": teid,
+        "outer_source_ip": packet.source_ip.map(|ip| ip.to_string()),
+        "outer_destination_ip": packet.destination_ip.map(|ip| ip.to_string()),
+    });
+    
+    let mut offset = GTPU_HEADER_LEN;
+    if flags & 0x07 != 0 {
+        let optional = data
+            .get(offset..offset + GTPU_OPTIONAL_LEN)
+            .ok_or_else(|| anyhow!("GTP-U optional fields truncated"))?;
+        if flags & 0x02 != 0 {
+            gtp["sequence"] = json!(u16::from_be_bytes([optional[0], optional[1]]));
+        }
+        if flags & 0x01 != 0 {
+            gtp["npdu_number"] = json!(optional[2]);
+        }
+        let mut next_type = if flags & 0x04 != 0 { optional[3] } else { 0 };
+        offset += GTPU_OPTIONAL_LEN;
+        
+        // Each extension header: its length in 4-octet units, content, and the next type
+        let mut extensions = Vec::new();
+        while next_type != 0 {
+            let units = data.get(offset).copied().unwrap_or(0) as usize;
+            let extension = data
+                .get(offset..offset + units * 4)
+                .filter(|extension| !extension.is_empty())
+                .ok_or_else(|| anyhow!("GTP-U extension header {:#04x} truncated", next_type))?;
+            
+            extensions.push(next_type);
+            if next_type == PDU_SESSION_CONTAINER && extension.len() >= 3 {
+                gtp["pdu_type"] = json!(extension[1] >> 4);
+                gtp["qfi"] = json!(extension[2] & 0x3f);
+            }
+            
+            next_type = extension[extension.len() - 1];
+            offset += extension.len();
+        }
+        if !extensions.is_empty() {
+            gtp["extension_headers"] = json!(extensions);
+        }
+    }
+    
+    packet.protocol = "GTP-U".to_string();
+    insert_header(packet, "gtp", gtp);
+    if let Some(metadata) = packet.metadata.as_object_mut() {
+        metadata.insert(TEID_KEY.to_string(), json!(teid));
+    }
+    
+    Ok((message_type == G_PDU && offset < end).then(|| &data[offset..end]))
+}
+
+/// Name of a GTP-U message type
+pub fn message_type_name(message_type: u8) -> &'static str {
+    match message_type {
+        1 => "Echo Request",
+        2 => "Echo Response",
+        26 => "Error Indication",
+        31 => "Supported Extension Headers Notification",
+        253 => "Tunnel Status",
+        254 => "End Marker",
+        G_PDU => "G-PDU",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+
+use crate::capture::protocols::{format_address, insert_header};
+use crate::models::packet::Packet;
+
+/// MAC address subtype of the chassis ID and port ID TLVs
+const SUBTYPE_CHASSIS_MAC: u8 = 4;
+const SUBTYPE_PORT_MAC: u8 = 3;
+
+/// Network address subtype of the chassis ID and port ID TLVs
+const SUBTYPE_CHASSIS_ADDRESS: u8 = 5;
+const SUBTYPE_PORT_ADDRESS: u8 = 4;
+
+/// Decode an LLDP data unit into `packet.headers["lldp"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    let mut fields = Map::new();
+    let mut management_addresses = Vec::new();
+    let mut offset = 0;
+    
+    // Walk the TLVs: 7-bit type, 9-bit length, bounded by the frame
+    while offset + 2 <= data.len() {
+        let header = u16::from_be_bytes([data[offset], data[offset + 1]]);
+        let tlv_type = (header >> 9) as u8;
+        let tlv_len = (header & 0x01ff) as usize;
+        offset += 2;
+        
+        if offset + tlv_len > data.len() {
+            return Err(anyhow!("LLDP TLV {} overruns the frame", tlv_type));
+        }
+        let value = &data[offset..offset + tlv_len];
+        offset += tlv_len;
+        
+        match tlv_type {
+            // End of LLDPDU
+            0 => break,
+            1 if !value.is_empty() => {
+                fields.insert("chassis_id_subtype".to_string(), json!(value[0]));
+                fields.insert("chassis_id".to_string(), json!(format_id(value[0], &value[1..], SUBTYPE_CHASSIS_MAC, SUBTYPE_CHASSIS_ADDRESS)));
+            },
+            2 if !value.is_empty() => {
+                fields.insert("port_id_subtype".to_string(), json!(value[0]));
+                fields.insert("port_id".to_string(), json!(format_id(value[0], &value[1..], SUBTYPE_PORT_MAC, SUBTYPE_PORT_ADDRESS)));
+            },
+            3 if value.len() >= 2 => {
+                fields.insert("ttl".to_string(), json!(u16::from_be_bytes([value[0], value[1]])));
+            },
+            4 => {
+                fields.insert("port_description".to_string(), json!(text(value)));
+            },
+            5 => {
+                fields.insert("system_name".to_string(), json!(text(value)));
+            },
+            6 => {
+                fields.insert("system_description".to_string(), json!(text(value)));
+            },
+            7 if value.len() >= 4 => {
+                let supported = u16::from_be_bytes([value[0], value[1]]);
+                let enabled = u16::from_be_bytes([value[2], value[3]]);
+                fields.insert("capabilities".to_string(), json!(capability_names(supported)));
+                fields.insert("enabled_capabilities".to_string(), json!(capability_names(enabled)));
+            },
+            8 if value.len() >= 2 => {
+                // Address string length (including the subtype), subtype, then the address
+                let addr_len = value[0] as usize;
+                if addr_len >= 1 && addr_len < value.len() {
+                    if let Some(address) = format_address(value[1], &value[2..1 + addr_len]) {
+                        management_addresses.push(address);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    
+    if !fields.contains_key("chassis_id") || !fields.contains_key("port_id") {
+        return Err(anyhow!("LLDP frame without chassis ID or port ID"));
+    }
+    
+    fields.insert("management_addresses".to_string(), json!(management_addresses));
+    
+    packet.protocol = "LLDP".to_string();
+    insert_header(packet, "lldp", Value::Object(fields));
+    
+    Ok(())
+}
+
+/// Render a chassis or port ID according to its subtype
+fn format_id(subtype: u8, value: &[u8], mac_subtype: u8, address_subtype: u8) -> String {
+    if subtype == mac_subtype && value.len() == 6 {
+        return value.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+    }
+    if subtype == address_subtype && !value.is_empty() {
+        if let Some(address) = format_address(value[0], &value[1..]) {
+            return address;
+        }
+    }
+    text(value)
+}
+
+/// Render a TLV string, replacing invalid UTF-8
+fn text(value: &[u8]) -> String {
+    String::from_utf8_lossy(value).trim_end_matches('\0').to_string()
+}
+
+/// Names of the set bits of an LLDP system capabilities field
+fn capability_names(bits: u16) -> Vec<&'static str> {
+    const NAMES: [&str; 11] = [
+        "other", "repeater", "bridge", "wlan_access_point", "router", "telephone",
+        "docsis_cable_device", "station", "c_vlan", "s_vlan", "two_port_mac_relay",
+    ];
+    
+    NAMES
+        .iter()
+        .enumerate()
+        .filter(|&(bit, _)| bits & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
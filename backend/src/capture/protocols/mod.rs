@@ -0,0 +1,58 @@
+// Application-layer protocol decoders
+//
+// Each decoder inspects a transport payload (the frame payload for the
+// link-layer discovery protocols) and, when it recognizes the
+// protocol, records its fields under `packet.headers[<name>]` and upgrades
+// `packet.protocol`. Decoders never fail the whole packet: the transport
+// parsers treat an error as "not this protocol" and keep the L4 label.
+//
+// `wifi` is the exception: it decodes the radiotap and 802.11 link layer that
+// replaces Ethernet on monitor-mode captures, and its errors fail the packet.
+
+pub mod cdp;
+pub mod diameter;
+pub mod dns;
+pub mod expected;
+pub mod ftp;
+pub mod gtp;
+pub mod lldp;
+pub mod ntp;
+pub mod ospf;
+pub mod radius;
+pub mod registry;
+pub mod rtp;
+pub mod sip;
+pub mod snmp;
+pub mod ssh;
+pub mod tftp;
+pub mod wifi;
+pub mod wol;
+
+/// Protocol labels of the application-layer decoders, as accepted by `disabled_protocols`
+pub const DECODERS: &[&str] = &["CDP", "DIAMETER", "DNS", "FTP", "FTP-DATA", "GTP-U", "LLDP", "NTP", "OSPF", "RADIUS", "RTP", "SIP", "SNMP", "SSH", "TFTP", "WOL"];
+
+use serde_json::Value;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::models::packet::Packet;
+
+/// Insert a decoded header section into the packet's headers JSON
+pub fn insert_header(packet: &mut Packet, name: &str, value: Value) {
+    if let Value::Object(ref mut obj) = packet.headers {
+        obj.insert(name.to_string(), value);
+    }
+}
+
+/// Render an address tagged with its IANA address family number (IPv4, IPv6 or MAC)
+pub fn format_address(family: u8, address: &[u8]) -> Option<String> {
+    match (family, address.len()) {
+        (1, 4) => Some(Ipv4Addr::new(address[0], address[1], address[2], address[3]).to_string()),
+        (2, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(address);
+            Some(Ipv6Addr::from(octets).to_string())
+        },
+        (6, 6) => Some(address.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")),
+        _ => None,
+    }
+}
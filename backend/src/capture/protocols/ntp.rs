@@ -0,0 +1,135 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// NTP runs on UDP port 123
+pub const NTP_PORT: u16 = 123;
+
+/// Size of the fixed NTP header (without extension fields or MAC)
+const NTP_HEADER_LEN: usize = 48;
+
+/// Seconds between the NTP epoch (1900-01-01) and the Unix epoch (1970-01-01)
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Length of one NTP era (2^32 seconds, roughly 136 years)
+const NTP_ERA_SECONDS: i64 = 1 << 32;
+
+/// Decode an NTP message into `packet.headers["ntp"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < NTP_HEADER_LEN {
+        return Err(anyhow!("NTP message too short: {} bytes", data.len()));
+    }
+    
+    let leap_indicator = data[0] >> 6;
+    let version = (data[0] >> 3) & 0x07;
+    let mode = data[0] & 0x07;
+    
+    if !(1..=4).contains(&version) {
+        return Err(anyhow!("Unsupported NTP version {}", version));
+    }
+    
+    let stratum = data[1];
+    let poll = data[2] as i8;
+    let precision = data[3] as i8;
+    let root_delay = read_u32(data, 4);
+    let root_dispersion = read_u32(data, 8);
+    
+    packet.protocol = "NTP".to_string();
+    
+    insert_header(packet, "ntp", json!({
+        "leap_indicator": leap_indicator,
+        "version": version,
+        "mode": mode,
+        "mode_name": mode_name(mode),
+        "stratum": stratum,
+        "poll": poll,
+        "poll_interval_secs": 2f64.powi(poll as i32),
+        "precision": precision,
+        "root_delay_secs": short_format_to_secs(root_delay),
+        "root_dispersion_secs": short_format_to_secs(root_dispersion),
+        "reference_id": reference_id(stratum, &data[12..16]),
+        "reference_timestamp": timestamp_json(read_u64(data, 16)),
+        "origin_timestamp": timestamp_json(read_u64(data, 24)),
+        "receive_timestamp": timestamp_json(read_u64(data, 32)),
+        "transmit_timestamp": timestamp_json(read_u64(data, 40)),
+    }));
+    
+    Ok(())
+}
+
+/// Human-readable name for an NTP association mode
+pub fn mode_name(mode: u8) -> &'static str {
+    match mode {
+        1 => "symmetric active",
+        2 => "symmetric passive",
+        3 => "client",
+        4 => "server",
+        5 => "broadcast",
+        6 => "control",
+        7 => "private",
+        _ => "reserved",
+    }
+}
+
+/// Convert a 64-bit NTP timestamp (32.32 fixed point, seconds since 1900) to UTC.
+///
+/// Timestamps with the most significant bit clear are taken to be in era 1
+/// (after the 2036 rollover), per the RFC 4330 convention. An all-zero
+/// timestamp means "not set" and yields `None`.
+pub fn ntp_to_datetime(timestamp: u64) -> Option<DateTime<Utc>> {
+    if timestamp == 0 {
+        return None;
+    }
+    
+    let seconds = (timestamp >> 32) as i64;
+    let fraction = timestamp & 0xFFFF_FFFF;
+    
+    let era_seconds = if seconds & 0x8000_0000 == 0 {
+        seconds + NTP_ERA_SECONDS
+    } else {
+        seconds
+    };
+    
+    // Scale the 32-bit binary fraction to nanoseconds
+    let nanos = ((fraction * 1_000_000_000) >> 32) as u32;
+    
+    DateTime::from_timestamp(era_seconds - NTP_UNIX_OFFSET, nanos)
+}
+
+/// Render an NTP timestamp as an RFC 3339 string, or null when unset
+fn timestamp_json(timestamp: u64) -> Value {
+    match ntp_to_datetime(timestamp) {
+        Some(time) => json!(time.to_rfc3339()),
+        None => Value::Null,
+    }
+}
+
+/// Convert a 32-bit NTP short format value (16.16 fixed point) to seconds
+fn short_format_to_secs(value: u32) -> f64 {
+    value as f64 / 65536.0
+}
+
+/// Format the reference identifier according to the stratum
+fn reference_id(stratum: u8, bytes: &[u8]) -> String {
+    match stratum {
+        // Stratum 0 (kiss code) and 1 (reference clock) carry ASCII identifiers
+        0 | 1 => bytes
+            .iter()
+            .take_while(|b| **b != 0)
+            .map(|b| if b.is_ascii_graphic() { *b as char } else { '.' })
+            .collect(),
+        // Higher strata carry the IPv4 address of the upstream server
+        _ => format!("{}.{}.{}.{}", bytes[0], bytes[1], bytes[2], bytes[3]),
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    ((read_u32(data, offset) as u64) << 32) | read_u32(data, offset + 4) as u64
+}
@@ -0,0 +1,176 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+use std::net::Ipv4Addr;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Common header of OSPFv2 (with authentication) and OSPFv3
+const OSPFV2_HEADER_LEN: usize = 24;
+const OSPFV3_HEADER_LEN: usize = 16;
+
+/// LSA header, the same size in both versions
+const LSA_HEADER_LEN: usize = 20;
+
+/// Decode an OSPFv2 or OSPFv3 packet (IP protocol 89) into `packet.headers["ospf"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < OSPFV3_HEADER_LEN {
+        return Err(anyhow!("OSPF packet too short: {} bytes", data.len()));
+    }
+    
+    let version = data[0];
+    let header_len = match version {
+        2 => OSPFV2_HEADER_LEN,
+        3 => OSPFV3_HEADER_LEN,
+        _ => return Err(anyhow!("Unsupported OSPF version {}", version)),
+    };
+    if data.len() < header_len {
+        return Err(anyhow!("OSPFv{} packet too short: {} bytes", version, data.len()));
+    }
+    
+    let packet_type = data[1];
+    let length = u16::from_be_bytes([data[2], data[3]]) as usize;
+    if length < header_len {
+        return Err(anyhow!("Invalid OSPF length {}", length));
+    }
+    
+    let mut fields = Map::new();
+    fields.insert("version".to_string(), json!(version));
+    fields.insert("type".to_string(), json!(packet_type));
+    fields.insert("type_name".to_string(), json!(type_name(packet_type)));
+    fields.insert("length".to_string(), json!(length));
+    fields.insert("router_id".to_string(), json!(read_ipv4(data, 4)));
+    fields.insert("area_id".to_string(), json!(read_ipv4(data, 8)));
+    if version == 2 {
+        fields.insert("auth_type".to_string(), json!(u16::from_be_bytes([data[14], data[15]])));
+    } else {
+        fields.insert("instance_id".to_string(), json!(data[14]));
+    }
+    
+    // The body is bounded by the declared length and the captured bytes
+    let body = &data[header_len..length.min(data.len())];
+    match (packet_type, version) {
+        (1, 2) => hello_v2(body, &mut fields),
+        (1, _) => hello_v3(body, &mut fields),
+        (2, _) => database_description(body, version, &mut fields),
+        (3, _) => {
+            // Link state type, ID and advertising router, 12 bytes each (v2 pads the type to 4)
+            fields.insert("requests".to_string(), json!(body.len() / 12));
+        },
+        (4, _) if body.len() >= 4 => {
+            let count = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+            fields.insert("lsa_count".to_string(), json!(count));
+            fields.insert("lsas".to_string(), json!(lsa_headers(&body[4..], version, true)));
+        },
+        (5, _) => {
+            fields.insert("lsas".to_string(), json!(lsa_headers(body, version, false)));
+        },
+        _ => {},
+    }
+    
+    packet.protocol = "OSPF".to_string();
+    insert_header(packet, "ospf", Value::Object(fields));
+    
+    Ok(())
+}
+
+/// Name of an OSPF packet type
+pub fn type_name(packet_type: u8) -> &'static str {
+    match packet_type {
+        1 => "Hello",
+        2 => "DBD",
+        3 => "LSR",
+        4 => "LSU",
+        5 => "LSAck",
+        _ => "Unknown",
+    }
+}
+
+/// OSPFv2 Hello: mask, intervals, priority, DR/BDR, then the neighbors
+fn hello_v2(body: &[u8], fields: &mut Map<String, Value>) {
+    if body.len() < 20 {
+        return;
+    }
+    fields.insert("network_mask".to_string(), json!(read_ipv4(body, 0)));
+    fields.insert("hello_interval".to_string(), json!(u16::from_be_bytes([body[4], body[5]])));
+    fields.insert("options".to_string(), json!(body[6]));
+    fields.insert("priority".to_string(), json!(body[7]));
+    fields.insert("dead_interval".to_string(), json!(u32::from_be_bytes([body[8], body[9], body[10], body[11]])));
+    fields.insert("designated_router".to_string(), json!(read_ipv4(body, 12)));
+    fields.insert("backup_designated_router".to_string(), json!(read_ipv4(body, 16)));
+    fields.insert("neighbors".to_string(), json!(neighbors(&body[20..])));
+}
+
+/// OSPFv3 Hello: interface ID, priority, options, intervals, DR/BDR, then the neighbors
+fn hello_v3(body: &[u8], fields: &mut Map<String, Value>) {
+    if body.len() < 20 {
+        return;
+    }
+    fields.insert("interface_id".to_string(), json!(u32::from_be_bytes([body[0], body[1], body[2], body[3]])));
+    fields.insert("priority".to_string(), json!(body[4]));
+    fields.insert("options".to_string(), json!(u32::from_be_bytes([0, body[5], body[6], body[7]])));
+    fields.insert("hello_interval".to_string(), json!(u16::from_be_bytes([body[8], body[9]])));
+    fields.insert("dead_interval".to_string(), json!(u16::from_be_bytes([body[10], body[11]])));
+    fields.insert("designated_router".to_string(), json!(read_ipv4(body, 12)));
+    fields.insert("backup_designated_router".to_string(), json!(read_ipv4(body, 16)));
+    fields.insert("neighbors".to_string(), json!(neighbors(&body[20..])));
+}
+
+/// Database description: MTU, flags and sequence number, followed by LSA headers
+fn database_description(body: &[u8], version: u8, fields: &mut Map<String, Value>) {
+    // v2: MTU, options, flags; v3: reserved, 24-bit options, MTU, reserved, flags
+    let (mtu, flags, header_len) = match version {
+        2 if body.len() >= 8 => (u16::from_be_bytes([body[0], body[1]]), body[3], 8),
+        3 if body.len() >= 12 => (u16::from_be_bytes([body[4], body[5]]), body[7], 12),
+        _ => return,
+    };
+    let sequence = u32::from_be_bytes([body[header_len - 4], body[header_len - 3], body[header_len - 2], body[header_len - 1]]);
+    
+    fields.insert("interface_mtu".to_string(), json!(mtu));
+    fields.insert("flags".to_string(), json!({
+        "init": flags & 0x04 != 0,
+        "more": flags & 0x02 != 0,
+        "master": flags & 0x01 != 0,
+    }));
+    fields.insert("sequence".to_string(), json!(sequence));
+    fields.insert("lsas".to_string(), json!(lsa_headers(&body[header_len..], version, false)));
+}
+
+/// Router IDs of the neighbors listed in a Hello
+fn neighbors(data: &[u8]) -> Vec<String> {
+    data.chunks_exact(4).map(|id| Ipv4Addr::new(id[0], id[1], id[2], id[3]).to_string()).collect()
+}
+
+/// Decode a list of LSA headers. With `full`, each header is followed by
+/// the LSA body (an LSU), so the walk advances by the LSA length.
+fn lsa_headers(data: &[u8], version: u8, full: bool) -> Vec<Value> {
+    let mut lsas = Vec::new();
+    let mut offset = 0;
+    
+    while offset + LSA_HEADER_LEN <= data.len() {
+        let header = &data[offset..offset + LSA_HEADER_LEN];
+        let lsa_type = match version {
+            2 => u16::from(header[3]),
+            _ => u16::from_be_bytes([header[2], header[3]]),
+        };
+        let length = u16::from_be_bytes([header[18], header[19]]) as usize;
+        
+        lsas.push(json!({
+            "age": u16::from_be_bytes([header[0], header[1]]),
+            "type": lsa_type,
+            "link_state_id": read_ipv4(header, 4),
+            "advertising_router": read_ipv4(header, 8),
+            "sequence": u32::from_be_bytes([header[12], header[13], header[14], header[15]]),
+            "length": length,
+        }));
+        
+        offset += if full { length.max(LSA_HEADER_LEN) } else { LSA_HEADER_LEN };
+    }
+    
+    lsas
+}
+
+/// Read four bytes as a dotted-quad router/area ID
+fn read_ipv4(data: &[u8], offset: usize) -> String {
+    Ipv4Addr::new(data[offset], data[offset + 1], data[offset + 2], data[offset + 3]).to_string()
+}
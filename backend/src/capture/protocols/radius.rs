@@ -0,0 +1,174 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Value};
+use std::net::Ipv4Addr;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// RADIUS authentication port
+pub const RADIUS_AUTH_PORT: u16 = 1812;
+
+/// RADIUS accounting port
+pub const RADIUS_ACCT_PORT: u16 = 1813;
+
+/// Code, identifier, length and the 16-byte authenticator
+const RADIUS_HEADER_LEN: usize = 20;
+
+/// User-Password attribute type (value is obfuscated and never decoded)
+const ATTR_USER_PASSWORD: u8 = 2;
+
+/// How an attribute value should be rendered
+enum AttrFormat {
+    Text,
+    Address,
+    Integer,
+    Opaque,
+}
+
+/// Whether a port carries RADIUS
+pub fn is_radius_port(port: u16) -> bool {
+    port == RADIUS_AUTH_PORT || port == RADIUS_ACCT_PORT
+}
+
+/// Decode a RADIUS message into `packet.headers["radius"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < RADIUS_HEADER_LEN {
+        return Err(anyhow!("RADIUS message too short: {} bytes", data.len()));
+    }
+    
+    let code = data[0];
+    let identifier = data[1];
+    let length = u16::from_be_bytes([data[2], data[3]]) as usize;
+    
+    if length < RADIUS_HEADER_LEN || length > data.len() {
+        return Err(anyhow!("Invalid RADIUS length {} for {} byte payload", length, data.len()));
+    }
+    
+    let authenticator: String = data[4..20].iter().map(|b| format!("{:02x}", b)).collect();
+    
+    // Walk the attribute TLVs, bounded by the declared message length
+    let mut attributes = Vec::new();
+    let mut user_name = None;
+    let mut offset = RADIUS_HEADER_LEN;
+    
+    while offset + 2 <= length {
+        let attr_type = data[offset];
+        let attr_len = data[offset + 1] as usize;
+        
+        if attr_len < 2 || offset + attr_len > length {
+            break;
+        }
+        
+        let value = &data[offset + 2..offset + attr_len];
+        let (name, format) = attribute_info(attr_type);
+        
+        let rendered = if attr_type == ATTR_USER_PASSWORD {
+            json!("<present>")
+        } else {
+            format_value(format, value)
+        };
+        
+        if attr_type == 1 {
+            user_name = rendered.as_str().map(|s| s.to_string());
+        }
+        
+        attributes.push(json!({
+            "type": attr_type,
+            "name": name,
+            "length": attr_len,
+            "value": rendered,
+        }));
+        
+        offset += attr_len;
+    }
+    
+    packet.protocol = "RADIUS".to_string();
+    
+    insert_header(packet, "radius", json!({
+        "code": code,
+        "code_name": code_name(code),
+        "identifier": identifier,
+        "length": length,
+        "authenticator": authenticator,
+        "user_name": user_name,
+        "attributes": attributes,
+    }));
+    
+    Ok(())
+}
+
+/// Human-readable name for a RADIUS packet code
+pub fn code_name(code: u8) -> &'static str {
+    match code {
+        1 => "Access-Request",
+        2 => "Access-Accept",
+        3 => "Access-Reject",
+        4 => "Accounting-Request",
+        5 => "Accounting-Response",
+        11 => "Access-Challenge",
+        12 => "Status-Server",
+        13 => "Status-Client",
+        40 => "Disconnect-Request",
+        41 => "Disconnect-ACK",
+        42 => "Disconnect-NAK",
+        43 => "CoA-Request",
+        44 => "CoA-ACK",
+        45 => "CoA-NAK",
+        _ => "Unknown",
+    }
+}
+
+/// Name and value format for the common attribute types (RFC 2865/2866)
+fn attribute_info(attr_type: u8) -> (&'static str, AttrFormat) {
+    match attr_type {
+        1 => ("User-Name", AttrFormat::Text),
+        2 => ("User-Password", AttrFormat::Opaque),
+        3 => ("CHAP-Password", AttrFormat::Opaque),
+        4 => ("NAS-IP-Address", AttrFormat::Address),
+        5 => ("NAS-Port", AttrFormat::Integer),
+        6 => ("Service-Type", AttrFormat::Integer),
+        7 => ("Framed-Protocol", AttrFormat::Integer),
+        8 => ("Framed-IP-Address", AttrFormat::Address),
+        9 => ("Framed-IP-Netmask", AttrFormat::Address),
+        11 => ("Filter-Id", AttrFormat::Text),
+        12 => ("Framed-MTU", AttrFormat::Integer),
+        18 => ("Reply-Message", AttrFormat::Text),
+        24 => ("State", AttrFormat::Opaque),
+        25 => ("Class", AttrFormat::Opaque),
+        26 => ("Vendor-Specific", AttrFormat::Opaque),
+        27 => ("Session-Timeout", AttrFormat::Integer),
+        28 => ("Idle-Timeout", AttrFormat::Integer),
+        30 => ("Called-Station-Id", AttrFormat::Text),
+        31 => ("Calling-Station-Id", AttrFormat::Text),
+        32 => ("NAS-Identifier", AttrFormat::Text),
+        40 => ("Acct-Status-Type", AttrFormat::Integer),
+        41 => ("Acct-Delay-Time", AttrFormat::Integer),
+        42 => ("Acct-Input-Octets", AttrFormat::Integer),
+        43 => ("Acct-Output-Octets", AttrFormat::Integer),
+        44 => ("Acct-Session-Id", AttrFormat::Text),
+        46 => ("Acct-Session-Time", AttrFormat::Integer),
+        47 => ("Acct-Input-Packets", AttrFormat::Integer),
+        48 => ("Acct-Output-Packets", AttrFormat::Integer),
+        49 => ("Acct-Terminate-Cause", AttrFormat::Integer),
+        60 => ("CHAP-Challenge", AttrFormat::Opaque),
+        61 => ("NAS-Port-Type", AttrFormat::Integer),
+        79 => ("EAP-Message", AttrFormat::Opaque),
+        80 => ("Message-Authenticator", AttrFormat::Opaque),
+        87 => ("NAS-Port-Id", AttrFormat::Text),
+        _ => ("Unknown", AttrFormat::Opaque),
+    }
+}
+
+/// Render an attribute value according to its format
+fn format_value(format: AttrFormat, value: &[u8]) -> Value {
+    match format {
+        AttrFormat::Text => json!(String::from_utf8_lossy(value)),
+        AttrFormat::Address if value.len() == 4 => {
+            json!(Ipv4Addr::new(value[0], value[1], value[2], value[3]).to_string())
+        },
+        AttrFormat::Integer if value.len() == 4 => {
+            json!(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+        },
+        _ => json!(value.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+    }
+}
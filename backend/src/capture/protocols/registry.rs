@@ -0,0 +1,310 @@
+// Registry of the protocols the parser understands
+//
+// Every layer the parser decodes and every application decoder is listed with
+// the `packet.headers` section it fills and that section's field keys, so
+// clients can build filter builders and column pickers without hard-coding
+// them. Add an entry here alongside any new decoder or header field.
+
+use serde::Serialize;
+
+use crate::capture::protocols::DECODERS;
+
+/// A protocol the parser decodes, and where its fields end up
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ProtocolInfo {
+    /// Label the packet's `protocol` takes when this is its top layer
+    pub name: &'static str,
+    
+    /// "link", "network", "transport" or "application"
+    pub layer: &'static str,
+    
+    /// Key of the section in `packet.headers`
+    pub header: &'static str,
+    
+    /// Field keys of that section (a field is absent when the packet lacks it)
+    pub fields: &'static [&'static str],
+    
+    /// Whether traffic on a port can be forced to this protocol with decode-as
+    pub decode_as: bool,
+}
+
+impl ProtocolInfo {
+    /// Whether the decoder can be turned off with `--disable-protocols`
+    pub fn optional(&self) -> bool {
+        DECODERS.contains(&self.name)
+    }
+}
+
+/// All protocols the parser decodes, lowest layer first
+pub const PROTOCOLS: &[ProtocolInfo] = &[
+    ProtocolInfo {
+        name: "Ethernet",
+        layer: "link",
+        header: "ethernet",
+        fields: &["source_mac", "destination_mac", "ethertype"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "Radiotap",
+        layer: "link",
+        header: "radiotap",
+        fields: &["length", "present", "flags", "data_rate_mbps", "channel_frequency", "channel", "signal_dbm", "noise_dbm"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "802.11",
+        layer: "link",
+        header: "wifi",
+        fields: &[
+            "type", "subtype", "type_name", "subtype_name", "to_ds", "from_ds", "retry", "protected",
+            "receiver", "transmitter", "bssid", "ssid", "hidden_ssid", "channel", "beacon_interval",
+            "capabilities", "privacy", "status_code", "association_id", "auth_algorithm",
+            "auth_sequence", "reason_code",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "SLL",
+        layer: "link",
+        header: "sll",
+        fields: &["packet_type", "packet_type_name", "link_type", "source_address", "protocol"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "Loopback",
+        layer: "link",
+        header: "loopback",
+        fields: &["family"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "PPPoE",
+        layer: "link",
+        header: "pppoe",
+        fields: &[
+            "version", "type", "code", "code_name", "session_id", "length", "service_name", "ac_name",
+            "ppp_protocol", "ppp_protocol_name",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "ARP",
+        layer: "network",
+        header: "arp",
+        fields: &[
+            "hardware_type", "protocol_type", "hw_addr_len", "proto_addr_len", "operation",
+            "sender_hw_addr", "sender_proto_addr", "target_hw_addr", "target_proto_addr",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "IPv4",
+        layer: "network",
+        header: "ipv4",
+        fields: &[
+            "version", "header_length", "total_length", "ttl", "protocol", "checksum", "source_ip",
+            "destination_ip", "options",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "IPv6",
+        layer: "network",
+        header: "ipv6",
+        fields: &[
+            "version", "traffic_class", "flow_label", "payload_length", "next_header", "hop_limit",
+            "source", "destination",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "TCP",
+        layer: "transport",
+        header: "tcp",
+        fields: &[
+            "source_port", "destination_port", "sequence", "acknowledgement", "data_offset", "flags",
+            "window", "checksum", "urgent_ptr",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "UDP",
+        layer: "transport",
+        header: "udp",
+        fields: &["source_port", "destination_port", "length", "checksum"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "SCTP",
+        layer: "transport",
+        header: "sctp",
+        fields: &["source_port", "destination_port", "verification_tag", "checksum", "chunks"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "ICMP",
+        layer: "transport",
+        header: "icmp",
+        fields: &["icmp_type", "icmp_code", "checksum", "next_hop_mtu", "original_destination"],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "CDP",
+        layer: "application",
+        header: "cdp",
+        fields: &[
+            "version", "ttl", "device_id", "port_id", "addresses", "capabilities", "software_version",
+            "platform", "native_vlan",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "DIAMETER",
+        layer: "application",
+        header: "diameter",
+        fields: &[
+            "version", "length", "flags", "command_code", "command_name", "application_id",
+            "hop_by_hop_id", "end_to_end_id", "avps",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "DNS",
+        layer: "application",
+        header: "dns",
+        fields: &[
+            "id", "is_response", "opcode", "flags", "rcode", "rcode_name", "questions", "answers",
+            "authorities", "additionals", "query_name", "query_type", "query_type_name", "transport",
+            "message_length", "segmented", "continuation", "zone_transfer",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "FTP",
+        layer: "application",
+        header: "ftp",
+        fields: &["command", "argument", "reply_code", "reply_text", "continues", "data_address", "data_port"],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "FTP-DATA",
+        layer: "application",
+        header: "ftp-data",
+        fields: &["data_length"],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "GTP-U",
+        layer: "application",
+        header: "gtp",
+        fields: &[
+            "version", "message_type", "message_type_name", "length", "teid", "outer_source_ip",
+            "outer_destination_ip", "sequence", "npdu_number", "extension_headers", "pdu_type", "qfi",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "LLDP",
+        layer: "application",
+        header: "lldp",
+        fields: &[
+            "chassis_id", "chassis_id_subtype", "port_id", "port_id_subtype", "ttl", "port_description",
+            "system_name", "system_description", "capabilities", "enabled_capabilities",
+            "management_addresses",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "NTP",
+        layer: "application",
+        header: "ntp",
+        fields: &[
+            "leap_indicator", "version", "mode", "mode_name", "stratum", "poll", "poll_interval_secs",
+            "precision", "root_delay_secs", "root_dispersion_secs", "reference_id",
+            "reference_timestamp", "origin_timestamp", "receive_timestamp", "transmit_timestamp",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "OSPF",
+        layer: "application",
+        header: "ospf",
+        fields: &[
+            "version", "type", "type_name", "length", "router_id", "area_id", "auth_type",
+            "instance_id", "network_mask", "interface_id", "hello_interval", "dead_interval",
+            "options", "priority", "designated_router", "backup_designated_router", "neighbors",
+            "interface_mtu", "flags", "sequence", "lsa_count", "lsas", "requests",
+        ],
+        decode_as: false,
+    },
+    ProtocolInfo {
+        name: "RADIUS",
+        layer: "application",
+        header: "radius",
+        fields: &["code", "code_name", "identifier", "length", "authenticator", "attributes", "user_name"],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "RTP",
+        layer: "application",
+        header: "rtp",
+        fields: &[
+            "version", "padding", "extension", "csrc_count", "marker", "payload_type", "codec",
+            "sequence", "timestamp", "ssrc", "payload_length", "csrcs",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "SIP",
+        layer: "application",
+        header: "sip",
+        fields: &[
+            "method", "request_uri", "status_code", "reason", "from", "to", "call_id", "cseq", "via",
+            "content_type", "content_length", "sdp",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "SNMP",
+        layer: "application",
+        header: "snmp",
+        fields: &[
+            "version", "version_name", "community", "msg_id", "security_model", "user_name",
+            "encrypted", "context_name", "pdu_type", "pdu_type_name", "request_id", "error_status",
+            "error_index", "non_repeaters", "max_repetitions", "enterprise", "agent_address",
+            "generic_trap", "specific_trap", "timestamp", "variable_bindings", "variable_bindings_total",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "SSH",
+        layer: "application",
+        header: "ssh",
+        fields: &[
+            "role", "banner", "protocol_version", "software", "comments", "packet_length",
+            "padding_length", "message_type", "message_type_name", "kex_algorithms",
+            "host_key_algorithms", "ciphers_client_to_server", "ciphers_server_to_client",
+            "macs_client_to_server", "macs_server_to_client", "compression_client_to_server",
+            "compression_server_to_client", "languages_client_to_server",
+            "languages_server_to_client", "first_kex_packet_follows", "truncated",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "TFTP",
+        layer: "application",
+        header: "tftp",
+        fields: &[
+            "opcode", "opcode_name", "filename", "mode", "options", "block", "data_length",
+            "last_block", "error_code", "error_name", "error_message",
+        ],
+        decode_as: true,
+    },
+    ProtocolInfo {
+        name: "WOL",
+        layer: "application",
+        header: "wol",
+        fields: &["target_mac", "offset", "secureon_password"],
+        decode_as: true,
+    },
+];
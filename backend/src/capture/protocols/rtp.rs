@@ -0,0 +1,138 @@
+// RTP (RFC 3550), the media of VoIP calls and video conferences
+//
+// RTP has no port of its own: a SIP call's SDP negotiates one, usually even
+// and above 1024, with RTCP on the next odd port. Without the signalling,
+// UDP datagrams between two even ports are tried as RTP when nothing else
+// claimed them, and the header must pass sanity checks (version 2, a known or
+// dynamic payload type, consistent CSRC count, extension and padding) to be
+// taken for it. Decode-as forces RTP on a port when the heuristic misses.
+//
+// The payload type names the codec for the static types of RFC 3551; its
+// clock rate turns timestamps into time for the stream statistics.
+
+use anyhow::{Result, anyhow};
+use serde_json::json;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Version, flags, payload type, sequence number, timestamp and SSRC
+const HEADER_LEN: usize = 12;
+
+/// Lowest port the heuristic considers; below are well-known services
+const MIN_HEURISTIC_PORT: u16 = 1024;
+
+/// Payload types from 96 up are assigned dynamically by the signalling
+const DYNAMIC_PAYLOAD_TYPES: std::ops::RangeInclusive<u8> = 96..=127;
+
+/// Whether a UDP port pair is one the heuristic tries as RTP
+pub fn is_heuristic_port_pair(source_port: u16, destination_port: u16) -> bool {
+    [source_port, destination_port].iter().all(|&port| port >= MIN_HEURISTIC_PORT && port % 2 == 0)
+}
+
+/// Decode an RTP header into `packet.headers["rtp"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    if data.len() < HEADER_LEN {
+        return Err(anyhow!("RTP packet too short: {} bytes", data.len()));
+    }
+    
+    let version = data[0] >> 6;
+    if version != 2 {
+        return Err(anyhow!("Not RTP version 2 (version {})", version));
+    }
+    let padding = data[0] & 0x20 != 0;
+    let extension = data[0] & 0x10 != 0;
+    let csrc_count = (data[0] & 0x0f) as usize;
+    let marker = data[1] & 0x80 != 0;
+    let payload_type = data[1] & 0x7f;
+    let codec = codec(payload_type).ok_or_else(|| anyhow!("Unassigned RTP payload type {}", payload_type))?;
+    
+    let mut header_len = HEADER_LEN + 4 * csrc_count;
+    if data.len() < header_len {
+        return Err(anyhow!("RTP CSRC list truncated"));
+    }
+    let csrcs: Vec<u32> = data[HEADER_LEN..header_len]
+        .chunks_exact(4)
+        .map(|csrc| u32::from_be_bytes([csrc[0], csrc[1], csrc[2], csrc[3]]))
+        .collect();
+    
+    if extension {
+        let Some(words) = data.get(header_len + 2..header_len + 4) else {
+            return Err(anyhow!("RTP header extension truncated"));
+        };
+        header_len += 4 + 4 * u16::from_be_bytes([words[0], words[1]]) as usize;
+        if data.len() < header_len {
+            return Err(anyhow!("RTP header extension truncated"));
+        }
+    }
+    
+    // The last octet counts the padding, itself included
+    let padding_len = if padding { data[data.len() - 1] as usize } else { 0 };
+    if padding && (padding_len == 0 || header_len + padding_len > data.len()) {
+        return Err(anyhow!("Invalid RTP padding length {}", padding_len));
+    }
+    
+    let mut rtp = json!({
+        "version": version,
+        "padding": padding,
+        "extension": extension,
+        "csrc_count": csrc_count,
+        "marker": marker,
+        "payload_type": payload_type,
+        "codec": codec,
+        "sequence": u16::from_be_bytes([data[2], data[3]]),
+        "timestamp": u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+        "ssrc": u32::from_be_bytes([data[8], data[9], data[10], data[11]]),
+        "payload_length": data.len() - header_len - padding_len,
+    });
+    if !csrcs.is_empty() {
+        rtp["csrcs"] = json!(csrcs);
+    }
+    
+    packet.protocol = "RTP".to_string();
+    insert_header(packet, "rtp", rtp);
+    Ok(())
+}
+
+/// Codec of a payload type (RFC 3551 for the static ones); None for unassigned types
+pub fn codec(payload_type: u8) -> Option<&'static str> {
+    let codec = match payload_type {
+        0 => "PCMU",
+        3 => "GSM",
+        4 => "G723",
+        5 | 6 | 16 | 17 => "DVI4",
+        7 => "LPC",
+        8 => "PCMA",
+        9 => "G722",
+        10 | 11 => "L16",
+        12 => "QCELP",
+        13 => "CN",
+        14 => "MPA",
+        15 => "G728",
+        18 => "G729",
+        25 => "CelB",
+        26 => "JPEG",
+        28 => "nv",
+        31 => "H261",
+        32 => "MPV",
+        33 => "MP2T",
+        34 => "H263",
+        _ if DYNAMIC_PAYLOAD_TYPES.contains(&payload_type) => "dynamic",
+        _ => return None,
+    };
+    Some(codec)
+}
+
+/// RTP timestamp units per second of a payload type; None for dynamic types,
+/// whose rate only the signalling knows
+pub fn clock_rate(payload_type: u8) -> Option<u32> {
+    match payload_type {
+        0 | 3 | 4 | 5 | 7 | 8 | 9 | 12 | 13 | 15 | 18 => Some(8000),
+        6 => Some(16000),
+        10 | 11 => Some(44100),
+        16 => Some(11025),
+        17 => Some(22050),
+        14 | 25 | 26 | 28 | 31 | 32 | 33 | 34 => Some(90000),
+        _ => None,
+    }
+}
@@ -0,0 +1,160 @@
+// SIP (RFC 3261), the signalling of VoIP calls, on UDP and TCP port 5060
+//
+// A message is a request line (`INVITE sip:bob@example.com SIP/2.0`) or a
+// status line (`SIP/2.0 200 OK`), header lines, and a body. The headers that
+// tie a call together are decoded (From, To, Call-ID, CSeq, every Via, and
+// the body's type and length), compact forms such as `i:` for Call-ID
+// included. An SDP body (RFC 4566) offers or answers the media of the call:
+// its connection address and the port of each media stream are returned so
+// the parser can expect the RTP on them. Over TCP only the first message of
+// a segment is decoded.
+
+use anyhow::{Result, anyhow};
+use serde_json::{json, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// SIP runs on port 5060 over UDP and TCP
+pub const SIP_PORT: u16 = 5060;
+
+/// Version token of the request and status lines
+const SIP_VERSION: &str = "SIP/2.0";
+
+/// Decode a SIP message into `packet.headers["sip"]`, returning the media
+/// endpoints (address and port) of an SDP body
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<Vec<(String, u16)>> {
+    let text = std::str::from_utf8(data).map_err(|_| anyhow!("SIP message is not text"))?;
+    let (head, body) = match text.split_once("\r\n\r\n") {
+        Some((head, body)) => (head, Some(body)),
+        None => (text.trim_end(), None),
+    };
+    let mut lines = unfold(head).into_iter();
+    let start_line = lines.next().unwrap_or_default();
+    
+    let mut sip = start(&start_line)?;
+    let mut via = Vec::new();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        let key = match name.trim().to_ascii_lowercase().as_str() {
+            "from" | "f" => "from",
+            "to" | "t" => "to",
+            "call-id" | "i" => "call_id",
+            "cseq" => "cseq",
+            "content-type" | "c" => "content_type",
+            "content-length" | "l" => "content_length",
+            "via" | "v" => {
+                // One header may list several hops
+                via.extend(value.split(',').map(|hop| hop.trim().to_string()));
+                continue;
+            },
+            _ => continue,
+        };
+        sip[key] = match key {
+            "content_length" => value.parse::<u64>().map_or_else(|_| json!(value), |length| json!(length)),
+            _ => json!(value),
+        };
+    }
+    if !via.is_empty() {
+        sip["via"] = json!(via);
+    }
+    
+    let mut media_endpoints = Vec::new();
+    let is_sdp = sip["content_type"].as_str().is_some_and(|content_type| content_type.to_ascii_lowercase().starts_with("application/sdp"));
+    if let (true, Some(body)) = (is_sdp, body.filter(|body| !body.is_empty())) {
+        let (sdp, endpoints) = sdp(body);
+        sip["sdp"] = sdp;
+        media_endpoints = endpoints;
+    }
+    
+    packet.protocol = "SIP".to_string();
+    insert_header(packet, "sip", sip);
+    Ok(media_endpoints)
+}
+
+/// Header lines with folded continuations (lines starting with whitespace) joined
+fn unfold(head: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in head.split("\r\n").flat_map(|line| line.split('\n')) {
+        match lines.last_mut() {
+            Some(previous) if line.starts_with([' ', '\t']) => {
+                previous.push(' ');
+                previous.push_str(line.trim());
+            },
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+    lines
+}
+
+/// The fields of a request or status line
+fn start(line: &str) -> Result<Value> {
+    if let Some(status) = line.strip_prefix(SIP_VERSION).and_then(|rest| rest.strip_prefix(' ')) {
+        let (code, reason) = status.split_once(' ').unwrap_or((status, ""));
+        let code: u16 = code.parse().ok().filter(|code| (100..700).contains(code))
+            .ok_or_else(|| anyhow!("Invalid SIP status code '{}'", code))?;
+        return Ok(json!({ "status_code": code, "reason": reason }));
+    }
+    
+    let mut parts = line.splitn(3, ' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(uri), Some(SIP_VERSION))
+            if !method.is_empty() && method.bytes().all(|b| b.is_ascii_uppercase() || b == b'-') =>
+        {
+            Ok(json!({ "method": method, "request_uri": uri }))
+        },
+        _ => Err(anyhow!("Not a SIP request or status line: '{}'", line.chars().take(64).collect::<String>())),
+    }
+}
+
+/// The connection address and media of an SDP body, and the endpoint of each
+/// RTP stream (its own connection address, else the session's)
+fn sdp(body: &str) -> (Value, Vec<(String, u16)>) {
+    let mut session_address: Option<String> = None;
+    let mut media: Vec<Value> = Vec::new();
+    
+    for line in body.lines().map(str::trim_end) {
+        let Some((kind, value)) = line.split_once('=') else { continue };
+        match kind {
+            // c=IN IP4 192.0.2.1 (a multicast address may carry /ttl)
+            "c" => {
+                let Some(address) = value.split(' ').nth(2).and_then(|address| address.split('/').next()) else { continue };
+                match media.last_mut() {
+                    Some(stream) => stream["connection_address"] = json!(address),
+                    None => session_address = Some(address.to_string()),
+                }
+            },
+            // m=audio 49170 RTP/AVP 0 8 97 (the port may carry /count)
+            "m" => {
+                let fields: Vec<&str> = value.split(' ').collect();
+                let [media_type, port, protocol, formats @ ..] = &fields[..] else { continue };
+                let Some(port) = port.split('/').next().and_then(|port| port.parse::<u16>().ok()) else { continue };
+                media.push(json!({
+                    "media": media_type,
+                    "port": port,
+                    "protocol": protocol,
+                    "formats": formats,
+                }));
+            },
+            _ => {},
+        }
+    }
+    
+    // Port 0 declines a stream; only RTP profiles (RTP/AVP, RTP/SAVP, ...) carry RTP
+    let endpoints = media
+        .iter()
+        .filter(|stream| stream["protocol"].as_str().is_some_and(|protocol| protocol.starts_with("RTP/")))
+        .filter_map(|stream| {
+            let port = stream["port"].as_u64().filter(|&port| port != 0)? as u16;
+            let address = stream["connection_address"].as_str().map(str::to_string).or_else(|| session_address.clone())?;
+            Some((address, port))
+        })
+        .collect();
+    
+    let mut sdp = json!({ "media": media });
+    if let Some(address) = session_address {
+        sdp["connection_address"] = json!(address);
+    }
+    (sdp, endpoints)
+}
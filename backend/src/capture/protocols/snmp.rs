@@ -0,0 +1,315 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+use std::net::Ipv4Addr;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// SNMP agent port (requests and responses)
+pub const SNMP_PORT: u16 = 161;
+
+/// SNMP trap/notification port
+pub const SNMP_TRAP_PORT: u16 = 162;
+
+/// BER universal tags
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// SMI application tags
+const TAG_IP_ADDRESS: u8 = 0x40;
+const TAG_TIMETICKS: u8 = 0x43;
+
+/// Trap-PDU (SNMPv1), whose layout differs from the other PDUs
+const PDU_TRAP_V1: u8 = 0xa4;
+
+/// GetBulkRequest-PDU, with non-repeaters/max-repetitions in place of the error fields
+const PDU_GET_BULK: u8 = 0xa5;
+
+/// msgFlags bit marking an encrypted (privacy) SNMPv3 scoped PDU
+const V3_FLAG_PRIV: u8 = 0x02;
+
+/// Variable bindings listed per message; the rest are only counted
+const MAX_VARBINDS: usize = 64;
+
+/// Whether a port carries SNMP
+pub fn is_snmp_port(port: u16) -> bool {
+    port == SNMP_PORT || port == SNMP_TRAP_PORT
+}
+
+/// Reads BER TLVs from a buffer, rejecting lengths that run past it
+struct BerReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BerReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+    
+    /// Read the next TLV as (tag, contents)
+    fn read(&mut self) -> Result<(u8, &'a [u8])> {
+        let tag = *self.data.get(self.pos).ok_or_else(|| anyhow!("BER element truncated"))?;
+        if tag & 0x1f == 0x1f {
+            return Err(anyhow!("Multi-byte BER tags are not used by SNMP"));
+        }
+        let first = *self.data.get(self.pos + 1).ok_or_else(|| anyhow!("BER length missing"))?;
+        let mut pos = self.pos + 2;
+        
+        let length = if first < 0x80 {
+            first as usize
+        } else {
+            // Long form; indefinite lengths (0x80) are not allowed in SNMP
+            let octets = (first & 0x7f) as usize;
+            if octets == 0 || octets > 4 {
+                return Err(anyhow!("Unsupported BER length form 0x{:02x}", first));
+            }
+            let bytes = self.data.get(pos..pos + octets).ok_or_else(|| anyhow!("BER length truncated"))?;
+            pos += octets;
+            bytes.iter().fold(0usize, |length, &b| (length << 8) | b as usize)
+        };
+        
+        let contents = pos.checked_add(length)
+            .and_then(|end| self.data.get(pos..end))
+            .ok_or_else(|| anyhow!("BER length {} exceeds the {} bytes left", length, self.data.len().saturating_sub(pos)))?;
+        self.pos = pos + length;
+        Ok((tag, contents))
+    }
+    
+    /// Read the next TLV, requiring its tag
+    fn expect(&mut self, tag: u8, what: &str) -> Result<&'a [u8]> {
+        match self.read()? {
+            (found, contents) if found == tag => Ok(contents),
+            (found, _) => Err(anyhow!("Expected {} (tag 0x{:02x}), found tag 0x{:02x}", what, tag, found)),
+        }
+    }
+    
+    fn integer(&mut self, what: &str) -> Result<i64> {
+        decode_integer(self.expect(TAG_INTEGER, what)?)
+    }
+}
+
+/// Decode an SNMP message into `packet.headers["snmp"]`
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    let message = BerReader::new(data).expect(TAG_SEQUENCE, "SNMP message")?;
+    let mut message = BerReader::new(message);
+    
+    let version = message.integer("version")?;
+    let mut snmp = Map::new();
+    snmp.insert("version".to_string(), json!(version));
+    snmp.insert("version_name".to_string(), json!(version_name(version)?));
+    
+    if version == 3 {
+        parse_v3(&mut message, &mut snmp)?;
+    } else {
+        let community = message.expect(TAG_OCTET_STRING, "community")?;
+        snmp.insert("community".to_string(), json!(String::from_utf8_lossy(community)));
+        let (tag, pdu) = message.read()?;
+        parse_pdu(tag, pdu, &mut snmp)?;
+    }
+    
+    packet.protocol = "SNMP".to_string();
+    insert_header(packet, "snmp", Value::Object(snmp));
+    
+    Ok(())
+}
+
+/// Header data, USM user and (unless encrypted) the scoped PDU of an SNMPv3 message
+fn parse_v3(message: &mut BerReader, snmp: &mut Map<String, Value>) -> Result<()> {
+    let mut global = BerReader::new(message.expect(TAG_SEQUENCE, "msgGlobalData")?);
+    snmp.insert("msg_id".to_string(), json!(global.integer("msgID")?));
+    global.integer("msgMaxSize")?;
+    let flags = global.expect(TAG_OCTET_STRING, "msgFlags")?.first().copied().unwrap_or(0);
+    snmp.insert("security_model".to_string(), json!(global.integer("msgSecurityModel")?));
+    
+    // User-based security model parameters, wrapped in an OCTET STRING
+    let security = message.expect(TAG_OCTET_STRING, "msgSecurityParameters")?;
+    if let Ok(usm) = BerReader::new(security).expect(TAG_SEQUENCE, "USM parameters") {
+        let mut usm = BerReader::new(usm);
+        let user = usm.read().and_then(|_| usm.read()).and_then(|_| usm.read())
+            .and_then(|_| usm.expect(TAG_OCTET_STRING, "msgUserName"));
+        if let Ok(user) = user {
+            snmp.insert("user_name".to_string(), json!(String::from_utf8_lossy(user)));
+        }
+    }
+    
+    let encrypted = flags & V3_FLAG_PRIV != 0;
+    snmp.insert("encrypted".to_string(), json!(encrypted));
+    if encrypted {
+        return Ok(());
+    }
+    
+    let mut scoped = BerReader::new(message.expect(TAG_SEQUENCE, "scopedPDU")?);
+    scoped.expect(TAG_OCTET_STRING, "contextEngineID")?;
+    let context_name = scoped.expect(TAG_OCTET_STRING, "contextName")?;
+    snmp.insert("context_name".to_string(), json!(String::from_utf8_lossy(context_name)));
+    let (tag, pdu) = scoped.read()?;
+    parse_pdu(tag, pdu, snmp)
+}
+
+/// Decode a PDU's fields and variable bindings
+fn parse_pdu(tag: u8, pdu: &[u8], snmp: &mut Map<String, Value>) -> Result<()> {
+    let name = pdu_type_name(tag).ok_or_else(|| anyhow!("Unknown SNMP PDU tag 0x{:02x}", tag))?;
+    snmp.insert("pdu_type".to_string(), json!(tag & 0x1f));
+    snmp.insert("pdu_type_name".to_string(), json!(name));
+    
+    let mut pdu = BerReader::new(pdu);
+    if tag == PDU_TRAP_V1 {
+        snmp.insert("enterprise".to_string(), json!(decode_oid(pdu.expect(TAG_OID, "enterprise")?)?));
+        let agent = pdu.expect(TAG_IP_ADDRESS, "agent-addr")?;
+        if let [a, b, c, d] = agent {
+            snmp.insert("agent_address".to_string(), json!(Ipv4Addr::new(*a, *b, *c, *d).to_string()));
+        }
+        snmp.insert("generic_trap".to_string(), json!(pdu.integer("generic-trap")?));
+        snmp.insert("specific_trap".to_string(), json!(pdu.integer("specific-trap")?));
+        snmp.insert("timestamp".to_string(), json!(decode_integer(pdu.expect(TAG_TIMETICKS, "time-stamp")?)?));
+    } else {
+        snmp.insert("request_id".to_string(), json!(pdu.integer("request-id")?));
+        let (first, second) = if tag == PDU_GET_BULK {
+            ("non_repeaters", "max_repetitions")
+        } else {
+            ("error_status", "error_index")
+        };
+        snmp.insert(first.to_string(), json!(pdu.integer(first)?));
+        snmp.insert(second.to_string(), json!(pdu.integer(second)?));
+    }
+    
+    let mut list = BerReader::new(pdu.expect(TAG_SEQUENCE, "variable-bindings")?);
+    let mut bindings = Vec::new();
+    let mut count = 0;
+    while !list.is_empty() {
+        let mut binding = BerReader::new(list.expect(TAG_SEQUENCE, "VarBind")?);
+        count += 1;
+        if bindings.len() >= MAX_VARBINDS {
+            continue;
+        }
+        let oid = decode_oid(binding.expect(TAG_OID, "name")?)?;
+        let (value_tag, value) = binding.read()?;
+        let (value_type, value) = render_value(value_tag, value);
+        bindings.push(json!({
+            "oid": oid,
+            "type": value_type,
+            "value": value,
+        }));
+    }
+    snmp.insert("variable_bindings".to_string(), json!(bindings));
+    if count > bindings.len() {
+        snmp.insert("variable_bindings_total".to_string(), json!(count));
+    }
+    
+    Ok(())
+}
+
+/// Decode a two's-complement BER INTEGER of at most 8 bytes
+fn decode_integer(bytes: &[u8]) -> Result<i64> {
+    if bytes.is_empty() || bytes.len() > 9 || (bytes.len() == 9 && bytes[0] != 0) {
+        return Err(anyhow!("Unsupported BER integer of {} bytes", bytes.len()));
+    }
+    // Unsigned 64-bit values (Counter64) may carry a leading zero byte
+    let seed = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+    Ok(bytes.iter().fold(seed, |value, &b| (value << 8) | b as i64))
+}
+
+/// Decode an OBJECT IDENTIFIER into dotted notation
+fn decode_oid(bytes: &[u8]) -> Result<String> {
+    if bytes.is_empty() {
+        return Err(anyhow!("Empty OID"));
+    }
+    
+    let mut arcs: Vec<u64> = Vec::new();
+    let mut value: u64 = 0;
+    let mut pending = false;
+    for &b in bytes {
+        if value > u64::MAX >> 7 {
+            return Err(anyhow!("OID arc overflows"));
+        }
+        value = (value << 7) | (b & 0x7f) as u64;
+        pending = b & 0x80 != 0;
+        if !pending {
+            if arcs.is_empty() {
+                // The first subidentifier packs the first two arcs
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    if pending {
+        return Err(anyhow!("OID truncated"));
+    }
+    
+    Ok(arcs.iter().map(|arc| arc.to_string()).collect::<Vec<_>>().join("."))
+}
+
+/// Type name and JSON value of a variable binding value
+fn render_value(tag: u8, value: &[u8]) -> (&'static str, Value) {
+    let integer = |name| match decode_integer(value) {
+        Ok(v) if tag == TAG_INTEGER => (name, json!(v)),
+        // Application integers are unsigned
+        Ok(v) => (name, json!(if v < 0 && value.len() <= 4 { (v as u32) as u64 } else { v as u64 })),
+        Err(_) => (name, json!(hex(value))),
+    };
+    
+    match tag {
+        TAG_INTEGER => integer("INTEGER"),
+        TAG_OCTET_STRING => match std::str::from_utf8(value) {
+            Ok(text) if text.chars().all(|c| !c.is_control() || c.is_whitespace()) => ("OCTET STRING", json!(text)),
+            _ => ("OCTET STRING", json!(hex(value))),
+        },
+        TAG_NULL => ("NULL", Value::Null),
+        TAG_OID => ("OBJECT IDENTIFIER", decode_oid(value).map(Value::from).unwrap_or_else(|_| json!(hex(value)))),
+        TAG_IP_ADDRESS => match value {
+            [a, b, c, d] => ("IpAddress", json!(Ipv4Addr::new(*a, *b, *c, *d).to_string())),
+            _ => ("IpAddress", json!(hex(value))),
+        },
+        0x41 => integer("Counter32"),
+        0x42 => integer("Gauge32"),
+        TAG_TIMETICKS => integer("TimeTicks"),
+        0x44 => ("Opaque", json!(hex(value))),
+        0x46 => integer("Counter64"),
+        0x80 => ("noSuchObject", Value::Null),
+        0x81 => ("noSuchInstance", Value::Null),
+        0x82 => ("endOfMibView", Value::Null),
+        _ => ("Unknown", json!(hex(value))),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Name of an SNMP message version number
+fn version_name(version: i64) -> Result<&'static str> {
+    match version {
+        0 => Ok("v1"),
+        1 => Ok("v2c"),
+        3 => Ok("v3"),
+        _ => Err(anyhow!("Unknown SNMP version {}", version)),
+    }
+}
+
+/// Name of a PDU by its context-specific tag
+fn pdu_type_name(tag: u8) -> Option<&'static str> {
+    match tag {
+        0xa0 => Some("GetRequest"),
+        0xa1 => Some("GetNextRequest"),
+        0xa2 => Some("GetResponse"),
+        0xa3 => Some("SetRequest"),
+        PDU_TRAP_V1 => Some("Trap"),
+        PDU_GET_BULK => Some("GetBulkRequest"),
+        0xa6 => Some("InformRequest"),
+        0xa7 => Some("SNMPv2-Trap"),
+        0xa8 => Some("Report"),
+        _ => None,
+    }
+}
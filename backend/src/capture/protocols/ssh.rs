@@ -0,0 +1,192 @@
+// SSH identification and key exchange (RFC 4253)
+//
+// Everything after the key exchange is encrypted, but each side first sends an
+// identification line in the clear (`SSH-2.0-OpenSSH_9.6 Ubuntu-3`), naming
+// its implementation and version, and then a KEXINIT listing the algorithms
+// it supports. Both are decoded here, which is enough to inventory SSH
+// implementations across a network from a passive capture. Encrypted packets
+// are not recognized and keep their TCP label.
+
+use anyhow::{Result, anyhow};
+use serde_json::{json, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// SSH runs on TCP port 22
+pub const SSH_PORT: u16 = 22;
+
+/// Identification lines are at most 255 characters, CR LF included
+const MAX_IDENTIFICATION_LEN: usize = 255;
+
+/// Lines a server may send before its identification
+const MAX_PRELUDE_LINES: usize = 8;
+
+/// Packet length, padding length and message type
+const PACKET_HEADER_LEN: usize = 6;
+
+/// Packet lengths an implementation must accept (the minimum, and the maximum of RFC 4253)
+const MIN_PACKET_LEN: u32 = 12;
+const MAX_PACKET_LEN: u32 = 35000;
+
+/// Random cookie preceding the algorithm lists of a KEXINIT
+const COOKIE_LEN: usize = 16;
+
+const MSG_KEXINIT: u8 = 20;
+
+/// The name-lists of a KEXINIT, in order
+const KEXINIT_LISTS: [&str; 10] = [
+    "kex_algorithms",
+    "host_key_algorithms",
+    "ciphers_client_to_server",
+    "ciphers_server_to_client",
+    "macs_client_to_server",
+    "macs_server_to_client",
+    "compression_client_to_server",
+    "compression_server_to_client",
+    "languages_client_to_server",
+    "languages_server_to_client",
+];
+
+/// Decode an SSH identification line and/or cleartext key exchange message
+/// into `packet.headers["ssh"]`. `server_port` tells the client's segments
+/// from the server's.
+pub fn parse(data: &[u8], packet: &mut Packet, server_port: u16) -> Result<()> {
+    // Bare ACKs and other segments without data carry nothing of SSH
+    if data.is_empty() {
+        return Err(anyhow!("Empty SSH segment"));
+    }
+    
+    let mut ssh = json!({});
+    if packet.source_port == Some(server_port) {
+        ssh["role"] = json!("server");
+    } else if packet.destination_port == Some(server_port) {
+        ssh["role"] = json!("client");
+    }
+    
+    // The identification may share a segment with the first binary packet
+    let rest = match identification(data) {
+        Some((line, rest)) => {
+            decode_identification(line, &mut ssh)?;
+            rest
+        },
+        None => data,
+    };
+    
+    if !rest.is_empty() {
+        if let Err(e) = decode_packet(rest, &mut ssh) {
+            // Whatever follows an identification need not be a packet
+            if ssh.get("banner").is_none() {
+                return Err(e);
+            }
+        }
+    }
+    
+    packet.protocol = "SSH".to_string();
+    insert_header(packet, "ssh", ssh);
+    Ok(())
+}
+
+/// Find the `SSH-` identification line, returning it and the bytes after it
+fn identification(data: &[u8]) -> Option<(&str, &[u8])> {
+    let mut offset = 0;
+    for _ in 0..=MAX_PRELUDE_LINES {
+        let remaining = data.get(offset..).filter(|remaining| !remaining.is_empty())?;
+        let (line_len, next) = match remaining.iter().position(|&b| b == b'\n') {
+            Some(newline) => (newline, offset + newline + 1),
+            None => (remaining.len(), data.len()),
+        };
+        
+        let line = std::str::from_utf8(&remaining[..line_len]).ok()?.trim_end_matches('\r');
+        if line.starts_with("SSH-") {
+            return Some((line, &data[next..]));
+        }
+        offset = next;
+    }
+    None
+}
+
+/// Split `SSH-protoversion-softwareversion SP comments`
+fn decode_identification(line: &str, ssh: &mut Value) -> Result<()> {
+    if line.len() > MAX_IDENTIFICATION_LEN {
+        return Err(anyhow!("SSH identification too long: {} bytes", line.len()));
+    }
+    
+    let (protocol_version, software) = line[4..]
+        .split_once('-')
+        .ok_or_else(|| anyhow!("Invalid SSH identification '{}'", line))?;
+    let (software, comments) = match software.split_once(' ') {
+        Some((software, comments)) => (software, Some(comments)),
+        None => (software, None),
+    };
+    
+    ssh["banner"] = json!(line);
+    ssh["protocol_version"] = json!(protocol_version);
+    ssh["software"] = json!(software);
+    if let Some(comments) = comments {
+        ssh["comments"] = json!(comments);
+    }
+    Ok(())
+}
+
+/// Decode the header of a binary packet, and the algorithm lists of a KEXINIT
+fn decode_packet(data: &[u8], ssh: &mut Value) -> Result<()> {
+    if data.len() < PACKET_HEADER_LEN {
+        return Err(anyhow!("SSH packet too short: {} bytes", data.len()));
+    }
+    
+    let packet_length = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let padding_length = data[4];
+    let message_type = data[5];
+    if !(MIN_PACKET_LEN..=MAX_PACKET_LEN).contains(&packet_length) || padding_length < 4 || padding_length as u32 >= packet_length {
+        return Err(anyhow!("Not a cleartext SSH packet (length {}, padding {})", packet_length, padding_length));
+    }
+    let Some(name) = message_type_name(message_type) else {
+        return Err(anyhow!("Not a key exchange message (type {})", message_type));
+    };
+    
+    ssh["packet_length"] = json!(packet_length);
+    ssh["padding_length"] = json!(padding_length);
+    ssh["message_type"] = json!(message_type);
+    ssh["message_type_name"] = json!(name);
+    
+    if message_type != MSG_KEXINIT {
+        return Ok(());
+    }
+    
+    // Name-lists are a length and comma-separated names; decode as many as were captured
+    let end = (4 + packet_length as usize - padding_length as usize).min(data.len());
+    let mut offset = PACKET_HEADER_LEN + COOKIE_LEN;
+    for key in KEXINIT_LISTS {
+        let Some(len) = data.get(offset..offset + 4).map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize) else {
+            break;
+        };
+        let Some(list) = data.get(offset + 4..offset + 4 + len).filter(|_| offset + 4 + len <= end) else {
+            ssh["truncated"] = json!(true);
+            break;
+        };
+        
+        let names: Vec<&str> = std::str::from_utf8(list)
+            .map_err(|_| anyhow!("SSH {} is not ASCII", key))?
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .collect();
+        ssh[key] = json!(names);
+        offset += 4 + len;
+    }
+    if let Some(&follows) = data.get(offset).filter(|_| offset < end) {
+        ssh["first_kex_packet_follows"] = json!(follows != 0);
+    }
+    Ok(())
+}
+
+/// Name of a message sent before encryption starts; None for any other type
+pub fn message_type_name(message_type: u8) -> Option<&'static str> {
+    match message_type {
+        MSG_KEXINIT => Some("SSH_MSG_KEXINIT"),
+        21 => Some("SSH_MSG_NEWKEYS"),
+        30 => Some("SSH_MSG_KEX_ECDH_INIT"),
+        31 => Some("SSH_MSG_KEX_ECDH_REPLY"),
+        _ => None,
+    }
+}
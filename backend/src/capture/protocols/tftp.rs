@@ -0,0 +1,149 @@
+// TFTP (RFC 1350, with the option extension of RFC 2347)
+//
+// A client sends its read or write request to UDP port 69, but the server
+// answers from a fresh port, and the DATA/ACK exchange of the transfer runs
+// between that port and the client's. The parser therefore expects a
+// conversation between the client endpoint and the server address when it
+// sees a request (see `expected`), and decodes its datagrams too.
+//
+// A request may propose a `blksize` option (RFC 2348); it only applies once
+// the server's OACK acknowledges it, so the parser records the acknowledged
+// size in the expectation and passes it back in for the DATA that follows.
+
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// TFTP requests go to UDP port 69
+pub const TFTP_PORT: u16 = 69;
+
+/// Data blocks are this long unless the `blksize` option changed it; a shorter one ends the transfer
+pub const DEFAULT_BLOCK_SIZE: usize = 512;
+
+/// Range of block sizes the `blksize` option may agree on (RFC 2348)
+const BLOCK_SIZES: std::ops::RangeInclusive<usize> = 8..=65464;
+
+const OP_RRQ: u16 = 1;
+const OP_WRQ: u16 = 2;
+const OP_DATA: u16 = 3;
+const OP_ACK: u16 = 4;
+const OP_ERROR: u16 = 5;
+const OP_OACK: u16 = 6;
+
+/// Decode a TFTP message into `packet.headers["tftp"]`, judging DATA blocks
+/// against the transfer's `block_size`. Returns the opcode and, for an OACK,
+/// the block size it agrees on.
+pub fn parse(data: &[u8], packet: &mut Packet, block_size: usize) -> Result<(u16, Option<usize>)> {
+    if data.len() < 4 {
+        return Err(anyhow!("TFTP message too short: {} bytes", data.len()));
+    }
+    
+    let opcode = u16::from_be_bytes([data[0], data[1]]);
+    let opcode_name = opcode_name(opcode).ok_or_else(|| anyhow!("Unknown TFTP opcode {}", opcode))?;
+    let mut agreed_block_size = None;
+    let mut tftp = json!({
+        "opcode": opcode,
+        "opcode_name": opcode_name,
+    });
+    
+    match opcode {
+        OP_RRQ | OP_WRQ => {
+            let mut strings = strings(&data[2..])?.into_iter();
+            let (Some(filename), Some(mode)) = (strings.next(), strings.next()) else {
+                return Err(anyhow!("TFTP request without filename and mode"));
+            };
+            if !["netascii", "octet", "mail"].iter().any(|known| mode.eq_ignore_ascii_case(known)) {
+                return Err(anyhow!("Unknown TFTP transfer mode '{}'", mode));
+            }
+            
+            tftp["filename"] = json!(filename);
+            tftp["mode"] = json!(mode.to_ascii_lowercase());
+            let options = options(strings);
+            if !options.is_empty() {
+                tftp["options"] = Value::Object(options);
+            }
+        },
+        OP_DATA => {
+            let data_length = data.len() - 4;
+            tftp["block"] = json!(u16::from_be_bytes([data[2], data[3]]));
+            tftp["data_length"] = json!(data_length);
+            tftp["last_block"] = json!(data_length < block_size);
+        },
+        OP_ACK => {
+            tftp["block"] = json!(u16::from_be_bytes([data[2], data[3]]));
+        },
+        OP_ERROR => {
+            let error_code = u16::from_be_bytes([data[2], data[3]]);
+            tftp["error_code"] = json!(error_code);
+            tftp["error_name"] = json!(error_name(error_code));
+            tftp["error_message"] = json!(strings(&data[4..])?.first().copied().unwrap_or(""));
+        },
+        _ => {
+            let options = options(strings(&data[2..])?.into_iter());
+            agreed_block_size = options
+                .get("blksize")
+                .and_then(|value| value.as_str()?.parse().ok())
+                .filter(|size| BLOCK_SIZES.contains(size));
+            tftp["options"] = Value::Object(options);
+        },
+    }
+    
+    packet.protocol = "TFTP".to_string();
+    insert_header(packet, "tftp", tftp);
+    Ok((opcode, agreed_block_size))
+}
+
+/// Whether an opcode starts a transfer
+pub fn is_request(opcode: u16) -> bool {
+    matches!(opcode, OP_RRQ | OP_WRQ)
+}
+
+/// The NUL-terminated strings of a message
+fn strings(data: &[u8]) -> Result<Vec<&str>> {
+    let Some(body) = data.strip_suffix(&[0]) else {
+        return Err(anyhow!("TFTP string not NUL-terminated"));
+    };
+    body.split(|&b| b == 0)
+        .map(|s| std::str::from_utf8(s).map_err(|_| anyhow!("TFTP string is not text")))
+        .collect()
+}
+
+/// Option names and values, alternating
+fn options<'a>(mut strings: impl Iterator<Item = &'a str>) -> Map<String, Value> {
+    let mut options = Map::new();
+    while let (Some(name), Some(value)) = (strings.next(), strings.next()) {
+        options.insert(name.to_ascii_lowercase(), json!(value));
+    }
+    options
+}
+
+/// Name of a TFTP opcode
+pub fn opcode_name(opcode: u16) -> Option<&'static str> {
+    match opcode {
+        OP_RRQ => Some("Read Request"),
+        OP_WRQ => Some("Write Request"),
+        OP_DATA => Some("Data Packet"),
+        OP_ACK => Some("Acknowledgement"),
+        OP_ERROR => Some("Error Code"),
+        OP_OACK => Some("Option Acknowledgement"),
+        _ => None,
+    }
+}
+
+/// Name of a TFTP error code
+fn error_name(code: u16) -> &'static str {
+    match code {
+        0 => "Not defined",
+        1 => "File not found",
+        2 => "Access violation",
+        3 => "Disk full or allocation exceeded",
+        4 => "Illegal TFTP operation",
+        5 => "Unknown transfer ID",
+        6 => "File already exists",
+        7 => "No such user",
+        8 => "Option negotiation failed",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::{Result, anyhow};
+use serde_json::{json, Map, Value};
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Version, padding, length and the first presence bitmap
+const RADIOTAP_HEADER_LEN: usize = 8;
+
+/// Radiotap presence bits of the fields decoded here
+const RADIOTAP_FLAGS: u32 = 1;
+const RADIOTAP_RATE: u32 = 2;
+const RADIOTAP_CHANNEL: u32 = 3;
+const RADIOTAP_SIGNAL: u32 = 5;
+const RADIOTAP_NOISE: u32 = 6;
+
+/// Presence bit announcing another bitmap word
+const RADIOTAP_EXT: u32 = 31;
+
+/// Radiotap flag: the frame ends with its 4-byte FCS
+const RADIOTAP_FLAG_FCS: u8 = 0x10;
+
+/// Alignment and size of radiotap fields 0 to 6 (TSFT, flags, rate, channel,
+/// FHSS, signal, noise), which precede everything decoded here
+const RADIOTAP_FIELDS: [(usize, usize); 7] = [(8, 8), (1, 1), (1, 1), (2, 4), (2, 2), (1, 1), (1, 1)];
+
+/// 802.11 frame types
+const TYPE_MANAGEMENT: u8 = 0;
+const TYPE_CONTROL: u8 = 1;
+const TYPE_DATA: u8 = 2;
+
+/// Management subtypes whose bodies are decoded
+const SUBTYPE_ASSOC_REQUEST: u8 = 0;
+const SUBTYPE_ASSOC_RESPONSE: u8 = 1;
+const SUBTYPE_REASSOC_REQUEST: u8 = 2;
+const SUBTYPE_REASSOC_RESPONSE: u8 = 3;
+const SUBTYPE_PROBE_REQUEST: u8 = 4;
+const SUBTYPE_PROBE_RESPONSE: u8 = 5;
+const SUBTYPE_BEACON: u8 = 8;
+const SUBTYPE_DISASSOC: u8 = 10;
+const SUBTYPE_AUTH: u8 = 11;
+const SUBTYPE_DEAUTH: u8 = 12;
+
+/// Frame control, duration, three addresses and sequence control
+const MAC_HEADER_LEN: usize = 24;
+
+/// Information element IDs
+const IE_SSID: u8 = 0;
+const IE_DS_PARAMETER: u8 = 3;
+
+/// Capability bit set by networks that require encryption
+const CAPABILITY_PRIVACY: u16 = 0x0010;
+
+/// LLC/SNAP header that precedes an EtherType in data frames
+const LLC_SNAP_HEADER: [u8; 6] = [0xaa, 0xaa, 0x03, 0x00, 0x00, 0x00];
+
+/// Decode a radiotap header into `packet.headers["radiotap"]`, returning the
+/// 802.11 frame that follows it (without its FCS)
+pub fn parse_radiotap<'a>(data: &'a [u8], packet: &mut Packet) -> Result<&'a [u8]> {
+    if data.len() < RADIOTAP_HEADER_LEN || data[0] != 0 {
+        return Err(anyhow!("Not a radiotap header"));
+    }
+    let length = u16::from_le_bytes([data[2], data[3]]) as usize;
+    if length < RADIOTAP_HEADER_LEN || length > data.len() {
+        return Err(anyhow!("Radiotap length {} out of range", length));
+    }
+    
+    // Skip any extended presence bitmaps; only the first one is decoded
+    let present = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+    let mut offset = 4;
+    while offset + 4 <= length && u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) & (1 << RADIOTAP_EXT) != 0 {
+        offset += 4;
+    }
+    offset += 4;
+    
+    let mut header = Map::new();
+    header.insert("length".to_string(), json!(length));
+    header.insert("present".to_string(), json!(present));
+    
+    let mut flags = 0;
+    for (bit, &(align, size)) in RADIOTAP_FIELDS.iter().enumerate() {
+        let bit = bit as u32;
+        if present & (1 << bit) == 0 {
+            continue;
+        }
+        offset = offset.next_multiple_of(align);
+        if offset + size > length {
+            break;
+        }
+        let field = &data[offset..offset + size];
+        
+        match bit {
+            RADIOTAP_FLAGS => {
+                flags = field[0];
+                header.insert("flags".to_string(), json!(flags));
+            },
+            RADIOTAP_RATE => {
+                header.insert("data_rate_mbps".to_string(), json!(field[0] as f64 / 2.0));
+            },
+            RADIOTAP_CHANNEL => {
+                let frequency = u16::from_le_bytes([field[0], field[1]]);
+                header.insert("channel_frequency".to_string(), json!(frequency));
+                if let Some(channel) = channel_number(frequency) {
+                    header.insert("channel".to_string(), json!(channel));
+                }
+            },
+            RADIOTAP_SIGNAL => {
+                header.insert("signal_dbm".to_string(), json!(field[0] as i8));
+            },
+            RADIOTAP_NOISE => {
+                header.insert("noise_dbm".to_string(), json!(field[0] as i8));
+            },
+            _ => {},
+        }
+        offset += size;
+    }
+    
+    insert_header(packet, "radiotap", Value::Object(header));
+    
+    let mut frame = &data[length..];
+    if flags & RADIOTAP_FLAG_FCS != 0 {
+        frame = &frame[..frame.len().saturating_sub(4)];
+    }
+    Ok(frame)
+}
+
+/// Decode an 802.11 MAC header (and the body of management frames) into
+/// `packet.headers["wifi"]`.
+///
+/// Returns the payload of an unprotected data frame when it carries an
+/// LLC/SNAP EtherType, so the caller can decode the network layer.
+pub fn parse<'a>(data: &'a [u8], packet: &mut Packet) -> Result<Option<(u16, &'a [u8])>> {
+    if data.len() < 10 {
+        return Err(anyhow!("802.11 frame too short: {} bytes", data.len()));
+    }
+    
+    let frame_type = (data[0] >> 2) & 0x03;
+    let subtype = data[0] >> 4;
+    let flags = data[1];
+    let to_ds = flags & 0x01 != 0;
+    let from_ds = flags & 0x02 != 0;
+    let protected = flags & 0x40 != 0;
+    
+    let address = |offset: usize| data.get(offset..offset + 6).map(format_mac);
+    let receiver = address(4);
+    let transmitter = address(10);
+    
+    // Which address names the network depends on the direction to/from the distribution system
+    let bssid = match (frame_type, to_ds, from_ds) {
+        (TYPE_CONTROL, _, _) => None,
+        (_, false, false) => address(16),
+        (_, true, false) => receiver.clone(),
+        (_, false, true) => transmitter.clone(),
+        (_, true, true) => None,
+    };
+    
+    let mut header = Map::new();
+    header.insert("type".to_string(), json!(frame_type));
+    header.insert("subtype".to_string(), json!(subtype));
+    header.insert("type_name".to_string(), json!(type_name(frame_type)));
+    header.insert("subtype_name".to_string(), json!(subtype_name(frame_type, subtype)));
+    header.insert("to_ds".to_string(), json!(to_ds));
+    header.insert("from_ds".to_string(), json!(from_ds));
+    header.insert("retry".to_string(), json!(flags & 0x08 != 0));
+    header.insert("protected".to_string(), json!(protected));
+    header.insert("receiver".to_string(), json!(receiver));
+    header.insert("transmitter".to_string(), json!(transmitter));
+    header.insert("bssid".to_string(), json!(bssid));
+    
+    packet.protocol = "802.11".to_string();
+    packet.destination_mac = receiver;
+    packet.source_mac = transmitter;
+    
+    let mut network = None;
+    match frame_type {
+        TYPE_MANAGEMENT if data.len() >= MAC_HEADER_LEN => {
+            parse_management(subtype, &data[MAC_HEADER_LEN..], &mut header);
+        },
+        TYPE_DATA if !protected => {
+            // Four-address frames carry a fourth address; QoS data frames a QoS control field
+            let mut offset = MAC_HEADER_LEN;
+            if to_ds && from_ds {
+                offset += 6;
+            }
+            if subtype & 0x08 != 0 {
+                offset += 2;
+            }
+            let body = data.get(offset..).unwrap_or_default();
+            if body.len() >= LLC_SNAP_HEADER.len() + 2 && body.starts_with(&LLC_SNAP_HEADER) {
+                let ethertype = u16::from_be_bytes([body[6], body[7]]);
+                network = Some((ethertype, &body[8..]));
+            } else if !body.is_empty() {
+                packet.payload = Some(body.to_vec());
+            }
+        },
+        _ => {},
+    }
+    
+    insert_header(packet, "wifi", Value::Object(header));
+    Ok(network)
+}
+
+/// Decode the fixed fields and information elements of a management frame body
+fn parse_management(subtype: u8, body: &[u8], header: &mut Map<String, Value>) {
+    let le16 = |offset: usize| body.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    
+    // Length of the fixed fields preceding the information elements
+    let fixed = match subtype {
+        SUBTYPE_BEACON | SUBTYPE_PROBE_RESPONSE => {
+            header.insert("beacon_interval".to_string(), json!(le16(8)));
+            if let Some(capabilities) = le16(10) {
+                header.insert("capabilities".to_string(), json!(capabilities));
+                header.insert("privacy".to_string(), json!(capabilities & CAPABILITY_PRIVACY != 0));
+            }
+            12
+        },
+        SUBTYPE_ASSOC_REQUEST => 4,
+        SUBTYPE_REASSOC_REQUEST => 10,
+        SUBTYPE_ASSOC_RESPONSE | SUBTYPE_REASSOC_RESPONSE => {
+            header.insert("status_code".to_string(), json!(le16(2)));
+            header.insert("association_id".to_string(), json!(le16(4).map(|aid| aid & 0x3fff)));
+            6
+        },
+        SUBTYPE_PROBE_REQUEST => 0,
+        SUBTYPE_AUTH => {
+            header.insert("auth_algorithm".to_string(), json!(le16(0)));
+            header.insert("auth_sequence".to_string(), json!(le16(2)));
+            header.insert("status_code".to_string(), json!(le16(4)));
+            return;
+        },
+        SUBTYPE_DISASSOC | SUBTYPE_DEAUTH => {
+            header.insert("reason_code".to_string(), json!(le16(0)));
+            return;
+        },
+        _ => return,
+    };
+    
+    let mut elements = body.get(fixed..).unwrap_or_default();
+    while elements.len() >= 2 {
+        let (id, len) = (elements[0], elements[1] as usize);
+        let Some(value) = elements.get(2..2 + len) else { break };
+        
+        match id {
+            IE_SSID => {
+                header.insert("ssid".to_string(), json!(String::from_utf8_lossy(value)));
+                header.insert("hidden_ssid".to_string(), json!(value.iter().all(|&b| b == 0)));
+            },
+            IE_DS_PARAMETER if len == 1 => {
+                header.insert("channel".to_string(), json!(value[0]));
+            },
+            _ => {},
+        }
+        elements = &elements[2 + len..];
+    }
+}
+
+/// Channel number of a 2.4, 5 or 6 GHz frequency in MHz
+pub fn channel_number(frequency: u16) -> Option<u16> {
+    match frequency {
+        2484 => Some(14),
+        2412..=2472 => Some((frequency - 2407) / 5),
+        5955..=7115 => Some((frequency - 5950) / 5),
+        5000..=5900 => Some((frequency - 5000) / 5),
+        _ => None,
+    }
+}
+
+fn type_name(frame_type: u8) -> &'static str {
+    match frame_type {
+        TYPE_MANAGEMENT => "Management",
+        TYPE_CONTROL => "Control",
+        TYPE_DATA => "Data",
+        _ => "Extension",
+    }
+}
+
+/// Name of a frame subtype, e.g. "Beacon" or "QoS Data"
+pub fn subtype_name(frame_type: u8, subtype: u8) -> &'static str {
+    match (frame_type, subtype) {
+        (TYPE_MANAGEMENT, SUBTYPE_ASSOC_REQUEST) => "Association Request",
+        (TYPE_MANAGEMENT, SUBTYPE_ASSOC_RESPONSE) => "Association Response",
+        (TYPE_MANAGEMENT, SUBTYPE_REASSOC_REQUEST) => "Reassociation Request",
+        (TYPE_MANAGEMENT, SUBTYPE_REASSOC_RESPONSE) => "Reassociation Response",
+        (TYPE_MANAGEMENT, SUBTYPE_PROBE_REQUEST) => "Probe Request",
+        (TYPE_MANAGEMENT, SUBTYPE_PROBE_RESPONSE) => "Probe Response",
+        (TYPE_MANAGEMENT, 6) => "Timing Advertisement",
+        (TYPE_MANAGEMENT, SUBTYPE_BEACON) => "Beacon",
+        (TYPE_MANAGEMENT, 9) => "ATIM",
+        (TYPE_MANAGEMENT, SUBTYPE_DISASSOC) => "Disassociation",
+        (TYPE_MANAGEMENT, SUBTYPE_AUTH) => "Authentication",
+        (TYPE_MANAGEMENT, SUBTYPE_DEAUTH) => "Deauthentication",
+        (TYPE_MANAGEMENT, 13) => "Action",
+        (TYPE_MANAGEMENT, 14) => "Action No Ack",
+        (TYPE_CONTROL, 8) => "Block Ack Request",
+        (TYPE_CONTROL, 9) => "Block Ack",
+        (TYPE_CONTROL, 10) => "PS-Poll",
+        (TYPE_CONTROL, 11) => "RTS",
+        (TYPE_CONTROL, 12) => "CTS",
+        (TYPE_CONTROL, 13) => "ACK",
+        (TYPE_DATA, 0) => "Data",
+        (TYPE_DATA, 4) => "Null",
+        (TYPE_DATA, 8) => "QoS Data",
+        (TYPE_DATA, 12) => "QoS Null",
+        _ => "Other",
+    }
+}
+
+fn format_mac(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
@@ -0,0 +1,54 @@
+use anyhow::{Result, anyhow};
+use serde_json::json;
+
+use crate::capture::protocols::insert_header;
+use crate::models::packet::Packet;
+
+/// Magic packets are usually sent to the echo (7) or discard (9) UDP port
+pub const WOL_PORTS: [u16; 2] = [7, 9];
+
+/// EtherType of magic packets sent as raw Ethernet frames
+pub const WOL_ETHERTYPE: u16 = 0x0842;
+
+/// Synchronization stream that starts a magic packet
+const SYNC_STREAM: [u8; 6] = [0xff; 6];
+
+/// Times the target MAC address is repeated after the synchronization stream
+const MAC_REPETITIONS: usize = 16;
+
+/// Synchronization stream plus the repeated MAC address
+const MAGIC_LEN: usize = SYNC_STREAM.len() + 6 * MAC_REPETITIONS;
+
+/// Decode a Wake-on-LAN magic packet into `packet.headers["wol"]`.
+///
+/// The magic sequence may sit anywhere in the payload; a SecureOn password
+/// (4 or 6 bytes) may follow it.
+pub fn parse(data: &[u8], packet: &mut Packet) -> Result<()> {
+    let start = (0..=data.len().saturating_sub(MAGIC_LEN))
+        .find(|&offset| is_magic(&data[offset..]))
+        .ok_or_else(|| anyhow!("No magic packet sequence in {} bytes", data.len()))?;
+    
+    let target = &data[start + SYNC_STREAM.len()..start + SYNC_STREAM.len() + 6];
+    let target_mac = target.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":");
+    let trailing = data.len() - start - MAGIC_LEN;
+    
+    packet.protocol = "WOL".to_string();
+    
+    insert_header(packet, "wol", json!({
+        "target_mac": target_mac,
+        "offset": start,
+        "secureon_password": matches!(trailing, 4 | 6),
+    }));
+    
+    Ok(())
+}
+
+/// Whether the data starts with the synchronization stream followed by 16 copies of one MAC address
+fn is_magic(data: &[u8]) -> bool {
+    if data.len() < MAGIC_LEN || !data.starts_with(&SYNC_STREAM) {
+        return false;
+    }
+    
+    let target = &data[SYNC_STREAM.len()..SYNC_STREAM.len() + 6];
+    data[SYNC_STREAM.len()..MAGIC_LEN].chunks_exact(6).all(|mac| mac == target)
+}
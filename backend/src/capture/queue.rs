@@ -0,0 +1,168 @@
+// Bounded hand-off of captured frames from the capture thread to the packet processor
+//
+// When the processor falls behind, something has to give. Blocking the
+// capture thread keeps every frame that reaches us but pushes the loss into
+// the kernel, where it goes unnoticed; the drop policies keep the capture
+// thread reading and count what they discard instead.
+
+use chrono::{DateTime, Utc};
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::models::config::OverflowPolicy;
+
+/// A raw captured frame, its capture time and, when a bond or bridge is
+/// captured on its members, the member it was captured on
+pub type Frame = (Vec<u8>, DateTime<Utc>, Option<Arc<str>>);
+
+/// State shared by both ends of the queue
+struct Shared {
+    frames: Mutex<VecDeque<Frame>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    
+    /// Frames discarded because the queue was full
+    dropped: AtomicUsize,
+    
+    /// Wakes the processor when a frame is queued or the sender goes away
+    not_empty: Notify,
+    
+    /// Wakes a blocked sender when the processor takes a frame
+    not_full: Condvar,
+    
+    /// Live senders and receivers; the queue is closed when either reaches zero
+    senders: AtomicUsize,
+    receivers: AtomicUsize,
+}
+
+/// Sending end, used from the (blocking) capture thread
+pub struct FrameSender {
+    shared: Arc<Shared>,
+}
+
+/// Receiving end, used by the async packet processor
+pub struct FrameReceiver {
+    shared: Arc<Shared>,
+}
+
+/// The processor has gone away
+#[derive(Debug)]
+pub struct QueueClosed;
+
+impl std::fmt::Display for QueueClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "packet queue closed")
+    }
+}
+
+/// Create a queue holding up to `capacity` frames
+pub fn channel(capacity: usize, policy: OverflowPolicy) -> (FrameSender, FrameReceiver) {
+    let shared = Arc::new(Shared {
+        frames: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity: capacity.max(1),
+        policy,
+        dropped: AtomicUsize::new(0),
+        not_empty: Notify::new(),
+        not_full: Condvar::new(),
+        senders: AtomicUsize::new(1),
+        receivers: AtomicUsize::new(1),
+    });
+    
+    (FrameSender { shared: shared.clone() }, FrameReceiver { shared })
+}
+
+impl FrameSender {
+    /// Queue a frame, applying the overflow policy when the queue is full.
+    ///
+    /// Only blocks under `OverflowPolicy::Block`; must not be called from async code.
+    pub fn send(&self, frame: Frame) -> Result<(), QueueClosed> {
+        let shared = &self.shared;
+        let mut frames = shared.frames.lock();
+        
+        loop {
+            if shared.receivers.load(Ordering::Acquire) == 0 {
+                return Err(QueueClosed);
+            }
+            if frames.len() < shared.capacity {
+                break;
+            }
+            
+            match shared.policy {
+                OverflowPolicy::Block => shared.not_full.wait(&mut frames),
+                OverflowPolicy::DropNewest => {
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                },
+                OverflowPolicy::DropOldest => {
+                    frames.pop_front();
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    break;
+                },
+            }
+        }
+        
+        frames.push_back(frame);
+        drop(frames);
+        shared.not_empty.notify_one();
+        Ok(())
+    }
+}
+
+impl Clone for FrameSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.not_empty.notify_one();
+        }
+    }
+}
+
+impl FrameReceiver {
+    /// Wait for the next frame; None once every sender is gone and the queue is drained
+    pub async fn recv(&mut self) -> Option<Frame> {
+        let shared = &self.shared;
+        
+        loop {
+            // Register for a wake-up before checking, so a send in between is not missed
+            let notified = shared.not_empty.notified();
+            
+            {
+                let mut frames = shared.frames.lock();
+                if let Some(frame) = frames.pop_front() {
+                    drop(frames);
+                    shared.not_full.notify_one();
+                    return Some(frame);
+                }
+                if shared.senders.load(Ordering::Acquire) == 0 {
+                    return None;
+                }
+            }
+            
+            notified.await;
+        }
+    }
+    
+    /// Number of frames discarded by the overflow policy so far
+    pub fn dropped(&self) -> usize {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        self.shared.receivers.fetch_sub(1, Ordering::AcqRel);
+        
+        // Release a sender blocked on a full queue
+        let _frames = self.shared.frames.lock();
+        self.shared.not_full.notify_all();
+    }
+}
@@ -0,0 +1,75 @@
+// Packet and byte rates over the last minute
+//
+// `packet_rate` in the stats is averaged over the whole capture, so it cannot
+// show whether traffic is picking up or dying down. Once a second the totals
+// are sampled and their change since the previous sample is kept, giving a
+// short wall-clock series for a trend sparkline. Unlike the stats history,
+// which buckets packets by their timestamps, seconds without traffic are
+// recorded as zero right away.
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::models::stats::RateTrend;
+
+/// Number of one-second samples retained
+pub const RATE_SECONDS: usize = 60;
+
+#[derive(Default)]
+struct Samples {
+    /// Packet and byte totals at the previous tick
+    last_totals: Option<(usize, usize)>,
+    
+    /// Packets and bytes per second, oldest first
+    seconds: VecDeque<(usize, usize)>,
+}
+
+/// Ring of the last `RATE_SECONDS` per-second packet and byte counts
+#[derive(Clone, Default)]
+pub struct RateHistory {
+    samples: Arc<Mutex<Samples>>,
+}
+
+impl RateHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record the change in totals since the previous tick (call once a second)
+    pub fn tick(&self, total_packets: usize, total_bytes: usize) {
+        let mut samples = self.samples.lock();
+        
+        // The first tick only sets the baseline; totals going back are a stale
+        // read of the stats (resets clear the history), so they are skipped
+        let Some((packets, bytes)) = samples.last_totals else {
+            samples.last_totals = Some((total_packets, total_bytes));
+            return;
+        };
+        if total_packets < packets || total_bytes < bytes {
+            return;
+        }
+        samples.last_totals = Some((total_packets, total_bytes));
+        
+        samples.seconds.push_back((total_packets - packets, total_bytes - bytes));
+        if samples.seconds.len() > RATE_SECONDS {
+            samples.seconds.pop_front();
+        }
+    }
+    
+    /// The recorded rates, oldest first
+    pub fn trend(&self) -> RateTrend {
+        let samples = self.samples.lock();
+        RateTrend {
+            packets_per_second: samples.seconds.iter().map(|&(packets, _)| packets).collect(),
+            bytes_per_second: samples.seconds.iter().map(|&(_, bytes)| bytes).collect(),
+        }
+    }
+    
+    /// Forget the samples, as when a new capture starts
+    pub fn clear(&self) {
+        let mut samples = self.samples.lock();
+        samples.last_totals = None;
+        samples.seconds.clear();
+    }
+}
@@ -0,0 +1,218 @@
+use dashmap::DashMap;
+use log::{debug, trace};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+use crate::capture::detail_cache::DetailCache;
+use crate::capture::packet_store::PacketStore;
+use crate::models::packet::Packet;
+
+/// Maximum number of reverse lookups in flight at once
+const MAX_CONCURRENT_LOOKUPS: usize = 8;
+
+/// How long a lookup may take before it is treated as a failure
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a resolved hostname stays cached
+const POSITIVE_TTL: Duration = Duration::from_secs(3600);
+
+/// How long a failed lookup is remembered before retrying
+const NEGATIVE_TTL: Duration = Duration::from_secs(300);
+
+/// Packets backfilled per address once its lookup completes; older ones
+/// waiting on it keep the address without a name
+const MAX_BACKFILL_PACKETS: usize = 1000;
+
+/// Metadata key for the resolved source hostname
+pub const SRC_HOST_KEY: &str = "src_host";
+
+/// Metadata key for the resolved destination hostname
+pub const DST_HOST_KEY: &str = "dst_host";
+
+/// A cached lookup result (`None` means the address has no name)
+#[derive(Clone)]
+struct CacheEntry {
+    name: Option<String>,
+    expires: Instant,
+}
+
+/// Asynchronous reverse-DNS resolver with a positive and negative cache.
+///
+/// Lookups never run on the capture path: unknown addresses are queued and
+/// resolved by a background task, which backfills the host names into the
+/// packets that were stored while the lookup was pending.
+#[derive(Clone)]
+pub struct NameResolver {
+    /// Resolved (or failed) addresses
+    cache: Arc<DashMap<IpAddr, CacheEntry>>,
+    
+    /// The newest packets waiting for an address to resolve
+    pending: Arc<DashMap<IpAddr, VecDeque<u64>>>,
+    
+    /// Addresses cached or pending at once (0 for no cap)
+    max_addresses: usize,
+    
+    /// Queue of addresses to resolve
+    tx: mpsc::UnboundedSender<IpAddr>,
+}
+
+impl NameResolver {
+    /// Create a resolver and start its background worker.
+    ///
+    /// Resolved names are written into the matching packets in `packets`, and
+    /// their cached details in `details` are dropped. At most `max_addresses`
+    /// (0 for no cap) are cached, and as many wait for a lookup.
+    pub fn new(packets: Arc<PacketStore>, details: DetailCache, max_addresses: usize) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let resolver = Self {
+            cache: Arc::new(DashMap::new()),
+            pending: Arc::new(DashMap::new()),
+            max_addresses,
+            tx,
+        };
+        
+        tokio::spawn(resolver.clone().run(rx, packets, details));
+        resolver
+    }
+    
+    /// Get the cached hostname for an address, if it has been resolved
+    pub fn cached(&self, ip: &IpAddr) -> Option<String> {
+        self.cache
+            .get(ip)
+            .filter(|entry| entry.expires > Instant::now())
+            .and_then(|entry| entry.name.clone())
+    }
+    
+    /// Add known hostnames to a packet's metadata and queue lookups for the rest.
+    ///
+    /// The packet must already have its final ID so it can be backfilled later.
+    pub fn annotate(&self, packet: &mut Packet) {
+        let addresses = [
+            (packet.source_ip, SRC_HOST_KEY),
+            (packet.destination_ip, DST_HOST_KEY),
+        ];
+        
+        for (ip, key) in addresses {
+            let Some(ip) = ip else { continue };
+            
+            match self.cache.get(&ip).map(|entry| entry.clone()) {
+                Some(entry) if entry.expires > Instant::now() => {
+                    if let Some(name) = entry.name {
+                        set_host(packet, key, name);
+                    }
+                },
+                _ => self.request(ip, packet.id),
+            }
+        }
+    }
+    
+    /// Register a packet as waiting on an address, queueing the lookup if it is new
+    fn request(&self, ip: IpAddr, packet_id: u64) {
+        if self.max_addresses > 0 && self.pending.len() >= self.max_addresses && !self.pending.contains_key(&ip) {
+            return;
+        }
+        
+        let mut waiting = self.pending.entry(ip).or_default();
+        if waiting.is_empty() && self.tx.send(ip).is_err() {
+            return;
+        }
+        if waiting.len() == MAX_BACKFILL_PACKETS {
+            waiting.pop_front();
+        }
+        waiting.push_back(packet_id);
+    }
+    
+    /// Cache a lookup result, making room past the cap by dropping expired
+    /// entries, then the one closest to expiring
+    fn store(&self, ip: IpAddr, entry: CacheEntry) {
+        if self.max_addresses > 0 && self.cache.len() >= self.max_addresses && !self.cache.contains_key(&ip) {
+            let now = Instant::now();
+            self.cache.retain(|_, entry| entry.expires > now);
+            if self.cache.len() >= self.max_addresses {
+                let oldest = self.cache.iter().min_by_key(|entry| entry.expires).map(|entry| *entry.key());
+                if let Some(oldest) = oldest {
+                    self.cache.remove(&oldest);
+                }
+            }
+        }
+        self.cache.insert(ip, entry);
+    }
+    
+    /// Background worker: resolve queued addresses with bounded concurrency
+    async fn run(self, mut rx: mpsc::UnboundedReceiver<IpAddr>, packets: Arc<PacketStore>, details: DetailCache) {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_LOOKUPS));
+        
+        while let Some(ip) = rx.recv().await {
+            let Ok(permit) = permits.clone().acquire_owned().await else { break };
+            let resolver = self.clone();
+            let packets = packets.clone();
+            let details = details.clone();
+            
+            tokio::spawn(async move {
+                let name = lookup(ip, permit).await;
+                
+                let ttl = if name.is_some() { POSITIVE_TTL } else { NEGATIVE_TTL };
+                resolver.store(ip, CacheEntry {
+                    name: name.clone(),
+                    expires: Instant::now() + ttl,
+                });
+                
+                // Backfill the packets that arrived while the lookup was pending
+                let waiting = resolver.pending.remove(&ip).map(|(_, ids)| ids).unwrap_or_default();
+                let Some(name) = name else { return };
+                
+                for id in waiting {
+                    let Some(mut packet) = packets.get_mut(&id) else { continue };
+                    if packet.source_ip == Some(ip) {
+                        set_host(&mut packet, SRC_HOST_KEY, name.clone());
+                    }
+                    if packet.destination_ip == Some(ip) {
+                        set_host(&mut packet, DST_HOST_KEY, name.clone());
+                    }
+                    drop(packet);
+                    details.invalidate(id);
+                }
+            });
+        }
+        
+        debug!("Name resolver stopped");
+    }
+}
+
+/// Perform a single blocking reverse lookup off the async runtime. The permit
+/// is held by the blocking call, not the wait for it, so lookups that time out
+/// still count against the limit until getnameinfo returns.
+async fn lookup(ip: IpAddr, permit: OwnedSemaphorePermit) -> Option<String> {
+    let task = tokio::task::spawn_blocking(move || {
+        let name = dns_lookup::lookup_addr(&ip);
+        drop(permit);
+        name
+    });
+    
+    match tokio::time::timeout(LOOKUP_TIMEOUT, task).await {
+        // getnameinfo falls back to the numeric form when there is no PTR record
+        Ok(Ok(Ok(name))) if name != ip.to_string() => Some(name),
+        Ok(Ok(Err(e))) => {
+            trace!("Reverse lookup of {} failed: {}", ip, e);
+            None
+        },
+        Err(_) => {
+            trace!("Reverse lookup of {} timed out", ip);
+            None
+        },
+        _ => None,
+    }
+}
+
+/// Store a hostname in a packet's metadata
+fn set_host(packet: &mut Packet, key: &str, name: String) {
+    if !packet.metadata.is_object() {
+        packet.metadata = serde_json::json!({});
+    }
+    if let Some(metadata) = packet.metadata.as_object_mut() {
+        metadata.insert(key.to_string(), serde_json::Value::String(name));
+    }
+}
@@ -0,0 +1,168 @@
+// RTP streams: packet loss and jitter per synchronization source
+//
+// Each SSRC sending from one endpoint to another is a stream. Loss is counted
+// as RFC 3550 (appendix A.3) counts it: the packets expected from the first
+// and the extended highest sequence numbers received, less those received, so
+// late packets are not lost and duplicates cannot make the count negative.
+// Jitter is the interarrival jitter of appendix A.8, the smoothed variation
+// in transit time, with capture timestamps as arrival times (so loaded files
+// work the same) and the payload type's clock rate converting RTP timestamps;
+// streams of dynamic payload types, whose rate the signalling alone knows,
+// report none.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::capture::conversations;
+use crate::capture::protocols::rtp;
+use crate::models::packet::Packet;
+use crate::models::rtp::RtpStream;
+
+/// Maximum number of streams tracked; new streams are ignored beyond this
+const MAX_STREAMS: usize = 1024;
+
+/// Comfort noise, sent during silence in place of the stream's codec
+const PAYLOAD_TYPE_CN: u8 = 13;
+
+/// Sequence numbers further ahead than this count as a late packet of the previous cycle
+const MAX_SEQUENCE_JUMP: u16 = 0x8000;
+
+/// Source, destination and SSRC of a stream
+type StreamKey = (SocketAddr, SocketAddr, u32);
+
+/// A stream and its sequence and timing state while it is being tracked
+struct StreamState {
+    stream: RtpStream,
+    
+    /// First sequence number received, and the highest, extended with its wrap-arounds
+    base_sequence: u32,
+    highest_sequence: u32,
+    
+    /// Arrival time (seconds) and RTP timestamp of the previous packet
+    previous: Option<(f64, u32)>,
+    
+    /// Jitter in RTP timestamp units
+    jitter: f64,
+    max_jitter: f64,
+}
+
+impl StreamState {
+    fn add(&mut self, packet: &Packet, payload_type: u8, sequence: u16, timestamp: u32) {
+        let stream = &mut self.stream;
+        stream.packets += 1;
+        stream.first_seen = stream.first_seen.min(packet.timestamp);
+        stream.last_seen = stream.last_seen.max(packet.timestamp);
+        if payload_type != PAYLOAD_TYPE_CN && payload_type != stream.payload_type {
+            stream.payload_type = payload_type;
+            stream.codec = rtp::codec(payload_type).unwrap_or_default().to_string();
+        }
+        
+        let ahead = sequence.wrapping_sub(self.highest_sequence as u16);
+        if ahead == 0 || ahead >= MAX_SEQUENCE_JUMP {
+            stream.out_of_order += 1;
+        } else {
+            self.highest_sequence += ahead as u32;
+        }
+        stream.expected_packets = (self.highest_sequence - self.base_sequence) as u64 + 1;
+        stream.lost_packets = stream.expected_packets.saturating_sub(stream.packets as u64);
+        stream.loss_percent = stream.lost_packets as f64 * 100.0 / stream.expected_packets as f64;
+        
+        let Some(rate) = rtp::clock_rate(stream.payload_type) else { return };
+        let arrival = packet.timestamp.timestamp_micros() as f64 / 1_000_000.0;
+        if let Some((previous_arrival, previous_timestamp)) = self.previous {
+            // Difference of the transit times, in timestamp units; RTP timestamps wrap
+            let difference = (arrival - previous_arrival) * rate as f64 - timestamp.wrapping_sub(previous_timestamp) as i32 as f64;
+            self.jitter += (difference.abs() - self.jitter) / 16.0;
+            self.max_jitter = self.max_jitter.max(self.jitter);
+            stream.jitter_ms = Some(self.jitter * 1000.0 / rate as f64);
+            stream.max_jitter_ms = Some(self.max_jitter * 1000.0 / rate as f64);
+        }
+        self.previous = Some((arrival, timestamp));
+    }
+}
+
+/// Tracks RTP streams decoded from the captured packets
+#[derive(Clone, Default)]
+pub struct RtpStreams {
+    streams: Arc<Mutex<HashMap<StreamKey, StreamState>>>,
+}
+
+impl RtpStreams {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Forget all streams
+    pub fn clear(&self) {
+        self.streams.lock().clear();
+    }
+    
+    /// Account for a packet in its stream, if it was decoded as RTP
+    pub fn record(&self, packet: &Packet) {
+        let rtp = &packet.headers["rtp"];
+        let (Some(payload_type), Some(sequence), Some(timestamp), Some(ssrc)) = (
+            rtp["payload_type"].as_u64(),
+            rtp["sequence"].as_u64(),
+            rtp["timestamp"].as_u64(),
+            rtp["ssrc"].as_u64(),
+        ) else {
+            return;
+        };
+        let (Some(source_ip), Some(source_port), Some(destination_ip), Some(destination_port)) =
+            (packet.source_ip, packet.source_port, packet.destination_ip, packet.destination_port)
+        else {
+            return;
+        };
+        let (payload_type, sequence, timestamp) = (payload_type as u8, sequence as u16, timestamp as u32);
+        let source = SocketAddr::new(source_ip, source_port);
+        let destination = SocketAddr::new(destination_ip, destination_port);
+        
+        let mut streams = self.streams.lock();
+        let key = (source, destination, ssrc as u32);
+        if let Some(state) = streams.get_mut(&key) {
+            state.add(packet, payload_type, sequence, timestamp);
+            return;
+        }
+        if streams.len() >= MAX_STREAMS {
+            return;
+        }
+        
+        let mut state = StreamState {
+            stream: RtpStream {
+                ssrc: ssrc as u32,
+                source: source.to_string(),
+                destination: destination.to_string(),
+                conversation_id: conversations::conversation_id(packet),
+                payload_type,
+                codec: rtp::codec(payload_type).unwrap_or_default().to_string(),
+                packets: 0,
+                expected_packets: 0,
+                lost_packets: 0,
+                loss_percent: 0.0,
+                out_of_order: 0,
+                jitter_ms: None,
+                max_jitter_ms: None,
+                first_seen: packet.timestamp,
+                last_seen: packet.timestamp,
+            },
+            // One behind the first packet, which then advances it; offset by a cycle so that cannot underflow
+            base_sequence: sequence as u32 + 0x10000,
+            highest_sequence: sequence as u32 + 0x10000 - 1,
+            previous: None,
+            jitter: 0.0,
+            max_jitter: 0.0,
+        };
+        state.add(packet, payload_type, sequence, timestamp);
+        streams.insert(key, state);
+    }
+    
+    /// The streams, earliest first
+    pub fn list(&self) -> Vec<RtpStream> {
+        let mut streams: Vec<RtpStream> = self.streams.lock().values().map(|state| state.stream.clone()).collect();
+        streams.sort_by(|a, b| a.first_seen.cmp(&b.first_seen).then(a.ssrc.cmp(&b.ssrc)));
+        streams
+    }
+}
@@ -0,0 +1,716 @@
+// Parser self-test (`--self-test`)
+//
+// Decodes a built-in sample frame for every protocol in the registry and checks
+// the packet's `protocol` label and a few key fields, so a build can be verified
+// without an interface, a capture file or capture privileges. Samples are
+// assembled from their layers here rather than stored as hex dumps, which keeps
+// the interesting bytes readable next to the fields they should decode to.
+
+use pcap::Linktype;
+use serde_json::Value;
+
+use crate::capture::parser::PacketParser;
+use crate::capture::protocols::{registry, DECODERS};
+
+const CLIENT_MAC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+const SERVER_MAC: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x02];
+const CLIENT_IP: [u8; 4] = [192, 0, 2, 1];
+const SERVER_IP: [u8; 4] = [192, 0, 2, 2];
+
+/// A sample frame and what decoding it must produce
+struct Sample {
+    /// Registry protocol the sample exercises
+    name: &'static str,
+    link_type: Linktype,
+    frame: fn() -> Vec<u8>,
+    
+    /// Frames decoded first, for a conversation another protocol's messages announce
+    setup: &'static [fn() -> Vec<u8>],
+    
+    /// Expected `protocol` label of the decoded packet
+    protocol: &'static str,
+    
+    /// JSON pointers into the decoded packet and their expected values (strings as is, others as JSON)
+    fields: &'static [(&'static str, &'static str)],
+}
+
+const SAMPLES: &[Sample] = &[
+    Sample {
+        name: "Ethernet",
+        link_type: Linktype::ETHERNET,
+        frame: arp_frame,
+        setup: &[],
+        protocol: "ARP",
+        fields: &[
+            ("/source_mac", "02:00:00:00:00:01"),
+            ("/headers/ethernet/destination_mac", "ff:ff:ff:ff:ff:ff"),
+        ],
+    },
+    Sample {
+        name: "Radiotap",
+        link_type: Linktype::IEEE802_11_RADIOTAP,
+        frame: || radiotap(&beacon()),
+        setup: &[],
+        protocol: "802.11",
+        fields: &[("/headers/radiotap/channel_frequency", "2437"), ("/headers/radiotap/channel", "6")],
+    },
+    Sample {
+        name: "802.11",
+        link_type: Linktype::IEEE802_11,
+        frame: beacon,
+        setup: &[],
+        protocol: "802.11",
+        fields: &[("/headers/wifi/subtype_name", "Beacon"), ("/headers/wifi/ssid", "rustshark")],
+    },
+    Sample {
+        name: "SLL",
+        link_type: Linktype::LINUX_SLL,
+        frame: || sll(&ipv4(IP_UDP, &udp(40000, 5000, b"hello"))),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[
+            ("/headers/sll/packet_type_name", "sent by us"),
+            ("/headers/sll/source_address", "02:00:00:00:00:01"),
+            ("/source_ip", "192.0.2.1"),
+        ],
+    },
+    Sample {
+        name: "Loopback",
+        link_type: Linktype::NULL,
+        frame: || loopback(&ipv4(IP_UDP, &udp(40000, 5000, b"hello"))),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[("/headers/loopback/family", "2"), ("/destination_port", "5000")],
+    },
+    Sample {
+        name: "PPPoE",
+        link_type: Linktype::ETHERNET,
+        frame: pppoe_frame,
+        setup: &[],
+        protocol: "PPPoE",
+        fields: &[("/headers/pppoe/code_name", "PADI"), ("/headers/pppoe/service_name", "internet")],
+    },
+    Sample {
+        name: "ARP",
+        link_type: Linktype::ETHERNET,
+        frame: arp_frame,
+        setup: &[],
+        protocol: "ARP",
+        fields: &[("/headers/arp/sender_proto_addr", "192.0.2.1"), ("/headers/arp/target_proto_addr", "192.0.2.2")],
+    },
+    Sample {
+        name: "IPv4",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 5000, b"hello")),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[("/source_ip", "192.0.2.1"), ("/headers/ipv4/ttl", "64")],
+    },
+    Sample {
+        name: "IPv6",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv6(IP_UDP, &udp(40000, 5000, b"hello")),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[("/source_ip", "2001:db8::1"), ("/headers/ipv6/hop_limit", "64")],
+    },
+    Sample {
+        name: "TCP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_TCP, &tcp(40000, 80, &[])),
+        setup: &[],
+        protocol: "TCP",
+        fields: &[("/destination_port", "80"), ("/headers/tcp/flags/syn", "true")],
+    },
+    Sample {
+        name: "UDP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 5000, b"hello")),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[("/source_port", "40000"), ("/headers/udp/length", "13")],
+    },
+    Sample {
+        name: "SCTP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_SCTP, &sctp()),
+        setup: &[],
+        protocol: "SCTP",
+        fields: &[("/destination_port", "2905"), ("/headers/sctp/verification_tag", "305419896")],
+    },
+    Sample {
+        name: "ICMP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_ICMP, &[8, 0, 0, 0, 0, 1, 0, 1]),
+        setup: &[],
+        protocol: "ICMP",
+        fields: &[("/headers/icmp/icmp_type", "8")],
+    },
+    Sample {
+        name: "CDP",
+        link_type: Linktype::ETHERNET,
+        frame: cdp_frame,
+        setup: &[],
+        protocol: "CDP",
+        fields: &[("/headers/cdp/device_id", "switch1"), ("/headers/cdp/port_id", "Gi0/1")],
+    },
+    Sample {
+        name: "DIAMETER",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_TCP, &tcp(40000, 3868, &diameter())),
+        setup: &[],
+        protocol: "Diameter",
+        fields: &[("/headers/diameter/command_code", "257")],
+    },
+    Sample {
+        name: "DNS",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 53, &dns_query())),
+        setup: &[],
+        protocol: "DNS",
+        fields: &[("/headers/dns/query_name", "example.com"), ("/headers/dns/query_type", "1")],
+    },
+    Sample {
+        name: "FTP",
+        link_type: Linktype::ETHERNET,
+        frame: ftp_port_command,
+        setup: &[],
+        protocol: "FTP",
+        fields: &[
+            ("/headers/ftp/command", "PORT"),
+            ("/headers/ftp/data_address", "192.0.2.1"),
+            ("/headers/ftp/data_port", "40001"),
+        ],
+    },
+    Sample {
+        name: "FTP-DATA",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_TCP, &tcp(40001, 20, b"uploaded file contents")),
+        setup: &[ftp_port_command],
+        protocol: "FTP-DATA",
+        fields: &[("/headers/ftp-data/data_length", "22")],
+    },
+    Sample {
+        name: "GTP-U",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(2152, 2152, &gtp_u(&ipv6(IP_UDP, &udp(40000, 5000, b"hello"))[14..]))),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[
+            ("/headers/gtp/teid", "305419896"),
+            ("/headers/gtp/outer_source_ip", "192.0.2.1"),
+            ("/headers/gtp/qfi", "9"),
+            ("/source_ip", "2001:db8::1"),
+            ("/metadata/gtp_teid", "305419896"),
+        ],
+    },
+    Sample {
+        name: "LLDP",
+        link_type: Linktype::ETHERNET,
+        frame: lldp_frame,
+        setup: &[],
+        protocol: "LLDP",
+        fields: &[("/headers/lldp/chassis_id", "02:00:00:00:00:01"), ("/headers/lldp/ttl", "120")],
+    },
+    Sample {
+        name: "NTP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 123, &ntp_request())),
+        setup: &[],
+        protocol: "NTP",
+        fields: &[("/headers/ntp/version", "4"), ("/headers/ntp/mode_name", "client")],
+    },
+    Sample {
+        name: "OSPF",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_OSPF, &ospf_hello()),
+        setup: &[],
+        protocol: "OSPF",
+        fields: &[("/headers/ospf/type_name", "Hello"), ("/headers/ospf/router_id", "192.0.2.1")],
+    },
+    Sample {
+        name: "RADIUS",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 1812, &radius_request())),
+        setup: &[],
+        protocol: "RADIUS",
+        fields: &[("/headers/radius/code", "1"), ("/headers/radius/user_name", "alice")],
+    },
+    Sample {
+        name: "RTP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 40002, &rtp_packet(1, 160))),
+        setup: &[],
+        protocol: "RTP",
+        fields: &[
+            ("/headers/rtp/codec", "PCMU"),
+            ("/headers/rtp/sequence", "1"),
+            ("/headers/rtp/ssrc", "305441741"),
+            ("/headers/rtp/payload_length", "160"),
+        ],
+    },
+    Sample {
+        name: "SIP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(5060, 5060, &sip_invite())),
+        setup: &[],
+        protocol: "SIP",
+        fields: &[
+            ("/headers/sip/method", "INVITE"),
+            ("/headers/sip/call_id", "a84b4c76e66710@pc33.example.com"),
+            ("/headers/sip/cseq", "314159 INVITE"),
+            ("/headers/sip/sdp/media/0/port", "49170"),
+        ],
+    },
+    Sample {
+        name: "SNMP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 161, &snmp_get())),
+        setup: &[],
+        protocol: "SNMP",
+        fields: &[
+            ("/headers/snmp/community", "public"),
+            ("/headers/snmp/pdu_type_name", "GetRequest"),
+            ("/headers/snmp/variable_bindings/0/oid", "1.3.6.1.2.1.1.5.0"),
+        ],
+    },
+    Sample {
+        name: "SSH",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_TCP, &tcp(40000, 22, &ssh_client_hello())),
+        setup: &[],
+        protocol: "SSH",
+        fields: &[
+            ("/headers/ssh/role", "client"),
+            ("/headers/ssh/software", "OpenSSH_9.6"),
+            ("/headers/ssh/comments", "Ubuntu-3"),
+            ("/headers/ssh/message_type_name", "SSH_MSG_KEXINIT"),
+            ("/headers/ssh/kex_algorithms/1", "ecdh-sha2-nistp256"),
+            ("/headers/ssh/first_kex_packet_follows", "false"),
+        ],
+    },
+    Sample {
+        name: "TFTP",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 69, &tftp_read_request())),
+        setup: &[],
+        protocol: "TFTP",
+        fields: &[
+            ("/headers/tftp/opcode_name", "Read Request"),
+            ("/headers/tftp/filename", "pxelinux.0"),
+            ("/headers/tftp/mode", "octet"),
+            ("/headers/tftp/options/blksize", "1468"),
+        ],
+    },
+    Sample {
+        name: "WOL",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_UDP, &udp(40000, 9, &magic_packet())),
+        setup: &[],
+        protocol: "WOL",
+        fields: &[("/headers/wol/target_mac", "02:00:00:00:00:02")],
+    },
+    Sample {
+        name: "Truncated",
+        link_type: Linktype::ETHERNET,
+        frame: || ipv4(IP_TCP, &tcp(40000, 80, &[])[..10]),
+        setup: &[],
+        protocol: "IPv4",
+        fields: &[("/source_ip", "192.0.2.1"), ("/metadata/parse_truncated", "true")],
+    },
+    Sample {
+        name: "Raw IP",
+        link_type: Linktype::RAW,
+        frame: || ipv6(IP_UDP, &udp(40000, 5000, b"hello"))[14..].to_vec(),
+        setup: &[],
+        protocol: "UDP",
+        fields: &[("/source_ip", "2001:db8::1"), ("/destination_port", "5000")],
+    },
+];
+
+/// Decode every sample and print a pass/fail line per protocol, then a
+/// summary; returns whether all samples passed. Samples of decoders in
+/// `disabled_protocols` are skipped.
+pub fn run(disabled_protocols: &[String]) -> bool {
+    let disabled = |name: &str| DECODERS.contains(&name)
+        && disabled_protocols.iter().any(|p| p.trim().eq_ignore_ascii_case(name));
+    
+    println!("RustShark v{} parser self-test", env!("CARGO_PKG_VERSION"));
+    
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for sample in SAMPLES {
+        if disabled(sample.name) {
+            println!("  SKIP  {:<10} decoder disabled", sample.name);
+            skipped += 1;
+            continue;
+        }
+        
+        // A parser per sample, so a conversation one sample announces cannot claim another's frame
+        let parser = PacketParser::new().with_disabled_protocols(disabled_protocols);
+        match check(&parser, sample) {
+            Ok(()) => {
+                println!("  PASS  {}", sample.name);
+                passed += 1;
+            },
+            Err(problems) => {
+                println!("  FAIL  {:<10} {}", sample.name, problems.join("; "));
+                failed += 1;
+            },
+        }
+    }
+    
+    // A protocol added to the registry without a sample counts as a failure
+    for protocol in registry::PROTOCOLS {
+        if !SAMPLES.iter().any(|sample| sample.name == protocol.name) {
+            println!("  FAIL  {:<10} no self-test sample", protocol.name);
+            failed += 1;
+        }
+    }
+    
+    println!("{} passed, {} failed, {} skipped", passed, failed, skipped);
+    failed == 0
+}
+
+/// Decode one sample, listing every expectation it misses
+fn check(parser: &PacketParser, sample: &Sample) -> Result<(), Vec<String>> {
+    for setup in sample.setup {
+        parser
+            .parse_frame(setup(), "self-test", sample.link_type)
+            .map_err(|e| vec![format!("setup frame parse error: {}", e)])?;
+    }
+    
+    let packet = parser
+        .parse_frame((sample.frame)(), "self-test", sample.link_type)
+        .map_err(|e| vec![format!("parse error: {}", e)])?;
+    
+    let mut problems = Vec::new();
+    if packet.protocol != sample.protocol {
+        problems.push(format!("protocol is {:?}, expected {:?}", packet.protocol, sample.protocol));
+    }
+    
+    let decoded = serde_json::to_value(&packet).unwrap_or_default();
+    for &(pointer, expected) in sample.fields {
+        match decoded.pointer(pointer) {
+            Some(value) if render(value) == expected => {},
+            Some(value) => problems.push(format!("{} is {}, expected {}", pointer, render(value), expected)),
+            None => problems.push(format!("{} is missing", pointer)),
+        }
+    }
+    
+    if problems.is_empty() { Ok(()) } else { Err(problems) }
+}
+
+fn render(value: &Value) -> String {
+    value.as_str().map_or_else(|| value.to_string(), str::to_string)
+}
+
+const IP_ICMP: u8 = 1;
+pub(crate) const IP_TCP: u8 = 6;
+pub(crate) const IP_UDP: u8 = 17;
+const IP_OSPF: u8 = 89;
+const IP_SCTP: u8 = 132;
+
+fn ethernet(destination: [u8; 6], ethertype: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(14 + payload.len());
+    frame.extend_from_slice(&destination);
+    frame.extend_from_slice(&CLIENT_MAC);
+    frame.extend_from_slice(&ethertype.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// The Ethernet frame's payload behind a Linux cooked capture header, sent by the client
+fn sll(frame: &[u8]) -> Vec<u8> {
+    let mut sll = vec![0, 4, 0, 1, 0, 6];
+    sll.extend_from_slice(&CLIENT_MAC);
+    sll.extend_from_slice(&[0, 0]);
+    sll.extend_from_slice(&frame[12..]);
+    sll
+}
+
+/// The Ethernet frame's IP packet behind a BSD loopback header (AF_INET, little-endian)
+fn loopback(frame: &[u8]) -> Vec<u8> {
+    let mut loopback = 2u32.to_le_bytes().to_vec();
+    loopback.extend_from_slice(&frame[14..]);
+    loopback
+}
+
+/// An Ethernet frame carrying an IPv4 packet from client to server
+pub(crate) fn ipv4(protocol: u8, payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x45, 0];
+    packet.extend_from_slice(&(20 + payload.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[0, 1, 0x40, 0, 64, protocol, 0, 0]);
+    packet.extend_from_slice(&CLIENT_IP);
+    packet.extend_from_slice(&SERVER_IP);
+    packet.extend_from_slice(payload);
+    ethernet(SERVER_MAC, 0x0800, &packet)
+}
+
+/// An Ethernet frame carrying an IPv6 packet from 2001:db8::1 to 2001:db8::2
+fn ipv6(next_header: u8, payload: &[u8]) -> Vec<u8> {
+    let mut packet = vec![0x60, 0, 0, 0];
+    packet.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+    packet.extend_from_slice(&[next_header, 64]);
+    for host in [1, 2] {
+        packet.extend_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, host]);
+    }
+    packet.extend_from_slice(payload);
+    ethernet(SERVER_MAC, 0x86dd, &packet)
+}
+
+/// A TCP segment; a SYN when it has no payload, else a PSH/ACK
+pub(crate) fn tcp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+    let flags = if payload.is_empty() { 0x02 } else { 0x18 };
+    let mut segment = Vec::with_capacity(20 + payload.len());
+    segment.extend_from_slice(&source_port.to_be_bytes());
+    segment.extend_from_slice(&destination_port.to_be_bytes());
+    segment.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+    segment.extend_from_slice(payload);
+    segment
+}
+
+pub(crate) fn udp(source_port: u16, destination_port: u16, payload: &[u8]) -> Vec<u8> {
+    let mut datagram = Vec::with_capacity(8 + payload.len());
+    datagram.extend_from_slice(&source_port.to_be_bytes());
+    datagram.extend_from_slice(&destination_port.to_be_bytes());
+    datagram.extend_from_slice(&(8 + payload.len() as u16).to_be_bytes());
+    datagram.extend_from_slice(&[0, 0]);
+    datagram.extend_from_slice(payload);
+    datagram
+}
+
+/// SCTP common header to the M3UA port, with a COOKIE ACK chunk
+fn sctp() -> Vec<u8> {
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&40000u16.to_be_bytes());
+    packet.extend_from_slice(&2905u16.to_be_bytes());
+    packet.extend_from_slice(&0x1234_5678u32.to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 0]);
+    packet.extend_from_slice(&[11, 0, 0, 4]);
+    packet
+}
+
+/// ARP request: who has 192.0.2.2, tell 192.0.2.1
+pub(crate) fn arp_frame() -> Vec<u8> {
+    let mut arp = vec![0, 1, 0x08, 0, 6, 4, 0, 1];
+    arp.extend_from_slice(&CLIENT_MAC);
+    arp.extend_from_slice(&CLIENT_IP);
+    arp.extend_from_slice(&[0; 6]);
+    arp.extend_from_slice(&SERVER_IP);
+    ethernet([0xff; 6], 0x0806, &arp)
+}
+
+/// PPPoE discovery initiation asking for the "internet" service
+fn pppoe_frame() -> Vec<u8> {
+    let service = b"internet";
+    let mut pppoe = vec![0x11, 0x09, 0, 0];
+    pppoe.extend_from_slice(&(4 + service.len() as u16).to_be_bytes());
+    pppoe.extend_from_slice(&[0x01, 0x01]);
+    pppoe.extend_from_slice(&(service.len() as u16).to_be_bytes());
+    pppoe.extend_from_slice(service);
+    ethernet([0xff; 6], 0x8863, &pppoe)
+}
+
+/// Beacon for the "rustshark" network on channel 6, without radiotap
+fn beacon() -> Vec<u8> {
+    let mut frame = vec![0x80, 0, 0, 0];
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&SERVER_MAC);
+    frame.extend_from_slice(&SERVER_MAC);
+    frame.extend_from_slice(&[0, 0]);
+    
+    // Timestamp, beacon interval, capabilities (ESS)
+    frame.extend_from_slice(&[0; 8]);
+    frame.extend_from_slice(&[0x64, 0, 0x01, 0]);
+    
+    // SSID and DS parameter set elements
+    frame.extend_from_slice(&[0, 9]);
+    frame.extend_from_slice(b"rustshark");
+    frame.extend_from_slice(&[3, 1, 6]);
+    frame
+}
+
+/// Prefix an 802.11 frame with a radiotap header carrying the channel (2437 MHz)
+fn radiotap(frame: &[u8]) -> Vec<u8> {
+    let mut data = vec![0, 0, 12, 0, 0x08, 0, 0, 0];
+    data.extend_from_slice(&2437u16.to_le_bytes());
+    data.extend_from_slice(&0x00a0u16.to_le_bytes());
+    data.extend_from_slice(frame);
+    data
+}
+
+/// CDP announcement over 802.3 with LLC/SNAP
+fn cdp_frame() -> Vec<u8> {
+    let mut payload = crate::capture::protocols::cdp::CDP_SNAP_HEADER.to_vec();
+    payload.extend_from_slice(&[2, 180, 0, 0]);
+    for (tlv_type, value) in [(1u16, &b"switch1"[..]), (3, &b"Gi0/1"[..])] {
+        payload.extend_from_slice(&tlv_type.to_be_bytes());
+        payload.extend_from_slice(&(4 + value.len() as u16).to_be_bytes());
+        payload.extend_from_slice(value);
+    }
+    ethernet([0x01, 0x00, 0x0c, 0xcc, 0xcc, 0xcc], payload.len() as u16, &payload)
+}
+
+/// Diameter Capabilities-Exchange-Request without AVPs
+fn diameter() -> Vec<u8> {
+    let mut message = vec![1, 0, 0, 20, 0x80, 0, 0x01, 0x01];
+    message.extend_from_slice(&[0; 4]);
+    message.extend_from_slice(&1u32.to_be_bytes());
+    message.extend_from_slice(&1u32.to_be_bytes());
+    message
+}
+
+/// FTP client announcing an active-mode data connection to its port 40001
+fn ftp_port_command() -> Vec<u8> {
+    ipv4(IP_TCP, &tcp(40000, 21, b"PORT 192,0,2,1,156,65\r\n"))
+}
+
+/// SSH client identification followed by its KEXINIT in the same segment
+fn ssh_client_hello() -> Vec<u8> {
+    let lists: [&[u8]; 10] = [
+        b"curve25519-sha256,ecdh-sha2-nistp256",
+        b"ssh-ed25519",
+        b"aes128-ctr",
+        b"aes128-ctr",
+        b"hmac-sha2-256",
+        b"hmac-sha2-256",
+        b"none",
+        b"none",
+        b"",
+        b"",
+    ];
+    let mut payload = vec![20];
+    payload.extend_from_slice(&[0x5a; 16]);
+    for list in lists {
+        payload.extend_from_slice(&(list.len() as u32).to_be_bytes());
+        payload.extend_from_slice(list);
+    }
+    payload.extend_from_slice(&[0, 0, 0, 0, 0]);
+    
+    // Padding brings the packet to a multiple of 8 bytes, with at least 4
+    let padding = 4 + (8 - (5 + payload.len() + 4) % 8) % 8;
+    let mut message = b"SSH-2.0-OpenSSH_9.6 Ubuntu-3\r\n".to_vec();
+    message.extend_from_slice(&((1 + payload.len() + padding) as u32).to_be_bytes());
+    message.push(padding as u8);
+    message.extend_from_slice(&payload);
+    message.extend(std::iter::repeat_n(0, padding));
+    message
+}
+
+/// TFTP read request negotiating a larger block size
+pub(crate) fn tftp_read_request() -> Vec<u8> {
+    let mut message = vec![0, 1];
+    for field in ["pxelinux.0", "octet", "blksize", "1468"] {
+        message.extend_from_slice(field.as_bytes());
+        message.push(0);
+    }
+    message
+}
+
+/// DNS query for the A record of example.com
+fn dns_query() -> Vec<u8> {
+    let mut message = vec![0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+    message.extend_from_slice(b"\x07example\x03com\x00");
+    message.extend_from_slice(&[0, 1, 0, 1]);
+    message
+}
+
+/// GTP-U G-PDU tunneling a packet, with an uplink PDU session container for QoS flow 9
+fn gtp_u(inner: &[u8]) -> Vec<u8> {
+    let mut message = vec![0x34, 0xff];
+    message.extend_from_slice(&(4 + 4 + inner.len() as u16).to_be_bytes());
+    message.extend_from_slice(&0x1234_5678u32.to_be_bytes());
+    message.extend_from_slice(&[0, 0, 0, 0x85]);
+    message.extend_from_slice(&[1, 0x10, 9, 0]);
+    message.extend_from_slice(inner);
+    message
+}
+
+/// LLDP data unit with a MAC chassis ID, an interface-name port ID and a TTL
+fn lldp_frame() -> Vec<u8> {
+    let mut lldpdu = vec![0x02, 0x07, 4];
+    lldpdu.extend_from_slice(&CLIENT_MAC);
+    lldpdu.extend_from_slice(&[0x04, 0x05, 5]);
+    lldpdu.extend_from_slice(b"eth0");
+    lldpdu.extend_from_slice(&[0x06, 0x02, 0, 120]);
+    lldpdu.extend_from_slice(&[0, 0]);
+    ethernet([0x01, 0x80, 0xc2, 0x00, 0x00, 0x0e], 0x88cc, &lldpdu)
+}
+
+/// NTPv4 client request
+fn ntp_request() -> Vec<u8> {
+    let mut message = vec![0; 48];
+    message[0] = 0x23;
+    message
+}
+
+/// OSPFv2 Hello from router 192.0.2.1 in the backbone area
+fn ospf_hello() -> Vec<u8> {
+    let mut packet = vec![2, 1, 0, 44];
+    packet.extend_from_slice(&CLIENT_IP);
+    packet.extend_from_slice(&[0; 4]);
+    packet.extend_from_slice(&[0; 12]);
+    
+    // Network mask, hello interval, options, priority, dead interval, DR, BDR
+    packet.extend_from_slice(&[255, 255, 255, 0, 0, 10, 0x02, 1, 0, 0, 0, 40]);
+    packet.extend_from_slice(&[0; 8]);
+    packet
+}
+
+/// RADIUS Access-Request for user "alice"
+fn radius_request() -> Vec<u8> {
+    let user = b"alice";
+    let mut message = vec![1, 1];
+    message.extend_from_slice(&(20 + 2 + user.len() as u16).to_be_bytes());
+    message.extend_from_slice(&[0; 16]);
+    message.extend_from_slice(&[1, 2 + user.len() as u8]);
+    message.extend_from_slice(user);
+    message
+}
+
+/// G.711 µ-law RTP packet of 20 ms (160 samples) from SSRC 0x1234ABCD
+pub(crate) fn rtp_packet(sequence: u16, timestamp: u32) -> Vec<u8> {
+    let mut packet = vec![0x80, 0];
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(&timestamp.to_be_bytes());
+    packet.extend_from_slice(&0x1234_abcd_u32.to_be_bytes());
+    packet.extend_from_slice(&[0xff; 160]);
+    packet
+}
+
+/// SIP INVITE from 192.0.2.1 offering PCMU audio on its port 49170
+pub(crate) fn sip_invite() -> Vec<u8> {
+    let sdp = "v=0\r\no=alice 2890844526 2890844526 IN IP4 192.0.2.1\r\ns=-\r\n\
+               c=IN IP4 192.0.2.1\r\nt=0 0\r\nm=audio 49170 RTP/AVP 0\r\n";
+    format!(
+        "INVITE sip:bob@example.com SIP/2.0\r\n\
+         Via: SIP/2.0/UDP pc33.example.com;branch=z9hG4bK776asdhds\r\n\
+         From: Alice <sip:alice@example.com>;tag=1928301774\r\n\
+         To: Bob <sip:bob@example.com>\r\n\
+         Call-ID: a84b4c76e66710@pc33.example.com\r\n\
+         CSeq: 314159 INVITE\r\n\
+         Content-Type: application/sdp\r\n\
+         Content-Length: {}\r\n\r\n{}",
+        sdp.len(),
+        sdp
+    )
+    .into_bytes()
+}
+
+/// SNMPv2c GetRequest for sysName.0 with community "public"
+fn snmp_get() -> Vec<u8> {
+    let tlv = |tag: u8, contents: &[u8]| [&[tag, contents.len() as u8][..], contents].concat();
+    let oid = [0x2b, 6, 1, 2, 1, 1, 5, 0];
+    let varbind = tlv(0x30, &[tlv(0x06, &oid), tlv(0x05, &[])].concat());
+    let pdu = tlv(0xa0, &[tlv(0x02, &[7]), tlv(0x02, &[0]), tlv(0x02, &[0]), tlv(0x30, &varbind)].concat());
+    tlv(0x30, &[tlv(0x02, &[1]), tlv(0x04, b"public"), pdu].concat())
+}
+
+/// Wake-on-LAN magic packet for the server
+fn magic_packet() -> Vec<u8> {
+    let mut message = vec![0xff; 6];
+    for _ in 0..16 {
+        message.extend_from_slice(&SERVER_MAC);
+    }
+    message
+}
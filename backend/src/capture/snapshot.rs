@@ -0,0 +1,105 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::models::stats::CaptureStats;
+use crate::utils::atomic_file;
+
+/// Current snapshot format version. Bump this when the layout changes;
+/// snapshots with a different version are skipped instead of loaded.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// A captured frame as stored in a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPacket {
+    /// Packet ID at the time of the snapshot
+    pub id: u64,
+    
+    /// Capture timestamp
+    pub timestamp: DateTime<Utc>,
+    
+    /// Interface the packet was captured on
+    pub interface: String,
+    
+    /// Raw frame bytes, hex encoded
+    pub data: String,
+    
+    /// Link type (LINKTYPE_* value) the frame was decoded as; missing from
+    /// older snapshots, whose frames are decoded as configured
+    #[serde(default)]
+    pub link_type: Option<i32>,
+}
+
+/// Point-in-time copy of the packet buffer and statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Snapshot format version
+    pub version: u32,
+    
+    /// Time the snapshot was taken
+    pub created_at: DateTime<Utc>,
+    
+    /// Statistics at the time of the snapshot
+    pub stats: CaptureStats,
+    
+    /// Buffered packets, oldest first
+    pub packets: Vec<SnapshotPacket>,
+}
+
+/// Version header used to check compatibility before decoding the full snapshot
+#[derive(Deserialize)]
+struct SnapshotHeader {
+    version: u32,
+}
+
+/// Write a snapshot atomically, replacing the previous one
+pub fn save(path: &Path, snapshot: &Snapshot) -> Result<()> {
+    atomic_file::write(path, |file| Ok(serde_json::to_writer(file, snapshot)?))
+}
+
+/// Load a snapshot if one exists and its version is supported.
+///
+/// Missing files yield `Ok(None)`. Version mismatches are logged and also
+/// yield `Ok(None)` so an old snapshot never prevents startup.
+pub fn load(path: &Path) -> Result<Option<Snapshot>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let contents = fs::read(path)?;
+    
+    let header: SnapshotHeader = serde_json::from_slice(&contents)
+        .map_err(|e| anyhow!("Unreadable snapshot header: {}", e))?;
+    
+    if header.version != SNAPSHOT_VERSION {
+        warn!("Ignoring snapshot {} with unsupported version {} (expected {})",
+              path.display(), header.version, SNAPSHOT_VERSION);
+        return Ok(None);
+    }
+    
+    let snapshot: Snapshot = serde_json::from_slice(&contents)?;
+    info!("Loaded snapshot from {} with {} packets (taken {})",
+          path.display(), snapshot.packets.len(), snapshot.created_at);
+    
+    Ok(Some(snapshot))
+}
+
+/// Encode bytes as a lowercase hex string
+pub fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode a hex string produced by `encode_hex`
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+        return Err(anyhow!("Malformed hex string"));
+    }
+    
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| anyhow!("Invalid hex: {}", e)))
+        .collect()
+}
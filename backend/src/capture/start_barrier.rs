@@ -0,0 +1,101 @@
+// Synchronized start of the captures of one session
+//
+// The member captures of a bond or bridge open one after the other, so each
+// would start reading a little later than the previous one, skewing
+// cross-interface timing (the forwarding latency through a box, say). With
+// --synchronized-start the capture threads are armed instead: each opens its
+// device, honors the start delay and then waits at the barrier, and the last
+// to arrive releases them all at once, recording the release time as the
+// session's synchronized start. Frames an open device captured before the
+// release are discarded, so every capture starts at the same instant. A
+// capture stopped before the release leaves the barrier so the others are
+// not held back by it.
+
+use chrono::{DateTime, Utc};
+use log::info;
+use parking_lot::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often a waiting capture checks whether it was asked to stop
+const STOP_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+struct BarrierState {
+    /// Captures still expected to arrive, and those already waiting
+    parties: usize,
+    waiting: usize,
+    
+    /// When the first capture arrived and when all were released
+    first_arrival: Option<Instant>,
+    released_at: Option<DateTime<Utc>>,
+}
+
+/// Holds the capture threads of a session until all are ready, then releases them together
+#[derive(Debug)]
+pub struct StartBarrier {
+    state: Mutex<BarrierState>,
+    released: Condvar,
+}
+
+impl StartBarrier {
+    /// A barrier for `parties` capture threads
+    pub fn new(parties: usize) -> Self {
+        Self {
+            state: Mutex::new(BarrierState { parties, waiting: 0, first_arrival: None, released_at: None }),
+            released: Condvar::new(),
+        }
+    }
+    
+    /// Wait until every capture has arrived, returning the release time, or
+    /// None when `stop_requested` is set first (the capture then leaves)
+    pub fn wait(&self, stop_requested: &AtomicBool) -> Option<DateTime<Utc>> {
+        let mut state = self.state.lock();
+        state.waiting += 1;
+        state.first_arrival.get_or_insert_with(Instant::now);
+        loop {
+            if let Some(released_at) = state.released_at {
+                return Some(released_at);
+            }
+            if state.waiting >= state.parties {
+                return Some(Self::release(&mut state, &self.released));
+            }
+            if stop_requested.load(Ordering::Relaxed) {
+                state.waiting -= 1;
+                state.parties -= 1;
+                // The others may all be here now
+                self.released.notify_all();
+                return None;
+            }
+            self.released.wait_for(&mut state, STOP_POLL);
+        }
+    }
+    
+    /// Stop expecting a capture that ends before reaching the barrier
+    pub fn leave(&self) {
+        let mut state = self.state.lock();
+        if state.released_at.is_none() {
+            state.parties = state.parties.saturating_sub(1);
+            if state.waiting > 0 && state.waiting >= state.parties {
+                Self::release(&mut state, &self.released);
+            }
+        }
+    }
+    
+    /// When the captures were released, once they have been
+    pub fn released_at(&self) -> Option<DateTime<Utc>> {
+        self.state.lock().released_at
+    }
+    
+    fn release(state: &mut BarrierState, released: &Condvar) -> DateTime<Utc> {
+        let released_at = Utc::now();
+        state.released_at = Some(released_at);
+        released.notify_all();
+        info!(
+            "Released {} captures together after waiting {}ms for the slowest",
+            state.waiting,
+            state.first_arrival.map_or(0, |first| first.elapsed().as_millis())
+        );
+        released_at
+    }
+}
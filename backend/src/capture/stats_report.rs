@@ -0,0 +1,130 @@
+// Periodic statistics reports
+//
+// For unattended runs (an overnight capture with no dashboard open), the
+// stats are written to a file every few seconds, leaving a timeline of how the
+// traffic evolved. In append mode each report is one JSON line added to the
+// file, written whole in a single append; in rotate mode each report gets its
+// own timestamped file next to the configured path, written through a
+// temporary file renamed into place, and only the newest few are kept.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::models::config::StatsReportMode;
+use crate::models::stats::CaptureStats;
+use crate::utils::atomic_file;
+
+/// One report: the stats at a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    /// When the report was taken
+    pub timestamp: DateTime<Utc>,
+    
+    /// Whether a capture was running
+    pub running: bool,
+    
+    /// Interfaces (or remote sources) being captured
+    pub interfaces: Vec<String>,
+    
+    pub stats: CaptureStats,
+}
+
+/// Writes reports to the configured file
+#[derive(Debug, Clone)]
+pub struct StatsReporter {
+    path: PathBuf,
+    mode: StatsReportMode,
+    
+    /// Rotated reports kept (0 keeps all)
+    keep: usize,
+}
+
+impl StatsReporter {
+    pub fn new(path: PathBuf, mode: StatsReportMode, keep: usize) -> Self {
+        Self { path, mode, keep }
+    }
+    
+    /// Write one report, appended or to a new rotated file
+    pub fn write(&self, report: &StatsReport) -> Result<()> {
+        match self.mode {
+            StatsReportMode::Append => self.append(report),
+            StatsReportMode::Rotate => self.rotate(report),
+        }
+    }
+    
+    fn append(&self, report: &StatsReport) -> Result<()> {
+        let mut line = serde_json::to_vec(report)?;
+        line.push(b'\n');
+        
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+    
+    fn rotate(&self, report: &StatsReport) -> Result<()> {
+        let (prefix, extension) = self.rotated_name_parts();
+        let path = self.path.with_file_name(format!(
+            "{}{}.{}",
+            prefix,
+            report.timestamp.format("%Y%m%dT%H%M%SZ"),
+            extension
+        ));
+        atomic_file::write(&path, |file| Ok(serde_json::to_writer_pretty(file, report)?))?;
+        
+        if self.keep > 0 {
+            self.prune(&prefix, &extension)?;
+        }
+        Ok(())
+    }
+    
+    /// Rotated files are named `<stem>-<timestamp>.<extension>` after the configured path
+    fn rotated_name_parts(&self) -> (String, String) {
+        let stem = self.path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let extension = self.path.extension().map(|ext| ext.to_string_lossy().into_owned()).unwrap_or_else(|| "json".to_string());
+        (format!("{}-", stem), extension)
+    }
+    
+    /// Delete the oldest rotated reports beyond `keep`; the timestamps sort by name
+    fn prune(&self, prefix: &str, extension: &str) -> Result<()> {
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut reports: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| is_rotated_report(&entry.file_name().to_string_lossy(), prefix, extension))
+            .map(|entry| entry.path())
+            .collect();
+        if reports.len() <= self.keep {
+            return Ok(());
+        }
+        
+        reports.sort();
+        for old in &reports[..reports.len() - self.keep] {
+            fs::remove_file(old)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a file name is exactly that of a rotated report,
+/// `<prefix>YYYYMMDDTHHMMSSZ.<extension>`, and not some other file sharing the prefix
+fn is_rotated_report(name: &str, prefix: &str, extension: &str) -> bool {
+    let Some(timestamp) = name
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_suffix(extension))
+        .and_then(|rest| rest.strip_suffix('.'))
+    else {
+        return false;
+    };
+    let bytes = timestamp.as_bytes();
+    bytes.len() == 16
+        && bytes[8] == b'T'
+        && bytes[15] == b'Z'
+        && bytes[..8].iter().chain(&bytes[9..15]).all(u8::is_ascii_digit)
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Utc};
+use log::warn;
+use pcap::Linktype;
+use serde_json::Value;
+use std::str::FromStr;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast;
+
+use crate::capture::export;
+use crate::capture::manager::{self, CaptureManager};
+use crate::models::packet::{Packet, PacketSummary};
+
+/// How tail mode prints each packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TailFormat {
+    /// Summary line in the style of `tshark`'s default output
+    Text,
+    
+    /// One JSON object per line, serialized as by the packet detail endpoint
+    Json,
+    
+    /// Tab-separated summary fields, like `tshark -T fields`
+    Fields(Vec<String>),
+}
+
+impl FromStr for TailFormat {
+    type Err = String;
+    
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "text" => return Ok(TailFormat::Text),
+            "json" => return Ok(TailFormat::Json),
+            _ => {},
+        }
+        
+        let Some(fields) = value.strip_prefix("fields:") else {
+            return Err(format!("expected text, json or fields:<list>, got '{}'", value));
+        };
+        
+        let fields: Vec<String> = fields
+            .split(',')
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(|f| f.to_string())
+            .collect();
+        if fields.is_empty() {
+            return Err("fields: needs at least one field".to_string());
+        }
+        if let Some(unknown) = fields.iter().find(|f| !PacketSummary::FIELDS.contains(&f.as_str())) {
+            return Err(format!("unknown field '{}' (available: {})", unknown, PacketSummary::FIELDS.join(", ")));
+        }
+        
+        Ok(TailFormat::Fields(fields))
+    }
+}
+
+/// Print every packet received on the feed to stdout in the given format
+pub async fn run_tail(mut rx: broadcast::Receiver<Packet>, format: TailFormat) {
+    let mut stdout = tokio::io::stdout();
+    
+    // Times for relative_time and delta_time, following the printed packets
+    let mut first: Option<DateTime<Utc>> = None;
+    let mut previous: Option<DateTime<Utc>> = None;
+    
+    loop {
+        match rx.recv().await {
+            Ok(packet) => {
+                let mut summary = CaptureManager::summarize(&packet);
+                let start = *first.get_or_insert(packet.timestamp);
+                summary.relative_time = Some(manager::seconds_between(start, packet.timestamp));
+                summary.delta_time = Some(previous.map_or(0.0, |p| manager::seconds_between(p, packet.timestamp)));
+                previous = Some(packet.timestamp);
+                
+                let mut line = match &format {
+                    TailFormat::Text => format_line(&summary),
+                    TailFormat::Json => serde_json::to_string(&packet).unwrap_or_default(),
+                    TailFormat::Fields(fields) => format_fields(&summary, fields),
+                };
+                line.push('\n');
+                
+                if write_out(&mut stdout, line.as_bytes()).await.is_err() {
+                    // Stdout closed (e.g. piped into `head`), nothing more to do
+                    break;
+                }
+            },
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Tail output fell behind, skipped {} packets", skipped);
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Stream every packet received on the feed to stdout as a pcap file, like
+/// `tcpdump -w -`: the global header (for the capture's `link_type`) once,
+/// then one record per packet, flushed as soon as the feed has nothing queued
+/// so a reader sees packets live
+pub async fn run_pcap_stdout(mut rx: broadcast::Receiver<Packet>, link_type: Linktype) {
+    let mut stdout = tokio::io::stdout();
+    let mut buffer = Vec::new();
+    
+    // Readers such as `wireshark -k -i -` wait for the header before showing anything
+    export::push_pcap_header(&mut buffer, link_type);
+    if write_out(&mut stdout, &buffer).await.is_err() {
+        return;
+    }
+    buffer.clear();
+    
+    loop {
+        match rx.recv().await {
+            Ok(packet) => export::push_pcap_record(&mut buffer, &packet, false),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                warn!("Pcap output fell behind, skipped {} packets", skipped);
+                continue;
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+        
+        // Batch whatever else is already queued into the same write
+        while let Ok(packet) = rx.try_recv() {
+            export::push_pcap_record(&mut buffer, &packet, false);
+        }
+        
+        if write_out(&mut stdout, &buffer).await.is_err() {
+            // The reader went away (e.g. Wireshark was closed)
+            break;
+        }
+        buffer.clear();
+    }
+}
+
+/// Write and flush without blocking the runtime thread on a slow reader
+async fn write_out(stdout: &mut tokio::io::Stdout, data: &[u8]) -> std::io::Result<()> {
+    stdout.write_all(data).await?;
+    stdout.flush().await
+}
+
+/// Format a single packet as a summary line
+fn format_line(summary: &PacketSummary) -> String {
+    format!(
+        "{:>6} {} {} -> {} {} {} {}",
+        summary.id,
+        summary.timestamp.format("%H:%M:%S%.6f"),
+        summary.source,
+        summary.destination,
+        summary.protocol,
+        summary.length,
+        summary.info
+    )
+}
+
+/// Format the chosen summary fields, tab-separated; absent fields are left empty
+fn format_fields(summary: &PacketSummary, fields: &[String]) -> String {
+    let projected = summary.project(fields);
+    
+    fields
+        .iter()
+        .map(|field| match &projected[field.as_str()] {
+            Value::Null => String::new(),
+            Value::String(s) => s.replace(['\t', '\n'], " "),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\t")
+}
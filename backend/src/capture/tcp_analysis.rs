@@ -0,0 +1,152 @@
+// TCP analysis: sequence tracking per stream direction
+//
+// Each direction of a TCP connection remembers the next sequence number it is
+// expected to send, and the acknowledgement and window it last advertised.
+// Segments are judged against that state the way Wireshark annotates them:
+// data below the expected sequence is a retransmission, a zero or one byte
+// segment just below it is a keep-alive probe, and a bare ACK that changes
+// nothing but the window is a window update. The findings are listed in
+// `metadata["tcp_analysis"]`.
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::models::packet::{Packet, TcpAnalysis};
+
+/// Maximum number of stream directions tracked; new ones are ignored beyond this
+const MAX_DIRECTIONS: usize = 32_768;
+
+/// Source and destination endpoints of one direction of a stream
+type DirectionKey = (IpAddr, u16, IpAddr, u16);
+
+/// What one direction of a stream has sent so far
+#[derive(Debug, Clone, Copy)]
+struct DirectionState {
+    next_seq: u32,
+    ack: u32,
+    window: u64,
+}
+
+/// The fields of a TCP segment the analysis looks at
+pub(crate) struct Segment {
+    pub(crate) seq: u32,
+    pub(crate) ack: u32,
+    window: u64,
+    len: u32,
+    pub(crate) syn: bool,
+    pub(crate) fin: bool,
+    rst: bool,
+    pub(crate) has_ack: bool,
+}
+
+impl Segment {
+    pub(crate) fn of(packet: &Packet) -> Option<Self> {
+        let headers = packet.expanded_headers();
+        let tcp = headers.get("tcp")?;
+        let flag = |name: &str| tcp["flags"][name].as_bool().unwrap_or(false);
+        
+        Some(Segment {
+            seq: tcp["sequence"].as_u64()? as u32,
+            ack: tcp["acknowledgement"].as_u64()? as u32,
+            window: tcp["window"].as_u64()?,
+            len: packet.payload.as_ref().map_or(0, |p| p.len()) as u32,
+            syn: flag("syn"),
+            fin: flag("fin"),
+            rst: flag("rst"),
+            has_ack: flag("ack"),
+        })
+    }
+    
+    /// Sequence number following the segment (SYN and FIN take one each)
+    pub(crate) fn end(&self) -> u32 {
+        self.seq
+            .wrapping_add(self.len)
+            .wrapping_add(self.syn as u32)
+            .wrapping_add(self.fin as u32)
+    }
+}
+
+/// Annotates TCP segments with retransmission, keep-alive and window update flags
+#[derive(Clone, Default)]
+pub struct TcpAnalyzer {
+    directions: Arc<DashMap<DirectionKey, DirectionState>>,
+    
+    /// Serializes inserts so the size cap holds
+    insert_lock: Arc<Mutex<()>>,
+}
+
+impl TcpAnalyzer {
+    /// Create an analyzer with no stream state
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Forget all stream state
+    pub fn clear(&self) {
+        self.directions.clear();
+    }
+    
+    /// Judge a TCP segment against its direction's state and record any findings
+    pub fn inspect(&self, packet: &mut Packet) {
+        let Some(segment) = Segment::of(packet) else { return };
+        let (Some(source), Some(source_port), Some(destination), Some(destination_port)) =
+            (packet.source_ip, packet.source_port, packet.destination_ip, packet.destination_port) else { return };
+        let key = (source, source_port, destination, destination_port);
+        
+        let findings = match self.directions.get_mut(&key) {
+            Some(mut state) => {
+                let findings = Self::analyze(&state, &segment);
+                if seq_before(state.next_seq, segment.end()) {
+                    state.next_seq = segment.end();
+                }
+                state.ack = segment.ack;
+                state.window = segment.window;
+                findings
+            },
+            None => {
+                let _guard = self.insert_lock.lock();
+                if self.directions.len() < MAX_DIRECTIONS {
+                    self.directions.insert(key, DirectionState {
+                        next_seq: segment.end(),
+                        ack: segment.ack,
+                        window: segment.window,
+                    });
+                }
+                Vec::new()
+            },
+        };
+        
+        // A reset ends the stream; its direction may be reused by a new connection
+        if segment.rst {
+            self.directions.remove(&key);
+        }
+        
+        if let (false, Some(metadata)) = (findings.is_empty(), packet.metadata.as_object_mut()) {
+            let flags = findings.iter().map(|flag| serde_json::Value::String(flag.as_str().to_string())).collect();
+            metadata.insert("tcp_analysis".to_string(), serde_json::Value::Array(flags));
+        }
+    }
+    
+    fn analyze(state: &DirectionState, segment: &Segment) -> Vec<TcpAnalysis> {
+        let control = segment.syn || segment.fin || segment.rst;
+        
+        if !control && segment.len <= 1 && segment.seq == state.next_seq.wrapping_sub(1) {
+            return vec![TcpAnalysis::KeepAlive];
+        }
+        if !control && segment.len == 0 && segment.has_ack && segment.seq == state.next_seq
+            && segment.ack == state.ack && segment.window != state.window {
+            return vec![TcpAnalysis::WindowUpdate];
+        }
+        if (segment.len > 0 || segment.syn || segment.fin) && seq_before(segment.seq, state.next_seq) {
+            return vec![TcpAnalysis::Retransmission];
+        }
+        Vec::new()
+    }
+}
+
+/// Whether sequence number `a` comes before `b`, allowing for wraparound
+pub(crate) fn seq_before(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
@@ -0,0 +1,69 @@
+// Inter-packet timing of a stream: gaps, their spread, and jitter
+//
+// A stream is one direction of a flow (same transport, source and destination),
+// which is what a VoIP or media stream is. Without decoding RTP there are no
+// sender timestamps, so the jitter estimator of RFC 3550 (section 6.4.1) is fed
+// the change between consecutive inter-arrival gaps, i.e. it assumes a steady
+// sending rate. Arrival times are the stored packet timestamps: the pcap record
+// times for loaded files, the time of reception for live captures.
+
+use chrono::{DateTime, Utc};
+
+use crate::capture::conversations::ConversationTable;
+use crate::capture::manager::seconds_between;
+use crate::models::packet::Packet;
+use crate::models::timing::StreamTiming;
+
+/// Gain of the jitter estimator (RFC 3550: J += (|D| - J) / 16)
+const JITTER_GAIN: f64 = 1.0 / 16.0;
+
+/// Whether `other` belongs to the stream `packet` is part of (same direction)
+pub fn same_stream(packet: &Packet, transport: &str, other: &Packet) -> bool {
+    other.source_ip == packet.source_ip
+        && other.destination_ip == packet.destination_ip
+        && other.source_port == packet.source_port
+        && other.destination_port == packet.destination_port
+        && ConversationTable::transport(other) == transport
+}
+
+/// Timing of the stream of `packet` from the arrival times of its packets,
+/// listing at most `limit` gaps. None when the packet has no IP addresses.
+pub fn stream_timing(packet: &Packet, mut arrivals: Vec<DateTime<Utc>>, limit: usize) -> Option<StreamTiming> {
+    let (source_ip, destination_ip) = (packet.source_ip?, packet.destination_ip?);
+    let endpoint = |ip: std::net::IpAddr, port: Option<u16>| match port {
+        Some(port) => format!("{}:{}", ip, port),
+        None => ip.to_string(),
+    };
+    
+    arrivals.sort_unstable();
+    let gaps: Vec<f64> = arrivals
+        .windows(2)
+        .map(|pair| seconds_between(pair[0], pair[1]) * 1000.0)
+        .collect();
+    
+    let mean = (!gaps.is_empty()).then(|| gaps.iter().sum::<f64>() / gaps.len() as f64);
+    let stddev = mean.filter(|_| gaps.len() >= 2).map(|mean| {
+        let variance = gaps.iter().map(|gap| (gap - mean).powi(2)).sum::<f64>() / (gaps.len() - 1) as f64;
+        variance.sqrt()
+    });
+    let jitter = (gaps.len() >= 2).then(|| {
+        gaps.windows(2).fold(0.0, |jitter, pair| jitter + ((pair[1] - pair[0]).abs() - jitter) * JITTER_GAIN)
+    });
+    
+    Some(StreamTiming {
+        packet_id: packet.id,
+        transport: ConversationTable::transport(packet),
+        source: endpoint(source_ip, packet.source_port),
+        destination: endpoint(destination_ip, packet.destination_port),
+        packets: arrivals.len(),
+        first_seen: arrivals.first().copied().unwrap_or(packet.timestamp),
+        last_seen: arrivals.last().copied().unwrap_or(packet.timestamp),
+        gaps_truncated: gaps.len() > limit,
+        min_gap_ms: gaps.iter().copied().reduce(f64::min),
+        max_gap_ms: gaps.iter().copied().reduce(f64::max),
+        mean_gap_ms: mean,
+        stddev_gap_ms: stddev,
+        jitter_ms: jitter,
+        gaps_ms: gaps.into_iter().take(limit).collect(),
+    })
+}
@@ -0,0 +1,237 @@
+// Armed mode: watch interface counters and start a capture when traffic crosses a threshold
+//
+// Sampling the kernel's per-interface counters is far cheaper than running
+// a capture, so an armed RustShark can sit idle for hours and only start
+// parsing packets once the transient event it is waiting for begins.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::capture::manager::CaptureManager;
+use crate::capture::remote;
+
+/// How often the interface counters are sampled while armed
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Thresholds that start a capture when crossed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerSettings {
+    /// Interface to watch and capture on (defaults to the selected interface)
+    #[serde(default)]
+    pub interface: Option<String>,
+    
+    /// Packets per second, received and transmitted combined
+    #[serde(default)]
+    pub pps: Option<u64>,
+    
+    /// Bits per second, received and transmitted combined
+    #[serde(default)]
+    pub bps: Option<u64>,
+    
+    /// Stop the triggered capture after this many seconds (keeps running when unset)
+    #[serde(default)]
+    pub post_trigger_secs: Option<u64>,
+}
+
+impl TriggerSettings {
+    /// Whether a measured rate crosses any of the thresholds
+    fn crossed(&self, pps: u64, bps: u64) -> bool {
+        self.pps.is_some_and(|limit| pps >= limit) || self.bps.is_some_and(|limit| bps >= limit)
+    }
+}
+
+/// Lifecycle of an armed trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerState {
+    /// Watching the counters
+    Armed,
+    
+    /// The threshold was crossed and a capture was started
+    Triggered,
+    
+    /// The post-trigger duration elapsed and the capture was stopped
+    Completed,
+    
+    /// The counters could not be read or the capture failed to start
+    Failed,
+}
+
+/// Current state of an armed trigger
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerStatus {
+    pub state: TriggerState,
+    pub settings: TriggerSettings,
+    pub armed_at: DateTime<Utc>,
+    pub triggered_at: Option<DateTime<Utc>>,
+    
+    /// Rates measured at the last sample
+    pub last_pps: u64,
+    pub last_bps: u64,
+    
+    pub error: Option<String>,
+}
+
+/// A running trigger; dropping it disarms the trigger
+pub struct ArmedTrigger {
+    status: Arc<Mutex<TriggerStatus>>,
+    task: JoinHandle<()>,
+}
+
+impl ArmedTrigger {
+    /// Get the trigger's current status
+    pub fn status(&self) -> TriggerStatus {
+        self.status.lock().clone()
+    }
+}
+
+impl Drop for ArmedTrigger {
+    fn drop(&mut self) {
+        // A capture that was already triggered keeps running
+        self.task.abort();
+    }
+}
+
+/// Interface packet and byte counters, received and transmitted combined
+#[derive(Debug, Clone, Copy)]
+struct InterfaceCounters {
+    packets: u64,
+    bytes: u64,
+}
+
+/// Validate the settings and start watching `settings.interface`
+pub fn arm(manager: Arc<RwLock<CaptureManager>>, settings: TriggerSettings) -> Result<ArmedTrigger> {
+    let interface = settings
+        .interface
+        .clone()
+        .ok_or_else(|| anyhow!("No interface selected for the trigger"))?;
+    if remote::is_remote_source(&interface) {
+        return Err(anyhow!("Triggers need local interface counters and cannot watch remote sources"));
+    }
+    // The name becomes a path under /sys/class/net
+    if interface.is_empty() || interface.contains('/') || interface.contains("..") {
+        return Err(anyhow!("Invalid interface name '{}'", interface));
+    }
+    if settings.pps.unwrap_or(0) == 0 && settings.bps.unwrap_or(0) == 0 {
+        return Err(anyhow!("At least one non-zero threshold (pps or bps) is required"));
+    }
+    
+    // Fail early if the counters are unavailable for this interface
+    read_counters(&interface)?;
+    
+    let status = Arc::new(Mutex::new(TriggerStatus {
+        state: TriggerState::Armed,
+        settings: settings.clone(),
+        armed_at: Utc::now(),
+        triggered_at: None,
+        last_pps: 0,
+        last_bps: 0,
+        error: None,
+    }));
+    
+    info!("Capture armed on {} (pps >= {:?}, bps >= {:?})", interface, settings.pps, settings.bps);
+    let task = tokio::spawn(run(manager, interface, settings, status.clone()));
+    
+    Ok(ArmedTrigger { status, task })
+}
+
+/// Sample the counters until a threshold is crossed, then run the capture
+async fn run(
+    manager: Arc<RwLock<CaptureManager>>,
+    interface: String,
+    settings: TriggerSettings,
+    status: Arc<Mutex<TriggerStatus>>,
+) {
+    let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+    let mut previous: Option<(InterfaceCounters, Instant)> = None;
+    
+    let session_id = loop {
+        ticker.tick().await;
+        
+        let counters = match read_counters(&interface) {
+            Ok(counters) => counters,
+            Err(e) => return fail(&status, e.to_string()),
+        };
+        let now = Instant::now();
+        let Some((last, at)) = previous.replace((counters, now)) else { continue };
+        
+        let secs = now.duration_since(at).as_secs_f64().max(f64::EPSILON);
+        let pps = (counters.packets.saturating_sub(last.packets) as f64 / secs) as u64;
+        let bps = (counters.bytes.saturating_sub(last.bytes) as f64 * 8.0 / secs) as u64;
+        {
+            let mut status = status.lock();
+            status.last_pps = pps;
+            status.last_bps = bps;
+        }
+        
+        if !settings.crossed(pps, bps) {
+            continue;
+        }
+        
+        let mut manager = manager.write().await;
+        if manager.get_status() {
+            // Someone is already capturing; keep watching for when they stop
+            continue;
+        }
+        
+        info!("Trigger crossed on {} ({} pps, {} bps), starting capture", interface, pps, bps);
+        if let Err(e) = manager.start_capture_on(interface.clone()).await {
+            return fail(&status, format!("Failed to start capture: {}", e));
+        }
+        
+        let mut status = status.lock();
+        status.state = TriggerState::Triggered;
+        status.triggered_at = Some(Utc::now());
+        break manager.get_session_id();
+    };
+    
+    let Some(post_trigger_secs) = settings.post_trigger_secs else { return };
+    tokio::time::sleep(Duration::from_secs(post_trigger_secs)).await;
+    
+    // Only stop the capture this trigger started
+    let mut manager = manager.write().await;
+    if manager.get_status() && manager.get_session_id() == session_id {
+        info!("Post-trigger duration of {}s elapsed, stopping capture", post_trigger_secs);
+        if let Err(e) = manager.stop_capture().await {
+            return fail(&status, format!("Failed to stop capture: {}", e));
+        }
+    }
+    status.lock().state = TriggerState::Completed;
+}
+
+/// Record a trigger failure
+fn fail(status: &Mutex<TriggerStatus>, message: String) {
+    warn!("Trigger failed: {}", message);
+    let mut status = status.lock();
+    status.state = TriggerState::Failed;
+    status.error = Some(message);
+}
+
+/// Read the kernel's counters for an interface
+#[cfg(target_os = "linux")]
+fn read_counters(interface: &str) -> Result<InterfaceCounters> {
+    let read = |name: &str| -> Result<u64> {
+        let path = format!("/sys/class/net/{}/statistics/{}", interface, name);
+        let value = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Cannot read counters of interface {}: {}", interface, e))?;
+        value.trim().parse().map_err(|e| anyhow!("Invalid counter in {}: {}", path, e))
+    };
+    
+    Ok(InterfaceCounters {
+        packets: read("rx_packets")? + read("tx_packets")?,
+        bytes: read("rx_bytes")? + read("tx_bytes")?,
+    })
+}
+
+/// Read the kernel's counters for an interface
+#[cfg(not(target_os = "linux"))]
+fn read_counters(_interface: &str) -> Result<InterfaceCounters> {
+    Err(anyhow!("Capture triggers are only supported on Linux"))
+}
@@ -0,0 +1,230 @@
+// Capture from a Linux tun device
+//
+// A tun device hands user space the IP packets the kernel routes into it,
+// without a link-layer header, which is how VPN and other inline tools see
+// traffic that never crosses a NIC. A `tun:<name>` source attaches to the
+// device, creating it if needed (this needs CAP_NET_ADMIN unless the device
+// was created for this user with `ip tuntap add mode tun user ...`), and its
+// packets are decoded as raw IP.
+//
+// Packets read from the device are taken away from the kernel, so for inline
+// use each one can be forwarded unchanged to a second tun device, entering
+// the stack there as if received on it. (Writing them back to the device read
+// from would hand the kernel's outbound packets back to it as inbound ones.)
+// libpcap cannot filter a descriptor it did not open, so capture filters are
+// compiled for raw IP and applied here; forwarded packets are not filtered.
+
+use anyhow::{anyhow, Result};
+use log::warn;
+use pcap::{BpfProgram, Capture, Linktype};
+
+/// Prefix of tun capture sources, followed by the device name
+pub const TUN_SCHEME: &str = "tun:";
+
+/// Largest packet a tun device delivers
+const MAX_PACKET_LEN: usize = 65535;
+
+/// How long a read waits for a packet before returning, so stop requests are noticed
+const READ_TIMEOUT_MS: i32 = 1000;
+
+/// Whether the capture interface names a tun device
+pub fn is_tun_source(interface: &str) -> bool {
+    interface.starts_with(TUN_SCHEME)
+}
+
+/// A compiled raw IP filter
+struct RawFilter(BpfProgram);
+
+// The program is owned exclusively and libpcap only reads it when filtering
+unsafe impl Send for RawFilter {}
+
+/// An open tun capture
+pub struct TunCapture {
+    device: device::TunDevice,
+    buffer: Vec<u8>,
+    filter: Option<RawFilter>,
+    
+    /// Device every packet read is written to
+    forward: Option<device::TunDevice>,
+    forward_failed: bool,
+}
+
+impl TunCapture {
+    /// Attach to the tun device a `tun:<name>` source names, and to the one
+    /// named by `forward` to pass its packets on to
+    pub fn open(source: &str, forward: Option<&str>, filter: Option<&str>) -> Result<Self> {
+        let name = source.strip_prefix(TUN_SCHEME).unwrap_or(source);
+        let forward = forward.map(|forward| forward.strip_prefix(TUN_SCHEME).unwrap_or(forward));
+        if forward == Some(name) {
+            return Err(anyhow!("Cannot forward packets to {}, the device they are read from", name));
+        }
+        
+        let mut capture = Self {
+            device: device::TunDevice::open(name)?,
+            buffer: vec![0; MAX_PACKET_LEN],
+            filter: None,
+            forward: forward.map(device::TunDevice::open).transpose()?,
+            forward_failed: false,
+        };
+        
+        if let Some(filter) = filter {
+            capture.set_filter(filter)?;
+        }
+        Ok(capture)
+    }
+    
+    /// Name of the device, as the kernel assigned it
+    pub fn name(&self) -> &str {
+        self.device.name()
+    }
+    
+    /// Name of the device packets are forwarded to
+    pub fn forward_name(&self) -> Option<&str> {
+        self.forward.as_ref().map(device::TunDevice::name)
+    }
+    
+    /// Wait for the next packet passing the filter; None when the wait timed
+    /// out or the packet was filtered out
+    pub fn next_frame(&mut self) -> Result<Option<&[u8]>> {
+        let Some(len) = self.device.read(&mut self.buffer, READ_TIMEOUT_MS)? else {
+            return Ok(None);
+        };
+        let packet = &self.buffer[..len];
+        
+        if let Some(forward) = &mut self.forward {
+            match forward.write(packet) {
+                Ok(()) => self.forward_failed = false,
+                Err(e) if !self.forward_failed => {
+                    warn!("Failed to forward a packet to {}: {}", forward.name(), e);
+                    self.forward_failed = true;
+                },
+                Err(_) => {},
+            }
+        }
+        
+        match &self.filter {
+            Some(RawFilter(program)) if !program.filter(packet) => Ok(None),
+            _ => Ok(Some(packet)),
+        }
+    }
+    
+    /// Replace the capture filter (an empty one captures everything)
+    pub fn set_filter(&mut self, filter: &str) -> Result<()> {
+        if filter.trim().is_empty() {
+            self.filter = None;
+            return Ok(());
+        }
+        
+        let program = Capture::dead(Linktype::RAW)?
+            .compile(filter, true)
+            .map_err(|e| anyhow!("Invalid BPF filter '{}': {}", filter, e))?;
+        self.filter = Some(RawFilter(program));
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod device {
+    use anyhow::{anyhow, Result};
+    use std::ffi::CStr;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::AsRawFd;
+    
+    pub struct TunDevice {
+        file: File,
+        name: String,
+    }
+    
+    impl TunDevice {
+        pub fn open(name: &str) -> Result<Self> {
+            if name.is_empty() || name.len() >= libc::IFNAMSIZ {
+                return Err(anyhow!("Invalid tun device name '{}'", name));
+            }
+            
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/net/tun")
+                .map_err(|e| anyhow!("Cannot open /dev/net/tun: {}", e))?;
+            
+            // SAFETY: ifreq is plain data, and the zeroed tail terminates the name
+            let mut request: libc::ifreq = unsafe { std::mem::zeroed() };
+            for (dst, &src) in request.ifr_name.iter_mut().zip(name.as_bytes()) {
+                *dst = src as libc::c_char;
+            }
+            // Raw IP packets, without the 4-byte packet information header
+            request.ifr_ifru.ifru_flags = (libc::IFF_TUN | libc::IFF_NO_PI) as libc::c_short;
+            
+            // SAFETY: TUNSETIFF reads the request and writes the assigned name back into it
+            if unsafe { libc::ioctl(file.as_raw_fd(), libc::TUNSETIFF, &mut request) } < 0 {
+                return Err(anyhow!(
+                    "Cannot attach to tun device {}: {} (creating one needs CAP_NET_ADMIN)",
+                    name,
+                    io::Error::last_os_error()
+                ));
+            }
+            
+            // SAFETY: the kernel leaves a NUL-terminated name in the request
+            let name = unsafe { CStr::from_ptr(request.ifr_name.as_ptr()) }.to_string_lossy().into_owned();
+            Ok(Self { file, name })
+        }
+        
+        pub fn name(&self) -> &str {
+            &self.name
+        }
+        
+        /// Wait up to `timeout_ms` for a packet and read it into `buffer`
+        pub fn read(&mut self, buffer: &mut [u8], timeout_ms: i32) -> Result<Option<usize>> {
+            let mut poll = libc::pollfd {
+                fd: self.file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            
+            // SAFETY: one valid pollfd
+            match unsafe { libc::poll(&mut poll, 1, timeout_ms) } {
+                0 => Ok(None),
+                n if n < 0 => match io::Error::last_os_error() {
+                    e if e.kind() == io::ErrorKind::Interrupted => Ok(None),
+                    e => Err(e.into()),
+                },
+                _ => Ok(Some(self.file.read(buffer)?)),
+            }
+        }
+        
+        /// Write one packet; a tun device takes a whole packet per write
+        pub fn write(&mut self, packet: &[u8]) -> Result<()> {
+            let written = self.file.write(packet)?;
+            if written != packet.len() {
+                return Err(anyhow!("wrote {} of {} bytes", written, packet.len()));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod device {
+    use anyhow::{anyhow, Result};
+    
+    pub struct TunDevice;
+    
+    impl TunDevice {
+        pub fn open(_name: &str) -> Result<Self> {
+            Err(anyhow!("Capturing from tun devices is only supported on Linux"))
+        }
+        
+        pub fn name(&self) -> &str {
+            ""
+        }
+        
+        pub fn read(&mut self, _buffer: &mut [u8], _timeout_ms: i32) -> Result<Option<usize>> {
+            Ok(None)
+        }
+        
+        pub fn write(&mut self, _packet: &[u8]) -> Result<()> {
+            Ok(())
+        }
+    }
+}
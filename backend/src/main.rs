@@ -3,28 +3,47 @@ mod capture;
 mod models;
 mod utils;
 
+#[cfg(test)]
+mod tests;
+
 use actix_web::{web, App, HttpServer};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
 use clap::Parser;
-use log::{info, warn};
+use log::{debug, info, warn};
+use std::collections::HashMap;
 use std::process::Command;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use pcap::{Capture, Packet, Device};
-use std::io::{self, Read};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, KeyEventKind};
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
 use std::time::Duration;
 
+use crate::api::cors::CorsPolicy;
+use crate::api::handlers::health::HealthState;
 use crate::api::routes;
 use crate::capture::manager::CaptureManager;
-use crate::models::config::AppConfig;
-use crate::utils::logging;
+use crate::capture::file::{FileSource, ReadFiles};
+use crate::capture::{bpf, event_socket, host_info, index, interfaces, otlp, protocols, self_test, snapshot, tail};
+use crate::capture::stats_report::{StatsReport, StatsReporter};
+use crate::capture::tail::TailFormat;
+use crate::models::config::{AppConfig, BudgetAction, LinkTypeOverride, OverflowPolicy, StatsReportMode, API_HOST};
+use crate::utils::{logging, tls};
+
+/// Default seconds between link checks; on Windows each check lists the
+/// adapters with PowerShell, so it checks less often
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_LINK_POLL_SECS: u64 = 5;
+#[cfg(target_os = "windows")]
+const DEFAULT_LINK_POLL_SECS: u64 = 30;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about = "A Wireshark-like network analyzer with REST API")]
+#[clap(group(clap::ArgGroup::new("stdout_output").args(["tail", "write_stdout"])))]
 struct Args {
-    /// Network interface to capture from
+    /// Network interface to capture from (or an rpcap://host/interface remote source)
     #[clap(short, long)]
     interface: Option<String>,
     
@@ -36,14 +55,38 @@ struct Args {
     #[clap(short = 'P', long)]
     promiscuous: bool,
     
+    /// When the interface is a bond or bridge, capture each of its members instead and record the one a packet crossed
+    #[clap(long)]
+    capture_members: bool,
+    
+    /// Open every interface of a multi-interface capture (--capture-members) first, then start them together, for cross-interface timing
+    #[clap(long)]
+    synchronized_start: bool,
+    
+    /// Capture RustShark's own API and WebSocket traffic too (left out by default)
+    #[clap(long)]
+    include_api_traffic: bool,
+    
+    /// Let a browser UI on this origin call the API, e.g. https://ui.example.com (repeatable, `*` for any)
+    #[clap(long = "cors-origin", value_name = "ORIGIN")]
+    cors_origins: Vec<String>,
+    
     /// Packet buffer size
     #[clap(long, default_value = "1000")]
     buffer_size: usize,
     
+    /// Split packet storage into this many shards, each evicting its own oldest packets (for buffers of millions)
+    #[clap(long, default_value = "1", value_parser = clap::value_parser!(u16).range(1..))]
+    storage_shards: u16,
+    
     /// BPF filter expression
     #[clap(long)]
     filter: Option<String>,
     
+    /// Read the BPF filter expression from a file (`#` comments and line continuations allowed)
+    #[clap(long, value_name = "PATH", conflicts_with = "filter")]
+    filter_file: Option<PathBuf>,
+    
     /// Log level (trace, debug, info, warn, error, off)
     #[clap(long, default_value = "info")]
     log_level: String,
@@ -55,6 +98,283 @@ struct Args {
     /// Start with verbose logging enabled
     #[clap(short, long)]
     verbose: bool,
+    
+    /// Mask IP and MAC addresses (keeping the subnet), ports and payload previews in all log output
+    #[clap(long)]
+    redact: bool,
+    
+    /// Discard the first N packets of each capture (warm-up)
+    #[clap(long, default_value = "0")]
+    warmup_packets: usize,
+    
+    /// Discard packets received in the first T milliseconds after each capture's first packet (warm-up)
+    #[clap(long, default_value = "0")]
+    warmup_ms: u64,
+    
+    /// Delay in milliseconds before a capture starts reading packets
+    #[clap(long, default_value = "0")]
+    start_delay_ms: u64,
+    
+    /// Stop the capture after this many seconds without packets (0 disables)
+    #[clap(long, default_value = "0")]
+    stop_after_idle_secs: u64,
+    
+    /// Force-stop any capture after this many seconds, however it was started; a guardrail for shared hosts (0 disables)
+    #[clap(long, default_value = "0")]
+    max_capture_seconds: u64,
+    
+    /// Every this many seconds, store the parsed headers of buffered packets as compact JSON text, decoded again when read; saves memory on large buffers for some CPU (0 disables)
+    #[clap(long, default_value = "0")]
+    compact_headers_secs: u64,
+    
+    /// Check the capture interface's link every this many seconds, logging it going down and up as capture events (0 disables; 30 by default on Windows, where each check runs PowerShell)
+    #[clap(long, default_value_t = DEFAULT_LINK_POLL_SECS)]
+    link_poll_secs: u64,
+    
+    /// Sliding window in seconds for SYN flood and port scan detection
+    #[clap(long, default_value = "10")]
+    detection_window_secs: u64,
+    
+    /// SYNs without ACK from one source within the window that raise an alert (0 disables)
+    #[clap(long, default_value = "200")]
+    syn_flood_threshold: usize,
+    
+    /// Distinct destination ports from one source within the window that raise an alert (0 disables)
+    #[clap(long, default_value = "50")]
+    port_scan_threshold: usize,
+    
+    /// Periodically save the packet buffer to this file and reload it on startup
+    #[clap(long)]
+    snapshot_file: Option<String>,
+    
+    /// Seconds between snapshot writes
+    #[clap(long, default_value = "30")]
+    snapshot_interval_secs: u64,
+    
+    /// Write the stats to this file periodically, for a timeline of unattended captures
+    #[clap(long)]
+    stats_report_file: Option<String>,
+    
+    /// Seconds between stats reports
+    #[clap(long, default_value = "60")]
+    stats_report_interval_secs: u64,
+    
+    /// Append reports to the file as JSON lines, or rotate them into timestamped files next to it
+    #[clap(long, value_enum, default_value = "append")]
+    stats_report_mode: StatsReportMode,
+    
+    /// Rotated stats reports to keep, deleting the oldest (0 keeps all)
+    #[clap(long, default_value = "24")]
+    stats_report_keep: usize,
+    
+    /// Keep a crash-recoverable index of packet summaries in this memory-mapped file (Unix only)
+    #[clap(long)]
+    index_file: Option<String>,
+    
+    /// Stream a JSON summary line per packet to consumers of this Unix socket (named pipe on Windows)
+    #[clap(long)]
+    event_socket: Option<String>,
+    
+    /// Push capture metrics every second to this OpenTelemetry collector (OTLP/HTTP, e.g. http://localhost:4318)
+    #[clap(long)]
+    otlp_endpoint: Option<String>,
+    
+    /// Start capturing immediately and print each packet's summary line to stdout
+    #[clap(long)]
+    tail: bool,
+    
+    /// Tail output: text, json (one object per line) or fields:<list> (tab-separated summary fields)
+    #[clap(long, default_value = "text", requires = "tail")]
+    tail_format: TailFormat,
+    
+    /// Start capturing immediately and stream the packets to stdout as pcap (like tcpdump -w -), logging to stderr
+    #[clap(long, conflicts_with = "tail")]
+    write_stdout: bool,
+    
+    /// Load packets from a capture file at startup (`label=path` or `path`, repeatable)
+    #[clap(long = "read-file", value_name = "[LABEL=]PATH")]
+    read_files: Vec<String>,
+    
+    /// Directory POST /api/capture/load may read capture files from; without it the API loads no files
+    #[clap(long, value_name = "DIR")]
+    load_dir: Option<String>,
+    
+    /// Application-layer decoders to skip, comma-separated (e.g. DNS,NTP,RADIUS)
+    #[clap(long = "disable-protocols", value_delimiter = ',')]
+    disabled_protocols: Vec<String>,
+    
+    /// Only process the first N packets of each conversation (5-tuple), dropping the rest (0 disables)
+    #[clap(long, default_value = "0")]
+    flow_head_packets: usize,
+    
+    /// Drop frames shorter than this many bytes before decoding, e.g. 100 to ignore bare ACKs (0 disables)
+    #[clap(long, default_value = "0")]
+    min_packet_size: usize,
+    
+    /// Drop frames longer than this many bytes before decoding (0 disables)
+    #[clap(long, default_value = "0")]
+    max_packet_size: usize,
+    
+    /// Mark packets identical to one of the previous N frames as duplicates, like editcap -D (0 disables)
+    #[clap(long, default_value = "0")]
+    dedup_window: usize,
+    
+    /// Distinct source and destination IPs counted in the stats maps, and sources tracked by the SYN flood and port scan detector; packets of further addresses are counted as untracked (0 for no cap)
+    #[clap(long, default_value = "10000")]
+    max_tracked_addresses: usize,
+    
+    /// Store at most this many packets from --read-file (0 for no cap)
+    #[clap(long, default_value = "0")]
+    read_max_packets: usize,
+    
+    /// Decode traffic on a port as a protocol, e.g. 5353=DNS (repeatable)
+    #[clap(long = "decode-as", value_name = "PORT=PROTOCOL", value_parser = parse_decode_as)]
+    decode_as: Vec<(u16, String)>,
+    
+    /// Maximum nesting depth of packet header JSON served by the API
+    #[clap(long, default_value = "32")]
+    max_headers_depth: usize,
+    
+    /// Maximum size in bytes of packet header JSON served by the API
+    #[clap(long, default_value = "262144")]
+    max_headers_bytes: usize,
+    
+    /// Username for an rpcap:// remote interface (password from RUSTSHARK_RPCAP_PASSWORD)
+    #[clap(long)]
+    rpcap_username: Option<String>,
+    
+    /// What to do when packet processing falls behind the capture
+    #[clap(long, value_enum, default_value = "block")]
+    overflow_policy: OverflowPolicy,
+    
+    /// Decode frames as this link layer instead of the one the interface or file reports; the wrong one yields garbage
+    #[clap(long, value_enum, value_name = "TYPE")]
+    link_type: Option<LinkTypeOverride>,
+    
+    /// Bytes a live capture may take in before --budget-action applies, protecting shared hosts (0 for no budget)
+    #[clap(long, default_value = "0")]
+    max_total_bytes: u64,
+    
+    /// What to do once --max-total-bytes have been captured: stop the capture, or evict the oldest packets
+    #[clap(long, value_enum, default_value = "stop")]
+    budget_action: BudgetAction,
+    
+    /// Leave the OS and hardware description out of exported pcapng files (the application is still named)
+    #[clap(long)]
+    hide_host_info: bool,
+    
+    /// Save packet coloring rules to this file and load them on startup
+    #[clap(long)]
+    color_rules_file: Option<String>,
+    
+    /// When capturing from a tun device (-i tun:<name>), forward each packet unchanged to this second tun device
+    #[clap(long, value_name = "DEVICE")]
+    tun_forward: Option<String>,
+    
+    /// MTU to flag oversized packets against (defaults to the interface's own MTU)
+    #[clap(long)]
+    mtu: Option<u32>,
+    
+    /// Resolve IP addresses to hostnames with reverse DNS (in the background)
+    #[clap(long)]
+    resolve_names: bool,
+    
+    /// PEM certificate (chain) to serve the API and WebSocket over HTTPS/WSS
+    #[clap(long, requires = "tls_key")]
+    tls_cert: Option<String>,
+    
+    /// PEM private key for --tls-cert
+    #[clap(long, requires = "tls_cert")]
+    tls_key: Option<String>,
+    
+    /// JSON file mapping device names to labels, shown as the interfaces' friendly names in the API, UI and exports
+    #[clap(long, value_name = "JSON", value_parser = interfaces::read_aliases)]
+    interface_aliases: Option<HashMap<String, String>>,
+    
+    /// Seconds /api/interfaces waits for pcap to enumerate devices before answering with the last known list
+    #[clap(long, default_value = "3")]
+    interface_timeout_secs: u64,
+    
+    /// Don't start the REST API server (only useful with --tail or --write-stdout)
+    #[clap(long, requires = "stdout_output")]
+    no_server: bool,
+    
+    /// Decode built-in sample packets of every supported protocol, print a pass/fail report and exit
+    #[clap(long)]
+    self_test: bool,
+    
+    /// Print the capture interfaces (names, friendly names, state, addresses) and exit, like tcpdump -D
+    #[clap(long)]
+    list_interfaces: bool,
+    
+    /// Print --list-interfaces as JSON instead of a table
+    #[clap(long, requires = "list_interfaces")]
+    json: bool,
+}
+
+impl Args {
+    /// Application config for these arguments, given the resolved capture filter
+    fn app_config(&self, filter: Option<String>) -> AppConfig {
+        AppConfig {
+            interface: self.interface.clone(),
+            port: self.port,
+            exclude_api_traffic: !self.include_api_traffic,
+            cors_origins: self.cors_origins.clone(),
+            promiscuous: self.promiscuous,
+            capture_members: self.capture_members,
+            synchronized_start: self.synchronized_start,
+            buffer_size: self.buffer_size,
+            storage_shards: self.storage_shards as usize,
+            filter,
+            warmup_packets: self.warmup_packets,
+            warmup_ms: self.warmup_ms,
+            start_delay_ms: self.start_delay_ms,
+            stop_after_idle_secs: self.stop_after_idle_secs,
+            max_capture_seconds: self.max_capture_seconds,
+            link_poll_secs: self.link_poll_secs,
+            detection_window_secs: self.detection_window_secs,
+            syn_flood_threshold: self.syn_flood_threshold,
+            port_scan_threshold: self.port_scan_threshold,
+            snapshot_file: self.snapshot_file.clone(),
+            snapshot_interval_secs: self.snapshot_interval_secs,
+            stats_report_file: self.stats_report_file.clone(),
+            stats_report_interval_secs: self.stats_report_interval_secs,
+            stats_report_mode: self.stats_report_mode,
+            stats_report_keep: self.stats_report_keep,
+            index_file: self.index_file.clone(),
+            event_socket: self.event_socket.clone(),
+            otlp_endpoint: self.otlp_endpoint.clone(),
+            tail: self.tail,
+            write_stdout: self.write_stdout,
+            resolve_names: self.resolve_names,
+            disabled_protocols: self.disabled_protocols.clone(),
+            flow_head_packets: self.flow_head_packets,
+            min_packet_size: self.min_packet_size,
+            max_packet_size: self.max_packet_size,
+            dedup_window: self.dedup_window,
+            max_tracked_addresses: self.max_tracked_addresses,
+            read_max_packets: self.read_max_packets,
+            load_dir: self.load_dir.clone(),
+            decode_as: self.decode_as.iter().cloned().collect(),
+            max_headers_depth: self.max_headers_depth,
+            max_headers_bytes: self.max_headers_bytes,
+            rpcap_username: self.rpcap_username.clone(),
+            rpcap_password: std::env::var("RUSTSHARK_RPCAP_PASSWORD").ok(),
+            overflow_policy: self.overflow_policy,
+            link_type: self.link_type,
+            mtu: self.mtu,
+            tls_cert: self.tls_cert.clone(),
+            tls_key: self.tls_key.clone(),
+            interface_timeout_secs: self.interface_timeout_secs,
+            max_total_bytes: self.max_total_bytes,
+            budget_action: self.budget_action,
+            hide_host_info: self.hide_host_info,
+            color_rules_file: self.color_rules_file.clone(),
+            tun_forward: self.tun_forward.clone(),
+            interface_aliases: self.interface_aliases.clone().unwrap_or_default(),
+            compact_headers_secs: self.compact_headers_secs,
+        }
+    }
 }
 
 #[actix_web::main]
@@ -62,14 +382,36 @@ async fn main() -> Result<()> {
     // Parse command line arguments
     let args = Args::parse();
     
-    // Initialize logger with specified level
-    logging::init_logger(logging::get_log_level(&args.log_level));
+    // The self-test needs no interface, logger or server; exit non-zero on failure
+    if args.self_test {
+        std::process::exit(if self_test::run(&args.disabled_protocols) { 0 } else { 1 });
+    }
+    
+    // Listing interfaces needs no server either; the manager enumerates them as for the API
+    if args.list_interfaces {
+        interfaces::print_list(&CaptureManager::new(args.app_config(None)).get_interface_info(), args.json);
+        return Ok(());
+    }
+    
+    logging::set_redact_mode(args.redact);
     
-    // Set initial verbose mode based on command line flag
-    logging::set_verbose_mode(args.verbose);
+    // Initialize logger with specified level. Tail and pcap output own stdout,
+    // so their logs go to stderr without the TUI status line.
+    let stdout_output = args.tail || args.write_stdout;
+    if stdout_output {
+        logging::init_stderr_logger(logging::get_log_level(&args.log_level));
+    } else {
+        logging::init_logger(logging::get_log_level(&args.log_level));
+        
+        // Set initial verbose mode based on command line flag
+        logging::set_verbose_mode(args.verbose);
+    }
     
-    // Start a background task to handle keyboard input
-    tokio::spawn(handle_keyboard_input());
+    // Start a background task to handle keyboard input. Tail and pcap output leave
+    // the terminal in cooked mode so their output (and Ctrl+C) behave normally.
+    if !stdout_output {
+        tokio::spawn(handle_keyboard_input());
+    }
     
     // Check if running as administrator on Windows
     #[cfg(target_os = "windows")]
@@ -103,42 +445,389 @@ async fn main() -> Result<()> {
         run_windows_diagnostics().await;
     }
     
+    // A filter file is assembled and checked before anything starts capturing
+    let filter = match &args.filter_file {
+        Some(path) => {
+            let filter = bpf::read_filter_file(path)?;
+            bpf::validate(&filter)?;
+            info!("Using BPF filter from {}: {}", path.display(), filter);
+            Some(filter)
+        },
+        None => args.filter.clone(),
+    };
+    
     // Create application config
-    let config = AppConfig {
-        interface: args.interface,
-        port: args.port,
-        promiscuous: args.promiscuous,
-        buffer_size: args.buffer_size,
-        filter: args.filter,
+    let config = args.app_config(filter);
+    
+    for protocol in &config.disabled_protocols {
+        if !protocols::DECODERS.contains(&protocol.trim().to_ascii_uppercase().as_str()) {
+            warn!("Unknown protocol '{}' in --disable-protocols (known: {})", protocol, protocols::DECODERS.join(", "));
+        }
+    }
+    
+    // Load the TLS certificate up front so a bad cert or key fails before anything starts
+    let tls_config = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => Some(tls::load_server_config(cert, key)?),
+        _ => None,
     };
     
+    // Describe this host for pcapng exports now, as the commands behind it can be slow
+    tokio::task::spawn_blocking(host_info::get);
+    
     // Initialize capture manager
     let capture_manager = Arc::new(RwLock::new(CaptureManager::new(config.clone())));
 
+    // Startup goes on when a snapshot or index cannot be restored; the
+    // failures are reported in the event log and by the health check
+    let events = capture_manager.read().await.event_log();
+    let mut restore_errors = Vec::new();
+    
+    // Restore the previous packet buffer and keep snapshotting it
+    if let Some(path) = &config.snapshot_file {
+        let path = PathBuf::from(path);
+        
+        match snapshot::load(&path) {
+            Ok(Some(snap)) => capture_manager.write().await.restore_snapshot(snap),
+            Ok(None) => info!("No snapshot to restore at {}", path.display()),
+            Err(e) => {
+                let message = format!("Failed to load snapshot {}: {}", path.display(), e);
+                events.error(message.clone());
+                restore_errors.push(message);
+            },
+        }
+        
+        tokio::spawn(run_snapshot_writer(
+            capture_manager.clone(),
+            path,
+            Duration::from_secs(config.snapshot_interval_secs.max(1)),
+        ));
+    }
+    
+    // Recover the packet index a crashed run left behind, then keep indexing into it
+    if let Some(path) = &config.index_file {
+        let path = PathBuf::from(path);
+        let mut manager = capture_manager.write().await;
+        
+        match index::recover(&path) {
+            Ok(Some(recovered)) => manager.restore_index(recovered),
+            Ok(None) => info!("No packet index to recover at {}", path.display()),
+            Err(e) => {
+                let message = format!("Failed to recover packet index {}: {}", path.display(), e);
+                events.error(message.clone());
+                restore_errors.push(message);
+            },
+        }
+        
+        manager.enable_index(&path)?;
+    }
+    
+    // Serve the live packet feed to local tools
+    if let Some(path) = &config.event_socket {
+        event_socket::serve(path, capture_manager.read().await.packet_feed())?;
+    }
+    
+    // Push capture metrics to an OpenTelemetry collector
+    if let Some(endpoint) = &config.otlp_endpoint {
+        otlp::spawn(endpoint, capture_manager.read().await.subscribe_to_stats());
+    }
+    
+    // Stop captures that go quiet for longer than the idle timeout, use up their
+    // byte budget or run past the capture length cap
+    tokio::spawn(run_idle_watchdog(capture_manager.clone()));
+    
+    // Record the capture interfaces' links going down and up in the session event log
+    if config.link_poll_secs > 0 {
+        tokio::spawn(run_link_watcher(capture_manager.clone(), config.link_poll_secs));
+    }
+    
+    // Trade CPU for memory by keeping the buffered packets' headers as text
+    if config.compact_headers_secs > 0 {
+        tokio::spawn(run_header_compactor(capture_manager.clone(), config.compact_headers_secs));
+    }
+    
+    // Sample the rates every second, reporting the stats to a file on some of the ticks
+    let stats_reporter = config.stats_report_file.as_ref().map(|path| {
+        let reporter = StatsReporter::new(PathBuf::from(path), config.stats_report_mode, config.stats_report_keep);
+        (reporter, config.stats_report_interval_secs.max(1))
+    });
+    tokio::spawn(run_rate_sampler(capture_manager.clone(), stats_reporter));
+    
+    // Load any capture files given on the command line (replacing a restored snapshot)
+    if !args.read_files.is_empty() {
+        let files: Vec<FileSource> = args.read_files.iter().map(|f| FileSource::parse(f)).collect();
+        let read = tokio::task::spawn_blocking(move || ReadFiles::read(files)).await??;
+        let summary = capture_manager.write().await.load_files(read, false, None)?;
+        info!("Capture files contained {} packets, {} retained", summary.read, summary.retained);
+    }
+
+    // In tail mode, start capturing right away and print packets as they arrive
+    if config.tail {
+        if config.interface.is_none() {
+            return Err(anyhow!("--tail requires --interface"));
+        }
+        
+        let mut manager = capture_manager.write().await;
+        tokio::spawn(tail::run_tail(manager.subscribe_to_packets(), args.tail_format.clone()));
+        manager.start_capture().await?;
+    }
+    
+    // With --write-stdout, start capturing right away and stream pcap to stdout
+    if config.write_stdout {
+        if config.interface.is_none() {
+            return Err(anyhow!("--write-stdout requires --interface"));
+        }
+        
+        // The header names the link type, known once the capture is open
+        let mut manager = capture_manager.write().await;
+        let packets = manager.subscribe_to_packets();
+        manager.start_capture().await?;
+        tokio::spawn(tail::run_pcap_stdout(packets, manager.link_type()));
+    }
+    
+    if args.no_server {
+        info!("API server disabled, press Ctrl+C to stop");
+        tokio::signal::ctrl_c().await?;
+        
+        let mut manager = capture_manager.write().await;
+        if manager.get_status() {
+            manager.stop_capture().await?;
+        }
+        return Ok(());
+    }
+
     // We'll skip listing interfaces at startup and let the API handle it when needed
     info!("Network interfaces will be detected when requested");
     
     // Create a shared state for our application
     let app_state = web::Data::new(capture_manager.clone());
     
-    info!("Starting RustShark API server on port {}", config.port);
+    // Health probes read the capture state through atomics only. Capture files
+    // and startup captures that fail have ended the process by now.
+    let health_state = web::Data::new(
+        HealthState::new(capture_manager.read().await.running_flag()).with_restore_errors(restore_errors)
+    );
+    health_state.set_ready(true);
+    
+    // Origins allowed to call the API from a browser (none: no CORS headers)
+    let cors_policy = web::Data::new(CorsPolicy::new(config.cors_origins.clone()));
+    if !config.cors_origins.is_empty() {
+        info!("Allowing cross-origin requests from {}", config.cors_origins.join(", "));
+    }
+    
+    let scheme = if tls_config.is_some() { "https" } else { "http" };
+    info!("Starting RustShark API server on {}://{}:{}", scheme, API_HOST, config.port);
     
     // Reset logging counters before starting the server
     logging::reset_counters();
     
-    // Start the HTTP server
-    HttpServer::new(move || {
+    // Start the HTTP server (HTTPS, and WSS for the WebSocket, with --tls-cert)
+    let server = HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
+            .app_data(health_state.clone())
+            .app_data(cors_policy.clone())
             .configure(routes::configure)
-    })
-    .bind(format!("127.0.0.1:{}", config.port))?
-    .run()
-    .await?;
+    });
+    let address = format!("{}:{}", API_HOST, config.port);
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_0_23(address, tls_config)?,
+        None => server.bind(address)?,
+    };
+    server.run().await?;
     
     Ok(())
 }
 
+/// Parse a `--decode-as` value of the form `PORT=PROTOCOL`
+fn parse_decode_as(value: &str) -> Result<(u16, String), String> {
+    let (port, protocol) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected PORT=PROTOCOL, got '{}'", value))?;
+    let port = port.trim().parse::<u16>().map_err(|e| format!("invalid port '{}': {}", port, e))?;
+    let protocol = protocol.trim();
+    if protocol.is_empty() {
+        return Err("protocol must not be empty".to_string());
+    }
+    Ok((port, protocol.to_ascii_uppercase()))
+}
+
+/// Stop the capture once it has gone without packets for `stop_after_idle_secs`,
+/// has used up its `max_total_bytes` budget with the stop action, or has run
+/// for `max_capture_seconds`
+async fn run_idle_watchdog(capture_manager: Arc<RwLock<CaptureManager>>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    
+    loop {
+        ticker.tick().await;
+        
+        {
+            let manager = capture_manager.read().await;
+            if !manager.idle_timeout_expired() && !manager.byte_budget_exhausted() && !manager.capture_cap_reached() {
+                continue;
+            }
+        }
+        
+        // Check again under the write lock, in case a packet or a restart came in between
+        let mut manager = capture_manager.write().await;
+        let reason = if manager.capture_cap_reached() {
+            // Operators set the cap to catch forgotten captures, so make it stand out
+            warn!(
+                "CAPTURE CAP REACHED: capture on {} has run for {} s, the --max-capture-seconds limit; stopping it",
+                manager.active_interfaces().join(", "),
+                manager.get_max_capture_seconds().unwrap_or_default()
+            );
+            "Maximum capture length reached"
+        } else if manager.byte_budget_exhausted() {
+            "Byte budget used up"
+        } else if manager.idle_timeout_expired() {
+            "No packets received within the idle timeout"
+        } else {
+            continue;
+        };
+        info!("{}, stopping capture", reason);
+        if let Err(e) = manager.stop_capture().await {
+            warn!("Failed to stop capture: {}", e);
+        }
+    }
+}
+
+/// Poll the link status of the interfaces being captured every `poll_secs`,
+/// recording each change in the session's event log. The capture keeps
+/// running through a flap, so the log is what explains the gap it leaves.
+async fn run_link_watcher(capture_manager: Arc<RwLock<CaptureManager>>, poll_secs: u64) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(poll_secs));
+    let mut links: HashMap<String, bool> = HashMap::new();
+    let mut session = None;
+    
+    loop {
+        ticker.tick().await;
+        
+        let (interfaces, events, session_id) = {
+            let manager = capture_manager.read().await;
+            (manager.active_interfaces(), manager.event_log(), manager.get_session_id())
+        };
+        
+        // A new session starts from the links as they are now
+        if session_id != session {
+            links.clear();
+            session = session_id;
+        }
+        links.retain(|interface, _| interfaces.contains(interface));
+        if interfaces.is_empty() {
+            continue;
+        }
+        
+        // Reading the status may run a command (Windows), so keep it off the runtime threads
+        let statuses = tokio::task::spawn_blocking(move || CaptureManager::link_states(&interfaces))
+            .await
+            .unwrap_or_default();
+        
+        for (interface, up) in statuses {
+            if links.insert(interface.clone(), up).is_some_and(|was_up| was_up != up) {
+                events.link_change(&interface, up);
+            }
+        }
+    }
+}
+
+/// Compact the headers of the packets stored since the last pass every
+/// `interval_secs`, off the runtime threads as a large buffer takes a while
+async fn run_header_compactor(capture_manager: Arc<RwLock<CaptureManager>>, interval_secs: u64) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval_secs));
+    
+    loop {
+        ticker.tick().await;
+        
+        let store = capture_manager.read().await.packet_store();
+        match tokio::task::spawn_blocking(move || store.compact_headers()).await {
+            Ok(0) => {},
+            Ok(compacted) => debug!("Compacted the headers of {} packets", compacted),
+            Err(e) => warn!("Header compaction failed: {}", e),
+        }
+    }
+}
+
+/// Sample the packet and byte totals once a second for the rate trend, and
+/// every `interval` seconds write a stats report, while a capture runs and
+/// once more after it stops
+async fn run_rate_sampler(
+    capture_manager: Arc<RwLock<CaptureManager>>,
+    stats_reporter: Option<(StatsReporter, u64)>,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut ticks: u64 = 0;
+    let mut reported_running = false;
+    
+    loop {
+        ticker.tick().await;
+        ticks += 1;
+        
+        let report = {
+            let manager = capture_manager.read().await;
+            manager.tick_rates();
+            
+            match &stats_reporter {
+                Some((_, interval)) if ticks.is_multiple_of(*interval) => {
+                    let running = manager.get_status();
+                    let due = running || reported_running;
+                    reported_running = running;
+                    due.then(|| StatsReport {
+                        timestamp: Utc::now(),
+                        running,
+                        interfaces: manager.active_interfaces(),
+                        stats: manager.get_stats(),
+                    })
+                },
+                _ => None,
+            }
+        };
+        
+        // Write off the ticker so a slow disk does not skew the rate samples
+        if let (Some(report), Some((reporter, _))) = (report, &stats_reporter) {
+            let reporter = reporter.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = reporter.write(&report) {
+                    warn!("Failed to write stats report: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// Periodically write the packet buffer to the snapshot file
+async fn run_snapshot_writer(
+    capture_manager: Arc<RwLock<CaptureManager>>,
+    path: PathBuf,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut last_written: Option<(usize, u64)> = None;
+    
+    loop {
+        ticker.tick().await;
+        
+        let snap = {
+            let manager = capture_manager.read().await;
+            
+            // Skip the write if nothing changed since the last snapshot
+            let fingerprint = manager.snapshot_fingerprint();
+            if last_written == Some(fingerprint) {
+                continue;
+            }
+            last_written = Some(fingerprint);
+            
+            manager.create_snapshot()
+        };
+        
+        let path = path.clone();
+        match tokio::task::spawn_blocking(move || snapshot::save(&path, &snap)).await {
+            Ok(Ok(())) => debug!("Snapshot written"),
+            Ok(Err(e)) => warn!("Failed to write snapshot: {}", e),
+            Err(e) => warn!("Snapshot writer task failed: {}", e),
+        }
+    }
+}
+
 /// Handle keyboard input in a background task
 async fn handle_keyboard_input() {
     // Try to enable raw mode, but don't fail if it doesn't work
@@ -196,13 +885,10 @@ async fn run_windows_diagnostics() {
     info!("Running Windows network diagnostics...");
     
     // Check Npcap service status
-    let npcap_service = Command::new("powershell")
-        .args(&["-Command", "Get-Service npcap | Select-Object -Property Name, Status | ConvertTo-Json"])
-        .output();
+    let npcap_service = host_info::powershell("Get-Service npcap | Select-Object -Property Name, Status | ConvertTo-Json");
         
     match npcap_service {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout) => {
             info!("Npcap service status: {}", stdout);
         },
         Err(e) => {
@@ -211,13 +897,10 @@ async fn run_windows_diagnostics() {
     }
     
     // List network adapters with GUIDs
-    let adapters = Command::new("powershell")
-        .args(&["-Command", "Get-NetAdapter | Select-Object -Property Name, InterfaceDescription, InterfaceGuid, Status | ConvertTo-Json"])
-        .output();
+    let adapters = host_info::powershell("Get-NetAdapter | Select-Object -Property Name, InterfaceDescription, InterfaceGuid, Status | ConvertTo-Json");
         
     match adapters {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout) => {
             info!("Network adapters: {}", stdout);
         },
         Err(e) => {
@@ -227,13 +910,10 @@ async fn run_windows_diagnostics() {
     
     // Check Windows Packet Capture permissions
     let process_id = std::process::id();
-    let permissions = Command::new("powershell")
-        .args(&["-Command", &format!("Get-Process -Id {} | Select-Object -Property ProcessName, Path, Company, StartTime | ConvertTo-Json", process_id)])
-        .output();
+    let permissions = host_info::powershell(&format!("Get-Process -Id {} | Select-Object -Property ProcessName, Path, Company, StartTime | ConvertTo-Json", process_id));
         
     match permissions {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout) => {
             info!("Process info: {}", stdout);
         },
         Err(e) => {
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+
+/// Kind of suspicious activity an alert reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// A source sent an unusual number of SYNs without ACK
+    SynFlood,
+    
+    /// A source probed an unusual number of distinct destination ports
+    PortScan,
+}
+
+/// A security alert raised by the traffic detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    /// Sequential identifier for this alert
+    pub id: u64,
+    
+    /// Time the threshold was crossed
+    pub timestamp: DateTime<Utc>,
+    
+    /// Kind of activity detected
+    pub kind: AlertKind,
+    
+    /// Source address responsible for the activity
+    pub source_ip: IpAddr,
+    
+    /// Observed count (SYNs or distinct ports) within the window
+    pub count: usize,
+    
+    /// Threshold that was exceeded
+    pub threshold: usize,
+    
+    /// Length of the sliding window in seconds
+    pub window_secs: u64,
+    
+    /// Human-readable description
+    pub message: String,
+}
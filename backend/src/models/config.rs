@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Address the REST API server listens on
+pub const API_HOST: &str = "127.0.0.1";
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,12 +13,249 @@ pub struct AppConfig {
     /// Port for the REST API server
     pub port: u16,
     
+    /// Leave the API server's own traffic out of captures
+    pub exclude_api_traffic: bool,
+    
+    /// Browser origins allowed to call the API cross-origin (`*` for any)
+    pub cors_origins: Vec<String>,
+    
     /// Enable promiscuous mode
     pub promiscuous: bool,
     
+    /// Capture a bond or bridge on each of its member interfaces
+    pub capture_members: bool,
+    
+    /// Arm the captures of a multi-interface session and release them together
+    pub synchronized_start: bool,
+    
     /// Packet buffer size
     pub buffer_size: usize,
     
+    /// Shards of the packet storage
+    pub storage_shards: usize,
+    
     /// BPF filter expression
     pub filter: Option<String>,
+    
+    /// Number of packets to discard at the start of a capture (warm-up)
+    pub warmup_packets: usize,
+    
+    /// Time window after the capture's first packet during which packets are discarded (milliseconds)
+    pub warmup_ms: u64,
+    
+    /// Delay before the capture starts reading packets (milliseconds)
+    pub start_delay_ms: u64,
+    
+    /// Stop a capture that has received no packets for this long (seconds, 0 disables)
+    pub stop_after_idle_secs: u64,
+    
+    /// Stop any capture that has run this long, however it was started (seconds, 0 disables)
+    pub max_capture_seconds: u64,
+    
+    /// How often the link of each capture interface is checked for going down or up (seconds, 0 disables)
+    pub link_poll_secs: u64,
+    
+    /// Sliding window for SYN flood and port scan detection (seconds)
+    pub detection_window_secs: u64,
+    
+    /// SYNs without ACK per source within the window that raise an alert (0 disables)
+    pub syn_flood_threshold: usize,
+    
+    /// Distinct destination ports per source within the window that raise an alert (0 disables)
+    pub port_scan_threshold: usize,
+    
+    /// File used to persist the packet buffer across restarts
+    pub snapshot_file: Option<String>,
+    
+    /// Interval between snapshot writes (seconds)
+    pub snapshot_interval_secs: u64,
+    
+    /// File the stats are periodically reported to, for a timeline of unattended runs
+    pub stats_report_file: Option<String>,
+    
+    /// Seconds between stats reports
+    pub stats_report_interval_secs: u64,
+    
+    /// Whether reports are appended to the file or written to rotated files next to it
+    pub stats_report_mode: StatsReportMode,
+    
+    /// Rotated reports kept, oldest deleted first (0 keeps all)
+    pub stats_report_keep: usize,
+    
+    /// Memory-mapped file indexing stored packets, recovered after a crash
+    pub index_file: Option<String>,
+    
+    /// Unix socket (or Windows named pipe) streaming a JSON summary line per packet
+    pub event_socket: Option<String>,
+    
+    /// OTLP/HTTP collector that capture metrics are pushed to
+    pub otlp_endpoint: Option<String>,
+    
+    /// Print a summary line to stdout for each captured packet
+    pub tail: bool,
+    
+    /// Stream captured packets to stdout as pcap
+    pub write_stdout: bool,
+    
+    /// Resolve IP addresses to hostnames with reverse DNS
+    pub resolve_names: bool,
+    
+    /// Application-layer decoders to skip (e.g. "DNS", "RADIUS")
+    pub disabled_protocols: Vec<String>,
+    
+    /// Only process the first packets of each conversation, dropping the rest (0 disables)
+    pub flow_head_packets: usize,
+    
+    /// Frames shorter than this many bytes are dropped before decoding (0 disables)
+    pub min_packet_size: usize,
+    
+    /// Frames longer than this many bytes are dropped before decoding (0 disables)
+    pub max_packet_size: usize,
+    
+    /// Frames each packet is compared with to detect duplicates (0 disables)
+    pub dedup_window: usize,
+    
+    /// Distinct addresses counted in each of the stats source/destination maps,
+    /// and sources tracked by the threat detector (0 for no cap)
+    pub max_tracked_addresses: usize,
+    
+    /// Maximum number of packets stored when loading capture files (0 for no cap)
+    pub read_max_packets: usize,
+    
+    /// Directory the API may load capture files from (none: the API loads no files)
+    pub load_dir: Option<String>,
+    
+    /// Ports whose traffic is decoded as the given protocol regardless of the default heuristics
+    pub decode_as: HashMap<u16, String>,
+    
+    /// Maximum nesting depth of header JSON returned by the packet detail endpoint
+    pub max_headers_depth: usize,
+    
+    /// Maximum serialized size in bytes of header JSON returned by the packet detail endpoint
+    pub max_headers_bytes: usize,
+    
+    /// Username for authenticating to an rpcap:// remote source
+    pub rpcap_username: Option<String>,
+    
+    /// Password for authenticating to an rpcap:// remote source
+    #[serde(skip_serializing)]
+    pub rpcap_password: Option<String>,
+    
+    /// What the capture thread does when the packet processor falls behind
+    pub overflow_policy: OverflowPolicy,
+    
+    /// Link layer frames are decoded as, whatever the capture source or file reports
+    pub link_type: Option<LinkTypeOverride>,
+    
+    /// MTU packets are compared against; read from the capture interface when unset
+    pub mtu: Option<u32>,
+    
+    /// PEM certificate (chain) for serving the API over HTTPS
+    pub tls_cert: Option<String>,
+    
+    /// PEM private key for serving the API over HTTPS
+    pub tls_key: Option<String>,
+    
+    /// Seconds an API request waits for interface enumeration before using the last known list
+    pub interface_timeout_secs: u64,
+    
+    /// Bytes a live capture may take in before `budget_action` applies (0 for no budget)
+    pub max_total_bytes: u64,
+    
+    /// What happens once a live capture has taken in `max_total_bytes`
+    pub budget_action: BudgetAction,
+    
+    /// Leave the OS and hardware out of the section header of pcapng exports
+    pub hide_host_info: bool,
+    
+    /// File the packet coloring rules are saved to and loaded from
+    pub color_rules_file: Option<String>,
+    
+    /// Tun device packets captured from a tun device are forwarded to unchanged
+    pub tun_forward: Option<String>,
+    
+    /// Labels shown as the friendly names of the devices they are keyed by
+    pub interface_aliases: HashMap<String, String>,
+    
+    /// Seconds between passes compacting the stored packets' headers (0 disables)
+    pub compact_headers_secs: u64,
+}
+
+/// What a capture does once it has used up its byte budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BudgetAction {
+    /// Drop further packets and stop the capture
+    #[default]
+    Stop,
+    
+    /// Keep capturing, evicting the oldest packets to hold the buffer within the budget
+    Evict,
+}
+
+/// How periodic stats reports are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatsReportMode {
+    /// Add each report to the file as a JSON line
+    #[default]
+    Append,
+    
+    /// Write each report to its own timestamped file, keeping the newest ones
+    Rotate,
+}
+
+/// Link layer to decode frames as in place of the one the source reports.
+/// Forcing the wrong one decodes every frame into garbage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum LinkTypeOverride {
+    /// Ethernet II frames
+    #[serde(rename = "ethernet")]
+    Ethernet,
+    
+    /// IPv4 or IPv6 packets without a link-layer header
+    #[value(name = "rawip")]
+    #[serde(rename = "rawip")]
+    RawIp,
+    
+    /// Linux cooked captures, as the `any` device delivers them
+    #[serde(rename = "sll")]
+    Sll,
+    
+    /// BSD loopback: the address family in 4 bytes, then the IP packet
+    #[serde(rename = "null")]
+    Null,
+    
+    /// 802.11 frames without a radiotap header
+    #[value(name = "802.11")]
+    #[serde(rename = "802.11")]
+    Ieee80211,
+}
+
+impl LinkTypeOverride {
+    /// The libpcap link type the parser is given for frames
+    pub fn linktype(self) -> pcap::Linktype {
+        match self {
+            Self::Ethernet => pcap::Linktype::ETHERNET,
+            Self::RawIp => pcap::Linktype::RAW,
+            Self::Sll => pcap::Linktype::LINUX_SLL,
+            Self::Null => pcap::Linktype::NULL,
+            Self::Ieee80211 => pcap::Linktype::IEEE802_11,
+        }
+    }
+}
+
+/// Behavior when the queue between the capture thread and the packet processor is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Wait for room, pushing the loss into kernel drops
+    #[default]
+    Block,
+    
+    /// Discard the frame that did not fit
+    DropNewest,
+    
+    /// Discard the oldest queued frame to make room
+    DropOldest,
 } 
\ No newline at end of file
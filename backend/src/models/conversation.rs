@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Traffic between two endpoints over one transport 5-tuple (or address pair, for
+/// protocols without ports), in both directions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    /// Stable ID of the conversation, derived from its transport and endpoints
+    pub id: String,
+    
+    /// Transport carrying the conversation ("TCP", "UDP", "SCTP", or the IP protocol)
+    pub transport: String,
+    
+    /// Application protocol of the latest classified packet (the transport when none was)
+    pub protocol: String,
+    
+    /// Every application protocol the packets were classified as, in name order
+    pub protocols: Vec<String>,
+    
+    /// Endpoint that sent the first packet seen
+    pub address_a: String,
+    pub port_a: Option<u16>,
+    
+    /// Endpoint the first packet seen was sent to
+    pub address_b: String,
+    pub port_b: Option<u16>,
+    
+    pub packets_a_to_b: usize,
+    pub bytes_a_to_b: usize,
+    pub packets_b_to_a: usize,
+    pub bytes_b_to_a: usize,
+    
+    /// Packets past the flow head that were dropped unprocessed (flow-head mode)
+    pub skipped_packets: usize,
+    pub skipped_bytes: usize,
+    
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    
+    /// Time from the SYN to the SYN/ACK, in milliseconds (TCP connections whose handshake was seen)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handshake_rtt_ms: Option<f64>,
+    
+    /// Bytes per second over the last minute of the capture, oldest first
+    /// (requested with `sparkline=true`, kept for the busiest conversations only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sparkline: Option<Vec<usize>>,
+    
+    /// IDs of the per-5-tuple conversations combined into this one (grouped by host only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_ids: Vec<String>,
+}
+
+impl Conversation {
+    /// Columns of the CSV export, in order
+    pub const CSV_FIELDS: &'static [&'static str] = &[
+        "id", "transport", "protocol", "protocols", "address_a", "port_a", "address_b", "port_b",
+        "packets", "bytes", "packets_a_to_b", "bytes_a_to_b", "packets_b_to_a", "bytes_b_to_a",
+        "first_seen", "last_seen", "duration", "handshake_rtt_ms",
+    ];
+    
+    /// Bytes in both directions
+    pub fn bytes(&self) -> usize {
+        self.bytes_a_to_b + self.bytes_b_to_a
+    }
+    
+    /// Packets in both directions
+    pub fn packets(&self) -> usize {
+        self.packets_a_to_b + self.packets_b_to_a
+    }
+    
+    /// Seconds from the first to the last packet
+    pub fn duration(&self) -> f64 {
+        (self.last_seen - self.first_seen).num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0
+    }
+}
+
+/// Order of an exported conversation table: counts and duration largest
+/// first, times earliest first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationSort {
+    #[default]
+    Bytes,
+    Packets,
+    Duration,
+    FirstSeen,
+    LastSeen,
+}
+
+impl ConversationSort {
+    pub fn sort(self, conversations: &mut [Conversation]) {
+        match self {
+            ConversationSort::Bytes => conversations.sort_by_key(|c| std::cmp::Reverse(c.bytes())),
+            ConversationSort::Packets => conversations.sort_by_key(|c| std::cmp::Reverse(c.packets())),
+            ConversationSort::Duration => conversations.sort_by_key(|c| std::cmp::Reverse(c.last_seen - c.first_seen)),
+            ConversationSort::FirstSeen => conversations.sort_by_key(|c| c.first_seen),
+            ConversationSort::LastSeen => conversations.sort_by_key(|c| c.last_seen),
+        }
+    }
+}
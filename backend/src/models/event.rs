@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Severity of a capture event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A warning or error raised on the capture path (parse failures, interface
+/// issues), or a change of a capture interface's link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureEvent {
+    /// Severity of the event
+    pub level: EventLevel,
+    
+    /// Human-readable description
+    pub message: String,
+    
+    /// Time the event occurred
+    pub timestamp: DateTime<Utc>,
+    
+    /// Interface whose link went up or down (link changes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    
+    /// Whether the link came up or went down (link changes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_up: Option<bool>,
+}
+
+impl CaptureEvent {
+    /// An event occurring now
+    pub fn new(level: EventLevel, message: String) -> Self {
+        Self {
+            level,
+            message,
+            timestamp: Utc::now(),
+            interface: None,
+            link_up: None,
+        }
+    }
+}
@@ -39,4 +39,45 @@ pub struct Filter {
     
     /// Whether this filter is currently active
     pub active: bool,
-} 
\ No newline at end of file
+}
+
+/// A display filter and the color label of packets matching it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorRule {
+    /// User-friendly name for this rule
+    #[serde(default)]
+    pub name: String,
+    
+    /// Display filter expression (e.g., "tcp.flags.rst == true")
+    pub filter: String,
+    
+    /// Color label given to matching packets (e.g., "red" or "#ff6060")
+    pub color: String,
+}
+
+/// A BPF expression compiled to the instructions the kernel runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledFilter {
+    /// Expression that was compiled
+    pub filter: String,
+    
+    /// Link-layer type the program was compiled for (e.g. "EN10MB")
+    pub link_type: String,
+    
+    /// Whether libpcap's optimizer was applied
+    pub optimized: bool,
+    
+    pub instructions: Vec<BpfInstruction>,
+}
+
+/// One BPF instruction, raw and disassembled as `tcpdump -d` prints it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BpfInstruction {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+    
+    /// Disassembly, e.g. "(002) jeq      #0x800           jt 3\tjf 5"
+    pub text: String,
+}
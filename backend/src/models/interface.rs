@@ -15,6 +15,10 @@ pub struct InterfaceInfo {
     /// IPv4 address (if available)
     pub ipv4_address: Option<String>,
     
+    /// Every IPv4 and IPv6 address assigned to the interface
+    #[serde(default)]
+    pub ip_addresses: Vec<String>,
+    
     /// MAC address (if available)
     pub mac_address: Option<String>,
     
@@ -23,6 +27,9 @@ pub struct InterfaceInfo {
     
     /// Whether this interface is up/active
     pub is_up: bool,
+    
+    /// Maximum transmission unit (if the platform reports it)
+    pub mtu: Option<u32>,
 }
 
 impl InterfaceInfo {
@@ -33,9 +40,11 @@ impl InterfaceInfo {
             friendly_name: None,
             description: None,
             ipv4_address: None,
+            ip_addresses: Vec::new(),
             mac_address: None,
             is_loopback: false,
             is_up: true,
+            mtu: None,
         }
     }
     
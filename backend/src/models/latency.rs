@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Request/response protocol whose response times are measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyProtocol {
+    /// Queries matched to responses by transaction ID
+    Dns,
+    
+    /// Requests matched to responses in order on each TCP stream
+    Http,
+}
+
+impl FromStr for LatencyProtocol {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "dns" => Ok(LatencyProtocol::Dns),
+            "http" => Ok(LatencyProtocol::Http),
+            other => Err(format!("Unknown latency protocol '{}', expected dns or http", other)),
+        }
+    }
+}
+
+/// Measured response times of one protocol in the current capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyReport {
+    pub protocol: LatencyProtocol,
+    
+    /// Responses matched to a query
+    pub responses: usize,
+    
+    /// Queries that went unanswered for longer than the response timeout
+    pub timeouts: usize,
+    
+    /// Queries still waiting for a response
+    pub pending: usize,
+    
+    /// Response times in milliseconds; min, avg and max cover every match,
+    /// p95 the most recent ones
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+}
@@ -2,4 +2,14 @@ pub mod config;
 pub mod packet;
 pub mod filter;
 pub mod stats;
-pub mod interface; 
\ No newline at end of file
+pub mod interface;
+pub mod alert; 
+pub mod event;
+pub mod neighbor;
+pub mod mtu;
+pub mod latency;
+pub mod conversation;
+pub mod wifi;
+pub mod timing;
+pub mod object;
+pub mod rtp;
\ No newline at end of file
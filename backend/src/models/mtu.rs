@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// What the capture has seen about the path MTU towards one destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathMtuHint {
+    /// Destination IP address
+    pub destination: String,
+    
+    /// Largest IP packet seen towards the destination
+    pub largest_packet: usize,
+    
+    /// Packets towards the destination at or above the interface MTU
+    pub oversized_packets: usize,
+    
+    /// ICMP "fragmentation needed" messages received for the destination
+    pub frag_needed: usize,
+    
+    /// Smallest next-hop MTU reported by those messages
+    pub reported_mtu: Option<u16>,
+    
+    /// Router that reported the smallest next-hop MTU
+    pub reported_by: Option<String>,
+    
+    /// Best guess at the path MTU: the reported MTU, else the interface MTU
+    pub path_mtu_hint: Option<u32>,
+    
+    pub last_seen: DateTime<Utc>,
+}
+
+/// MTU statistics of the current capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MtuReport {
+    /// MTU packets are compared against (configured or read from the interface)
+    pub interface_mtu: Option<u32>,
+    
+    /// Packets at or above the interface MTU
+    pub oversized_packets: usize,
+    
+    /// ICMP "fragmentation needed" messages seen
+    pub frag_needed_messages: usize,
+    
+    /// Per-destination hints, destinations with reported MTUs first
+    pub paths: Vec<PathMtuHint>,
+}
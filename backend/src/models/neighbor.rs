@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// A network device announced by LLDP or CDP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbor {
+    /// Discovery protocol that announced the device ("LLDP" or "CDP")
+    pub protocol: String,
+    
+    /// Chassis ID (LLDP) or device ID (CDP)
+    pub chassis_id: String,
+    
+    /// Port of the announcing device the frames were sent from
+    pub port_id: String,
+    
+    /// Port description (LLDP)
+    pub port_description: Option<String>,
+    
+    /// System name (LLDP)
+    pub system_name: Option<String>,
+    
+    /// System description (LLDP) or software version (CDP)
+    pub system_description: Option<String>,
+    
+    /// Hardware platform (CDP)
+    pub platform: Option<String>,
+    
+    /// Management or device addresses
+    pub addresses: Vec<String>,
+    
+    /// Advertised capabilities (e.g. "bridge", "router")
+    pub capabilities: Vec<String>,
+    
+    /// Source MAC address of the announcements
+    pub source_mac: Option<String>,
+    
+    /// Local interface the announcements were seen on
+    pub interface: String,
+    
+    /// Advertised time to live in seconds
+    pub ttl: u64,
+    
+    /// First and most recent announcement
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    
+    /// Number of announcements seen
+    pub announcements: u64,
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+/// The files carved out of the TCP connection of a packet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamObjects {
+    /// Packet the stream was selected by
+    pub packet_id: u64,
+    
+    /// Endpoint that opened the connection (IP:port), and the one it connected to
+    pub client: String,
+    pub server: String,
+    
+    /// Bytes reassembled from each side
+    pub client_bytes: usize,
+    pub server_bytes: usize,
+    
+    /// Whether a segment missing from the capture (or no longer stored) cut
+    /// the reassembly short; objects after it cannot be carved
+    pub gaps: bool,
+    
+    pub objects: Vec<StreamObject>,
+}
+
+/// One transferred file, as listed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamObject {
+    /// Position in the stream's list, the `n` of `/api/streams/{id}/objects/{n}`
+    pub index: usize,
+    
+    /// Protocol that transferred it ("HTTP")
+    pub protocol: String,
+    
+    /// Packet carrying the start of the message the object came in
+    pub packet_id: Option<u64>,
+    
+    /// Host the object was requested from, when the request named it
+    pub hostname: Option<String>,
+    
+    /// Request that transferred it, as `METHOD target`
+    pub request: Option<String>,
+    
+    /// Status of the response carrying it; null for uploads
+    pub status_code: Option<u16>,
+    
+    /// Sent by the client (a request body) rather than received
+    pub upload: bool,
+    
+    /// Name taken from the request target, `object` when it has none
+    pub filename: String,
+    pub content_type: Option<String>,
+    
+    /// Content encoding the object was sent with; gzip and deflate bodies are
+    /// decoded when downloaded, others are served as sent
+    pub content_encoding: Option<String>,
+    
+    /// Bytes of the object as sent, before any content encoding is undone
+    pub size: usize,
+    
+    /// Whether the whole object was captured (its declared length, or its last chunk)
+    pub complete: bool,
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use pcap::Linktype;
+use std::borrow::Cow;
+use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
 
 /// Represents a captured network packet
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +46,10 @@ pub struct Packet {
     #[serde(skip_serializing)]
     pub raw_data: Vec<u8>,
     
+    /// Link type (LINKTYPE_* value) `raw_data` was decoded as, written into exports
+    #[serde(skip, default = "ethernet")]
+    pub link_type: Linktype,
+    
     /// Parsed packet headers as JSON
     pub headers: serde_json::Value,
     
@@ -50,6 +58,10 @@ pub struct Packet {
     
     /// Additional metadata
     pub metadata: serde_json::Value,
+    
+    /// `headers` as JSON text once compacted, `headers` then being null
+    #[serde(skip)]
+    pub compact_headers: Option<Box<str>>,
 }
 
 /// A more concise representation of a packet for list views
@@ -75,4 +87,317 @@ pub struct PacketSummary {
     
     /// Brief description of the packet
     pub info: String,
-} 
\ No newline at end of file
+    
+    /// Resolved source hostname (with --resolve-names)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_host: Option<String>,
+    
+    /// Resolved destination hostname (with --resolve-names)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_host: Option<String>,
+    
+    /// Seconds since the capture session started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_time: Option<f64>,
+    
+    /// Seconds since the previous packet in the listing's order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_time: Option<f64>,
+    
+    /// Color label of the first coloring rule the packet matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl PacketSummary {
+    /// Names of the serialized fields, for clients selecting a subset of columns
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "timestamp",
+        "protocol",
+        "source",
+        "destination",
+        "length",
+        "info",
+        "source_host",
+        "destination_host",
+        "relative_time",
+        "delta_time",
+        "color",
+    ];
+    
+    /// Serialize only the requested fields into a partial JSON object
+    pub fn project(&self, fields: &[String]) -> serde_json::Value {
+        let mut projected = serde_json::Map::new();
+        
+        if let Ok(serde_json::Value::Object(full)) = serde_json::to_value(self) {
+            for field in fields {
+                if let Some(value) = full.get(field) {
+                    projected.insert(field.clone(), value.clone());
+                }
+            }
+        }
+        
+        serde_json::Value::Object(projected)
+    }
+}
+
+/// A packet found by a decoded-field search, with the fields that matched
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldMatch {
+    #[serde(flatten)]
+    pub summary: PacketSummary,
+    
+    /// Paths of the matching values, e.g. `headers.dns.query_name` or `headers.lldp.management_addresses[0]`
+    pub fields: Vec<String>,
+}
+
+/// Link type of packets that did not come with one
+fn ethernet() -> Linktype {
+    Linktype::ETHERNET
+}
+
+impl Packet {
+    /// The parsed headers, decoded from their JSON text if they were compacted
+    pub fn expanded_headers(&self) -> Cow<'_, serde_json::Value> {
+        match &self.compact_headers {
+            Some(json) => Cow::Owned(serde_json::from_str(json).unwrap_or_default()),
+            None => Cow::Borrowed(&self.headers),
+        }
+    }
+    
+    /// Keep the headers as JSON text instead of a tree of values, which takes
+    /// a fraction of the memory. Returns whether there was anything to compact.
+    pub fn compact(&mut self) -> bool {
+        if self.compact_headers.is_some() || self.headers.is_null() {
+            return false;
+        }
+        self.compact_headers = Some(self.headers.to_string().into_boxed_str());
+        self.headers = serde_json::Value::Null;
+        true
+    }
+    
+    /// Undo `compact`, for packets leaving the store
+    pub fn expanded(mut self) -> Self {
+        if let Some(json) = self.compact_headers.take() {
+            self.headers = serde_json::from_str(&json).unwrap_or_default();
+        }
+        self
+    }
+    
+    /// Paths of the `headers` and `metadata` values matching a search term:
+    /// strings containing it (case-insensitively), numbers and booleans equal to it
+    pub fn matching_fields(&self, term: &str) -> Vec<String> {
+        let term = term.to_lowercase();
+        let mut matches = Vec::new();
+        collect_matching_fields(&self.expanded_headers(), &term, &mut "headers".to_string(), &mut matches);
+        collect_matching_fields(&self.metadata, &term, &mut "metadata".to_string(), &mut matches);
+        matches
+    }
+}
+
+/// Walk a JSON value depth-first, adding the path of every matching leaf
+fn collect_matching_fields(value: &serde_json::Value, term: &str, path: &mut String, matches: &mut Vec<String>) {
+    use serde_json::Value;
+    
+    let len = path.len();
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                path.push('.');
+                path.push_str(key);
+                collect_matching_fields(value, term, path, matches);
+                path.truncate(len);
+            }
+        },
+        Value::Array(items) => {
+            for (i, value) in items.iter().enumerate() {
+                path.push_str(&format!("[{}]", i));
+                collect_matching_fields(value, term, path, matches);
+                path.truncate(len);
+            }
+        },
+        Value::String(s) => {
+            if s.to_lowercase().contains(term) {
+                matches.push(path.clone());
+            }
+        },
+        Value::Number(n) => {
+            if n.to_string() == term {
+                matches.push(path.clone());
+            }
+        },
+        Value::Bool(b) => {
+            if b.to_string() == term {
+                matches.push(path.clone());
+            }
+        },
+        Value::Null => {},
+    }
+}
+
+/// Address scope of a packet, separating real conversations from LAN background noise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressScope {
+    Unicast,
+    Multicast,
+    Broadcast,
+    
+    /// Traffic from or to a link-local address (169.254.0.0/16, fe80::/10)
+    LinkLocal,
+}
+
+impl AddressScope {
+    /// Classify a packet by its destination (broadcast, multicast), then its
+    /// addresses (link-local); anything else is unicast
+    pub fn of(packet: &Packet) -> Self {
+        let destination_mac = packet.destination_mac.as_deref().unwrap_or("");
+        let group_mac = u8::from_str_radix(destination_mac.get(..2).unwrap_or("00"), 16).is_ok_and(|octet| octet & 0x01 != 0);
+        
+        let broadcast = destination_mac.eq_ignore_ascii_case("ff:ff:ff:ff:ff:ff")
+            || matches!(packet.destination_ip, Some(IpAddr::V4(ip)) if ip.is_broadcast());
+        if broadcast {
+            return AddressScope::Broadcast;
+        }
+        if group_mac || packet.destination_ip.is_some_and(|ip| ip.is_multicast()) {
+            return AddressScope::Multicast;
+        }
+        if [packet.source_ip, packet.destination_ip].into_iter().flatten().any(is_link_local) {
+            return AddressScope::LinkLocal;
+        }
+        AddressScope::Unicast
+    }
+    
+    /// Name used in the stats and the `scope` query parameter
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AddressScope::Unicast => "unicast",
+            AddressScope::Multicast => "multicast",
+            AddressScope::Broadcast => "broadcast",
+            AddressScope::LinkLocal => "link_local",
+        }
+    }
+}
+
+impl FromStr for AddressScope {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "unicast" => Ok(AddressScope::Unicast),
+            "multicast" => Ok(AddressScope::Multicast),
+            "broadcast" => Ok(AddressScope::Broadcast),
+            "link_local" => Ok(AddressScope::LinkLocal),
+            other => Err(format!("Unknown address scope '{}', expected unicast, multicast, broadcast or link_local", other)),
+        }
+    }
+}
+
+impl fmt::Display for AddressScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Direction of a packet relative to the capture host, stored in `metadata["direction"]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PacketDirection {
+    /// Received by the capture host
+    In,
+    
+    /// Sent by the capture host
+    Out,
+    
+    /// Between the capture host's own addresses (e.g. on loopback)
+    Local,
+    
+    /// Neither from nor to the capture host (promiscuous or mirrored traffic)
+    Transit,
+}
+
+impl PacketDirection {
+    /// Name used in the packet metadata and the `direction` query parameter
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PacketDirection::In => "in",
+            PacketDirection::Out => "out",
+            PacketDirection::Local => "local",
+            PacketDirection::Transit => "transit",
+        }
+    }
+    
+    /// The direction recorded in a packet's metadata, if any
+    pub fn of(packet: &Packet) -> Option<Self> {
+        packet.metadata.get("direction").and_then(|v| v.as_str()).and_then(|s| s.parse().ok())
+    }
+}
+
+impl FromStr for PacketDirection {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "in" => Ok(PacketDirection::In),
+            "out" => Ok(PacketDirection::Out),
+            "local" => Ok(PacketDirection::Local),
+            "transit" => Ok(PacketDirection::Transit),
+            other => Err(format!("Unknown direction '{}', expected in, out, local or transit", other)),
+        }
+    }
+}
+
+/// TCP analysis annotation of a segment, listed in `metadata["tcp_analysis"]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpAnalysis {
+    /// Data (or SYN/FIN) below the next sequence number expected in its direction
+    Retransmission,
+    
+    /// Zero or one byte one below the next expected sequence number, probing an idle connection
+    KeepAlive,
+    
+    /// An ACK without data that only changes the advertised window
+    WindowUpdate,
+}
+
+impl TcpAnalysis {
+    /// Name used in the packet metadata and the `tcp_analysis` query parameter
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TcpAnalysis::Retransmission => "retransmission",
+            TcpAnalysis::KeepAlive => "keep_alive",
+            TcpAnalysis::WindowUpdate => "window_update",
+        }
+    }
+    
+    /// Whether the packet's metadata carries this annotation
+    pub fn is_set(self, packet: &Packet) -> bool {
+        packet.metadata
+            .get("tcp_analysis")
+            .and_then(|v| v.as_array())
+            .is_some_and(|flags| flags.iter().any(|flag| flag.as_str() == Some(self.as_str())))
+    }
+}
+
+impl FromStr for TcpAnalysis {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "retransmission" => Ok(TcpAnalysis::Retransmission),
+            "keep_alive" => Ok(TcpAnalysis::KeepAlive),
+            "window_update" => Ok(TcpAnalysis::WindowUpdate),
+            other => Err(format!("Unknown TCP analysis flag '{}', expected retransmission, keep_alive or window_update", other)),
+        }
+    }
+}
+
+/// Whether an address is IPv4 (169.254.0.0/16) or IPv6 (fe80::/10) link-local
+fn is_link_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_link_local(),
+        IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// One RTP stream: a synchronization source (SSRC) sending from one endpoint to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RtpStream {
+    pub ssrc: u32,
+    
+    /// Sending endpoint (IP:port)
+    pub source: String,
+    
+    /// Receiving endpoint (IP:port)
+    pub destination: String,
+    
+    /// Conversation carrying the stream, as listed by /api/stats/conversations
+    pub conversation_id: Option<String>,
+    
+    /// Payload type of the stream's media (comfort noise aside)
+    pub payload_type: u8,
+    
+    /// Codec of that payload type ("dynamic" for types the signalling assigns)
+    pub codec: String,
+    
+    /// Packets received
+    pub packets: usize,
+    
+    /// Packets the sequence numbers say were sent, from the first to the highest received
+    pub expected_packets: u64,
+    
+    /// Expected packets never received (sequence gaps), and their share of the expected
+    pub lost_packets: u64,
+    pub loss_percent: f64,
+    
+    /// Packets arriving after a higher sequence number, duplicates included
+    pub out_of_order: usize,
+    
+    /// RFC 3550 interarrival jitter in milliseconds, currently and at its highest;
+    /// null when the clock rate of the payload type is unknown
+    pub jitter_ms: Option<f64>,
+    pub max_jitter_ms: Option<f64>,
+    
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
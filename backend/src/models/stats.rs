@@ -1,10 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc};
 
+use crate::models::config::BudgetAction;
+
 /// Statistics for captured packets
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CaptureStats {
+    /// ID of the capture session these statistics belong to
+    #[serde(default)]
+    pub session_id: Option<String>,
+    
     /// Total number of packets captured
     pub total_packets: usize,
     
@@ -20,12 +26,24 @@ pub struct CaptureStats {
     /// Packets per destination IP
     pub destinations: HashMap<String, usize>,
     
+    /// Packets from sources not counted in `sources` because the map was full
+    #[serde(default)]
+    pub untracked_sources: usize,
+    
+    /// Packets to destinations not counted in `destinations` because the map was full
+    #[serde(default)]
+    pub untracked_destinations: usize,
+    
     /// Capture start time
     pub start_time: Option<DateTime<Utc>>,
     
     /// Capture end time (if stopped)
     pub end_time: Option<DateTime<Utc>>,
     
+    /// When the interfaces of a synchronized start were released together
+    #[serde(default)]
+    pub synchronized_start: Option<DateTime<Utc>>,
+    
     /// Packet rate (packets per second)
     pub packet_rate: f64,
     
@@ -34,4 +52,262 @@ pub struct CaptureStats {
     
     /// Errors encountered during capture
     pub errors: usize,
-} 
\ No newline at end of file
+    
+    /// Packets discarded during the capture warm-up period
+    pub warmup_discarded: usize,
+    
+    /// Packets dropped unprocessed because their conversation was past its flow head
+    #[serde(default)]
+    pub flow_head_skipped: usize,
+    
+    /// Frames dropped undecoded for being outside the min/max packet size
+    #[serde(default)]
+    pub size_filtered: usize,
+    
+    /// Packets marked as copies of a recent frame (with --dedup-window)
+    #[serde(default)]
+    pub duplicates: usize,
+    
+    /// Resolved hostnames for source/destination IPs (with --resolve-names)
+    #[serde(default)]
+    pub hostnames: HashMap<String, String>,
+    
+    /// Packets captured since the previous WebSocket stats message (WebSocket only)
+    #[serde(default)]
+    pub packets_delta: usize,
+    
+    /// Bytes captured since the previous WebSocket stats message (WebSocket only)
+    #[serde(default)]
+    pub bytes_delta: usize,
+    
+    /// Frames discarded by the overflow policy because processing fell behind
+    /// (not included in kernel drops)
+    #[serde(default)]
+    pub backpressure_dropped: usize,
+    
+    /// Free-text description of the capture session
+    #[serde(default)]
+    pub description: Option<String>,
+    
+    /// Key/value tags of the capture session
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    
+    /// Packets and bytes per address scope (unicast, multicast, broadcast, link_local)
+    #[serde(default)]
+    pub scopes: HashMap<String, ScopeCounts>,
+}
+
+/// How traffic is keyed in stats and conversations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// By IP address
+    #[default]
+    Ip,
+    
+    /// By resolved hostname where one is known, by IP address otherwise
+    Host,
+}
+
+/// Traffic counted for one address scope
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ScopeCounts {
+    pub packets: usize,
+    pub bytes: usize,
+}
+
+impl CaptureStats {
+    /// These stats with sources and destinations keyed by resolved hostname
+    /// where one is known, adding up a dual-stack host's IPv4 and IPv6 traffic
+    pub fn grouped_by_host(mut self) -> Self {
+        let by_host = |counts: HashMap<String, usize>, hostnames: &HashMap<String, String>| {
+            let mut grouped: HashMap<String, usize> = HashMap::new();
+            for (address, count) in counts {
+                let host = hostnames.get(&address).cloned().unwrap_or(address);
+                *grouped.entry(host).or_default() += count;
+            }
+            grouped
+        };
+        
+        self.sources = by_host(std::mem::take(&mut self.sources), &self.hostnames);
+        self.destinations = by_host(std::mem::take(&mut self.destinations), &self.hostnames);
+        self
+    }
+    
+    /// Protocol counts as chart slices: the `top` largest by count, then one
+    /// "Other" slice for the rest and for anything below `min_percent`
+    pub fn protocol_chart(&self, top: usize, min_percent: f64) -> ProtocolChart {
+        let total: usize = self.protocols.values().sum();
+        let percent = |count: usize| if total == 0 { 0.0 } else { count as f64 * 100.0 / total as f64 };
+        
+        let mut counts: Vec<(&String, usize)> = self.protocols.iter().map(|(p, &c)| (p, c)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        
+        let mut slices = Vec::new();
+        let mut other = 0;
+        for (protocol, count) in counts {
+            if slices.len() < top && percent(count) >= min_percent {
+                slices.push(ChartSlice { label: protocol.clone(), count, percent: percent(count) });
+            } else {
+                other += count;
+            }
+        }
+        
+        let other_protocols = self.protocols.len() - slices.len();
+        if other > 0 {
+            slices.push(ChartSlice { label: OTHER_SLICE.to_string(), count: other, percent: percent(other) });
+        }
+        
+        ProtocolChart { total, slices, other_protocols }
+    }
+}
+
+/// Label of the slice aggregating the protocols left out of a chart
+const OTHER_SLICE: &str = "Other";
+
+/// One slice of a distribution chart
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartSlice {
+    pub label: String,
+    pub count: usize,
+    
+    /// Share of the total, 0 to 100
+    pub percent: f64,
+}
+
+/// Protocol distribution ready for a pie chart, largest slice first and "Other" last
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtocolChart {
+    /// Packets counted across all protocols
+    pub total: usize,
+    
+    pub slices: Vec<ChartSlice>,
+    
+    /// Number of protocols folded into the "Other" slice
+    pub other_protocols: usize,
+}
+
+/// What a capture session is for, recorded alongside its packets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureDescription {
+    /// Free-text description ("what was I capturing and why")
+    #[serde(default)]
+    pub description: Option<String>,
+    
+    /// Key/value tags, e.g. ticket or site
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl CaptureDescription {
+    /// Whether neither a description nor tags are set
+    pub fn is_empty(&self) -> bool {
+        self.description.as_deref().is_none_or(str::is_empty) && self.tags.is_empty()
+    }
+    
+    /// Render as plain text: the description, then one `key=value` line per tag
+    pub fn to_comment(&self) -> String {
+        let mut lines: Vec<String> = self.description.iter().filter(|d| !d.is_empty()).cloned().collect();
+        lines.extend(self.tags.iter().map(|(key, value)| format!("{}={}", key, value)));
+        lines.join("\n")
+    }
+}
+
+/// Byte budget of a live capture and how much of it has been used
+#[derive(Debug, Clone, Serialize)]
+pub struct ByteBudget {
+    pub max_total_bytes: u64,
+    
+    /// Bytes taken in since the capture started
+    pub used_bytes: u64,
+    
+    pub action: BudgetAction,
+    
+    /// Whether the budget has been reached
+    pub exhausted: bool,
+}
+
+/// Packets and bytes per second over the last minute, oldest first, for a trend sparkline
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RateTrend {
+    pub packets_per_second: Vec<usize>,
+    pub bytes_per_second: Vec<usize>,
+}
+
+/// Approximate memory used by the packet buffer, computed by walking it once
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryEstimate {
+    /// Time the estimate was taken (the buffer keeps changing while capturing)
+    pub computed_at: DateTime<Utc>,
+    
+    /// Packets in the buffer
+    pub packet_count: usize,
+    
+    /// Configured buffer limit (`buffer_size`)
+    pub buffer_size: usize,
+    
+    /// Raw frame bytes
+    pub raw_bytes: usize,
+    
+    /// Payload copies
+    pub payload_bytes: usize,
+    
+    /// Parsed headers: compacted ones as stored, the others by a per-packet allowance
+    pub header_bytes: usize,
+    
+    /// Packets whose headers are compacted
+    pub compacted_packets: usize,
+    
+    /// Struct, string, metadata and map entry overhead
+    pub overhead_bytes: usize,
+    
+    /// Sum of the above
+    pub estimated_bytes: usize,
+    
+    /// Estimate for a full buffer at the current average packet size
+    pub estimated_bytes_at_limit: usize,
+}
+
+/// One-shot summary of a capture or loaded files, in the style of `capinfos`
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureInfo {
+    pub session_id: Option<String>,
+    
+    /// Whether the capture is still running (the figures are then provisional)
+    pub is_running: bool,
+    
+    pub packets: usize,
+    pub bytes: usize,
+    
+    /// Timestamp of the oldest stored packet
+    pub first_packet_time: Option<DateTime<Utc>>,
+    
+    /// Capture end (now while running), or the last packet of loaded files
+    pub last_packet_time: Option<DateTime<Utc>>,
+    
+    pub duration_secs: f64,
+    
+    /// Average rates over the duration
+    pub packets_per_sec: f64,
+    pub bits_per_sec: f64,
+    pub bytes_per_sec: f64,
+    
+    pub average_packet_size: f64,
+    
+    /// Distinct protocols seen, in name order
+    pub protocols: Vec<String>,
+    
+    /// Capture files the packets were loaded from, with their hashes
+    pub files: Vec<crate::capture::file::LoadedFile>,
+}
+
+/// Traffic in one second of the capture, for rate charts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsBucket {
+    /// Start of the second this bucket covers
+    pub start: DateTime<Utc>,
+    
+    pub packets: usize,
+    pub bytes: usize,
+}
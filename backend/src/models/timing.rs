@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// Packet timing of one direction of a flow, for QoS analysis of VoIP and streaming
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamTiming {
+    /// Packet the stream was selected by
+    pub packet_id: u64,
+    
+    /// Transport of the stream ("UDP", "TCP", "SCTP", or the IP protocol)
+    pub transport: String,
+    
+    /// Sending endpoint (IP:port, or IP for protocols without ports)
+    pub source: String,
+    
+    /// Receiving endpoint
+    pub destination: String,
+    
+    /// Stored packets of the stream
+    pub packets: usize,
+    
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    
+    /// Inter-packet gaps in milliseconds, in arrival order (the first `limit`)
+    pub gaps_ms: Vec<f64>,
+    
+    /// Whether more gaps were measured than listed
+    pub gaps_truncated: bool,
+    
+    /// Gap statistics over all packets; null with too few packets to tell
+    pub min_gap_ms: Option<f64>,
+    pub mean_gap_ms: Option<f64>,
+    pub max_gap_ms: Option<f64>,
+    pub stddev_gap_ms: Option<f64>,
+    
+    /// RFC 3550-style interarrival jitter estimate in milliseconds; null
+    /// below three packets
+    pub jitter_ms: Option<f64>,
+}
+
+/// Unacknowledged data of one direction of a TCP connection over time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamInflight {
+    /// Packet the stream was selected by
+    pub packet_id: u64,
+    
+    /// Sending endpoint (IP:port), whose unacknowledged data is measured
+    pub source: String,
+    
+    /// Receiving endpoint, whose acknowledgements retire it
+    pub destination: String,
+    
+    /// Stored segments of the connection, both directions
+    pub packets: usize,
+    
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    
+    /// Estimate after each segment, in capture order (the first `limit`)
+    pub samples: Vec<InflightSample>,
+    
+    /// Whether more samples were taken than listed
+    pub samples_truncated: bool,
+    
+    /// Samples without an estimate, for lack of one direction's data
+    pub unknown_samples: usize,
+    
+    /// Statistics over the known samples; null when there are none
+    pub max_bytes_in_flight: Option<u64>,
+    pub mean_bytes_in_flight: Option<f64>,
+}
+
+/// Bytes in flight after one segment of the connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InflightSample {
+    pub timestamp: DateTime<Utc>,
+    
+    /// The segment, from either direction
+    pub packet_id: u64,
+    
+    /// Sent and not yet acknowledged; null when unknown
+    pub bytes_in_flight: Option<u64>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// A Wi-Fi access point seen in beacons and probe responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessPoint {
+    /// MAC address identifying the network (BSSID)
+    pub bssid: String,
+    
+    /// Network name, or None when the SSID is hidden
+    pub ssid: Option<String>,
+    
+    /// Channel from the DS parameter set, or the one the frames were captured on
+    pub channel: Option<u64>,
+    
+    /// Signal strength of the latest frame (radiotap captures only)
+    pub signal_dbm: Option<i64>,
+    
+    /// Whether the network requires encryption
+    pub privacy: bool,
+    
+    /// Beacons and probe responses seen
+    pub beacons: u64,
+    pub probe_responses: u64,
+    
+    /// First and most recent frame
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
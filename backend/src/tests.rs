@@ -0,0 +1,1214 @@
+// End-to-end tests of the capture pipeline
+//
+// Frames are injected into an "inject" capture session, which hands them to
+// the same packet processor as a device capture, and the results are checked
+// through the REST API and the WebSocket, so no NIC or privileges are needed.
+
+use actix_web::dev::ServiceResponse;
+use actix_web::{test, web, App, HttpServer};
+use chrono::Utc;
+use clap::Parser;
+use pcap::Linktype;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::RwLock;
+
+use crate::api::cors::CorsPolicy;
+use crate::api::handlers::packets::export_body;
+use crate::api::routes;
+use crate::capture::export::ExportFormat;
+use crate::capture::alerts::{DetectionSettings, ThreatDetector};
+use crate::capture::dedup::DuplicateDetector;
+use crate::capture::events::EventLog;
+use crate::capture::interfaces;
+use crate::capture::manager::{CaptureManager, INJECTED_INTERFACE};
+use crate::capture::parser::PacketParser;
+use crate::capture::start_barrier::StartBarrier;
+use crate::capture::self_test::{arp_frame, ipv4, rtp_packet, sip_invite, tcp, tftp_read_request, udp, IP_TCP, IP_UDP};
+use crate::capture::snapshot::{self, Snapshot, SnapshotPacket, SNAPSHOT_VERSION};
+use crate::capture::stats_report::{StatsReport, StatsReporter};
+use crate::models::alert::AlertKind;
+use crate::models::config::{AppConfig, BudgetAction, LinkTypeOverride, StatsReportMode};
+use crate::models::packet::PacketSummary;
+use crate::models::stats::CaptureDescription;
+use crate::Args;
+
+/// Default settings, capturing from the injection interface and saving nothing
+fn config() -> AppConfig {
+    let args = Args::parse_from(["rustshark", "--interface", INJECTED_INTERFACE]);
+    args.app_config(None)
+}
+
+/// A UDP datagram, a TCP SYN and an ARP request
+fn frames() -> Vec<Vec<u8>> {
+    vec![
+        ipv4(IP_UDP, &udp(40000, 5000, b"hello")),
+        ipv4(IP_TCP, &tcp(40000, 80, &[])),
+        arp_frame(),
+    ]
+}
+
+/// Start an injected capture, feed it `frames` and wait until all are processed
+async fn capture(frames: Vec<Vec<u8>>) -> Arc<RwLock<CaptureManager>> {
+    capture_with(config(), frames).await
+}
+
+/// Like `capture`, with the given settings
+async fn capture_with(config: AppConfig, frames: Vec<Vec<u8>>) -> Arc<RwLock<CaptureManager>> {
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config)));
+    let expected = frames.len();
+    
+    {
+        let mut manager = manager.write().await;
+        manager.start_capture().await.expect("injected capture starts");
+        for frame in frames {
+            manager.inject_packet(frame, Utc::now()).expect("frame is queued");
+        }
+    }
+    
+    for _ in 0..200 {
+        if manager.read().await.get_stats().total_packets == expected {
+            return manager;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("injected frames were not processed");
+}
+
+/// GET a JSON resource from the API
+async fn get_json(manager: &Arc<RwLock<CaptureManager>>, uri: &str) -> Value {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+    assert!(response.status().is_success(), "GET {} returned {}", uri, response.status());
+    test::read_body_json(response).await
+}
+
+/// Connect to the WebSocket and read its first `count` text messages
+async fn websocket_messages(address: SocketAddr, count: usize) -> Vec<Value> {
+    let mut stream = TcpStream::connect(address).await.expect("server accepts connections");
+    let handshake = format!(
+        "GET /api/ws HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        address
+    );
+    stream.write_all(handshake.as_bytes()).await.unwrap();
+    
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await.unwrap();
+    assert!(line.contains(" 101 "), "unexpected handshake response: {}", line);
+    while line != "\r\n" {
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+    }
+    
+    // Server frames are unmasked; anything but text (pings) is skipped
+    let mut messages = Vec::new();
+    while messages.len() < count {
+        let mut head = [0u8; 2];
+        reader.read_exact(&mut head).await.unwrap();
+        let len = match head[1] & 0x7f {
+            126 => reader.read_u16().await.unwrap() as usize,
+            127 => reader.read_u64().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut payload = vec![0; len];
+        reader.read_exact(&mut payload).await.unwrap();
+        
+        if head[0] & 0x0f == 1 {
+            messages.push(serde_json::from_slice(&payload).expect("text frames carry JSON"));
+        }
+    }
+    messages
+}
+
+#[actix_web::test]
+async fn injected_frames_are_listed() {
+    let manager = capture(frames()).await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    assert_eq!(listing["total"], 3);
+    
+    let mut protocols: Vec<&str> = listing["packets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|packet| packet["protocol"].as_str().unwrap())
+        .collect();
+    protocols.sort_unstable();
+    assert_eq!(protocols, ["ARP", "TCP", "UDP"]);
+    
+    let id = listing["packets"][0]["id"].as_u64().unwrap();
+    let detail = get_json(&manager, &format!("/api/packets/{}", id)).await;
+    assert_eq!(detail["id"], id);
+}
+
+#[actix_web::test]
+async fn injected_frames_are_counted() {
+    let manager = capture(frames()).await;
+    
+    let stats = get_json(&manager, "/api/packets/stats").await;
+    assert_eq!(stats["total_packets"], 3);
+    assert_eq!(stats["protocols"]["UDP"], 1);
+    assert_eq!(stats["protocols"]["TCP"], 1);
+    assert_eq!(stats["protocols"]["ARP"], 1);
+    assert_eq!(stats["sources"]["192.0.2.1"], 2);
+    assert_eq!(stats["destinations"]["192.0.2.2"], 2);
+    
+    let status = get_json(&manager, "/api/capture/status").await;
+    assert_eq!(status["is_running"], true);
+    assert_eq!(status["interfaces"][0], INJECTED_INTERFACE);
+}
+
+#[actix_web::test]
+async fn websocket_reports_injected_frames() {
+    let manager = capture(frames()).await;
+    
+    let app_state = web::Data::new(manager.clone());
+    let server = HttpServer::new(move || App::new().app_data(app_state.clone()).configure(routes::configure))
+        .workers(1)
+        .bind("127.0.0.1:0")
+        .unwrap();
+    let address = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+    
+    let messages = websocket_messages(address, 2).await;
+    assert_eq!(messages[0]["type"], "status");
+    assert_eq!(messages[0]["running"], true);
+    assert_eq!(messages[0]["packet_count"], 3);
+    assert_eq!(messages[1]["type"], "stats");
+    assert_eq!(messages[1]["stats"]["total_packets"], 3);
+    
+    let session_id = manager.read().await.get_session_id();
+    assert!(session_id.is_some());
+    assert_eq!(messages[1]["session_id"].as_str(), session_id.as_deref());
+    
+    handle.stop(false).await;
+}
+
+#[actix_web::test]
+async fn deleting_packets_updates_the_stats() {
+    let manager = capture(frames()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    let delete = |uri: &str| test::TestRequest::delete().uri(uri).to_request();
+    
+    let response = test::call_service(&app, delete("/api/packets")).await;
+    assert_eq!(response.status(), 400);
+    
+    let response = test::call_service(&app, delete("/api/packets?display_filter=udp%20%7C%7C%20arp")).await;
+    assert!(response.status().is_success());
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["removed"], 2);
+    assert_eq!(body["remaining"], 1);
+    
+    let stats = get_json(&manager, "/api/packets/stats").await;
+    assert_eq!(stats["total_packets"], 1);
+    assert_eq!(stats["protocols"]["TCP"], 1);
+    assert!(stats["protocols"].get("UDP").is_none());
+    assert_eq!(stats["sources"]["192.0.2.1"], 1);
+    
+    let response = test::call_service(&app, delete("/api/packets?confirm=true")).await;
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["removed"], 1);
+    assert_eq!(get_json(&manager, "/api/packets").await["total"], 0);
+}
+
+#[actix_web::test]
+async fn capture_info_starts_at_the_oldest_stored_packet() {
+    let manager = capture(frames()).await;
+    
+    let info = get_json(&manager, "/api/capture/info").await;
+    let first = get_json(&manager, "/api/packets/1").await;
+    // The session started before the first frame was injected
+    assert_eq!(info["first_packet_time"], first["timestamp"]);
+}
+
+#[actix_web::test]
+async fn resetting_the_stats_restarts_relative_times() {
+    let manager = capture(frames()).await;
+    let before = get_json(&manager, "/api/packets/1").await["relative_time"].as_f64().unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    manager.write().await.reset_stats().await;
+    
+    // The detail cached by the first request is not served again
+    let after = get_json(&manager, "/api/packets/1").await["relative_time"].as_f64().unwrap();
+    assert!(after < before, "relative time {} did not move back from {}", after, before);
+}
+
+#[actix_web::test]
+async fn conversations_are_exported_as_csv() {
+    let manager = capture(frames()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    let transports = |csv: &str| -> Vec<String> {
+        csv.lines().skip(1).map(|row| row.split(',').nth(1).unwrap().to_string()).collect()
+    };
+    
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/stats/conversations/export").to_request()).await;
+    assert_eq!(header(&response, "content-type"), "text/csv");
+    let csv = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert!(csv.starts_with("id,transport,protocol,protocols,address_a,port_a,address_b,port_b,packets,bytes,"));
+    assert_eq!(transports(&csv), ["TCP", "UDP"]);
+    
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/stats/conversations/export?sort=first_seen").to_request()).await;
+    let csv = String::from_utf8(test::read_body(response).await.to_vec()).unwrap();
+    assert_eq!(transports(&csv), ["UDP", "TCP"]);
+    
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/stats/conversations/export?format=pcap").to_request()).await;
+    assert_eq!(response.status(), 400);
+}
+
+#[actix_web::test]
+async fn interface_aliases_label_pcapng_exports() {
+    let mut config = config();
+    config.interface_aliases.insert(INJECTED_INTERFACE.to_string(), "Lab tap".to_string());
+    let manager = capture_with(config, frames()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    
+    let request = test::TestRequest::post()
+        .uri("/api/packets/export")
+        .set_json(serde_json::json!({ "ids": [1, 2, 3], "format": "pcapng" }))
+        .to_request();
+    let pcapng = test::read_body(test::call_service(&app, request).await).await;
+    let contains = |needle: &[u8]| pcapng.windows(needle.len()).any(|window| window == needle);
+    // if_name and if_description options of the interface description block
+    assert!(contains(&[&[2, 0, INJECTED_INTERFACE.len() as u8, 0][..], INJECTED_INTERFACE.as_bytes()].concat()));
+    assert!(contains(b"\x03\x00\x07\x00Lab tap"));
+}
+
+#[actix_web::test]
+async fn triggers_refuse_interface_names_that_leave_sysfs() {
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config())));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    
+    for interface in ["../../../etc", "lo/../eth0"] {
+        let request = test::TestRequest::post()
+            .uri("/api/capture/arm")
+            .set_json(serde_json::json!({ "interface": interface, "pps": 100 }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), 400);
+        let body: Value = test::read_body_json(response).await;
+        assert!(body["message"].as_str().unwrap().contains("Invalid interface name"), "{}", body);
+    }
+    assert_eq!(manager.read().await.get_selected_interface().as_deref(), Some(INJECTED_INTERFACE));
+}
+
+#[actix_web::test]
+async fn snapshots_are_written_through_their_own_temporary_file() {
+    let dir = std::env::temp_dir().join(format!("rustshark-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let neighbor = dir.join("state.tmp");
+    std::fs::write(&neighbor, "unrelated").unwrap();
+    
+    let manager = capture(frames()).await;
+    let path = dir.join("state.json");
+    snapshot::save(&path, &manager.read().await.create_snapshot()).unwrap();
+    
+    assert_eq!(snapshot::load(&path).unwrap().expect("snapshot is saved").packets.len(), 3);
+    assert_eq!(std::fs::read_to_string(&neighbor).unwrap(), "unrelated");
+    assert!(!dir.join("state.json.tmp").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn stats_broadcasts_carry_the_description() {
+    let mut manager = CaptureManager::new(config());
+    let mut description = CaptureDescription { description: Some("Uplink check".to_string()), ..Default::default() };
+    description.tags.insert("ticket".to_string(), "NET-42".to_string());
+    manager.set_description(description);
+    let mut stats = manager.subscribe_to_stats();
+    manager.start_capture().await.expect("injected capture starts");
+    manager.inject_packet(arp_frame(), Utc::now()).expect("frame is queued");
+    
+    let update = tokio::time::timeout(Duration::from_secs(2), stats.recv()).await.unwrap().unwrap();
+    assert_eq!(update.total_packets, 1);
+    assert_eq!(update.description.as_deref(), Some("Uplink check"));
+    assert_eq!(update.tags["ticket"], "NET-42");
+}
+
+#[actix_web::test]
+async fn long_descriptions_are_cut_to_fit_the_pcapng_comment() {
+    let manager = capture(frames()).await;
+    let mut manager = manager.write().await;
+    manager.set_description(CaptureDescription { description: Some("é".repeat(40_000)), ..Default::default() });
+    
+    let packets = vec![manager.get_packet(1).unwrap()];
+    let pcapng = export_body(&manager, &packets, ExportFormat::Pcapng, false);
+    // The comment is the first option of the section header, cut short of splitting a character
+    assert_eq!(pcapng[24..28], [1, 0, 0xfe, 0xff]);
+    assert!(std::str::from_utf8(&pcapng[28..28 + 0xfffe]).is_ok());
+}
+
+#[actix_web::test]
+async fn exports_and_snapshots_keep_the_link_type() {
+    let raw_ip = ipv4(IP_UDP, &udp(40000, 5000, b"hello"))[14..].to_vec();
+    let mut manager = CaptureManager::new(config());
+    manager.restore_snapshot(Snapshot {
+        version: SNAPSHOT_VERSION,
+        created_at: Utc::now(),
+        stats: manager.get_stats(),
+        packets: vec![SnapshotPacket {
+            id: 1,
+            timestamp: Utc::now(),
+            interface: "tun0".to_string(),
+            data: snapshot::encode_hex(&raw_ip),
+            link_type: Some(Linktype::RAW.0),
+        }],
+    });
+    assert_eq!(manager.create_snapshot().packets[0].link_type, Some(Linktype::RAW.0));
+    let manager = Arc::new(RwLock::new(manager));
+    assert_eq!(get_json(&manager, "/api/packets/1").await["protocol"], "UDP");
+    
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    let export = |format: &str| test::TestRequest::post()
+        .uri("/api/packets/export")
+        .set_json(serde_json::json!({ "ids": [1], "format": format }))
+        .to_request();
+    // Network field of the pcap header, link type of the interface description block
+    let pcap = test::read_body(test::call_service(&app, export("pcap")).await).await;
+    assert_eq!(pcap[20..24], 101u32.to_le_bytes());
+    let pcapng = test::read_body(test::call_service(&app, export("pcapng")).await).await;
+    let section_len = u32::from_le_bytes(pcapng[4..8].try_into().unwrap()) as usize;
+    assert_eq!(pcapng[section_len + 8..section_len + 10], 101u16.to_le_bytes());
+}
+
+#[actix_web::test]
+async fn openapi_spec_describes_every_route() {
+    let manager = capture(Vec::new()).await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/openapi.json").to_request()).await;
+    assert_eq!(response.status(), 200);
+    let spec: Value = test::read_body_json(response).await;
+    assert_eq!(spec["openapi"], "3.0.3");
+    for endpoint in routes::ENDPOINTS {
+        let operation = &spec["paths"][endpoint.path][endpoint.method.to_ascii_lowercase()];
+        assert_eq!(operation["summary"], endpoint.description, "{} {}", endpoint.method, endpoint.path);
+    }
+    
+    // Every reference resolves to a component schema
+    let schemas = &spec["components"]["schemas"];
+    let text = spec.to_string();
+    for reference in text.split("\"#/components/schemas/").skip(1) {
+        let name = &reference[..reference.find('"').unwrap()];
+        assert!(schemas[name].is_object(), "unresolved reference to {}", name);
+    }
+    
+    let summary = &schemas["PacketSummary"];
+    let mut fields: Vec<&str> = PacketSummary::FIELDS.to_vec();
+    fields.sort_unstable();
+    assert!(summary["properties"].as_object().unwrap().keys().eq(fields));
+    assert_eq!(summary["required"], serde_json::json!(["id", "timestamp", "protocol", "source", "destination", "length", "info"]));
+    assert_eq!(summary["properties"]["source_host"]["nullable"], true);
+    assert_eq!(schemas["CaptureStats"]["properties"]["scopes"]["additionalProperties"]["$ref"], "#/components/schemas/ScopeCounts");
+    
+    // Query parameters come from the handlers' query structs
+    let parameters = spec["paths"]["/api/packets/search-field"]["get"]["parameters"].as_array().unwrap();
+    let q = parameters.iter().find(|parameter| parameter["name"] == "q").unwrap();
+    assert_eq!(q["in"], "query");
+    assert_eq!(q["required"], true);
+    let limit = parameters.iter().find(|parameter| parameter["name"] == "limit").unwrap();
+    assert_eq!(limit["required"], false);
+    assert_eq!(limit["schema"]["type"], "integer");
+    let parameters = spec["paths"]["/api/stats/conversations/export"]["get"]["parameters"].as_array().unwrap();
+    let sort = parameters.iter().find(|parameter| parameter["name"] == "sort").unwrap();
+    assert_eq!(sort["schema"]["enum"][1], "packets");
+}
+
+#[actix_web::test]
+async fn packet_ids_increase_within_a_session_and_restart_with_the_next() {
+    let manager = capture(frames()).await;
+    let ids = |listing: Value| {
+        let mut packets = listing["packets"].as_array().unwrap().clone();
+        packets.sort_by_key(|packet| packet["timestamp"].as_str().unwrap().to_string());
+        packets.iter().map(|packet| packet["id"].as_u64().unwrap()).collect::<Vec<_>>()
+    };
+    assert_eq!(ids(get_json(&manager, "/api/packets").await), [1, 2, 3]);
+    
+    {
+        let mut capture_manager = manager.write().await;
+        capture_manager.stop_capture().await.expect("injected capture stops");
+        capture_manager.start_capture().await.expect("injected capture restarts");
+        for frame in frames() {
+            capture_manager.inject_packet(frame, Utc::now()).expect("frame is queued");
+        }
+    }
+    for _ in 0..200 {
+        if manager.read().await.get_stats().total_packets == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(ids(get_json(&manager, "/api/packets").await), [1, 2, 3]);
+}
+
+#[actix_web::test]
+async fn link_changes_are_kept_in_the_session_event_log() {
+    let manager = capture(frames()).await;
+    assert!(CaptureManager::link_states(&["rustshark-no-such-interface".to_string()]).is_empty());
+    
+    let events = manager.read().await.event_log();
+    events.link_change("eth0", false);
+    events.link_change("eth0", true);
+    
+    let log = get_json(&manager, "/api/capture/events").await;
+    let links: Vec<&Value> = log["events"].as_array().unwrap().iter().filter(|event| event["interface"] == "eth0").collect();
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0]["link_up"], false);
+    assert_eq!(links[0]["level"], "warning");
+    assert_eq!(links[1]["link_up"], true);
+    assert_eq!(links[1]["level"], "info");
+    assert!(links[0]["timestamp"].as_str().unwrap() <= links[1]["timestamp"].as_str().unwrap());
+    
+    // The next session starts with an empty log
+    {
+        let mut capture_manager = manager.write().await;
+        capture_manager.stop_capture().await.expect("injected capture stops");
+        capture_manager.start_capture().await.expect("injected capture restarts");
+    }
+    let log = get_json(&manager, "/api/capture/events").await;
+    assert!(log["events"].as_array().unwrap().iter().all(|event| event["interface"].is_null()));
+}
+
+#[actix_web::test]
+async fn events_suppressed_by_a_burst_are_reported_when_it_ends() {
+    let events = EventLog::new();
+    let mut rx = events.subscribe();
+    for i in 0..15 {
+        events.warn(format!("burst {}", i));
+    }
+    
+    let mut messages = Vec::new();
+    for _ in 0..11 {
+        let event = tokio::time::timeout(Duration::from_secs(2), rx.recv()).await.expect("event is published");
+        messages.push(event.expect("event is received").message);
+    }
+    assert_eq!(messages[9], "burst 9");
+    assert_eq!(messages[10], "5 capture events suppressed by rate limit");
+}
+
+#[actix_web::test]
+async fn forced_link_types_replace_the_reported_one() {
+    let parse = |link_type: &str| Args::try_parse_from(["rustshark", "--link-type", link_type]).map(|args| args.link_type);
+    assert_eq!(parse("802.11").unwrap(), Some(LinkTypeOverride::Ieee80211));
+    assert_eq!(parse("rawip").unwrap(), Some(LinkTypeOverride::RawIp));
+    assert!(parse("token-ring").is_err());
+    
+    // The injected session reports Ethernet; its frames are raw IP
+    let mut config = config();
+    config.link_type = Some(LinkTypeOverride::RawIp);
+    let manager = capture_with(config, vec![ipv4(IP_UDP, &udp(40000, 5000, b"hello"))[14..].to_vec()]).await;
+    let packet = get_json(&manager, "/api/packets/1").await;
+    assert_eq!(packet["protocol"], "UDP");
+    assert_eq!(packet["source_ip"], "192.0.2.1");
+    assert_eq!(packet["headers"]["ethernet"], Value::Null);
+    
+    // Exports and snapshots carry the forced link type, not the reported one
+    let mut manager = manager.write().await;
+    assert_eq!(manager.link_type(), Linktype::RAW);
+    let pcap = export_body(&manager, &[manager.get_packet(1).unwrap()], ExportFormat::Pcap, false);
+    assert_eq!(pcap[20..24], 101u32.to_le_bytes());
+    manager.stop_capture().await.unwrap();
+    let snapshot = manager.create_snapshot();
+    assert_eq!(snapshot.packets[0].link_type, Some(Linktype::RAW.0));
+    manager.restore_snapshot(snapshot);
+    assert_eq!(manager.get_packet(1).unwrap().protocol, "UDP");
+}
+
+#[actix_web::test]
+async fn stopping_ends_the_injected_session() {
+    let manager = capture(frames()).await;
+    
+    let mut capture_manager = manager.write().await;
+    capture_manager.stop_capture().await.expect("injected capture stops");
+    assert!(!capture_manager.get_status());
+    assert!(capture_manager.inject_packet(arp_frame(), Utc::now()).is_err());
+    assert_eq!(capture_manager.get_packet_count(), 3);
+}
+
+#[actix_web::test]
+async fn capture_cap_is_reached_after_max_seconds() {
+    let mut config = config();
+    config.max_capture_seconds = 1;
+    let manager = capture_with(config, frames()).await;
+    
+    assert!(!manager.read().await.capture_cap_reached());
+    
+    // Resetting the stats does not restart the clock
+    manager.write().await.reset_stats().await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(manager.read().await.capture_cap_reached());
+    
+    let status = get_json(&manager, "/api/capture/status").await;
+    assert_eq!(status["max_capture_seconds"], 1);
+    
+    manager.write().await.stop_capture().await.expect("injected capture stops");
+    assert!(!manager.read().await.capture_cap_reached());
+}
+
+#[actix_web::test]
+async fn syn_floods_and_scans_are_detected_with_bounded_tracking() {
+    let parser = PacketParser::new();
+    let mut detector = ThreatDetector::new(DetectionSettings {
+        window_secs: 60,
+        syn_flood_threshold: 5,
+        port_scan_threshold: 3,
+        max_sources: 1,
+    });
+    let mut inspect = |port: u16, source: [u8; 4]| {
+        let mut frame = ipv4(IP_TCP, &tcp(40000, port, &[]));
+        frame[26..30].copy_from_slice(&source);
+        detector.inspect(&parser.parse_frame(frame, "eth0", Linktype::ETHERNET).unwrap())
+    };
+    let client = [10, 0, 0, 1];
+    
+    // A flood raises one alert however long it goes on
+    let floods: Vec<_> = (0..10_000).flat_map(|_| inspect(80, client)).collect();
+    assert_eq!(floods.len(), 1);
+    assert_eq!(floods[0].kind, AlertKind::SynFlood);
+    
+    // New ports still count once the flood has stopped being recorded
+    let scans: Vec<_> = (1..=3).flat_map(|port| inspect(port, client)).collect();
+    assert_eq!(scans.len(), 1);
+    assert_eq!(scans[0].kind, AlertKind::PortScan);
+    assert_eq!(scans[0].count, 4);
+    
+    // Sources past the cap are not tracked while the first is active
+    assert!((0..100).flat_map(|port| inspect(port, [10, 0, 0, 2])).next().is_none());
+}
+
+#[actix_web::test]
+async fn copies_mirrored_with_vlan_tags_are_duplicates() {
+    let parser = PacketParser::new();
+    let detector = DuplicateDetector::new(8);
+    let frame = ipv4(IP_UDP, &udp(40000, 5000, b"hello"));
+    
+    // The same datagram a hop later (TTL 63), tagged with 802.1Q, then QinQ
+    let mut routed = frame.clone();
+    routed[22] = 63;
+    let mut tagged = routed.clone();
+    tagged.splice(12..12, [0x81, 0x00, 0x00, 0x0a]);
+    let mut double_tagged = routed.clone();
+    double_tagged.splice(12..12, [0x88, 0xa8, 0x00, 0x64, 0x81, 0x00, 0x00, 0x0a]);
+    
+    let inspect = |frame: Vec<u8>| detector.inspect(&mut parser.parse_frame(frame, "eth0", Linktype::ETHERNET).unwrap());
+    assert!(!inspect(frame));
+    assert!(inspect(tagged));
+    assert!(inspect(double_tagged));
+    assert!(!inspect(ipv4(IP_UDP, &udp(40000, 5000, b"other"))));
+}
+
+#[actix_web::test]
+async fn warmup_window_starts_at_the_first_packet() {
+    let mut config = config();
+    config.warmup_ms = 500;
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config)));
+    manager.write().await.start_capture().await.expect("injected capture starts");
+    
+    // Traffic that starts later than the window is still warm-up
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    for frame in frames() {
+        manager.read().await.inject_packet(frame, Utc::now()).expect("frame is queued");
+    }
+    for _ in 0..200 {
+        if manager.read().await.get_stats().warmup_discarded == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let stats = manager.read().await.get_stats();
+    assert_eq!(stats.warmup_discarded, 3);
+    assert_eq!(stats.total_packets, 0);
+}
+
+#[actix_web::test]
+async fn sharded_storage_keeps_the_newest_packets() {
+    let mut config = config();
+    config.buffer_size = 4;
+    config.storage_shards = 2;
+    let manager = capture_with(config, (0..6).map(|i| ipv4(IP_UDP, &udp(40000 + i, 5000, b"hello"))).collect()).await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    assert_eq!(listing["total"], 4);
+    let mut ports: Vec<u64> = listing["packets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|packet| packet["source"].as_str().unwrap().rsplit(':').next().unwrap().parse().unwrap())
+        .collect();
+    ports.sort_unstable();
+    assert_eq!(ports, [40002, 40003, 40004, 40005]);
+}
+
+#[actix_web::test]
+async fn evicting_byte_budget_counts_the_stored_frames() {
+    let frame_len = ipv4(IP_UDP, &udp(40000, 5000, b"hello")).len() as u64;
+    let mut config = config();
+    config.max_total_bytes = 3 * frame_len + 10;
+    config.budget_action = BudgetAction::Evict;
+    let manager = capture_with(config, (0..6).map(|i| ipv4(IP_UDP, &udp(40000 + i, 5000, b"hello"))).collect()).await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut ports: Vec<u64> = listing["packets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|packet| packet["source"].as_str().unwrap().rsplit(':').next().unwrap().parse().unwrap())
+        .collect();
+    ports.sort_unstable();
+    assert_eq!(ports, [40003, 40004, 40005]);
+    
+    let status = get_json(&manager, "/api/capture/status").await;
+    assert_eq!(status["budget"]["used_bytes"], 3 * frame_len);
+    
+    // Deleting packets gives their bytes back
+    assert_eq!(manager.write().await.delete_packets(None).await, 3);
+    let status = get_json(&manager, "/api/capture/status").await;
+    assert_eq!(status["budget"]["used_bytes"], 0);
+}
+
+#[actix_web::test]
+async fn compacted_headers_are_expanded_when_read() {
+    let manager = capture(frames()).await;
+    let before = get_json(&manager, "/api/packets").await;
+    assert_eq!(manager.read().await.packet_store().compact_headers(), 3);
+    
+    let memory = get_json(&manager, "/api/capture/memory").await;
+    assert_eq!(memory["compacted_packets"], 3);
+    assert_eq!(get_json(&manager, "/api/packets").await["packets"], before["packets"]);
+    
+    let udp = get_json(&manager, "/api/packets/search-field?q=5000").await;
+    assert_eq!(udp["total"], 1);
+    let detail = get_json(&manager, &format!("/api/packets/{}", udp["packets"][0]["id"])).await;
+    assert_eq!(detail["headers"]["udp"]["destination_port"], 5000);
+}
+
+#[actix_web::test]
+async fn tftp_transfers_are_followed_to_their_ports() {
+    // The client acknowledges the server's first block, sent from a new port
+    let ack = [0, 4, 0, 1];
+    let manager = capture(vec![
+        ipv4(IP_UDP, &udp(40000, 69, &tftp_read_request())),
+        ipv4(IP_UDP, &udp(40000, 50123, &ack)),
+        ipv4(IP_UDP, &udp(40001, 50123, &ack)),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut packets = listing["packets"].as_array().unwrap().clone();
+    packets.sort_by_key(|packet| packet["id"].as_u64());
+    assert_eq!(packets[0]["protocol"], "TFTP");
+    assert_eq!(packets[1]["protocol"], "TFTP");
+    assert_eq!(packets[1]["info"], "TFTP Acknowledgement, Block: 1");
+    assert_eq!(packets[2]["protocol"], "UDP");
+}
+
+#[actix_web::test]
+async fn tftp_blocks_are_short_against_the_agreed_block_size() {
+    // The server acknowledges the requested 1468-byte blocks, so a 1000-byte block ends the transfer
+    let from_server = |message: &[u8]| {
+        let mut frame = ipv4(IP_UDP, &udp(50123, 40000, message));
+        let (source, destination) = frame[26..34].split_at_mut(4);
+        source.swap_with_slice(destination);
+        frame
+    };
+    let data = |block: u8, length: usize| {
+        let mut message = vec![0, 3, 0, block];
+        message.resize(4 + length, 0x55);
+        from_server(&message)
+    };
+    let manager = capture(vec![
+        ipv4(IP_UDP, &udp(40000, 69, &tftp_read_request())),
+        from_server(b"\x00\x06blksize\x001468\x00"),
+        data(1, 1468),
+        data(2, 1000),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut packets = listing["packets"].as_array().unwrap().clone();
+    packets.sort_by_key(|packet| packet["id"].as_u64());
+    assert_eq!(packets[1]["protocol"], "TFTP");
+    assert_eq!(packets[2]["info"], "TFTP Data Packet, Block: 1");
+    assert_eq!(packets[3]["info"], "TFTP Data Packet, Block: 2 (last)");
+}
+
+#[actix_web::test]
+async fn bare_acks_on_the_ssh_port_stay_tcp() {
+    let manager = capture(vec![
+        ipv4(IP_TCP, &tcp(22, 40000, b"SSH-2.0-OpenSSH_9.6\r\n")),
+        ipv4(IP_TCP, &tcp(40000, 22, &[])),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut packets = listing["packets"].as_array().unwrap().clone();
+    packets.sort_by_key(|packet| packet["id"].as_u64());
+    assert_eq!(packets[0]["protocol"], "SSH");
+    assert_eq!(packets[1]["protocol"], "TCP");
+}
+
+#[actix_web::test]
+async fn ftp_data_connections_are_learned_from_the_control_channel() {
+    // 192.0.2.1 serves FTP here: it answers PASV with port 50000 (195 * 256 + 80)
+    let manager = capture(vec![
+        ipv4(IP_TCP, &tcp(21, 40000, b"227 Entering Passive Mode (10,0,0,1,195,80).\r\n")),
+        ipv4(IP_TCP, &tcp(50000, 40001, b"drwxr-xr-x 2 ftp ftp 4096 pub\r\n")),
+        ipv4(IP_TCP, &tcp(50001, 40001, b"drwxr-xr-x 2 ftp ftp 4096 pub\r\n")),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut packets = listing["packets"].as_array().unwrap().clone();
+    packets.sort_by_key(|packet| packet["id"].as_u64());
+    assert_eq!(packets[0]["protocol"], "FTP");
+    assert_eq!(packets[0]["info"], "FTP Response: 227 Entering Passive Mode (10,0,0,1,195,80).");
+    assert_eq!(packets[1]["protocol"], "FTP-DATA");
+    assert_eq!(packets[1]["info"], "FTP Data: 31 bytes");
+    assert_eq!(packets[2]["protocol"], "TCP");
+    
+    let control = get_json(&manager, &format!("/api/packets/{}", packets[0]["id"])).await;
+    assert_eq!(control["headers"]["ftp"]["data_address"], "10.0.0.1");
+    assert_eq!(control["headers"]["ftp"]["data_port"], 50000);
+}
+
+#[actix_web::test]
+async fn sip_offers_are_decoded_and_their_media_expected_as_rtp() {
+    // The INVITE offers audio on 192.0.2.1:49170; port 40001 is odd, so only the offer makes it RTP
+    let manager = capture(vec![
+        ipv4(IP_UDP, &udp(5060, 5060, &sip_invite())),
+        ipv4(IP_UDP, &udp(49170, 40001, &rtp_packet(1, 160))),
+        ipv4(IP_UDP, &udp(49172, 40001, &rtp_packet(1, 160))),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let mut packets = listing["packets"].as_array().unwrap().clone();
+    packets.sort_by_key(|packet| packet["id"].as_u64());
+    assert_eq!(packets[0]["protocol"], "SIP");
+    assert_eq!(packets[0]["info"], "SIP Request: INVITE sip:bob@example.com, Call-ID: a84b4c76e66710@pc33.example.com (SDP)");
+    assert_eq!(packets[1]["protocol"], "RTP");
+    assert_eq!(packets[2]["protocol"], "UDP");
+    
+    let invite = get_json(&manager, &format!("/api/packets/{}", packets[0]["id"])).await;
+    let sip = &invite["headers"]["sip"];
+    assert_eq!(sip["from"], "Alice <sip:alice@example.com>;tag=1928301774");
+    assert_eq!(sip["via"][0], "SIP/2.0/UDP pc33.example.com;branch=z9hG4bK776asdhds");
+    assert_eq!(sip["sdp"]["connection_address"], "192.0.2.1");
+    assert_eq!(sip["sdp"]["media"][0]["formats"][0], "0");
+}
+
+#[actix_web::test]
+async fn rtp_streams_report_loss_and_jitter() {
+    let frames = |sequences: &[u16]| {
+        sequences
+            .iter()
+            .map(|&sequence| ipv4(IP_UDP, &udp(40000, 40002, &rtp_packet(sequence, sequence as u32 * 160))))
+            .collect()
+    };
+    
+    // Packet 3 of the stream never arrives
+    let manager = capture(frames(&[1, 2, 4, 5])).await;
+    let rtp = get_json(&manager, "/api/stats/rtp").await;
+    assert_eq!(rtp["streams"][0]["packets"], 4);
+    assert_eq!(rtp["streams"][0]["expected_packets"], 5);
+    assert_eq!(rtp["streams"][0]["lost_packets"], 1);
+    
+    // Packet 2 then arrives twice, late, making up for the lost one in the count
+    let manager = capture(frames(&[1, 2, 4, 5, 2])).await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    assert!(listing["packets"].as_array().unwrap().iter().all(|packet| packet["protocol"] == "RTP"));
+    
+    let rtp = get_json(&manager, "/api/stats/rtp").await;
+    assert_eq!(rtp["total"], 1);
+    let stream = &rtp["streams"][0];
+    assert_eq!(stream["ssrc"], 0x1234abcd);
+    assert_eq!(stream["source"], "192.0.2.1:40000");
+    assert_eq!(stream["codec"], "PCMU");
+    assert_eq!(stream["packets"], 5);
+    assert_eq!(stream["expected_packets"], 5);
+    assert_eq!(stream["lost_packets"], 0);
+    assert_eq!(stream["out_of_order"], 1);
+    assert!(stream["conversation_id"].is_string());
+    // Sent 20 ms apart but captured together, every packet arrives early
+    assert!(stream["jitter_ms"].as_f64().unwrap() > 0.0);
+}
+
+/// An ACK segment of a connection between 192.0.2.1:40000 and 192.0.2.2:5001,
+/// from the server when `reply`
+fn segment(reply: bool, sequence: u32, acknowledgement: u32, length: usize) -> Vec<u8> {
+    let (source_port, destination_port) = if reply { (5001, 40000) } else { (40000, 5001) };
+    let mut segment = tcp(source_port, destination_port, &vec![0x55; length]);
+    segment[4..8].copy_from_slice(&sequence.to_be_bytes());
+    segment[8..12].copy_from_slice(&acknowledgement.to_be_bytes());
+    segment[13] = 0x10;
+    
+    let mut frame = ipv4(IP_TCP, &segment);
+    if reply {
+        let (source, destination) = frame[26..34].split_at_mut(4);
+        source.swap_with_slice(destination);
+    }
+    frame
+}
+
+#[actix_web::test]
+async fn bytes_in_flight_follow_the_acknowledgements() {
+    // Data before the first acknowledgement has no estimate; the server then
+    // acknowledges 1000 of 3000 bytes, and finally all of them
+    let manager = capture(vec![
+        segment(false, 1, 1, 1000),
+        segment(true, 1, 1001, 0),
+        segment(false, 1001, 1, 1000),
+        segment(false, 2001, 1, 1000),
+        segment(true, 1, 3001, 0),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/packets").await;
+    let first = listing["packets"].as_array().unwrap().iter().map(|packet| packet["id"].as_u64().unwrap()).min().unwrap();
+    
+    let inflight = get_json(&manager, &format!("/api/streams/{}/inflight", first)).await;
+    assert_eq!(inflight["source"], "192.0.2.1:40000");
+    assert_eq!(inflight["packets"], 5);
+    let samples: Vec<Value> = inflight["samples"].as_array().unwrap().iter().map(|sample| sample["bytes_in_flight"].clone()).collect();
+    assert_eq!(samples, [Value::Null, 0.into(), 1000.into(), 2000.into(), 0.into()]);
+    assert_eq!(inflight["unknown_samples"], 1);
+    assert_eq!(inflight["max_bytes_in_flight"], 2000);
+    
+    // The server sends no data: nothing is in flight once it has been seen at all
+    let reply = get_json(&manager, &format!("/api/streams/{}/inflight", first + 1)).await;
+    assert_eq!(reply["source"], "192.0.2.2:5001");
+    assert_eq!(reply["unknown_samples"], 1);
+    assert_eq!(reply["max_bytes_in_flight"], 0);
+}
+
+/// A segment with `flags` of a connection between 192.0.2.1:40000 and
+/// 192.0.2.2:80, from the server when `reply`
+fn http_segment(reply: bool, sequence: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let (source_port, destination_port) = if reply { (80, 40000) } else { (40000, 80) };
+    let mut segment = tcp(source_port, destination_port, payload);
+    segment[4..8].copy_from_slice(&sequence.to_be_bytes());
+    segment[13] = flags;
+    
+    let mut frame = ipv4(IP_TCP, &segment);
+    if reply {
+        let (source, destination) = frame[26..34].split_at_mut(4);
+        source.swap_with_slice(destination);
+    }
+    frame
+}
+
+#[actix_web::test]
+async fn files_are_carved_out_of_http_streams() {
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    
+    let requests = b"GET /files/report.txt HTTP/1.1\r\nHost: example.com\r\n\r\n\
+        GET /logo.png?size=2 HTTP/1.1\r\nHost: example.com\r\n\r\n";
+    let first = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nTransfer-Encoding: chunked\r\n\r\n\
+        7;ext=1\r\nHello, \r\n";
+    let rest = b"d\r\ncarved world!\r\n0\r\nExpires: never\r\n\r\n";
+    let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip.write_all(&[0x89; 300]).unwrap();
+    let logo = gzip.finish().unwrap();
+    let mut second = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+        logo.len()
+    ).into_bytes();
+    second.extend_from_slice(&logo);
+    
+    // The chunked body arrives out of order, its first part twice
+    let server = 5001 + first.len() as u32;
+    let manager = capture(vec![
+        http_segment(false, 1000, 0x02, &[]),
+        http_segment(true, 5000, 0x12, &[]),
+        http_segment(false, 1001, 0x18, requests),
+        http_segment(true, server, 0x18, rest),
+        http_segment(true, 5001, 0x18, first),
+        http_segment(true, 5001, 0x18, first),
+        http_segment(true, server + rest.len() as u32, 0x19, &second),
+    ])
+    .await;
+    
+    let listing = get_json(&manager, "/api/streams/3/objects").await;
+    assert_eq!(listing["client"], "192.0.2.1:40000");
+    assert_eq!(listing["gaps"], false);
+    let objects = listing["objects"].as_array().unwrap();
+    assert_eq!(objects.len(), 2);
+    assert_eq!(objects[0]["filename"], "report.txt");
+    assert_eq!(objects[0]["hostname"], "example.com");
+    assert_eq!(objects[0]["request"], "GET /files/report.txt");
+    assert_eq!(objects[0]["packet_id"], 5);
+    assert_eq!(objects[0]["size"], 20);
+    assert_eq!(objects[0]["complete"], true);
+    assert_eq!(objects[1]["filename"], "logo.png");
+    // Listed as sent; the download is decoded
+    assert_eq!(objects[1]["content_encoding"], "gzip");
+    assert_eq!(objects[1]["size"], logo.len());
+    
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager.clone()))
+            .configure(routes::configure)
+    ).await;
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/streams/1/objects/0").to_request()).await;
+    assert_eq!(header(&response, "content-type"), "text/plain");
+    assert_eq!(header(&response, "content-disposition"), "attachment; filename=\"report.txt\"");
+    assert_eq!(test::read_body(response).await, "Hello, carved world!");
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/streams/1/objects/1").to_request()).await;
+    assert_eq!(test::read_body(response).await, vec![0x89; 300]);
+    let response = test::call_service(&app, test::TestRequest::get().uri("/api/streams/1/objects/2").to_request()).await;
+    assert_eq!(response.status(), 404);
+}
+
+#[actix_web::test]
+async fn capture_files_are_only_loaded_from_the_load_directory() {
+    // Files next to the load directory are out of reach, by a relative or an absolute path
+    let base = std::env::temp_dir().join(format!("rustshark-load-{}", std::process::id()));
+    let dir = base.join("captures");
+    std::fs::create_dir_all(&dir).unwrap();
+    let outside = base.join("outside.pcap");
+    std::fs::write(&outside, b"").unwrap();
+    
+    
+    let load = |manager: Arc<RwLock<CaptureManager>>, path: &str| {
+        let request = test::TestRequest::post()
+            .uri("/api/capture/load")
+            .set_json(serde_json::json!({ "files": [{ "path": path, "source": "good" }] }))
+            .to_request();
+        async move {
+            let app = test::init_service(App::new().app_data(web::Data::new(manager)).configure(routes::configure)).await;
+            test::call_service(&app, request).await.status()
+        }
+    };
+    
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config())));
+    assert_eq!(load(manager, "outside.pcap").await, 403);
+    
+    let mut config = config();
+    config.load_dir = Some(dir.display().to_string());
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config)));
+    assert_eq!(load(manager.clone(), "../outside.pcap").await, 400);
+    assert_eq!(load(manager.clone(), &outside.display().to_string()).await, 400);
+    assert_eq!(manager.read().await.get_stats().total_packets, 0);
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+#[actix_web::test]
+async fn rotated_stats_reports_are_pruned_without_touching_other_files() {
+    let dir = std::env::temp_dir().join(format!("rustshark-reports-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let unrelated = ["stats-old.json", "stats-20240101T000000Z.json.bak", "stats-backup-20240101T000000Z.json"];
+    for name in unrelated {
+        std::fs::write(dir.join(name), "{}").unwrap();
+    }
+    
+    let reporter = StatsReporter::new(dir.join("stats.json"), StatsReportMode::Rotate, 2);
+    let start = Utc::now();
+    for second in 0..4 {
+        let report = StatsReport {
+            timestamp: start + chrono::Duration::seconds(second),
+            running: true,
+            interfaces: Vec::new(),
+            stats: Default::default(),
+        };
+        reporter.write(&report).unwrap();
+    }
+    
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let newest = |second: i64| format!("stats-{}.json", (start + chrono::Duration::seconds(second)).format("%Y%m%dT%H%M%SZ"));
+    let mut expected: Vec<String> = unrelated.iter().map(|name| name.to_string()).chain([newest(2), newest(3)]).collect();
+    expected.sort();
+    assert_eq!(names, expected);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[actix_web::test]
+async fn bond_and_bridge_members_are_found() {
+    // A sysfs net class directory with a bond, a bridge and a plain NIC
+    let net = std::env::temp_dir().join(format!("rustshark-net-{}", std::process::id()));
+    std::fs::create_dir_all(net.join("bond0").join("bonding")).unwrap();
+    std::fs::write(net.join("bond0").join("bonding").join("slaves"), "eth1 eth0\n").unwrap();
+    for port in ["veth1", "veth0"] {
+        std::fs::create_dir_all(net.join("br0").join("brif").join(port)).unwrap();
+    }
+    std::fs::create_dir_all(net.join("eth0")).unwrap();
+    
+    let members = |master: &str| interfaces::members_in(&net, master);
+    assert_eq!(members("bond0"), Some(vec!["eth1".to_string(), "eth0".to_string()]));
+    assert_eq!(members("br0"), Some(vec!["veth0".to_string(), "veth1".to_string()]));
+    assert_eq!(members("eth0"), None);
+    std::fs::remove_dir_all(&net).unwrap();
+}
+
+#[actix_web::test]
+async fn synchronized_captures_start_together_unless_stopped() {
+    // Of three captures one waits, one is stopped while waiting and the last arrives after
+    let barrier = Arc::new(StartBarrier::new(3));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let waiter = || {
+        let barrier = barrier.clone();
+        std::thread::spawn(move || barrier.wait(&AtomicBool::new(false)))
+    };
+    let first = waiter();
+    let quitter = {
+        let (barrier, stopped) = (barrier.clone(), stopped.clone());
+        std::thread::spawn(move || barrier.wait(&stopped))
+    };
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(barrier.released_at(), None);
+    
+    stopped.store(true, Ordering::Relaxed);
+    assert_eq!(quitter.join().unwrap(), None);
+    assert_eq!(barrier.released_at(), None);
+    let last = waiter();
+    let starts = [first.join().unwrap(), last.join().unwrap()];
+    assert!(starts[0].is_some());
+    assert_eq!(starts[0], starts[1]);
+    assert_eq!(barrier.released_at(), starts[0]);
+    
+    // A capture ending before it reached the barrier releases the one waiting
+    let barrier = Arc::new(StartBarrier::new(2));
+    let waiter = {
+        let barrier = barrier.clone();
+        std::thread::spawn(move || barrier.wait(&AtomicBool::new(false)))
+    };
+    std::thread::sleep(Duration::from_millis(100));
+    barrier.leave();
+    assert!(waiter.join().unwrap().is_some());
+}
+
+#[actix_web::test]
+async fn api_traffic_is_left_out_of_captures() {
+    let mut config = config();
+    config.filter = Some("udp".to_string());
+    let mut manager = CaptureManager::new(config);
+    
+    assert_eq!(
+        manager.capture_filter().as_deref(),
+        Some("(udp) and not (host 127.0.0.1 and tcp port 3000)")
+    );
+    
+    manager.set_exclude_api_traffic(false);
+    assert_eq!(manager.capture_filter().as_deref(), Some("udp"));
+}
+
+/// A response header as text, empty when missing
+fn header<B>(response: &ServiceResponse<B>, name: &str) -> String {
+    response.headers().get(name).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string()
+}
+
+#[actix_web::test]
+async fn cors_headers_are_only_sent_to_allowed_origins() {
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config())));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager))
+            .app_data(web::Data::new(CorsPolicy::new(vec!["https://ui.example.com".to_string()])))
+            .configure(routes::configure)
+    ).await;
+    
+    let preflight = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri("/api/capture/settings")
+        .insert_header(("Origin", "https://ui.example.com"))
+        .insert_header(("Access-Control-Request-Method", "POST"))
+        .insert_header(("Access-Control-Request-Headers", "content-type"))
+        .to_request();
+    let response = test::call_service(&app, preflight).await;
+    assert_eq!(response.status(), 204);
+    assert_eq!(header(&response, "access-control-allow-origin"), "https://ui.example.com");
+    assert_eq!(header(&response, "access-control-allow-credentials"), "true");
+    assert_eq!(header(&response, "access-control-allow-headers"), "content-type");
+    
+    let allowed = test::TestRequest::get()
+        .uri("/api/packets/stats")
+        .insert_header(("Origin", "https://ui.example.com"))
+        .to_request();
+    let response = test::call_service(&app, allowed).await;
+    assert!(response.status().is_success());
+    assert_eq!(header(&response, "access-control-allow-origin"), "https://ui.example.com");
+    
+    let other = test::TestRequest::get()
+        .uri("/api/packets/stats")
+        .insert_header(("Origin", "https://elsewhere.example"))
+        .to_request();
+    let response = test::call_service(&app, other).await;
+    assert!(response.status().is_success());
+    assert_eq!(header(&response, "access-control-allow-origin"), "");
+    
+    let upgrade = |origin: &str| test::TestRequest::get()
+        .uri("/api/ws")
+        .insert_header(("Origin", origin))
+        .insert_header(("Connection", "Upgrade"))
+        .insert_header(("Upgrade", "websocket"))
+        .insert_header(("Sec-WebSocket-Version", "13"))
+        .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .to_request();
+    assert_eq!(test::call_service(&app, upgrade("https://elsewhere.example")).await.status(), 403);
+    assert_eq!(test::call_service(&app, upgrade("https://ui.example.com")).await.status(), 101);
+}
+
+#[actix_web::test]
+async fn any_origin_is_allowed_without_credentials() {
+    let manager = Arc::new(RwLock::new(CaptureManager::new(config())));
+    let origins = vec!["https://ui.example.com".to_string(), "*".to_string()];
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager))
+            .app_data(web::Data::new(CorsPolicy::new(origins)))
+            .configure(routes::configure)
+    ).await;
+    let get = |origin: &str| test::TestRequest::get()
+        .uri("/api/packets/stats")
+        .insert_header(("Origin", origin))
+        .to_request();
+    
+    let response = test::call_service(&app, get("https://elsewhere.example")).await;
+    assert_eq!(header(&response, "access-control-allow-origin"), "*");
+    assert_eq!(header(&response, "access-control-allow-credentials"), "");
+    
+    let response = test::call_service(&app, get("https://ui.example.com")).await;
+    assert_eq!(header(&response, "access-control-allow-origin"), "https://ui.example.com");
+    assert_eq!(header(&response, "access-control-allow-credentials"), "true");
+}
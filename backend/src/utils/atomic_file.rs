@@ -0,0 +1,37 @@
+// Atomic file replacement
+//
+// Snapshots, rotated stats reports and the coloring rules are written to a
+// temporary file next to the target, flushed to disk, then renamed over it, so
+// a crash mid-write never leaves a truncated file behind. The temporary file
+// is the target's full name with `.tmp` appended, so targets that differ only
+// in their extension never share one.
+
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Replace `path` with what `fill` writes, through a temporary file
+pub fn write(path: &Path, fill: impl FnOnce(&mut fs::File) -> Result<()>) -> Result<()> {
+    let tmp_path = tmp_path(path)?;
+    
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        fill(&mut file)?;
+        file.flush()?;
+        file.sync_all()?;
+    }
+    
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// The temporary file `path` is written through
+fn tmp_path(path: &Path) -> Result<PathBuf> {
+    let mut name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("No file name in {}", path.display()))?
+        .to_os_string();
+    name.push(".tmp");
+    Ok(path.with_file_name(name))
+}
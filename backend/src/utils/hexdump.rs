@@ -0,0 +1,59 @@
+// Hex dump formatting for the packet byte view
+
+use std::fmt::Write;
+
+/// Bytes per line the dump can be laid out with
+pub const WIDTHS: [usize; 3] = [8, 16, 32];
+
+/// Layout of a hex dump
+#[derive(Debug, Clone, Copy)]
+pub struct HexDumpOptions {
+    /// Bytes per line, one of `WIDTHS`
+    pub width: usize,
+    
+    /// Whether to end each line with the printable ASCII of its bytes
+    pub ascii: bool,
+}
+
+impl Default for HexDumpOptions {
+    fn default() -> Self {
+        HexDumpOptions { width: 16, ascii: true }
+    }
+}
+
+/// Format bytes as lines of offset, hex bytes and (optionally) ASCII.
+///
+/// Offsets are as wide as the largest one needs (at least four digits), bytes
+/// are grouped in eights, and a short last line is padded, so every column
+/// lines up whatever the width.
+pub fn format(data: &[u8], options: HexDumpOptions) -> String {
+    let width = options.width.max(1);
+    let last_offset = data.len().saturating_sub(1);
+    let offset_digits = format!("{:x}", last_offset).len().max(4);
+    
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(width).enumerate() {
+        let _ = write!(out, "{:0digits$x} ", line * width, digits = offset_digits);
+        
+        for i in 0..width {
+            if i % 8 == 0 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => { let _ = write!(out, "{:02x} ", byte); },
+                None => out.push_str("   "),
+            }
+        }
+        
+        if options.ascii {
+            out.push(' ');
+            out.extend(chunk.iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }));
+        } else {
+            // Drop the separator after the last byte
+            out.truncate(out.trim_end().len());
+        }
+        out.push('\n');
+    }
+    
+    out
+}
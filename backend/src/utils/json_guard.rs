@@ -0,0 +1,93 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Limits applied to decoded header JSON before it is returned to clients
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct JsonLimits {
+    /// Maximum nesting depth of objects and arrays
+    pub max_depth: usize,
+    
+    /// Maximum serialized size in bytes
+    pub max_bytes: usize,
+}
+
+impl JsonLimits {
+    /// Cap the depth and size of a JSON value, returning whether anything was truncated.
+    ///
+    /// Subtrees nested deeper than `max_depth` are replaced by a marker string.
+    /// If the result is still larger than `max_bytes`, top-level entries are
+    /// kept in order until the budget runs out and the rest are dropped, with
+    /// a `_truncated` entry recording what happened.
+    pub fn apply(&self, value: &mut Value) -> bool {
+        let mut truncated = limit_depth(value, self.max_depth);
+        
+        let size = serialized_len(value);
+        if size > self.max_bytes {
+            truncated = true;
+            *value = match std::mem::take(value) {
+                Value::Object(entries) => {
+                    let mut budget = self.max_bytes;
+                    let mut kept = Map::new();
+                    let mut dropped = Vec::new();
+                    
+                    for (key, entry) in entries {
+                        let entry_len = key.len() + serialized_len(&entry) + 4;
+                        if entry_len <= budget {
+                            budget -= entry_len;
+                            kept.insert(key, entry);
+                        } else {
+                            dropped.push(key);
+                        }
+                    }
+                    
+                    kept.insert("_truncated".to_string(), json!({
+                        "reason": "max_bytes",
+                        "max_bytes": self.max_bytes,
+                        "original_bytes": size,
+                        "dropped": dropped,
+                    }));
+                    Value::Object(kept)
+                },
+                _ => json!({
+                    "_truncated": {
+                        "reason": "max_bytes",
+                        "max_bytes": self.max_bytes,
+                        "original_bytes": size,
+                    }
+                }),
+            };
+        }
+        
+        truncated
+    }
+}
+
+/// Replace containers below `remaining` levels with a marker string
+fn limit_depth(value: &mut Value, remaining: usize) -> bool {
+    match value {
+        Value::Object(_) | Value::Array(_) if remaining == 0 => {
+            *value = Value::String("<truncated: max depth reached>".to_string());
+            true
+        },
+        Value::Object(entries) => {
+            let mut truncated = false;
+            for entry in entries.values_mut() {
+                truncated |= limit_depth(entry, remaining - 1);
+            }
+            truncated
+        },
+        Value::Array(items) => {
+            let mut truncated = false;
+            for item in items.iter_mut() {
+                truncated |= limit_depth(item, remaining - 1);
+            }
+            truncated
+        },
+        _ => false,
+    }
+}
+
+/// Length of a value's compact JSON serialization
+fn serialized_len(value: &Value) -> usize {
+    serde_json::to_vec(value).map(|bytes| bytes.len()).unwrap_or(0)
+}
@@ -1,4 +1,6 @@
 use log::{Level, LevelFilter, Log, Metadata, Record};
+use regex::{Captures, Regex};
+use std::borrow::Cow;
 use std::io::Write;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
@@ -15,6 +17,24 @@ static LAST_STATS_TIME: Mutex<Option<Instant>> = Mutex::new(None);
 // Track verbose output mode
 static VERBOSE_MODE: AtomicBool = AtomicBool::new(false);
 
+// Mask addresses, ports and payloads in all log output (--redact)
+static REDACT_MODE: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    // IPv4 address with an optional port; the first three octets are kept
+    static ref IPV4_RE: Regex = Regex::new(r"\b(\d{1,3}\.\d{1,3}\.\d{1,3}\.)\d{1,3}\b(:\d{1,5}\b)?").unwrap();
+    
+    // Three or more colon-separated hex groups: IPv6 and MAC addresses, but also
+    // times (told apart below)
+    static ref COLON_HEX_RE: Regex = Regex::new(r"\b[0-9A-Fa-f]{1,4}(?::[0-9A-Fa-f]{0,4}){1,6}:[0-9A-Fa-f]{1,4}\b(\]:\d{1,5}\b)?").unwrap();
+    
+    // "port 443", "ports: 80", "src port: 53"
+    static ref PORT_RE: Regex = Regex::new(r"(?i)\b(ports?:?\s+)\d{1,5}\b").unwrap();
+    
+    // Payload previews: eight or more hex bytes, spaced or run together
+    static ref PAYLOAD_RE: Regex = Regex::new(r"\b[0-9A-Fa-f]{2}(?: [0-9A-Fa-f]{2}){7,}\b|\b[0-9A-Fa-f]{16,}\b").unwrap();
+}
+
 // Structure to hold stats for TUI output
 struct CaptureStats {
     total_packets: usize,
@@ -34,17 +54,16 @@ impl Default for CaptureStats {
     }
 }
 
-// Our custom logger implementation
+// Our custom logger implementation; the level is log's max level, so it can
+// be changed at runtime
 struct CompactLogger {
-    level: LevelFilter,
     is_initialized: bool,
     stats: Arc<Mutex<CaptureStats>>,
 }
 
 impl CompactLogger {
-    fn new(level: LevelFilter) -> Self {
+    fn new() -> Self {
         Self {
-            level,
             is_initialized: false,
             stats: Arc::new(Mutex::new(CaptureStats::default())),
         }
@@ -162,7 +181,7 @@ impl CompactLogger {
         buffer.reset().unwrap();
         
         // Format message
-        write!(&mut buffer, " {}", record.args()).unwrap();
+        write!(&mut buffer, " {}", redact(&record.args().to_string())).unwrap();
         
         // Print and add a newline
         stdout.print(&buffer).unwrap();
@@ -175,7 +194,7 @@ impl CompactLogger {
 
 impl Log for CompactLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.level
+        metadata.level() <= log::max_level()
     }
 
     fn log(&self, record: &Record) {
@@ -244,7 +263,7 @@ unsafe impl Sync for CompactLogger {}
 
 // Initialize the logger
 pub fn init_logger(level: LevelFilter) {
-    let logger = Box::new(CompactLogger::new(level));
+    let logger = Box::new(CompactLogger::new());
     log::set_boxed_logger(logger).unwrap();
     log::set_max_level(level);
     
@@ -253,17 +272,97 @@ pub fn init_logger(level: LevelFilter) {
     println!("------------------------------------------------");
 }
 
+// Plain logger for tail mode: one line per message on stderr, so stdout
+// carries nothing but packet output
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", record.level(), redact(&record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Initialize the tail mode logger (no TUI header or status line)
+pub fn init_stderr_logger(level: LevelFilter) {
+    log::set_boxed_logger(Box::new(StderrLogger)).unwrap();
+    log::set_max_level(level);
+}
+
+// Names of the log levels, most verbose first
+pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error", "off"];
+
 // Convert string level to LevelFilter
 pub fn get_log_level(level_str: &str) -> LevelFilter {
+    parse_log_level(level_str).unwrap_or(LevelFilter::Info)
+}
+
+// Convert string level to LevelFilter, None when it is not one of LOG_LEVELS
+pub fn parse_log_level(level_str: &str) -> Option<LevelFilter> {
     match level_str.to_lowercase().as_str() {
-        "trace" => LevelFilter::Trace,
-        "debug" => LevelFilter::Debug,
-        "info" => LevelFilter::Info,
-        "warn" => LevelFilter::Warn,
-        "error" => LevelFilter::Error,
-        "off" => LevelFilter::Off,
-        _ => LevelFilter::Info,
+        "trace" => Some(LevelFilter::Trace),
+        "debug" => Some(LevelFilter::Debug),
+        "info" => Some(LevelFilter::Info),
+        "warn" => Some(LevelFilter::Warn),
+        "error" => Some(LevelFilter::Error),
+        "off" => Some(LevelFilter::Off),
+        _ => None,
+    }
+}
+
+// Change the active log level
+pub fn set_log_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+// Get the active log level
+pub fn current_log_level() -> LevelFilter {
+    log::max_level()
+}
+
+// Enable masking of IP and MAC addresses (keeping the subnet or vendor
+// prefix), ports and payload previews in every log message
+pub fn set_redact_mode(redact: bool) {
+    REDACT_MODE.store(redact, Ordering::Relaxed);
+}
+
+// Apply redact mode to a log message
+fn redact(message: &str) -> Cow<'_, str> {
+    if !REDACT_MODE.load(Ordering::Relaxed) {
+        return Cow::Borrowed(message);
     }
+    
+    let message = PAYLOAD_RE.replace_all(message, "[payload redacted]");
+    let message = IPV4_RE.replace_all(&message, |caps: &Captures| {
+        format!("{}x{}", &caps[1], if caps.get(2).is_some() { ":x" } else { "" })
+    });
+    let message = COLON_HEX_RE.replace_all(&message, |caps: &Captures| {
+        let matched = &caps[0];
+        let address = matched.strip_suffix(caps.get(1).map_or("", |port| port.as_str())).unwrap_or(matched);
+        let port = if caps.get(1).is_some() { "]:x" } else { "" };
+        
+        // Times ("12:34:56") have no hex letters, no "::" and fewer groups than a MAC
+        let groups = address.split(':').count();
+        if !address.contains("::") && groups < 6 && !address.chars().any(|c| c.is_ascii_alphabetic()) {
+            return matched.to_string();
+        }
+        
+        // Keep the /48 of an IPv6 address (or what precedes "::" within it), or a MAC's vendor prefix
+        match address.find("::") {
+            Some(index) if address[..index].split(':').count() <= 3 => format!("{}::x{}", &address[..index], port),
+            _ => format!("{}:x{}", address.split(':').take(3).collect::<Vec<_>>().join(":"), port),
+        }
+    });
+    let message = PORT_RE.replace_all(&message, "${1}x");
+    
+    Cow::Owned(message.into_owned())
 }
 
 // Set verbose mode
@@ -277,6 +376,17 @@ pub fn set_verbose_mode(verbose: bool) {
     }
 }
 
+// Set verbose mode without the console notice, for callers that log the change
+// themselves (the notice would end up in stdout packet output)
+pub fn store_verbose_mode(verbose: bool) {
+    VERBOSE_MODE.store(verbose, Ordering::Relaxed);
+}
+
+// Get verbose mode
+pub fn is_verbose_mode() -> bool {
+    VERBOSE_MODE.load(Ordering::Relaxed)
+}
+
 // Toggle verbose mode - returns the new state
 pub fn toggle_verbose_mode() -> bool {
     let current = VERBOSE_MODE.load(Ordering::Relaxed);
@@ -1,2 +1,6 @@
 pub mod error;
-pub mod logging; 
\ No newline at end of file
+pub mod logging; 
+pub mod hexdump;
+pub mod json_guard;
+pub mod atomic_file;
+pub mod tls;
\ No newline at end of file
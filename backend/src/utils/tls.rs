@@ -0,0 +1,42 @@
+use anyhow::{anyhow, Context, Result};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+/// Build the API server's TLS configuration from PEM certificate and key files.
+///
+/// The certificate file may contain the full chain; the key file must hold
+/// one PKCS#8, PKCS#1 or SEC1 private key.
+pub fn load_server_config(cert_path: &str, key_path: &str) -> Result<ServerConfig> {
+    let certs = load_certs(cert_path)?;
+    let key = load_key(key_path)?;
+    
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .with_context(|| format!("TLS certificate {} does not match key {}", cert_path, key_path))
+}
+
+/// Read every certificate from a PEM file
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path).with_context(|| format!("Failed to open TLS certificate {}", path))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to parse TLS certificate {}", path))?;
+    
+    if certs.is_empty() {
+        return Err(anyhow!("No PEM certificates found in {}", path));
+    }
+    Ok(certs)
+}
+
+/// Read the private key from a PEM file
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    let file = File::open(path).with_context(|| format!("Failed to open TLS key {}", path))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .with_context(|| format!("Failed to parse TLS key {}", path))?
+        .ok_or_else(|| anyhow!("No PEM private key found in {}", path))
+}